@@ -20,7 +20,7 @@ use language_model::{
     Role, report_assistant_event,
 };
 use project::Project;
-use prompt_store::{PromptBuilder, PromptStore};
+use prompt_store::{PromptBuilder, PromptStore, TerminalCommandContext};
 use std::sync::Arc;
 use telemetry_events::{AssistantEventData, AssistantKind, AssistantPhase};
 use terminal_view::TerminalView;
@@ -39,6 +39,7 @@ pub fn init(
 }
 
 const DEFAULT_CONTEXT_LINES: usize = 50;
+const DEFAULT_CONTEXT_COMMANDS: usize = 10;
 const PROMPT_HISTORY_MAX_LEN: usize = 20;
 
 pub struct TerminalInlineAssistant {
@@ -231,7 +232,7 @@ impl TerminalInlineAssistant {
         let assist = self.assists.get(&assist_id).context("invalid assist")?;
 
         let shell = std::env::var("SHELL").ok();
-        let (latest_output, working_directory) = assist
+        let (latest_output, working_directory, recent_commands) = assist
             .terminal
             .update(cx, |terminal, cx| {
                 let terminal = terminal.entity().read(cx);
@@ -239,7 +240,25 @@ impl TerminalInlineAssistant {
                 let working_directory = terminal
                     .working_directory()
                     .map(|path| path.to_string_lossy().to_string());
-                (latest_output, working_directory)
+                let mut recent_commands = terminal
+                    .command_blocks()
+                    .rev()
+                    .filter_map(|block| {
+                        let exit_code = block.exit_code?;
+                        Some(TerminalCommandContext {
+                            command: block.command.clone()?,
+                            working_directory: block
+                                .working_directory
+                                .as_ref()
+                                .map(|path| path.to_string_lossy().to_string()),
+                            exit_code,
+                            succeeded: exit_code == 0,
+                        })
+                    })
+                    .take(DEFAULT_CONTEXT_COMMANDS)
+                    .collect::<Vec<_>>();
+                recent_commands.reverse();
+                (latest_output, working_directory, recent_commands)
             })
             .ok()
             .unwrap_or_default();
@@ -254,6 +273,7 @@ impl TerminalInlineAssistant {
             shell.as_deref(),
             working_directory.as_deref(),
             &latest_output,
+            recent_commands,
         )?;
 
         let contexts = assist
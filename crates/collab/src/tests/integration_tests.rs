@@ -4114,7 +4114,7 @@ async fn test_collaborating_with_diagnostics(
                     worktree_id,
                     path: Arc::from(Path::new("a.rs")),
                 },
-                LanguageServerId(0),
+                Some(LanguageServerId(0)),
                 DiagnosticSummary {
                     error_count: 1,
                     warning_count: 0,
@@ -4150,7 +4150,7 @@ async fn test_collaborating_with_diagnostics(
                 worktree_id,
                 path: Arc::from(Path::new("a.rs")),
             },
-            LanguageServerId(0),
+            Some(LanguageServerId(0)),
             DiagnosticSummary {
                 error_count: 1,
                 warning_count: 0,
@@ -4191,7 +4191,7 @@ async fn test_collaborating_with_diagnostics(
                     worktree_id,
                     path: Arc::from(Path::new("a.rs")),
                 },
-                LanguageServerId(0),
+                Some(LanguageServerId(0)),
                 DiagnosticSummary {
                     error_count: 1,
                     warning_count: 1,
@@ -4208,7 +4208,7 @@ async fn test_collaborating_with_diagnostics(
                     worktree_id,
                     path: Arc::from(Path::new("a.rs")),
                 },
-                LanguageServerId(0),
+                Some(LanguageServerId(0)),
                 DiagnosticSummary {
                     error_count: 1,
                     warning_count: 1,
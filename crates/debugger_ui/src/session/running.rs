@@ -932,6 +932,11 @@ impl RunningState {
                 let Some(task) = task_template.resolve_task("debug-build-task", &task_context) else {
                     anyhow::bail!("Could not resolve task variables within a debug scenario");
                 };
+                anyhow::ensure!(
+                    task.resolved.command.is_some(),
+                    "Task `{}` has no command and cannot be used as a debug build task",
+                    task.display_label()
+                );
 
                 let locator_name = if let Some(locator_name) = locator_name {
                     extra_config = config.clone();
@@ -1148,6 +1153,7 @@ impl RunningState {
             show_summary: false,
             show_command: false,
             show_rerun: false,
+            problem_matchers: Vec::new(),
         });
 
         let workspace = self.workspace.clone();
@@ -400,6 +400,10 @@ actions!(
         Fold,
         /// Folds all foldable regions in the editor.
         FoldAll,
+        /// Folds all comment blocks reported by the language server.
+        FoldAllComments,
+        /// Folds all import blocks reported by the language server.
+        FoldAllImports,
         /// Folds all function bodies in the editor.
         FoldFunctionBodies,
         /// Folds the current code block and all its children.
@@ -0,0 +1,493 @@
+use std::{collections::BTreeMap, mem, sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use futures::future::join_all;
+use gpui::{Entity, Task, WeakEntity};
+use language::{Buffer, BufferSnapshot, Point};
+use project::{CodeAction, LspAction};
+use settings::Settings as _;
+use text::{BufferId, OffsetRangeExt as _, ToOffset as _, ToPoint as _};
+use ui::{App, ButtonSize, ButtonStyle, Context, Window, prelude::*};
+use workspace::notifications::NotifyTaskExt as _;
+
+use crate::{
+    Editor, EditorSettings, ExcerptId,
+    display_map::{
+        BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, RenderBlock,
+    },
+};
+
+/// How long to wait after an edit before requesting the lenses again, as servers
+/// usually recompute them for the whole document.
+const CODE_LENS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub(super) struct CodeLensData {
+    buffer_lenses: HashMap<BufferId, BufferCodeLenses>,
+    enabled: bool,
+    fetch_task: Task<()>,
+}
+
+#[derive(Default)]
+struct BufferCodeLenses {
+    lines: Vec<CodeLensLine>,
+    /// Whether the lenses have to be requested again, after an edit or a refresh request.
+    outdated: bool,
+}
+
+/// The lenses of one line of a buffer, shown in a block above it in one excerpt.
+struct CodeLensLine {
+    block_id: CustomBlockId,
+    excerpt_id: ExcerptId,
+    position: text::Anchor,
+    lenses: Vec<CodeAction>,
+    resolving: bool,
+}
+
+impl CodeLensData {
+    pub fn new(cx: &App) -> Self {
+        Self {
+            buffer_lenses: HashMap::default(),
+            enabled: EditorSettings::get_global(cx).lsp_code_lens,
+            fetch_task: Task::ready(()),
+        }
+    }
+
+    /// Returns whether the lenses have to be removed or requested, after the setting changed.
+    pub fn settings_updated(&mut self, cx: &App) -> bool {
+        let enabled = EditorSettings::get_global(cx).lsp_code_lens;
+        if self.enabled == enabled {
+            return false;
+        }
+        self.enabled = enabled;
+        true
+    }
+}
+
+impl Editor {
+    /// Marks the lenses of the buffer, or of all buffers, as outdated and requests
+    /// the lenses of the visible ones again.
+    pub(super) fn refresh_code_lenses(
+        &mut self,
+        buffer_id: Option<BufferId>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode().is_full() || self.project.is_none() {
+            return;
+        }
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+        if !code_lens.enabled {
+            return;
+        }
+        for (lenses_buffer_id, buffer_lenses) in &mut code_lens.buffer_lenses {
+            if buffer_id.is_none_or(|buffer_id| buffer_id == *lenses_buffer_id) {
+                buffer_lenses.outdated = true;
+            }
+        }
+        self.schedule_code_lens_fetch(window, cx);
+    }
+
+    fn schedule_code_lens_fetch(&mut self, window: &Window, cx: &mut Context<Self>) {
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+        code_lens.fetch_task = cx.spawn_in(window, async move |editor, cx| {
+            cx.background_executor().timer(CODE_LENS_DEBOUNCE).await;
+            editor
+                .update_in(cx, |editor, window, cx| {
+                    editor.fetch_visible_code_lenses(window, cx)
+                })
+                .ok();
+        });
+    }
+
+    fn fetch_visible_code_lenses(&mut self, window: &Window, cx: &mut Context<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let visible_buffers = self
+            .visible_excerpts(None, cx)
+            .into_values()
+            .map(|(buffer, ..)| (buffer.read(cx).remote_id(), buffer))
+            .collect::<HashMap<_, _>>();
+        let multi_buffer = self.buffer.read(cx);
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+
+        let mut removed_blocks = HashSet::default();
+        code_lens.buffer_lenses.retain(|buffer_id, buffer_lenses| {
+            let is_in_editor = multi_buffer.buffer(*buffer_id).is_some();
+            if !is_in_editor {
+                removed_blocks.extend(buffer_lenses.lines.iter().map(|line| line.block_id));
+            }
+            is_in_editor
+        });
+        if !removed_blocks.is_empty() {
+            self.remove_blocks(removed_blocks, None, cx);
+        }
+
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+        let buffers_to_fetch = visible_buffers
+            .into_iter()
+            .filter(|(buffer_id, _)| {
+                let buffer_lenses =
+                    code_lens
+                        .buffer_lenses
+                        .entry(*buffer_id)
+                        .or_insert_with(|| BufferCodeLenses {
+                            lines: Vec::new(),
+                            outdated: true,
+                        });
+                let outdated = buffer_lenses.outdated;
+                buffer_lenses.outdated = false;
+                outdated
+            })
+            .collect::<Vec<_>>();
+        if buffers_to_fetch.is_empty() {
+            self.resolve_visible_code_lenses(window, cx);
+            return;
+        }
+
+        let fetch_tasks = project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            buffers_to_fetch
+                .into_iter()
+                .map(|(buffer_id, buffer)| {
+                    let lenses_task = lsp_store.code_lens(&buffer, cx);
+                    async move { (buffer_id, lenses_task.await) }
+                })
+                .collect::<Vec<_>>()
+        });
+        cx.spawn_in(window, async move |editor, cx| {
+            let fetched_lenses = join_all(fetch_tasks).await;
+            editor
+                .update_in(cx, |editor, window, cx| {
+                    for (buffer_id, lenses) in fetched_lenses {
+                        match lenses {
+                            Ok(lenses) => editor.set_code_lenses(buffer_id, lenses, cx),
+                            Err(e) => log::error!("Failed to fetch code lenses: {e:#}"),
+                        }
+                    }
+                    editor.resolve_visible_code_lenses(window, cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    /// Replaces the blocks of a buffer's lenses, with one block above each line that
+    /// has lenses, in each excerpt containing that line.
+    ///
+    /// Lines whose lenses didn't change keep their blocks, along with the lenses that were
+    /// resolved already, so that they don't flicker.
+    fn set_code_lenses(
+        &mut self,
+        buffer_id: BufferId,
+        lenses: Vec<CodeAction>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(buffer) = self.buffer.read(cx).buffer(buffer_id) else {
+            return;
+        };
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let mut old_lines = code_lens
+            .buffer_lenses
+            .get_mut(&buffer_id)
+            .map(|buffer_lenses| mem::take(&mut buffer_lenses.lines))
+            .unwrap_or_default();
+
+        let mut lenses_by_row = BTreeMap::<u32, Vec<CodeAction>>::new();
+        for lens in lenses {
+            let row = lens.range.start.to_point(&buffer_snapshot).row;
+            lenses_by_row.entry(row).or_default().push(lens);
+        }
+
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let editor = cx.entity().downgrade();
+        let mut lines = Vec::new();
+        let mut new_lines = Vec::new();
+        let mut blocks = Vec::new();
+        for (row, lenses) in lenses_by_row {
+            let indent = buffer_snapshot.indent_size_for_line(row);
+            let position = buffer_snapshot.anchor_before(Point::new(row, indent.len));
+            let offset = position.to_offset(&buffer_snapshot);
+            for (excerpt_id, excerpt_buffer, excerpt_range) in multi_buffer_snapshot.excerpts() {
+                if excerpt_buffer.remote_id() != buffer_id {
+                    continue;
+                }
+                let excerpt_range = excerpt_range.context.to_offset(&buffer_snapshot);
+                if !excerpt_range.contains(&offset) {
+                    continue;
+                }
+                if let Some(ix) = old_lines.iter().position(|line| {
+                    line.excerpt_id == excerpt_id
+                        && line.position.is_valid(&buffer_snapshot)
+                        && line.position.to_point(&buffer_snapshot).row == row
+                        && same_lenses(&line.lenses, &lenses, &buffer_snapshot)
+                }) {
+                    lines.push(old_lines.swap_remove(ix));
+                    continue;
+                }
+                let Some(block_position) =
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, position)
+                else {
+                    continue;
+                };
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Above(block_position),
+                    height: Some(1),
+                    style: BlockStyle::Flex,
+                    render: render_code_lens_line(editor.clone(), buffer.clone(), lenses.clone()),
+                    priority: 0,
+                    render_in_minimap: false,
+                });
+                new_lines.push((excerpt_id, position, lenses.clone()));
+            }
+        }
+
+        if !old_lines.is_empty() {
+            let old_blocks = old_lines.into_iter().map(|line| line.block_id).collect();
+            self.remove_blocks(old_blocks, None, cx);
+        }
+        let block_ids = self.insert_blocks(blocks, None, cx);
+        lines.extend(block_ids.into_iter().zip(new_lines).map(
+            |(block_id, (excerpt_id, position, lenses))| CodeLensLine {
+                block_id,
+                excerpt_id,
+                position,
+                lenses,
+                resolving: false,
+            },
+        ));
+        if let Some(code_lens) = &mut self.code_lens {
+            code_lens.buffer_lenses.entry(buffer_id).or_default().lines = lines;
+        }
+    }
+
+    /// Resolves the lenses of the lines that are scrolled into view, and requests the
+    /// lenses of buffers that became visible.
+    pub(super) fn resolve_visible_code_lenses(&mut self, window: &Window, cx: &mut Context<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        if self
+            .code_lens
+            .as_ref()
+            .is_none_or(|code_lens| !code_lens.enabled)
+        {
+            return;
+        }
+        let visible_excerpts = self.visible_excerpts(None, cx);
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+
+        let mut has_new_buffers = false;
+        let mut lines_to_resolve = Vec::new();
+        for (buffer, _, visible_range) in visible_excerpts.into_values() {
+            let buffer_id = buffer.read(cx).remote_id();
+            let Some(buffer_lenses) = code_lens.buffer_lenses.get_mut(&buffer_id) else {
+                has_new_buffers = true;
+                continue;
+            };
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            for line in &mut buffer_lenses.lines {
+                if line.resolving || line.lenses.iter().all(|lens| lens.resolved) {
+                    continue;
+                }
+                if !visible_range.contains(&line.position.to_offset(&buffer_snapshot)) {
+                    continue;
+                }
+                line.resolving = true;
+                lines_to_resolve.push((buffer.clone(), line.block_id, line.lenses.clone()));
+            }
+        }
+        if has_new_buffers {
+            self.schedule_code_lens_fetch(window, cx);
+        }
+        if lines_to_resolve.is_empty() {
+            return;
+        }
+
+        let resolve_tasks = project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            lines_to_resolve
+                .into_iter()
+                .map(|(buffer, block_id, lenses)| {
+                    let lens_tasks = lenses
+                        .into_iter()
+                        .map(|lens| {
+                            let resolve_task =
+                                lsp_store.resolve_code_lens(&buffer, lens.clone(), cx);
+                            async move {
+                                resolve_task.await.unwrap_or_else(|e| {
+                                    log::error!("Failed to resolve code lens: {e:#}");
+                                    CodeAction {
+                                        resolved: true,
+                                        ..lens
+                                    }
+                                })
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    async move { (buffer, block_id, join_all(lens_tasks).await) }
+                })
+                .collect::<Vec<_>>()
+        });
+        cx.spawn(async move |editor, cx| {
+            let resolved_lines = join_all(resolve_tasks).await;
+            editor
+                .update(cx, |editor, cx| {
+                    let editor_handle = cx.entity().downgrade();
+                    let Some(code_lens) = &mut editor.code_lens else {
+                        return;
+                    };
+                    let mut renderers = HashMap::default();
+                    for (buffer, block_id, lenses) in resolved_lines {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        // The lenses may have been requested again while resolving these.
+                        let Some(line) =
+                            code_lens
+                                .buffer_lenses
+                                .get_mut(&buffer_id)
+                                .and_then(|buffer_lenses| {
+                                    buffer_lenses
+                                        .lines
+                                        .iter_mut()
+                                        .find(|line| line.block_id == block_id)
+                                })
+                        else {
+                            continue;
+                        };
+                        line.resolving = false;
+                        line.lenses = lenses.clone();
+                        renderers.insert(
+                            block_id,
+                            render_code_lens_line(editor_handle.clone(), buffer, lenses),
+                        );
+                    }
+                    editor.replace_blocks(renderers, None, cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    pub(super) fn clear_code_lenses(&mut self, cx: &mut Context<Self>) {
+        let Some(code_lens) = &mut self.code_lens else {
+            return;
+        };
+        code_lens.fetch_task = Task::ready(());
+        let block_ids = code_lens
+            .buffer_lenses
+            .drain()
+            .flat_map(|(_, buffer_lenses)| buffer_lenses.lines)
+            .map(|line| line.block_id)
+            .collect::<HashSet<_>>();
+        if !block_ids.is_empty() {
+            self.remove_blocks(block_ids, None, cx);
+        }
+    }
+
+    fn execute_code_lens(
+        &mut self,
+        buffer: Entity<Buffer>,
+        lens: CodeAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let title = lens.lsp_action.title().to_owned();
+        let apply_lens = project.update(cx, |project, cx| {
+            project.apply_code_action(buffer, lens, true, cx)
+        });
+        let workspace = workspace.downgrade();
+        cx.spawn_in(window, async move |editor, cx| {
+            let project_transaction = apply_lens.await?;
+            Self::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+        })
+        .detach_and_notify_err(window, cx);
+    }
+}
+
+/// Returns whether the lenses fetched for a line are the ones shown already, which may have
+/// been resolved since.
+fn same_lenses(shown: &[CodeAction], fetched: &[CodeAction], buffer: &BufferSnapshot) -> bool {
+    shown.len() == fetched.len()
+        && shown.iter().zip(fetched).all(|(shown, fetched)| {
+            let (LspAction::CodeLens(shown_lens), LspAction::CodeLens(fetched_lens)) =
+                (&shown.lsp_action, &fetched.lsp_action)
+            else {
+                return false;
+            };
+            shown.server_id == fetched.server_id
+                && shown.range.to_offset(buffer) == fetched.range.to_offset(buffer)
+                && shown_lens.data == fetched_lens.data
+                && (fetched_lens.command.is_none() || fetched_lens.command == shown_lens.command)
+        })
+}
+
+fn render_code_lens_line(
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    lenses: Vec<CodeAction>,
+) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let mut children = Vec::new();
+        for (ix, lens) in lenses.iter().enumerate() {
+            // Unresolved lenses have no command yet, and keep the line empty until they do.
+            let LspAction::CodeLens(lsp_lens) = &lens.lsp_action else {
+                continue;
+            };
+            let Some(command) = &lsp_lens.command else {
+                continue;
+            };
+            if !children.is_empty() {
+                children.push(
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Disabled)
+                        .into_any_element(),
+                );
+            }
+            let editor = editor.clone();
+            let buffer = buffer.clone();
+            let lens = lens.clone();
+            children.push(
+                Button::new(ix, command.title.clone())
+                    .style(ButtonStyle::Transparent)
+                    .size(ButtonSize::None)
+                    .label_size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .on_click(move |_, window, cx| {
+                        editor
+                            .update(cx, |editor, cx| {
+                                editor.execute_code_lens(buffer.clone(), lens.clone(), window, cx)
+                            })
+                            .ok();
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        h_flex()
+            .id(cx.block_id)
+            .block_mouse_except_scroll()
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(children)
+            .into_any_element()
+    })
+}
@@ -78,6 +78,8 @@ pub enum FoldStatus {
     Foldable,
 }
 
+enum SemanticTokenHighlight {}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum HighlightKey {
     Type(TypeId),
@@ -111,10 +113,14 @@ pub struct DisplayMap {
     block_map: BlockMap,
     /// Regions of text that should be highlighted.
     text_highlights: TextHighlights,
+    /// Regions of text styled by the language servers' semantic tokens, in place of their syntax highlighting.
+    semantic_token_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
+    /// Foldable ranges reported by language servers, which supersede indentation based suggestions in their buffers.
+    lsp_folding_ranges: TreeMap<BufferId, Arc<[Range<text::Anchor>]>>,
     pub(crate) fold_placeholder: FoldPlaceholder,
     pub clip_at_line_ends: bool,
     pub(crate) masked: bool,
@@ -155,9 +161,11 @@ impl DisplayMap {
             wrap_map,
             block_map,
             crease_map,
+            lsp_folding_ranges: Default::default(),
             fold_placeholder,
             diagnostics_max_severity,
             text_highlights: Default::default(),
+            semantic_token_highlights: Default::default(),
             inlay_highlights: Default::default(),
             clip_at_line_ends: false,
             masked: false,
@@ -185,7 +193,9 @@ impl DisplayMap {
             block_snapshot,
             diagnostics_max_severity: self.diagnostics_max_severity,
             crease_snapshot: self.crease_map.snapshot(),
+            lsp_folding_ranges: self.lsp_folding_ranges.clone(),
             text_highlights: self.text_highlights.clone(),
+            semantic_token_highlights: self.semantic_token_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
@@ -488,6 +498,35 @@ impl DisplayMap {
         self.text_highlights.insert(key, Arc::new((style, ranges)));
     }
 
+    /// Replaces the semantic token highlights, given the ranges styled with each style.
+    pub fn highlight_semantic_tokens(
+        &mut self,
+        highlights: impl IntoIterator<Item = (HighlightStyle, Vec<Range<Anchor>>)>,
+    ) {
+        self.semantic_token_highlights = TreeMap::from_ordered_entries(
+            highlights.into_iter().enumerate().map(|(ix, highlight)| {
+                (
+                    HighlightKey::TypePlus(TypeId::of::<SemanticTokenHighlight>(), ix),
+                    Arc::new(highlight),
+                )
+            }),
+        );
+    }
+
+    /// Replaces the folding ranges of the buffer reported by its language servers, which have to
+    /// be sorted by their start. Without any ranges, the buffer's lines fold by indentation.
+    pub fn set_lsp_folding_ranges(
+        &mut self,
+        buffer_id: BufferId,
+        ranges: Vec<Range<text::Anchor>>,
+    ) {
+        if ranges.is_empty() {
+            self.lsp_folding_ranges.remove(&buffer_id);
+        } else {
+            self.lsp_folding_ranges.insert(buffer_id, ranges.into());
+        }
+    }
+
     pub(crate) fn highlight_inlays(
         &mut self,
         type_id: TypeId,
@@ -631,6 +670,7 @@ impl DisplayMap {
 #[derive(Debug, Default)]
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
+    pub semantic_token_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
     pub styles: HighlightStyles,
 }
@@ -766,11 +806,13 @@ pub struct DisplaySnapshot {
     pub buffer_snapshot: MultiBufferSnapshot,
     pub fold_snapshot: FoldSnapshot,
     pub crease_snapshot: CreaseSnapshot,
+    lsp_folding_ranges: TreeMap<BufferId, Arc<[Range<text::Anchor>]>>,
     inlay_snapshot: InlaySnapshot,
     tab_snapshot: TabSnapshot,
     wrap_snapshot: WrapSnapshot,
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
+    semantic_token_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
     clip_at_line_ends: bool,
     masked: bool,
@@ -942,6 +984,7 @@ impl DisplaySnapshot {
             self.masked,
             Highlights {
                 text_highlights: Some(&self.text_highlights),
+                semantic_token_highlights: Some(&self.semantic_token_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
                 styles: highlight_styles,
             },
@@ -963,9 +1006,11 @@ impl DisplaySnapshot {
             },
         )
         .flat_map(|chunk| {
-            let mut highlight_style = chunk
-                .syntax_highlight_id
-                .and_then(|id| id.style(&editor_style.syntax));
+            let mut highlight_style = chunk.semantic_highlight_style.or_else(|| {
+                chunk
+                    .syntax_highlight_id
+                    .and_then(|id| id.style(&editor_style.syntax))
+            });
 
             if let Some(chunk_highlight) = chunk.highlight_style {
                 // For color inlays, blend the color with the editor background
@@ -1274,6 +1319,64 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Whether the line can be folded, either by a language server's folding range starting on it
+    /// or, for buffers without any, by the following lines being indented deeper.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        match self.excerpt_lsp_folding_range(buffer_row) {
+            Some(range) => range.is_some(),
+            None => self.starts_indent(buffer_row),
+        }
+    }
+
+    /// The outermost folding range reported by language servers that starts on the row, in
+    /// multibuffer coordinates. Returns `None` when the buffer of the excerpt containing the row
+    /// has no such ranges, and `Some(None)` when none of them starts on the row or ends within
+    /// the excerpt.
+    fn excerpt_lsp_folding_range(
+        &self,
+        buffer_row: MultiBufferRow,
+    ) -> Option<Option<Range<MultiBufferPoint>>> {
+        let row_start = self
+            .buffer_snapshot
+            .point_to_offset(MultiBufferPoint::new(buffer_row.0, 0));
+        let mut excerpt = self
+            .buffer_snapshot
+            .excerpt_containing(row_start..row_start)?;
+        let ranges = self.lsp_folding_ranges.get(&excerpt.buffer_id())?;
+        let buffer = excerpt.buffer();
+        let row = buffer
+            .offset_to_point(excerpt.map_offset_to_buffer(row_start))
+            .row;
+        let range = Self::lsp_folding_range_for_row(buffer, ranges, row)
+            .map(|range| buffer.point_to_offset(range.start)..buffer.point_to_offset(range.end))
+            .filter(|range| excerpt.contains_buffer_range(range.clone()))
+            .map(|range| {
+                let range = excerpt.map_range_from_buffer(range);
+                self.buffer_snapshot.offset_to_point(range.start)
+                    ..self.buffer_snapshot.offset_to_point(range.end)
+            });
+        Some(range)
+    }
+
+    /// Returns the outermost of the folding ranges starting on the row.
+    fn lsp_folding_range_for_row(
+        buffer: &language::BufferSnapshot,
+        ranges: &[Range<text::Anchor>],
+        row: u32,
+    ) -> Option<Range<Point>> {
+        let first_ix = ranges
+            .partition_point(|range| buffer.summary_for_anchor::<Point>(&range.start).row < row);
+        ranges[first_ix..]
+            .iter()
+            .map(|range| {
+                buffer.summary_for_anchor::<Point>(&range.start)
+                    ..buffer.summary_for_anchor::<Point>(&range.end)
+            })
+            .take_while(|range| range.start.row == row)
+            .filter(|range| range.end.row > row)
+            .max_by_key(|range| range.end)
+    }
+
     pub fn crease_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Crease<Point>> {
         let start = MultiBufferPoint::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
         if let Some(crease) = self
@@ -1310,6 +1413,18 @@ impl DisplaySnapshot {
                     render_toggle: render_toggle.clone(),
                 }),
             }
+        } else if let Some(range) = self.excerpt_lsp_folding_range(buffer_row) {
+            let range = range?;
+            if self.is_line_folded(buffer_row) {
+                return None;
+            }
+            Some(Crease::Inline {
+                range: start..range.end,
+                placeholder: self.fold_placeholder.clone(),
+                render_toggle: None,
+                render_trailer: None,
+                metadata: None,
+            })
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
@@ -2674,6 +2789,68 @@ pub mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_chunks_with_semantic_token_highlights(cx: &mut gpui::App) {
+        init_test(cx, |_| {});
+
+        let (text, highlighted_ranges) = marked_text_ranges("let «a» = «b»(c);", false);
+        let buffer = MultiBuffer::build_simple(&text, cx);
+        let buffer_snapshot = buffer.read(cx).snapshot(cx);
+        let map = cx.new(|cx| {
+            DisplayMap::new(
+                buffer,
+                font("Helvetica"),
+                px(14.0),
+                None,
+                1,
+                1,
+                FoldPlaceholder::test(),
+                DiagnosticSeverity::Warning,
+                cx,
+            )
+        });
+
+        let style = HighlightStyle {
+            color: Some(Hsla::blue()),
+            ..Default::default()
+        };
+        map.update(cx, |map, _| {
+            map.highlight_semantic_tokens([(
+                style,
+                highlighted_ranges
+                    .into_iter()
+                    .map(|range| {
+                        buffer_snapshot.anchor_before(range.start)
+                            ..buffer_snapshot.anchor_after(range.end)
+                    })
+                    .collect(),
+            )]);
+        });
+
+        let snapshot = map.update(cx, |map, cx| map.snapshot(cx));
+        let mut chunks: Vec<(String, Option<Hsla>)> = Vec::new();
+        for chunk in snapshot.chunks(DisplayRow(0)..DisplayRow(1), false, Default::default()) {
+            let color = chunk.semantic_highlight_style.and_then(|style| style.color);
+            if let Some((last_text, last_color)) = chunks.last_mut() {
+                if color == *last_color {
+                    last_text.push_str(chunk.text);
+                    continue;
+                }
+            }
+            chunks.push((chunk.text.to_string(), color));
+        }
+        assert_eq!(
+            chunks,
+            [
+                ("let ".to_string(), None),
+                ("a".to_string(), Some(Hsla::blue())),
+                (" = ".to_string(), None),
+                ("b".to_string(), Some(Hsla::blue())),
+                ("(c);".to_string(), None),
+            ]
+        );
+    }
+
     #[gpui::test]
     fn test_clip_point(cx: &mut gpui::App) {
         init_test(cx, |_| {});
@@ -19,13 +19,16 @@ pub struct CustomHighlightsChunks<'a> {
 
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
     active_highlights: BTreeMap<HighlightKey, HighlightStyle>,
+    active_semantic_token_highlights: BTreeMap<HighlightKey, HighlightStyle>,
     text_highlights: Option<&'a TextHighlights>,
+    semantic_token_highlights: Option<&'a TextHighlights>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct HighlightEndpoint {
     offset: usize,
     is_start: bool,
+    is_semantic_token: bool,
     tag: HighlightKey,
     style: HighlightStyle,
 }
@@ -35,6 +38,7 @@ impl<'a> CustomHighlightsChunks<'a> {
         range: Range<usize>,
         language_aware: bool,
         text_highlights: Option<&'a TextHighlights>,
+        semantic_token_highlights: Option<&'a TextHighlights>,
         multibuffer_snapshot: &'a MultiBufferSnapshot,
     ) -> Self {
         Self {
@@ -43,36 +47,53 @@ impl<'a> CustomHighlightsChunks<'a> {
             offset: range.start,
 
             text_highlights,
+            semantic_token_highlights,
             highlight_endpoints: create_highlight_endpoints(
                 &range,
                 text_highlights,
+                semantic_token_highlights,
                 multibuffer_snapshot,
             ),
             active_highlights: Default::default(),
+            active_semantic_token_highlights: Default::default(),
             multibuffer_snapshot,
         }
     }
 
     pub fn seek(&mut self, new_range: Range<usize>) {
-        self.highlight_endpoints =
-            create_highlight_endpoints(&new_range, self.text_highlights, self.multibuffer_snapshot);
+        self.highlight_endpoints = create_highlight_endpoints(
+            &new_range,
+            self.text_highlights,
+            self.semantic_token_highlights,
+            self.multibuffer_snapshot,
+        );
         self.offset = new_range.start;
         self.buffer_chunks.seek(new_range);
         self.buffer_chunk.take();
-        self.active_highlights.clear()
+        self.active_highlights.clear();
+        self.active_semantic_token_highlights.clear();
     }
 }
 
 fn create_highlight_endpoints(
     range: &Range<usize>,
     text_highlights: Option<&TextHighlights>,
+    semantic_token_highlights: Option<&TextHighlights>,
     buffer: &MultiBufferSnapshot,
 ) -> iter::Peekable<vec::IntoIter<HighlightEndpoint>> {
     let mut highlight_endpoints = Vec::new();
-    if let Some(text_highlights) = text_highlights {
-        let start = buffer.anchor_after(range.start);
-        let end = buffer.anchor_after(range.end);
-        for (&tag, text_highlights) in text_highlights.iter() {
+    let start = buffer.anchor_after(range.start);
+    let end = buffer.anchor_after(range.end);
+    let highlight_sources = text_highlights
+        .into_iter()
+        .map(|highlights| (highlights, false))
+        .chain(
+            semantic_token_highlights
+                .into_iter()
+                .map(|highlights| (highlights, true)),
+        );
+    for (highlights, is_semantic_token) in highlight_sources {
+        for (&tag, text_highlights) in highlights.iter() {
             let style = text_highlights.0;
             let ranges = &text_highlights.1;
 
@@ -95,19 +116,21 @@ fn create_highlight_endpoints(
                 highlight_endpoints.push(HighlightEndpoint {
                     offset: range.start.to_offset(&buffer),
                     is_start: true,
+                    is_semantic_token,
                     tag,
                     style,
                 });
                 highlight_endpoints.push(HighlightEndpoint {
                     offset: range.end.to_offset(&buffer),
                     is_start: false,
+                    is_semantic_token,
                     tag,
                     style,
                 });
             }
         }
-        highlight_endpoints.sort();
     }
+    highlight_endpoints.sort();
     highlight_endpoints.into_iter().peekable()
 }
 
@@ -118,10 +141,15 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
         let mut next_highlight_endpoint = usize::MAX;
         while let Some(endpoint) = self.highlight_endpoints.peek().copied() {
             if endpoint.offset <= self.offset {
+                let active_highlights = if endpoint.is_semantic_token {
+                    &mut self.active_semantic_token_highlights
+                } else {
+                    &mut self.active_highlights
+                };
                 if endpoint.is_start {
-                    self.active_highlights.insert(endpoint.tag, endpoint.style);
+                    active_highlights.insert(endpoint.tag, endpoint.style);
                 } else {
-                    self.active_highlights.remove(&endpoint.tag);
+                    active_highlights.remove(&endpoint.tag);
                 }
                 self.highlight_endpoints.next();
             } else {
@@ -154,6 +182,13 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
             }
             prefix.highlight_style = Some(highlight_style);
         }
+        if !self.active_semantic_token_highlights.is_empty() {
+            let mut highlight_style = HighlightStyle::default();
+            for active_highlight in self.active_semantic_token_highlights.values() {
+                highlight_style.highlight(*active_highlight);
+            }
+            prefix.semantic_highlight_style = Some(highlight_style);
+        }
         Some(prefix)
     }
 }
@@ -1256,6 +1256,9 @@ pub struct Chunk<'a> {
     /// The highlight style that has been applied to this chunk in
     /// the editor.
     pub highlight_style: Option<HighlightStyle>,
+    /// The style of the language server's semantic token covering this chunk,
+    /// which takes precedence over its syntax highlighting.
+    pub semantic_highlight_style: Option<HighlightStyle>,
     /// The severity of diagnostic associated with this chunk, if any.
     pub diagnostic_severity: Option<lsp::DiagnosticSeverity>,
     /// Whether this chunk of text is marked as unnecessary.
@@ -1435,6 +1438,7 @@ impl<'a> Iterator for FoldChunks<'a> {
                 text: chunk.text,
                 syntax_highlight_id: chunk.syntax_highlight_id,
                 highlight_style: chunk.highlight_style,
+                semantic_highlight_style: chunk.semantic_highlight_style,
                 diagnostic_severity: chunk.diagnostic_severity,
                 is_unnecessary: chunk.is_unnecessary,
                 is_tab: chunk.is_tab,
@@ -1108,6 +1108,7 @@ impl InlaySnapshot {
             buffer_range,
             language_aware,
             highlights.text_highlights,
+            highlights.semantic_token_highlights,
             &self.buffer,
         );
 
@@ -1992,6 +1993,7 @@ mod tests {
 
         let highlights = crate::display_map::Highlights {
             text_highlights: None,
+            semantic_token_highlights: None,
             inlay_highlights: Some(&inlay_highlights),
             styles: crate::display_map::HighlightStyles::default(),
         };
@@ -2108,6 +2110,7 @@ mod tests {
 
             let highlights = crate::display_map::Highlights {
                 text_highlights: None,
+                semantic_token_highlights: None,
                 inlay_highlights: Some(&inlay_highlights),
                 styles: crate::display_map::HighlightStyles::default(),
             };
@@ -16,6 +16,7 @@ pub mod actions;
 mod blink_manager;
 mod clangd_ext;
 pub mod code_context_menus;
+mod code_lens;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
@@ -30,6 +31,7 @@ pub mod items;
 mod jsx_tag_auto_close;
 mod linked_editing_ranges;
 mod lsp_colors;
+mod lsp_document_ranges;
 mod lsp_ext;
 mod mouse_context_menu;
 pub mod movement;
@@ -38,6 +40,7 @@ mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -58,6 +61,7 @@ use blink_manager::BlinkManager;
 use buffer_diff::DiffHunkStatus;
 use client::{Collaborator, ParticipantIndex};
 use clock::{AGENT_REPLICA_ID, ReplicaId};
+use code_lens::CodeLensData;
 use collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use convert_case::{Case, Casing};
 use dap::TelemetrySpawnLocation;
@@ -69,7 +73,9 @@ pub use editor_settings::{
 };
 use editor_settings::{GoToDefinitionFallback, Minimap as MinimapSettings};
 pub use editor_settings_controls::*;
-use element::{AcceptEditPredictionBinding, LineWithInvisibles, PositionMap, layout_line};
+use element::{
+    AcceptEditPredictionBinding, LineWithInvisibles, PositionMap, StickyScrollOutline, layout_line,
+};
 pub use element::{
     CursorLayout, EditorElement, HighlightedRange, HighlightedRangeLine, PointForPosition,
 };
@@ -80,6 +86,8 @@ use futures::{
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use lsp_colors::LspColorData;
+use lsp_document_ranges::DocumentRangesData;
+use semantic_tokens::SemanticTokensData;
 
 use ::git::blame::BlameEntry;
 use ::git::{Restore, blame::ParsedCommitMessage};
@@ -165,7 +173,7 @@ use parking_lot::Mutex;
 use project::{
     CodeAction, Completion, CompletionIntent, CompletionSource, DocumentHighlight, InlayHint,
     Location, LocationLink, PrepareRenameResponse, Project, ProjectItem, ProjectTransaction,
-    TaskSourceKind,
+    SnippetVariables, TaskSourceKind,
     debugger::breakpoint_store::Breakpoint,
     lsp_store::{CompletionDocumentation, FormatTrigger, LspFormatTarget, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
@@ -179,7 +187,7 @@ use selections_collection::{
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsLocation, SettingsStore, update_settings_file};
 use smallvec::{SmallVec, smallvec};
-use snippet::Snippet;
+use snippet::{Snippet, Transform, VariableResolver};
 use std::sync::Arc;
 use std::{
     any::TypeId,
@@ -1145,6 +1153,10 @@ pub struct Editor {
     drag_and_drop_selection_enabled: bool,
     next_color_inlay_id: usize,
     colors: Option<LspColorData>,
+    semantic_tokens: Option<SemanticTokensData>,
+    code_lens: Option<CodeLensData>,
+    document_ranges: Option<DocumentRangesData>,
+    sticky_scroll_outline: Option<StickyScrollOutline>,
     folding_newlines: Task<()>,
 }
 
@@ -1476,6 +1488,38 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    transforms: Vec<Vec<SnippetTransform>>,
+}
+
+/// A range that mirrors a snippet tabstop through a regex transform.
+#[derive(Debug)]
+struct SnippetTransform {
+    source: Range<Anchor>,
+    target: Range<Anchor>,
+    after_source: bool,
+    transform: Transform,
+}
+
+impl SnippetTransform {
+    /// Anchors the target so that it doesn't grow when text is typed next to it, which
+    /// would then be replaced when the transform is applied. An empty target stays on the
+    /// far side of text typed into an adjacent tabstop.
+    fn anchor_target(
+        snapshot: &MultiBufferSnapshot,
+        range: Range<usize>,
+        after_source: bool,
+    ) -> Range<Anchor> {
+        if range.is_empty() {
+            let anchor = if after_source {
+                snapshot.anchor_after(range.start)
+            } else {
+                snapshot.anchor_before(range.start)
+            };
+            anchor..anchor
+        } else {
+            snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end)
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -1841,12 +1885,15 @@ impl Editor {
                     window,
                     |editor, _, event, window, cx| match event {
                         project::Event::RefreshCodeLens => {
-                            // we always query lens with actions, without storing them, always refreshing them
+                            editor.refresh_code_lenses(None, window, cx);
                         }
                         project::Event::RefreshInlayHints => {
                             editor
                                 .refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
                         }
+                        project::Event::RefreshSemanticTokens => {
+                            editor.refresh_semantic_tokens(false, None, window, cx);
+                        }
                         project::Event::LanguageServerAdded(..)
                         | project::Event::LanguageServerRemoved(..) => {
                             if editor.tasks_update_task.is_none() {
@@ -2149,6 +2196,10 @@ impl Editor {
             tasks_update_task: None,
             pull_diagnostics_task: Task::ready(()),
             colors: None,
+            semantic_tokens: None,
+            code_lens: None,
+            document_ranges: None,
+            sticky_scroll_outline: None,
             next_color_inlay_id: 0,
             linked_edit_ranges: Default::default(),
             in_project_search: false,
@@ -2293,6 +2344,9 @@ impl Editor {
             editor.minimap =
                 editor.create_minimap(EditorSettings::get_global(cx).minimap, window, cx);
             editor.colors = Some(LspColorData::new(cx));
+            editor.semantic_tokens = Some(SemanticTokensData::new(cx));
+            editor.code_lens = Some(CodeLensData::new(cx));
+            editor.document_ranges = Some(DocumentRangesData::new());
             editor.update_lsp_data(false, None, window, cx);
         }
 
@@ -5738,6 +5792,22 @@ impl Editor {
         cx.stop_propagation();
 
         let buffer_handle = completions_menu.buffer.clone();
+        let snippet_variables = {
+            let buffer = buffer_handle.read(cx);
+            let selection = self.selections.newest_anchor();
+            let (selection, cursor) = if selection.start.buffer_id == Some(buffer.remote_id())
+                && selection.end.buffer_id == Some(buffer.remote_id())
+            {
+                let start = text::ToPoint::to_point(&selection.start.text_anchor, buffer);
+                let end = text::ToPoint::to_point(&selection.end.text_anchor, buffer);
+                (start..end, if selection.reversed { start } else { end })
+            } else {
+                let cursor =
+                    text::ToPoint::to_point(&completions_menu.initial_position.text_anchor, buffer);
+                (cursor..cursor, cursor)
+            };
+            SnippetVariables::new(&buffer_handle, selection, cursor, cx)
+        };
 
         let CompletionEdit {
             new_text,
@@ -5748,6 +5818,7 @@ impl Editor {
             intent,
             &buffer_handle,
             &completions_menu.initial_position.text_anchor,
+            &snippet_variables,
             cx,
         );
 
@@ -9388,6 +9459,7 @@ impl Editor {
             is_end_tabstop: bool,
             ranges: Vec<Range<T>>,
             choices: Option<Vec<String>>,
+            transforms: Vec<SnippetTransform>,
         }
 
         let tabstops = self.buffer.update(cx, |buffer, cx| {
@@ -9403,6 +9475,19 @@ impl Editor {
 
             let snapshot = &*buffer.read(cx);
             let snippet = &snippet;
+            let insertion_starts = insertion_ranges
+                .iter()
+                .scan(0_isize, |delta, insertion_range| {
+                    let insertion_start = insertion_range.start as isize + *delta;
+                    *delta += snippet.text.len() as isize - insertion_range.len() as isize;
+                    Some(insertion_start)
+                })
+                .collect::<Vec<_>>();
+            let offset_range = |insertion_start: isize, range: &Range<isize>| {
+                let start = ((insertion_start + range.start) as usize).min(snapshot.len());
+                let end = ((insertion_start + range.end) as usize).min(snapshot.len());
+                start..end
+            };
             snippet
                 .tabstops
                 .iter()
@@ -9414,26 +9499,50 @@ impl Editor {
                         .ranges
                         .iter()
                         .flat_map(|tabstop_range| {
-                            let mut delta = 0_isize;
-                            insertion_ranges.iter().map(move |insertion_range| {
-                                let insertion_start = insertion_range.start as isize + delta;
-                                delta +=
-                                    snippet.text.len() as isize - insertion_range.len() as isize;
-
-                                let start = ((insertion_start + tabstop_range.start) as usize)
-                                    .min(snapshot.len());
-                                let end = ((insertion_start + tabstop_range.end) as usize)
-                                    .min(snapshot.len());
-                                snapshot.anchor_before(start)..snapshot.anchor_after(end)
+                            insertion_starts.iter().map(|insertion_start| {
+                                let range = offset_range(*insertion_start, tabstop_range);
+                                snapshot.anchor_before(range.start)
+                                    ..snapshot.anchor_after(range.end)
                             })
                         })
                         .collect::<Vec<_>>();
                     tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
 
+                    // Transforms are applied to the text of the tabstop's first range in the
+                    // same insertion.
+                    let transforms = tabstop
+                        .ranges
+                        .first()
+                        .map(|source_range| {
+                            insertion_starts
+                                .iter()
+                                .flat_map(|insertion_start| {
+                                    let source = offset_range(*insertion_start, source_range);
+                                    tabstop.transforms.iter().map(move |(target, transform)| {
+                                        let target = offset_range(*insertion_start, target);
+                                        let after_source = target.start >= source.end;
+                                        SnippetTransform {
+                                            source: snapshot.anchor_before(source.start)
+                                                ..snapshot.anchor_after(source.end),
+                                            target: SnippetTransform::anchor_target(
+                                                snapshot,
+                                                target,
+                                                after_source,
+                                            ),
+                                            after_source,
+                                            transform: transform.clone(),
+                                        }
+                                    })
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
                     Tabstop {
                         is_end_tabstop,
                         ranges: tabstop_ranges,
                         choices: tabstop.choices.clone(),
+                        transforms,
                     }
                 })
                 .collect::<Vec<_>>()
@@ -9459,15 +9568,16 @@ impl Editor {
                     .map(|tabstop| tabstop.choices.clone())
                     .collect();
 
-                let ranges = tabstops
+                let (ranges, transforms) = tabstops
                     .into_iter()
-                    .map(|tabstop| tabstop.ranges)
-                    .collect::<Vec<_>>();
+                    .map(|tabstop| (tabstop.ranges, tabstop.transforms))
+                    .unzip();
 
                 self.snippet_stack.push(SnippetState {
                     active_index: 0,
                     ranges,
                     choices,
+                    transforms,
                 });
             }
 
@@ -9538,6 +9648,7 @@ impl Editor {
         cx: &mut Context<Self>,
     ) -> bool {
         if let Some(mut snippet) = self.snippet_stack.pop() {
+            let exited_index = snippet.active_index;
             match bias {
                 Bias::Left => {
                     if snippet.active_index > 0 {
@@ -9556,6 +9667,9 @@ impl Editor {
                     }
                 }
             }
+            if let Some(transforms) = snippet.transforms.get_mut(exited_index) {
+                self.apply_snippet_transforms(transforms, cx);
+            }
             if let Some(current_ranges) = snippet.ranges.get(snippet.active_index) {
                 self.change_selections(Default::default(), window, cx, |s| {
                     // Reverse order so that the first range is the newest created selection.
@@ -9580,6 +9694,50 @@ impl Editor {
         false
     }
 
+    /// Updates the ranges that mirror a tabstop through a transform, using the tabstop's
+    /// current text.
+    fn apply_snippet_transforms(
+        &mut self,
+        transforms: &mut [SnippetTransform],
+        cx: &mut Context<Self>,
+    ) {
+        if transforms.is_empty() {
+            return;
+        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let edits = transforms
+            .iter()
+            .map(|transform| {
+                let source_text = snapshot
+                    .text_for_range(transform.source.clone())
+                    .collect::<String>();
+                let start = transform.target.start.to_offset(&snapshot);
+                let end = transform.target.end.to_offset(&snapshot).max(start);
+                let new_text = transform.transform.apply(&source_text);
+                (snapshot.anchor_before(start), start..end, new_text)
+            })
+            .collect::<Vec<_>>();
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                edits
+                    .iter()
+                    .map(|(_, range, new_text)| (range.clone(), new_text.clone())),
+                None,
+                cx,
+            )
+        });
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        for (transform, (start, _, new_text)) in transforms.iter_mut().zip(edits) {
+            let start = start.to_offset(&snapshot);
+            transform.target = SnippetTransform::anchor_target(
+                &snapshot,
+                start..start + new_text.len(),
+                transform.after_source,
+            );
+        }
+    }
+
     pub fn clear(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.transact(window, cx, |this, window, cx| {
             this.select_all(&SelectAll, window, cx);
@@ -19910,6 +20068,24 @@ impl Editor {
             self.refresh_colors(false, None, window, cx);
         }
 
+        if self
+            .semantic_tokens
+            .as_mut()
+            .is_some_and(|semantic_tokens| semantic_tokens.settings_updated(cx))
+        {
+            self.update_semantic_token_highlights(cx);
+            self.refresh_semantic_tokens(false, None, window, cx);
+        }
+
+        if self
+            .code_lens
+            .as_mut()
+            .is_some_and(|code_lens| code_lens.settings_updated(cx))
+        {
+            self.clear_code_lenses(cx);
+            self.refresh_code_lenses(None, window, cx);
+        }
+
         cx.notify();
     }
 
@@ -20815,6 +20991,9 @@ impl Editor {
     ) {
         self.pull_diagnostics(for_buffer, window, cx);
         self.refresh_colors(ignore_cache, for_buffer, window, cx);
+        self.refresh_semantic_tokens(ignore_cache, for_buffer, window, cx);
+        self.refresh_code_lenses(for_buffer, window, cx);
+        self.refresh_document_ranges(for_buffer, cx);
     }
 }
 
@@ -20830,6 +21009,7 @@ fn process_completion_for_edit(
     intent: CompletionIntent,
     buffer: &Entity<Buffer>,
     cursor_position: &text::Anchor,
+    snippet_variables: &dyn VariableResolver,
     cx: &mut Context<Editor>,
 ) -> CompletionEdit {
     let buffer = buffer.read(cx);
@@ -20853,7 +21033,7 @@ fn process_completion_for_edit(
                 }
             }
         }
-        match Snippet::parse(&snippet_source).log_err() {
+        match Snippet::parse_with_variables(&snippet_source, snippet_variables).log_err() {
             Some(parsed_snippet) => (Some(parsed_snippet.clone()), parsed_snippet.text),
             None => (None, completion.new_text.clone()),
         }
@@ -22369,7 +22549,7 @@ impl EditorSnapshot {
             }
         }
 
-        is_foldable |= self.starts_indent(buffer_row);
+        is_foldable |= self.starts_fold(buffer_row);
 
         if folded || (is_foldable && (row_contains_cursor || self.gutter_hovered)) {
             Some(
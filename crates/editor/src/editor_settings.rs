@@ -24,6 +24,7 @@ pub struct EditorSettings {
     pub scrollbar: Scrollbar,
     pub minimap: Minimap,
     pub gutter: Gutter,
+    pub sticky_scroll: StickyScroll,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
     pub autoscroll_on_clicks: bool,
@@ -54,6 +55,8 @@ pub struct EditorSettings {
     pub inline_code_actions: bool,
     pub drag_and_drop_selection: bool,
     pub lsp_document_colors: DocumentColorsRenderMode,
+    pub lsp_semantic_tokens: bool,
+    pub lsp_code_lens: bool,
 }
 
 /// How to render LSP `textDocument/documentColor` colors in the editor.
@@ -183,6 +186,12 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -426,6 +435,8 @@ pub struct EditorSettingsContent {
     pub minimap: Option<MinimapContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
     /// Whether the editor will scroll beyond the last line.
     ///
     /// Default: one_page
@@ -545,6 +556,18 @@ pub struct EditorSettingsContent {
     ///
     /// Default: [`DocumentColorsRenderMode::Inlay`]
     pub lsp_document_colors: Option<DocumentColorsRenderMode>,
+
+    /// Whether to style text with the semantic tokens of language servers, over its
+    /// syntax highlighting.
+    ///
+    /// Default: true
+    pub lsp_semantic_tokens: Option<bool>,
+
+    /// Whether to show the code lenses of language servers above the lines they
+    /// belong to.
+    ///
+    /// Default: true
+    pub lsp_code_lens: Option<bool>,
 }
 
 // Toolbar related settings
@@ -681,6 +704,20 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the first lines of the scopes enclosing the top of the viewport,
+    /// such as functions and classes, at the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of nested scopes pinned at once.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
 impl EditorSettings {
     pub fn jupyter_enabled(cx: &App) -> bool {
         EditorSettings::get_global(cx).jupyter.enabled
@@ -853,6 +890,23 @@ impl Settings for EditorSettings {
             "editor.parameterHints.enabled",
             &mut current.show_signature_help_after_edits,
         );
+        vscode.bool_setting("editor.codeLens", &mut current.lsp_code_lens);
+
+        let mut sticky_scroll = StickyScrollContent::default();
+        vscode.bool_setting("editor.stickyScroll.enabled", &mut sticky_scroll.enabled);
+        vscode.usize_setting(
+            "editor.stickyScroll.maxLineCount",
+            &mut sticky_scroll.max_depth,
+        );
+        if sticky_scroll != StickyScrollContent::default() {
+            let current_sticky_scroll = current.sticky_scroll.get_or_insert_default();
+            if sticky_scroll.enabled.is_some() {
+                current_sticky_scroll.enabled = sticky_scroll.enabled;
+            }
+            if sticky_scroll.max_depth.is_some() {
+                current_sticky_scroll.max_depth = sticky_scroll.max_depth;
+            }
+        }
 
         if let Some(use_ignored) = vscode.read_bool("search.useIgnoreFiles") {
             let search = current.search.get_or_insert_default();
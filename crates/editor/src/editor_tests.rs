@@ -44,7 +44,7 @@ use serde_json::{self, json};
 use std::{cell::RefCell, future::Future, rc::Rc, sync::atomic::AtomicBool, time::Instant};
 use std::{
     iter,
-    sync::atomic::{self, AtomicUsize},
+    sync::atomic::{self, AtomicU32, AtomicUsize},
 };
 use test::{build_editor_with_project, editor_lsp_test_context::rust_lang};
 use text::ToPoint as _;
@@ -9306,6 +9306,66 @@ async fn test_snippets(cx: &mut TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_snippet_transforms(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        ˇ
+        ˇ
+    "});
+
+    cx.update_editor(|editor, window, cx| {
+        let snippet =
+            Snippet::parse("${1:a}${1/(.*)/-${1:/upcase}/} = ${2:b}; // ${2/(.*)/$1$1/}").unwrap();
+        let insertion_ranges = editor
+            .selections
+            .all(cx)
+            .iter()
+            .map(|s| s.range().clone())
+            .collect::<Vec<_>>();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, window, cx)
+            .unwrap();
+    });
+
+    cx.assert_editor_state(indoc! {"
+        «aˇ»-A = b; // bb
+        «aˇ»-A = b; // bb
+    "});
+
+    // Transforms are applied once their tabstop is exited, and text typed next
+    // to them isn't included in the transformed range.
+    cx.update_editor(|editor, window, cx| {
+        editor.handle_input("xy", window, cx);
+        assert!(editor.move_to_next_snippet_tabstop(window, cx));
+    });
+    cx.assert_editor_state(indoc! {"
+        xy-XY = «bˇ»; // bb
+        xy-XY = «bˇ»; // bb
+    "});
+
+    cx.update_editor(|editor, window, cx| {
+        editor.handle_input("c", window, cx);
+        assert!(editor.move_to_prev_snippet_tabstop(window, cx));
+    });
+    cx.assert_editor_state(indoc! {"
+        «xyˇ»-XY = c; // cc
+        «xyˇ»-XY = c; // cc
+    "});
+
+    cx.update_editor(|editor, window, cx| {
+        editor.handle_input("z", window, cx);
+        assert!(editor.move_to_next_snippet_tabstop(window, cx));
+    });
+    cx.assert_editor_state(indoc! {"
+        z-Z = «cˇ»; // cc
+        z-Z = «cˇ»; // cc
+    "});
+}
+
 #[gpui::test]
 async fn test_snippet_indentation(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -21266,6 +21326,253 @@ async fn test_apply_code_lens_actions_with_commands(cx: &mut gpui::TestAppContex
     });
 }
 
+#[gpui::test]
+async fn test_code_lens_blocks(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/a"),
+        json!({
+            "main.rs": "fn main() {\n    other();\n}\n\nfn other() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/a").as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["_the/command".to_string()],
+                    ..lsp::ExecuteCommandOptions::default()
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let editor = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.open_abs_path(
+                PathBuf::from(path!("/a/main.rs")),
+                OpenOptions::default(),
+                window,
+                cx,
+            )
+        })
+        .unwrap()
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    // `other` moves down a line after the edit below.
+    let other_row = Arc::new(AtomicU32::new(4));
+    let mut code_lens_requests = fake_server
+        .set_request_handler::<lsp::request::CodeLensRequest, _, _>({
+            let other_row = other_row.clone();
+            move |_, _| {
+                let other_row = other_row.load(atomic::Ordering::Acquire);
+                async move {
+                    Ok(Some(vec![
+                        lsp::CodeLens {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 3),
+                                lsp::Position::new(0, 7),
+                            ),
+                            command: None,
+                            data: Some(json!("main")),
+                        },
+                        lsp::CodeLens {
+                            range: lsp::Range::new(
+                                lsp::Position::new(other_row, 3),
+                                lsp::Position::new(other_row, 8),
+                            ),
+                            command: None,
+                            data: Some(json!("other")),
+                        },
+                    ]))
+                }
+            }
+        });
+    let resolve_requests = Arc::new(AtomicUsize::new(0));
+    fake_server.set_request_handler::<lsp::request::CodeLensResolve, _, _>({
+        let resolve_requests = resolve_requests.clone();
+        move |mut lens, _| {
+            resolve_requests.fetch_add(1, atomic::Ordering::Release);
+            async move {
+                lens.command = Some(lsp::Command {
+                    title: format!("Run {}", lens.data.as_ref().unwrap()),
+                    command: "_the/command".to_owned(),
+                    arguments: None,
+                });
+                Ok(lens)
+            }
+        }
+    });
+
+    cx.executor().advance_clock(Duration::from_millis(500));
+    code_lens_requests.next().await.unwrap();
+    cx.run_until_parked();
+    editor.update(cx, |editor, cx| {
+        assert_eq!(
+            editor.display_text(cx),
+            "\nfn main() {\n    other();\n}\n\n\nfn other() {}",
+            "Each line with lenses should have a block above it"
+        );
+    });
+    assert_eq!(
+        resolve_requests.load(atomic::Ordering::Acquire),
+        2,
+        "Visible lenses should be resolved once"
+    );
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([Point::new(3, 0)..Point::new(3, 0)])
+        });
+        editor.handle_input("\n", window, cx);
+    });
+    other_row.store(5, atomic::Ordering::Release);
+    cx.executor().advance_clock(Duration::from_millis(500));
+    code_lens_requests.next().await.unwrap();
+    cx.run_until_parked();
+    editor.update(cx, |editor, cx| {
+        assert_eq!(
+            editor.display_text(cx),
+            "\nfn main() {\n    other();\n}\n\n\n\nfn other() {}",
+            "Lenses should be requested again after an edit"
+        );
+    });
+    assert_eq!(
+        resolve_requests.load(atomic::Ordering::Acquire),
+        2,
+        "Lenses that didn't change should keep their blocks, resolved"
+    );
+
+    cx.update(|_, cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.lsp_code_lens = Some(false);
+            });
+        });
+    });
+    cx.run_until_parked();
+    editor.update(cx, |editor, cx| {
+        assert_eq!(
+            editor.display_text(cx),
+            "fn main() {\n    other();\n}\n\n\nfn other() {}",
+            "Disabling code lenses should remove their blocks"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_lsp_folding_ranges(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/a"),
+        json!({
+            "main.rs": "use std::fmt;\nuse std::io;\n\n// a\n// b\nfn main() {\n    other();\n}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/a").as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let editor = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.open_abs_path(
+                PathBuf::from(path!("/a/main.rs")),
+                OpenOptions::default(),
+                window,
+                cx,
+            )
+        })
+        .unwrap()
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let mut folding_range_requests = fake_server
+        .set_request_handler::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![
+                lsp::FoldingRange {
+                    start_line: 0,
+                    end_line: 1,
+                    kind: Some(lsp::FoldingRangeKind::Imports),
+                    ..lsp::FoldingRange::default()
+                },
+                lsp::FoldingRange {
+                    start_line: 3,
+                    end_line: 4,
+                    kind: Some(lsp::FoldingRangeKind::Comment),
+                    ..lsp::FoldingRange::default()
+                },
+            ]))
+        });
+
+    cx.executor().advance_clock(Duration::from_millis(500));
+    folding_range_requests.next().await.unwrap();
+    cx.run_until_parked();
+    editor.update_in(cx, |editor, window, cx| {
+        let snapshot = editor.snapshot(window, cx);
+        assert_eq!(
+            snapshot
+                .crease_for_buffer_row(MultiBufferRow(0))
+                .map(|crease| crease.range().clone()),
+            Some(Point::new(0, 13)..Point::new(1, 12))
+        );
+        assert!(snapshot.starts_indent(MultiBufferRow(5)));
+        assert!(
+            snapshot.crease_for_buffer_row(MultiBufferRow(5)).is_none(),
+            "The server's folding ranges should supersede the indentation based ones"
+        );
+
+        editor.fold_all_imports(&FoldAllImports, window, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            "use std::fmt;⋯\n\n// a\n// b\nfn main() {\n    other();\n}"
+        );
+        editor.fold_all_comments(&FoldAllComments, window, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            "use std::fmt;⋯\n\n// a⋯\nfn main() {\n    other();\n}"
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_editor_restore_data_different_in_panes(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
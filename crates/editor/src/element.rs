@@ -8,8 +8,8 @@ use crate::{
     HoveredCursor, InlayHintRefreshReason, InlineCompletion, JumpData, LineDown, LineHighlight,
     LineUp, MAX_LINE_LEN, MINIMAP_FONT_SIZE, MULTI_BUFFER_EXCERPT_HEADER_HEIGHT, OpenExcerpts,
     PageDown, PageUp, PhantomBreakpointIndicator, Point, RowExt, RowRangeExt, SelectPhase,
-    SelectedTextHighlight, Selection, SelectionDragState, SoftWrap, StickyHeaderExcerpt, ToPoint,
-    ToggleFold,
+    SelectedTextHighlight, Selection, SelectionDragState, SelectionEffects, SoftWrap,
+    StickyHeaderExcerpt, ToPoint, ToggleFold,
     code_context_menus::{CodeActionsMenu, MENU_ASIDE_MAX_WIDTH, MENU_ASIDE_MIN_WIDTH, MENU_GAP},
     display_map::{
         Block, BlockContext, BlockStyle, ChunkRendererId, DisplaySnapshot, EditorMargins,
@@ -28,7 +28,7 @@ use crate::{
     inlay_hint_settings,
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition},
-    scroll::{ActiveScrollbarState, ScrollbarThumbState, scroll_amount::ScrollAmount},
+    scroll::{ActiveScrollbarState, Autoscroll, ScrollbarThumbState, scroll_amount::ScrollAmount},
 };
 use buffer_diff::{DiffHunkStatus, DiffHunkStatusKind};
 use collections::{BTreeMap, HashMap};
@@ -50,6 +50,7 @@ use gpui::{
     quad, relative, size, solid_background, transparent_black,
 };
 use itertools::Itertools;
+use language::OutlineItem;
 use language::language_settings::{
     IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings, ShowWhitespaceSetting,
 };
@@ -78,7 +79,7 @@ use std::{
     time::{Duration, Instant},
 };
 use sum_tree::Bias;
-use text::{BufferId, SelectionGoal};
+use text::{BufferId, SelectionGoal, ToPoint as _};
 use theme::{ActiveTheme, Appearance, BufferLineHeight, PlayerColor};
 use ui::{ButtonLike, KeyBinding, POPOVER_Y_PADDING, Tooltip, h_flex, prelude::*};
 use unicode_segmentation::UnicodeSegmentation;
@@ -412,6 +413,8 @@ impl EditorElement {
         register_action(editor, window, Editor::fold);
         register_action(editor, window, Editor::fold_at_level);
         register_action(editor, window, Editor::fold_all);
+        register_action(editor, window, Editor::fold_all_comments);
+        register_action(editor, window, Editor::fold_all_imports);
         register_action(editor, window, Editor::fold_function_bodies);
         register_action(editor, window, Editor::fold_recursive);
         register_action(editor, window, Editor::toggle_fold);
@@ -4031,6 +4034,181 @@ impl EditorElement {
         header
     }
 
+    /// Returns the first lines of the syntax scopes enclosing the top of the viewport, outermost
+    /// first, whose lines have been scrolled out of view (or under the lines pinned above them).
+    fn sticky_scroll_rows(
+        snapshot: &EditorSnapshot,
+        scroll_top: f32,
+        max_depth: usize,
+        outline: &mut Option<StickyScrollOutline>,
+    ) -> Vec<StickyScrollRow> {
+        let mut rows: Vec<StickyScrollRow> = Vec::new();
+        if max_depth == 0 {
+            return rows;
+        }
+        let Some((_, _, buffer)) = snapshot.buffer_snapshot.as_singleton() else {
+            return rows;
+        };
+
+        let top_row = DisplayRow(scroll_top as u32);
+        let query_start = DisplayPoint::new(top_row, 0).to_point(&snapshot.display_snapshot);
+        let query_end = snapshot
+            .clip_point(
+                DisplayPoint::new(top_row + DisplayRow(max_depth as u32), 0),
+                Bias::Left,
+            )
+            .to_point(&snapshot.display_snapshot);
+        let query = query_start..query_end;
+        let is_cached = outline.as_ref().is_some_and(|outline| {
+            outline.query == query
+                && outline.syntax_update_count == buffer.syntax_update_count()
+                && &outline.version == buffer.version()
+        });
+        if !is_cached {
+            *outline = Some(StickyScrollOutline {
+                version: buffer.version().clone(),
+                syntax_update_count: buffer.syntax_update_count(),
+                items: buffer.outline_items_containing(query.clone(), false, None),
+                query,
+            });
+        }
+        let Some(items) = outline.as_ref().and_then(|outline| outline.items.as_ref()) else {
+            return rows;
+        };
+
+        for item in items {
+            let depth = rows.len();
+            if depth == max_depth {
+                break;
+            }
+
+            let start = item.range.start.to_point(buffer);
+            let end = item.range.end.to_point(buffer);
+            // Only the first display row of the scope's first line is pinned, even when the line
+            // is soft-wrapped.
+            let display_row = Point::new(start.row, 0)
+                .to_display_point(&snapshot.display_snapshot)
+                .row();
+            let end_display_row = end.to_display_point(&snapshot.display_snapshot).row();
+
+            // Items are ordered by their start, so all remaining scopes start in plain view.
+            if display_row.as_f32() >= scroll_top + depth as f32 {
+                break;
+            }
+            // The scope ends above the line it would be pinned over, and would be fully covered
+            // by the line of its parent.
+            if end_display_row.as_f32() <= scroll_top + depth as f32 - 1. {
+                continue;
+            }
+            if let Some(parent) = rows.last() {
+                if display_row <= parent.display_row || end_display_row > parent.end_display_row {
+                    continue;
+                }
+            }
+
+            rows.push(StickyScrollRow {
+                display_row,
+                end_display_row,
+                target: start,
+            });
+        }
+
+        rows
+    }
+
+    fn layout_sticky_scroll(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_top: f32,
+        line_height: Pixels,
+        editor_width: Pixels,
+        right_margin: Pixels,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<StickyScrollLayout> {
+        let settings = EditorSettings::get_global(cx);
+        if !settings.sticky_scroll.enabled || !snapshot.mode.is_full() {
+            return None;
+        }
+        let rows = self.editor.update(cx, |editor, _| {
+            Self::sticky_scroll_rows(
+                snapshot,
+                scroll_top,
+                settings.sticky_scroll.max_depth,
+                &mut editor.sticky_scroll_outline,
+            )
+        });
+        if rows.is_empty() {
+            return None;
+        }
+
+        let include_line_numbers = gutter_hitbox.size.width > Pixels::ZERO
+            && snapshot
+                .show_line_numbers
+                .unwrap_or_else(|| settings.gutter.line_numbers);
+        let line_number_color = cx.theme().colors().editor_line_number;
+        let mut bounds = gutter_hitbox.bounds.union(&text_hitbox.bounds);
+        bounds.size.width -= right_margin;
+
+        let mut layouts = Vec::with_capacity(rows.len());
+        for (depth, row) in rows.into_iter().enumerate() {
+            let Some(line) = Self::layout_lines(
+                row.display_row..row.display_row.next_row(),
+                snapshot,
+                &self.style,
+                editor_width,
+                |_| false,
+                window,
+                cx,
+            )
+            .pop() else {
+                continue;
+            };
+
+            // Push the line up as the end of its scope scrolls past it.
+            let offset = (depth as f32).min(row.end_display_row.as_f32() - scroll_top);
+            let origin = point(bounds.origin.x, bounds.origin.y + offset * line_height);
+
+            let line_number = include_line_numbers.then(|| {
+                let shaped_line = self.shape_line_number(
+                    SharedString::from((row.target.row + 1).to_string()),
+                    line_number_color,
+                    window,
+                );
+                let origin = point(
+                    gutter_hitbox.bounds.right()
+                        - shaped_line.width
+                        - gutter_dimensions.right_padding,
+                    origin.y,
+                );
+                (shaped_line, origin)
+            });
+
+            layouts.push(StickyScrollRowLayout {
+                origin,
+                line,
+                line_number,
+                target: row.target,
+                depth,
+                hitbox: None,
+            });
+        }
+
+        // Deeper lines are inserted first, so that the lines of their parents cover them while
+        // they're being pushed up.
+        for row in layouts.iter_mut().rev() {
+            row.hitbox = Some(window.insert_hitbox(
+                Bounds::new(row.origin, size(bounds.size.width, line_height)),
+                HitboxBehavior::BlockMouseExceptScroll,
+            ));
+        }
+
+        Some(StickyScrollLayout { rows: layouts })
+    }
+
     fn layout_cursor_popovers(
         &self,
         line_height: Pixels,
@@ -6633,6 +6811,117 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_scroll(
+        &mut self,
+        layout: &mut EditorLayout,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some(sticky_scroll) = layout.sticky_scroll.take() else {
+            return;
+        };
+
+        let line_height = layout.position_map.line_height;
+        let gutter_bounds = layout.gutter_hitbox.bounds;
+        let text_bounds = layout.position_map.text_hitbox.bounds;
+        let gutter_background = cx.theme().colors().editor_gutter_background;
+        let border_color = cx.theme().colors().border_variant;
+        let scroll_left = layout.position_map.scroll_pixel_position.x;
+
+        window.paint_layer(layout.hitbox.bounds, |window| {
+            for row in sticky_scroll.rows.iter().rev() {
+                let gutter_row_bounds = Bounds::new(
+                    point(gutter_bounds.origin.x, row.origin.y),
+                    size(gutter_bounds.size.width, line_height),
+                );
+                let text_row_bounds = Bounds::new(
+                    point(text_bounds.origin.x, row.origin.y),
+                    size(text_bounds.size.width, line_height),
+                );
+                window.paint_quad(fill(gutter_row_bounds, gutter_background));
+                window.paint_quad(fill(text_row_bounds, self.style.background));
+
+                if let Some((line_number, origin)) = &row.line_number {
+                    line_number
+                        .paint(*origin, line_height, window, cx)
+                        .log_err();
+                }
+
+                window.with_content_mask(
+                    Some(ContentMask {
+                        bounds: text_row_bounds,
+                    }),
+                    |window| {
+                        let mut fragment_origin =
+                            point(layout.content_origin.x - scroll_left, row.origin.y);
+                        for fragment in &row.line.fragments {
+                            match fragment {
+                                LineFragment::Text(line) => {
+                                    line.paint_background(fragment_origin, line_height, window, cx)
+                                        .log_err();
+                                    line.paint(fragment_origin, line_height, window, cx)
+                                        .log_err();
+                                    fragment_origin.x += line.width;
+                                }
+                                LineFragment::Element { size, .. } => {
+                                    fragment_origin.x += size.width;
+                                }
+                            }
+                        }
+                    },
+                );
+            }
+
+            let bottom = sticky_scroll
+                .rows
+                .iter()
+                .map(|row| row.origin.y + line_height)
+                .fold(layout.hitbox.origin.y, Pixels::max);
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(layout.hitbox.origin.x, bottom - px(1.)),
+                    size(gutter_bounds.size.width + text_bounds.size.width, px(1.)),
+                ),
+                border_color,
+            ));
+        });
+
+        let targets = sticky_scroll
+            .rows
+            .into_iter()
+            .filter_map(|row| Some((row.hitbox?, row.target, row.depth)))
+            .collect::<Vec<_>>();
+        for (hitbox, _, _) in &targets {
+            window.set_cursor_style(CursorStyle::PointingHand, hitbox);
+        }
+
+        let editor = self.editor.clone();
+        window.on_mouse_event(move |event: &MouseDownEvent, phase, window, cx| {
+            if phase != DispatchPhase::Bubble || event.button != MouseButton::Left {
+                return;
+            }
+            let Some((_, target, depth)) = targets
+                .iter()
+                .find(|(hitbox, _, _)| hitbox.is_hovered(window))
+            else {
+                return;
+            };
+
+            let (target, depth) = (*target, *depth);
+            editor.update(cx, |editor, cx| {
+                window.focus(&editor.focus_handle(cx));
+                // Scroll the scope's first line right below the lines of its parents.
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::top_relative(depth)),
+                    window,
+                    cx,
+                    |selections| selections.select_ranges([target..target]),
+                );
+            });
+            cx.stop_propagation();
+        });
+    }
+
     fn paint_inline_completion_popover(
         &mut self,
         layout: &mut EditorLayout,
@@ -8794,6 +9083,19 @@ impl Element for EditorElement {
                         self.prepaint_expand_toggles(&mut expand_toggles, window, cx)
                     });
 
+                    let sticky_scroll = self.layout_sticky_scroll(
+                        &snapshot,
+                        scroll_position.y,
+                        line_height,
+                        editor_width,
+                        right_margin,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &text_hitbox,
+                        window,
+                        cx,
+                    );
+
                     let wrap_guides = self.layout_wrap_guides(
                         em_advance,
                         scroll_position,
@@ -8925,6 +9227,7 @@ impl Element for EditorElement {
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scroll,
                         expand_toggles,
                     }
                 })
@@ -8987,6 +9290,8 @@ impl Element for EditorElement {
                         });
                     }
 
+                    self.paint_sticky_scroll(layout, window, cx);
+
                     window.with_element_namespace("blocks", |window| {
                         if let Some(mut sticky_header) = layout.sticky_buffer_header.take() {
                             sticky_header.paint(window, cx)
@@ -9110,6 +9415,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scroll: Option<StickyScrollLayout>,
     document_colors: Option<(DocumentColorsRenderMode, Vec<(Range<DisplayPoint>, Hsla)>)>,
 }
 
@@ -9124,6 +9430,38 @@ struct LineNumberLayout {
     hitbox: Option<Hitbox>,
 }
 
+/// The outline items around the top of the viewport that sticky scroll rows were last laid out
+/// with, which are only queried again after the buffer is edited or reparsed, or scrolled.
+pub(crate) struct StickyScrollOutline {
+    version: clock::Global,
+    syntax_update_count: usize,
+    query: Range<Point>,
+    items: Option<Vec<OutlineItem<language::Anchor>>>,
+}
+
+/// The first line of a syntax scope enclosing the top of the viewport.
+#[derive(Debug, PartialEq)]
+struct StickyScrollRow {
+    display_row: DisplayRow,
+    /// The last display row of the scope, which pushes the pinned line up as it scrolls by.
+    end_display_row: DisplayRow,
+    /// Where to move the cursor when the pinned line is clicked.
+    target: Point,
+}
+
+struct StickyScrollLayout {
+    rows: Vec<StickyScrollRowLayout>,
+}
+
+struct StickyScrollRowLayout {
+    origin: gpui::Point<Pixels>,
+    line: LineWithInvisibles,
+    line_number: Option<(ShapedLine, gpui::Point<Pixels>)>,
+    target: Point,
+    depth: usize,
+    hitbox: Option<Hitbox>,
+}
+
 struct ColoredRange<T> {
     start: T,
     end: T,
@@ -10063,11 +10401,13 @@ mod tests {
     use crate::{
         Editor, MultiBuffer, SelectionEffects,
         display_map::{BlockPlacement, BlockProperties},
+        editor_settings::StickyScrollContent,
         editor_tests::{init_test, update_test_language_settings},
     };
-    use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use gpui::{TestAppContext, VisualTestContext, WindowHandle};
+    use language::{Buffer, Language, LanguageConfig, language_settings};
     use log::info;
+    use settings::SettingsStore;
     use std::num::NonZeroU32;
     use util::test::sample_text;
 
@@ -10290,6 +10630,208 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_sticky_scroll_rows(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let window = build_sticky_scroll_editor(cx);
+        let sticky_rows = |scroll_top: f32, max_depth: usize, cx: &mut TestAppContext| {
+            window
+                .update(cx, |editor, window, cx| {
+                    let snapshot = editor.snapshot(window, cx);
+                    EditorElement::sticky_scroll_rows(
+                        &snapshot,
+                        scroll_top,
+                        max_depth,
+                        &mut editor.sticky_scroll_outline,
+                    )
+                    .into_iter()
+                    .map(|row| (row.display_row.0, row.end_display_row.0, row.target.row))
+                    .collect::<Vec<_>>()
+                })
+                .unwrap()
+        };
+
+        assert_eq!(sticky_rows(0., 5, cx), Vec::new());
+        // Pinning `mod outer` covers the line of `impl Foo`, which covers the line of `one`.
+        assert_eq!(
+            sticky_rows(1., 5, cx),
+            vec![(0, 11, 0), (1, 10, 1), (2, 6, 2)]
+        );
+        assert_eq!(
+            sticky_rows(3., 5, cx),
+            vec![(0, 11, 0), (1, 10, 1), (2, 6, 2)]
+        );
+        assert_eq!(sticky_rows(3., 2, cx), vec![(0, 11, 0), (1, 10, 1)]);
+        assert_eq!(sticky_rows(3., 0, cx), Vec::new());
+        // The line of `one` is being pushed up by its closing brace...
+        assert_eq!(
+            sticky_rows(4.5, 5, cx),
+            vec![(0, 11, 0), (1, 10, 1), (2, 6, 2)]
+        );
+        // ...until the line of its parent covers it completely.
+        assert_eq!(sticky_rows(5., 5, cx), vec![(0, 11, 0), (1, 10, 1)]);
+        // `two` starts under the line pinned for `impl Foo`.
+        assert_eq!(
+            sticky_rows(6., 5, cx),
+            vec![(0, 11, 0), (1, 10, 1), (7, 9, 7)]
+        );
+
+        window
+            .update(cx, |editor, window, cx| {
+                editor.fold_ranges(vec![Point::new(2, 41)..Point::new(6, 8)], false, window, cx);
+            })
+            .unwrap();
+        // The folded `one` takes a single display row, so it never encloses the top of the
+        // viewport, and `two` moves up with the rows below the fold.
+        assert_eq!(
+            sticky_rows(3., 5, cx),
+            vec![(0, 7, 0), (1, 6, 1), (3, 5, 7)]
+        );
+    }
+
+    #[gpui::test]
+    fn test_sticky_scroll_rows_with_soft_wrap(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let window = build_sticky_scroll_editor(cx);
+        window
+            .update(cx, |editor, _, cx| {
+                editor.set_wrap_width(Some(140.0.into()), cx);
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        window
+            .update(cx, |editor, window, cx| {
+                let snapshot = editor.snapshot(window, cx);
+                let display_row = |row: u32| {
+                    Point::new(row, 0)
+                        .to_display_point(&snapshot.display_snapshot)
+                        .row()
+                        .0
+                };
+                assert!(
+                    display_row(3) > display_row(2) + 1,
+                    "the signature of `one` should be soft-wrapped"
+                );
+
+                // Only the first display row of the wrapped line is pinned.
+                let scroll_top = display_row(3) as f32;
+                let rows = EditorElement::sticky_scroll_rows(&snapshot, scroll_top, 5, &mut None)
+                    .into_iter()
+                    .map(|row| (row.display_row.0, row.target.row))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    rows,
+                    vec![
+                        (display_row(0), 0),
+                        (display_row(1), 1),
+                        (display_row(2), 2)
+                    ]
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_sticky_scroll_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let window = build_sticky_scroll_editor(cx);
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        window
+            .update(cx, |editor, window, cx| {
+                editor.set_scroll_position(gpui::Point::new(0., 3.), window, cx);
+            })
+            .unwrap();
+
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        assert!(
+            state.sticky_scroll.is_none(),
+            "sticky scroll is disabled by default"
+        );
+
+        cx.update(|_, cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: None,
+                    });
+                });
+            });
+        });
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style),
+        );
+        let line_numbers = state
+            .sticky_scroll
+            .as_ref()
+            .unwrap()
+            .rows
+            .iter()
+            .map(|row| {
+                row.line_number
+                    .as_ref()
+                    .map(|(line_number, _)| line_number.text.to_string())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            line_numbers,
+            vec![Some("1".into()), Some("2".into()), Some("3".into())]
+        );
+    }
+
+    fn build_sticky_scroll_editor(cx: &mut TestAppContext) -> WindowHandle<Editor> {
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (mod_item "mod" @context name: (_) @name) @item
+                (impl_item "impl" @context type: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = indoc::indoc! {"
+            mod outer {
+                impl Foo {
+                    fn one(first: u32, second: u32) {
+                        a();
+                        b();
+                        c();
+                    }
+                    fn two() {
+                        d();
+                    }
+                }
+            }
+        "};
+
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+        let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+        let window =
+            cx.add_window(|window, cx| Editor::new(EditorMode::full(), buffer, None, window, cx));
+        cx.run_until_parked();
+        window
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
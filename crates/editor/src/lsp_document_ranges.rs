@@ -0,0 +1,278 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use futures::future::{join, join_all};
+use gpui::{Entity, Task};
+use language::Buffer;
+use project::{DocumentLink, FoldingRange, ResolvedPath};
+use text::{BufferId, OffsetRangeExt as _, ToOffset as _};
+use ui::{App, Context, Window};
+
+use crate::{
+    Editor,
+    actions::{FoldAllComments, FoldAllImports},
+    display_map::Crease,
+    hover_links::HoverLink,
+};
+
+/// How long to wait after an edit before requesting the ranges again, as servers
+/// compute them for the whole document.
+const DOCUMENT_RANGES_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The folding ranges and document links that language servers reported for the
+/// buffers of an editor.
+pub(super) struct DocumentRangesData {
+    buffer_ranges: HashMap<BufferId, BufferDocumentRanges>,
+    fetch_task: Task<()>,
+}
+
+struct BufferDocumentRanges {
+    folding_ranges: Vec<FoldingRange>,
+    links: Vec<DocumentLink>,
+    /// Whether the ranges have to be requested again, after an edit.
+    outdated: bool,
+}
+
+impl DocumentRangesData {
+    pub fn new() -> Self {
+        Self {
+            buffer_ranges: HashMap::default(),
+            fetch_task: Task::ready(()),
+        }
+    }
+}
+
+impl Editor {
+    /// Marks the ranges of the buffer, or of all buffers, as outdated and requests
+    /// the ranges of the visible ones again.
+    pub(super) fn refresh_document_ranges(
+        &mut self,
+        buffer_id: Option<BufferId>,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode().is_full() || self.project.is_none() {
+            return;
+        }
+        let Some(document_ranges) = &mut self.document_ranges else {
+            return;
+        };
+        for (ranges_buffer_id, buffer_ranges) in &mut document_ranges.buffer_ranges {
+            if buffer_id.is_none_or(|buffer_id| buffer_id == *ranges_buffer_id) {
+                buffer_ranges.outdated = true;
+            }
+        }
+        document_ranges.fetch_task = cx.spawn(async move |editor, cx| {
+            cx.background_executor()
+                .timer(DOCUMENT_RANGES_DEBOUNCE)
+                .await;
+            editor
+                .update(cx, |editor, cx| editor.fetch_visible_document_ranges(cx))
+                .ok();
+        });
+    }
+
+    /// Requests the ranges of the visible buffers that have none yet, or outdated ones.
+    pub(super) fn fetch_visible_document_ranges(&mut self, cx: &mut Context<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        if self.document_ranges.is_none() {
+            return;
+        }
+        let visible_buffers = self
+            .visible_excerpts(None, cx)
+            .into_values()
+            .map(|(buffer, ..)| (buffer.read(cx).remote_id(), buffer))
+            .collect::<HashMap<_, _>>();
+        let multi_buffer = self.buffer.read(cx);
+        let Some(document_ranges) = &mut self.document_ranges else {
+            return;
+        };
+
+        let mut removed_buffers = Vec::new();
+        document_ranges.buffer_ranges.retain(|buffer_id, _| {
+            let is_in_editor = multi_buffer.buffer(*buffer_id).is_some();
+            if !is_in_editor {
+                removed_buffers.push(*buffer_id);
+            }
+            is_in_editor
+        });
+        let buffers_to_fetch = visible_buffers
+            .into_iter()
+            .filter(|(buffer_id, _)| {
+                let buffer_ranges = document_ranges
+                    .buffer_ranges
+                    .entry(*buffer_id)
+                    .or_insert_with(|| BufferDocumentRanges {
+                        folding_ranges: Vec::new(),
+                        links: Vec::new(),
+                        outdated: true,
+                    });
+                let outdated = buffer_ranges.outdated;
+                buffer_ranges.outdated = false;
+                outdated
+            })
+            .collect::<Vec<_>>();
+        if !removed_buffers.is_empty() {
+            self.display_map.update(cx, |display_map, _| {
+                for buffer_id in removed_buffers {
+                    display_map.set_lsp_folding_ranges(buffer_id, Vec::new());
+                }
+            });
+        }
+        if buffers_to_fetch.is_empty() {
+            return;
+        }
+
+        let fetch_tasks = project.update(cx, |project, cx| {
+            buffers_to_fetch
+                .into_iter()
+                .map(|(buffer_id, buffer)| {
+                    let ranges_task = join(
+                        project.folding_ranges(&buffer, cx),
+                        project.document_links(&buffer, cx),
+                    );
+                    async move { (buffer_id, ranges_task.await) }
+                })
+                .collect::<Vec<_>>()
+        });
+        cx.spawn(async move |editor, cx| {
+            let fetched_ranges = join_all(fetch_tasks).await;
+            editor
+                .update(cx, |editor, cx| {
+                    for (buffer_id, (folding_ranges, links)) in fetched_ranges {
+                        let folding_ranges = folding_ranges.unwrap_or_else(|e| {
+                            log::error!("Failed to fetch folding ranges: {e:#}");
+                            Vec::new()
+                        });
+                        let links = links.unwrap_or_else(|e| {
+                            log::error!("Failed to fetch document links: {e:#}");
+                            Vec::new()
+                        });
+                        editor.set_document_ranges(buffer_id, folding_ranges, links, cx);
+                    }
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    fn set_document_ranges(
+        &mut self,
+        buffer_id: BufferId,
+        folding_ranges: Vec<FoldingRange>,
+        links: Vec<DocumentLink>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(document_ranges) = &mut self.document_ranges else {
+            return;
+        };
+        // The buffer may have been removed from the editor while fetching.
+        let Some(buffer_ranges) = document_ranges.buffer_ranges.get_mut(&buffer_id) else {
+            return;
+        };
+        let display_ranges = folding_ranges
+            .iter()
+            .map(|folding_range| folding_range.range.clone())
+            .collect();
+        buffer_ranges.folding_ranges = folding_ranges;
+        buffer_ranges.links = links;
+        self.display_map.update(cx, |display_map, _| {
+            display_map.set_lsp_folding_ranges(buffer_id, display_ranges)
+        });
+        cx.notify();
+    }
+
+    /// Returns the range and the target of the document link containing the position.
+    pub(super) fn document_link_at(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: text::Anchor,
+        cx: &App,
+    ) -> Option<(Range<text::Anchor>, HoverLink)> {
+        let buffer = buffer.read(cx);
+        let links = &self
+            .document_ranges
+            .as_ref()?
+            .buffer_ranges
+            .get(&buffer.remote_id())?
+            .links;
+        let offset = position.to_offset(buffer);
+        let link = links.iter().find(|link| {
+            let range = link.range.to_offset(buffer);
+            range.start <= offset && offset < range.end
+        })?;
+        let hover_link = match url::Url::parse(&link.target) {
+            Ok(url) if url.scheme() == "file" => HoverLink::File(ResolvedPath::AbsPath {
+                path: url.to_file_path().ok()?,
+                is_dir: false,
+            }),
+            _ => HoverLink::Url(link.target.clone()),
+        };
+        Some((link.range.clone(), hover_link))
+    }
+
+    pub fn fold_all_comments(
+        &mut self,
+        _: &FoldAllComments,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fold_lsp_ranges_of_kind(lsp::FoldingRangeKind::Comment, window, cx);
+    }
+
+    pub fn fold_all_imports(
+        &mut self,
+        _: &FoldAllImports,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fold_lsp_ranges_of_kind(lsp::FoldingRangeKind::Imports, window, cx);
+    }
+
+    /// Folds the language servers' folding ranges of the kind, in each excerpt that
+    /// contains them.
+    fn fold_lsp_ranges_of_kind(
+        &mut self,
+        kind: lsp::FoldingRangeKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(document_ranges) = &self.document_ranges else {
+            return;
+        };
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let placeholder = self.display_map.read(cx).fold_placeholder.clone();
+        let mut creases = Vec::new();
+        for (excerpt_id, buffer_snapshot, excerpt_range) in multi_buffer_snapshot.excerpts() {
+            let Some(buffer_ranges) = document_ranges
+                .buffer_ranges
+                .get(&buffer_snapshot.remote_id())
+            else {
+                continue;
+            };
+            let excerpt_range = excerpt_range.context.to_offset(buffer_snapshot);
+            for folding_range in &buffer_ranges.folding_ranges {
+                if folding_range.kind.as_ref() != Some(&kind) {
+                    continue;
+                }
+                let range = folding_range.range.to_offset(buffer_snapshot);
+                if range.start < excerpt_range.start || range.end > excerpt_range.end {
+                    continue;
+                }
+                let Some(start) =
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.start)
+                else {
+                    continue;
+                };
+                let Some(end) =
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, folding_range.range.end)
+                else {
+                    continue;
+                };
+                creases.push(Crease::simple(start..end, placeholder.clone()));
+            }
+        }
+        self.fold_creases(creases, true, window, cx);
+    }
+}
@@ -11,6 +11,7 @@ use gpui::{Context, DismissEvent, Entity, Focusable as _, Pixels, Point, Subscri
 use std::ops::Range;
 use text::PointUtf16;
 use workspace::OpenInTerminal;
+use zed_actions::hierarchy_panel::{ShowIncomingCalls, ShowSubtypes};
 
 #[derive(Debug)]
 pub enum MenuPosition {
@@ -214,6 +215,8 @@ pub fn deploy_context_menu(
                 .action("Go to Type Definition", Box::new(GoToTypeDefinition))
                 .action("Go to Implementation", Box::new(GoToImplementation))
                 .action("Find All References", Box::new(FindAllReferences))
+                .action("Show Call Hierarchy", Box::new(ShowIncomingCalls))
+                .action("Show Type Hierarchy", Box::new(ShowSubtypes))
                 .separator()
                 .action("Rename Symbol", Box::new(Rename))
                 .action("Format Buffer", Box::new(Format))
@@ -251,6 +254,16 @@ pub fn deploy_context_menu(
                     !has_git_repo,
                     "Copy Permalink",
                     Box::new(CopyPermalinkToLine),
+                )
+                .action_disabled_when(
+                    !has_git_repo,
+                    "View File History",
+                    Box::new(git::FileHistory),
+                )
+                .action_disabled_when(
+                    !has_git_repo,
+                    "View Selection History",
+                    Box::new(git::SelectionHistory),
                 );
             match focus {
                 Some(focus) => builder.context(focus),
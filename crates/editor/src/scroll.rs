@@ -490,6 +490,9 @@ impl Editor {
                     .update_in(cx, |editor, window, cx| {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
                         editor.refresh_colors(false, None, window, cx);
+                        editor.refresh_semantic_tokens(false, None, window, cx);
+                        editor.resolve_visible_code_lenses(window, cx);
+                        editor.fetch_visible_document_ranges(cx);
                     })
                     .ok()
             })
@@ -601,6 +604,9 @@ impl Editor {
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
         self.refresh_colors(false, None, window, cx);
+        self.refresh_semantic_tokens(false, None, window, cx);
+        self.resolve_visible_code_lenses(window, cx);
+        self.fetch_visible_document_ranges(cx);
     }
 
     pub fn scroll_position(&self, cx: &mut Context<Self>) -> gpui::Point<f32> {
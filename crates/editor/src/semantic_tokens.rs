@@ -0,0 +1,213 @@
+use std::{ops::Range, sync::Arc};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::HighlightStyle;
+use itertools::Itertools as _;
+use language::{PointUtf16, Unclipped};
+use multi_buffer::Anchor;
+use settings::Settings as _;
+use text::{Bias, BufferId, OffsetRangeExt as _};
+use theme::{ActiveTheme as _, SyntaxTheme};
+use ui::{App, Context, Window};
+
+use crate::{Editor, EditorSettings};
+
+#[derive(Debug)]
+pub(super) struct SemanticTokensData {
+    buffer_highlights: HashMap<BufferId, BufferSemanticTokens>,
+    syntax_theme: Arc<SyntaxTheme>,
+    enabled: bool,
+}
+
+#[derive(Debug, Default)]
+struct BufferSemanticTokens {
+    highlights: Vec<(Range<Anchor>, HighlightStyle)>,
+    cache_version_used: Option<usize>,
+}
+
+impl SemanticTokensData {
+    pub fn new(cx: &App) -> Self {
+        Self {
+            buffer_highlights: HashMap::default(),
+            syntax_theme: cx.theme().syntax().clone(),
+            enabled: EditorSettings::get_global(cx).lsp_semantic_tokens,
+        }
+    }
+
+    /// Returns whether the tokens need to be styled again, after the setting or the
+    /// theme changed.
+    pub fn settings_updated(&mut self, cx: &App) -> bool {
+        let enabled = EditorSettings::get_global(cx).lsp_semantic_tokens;
+        let syntax_theme = cx.theme().syntax();
+        if self.enabled == enabled && Arc::ptr_eq(&self.syntax_theme, syntax_theme) {
+            return false;
+        }
+        self.enabled = enabled;
+        self.syntax_theme = syntax_theme.clone();
+        self.buffer_highlights.clear();
+        true
+    }
+}
+
+impl Editor {
+    pub(super) fn refresh_semantic_tokens(
+        &mut self,
+        ignore_cache: bool,
+        buffer_id: Option<BufferId>,
+        _: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode().is_full() {
+            return;
+        }
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        if self
+            .semantic_tokens
+            .as_ref()
+            .is_none_or(|semantic_tokens| !semantic_tokens.enabled)
+        {
+            return;
+        }
+
+        let visible_buffers = self
+            .visible_excerpts(None, cx)
+            .into_values()
+            .map(|(buffer, ..)| buffer)
+            .filter(|editor_buffer| {
+                buffer_id.is_none_or(|buffer_id| buffer_id == editor_buffer.read(cx).remote_id())
+            })
+            .unique_by(|buffer| buffer.read(cx).remote_id())
+            .collect::<Vec<_>>();
+
+        let all_tokens_task = project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            visible_buffers
+                .into_iter()
+                .filter_map(|buffer| {
+                    let buffer_id = buffer.read(cx).remote_id();
+                    let known_cache_version = self.semantic_tokens.as_ref().and_then(|tokens| {
+                        tokens.buffer_highlights.get(&buffer_id)?.cache_version_used
+                    });
+                    let tokens_task =
+                        lsp_store.semantic_tokens(buffer, ignore_cache, known_cache_version, cx)?;
+                    Some(async move { (buffer_id, tokens_task.await) })
+                })
+                .collect::<Vec<_>>()
+        });
+        cx.spawn(async move |editor, cx| {
+            let all_tokens = join_all(all_tokens_task).await;
+            if all_tokens.is_empty() {
+                return;
+            }
+            editor
+                .update(cx, |editor, cx| {
+                    let multi_buffer_snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let Some(semantic_tokens) = &mut editor.semantic_tokens else {
+                        return;
+                    };
+                    for (buffer_id, tokens) in all_tokens {
+                        let tokens = match tokens {
+                            Ok(tokens) => tokens,
+                            Err(e) => {
+                                log::error!("Failed to retrieve semantic tokens: {e}");
+                                continue;
+                            }
+                        };
+
+                        // Adjacent tokens with the same style are merged, as they would
+                        // otherwise end the highlight at the point where the next one starts.
+                        let mut styled_ranges = Vec::<(Range<PointUtf16>, HighlightStyle)>::new();
+                        for token in tokens.servers.values().flat_map(|tokens| tokens.tokens()) {
+                            let Some(style) = semantic_tokens
+                                .syntax_theme
+                                .semantic_token_style(&token.token_type, &token.modifiers)
+                            else {
+                                continue;
+                            };
+                            if let Some((last_range, last_style)) = styled_ranges.last_mut() {
+                                if last_range.end == token.range.start && *last_style == style {
+                                    last_range.end = token.range.end;
+                                    continue;
+                                }
+                            }
+                            styled_ranges.push((token.range, style));
+                        }
+
+                        let mut highlights = Vec::new();
+                        for (excerpt_id, buffer_snapshot, excerpt_range) in
+                            multi_buffer_snapshot.excerpts()
+                        {
+                            if buffer_snapshot.remote_id() != buffer_id {
+                                continue;
+                            }
+                            let excerpt_range =
+                                excerpt_range.context.to_point_utf16(buffer_snapshot);
+                            for (range, style) in &styled_ranges {
+                                if range.start < excerpt_range.start
+                                    || range.end > excerpt_range.end
+                                {
+                                    continue;
+                                }
+                                let start = buffer_snapshot.anchor_before(
+                                    buffer_snapshot
+                                        .clip_point_utf16(Unclipped(range.start), Bias::Left),
+                                );
+                                let end = buffer_snapshot.anchor_after(
+                                    buffer_snapshot
+                                        .clip_point_utf16(Unclipped(range.end), Bias::Right),
+                                );
+                                let (Some(start), Some(end)) = (
+                                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, start),
+                                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, end),
+                                ) else {
+                                    continue;
+                                };
+                                highlights.push((start..end, *style));
+                            }
+                        }
+                        semantic_tokens.buffer_highlights.insert(
+                            buffer_id,
+                            BufferSemanticTokens {
+                                highlights,
+                                cache_version_used: tokens.cache_version,
+                            },
+                        );
+                    }
+                    editor.update_semantic_token_highlights(cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    pub(super) fn update_semantic_token_highlights(&mut self, cx: &mut Context<Self>) {
+        let Some(semantic_tokens) = &mut self.semantic_tokens else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        semantic_tokens
+            .buffer_highlights
+            .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+
+        let multi_buffer_snapshot = buffer.snapshot(cx);
+        let mut ranges_by_style = HashMap::<HighlightStyle, Vec<Range<Anchor>>>::default();
+        for (range, style) in semantic_tokens
+            .buffer_highlights
+            .values()
+            .flat_map(|buffer_tokens| &buffer_tokens.highlights)
+        {
+            ranges_by_style
+                .entry(*style)
+                .or_default()
+                .push(range.clone());
+        }
+        for ranges in ranges_by_style.values_mut() {
+            ranges.sort_by(|a, b| a.start.cmp(&b.start, &multi_buffer_snapshot));
+        }
+        self.display_map
+            .update(cx, |map, _| map.highlight_semantic_tokens(ranges_by_style));
+        cx.notify();
+    }
+}
@@ -5,8 +5,11 @@ use futures::future::{self, BoxFuture};
 use git::{
     blame::Blame,
     repository::{
-        AskPassDelegate, Branch, CommitDetails, CommitOptions, FetchOptions, GitRepository,
-        GitRepositoryCheckpoint, PushOptions, Remote, RepoPath, ResetMode,
+        AskPassDelegate, Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions,
+        ConflictVersions, FetchOptions, FileHistoryEntry, GitRepository, GitRepositoryCheckpoint,
+        GitWorktree, LogEntry, LogOptions, PushOptions, RebaseCommand, RebaseStatus,
+        RebaseTodoEntry, Remote, RepoPath, ResetMode, SequencerOperation, SequencerState,
+        StashEntry, StashScope, Tag, WORK_DIRECTORY_REPO_PATH,
     },
     status::{FileStatus, GitStatus, StatusCode, TrackedStatus, UnmergedStatus},
 };
@@ -14,7 +17,7 @@ use gpui::{AsyncApp, BackgroundExecutor};
 use ignore::gitignore::GitignoreBuilder;
 use rope::Rope;
 use smol::future::FutureExt as _;
-use std::{path::PathBuf, sync::Arc};
+use std::{ops::Range, path::PathBuf, sync::Arc};
 
 #[derive(Clone)]
 pub struct FakeGitRepository {
@@ -36,6 +39,14 @@ pub struct FakeGitRepositoryState {
     pub branches: HashSet<String>,
     pub simulated_index_write_error_message: Option<String>,
     pub refs: HashMap<String, String>,
+    pub stash_entries: Vec<FakeStashEntry>,
+}
+
+/// An entry of the fake repository's stash, with the changes that were stashed.
+#[derive(Debug, Clone)]
+pub struct FakeStashEntry {
+    pub entry: StashEntry,
+    pub files: Vec<CommitFile>,
 }
 
 impl FakeGitRepositoryState {
@@ -50,11 +61,24 @@ impl FakeGitRepositoryState {
             branches: Default::default(),
             simulated_index_write_error_message: Default::default(),
             refs: HashMap::from_iter([("HEAD".into(), "abc".into())]),
+            stash_entries: Default::default(),
         }
     }
 }
 
 impl FakeGitRepository {
+    fn remove_stash_entry(state: &mut FakeGitRepositoryState, index: usize) -> Result<()> {
+        anyhow::ensure!(
+            index < state.stash_entries.len(),
+            "stash@{{{index}}} is not a valid reference"
+        );
+        state.stash_entries.remove(index);
+        for (index, stash_entry) in state.stash_entries.iter_mut().enumerate() {
+            stash_entry.entry.index = index;
+        }
+        Ok(())
+    }
+
     fn with_state_async<F, T>(&self, write: bool, f: F) -> BoxFuture<'static, Result<T>>
     where
         F: 'static + Send + FnOnce(&mut FakeGitRepositoryState) -> Result<T>,
@@ -104,6 +128,10 @@ impl GitRepository for FakeGitRepository {
         .boxed()
     }
 
+    fn load_conflict_versions(&self, _path: RepoPath) -> BoxFuture<'_, Result<ConflictVersions>> {
+        unimplemented!()
+    }
+
     fn load_commit(
         &self,
         _commit: String,
@@ -112,6 +140,95 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn log(&self, _options: LogOptions) -> BoxFuture<'_, Result<Vec<LogEntry>>> {
+        unimplemented!()
+    }
+
+    fn file_history(
+        &self,
+        _path: RepoPath,
+        _rows: Option<Range<u32>>,
+        _limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<FileHistoryEntry>>> {
+        unimplemented!()
+    }
+
+    fn rebase_todo(&self, _onto: String) -> BoxFuture<'_, Result<Vec<RebaseTodoEntry>>> {
+        unimplemented!()
+    }
+
+    fn rebase_start(
+        &self,
+        _onto: String,
+        _todo: Vec<RebaseTodoEntry>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn rebase_command(
+        &self,
+        _command: RebaseCommand,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn rebase_status(&self) -> BoxFuture<'_, Result<Option<RebaseStatus>>> {
+        async { Ok(None) }.boxed()
+    }
+
+    fn sequencer_state(&self) -> BoxFuture<'_, Option<SequencerState>> {
+        async { None }.boxed()
+    }
+
+    fn cherry_pick(
+        &self,
+        _commits: Vec<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn revert(
+        &self,
+        _commits: Vec<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn sequencer_command(
+        &self,
+        _operation: SequencerOperation,
+        _command: RebaseCommand,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn tags(&self) -> BoxFuture<'_, Result<Vec<Tag>>> {
+        unimplemented!()
+    }
+
+    fn create_tag(
+        &self,
+        _name: String,
+        _commit: String,
+        _message: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn delete_tag(
+        &self,
+        _name: String,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -179,6 +296,50 @@ impl GitRepository for FakeGitRepository {
         self.common_dir_path.clone()
     }
 
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>> {
+        let path = self
+            .dot_git_path
+            .parent()
+            .unwrap_or(&self.dot_git_path)
+            .to_path_buf();
+        self.with_state_async(false, move |state| {
+            Ok(vec![GitWorktree {
+                path,
+                head_sha: state.refs.get("HEAD").map(|sha| sha.clone().into()),
+                branch: state
+                    .current_branch_name
+                    .as_ref()
+                    .map(|name| format!("refs/heads/{name}").into()),
+                is_main: true,
+                is_locked: false,
+                is_prunable: false,
+            }])
+        })
+    }
+
+    fn create_worktree(
+        &self,
+        _path: PathBuf,
+        _commit: String,
+        _new_branch: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn remove_worktree(
+        &self,
+        _path: PathBuf,
+        _force: bool,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
+    fn prune_worktrees(&self, _env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>> {
+        unimplemented!()
+    }
+
     fn merge_message(&self) -> BoxFuture<'_, Option<String>> {
         async move { None }.boxed()
     }
@@ -392,6 +553,17 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn push_tag(
+        &self,
+        _name: String,
+        _remote: String,
+        _askpass: AskPassDelegate,
+        _env: Arc<HashMap<String, String>>,
+        _cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<git::repository::RemoteCommandOutput>> {
+        unimplemented!()
+    }
+
     fn pull(
         &self,
         _branch: String,
@@ -425,6 +597,131 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let workdir_path = self.dot_git_path.parent().unwrap().to_path_buf();
+        async move {
+            let status = self
+                .status(std::slice::from_ref(&WORK_DIRECTORY_REPO_PATH))
+                .await?;
+            let changed_files = status
+                .entries
+                .iter()
+                .filter(|(path, _)| match &scope {
+                    StashScope::All | StashScope::Staged => true,
+                    StashScope::Paths(paths) => paths.iter().any(|prefix| path.starts_with(prefix)),
+                })
+                .map(|(path, _)| {
+                    let working_copy_text = self
+                        .fs
+                        .read_file_sync(workdir_path.join(path))
+                        .ok()
+                        .and_then(|content| String::from_utf8(content).ok());
+                    (path.clone(), working_copy_text)
+                })
+                .collect::<Vec<_>>();
+            self.with_state_async(true, move |state| {
+                let files = changed_files
+                    .into_iter()
+                    .filter_map(|(path, working_copy_text)| {
+                        let old_text = state.head_contents.get(&path).cloned();
+                        let new_text = match &scope {
+                            StashScope::Staged => state.index_contents.get(&path).cloned(),
+                            StashScope::All | StashScope::Paths(_) => working_copy_text,
+                        };
+                        (old_text != new_text).then(|| CommitFile {
+                            path,
+                            old_text,
+                            new_text,
+                        })
+                    })
+                    .collect();
+                let branch = state
+                    .current_branch_name
+                    .as_deref()
+                    .unwrap_or("(no branch)");
+                let message = match message {
+                    Some(message) => format!("On {branch}: {message}"),
+                    None => format!("WIP on {branch}"),
+                };
+                state.stash_entries.insert(
+                    0,
+                    FakeStashEntry {
+                        entry: StashEntry {
+                            index: 0,
+                            sha: format!("stash-{}", state.stash_entries.len()).into(),
+                            message: message.into(),
+                            commit_timestamp: 0,
+                        },
+                        files,
+                    },
+                );
+                for (index, stash_entry) in state.stash_entries.iter_mut().enumerate() {
+                    stash_entry.entry.index = index;
+                }
+                Ok(())
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn stash_list(&self) -> BoxFuture<'_, Result<Vec<StashEntry>>> {
+        self.with_state_async(false, |state| {
+            Ok(state
+                .stash_entries
+                .iter()
+                .map(|stash_entry| stash_entry.entry.clone())
+                .collect())
+        })
+    }
+
+    fn stash_diff(&self, index: usize) -> BoxFuture<'_, Result<CommitDiff>> {
+        self.with_state_async(false, move |state| {
+            let stash_entry = state
+                .stash_entries
+                .get(index)
+                .with_context(|| format!("stash@{{{index}}} is not a valid reference"))?;
+            Ok(CommitDiff {
+                files: stash_entry.files.clone(),
+            })
+        })
+    }
+
+    fn stash_apply(
+        &self,
+        index: usize,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(false, move |state| {
+            anyhow::ensure!(
+                index < state.stash_entries.len(),
+                "stash@{{{index}}} is not a valid reference"
+            );
+            Ok(())
+        })
+    }
+
+    fn stash_pop(
+        &self,
+        index: usize,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| Self::remove_stash_entry(state, index))
+    }
+
+    fn stash_drop(
+        &self,
+        index: usize,
+        _env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.with_state_async(true, move |state| Self::remove_stash_entry(state, index))
+    }
+
     fn checkpoint(&self) -> BoxFuture<'static, Result<GitRepositoryCheckpoint>> {
         unimplemented!()
     }
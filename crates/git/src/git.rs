@@ -48,6 +48,10 @@ actions!(
         StageFile,
         /// Unstages the current file.
         UnstageFile,
+        /// Stashes the changes in the current file.
+        StashFile,
+        /// Opens the three-way merge editor for the current conflicted file.
+        OpenMergeEditor,
         // repo-wide
         /// Stages all changes in the repository.
         StageAll,
@@ -85,6 +89,42 @@ actions!(
         Init,
         /// Opens all modified files in the editor.
         OpenModifiedFiles,
+        /// Stashes all changes, including untracked files.
+        StashAll,
+        /// Stashes only the staged changes.
+        StashStaged,
+        /// Applies the most recent stash entry and removes it.
+        StashPop,
+        /// Applies the most recent stash entry, keeping it in the stash.
+        StashApply,
+        /// Shows the list of stash entries.
+        ViewStash,
+        /// Opens the commit history of the repository.
+        ViewHistory,
+        /// Starts an interactive rebase of the current branch onto another branch.
+        Rebase,
+        /// Continues the rebase in progress, once its conflicts are resolved.
+        ContinueRebase,
+        /// Skips the commit that the rebase in progress stopped at.
+        SkipRebase,
+        /// Aborts the rebase in progress, returning to the state before it started.
+        AbortRebase,
+        /// Shows the commits that changed the active file.
+        FileHistory,
+        /// Shows the commits that changed the selected lines of the active file.
+        SelectionHistory,
+        /// Shows the worktrees of the repository, and creates new ones from branches.
+        ViewWorktrees,
+        /// Cleans up the worktrees whose directories were deleted.
+        PruneWorktrees,
+        /// Continues the cherry-pick in progress, once its conflicts are resolved.
+        ContinueCherryPick,
+        /// Aborts the cherry-pick in progress, returning to the state before it started.
+        AbortCherryPick,
+        /// Continues the revert in progress, once its conflicts are resolved.
+        ContinueRevert,
+        /// Aborts the revert in progress, returning to the state before it started.
+        AbortRevert,
     ]
 );
 
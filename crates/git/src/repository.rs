@@ -13,6 +13,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use std::borrow::{Borrow, Cow};
 use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
 use std::io::prelude::*;
 use std::path::Component;
 use std::process::{ExitStatus, Stdio};
@@ -21,6 +22,7 @@ use std::{
     cmp::Ordering,
     future,
     io::{BufRead, BufReader, BufWriter, Read},
+    ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -161,12 +163,12 @@ pub struct CommitDetails {
     pub author_name: SharedString,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CommitDiff {
     pub files: Vec<CommitFile>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CommitFile {
     pub path: RepoPath,
     pub old_text: Option<String>,
@@ -184,6 +186,228 @@ pub struct Remote {
     pub name: SharedString,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The entry's position in the stash, as in `stash@{index}`, where 0 is the most recent.
+    pub index: usize,
+    pub sha: SharedString,
+    pub message: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+}
+
+impl StashEntry {
+    pub fn reference(&self) -> String {
+        stash_reference(self.index)
+    }
+}
+
+fn stash_reference(index: usize) -> String {
+    format!("stash@{{{index}}}")
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum StashScope {
+    /// All changes, including untracked files.
+    All,
+    /// Only the staged changes.
+    Staged,
+    /// The changes to the given paths, including untracked ones.
+    Paths(Vec<RepoPath>),
+}
+
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct LogOptions {
+    /// The number of commits to skip, for loading the history one page at a time.
+    pub skip: usize,
+    pub limit: usize,
+    /// Include the commits reachable from any branch or tag, not just from `HEAD`.
+    pub all_branches: bool,
+    /// Only include commits that touch this path.
+    pub path: Option<RepoPath>,
+    /// Only include commits whose author name or email contains this text.
+    pub author: Option<String>,
+    /// Only include commits whose message contains this text.
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LogEntry {
+    pub sha: SharedString,
+    pub parents: Vec<SharedString>,
+    pub subject: SharedString,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+    /// The branches and tags pointing at this commit, e.g. `HEAD -> main` or `tag: v1.0`.
+    pub refs: Vec<SharedString>,
+}
+
+impl LogEntry {
+    pub fn to_commit_summary(&self) -> CommitSummary {
+        CommitSummary {
+            sha: self.sha.clone(),
+            subject: self.subject.clone(),
+            commit_timestamp: self.commit_timestamp,
+            has_parent: !self.parents.is_empty(),
+        }
+    }
+}
+
+/// A commit that changed a file, or a range of lines in it.
+#[derive(Debug)]
+pub struct FileHistoryEntry {
+    pub commit: LogEntry,
+    /// The file before and after the commit, at the path it had in that commit.
+    pub file: CommitFile,
+    /// The rows of the file after the commit that were changed, or that the traced
+    /// range of lines covered.
+    pub rows: Vec<Range<u32>>,
+}
+
+/// The versions of a conflicted file that are recorded in the index during a merge.
+///
+/// Each version is `None` if that side doesn't have the file, or if it isn't text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConflictVersions {
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+/// A working tree of the repository, as listed by `git worktree list`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitWorktree {
+    pub path: PathBuf,
+    /// `None` for a bare repository, or a worktree whose branch has no commits yet.
+    pub head_sha: Option<SharedString>,
+    /// The full name of the checked out branch, e.g. `refs/heads/main`, or `None` if HEAD
+    /// is detached.
+    pub branch: Option<SharedString>,
+    /// Whether this is the repository's main worktree, rather than a linked one.
+    pub is_main: bool,
+    pub is_locked: bool,
+    /// Whether git considers this worktree stale, e.g. because its directory was deleted.
+    pub is_prunable: bool,
+}
+
+impl GitWorktree {
+    pub fn branch_name(&self) -> Option<&str> {
+        self.branch
+            .as_ref()
+            .map(|branch| branch.strip_prefix("refs/heads/").unwrap_or(branch))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [RebaseAction; 6] = [
+        RebaseAction::Pick,
+        RebaseAction::Reword,
+        RebaseAction::Edit,
+        RebaseAction::Squash,
+        RebaseAction::Fixup,
+        RebaseAction::Drop,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Edit => "edit",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+
+    /// Whether this action folds the commit into the one before it.
+    pub fn is_fold(&self) -> bool {
+        matches!(self, RebaseAction::Squash | RebaseAction::Fixup)
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub action: RebaseAction,
+    pub sha: SharedString,
+    pub subject: SharedString,
+    /// The new message of a reworded commit. When `None`, the message is kept.
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum RebaseCommand {
+    Continue,
+    Skip,
+    Abort,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum SequencerOperation {
+    CherryPick,
+    Revert,
+}
+
+impl SequencerOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SequencerOperation::CherryPick => "cherry-pick",
+            SequencerOperation::Revert => "revert",
+        }
+    }
+
+    /// The file in the git directory that records the commit being applied.
+    fn head_file(&self) -> &'static str {
+        match self {
+            SequencerOperation::CherryPick => "CHERRY_PICK_HEAD",
+            SequencerOperation::Revert => "REVERT_HEAD",
+        }
+    }
+}
+
+/// A cherry-pick or revert that stopped before it was done, usually because of conflicts.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SequencerState {
+    pub operation: SequencerOperation,
+    /// The commit that was being applied when the operation stopped.
+    pub sha: SharedString,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Tag {
+    pub name: SharedString,
+    /// The commit that the tag points to.
+    pub sha: SharedString,
+    /// The subject of an annotated tag's message, or `None` for a lightweight tag.
+    pub message: Option<SharedString>,
+    /// When an annotated tag was created, or a lightweight tag's commit was made, as a unix
+    /// timestamp.
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct RebaseStatus {
+    /// The branch being rebased, or `None` when rebasing a detached `HEAD`.
+    pub head_name: Option<SharedString>,
+    pub onto: SharedString,
+    /// The number of the step being applied, starting at 1.
+    pub step: usize,
+    pub total_steps: usize,
+    /// The commit that the rebase stopped at, because of a conflict or an `edit` step.
+    pub stopped_at: Option<SharedString>,
+}
+
 pub enum ResetMode {
     /// Reset the branch pointer, leave index and worktree unchanged (this will make it look like things that were
     /// committed are now staged).
@@ -310,6 +534,9 @@ pub trait GitRepository: Send + Sync {
     /// Also returns `None` for symlinks.
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>>;
 
+    /// Returns the base, ours and theirs versions of a conflicted file from the index.
+    fn load_conflict_versions(&self, path: RepoPath) -> BoxFuture<'_, Result<ConflictVersions>>;
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -361,6 +588,89 @@ pub trait GitRepository: Send + Sync {
     fn show(&self, commit: String) -> BoxFuture<'_, Result<CommitDetails>>;
 
     fn load_commit(&self, commit: String, cx: AsyncApp) -> BoxFuture<'_, Result<CommitDiff>>;
+
+    /// Returns a page of the commit history, newest first, with parents listed before their children.
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<LogEntry>>>;
+
+    /// Returns the commits that changed the file at `path`, newest first, following renames.
+    /// When `rows` is given, only the commits that changed those rows of the file are returned.
+    fn file_history(
+        &self,
+        path: RepoPath,
+        rows: Option<Range<u32>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<FileHistoryEntry>>>;
+
+    /// Returns the commits that an interactive rebase onto `onto` would apply, oldest first.
+    fn rebase_todo(&self, onto: String) -> BoxFuture<'_, Result<Vec<RebaseTodoEntry>>>;
+
+    /// Starts an interactive rebase onto `onto` that applies the given steps.
+    ///
+    /// Stopping at a conflict is not an error: the conflicted paths show up in the
+    /// repository's status, and the rebase can be resumed with [`RebaseCommand::Continue`].
+    fn rebase_start(
+        &self,
+        onto: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Continues, skips the current step of, or aborts the rebase in progress.
+    fn rebase_command(
+        &self,
+        command: RebaseCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Returns the progress of the rebase in progress, if any.
+    fn rebase_status(&self) -> BoxFuture<'_, Result<Option<RebaseStatus>>>;
+
+    /// Returns the cherry-pick or revert that stopped before it was done, if any.
+    fn sequencer_state(&self) -> BoxFuture<'_, Option<SequencerState>>;
+
+    /// Applies the changes of the given commits on top of `HEAD`, committing each of them.
+    ///
+    /// When a commit doesn't apply cleanly, the cherry-pick stops with the conflicts in the
+    /// working tree, and can be continued with `sequencer_command` once they are resolved.
+    fn cherry_pick(
+        &self,
+        commits: Vec<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Commits the inverse of the changes of the given commits. Like a cherry-pick, this
+    /// stops when a commit can't be reverted cleanly.
+    fn revert(
+        &self,
+        commits: Vec<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    fn sequencer_command(
+        &self,
+        operation: SequencerOperation,
+        command: RebaseCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Returns the tags of the repository, most recent first.
+    fn tags(&self) -> BoxFuture<'_, Result<Vec<Tag>>>;
+
+    /// Creates a tag pointing at the given commit. The tag is annotated if it has a message.
+    fn create_tag(
+        &self,
+        name: String,
+        commit: String,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    fn delete_tag(
+        &self,
+        name: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
     fn blame(&self, path: RepoPath, content: Rope) -> BoxFuture<'_, Result<crate::blame::Blame>>;
 
     /// Returns the absolute path to the repository. For worktrees, this will be the path to the
@@ -369,6 +679,31 @@ pub trait GitRepository: Send + Sync {
 
     fn main_repository_path(&self) -> PathBuf;
 
+    /// Returns the main worktree of the repository, followed by its linked worktrees.
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>>;
+
+    /// Adds a linked worktree at the given path that checks out `commit`, or a new branch
+    /// starting at it.
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        commit: String,
+        new_branch: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Removes a linked worktree. Unless `force` is set, this fails if the worktree has
+    /// uncommitted changes.
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        force: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Cleans up the administrative files of worktrees whose directories no longer exist.
+    fn prune_worktrees(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>>;
+
     /// Updates the index to match the worktree at the given paths.
     ///
     /// If any of the paths have been deleted from the worktree, they will be removed from the index if found there.
@@ -406,6 +741,17 @@ pub trait GitRepository: Send + Sync {
         cx: AsyncApp,
     ) -> BoxFuture<'_, Result<RemoteCommandOutput>>;
 
+    fn push_tag(
+        &self,
+        name: String,
+        remote_name: String,
+        askpass: AskPassDelegate,
+        env: Arc<HashMap<String, String>>,
+        // This method takes an AsyncApp to ensure it's invoked on the main thread,
+        // otherwise git-credentials-manager won't work.
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<RemoteCommandOutput>>;
+
     fn pull(
         &self,
         branch_name: String,
@@ -435,6 +781,41 @@ pub trait GitRepository: Send + Sync {
     /// Run git diff
     fn diff(&self, diff: DiffType) -> BoxFuture<'_, Result<String>>;
 
+    /// Saves the changes in the given scope as a new stash entry and reverts them.
+    fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Returns the stash entries, most recent first.
+    fn stash_list(&self) -> BoxFuture<'_, Result<Vec<StashEntry>>>;
+
+    /// Loads the changes saved in a stash entry, including untracked files.
+    fn stash_diff(&self, index: usize) -> BoxFuture<'_, Result<CommitDiff>>;
+
+    /// Applies the changes saved in a stash entry, keeping the entry.
+    fn stash_apply(
+        &self,
+        index: usize,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Applies the changes saved in a stash entry and removes the entry if they applied cleanly.
+    fn stash_pop(
+        &self,
+        index: usize,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Removes a stash entry without applying it.
+    fn stash_drop(
+        &self,
+        index: usize,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>>;
+
     /// Creates a checkpoint for the repository.
     fn checkpoint(&self) -> BoxFuture<'static, Result<GitRepositoryCheckpoint>>;
 
@@ -501,6 +882,57 @@ impl RealGitRepository {
             .context("failed to read git work directory")
             .map(Path::to_path_buf)
     }
+
+    /// Runs a git subcommand in the working directory, failing with its error output.
+    fn run_git_subcommand(
+        &self,
+        subcommand: &'static str,
+        args: Vec<OsString>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .arg(subcommand)
+                    .args(args)
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to run git {subcommand}:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                Ok(())
+            })
+            .boxed()
+    }
+
+    fn run_sequencer(
+        &self,
+        operation: SequencerOperation,
+        args: Vec<OsString>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .env("GIT_EDITOR", "true")
+                    .arg(operation.as_str())
+                    .args(args)
+                    .output()
+                    .await?;
+                check_sequencer_output(operation, output)
+            })
+            .boxed()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -569,6 +1001,59 @@ impl GitRepository for RealGitRepository {
         repo.commondir().into()
     }
 
+    fn worktrees(&self) -> BoxFuture<'_, Result<Vec<GitWorktree>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args(["--no-optional-locks", "worktree", "list", "--porcelain"])
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to list worktrees:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                parse_worktree_list(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn create_worktree(
+        &self,
+        path: PathBuf,
+        commit: String,
+        new_branch: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let mut args = vec![OsString::from("add"), "--quiet".into()];
+        if let Some(new_branch) = new_branch {
+            args.extend(["-b".into(), new_branch.into()]);
+        }
+        args.extend(["--".into(), path.into(), commit.into()]);
+        self.run_git_subcommand("worktree", args, env)
+    }
+
+    fn remove_worktree(
+        &self,
+        path: PathBuf,
+        force: bool,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let mut args = vec![OsString::from("remove")];
+        if force {
+            args.push("--force".into());
+        }
+        args.extend(["--".into(), path.into()]);
+        self.run_git_subcommand("worktree", args, env)
+    }
+
+    fn prune_worktrees(&self, env: Arc<HashMap<String, String>>) -> BoxFuture<'_, Result<()>> {
+        self.run_git_subcommand("worktree", vec!["prune".into()], env)
+    }
+
     fn show(&self, commit: String) -> BoxFuture<'_, Result<CommitDetails>> {
         let working_directory = self.working_directory();
         self.executor
@@ -631,76 +1116,20 @@ impl GitRepository for RealGitRepository {
             let show_stdout = String::from_utf8_lossy(&show_output.stdout);
             let mut lines = show_stdout.split('\n');
             let parent_sha = lines.next().unwrap().trim().trim_end_matches('\0');
-            let changes = parse_git_diff_name_status(lines.next().unwrap_or(""));
-
-            let mut cat_file_process = util::command::new_std_command("git")
-                .current_dir(&working_directory)
-                .args(["--no-optional-locks", "cat-file", "--batch=%(objectsize)"])
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .context("starting git cat-file process")?;
-
-            use std::io::Write as _;
-            let mut files = Vec::<CommitFile>::new();
-            let mut stdin = BufWriter::with_capacity(512, cat_file_process.stdin.take().unwrap());
-            let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
-            let mut info_line = String::new();
-            let mut newline = [b'\0'];
-            for (path, status_code) in changes {
-                match status_code {
-                    StatusCode::Modified => {
-                        writeln!(&mut stdin, "{commit}:{}", path.display())?;
-                        writeln!(&mut stdin, "{parent_sha}:{}", path.display())?;
-                    }
-                    StatusCode::Added => {
-                        writeln!(&mut stdin, "{commit}:{}", path.display())?;
-                    }
-                    StatusCode::Deleted => {
-                        writeln!(&mut stdin, "{parent_sha}:{}", path.display())?;
-                    }
-                    _ => continue,
-                }
-                stdin.flush()?;
-
-                info_line.clear();
-                stdout.read_line(&mut info_line)?;
-
-                let len = info_line.trim_end().parse().with_context(|| {
-                    format!("invalid object size output from cat-file {info_line}")
-                })?;
-                let mut text = vec![0; len];
-                stdout.read_exact(&mut text)?;
-                stdout.read_exact(&mut newline)?;
-                let text = String::from_utf8_lossy(&text).to_string();
-
-                let mut old_text = None;
-                let mut new_text = None;
-                match status_code {
-                    StatusCode::Modified => {
-                        info_line.clear();
-                        stdout.read_line(&mut info_line)?;
-                        let len = info_line.trim_end().parse().with_context(|| {
-                            format!("invalid object size output from cat-file {}", info_line)
-                        })?;
-                        let mut parent_text = vec![0; len];
-                        stdout.read_exact(&mut parent_text)?;
-                        stdout.read_exact(&mut newline)?;
-                        old_text = Some(String::from_utf8_lossy(&parent_text).to_string());
-                        new_text = Some(text);
-                    }
-                    StatusCode::Added => new_text = Some(text),
-                    StatusCode::Deleted => old_text = Some(text),
-                    _ => continue,
-                }
-
-                files.push(CommitFile {
-                    path: path.into(),
-                    old_text,
-                    new_text,
-                })
-            }
+            let changes = parse_git_diff_name_status(lines.next().unwrap_or("")).filter_map(
+                |(path, status_code)| {
+                    let new_object = format!("{commit}:{}", path.display());
+                    let old_object = format!("{parent_sha}:{}", path.display());
+                    let (old_object, new_object) = match status_code {
+                        StatusCode::Modified => (Some(old_object), Some(new_object)),
+                        StatusCode::Added => (None, Some(new_object)),
+                        StatusCode::Deleted => (Some(old_object), None),
+                        _ => return None,
+                    };
+                    Some((path.into(), old_object, new_object))
+                },
+            );
+            let files = read_file_versions(&working_directory, changes)?;
 
             Ok(CommitDiff { files })
         })
@@ -800,9 +1229,310 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
-    fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
-        let repo = self.repository.clone();
-        self.executor
+    fn log(&self, options: LogOptions) -> BoxFuture<'_, Result<Vec<LogEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args(git_log_args(&options))
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to load the commit history:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                parse_log(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn file_history(
+        &self,
+        path: RepoPath,
+        rows: Option<Range<u32>>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<FileHistoryEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .args(git_file_history_args(&path, rows, limit))
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to load the file history:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+
+                let changes = parse_file_history(&String::from_utf8_lossy(&output.stdout))?;
+                let files = read_file_versions(
+                    &working_directory,
+                    changes.iter().map(|change| {
+                        let old_object = change
+                            .old_path
+                            .as_ref()
+                            .zip(change.commit.parents.first())
+                            .map(|(path, parent)| format!("{parent}:{}", path.display()));
+                        let new_object = change
+                            .new_path
+                            .as_ref()
+                            .map(|path| format!("{}:{}", change.commit.sha, path.display()));
+                        (change.path.clone(), old_object, new_object)
+                    }),
+                )?;
+                Ok(changes
+                    .into_iter()
+                    .zip(files)
+                    .map(|(change, file)| FileHistoryEntry {
+                        commit: change.commit,
+                        file,
+                        rows: change.rows,
+                    })
+                    .collect())
+            })
+            .boxed()
+    }
+
+    fn rebase_todo(&self, onto: String) -> BoxFuture<'_, Result<Vec<RebaseTodoEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                // Lists the same commits as the todo list that `git rebase -i` would generate.
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "log",
+                        "--reverse",
+                        "--no-merges",
+                        "--right-only",
+                        "--cherry-pick",
+                        "--format=%H%x00%s",
+                        "--end-of-options",
+                        &format!("{onto}...HEAD"),
+                        "--",
+                    ])
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to list the commits to rebase:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|line| {
+                        let (sha, subject) = line
+                            .split_once('\0')
+                            .with_context(|| format!("unexpected git log output: {line:?}"))?;
+                        Ok(RebaseTodoEntry {
+                            action: RebaseAction::Pick,
+                            sha: sha.to_string().into(),
+                            subject: subject.to_string().into(),
+                            message: None,
+                        })
+                    })
+                    .collect()
+            })
+            .boxed()
+    }
+
+    fn rebase_start(
+        &self,
+        onto: String,
+        todo: Vec<RebaseTodoEntry>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        let todo_path = self.path().join("ZED_REBASE_TODO");
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                // Only an object id is passed on, so that `onto` can't be taken for an option.
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .args(["rev-parse", "--verify", "--end-of-options"])
+                    .arg(format!("{onto}^{{commit}}"))
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to resolve {onto:?}:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                let onto = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                smol::fs::write(&todo_path, rebase_todo_script(&todo)).await?;
+                // Git runs the sequence editor with the path of its own todo list, which we
+                // replace with ours. Squashed messages are accepted without opening an editor.
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory)
+                    .envs(env.iter())
+                    .env(
+                        "GIT_SEQUENCE_EDITOR",
+                        format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+                    )
+                    .env("GIT_EDITOR", "true")
+                    .args(["rebase", "--interactive", &onto])
+                    .output()
+                    .await;
+                smol::fs::remove_file(&todo_path).await.log_err();
+                check_rebase_output(output?)
+            })
+            .boxed()
+    }
+
+    fn rebase_command(
+        &self,
+        command: RebaseCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let arg = match command {
+                    RebaseCommand::Continue => "--continue",
+                    RebaseCommand::Skip => "--skip",
+                    RebaseCommand::Abort => "--abort",
+                };
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .envs(env.iter())
+                    .env("GIT_EDITOR", "true")
+                    .args(["rebase", arg])
+                    .output()
+                    .await?;
+                check_rebase_output(output)
+            })
+            .boxed()
+    }
+
+    fn rebase_status(&self) -> BoxFuture<'_, Result<Option<RebaseStatus>>> {
+        let git_dir = self.path();
+        self.executor
+            .spawn(async move { read_rebase_status(&git_dir).await })
+            .boxed()
+    }
+
+    fn sequencer_state(&self) -> BoxFuture<'_, Option<SequencerState>> {
+        let git_dir = self.path();
+        self.executor
+            .spawn(async move {
+                [SequencerOperation::CherryPick, SequencerOperation::Revert]
+                    .into_iter()
+                    .find_map(|operation| {
+                        let sha = std::fs::read_to_string(git_dir.join(operation.head_file()));
+                        Some(SequencerState {
+                            operation,
+                            sha: sha.ok()?.trim().to_string().into(),
+                        })
+                    })
+            })
+            .boxed()
+    }
+
+    fn cherry_pick(
+        &self,
+        commits: Vec<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let args = commits.into_iter().map(OsString::from).collect();
+        self.run_sequencer(SequencerOperation::CherryPick, args, env)
+    }
+
+    fn revert(
+        &self,
+        commits: Vec<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let mut args = vec![OsString::from("--no-edit")];
+        args.extend(commits.into_iter().map(OsString::from));
+        self.run_sequencer(SequencerOperation::Revert, args, env)
+    }
+
+    fn sequencer_command(
+        &self,
+        operation: SequencerOperation,
+        command: RebaseCommand,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let arg = match command {
+            RebaseCommand::Continue => "--continue",
+            RebaseCommand::Skip => "--skip",
+            RebaseCommand::Abort => "--abort",
+        };
+        self.run_sequencer(operation, vec![arg.into()], env)
+    }
+
+    fn tags(&self) -> BoxFuture<'_, Result<Vec<Tag>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "for-each-ref",
+                        "--sort=-creatordate",
+                        concat!(
+                            "--format=%(refname:strip=2)%00%(objectname)%00%(*objectname)",
+                            "%00%(creatordate:unix)%00%(contents:subject)",
+                        ),
+                        "refs/tags",
+                    ])
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to list tags:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                parse_tags(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn create_tag(
+        &self,
+        name: String,
+        commit: String,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let mut args = Vec::<OsString>::new();
+        if let Some(message) = message {
+            args.extend(["--annotate".into(), "--message".into(), message.into()]);
+        }
+        // The name comes after `--`, so that it can't be taken for an option.
+        args.extend(["--".into(), name.into(), commit.into()]);
+        self.run_git_subcommand("tag", args, env)
+    }
+
+    fn delete_tag(
+        &self,
+        name: String,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.run_git_subcommand(
+            "tag",
+            vec!["--delete".into(), "--".into(), name.into()],
+            env,
+        )
+    }
+
+    fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>> {
+        let repo = self.repository.clone();
+        self.executor
             .spawn(async move {
                 let repo = repo.lock();
                 let head = repo.head().ok()?.peel_to_tree().log_err()?;
@@ -816,6 +1546,36 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn load_conflict_versions(&self, path: RepoPath) -> BoxFuture<'_, Result<ConflictVersions>> {
+        let repo = self.repository.clone();
+        self.executor
+            .spawn(async move {
+                // This check is required because index.get_path() unwraps internally :(
+                check_path_to_repo_path_errors(&path)?;
+
+                let repo = repo.lock();
+                let mut index = repo.index()?;
+                index.read(false)?;
+
+                const STAGE_BASE: i32 = 1;
+                const STAGE_OURS: i32 = 2;
+                const STAGE_THEIRS: i32 = 3;
+                let load_stage = |stage| -> Result<Option<String>> {
+                    let Some(entry) = index.get_path(&path, stage) else {
+                        return Ok(None);
+                    };
+                    let content = repo.find_blob(entry.id)?.content().to_owned();
+                    Ok(String::from_utf8(content).ok())
+                };
+                Ok(ConflictVersions {
+                    base: load_stage(STAGE_BASE)?,
+                    ours: load_stage(STAGE_OURS)?,
+                    theirs: load_stage(STAGE_THEIRS)?,
+                })
+            })
+            .boxed()
+    }
+
     fn set_index_text(
         &self,
         path: RepoPath,
@@ -1130,6 +1890,163 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn stash_push(
+        &self,
+        scope: StashScope,
+        message: Option<String>,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        let mut args = vec![OsString::from("push"), "--quiet".into()];
+        if let Some(message) = message {
+            args.extend(["--message".into(), message.into()]);
+        }
+        match scope {
+            StashScope::All => args.push("--include-untracked".into()),
+            StashScope::Staged => args.push("--staged".into()),
+            StashScope::Paths(paths) => {
+                args.extend(["--include-untracked".into(), "--".into()]);
+                args.extend(paths.iter().map(|path| path.to_unix_style().into_owned()));
+            }
+        }
+        self.run_git_subcommand("stash", args, env)
+    }
+
+    fn stash_list(&self) -> BoxFuture<'_, Result<Vec<StashEntry>>> {
+        let working_directory = self.working_directory();
+        let git_binary_path = self.git_binary_path.clone();
+        self.executor
+            .spawn(async move {
+                let output = new_smol_command(&git_binary_path)
+                    .current_dir(&working_directory?)
+                    .args([
+                        "--no-optional-locks",
+                        "stash",
+                        "list",
+                        "--format=%H%x00%gs%x00%ct",
+                    ])
+                    .output()
+                    .await?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to list stash entries:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                parse_stash_list(&String::from_utf8_lossy(&output.stdout))
+            })
+            .boxed()
+    }
+
+    fn stash_diff(&self, index: usize) -> BoxFuture<'_, Result<CommitDiff>> {
+        let working_directory = self.working_directory();
+        self.executor
+            .spawn(async move {
+                let working_directory = working_directory?;
+                let stash = stash_reference(index);
+                let git = |args: &[&str]| {
+                    new_std_command("git")
+                        .current_dir(&working_directory)
+                        .arg("--no-optional-locks")
+                        .args(args)
+                        .stdin(Stdio::null())
+                        .output()
+                };
+
+                // A stash entry is a merge commit of the index and the worktree onto the
+                // commit it was created on, with untracked files in an optional third parent.
+                let base = format!("{stash}^1");
+                let output = git(&["diff", "-z", "--no-renames", "--name-status", &base, &stash])?;
+                anyhow::ensure!(
+                    output.status.success(),
+                    "Failed to load stash entry:\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                );
+                let diff_output = String::from_utf8_lossy(&output.stdout);
+                let mut changes = parse_git_diff_name_status(&diff_output)
+                    .filter_map(|(path, status_code)| {
+                        let new_object = format!("{stash}:{}", path.display());
+                        let old_object = format!("{base}:{}", path.display());
+                        let (old_object, new_object) = match status_code {
+                            StatusCode::Modified => (Some(old_object), Some(new_object)),
+                            StatusCode::Added => (None, Some(new_object)),
+                            StatusCode::Deleted => (Some(old_object), None),
+                            _ => return None,
+                        };
+                        Some((RepoPath::from(path), old_object, new_object))
+                    })
+                    .collect::<Vec<_>>();
+
+                let untracked = format!("{stash}^3");
+                let output = git(&["ls-tree", "-r", "-z", "--name-only", &untracked])?;
+                if output.status.success() {
+                    let untracked_paths = String::from_utf8_lossy(&output.stdout);
+                    changes.extend(
+                        untracked_paths
+                            .split('\0')
+                            .filter(|path| !path.is_empty())
+                            .map(|path| {
+                                (
+                                    RepoPath::from_str(path),
+                                    None,
+                                    Some(format!("{untracked}:{path}")),
+                                )
+                            }),
+                    );
+                }
+
+                let files = read_file_versions(&working_directory, changes)?;
+                Ok(CommitDiff { files })
+            })
+            .boxed()
+    }
+
+    fn stash_apply(
+        &self,
+        index: usize,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.run_git_subcommand(
+            "stash",
+            vec![
+                "apply".into(),
+                "--quiet".into(),
+                stash_reference(index).into(),
+            ],
+            env,
+        )
+    }
+
+    fn stash_pop(
+        &self,
+        index: usize,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.run_git_subcommand(
+            "stash",
+            vec![
+                "pop".into(),
+                "--quiet".into(),
+                stash_reference(index).into(),
+            ],
+            env,
+        )
+    }
+
+    fn stash_drop(
+        &self,
+        index: usize,
+        env: Arc<HashMap<String, String>>,
+    ) -> BoxFuture<'_, Result<()>> {
+        self.run_git_subcommand(
+            "stash",
+            vec![
+                "drop".into(),
+                "--quiet".into(),
+                stash_reference(index).into(),
+            ],
+            env,
+        )
+    }
+
     fn stage_paths(
         &self,
         paths: Vec<RepoPath>,
@@ -1258,9 +2175,9 @@ impl GitRepository for RealGitRepository {
         .boxed()
     }
 
-    fn pull(
+    fn push_tag(
         &self,
-        branch_name: String,
+        name: String,
         remote_name: String,
         ask_pass: AskPassDelegate,
         env: Arc<HashMap<String, String>>,
@@ -1269,13 +2186,15 @@ impl GitRepository for RealGitRepository {
         let working_directory = self.working_directory();
         let executor = cx.background_executor().clone();
         async move {
+            let working_directory = working_directory?;
             let mut command = new_smol_command("git");
             command
                 .envs(env.iter())
-                .current_dir(&working_directory?)
-                .args(["pull"])
+                .current_dir(&working_directory)
+                .arg("push")
                 .arg(remote_name)
-                .arg(branch_name)
+                .arg(format!("refs/tags/{name}:refs/tags/{name}"))
+                .stdin(smol::process::Stdio::null())
                 .stdout(smol::process::Stdio::piped())
                 .stderr(smol::process::Stdio::piped());
 
@@ -1284,22 +2203,48 @@ impl GitRepository for RealGitRepository {
         .boxed()
     }
 
-    fn fetch(
+    fn pull(
         &self,
-        fetch_options: FetchOptions,
+        branch_name: String,
+        remote_name: String,
         ask_pass: AskPassDelegate,
         env: Arc<HashMap<String, String>>,
         cx: AsyncApp,
     ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
         let working_directory = self.working_directory();
-        let remote_name = format!("{}", fetch_options);
         let executor = cx.background_executor().clone();
         async move {
             let mut command = new_smol_command("git");
             command
                 .envs(env.iter())
                 .current_dir(&working_directory?)
-                .args(["fetch", &remote_name])
+                .args(["pull"])
+                .arg(remote_name)
+                .arg(branch_name)
+                .stdout(smol::process::Stdio::piped())
+                .stderr(smol::process::Stdio::piped());
+
+            run_git_command(env, ask_pass, command, &executor).await
+        }
+        .boxed()
+    }
+
+    fn fetch(
+        &self,
+        fetch_options: FetchOptions,
+        ask_pass: AskPassDelegate,
+        env: Arc<HashMap<String, String>>,
+        cx: AsyncApp,
+    ) -> BoxFuture<'_, Result<RemoteCommandOutput>> {
+        let working_directory = self.working_directory();
+        let remote_name = format!("{}", fetch_options);
+        let executor = cx.background_executor().clone();
+        async move {
+            let mut command = new_smol_command("git");
+            command
+                .envs(env.iter())
+                .current_dir(&working_directory?)
+                .args(["fetch", &remote_name])
                 .stdout(smol::process::Stdio::piped())
                 .stderr(smol::process::Stdio::piped());
 
@@ -1547,6 +2492,489 @@ impl GitRepository for RealGitRepository {
     }
 }
 
+/// Reads the old and new versions of changed files, given as `<revision>:<path>` object names.
+fn read_file_versions(
+    working_directory: &Path,
+    changes: impl IntoIterator<Item = (RepoPath, Option<String>, Option<String>)>,
+) -> Result<Vec<CommitFile>> {
+    let mut cat_file_process = util::command::new_std_command("git")
+        .current_dir(working_directory)
+        .args(["--no-optional-locks", "cat-file", "--batch=%(objectsize)"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("starting git cat-file process")?;
+
+    use std::io::Write as _;
+    let mut stdin = BufWriter::with_capacity(512, cat_file_process.stdin.take().unwrap());
+    let mut stdout = BufReader::new(cat_file_process.stdout.take().unwrap());
+    let mut info_line = String::new();
+    let mut newline = [b'\0'];
+    let mut read_object = |object: Option<String>| -> Result<Option<String>> {
+        let Some(object) = object else {
+            return Ok(None);
+        };
+        writeln!(&mut stdin, "{object}")?;
+        stdin.flush()?;
+
+        info_line.clear();
+        stdout.read_line(&mut info_line)?;
+        let len = info_line
+            .trim_end()
+            .parse()
+            .with_context(|| format!("invalid object size output from cat-file {info_line}"))?;
+        let mut text = vec![0; len];
+        stdout.read_exact(&mut text)?;
+        stdout.read_exact(&mut newline)?;
+        Ok(Some(String::from_utf8_lossy(&text).to_string()))
+    };
+
+    let mut files = Vec::new();
+    for (path, old_object, new_object) in changes {
+        let old_text = read_object(old_object)?;
+        let new_text = read_object(new_object)?;
+        files.push(CommitFile {
+            path,
+            old_text,
+            new_text,
+        });
+    }
+    Ok(files)
+}
+
+fn parse_stash_list(output: &str) -> Result<Vec<StashEntry>> {
+    output
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let mut fields = line.split('\0');
+            let (Some(sha), Some(message), Some(timestamp)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                bail!("unexpected git stash list output: {line:?}");
+            };
+            Ok(StashEntry {
+                index,
+                sha: sha.to_string().into(),
+                message: message.to_string().into(),
+                commit_timestamp: timestamp.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// Parses the output of `git worktree list --porcelain`, which describes each worktree
+/// in a block of lines, with the main worktree first.
+fn parse_worktree_list(output: &str) -> Result<Vec<GitWorktree>> {
+    let mut worktrees = Vec::new();
+    for block in output.split("\n\n") {
+        let mut lines = block.lines();
+        let Some(first_line) = lines.next() else {
+            continue;
+        };
+        let Some(path) = first_line.strip_prefix("worktree ") else {
+            bail!("unexpected git worktree list output: {first_line:?}");
+        };
+        let mut worktree = GitWorktree {
+            path: PathBuf::from(path),
+            head_sha: None,
+            branch: None,
+            is_main: worktrees.is_empty(),
+            is_locked: false,
+            is_prunable: false,
+        };
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "HEAD" if value.bytes().any(|byte| byte != b'0') => {
+                    worktree.head_sha = Some(value.to_string().into());
+                }
+                "branch" => worktree.branch = Some(value.to_string().into()),
+                "locked" => worktree.is_locked = true,
+                "prunable" => worktree.is_prunable = true,
+                _ => {}
+            }
+        }
+        worktrees.push(worktree);
+    }
+    Ok(worktrees)
+}
+
+/// Checks that a worktree requested by a collaborator would be created where the worktree
+/// picker puts them: next to the main worktree or to `work_directory`.
+pub fn validate_new_worktree_path(
+    path: &Path,
+    work_directory: &Path,
+    worktrees: &[GitWorktree],
+) -> Result<()> {
+    anyhow::ensure!(
+        path.is_absolute()
+            && path.components().all(|component| {
+                matches!(
+                    component,
+                    Component::Prefix(_) | Component::RootDir | Component::Normal(_)
+                )
+            }),
+        "invalid worktree path: {path:?}"
+    );
+    let main_worktree = worktrees
+        .iter()
+        .find(|worktree| worktree.is_main)
+        .map(|worktree| worktree.path.as_path());
+    let parent = path.parent();
+    anyhow::ensure!(
+        parent.is_some()
+            && [Some(work_directory), main_worktree]
+                .into_iter()
+                .flatten()
+                .any(|sibling| sibling.parent() == parent),
+        "worktree path {path:?} is not next to the repository"
+    );
+    Ok(())
+}
+
+/// Checks that a worktree that a collaborator asks to remove is one of the repository's linked
+/// worktrees, and not the one at `work_directory`.
+pub fn validate_removed_worktree_path(
+    path: &Path,
+    work_directory: &Path,
+    worktrees: &[GitWorktree],
+) -> Result<()> {
+    anyhow::ensure!(
+        path != work_directory
+            && worktrees
+                .iter()
+                .any(|worktree| !worktree.is_main && worktree.path == path),
+        "{path:?} is not a linked worktree of the repository"
+    );
+    Ok(())
+}
+
+/// Checks that the steps of a rebase can be written to its todo list as they are: each
+/// step must name a commit by its hash, and subjects must be a single line.
+pub fn validate_rebase_todo(todo: &[RebaseTodoEntry]) -> Result<()> {
+    for entry in todo {
+        anyhow::ensure!(
+            !entry.sha.is_empty() && entry.sha.chars().all(|c| c.is_ascii_hexdigit()),
+            "invalid commit hash in rebase step: {:?}",
+            entry.sha
+        );
+        anyhow::ensure!(
+            !entry.subject.contains(['\n', '\r']),
+            "invalid subject in rebase step: {:?}",
+            entry.subject
+        );
+    }
+    Ok(())
+}
+
+/// Writes the todo list for `git rebase -i`. Reworded commits are picked and then
+/// amended with their new message, so that git doesn't need to open an editor.
+fn rebase_todo_script(todo: &[RebaseTodoEntry]) -> String {
+    let mut script = String::new();
+    for entry in todo {
+        match (entry.action, &entry.message) {
+            (RebaseAction::Reword, Some(message)) => {
+                writeln!(script, "pick {}", entry.sha).unwrap();
+                let lines = message
+                    .lines()
+                    .map(shell_quote)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(
+                    script,
+                    "exec printf '%s\\n' {lines} | git commit --amend --allow-empty --quiet --file=-"
+                )
+                .unwrap();
+            }
+            (action, _) => {
+                writeln!(script, "{} {}", action.as_str(), entry.sha).unwrap();
+            }
+        }
+    }
+    script
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// A rebase that stops at a conflict exits with an error, but it is still in progress
+/// and can be continued once the conflicts are resolved.
+/// A cherry-pick or revert that stops because of conflicts still succeeds: its state is
+/// left in the repository, for the conflicts to be resolved before continuing.
+fn check_sequencer_output(
+    operation: SequencerOperation,
+    output: std::process::Output,
+) -> Result<()> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let conflict_message = match operation {
+        SequencerOperation::CherryPick => "could not apply",
+        SequencerOperation::Revert => "could not revert",
+    };
+    anyhow::ensure!(
+        output.status.success() || stderr.contains(conflict_message),
+        "Failed to {}:\n{stderr}",
+        operation.as_str(),
+    );
+    Ok(())
+}
+
+fn parse_tags(output: &str) -> Result<Vec<Tag>> {
+    output
+        .lines()
+        .map(|line| {
+            let mut fields = line.split('\0');
+            let (Some(name), Some(sha), Some(peeled_sha), Some(timestamp), Some(subject)) = (
+                fields.next(),
+                fields.next(),
+                fields.next(),
+                fields.next(),
+                fields.next(),
+            ) else {
+                bail!("unexpected git for-each-ref output: {line:?}");
+            };
+            // Only annotated tags are objects of their own, which peel to a commit.
+            let (sha, message) = if peeled_sha.is_empty() {
+                (sha, None)
+            } else {
+                (peeled_sha, Some(subject.to_string().into()))
+            };
+            Ok(Tag {
+                name: name.to_string().into(),
+                sha: sha.to_string().into(),
+                message,
+                timestamp: timestamp.parse().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn check_rebase_output(output: std::process::Output) -> Result<()> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    anyhow::ensure!(
+        output.status.success() || stderr.contains("could not apply"),
+        "Failed to rebase:\n{stderr}",
+    );
+    Ok(())
+}
+
+async fn read_rebase_status(git_dir: &Path) -> Result<Option<RebaseStatus>> {
+    async fn read(dir: &Path, name: &str) -> Option<String> {
+        let contents = smol::fs::read_to_string(dir.join(name)).await.ok()?;
+        Some(contents.trim().to_string())
+    }
+
+    // Interactive rebases keep their state in `rebase-merge`, and ones that apply
+    // patches with `git am` in `rebase-apply`.
+    let (dir, step_file, total_file) = if git_dir.join("rebase-merge").is_dir() {
+        (git_dir.join("rebase-merge"), "msgnum", "end")
+    } else if git_dir.join("rebase-apply").is_dir() {
+        (git_dir.join("rebase-apply"), "next", "last")
+    } else {
+        return Ok(None);
+    };
+
+    let head_name = read(&dir, "head-name")
+        .await
+        .filter(|name| name != "detached HEAD")
+        .map(|name| name.trim_start_matches("refs/heads/").to_string().into());
+    Ok(Some(RebaseStatus {
+        head_name,
+        onto: read(&dir, "onto").await.unwrap_or_default().into(),
+        step: read(&dir, step_file)
+            .await
+            .and_then(|step| step.parse().ok())
+            .unwrap_or_default(),
+        total_steps: read(&dir, total_file)
+            .await
+            .and_then(|total| total.parse().ok())
+            .unwrap_or_default(),
+        stopped_at: read(&dir, "stopped-sha").await.map(Into::into),
+    }))
+}
+
+const LOG_FORMAT: &str = "%H%x00%P%x00%an%x00%ae%x00%ct%x00%D%x00%s";
+
+fn git_log_args(options: &LogOptions) -> Vec<OsString> {
+    let mut args = vec![
+        OsString::from("--no-optional-locks"),
+        OsString::from("log"),
+        OsString::from("--topo-order"),
+        OsString::from("--decorate=short"),
+        // Rewrites the parents printed by `%P` to the nearest commits that are part of a
+        // path-limited log, so that the branch graph stays connected.
+        OsString::from("--parents"),
+        OsString::from(format!("--format={LOG_FORMAT}")),
+        OsString::from(format!("--skip={}", options.skip)),
+        OsString::from(format!("--max-count={}", options.limit)),
+    ];
+    if options.author.is_some() || options.message.is_some() {
+        args.push("--regexp-ignore-case".into());
+        args.push("--fixed-strings".into());
+    }
+    if let Some(author) = &options.author {
+        args.push(format!("--author={author}").into());
+    }
+    if let Some(message) = &options.message {
+        args.push(format!("--grep={message}").into());
+    }
+    if options.all_branches {
+        args.push("--exclude=refs/stash".into());
+        args.push("--all".into());
+    }
+    args.push("--".into());
+    if let Some(path) = &options.path {
+        args.push(path.to_unix_style().into_owned());
+    }
+    args
+}
+
+fn parse_log(output: &str) -> Result<Vec<LogEntry>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(parse_log_line)
+        .collect()
+}
+
+fn parse_log_line(line: &str) -> Result<LogEntry> {
+    let fields = line.splitn(7, '\0').collect::<Vec<_>>();
+    let [
+        sha,
+        parents,
+        author_name,
+        author_email,
+        timestamp,
+        refs,
+        subject,
+    ] = fields[..]
+    else {
+        bail!("unexpected git log output: {line:?}");
+    };
+    Ok(LogEntry {
+        sha: sha.to_string().into(),
+        parents: parents
+            .split_whitespace()
+            .map(|parent| parent.to_string().into())
+            .collect(),
+        subject: subject.to_string().into(),
+        author_name: author_name.to_string().into(),
+        author_email: author_email.to_string().into(),
+        commit_timestamp: timestamp.parse()?,
+        refs: refs
+            .split(", ")
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string().into())
+            .collect(),
+    })
+}
+
+/// Lists the changes to a file with `git log --follow`, or to a range of its lines
+/// with `git log -L`, which follows renames too. Every commit is preceded by a
+/// record separator, so that it can be told apart from the patch that follows it.
+fn git_file_history_args(path: &RepoPath, rows: Option<Range<u32>>, limit: usize) -> Vec<OsString> {
+    let mut args = vec![
+        OsString::from("-c"),
+        OsString::from("core.quotePath=false"),
+        OsString::from("--no-optional-locks"),
+        OsString::from("log"),
+        OsString::from(format!("--format=%x1e{LOG_FORMAT}")),
+        OsString::from(format!("--max-count={limit}")),
+    ];
+    match rows {
+        Some(rows) => {
+            let end = rows.end.max(rows.start + 1);
+            let mut range = OsString::from(format!("-L{},{end}:", rows.start + 1));
+            range.push(path.to_unix_style());
+            args.push(range);
+        }
+        None => {
+            args.push("--follow".into());
+            args.push("--patch".into());
+            args.push("--unified=0".into());
+            args.push("--".into());
+            args.push(path.to_unix_style().into_owned());
+        }
+    }
+    args
+}
+
+struct FileChange {
+    commit: LogEntry,
+    path: RepoPath,
+    old_path: Option<RepoPath>,
+    new_path: Option<RepoPath>,
+    rows: Vec<Range<u32>>,
+}
+
+fn parse_file_history(output: &str) -> Result<Vec<FileChange>> {
+    fn parse_path(path: &str, prefix: &str) -> Option<RepoPath> {
+        // Paths with control characters are still quoted, even with `core.quotePath` unset.
+        let path = path.trim_matches('"');
+        Some(RepoPath::from_str(path.strip_prefix(prefix)?))
+    }
+
+    let mut changes = Vec::new();
+    for commit in output.split('\x1e').filter(|commit| !commit.is_empty()) {
+        let (header, patch) = commit.split_once('\n').unwrap_or((commit, ""));
+        let commit = parse_log_line(header)?;
+        let mut old_path = None;
+        let mut new_path = None;
+        let mut rows = Vec::new();
+        let mut in_diff_header = false;
+        for line in patch.lines() {
+            if line.starts_with("diff --git ") {
+                in_diff_header = true;
+            } else if line.starts_with("@@ ") {
+                in_diff_header = false;
+                rows.extend(parse_hunk_rows(line));
+            } else if in_diff_header {
+                if let Some(path) = line.strip_prefix("--- ") {
+                    old_path = parse_path(path, "a/");
+                } else if let Some(path) = line.strip_prefix("+++ ") {
+                    new_path = parse_path(path, "b/");
+                } else if let Some(path) = line.strip_prefix("rename from ") {
+                    old_path = parse_path(path, "");
+                } else if let Some(path) = line.strip_prefix("rename to ") {
+                    new_path = parse_path(path, "");
+                }
+            }
+        }
+        // Binary files have no patch to show.
+        let Some(path) = new_path.clone().or_else(|| old_path.clone()) else {
+            continue;
+        };
+        changes.push(FileChange {
+            commit,
+            path,
+            old_path,
+            new_path,
+            rows,
+        });
+    }
+    Ok(changes)
+}
+
+/// Returns the rows of the new text covered by a hunk header such as `@@ -1,2 +3,4 @@`.
+fn parse_hunk_rows(line: &str) -> Option<Range<u32>> {
+    let new_range = line
+        .strip_prefix("@@ ")?
+        .split(' ')
+        .find_map(|range| range.strip_prefix('+'))?;
+    let (start, len) = match new_range.split_once(',') {
+        Some((start, len)) => (start.parse::<u32>().ok()?, len.parse::<u32>().ok()?),
+        None => (new_range.parse::<u32>().ok()?, 1),
+    };
+    // An empty hunk starts after the given line, while others start at it.
+    if len == 0 {
+        Some(start..start)
+    } else {
+        Some(start - 1..start - 1 + len)
+    }
+}
+
 fn git_status_args(path_prefixes: &[RepoPath]) -> Vec<OsString> {
     let mut args = vec![
         OsString::from("--no-optional-locks"),
@@ -2027,6 +3455,7 @@ fn checkpoint_author_envs() -> HashMap<String, String> {
 mod tests {
     use super::*;
     use gpui::TestAppContext;
+    use util::path;
 
     #[gpui::test]
     async fn test_checkpoint_basic(cx: &mut TestAppContext) {
@@ -2246,6 +3675,719 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_stash(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let file_path = repo_dir.path().join("file");
+        let untracked_path = repo_dir.path().join("untracked");
+        smol::fs::write(&file_path, "initial").await.unwrap();
+
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+            .await
+            .unwrap();
+        repo.commit(
+            "Initial commit".into(),
+            None,
+            CommitOptions::default(),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(repo.stash_list().await.unwrap(), Vec::new());
+
+        smol::fs::write(&file_path, "modified").await.unwrap();
+        smol::fs::write(&untracked_path, "new").await.unwrap();
+        repo.stash_push(StashScope::All, Some("wip".into()), env.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            smol::fs::read_to_string(&file_path).await.unwrap(),
+            "initial"
+        );
+        assert!(!untracked_path.exists());
+
+        let entries = repo.stash_list().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].index, 0);
+        assert!(entries[0].message.ends_with(": wip"));
+
+        let files = repo
+            .stash_diff(0)
+            .await
+            .unwrap()
+            .files
+            .into_iter()
+            .map(|file| {
+                (
+                    file.path.to_string_lossy().into_owned(),
+                    file.old_text,
+                    file.new_text,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            [
+                (
+                    "file".to_string(),
+                    Some("initial".to_string()),
+                    Some("modified".to_string())
+                ),
+                ("untracked".to_string(), None, Some("new".to_string())),
+            ]
+        );
+
+        repo.stash_apply(0, env.clone()).await.unwrap();
+        assert_eq!(
+            smol::fs::read_to_string(&file_path).await.unwrap(),
+            "modified"
+        );
+        assert_eq!(repo.stash_list().await.unwrap().len(), 1);
+        repo.stash_drop(0, env.clone()).await.unwrap();
+        assert_eq!(repo.stash_list().await.unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_stash_list_parsing() {
+        let input = "0f9e1c0a50ec2a4e92ccc2b16ad1cdb54e2bd9f8\x00On main: wip\x001733187470\n\
+            2b1d0fbd0a2c55a4f1b5a6d0d5ec4f37c4e2f1a9\x00WIP on main: 060964d generated protobuf\x001733100000\n";
+        assert_eq!(
+            parse_stash_list(input).unwrap(),
+            vec![
+                StashEntry {
+                    index: 0,
+                    sha: "0f9e1c0a50ec2a4e92ccc2b16ad1cdb54e2bd9f8".into(),
+                    message: "On main: wip".into(),
+                    commit_timestamp: 1733187470,
+                },
+                StashEntry {
+                    index: 1,
+                    sha: "2b1d0fbd0a2c55a4f1b5a6d0d5ec4f37c4e2f1a9".into(),
+                    message: "WIP on main: 060964d generated protobuf".into(),
+                    commit_timestamp: 1733100000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worktree_list_parsing() {
+        let input = "worktree /home/user/project\n\
+            HEAD 81c05241dbc948115af9de7e3d406e86e0cb62c1\n\
+            branch refs/heads/main\n\
+            \n\
+            worktree /home/user/project-review\n\
+            HEAD 81c05241dbc948115af9de7e3d406e86e0cb62c1\n\
+            detached\n\
+            locked on a removable drive\n\
+            \n\
+            worktree /home/user/project-feature\n\
+            HEAD 0000000000000000000000000000000000000000\n\
+            branch refs/heads/feature\n\
+            prunable gitdir file points to non-existent location\n\
+            \n";
+        assert_eq!(
+            parse_worktree_list(input).unwrap(),
+            vec![
+                GitWorktree {
+                    path: PathBuf::from("/home/user/project"),
+                    head_sha: Some("81c05241dbc948115af9de7e3d406e86e0cb62c1".into()),
+                    branch: Some("refs/heads/main".into()),
+                    is_main: true,
+                    is_locked: false,
+                    is_prunable: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/home/user/project-review"),
+                    head_sha: Some("81c05241dbc948115af9de7e3d406e86e0cb62c1".into()),
+                    branch: None,
+                    is_main: false,
+                    is_locked: true,
+                    is_prunable: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/home/user/project-feature"),
+                    head_sha: None,
+                    branch: Some("refs/heads/feature".into()),
+                    is_main: false,
+                    is_locked: false,
+                    is_prunable: true,
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_worktrees(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        let main_path = repo_dir.path().join("main");
+        git2::Repository::init(&main_path).unwrap();
+        smol::fs::write(main_path.join("file"), "initial")
+            .await
+            .unwrap();
+
+        let repo = RealGitRepository::new(&main_path.join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+            .await
+            .unwrap();
+        repo.commit(
+            "Initial commit".into(),
+            None,
+            CommitOptions::default(),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+
+        let feature_path = repo_dir.path().join("feature");
+        repo.create_worktree(
+            feature_path.clone(),
+            "HEAD".into(),
+            Some("feature".into()),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            smol::fs::read_to_string(feature_path.join("file"))
+                .await
+                .unwrap(),
+            "initial"
+        );
+
+        let worktrees = repo.worktrees().await.unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].is_main);
+        assert_eq!(worktrees[1].branch_name(), Some("feature"));
+        assert_eq!(
+            worktrees[1].path.canonicalize().unwrap(),
+            feature_path.canonicalize().unwrap()
+        );
+
+        // Worktrees with changes are only removed when forced.
+        smol::fs::write(feature_path.join("file"), "modified")
+            .await
+            .unwrap();
+        assert!(
+            repo.remove_worktree(feature_path.clone(), false, env.clone())
+                .await
+                .is_err()
+        );
+        repo.remove_worktree(feature_path.clone(), true, env.clone())
+            .await
+            .unwrap();
+        assert!(!feature_path.exists());
+        assert_eq!(repo.worktrees().await.unwrap().len(), 1);
+
+        // Worktrees whose directories were deleted are pruned.
+        let stale_path = repo_dir.path().join("stale");
+        repo.create_worktree(stale_path.clone(), "HEAD".into(), None, env.clone())
+            .await
+            .unwrap();
+        smol::fs::remove_dir_all(&stale_path).await.unwrap();
+        assert!(repo.worktrees().await.unwrap()[1].is_prunable);
+        repo.prune_worktrees(env.clone()).await.unwrap();
+        assert_eq!(repo.worktrees().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tag_parsing() {
+        let input = "v1.1\x00a1f0c3d\x00c2a1b8e\x001733187470\x00Release v1.1\n\
+            v1.0\x009e8d7c6\x00\x001733100000\x00Initial commit\n";
+        assert_eq!(
+            parse_tags(input).unwrap(),
+            vec![
+                Tag {
+                    name: "v1.1".into(),
+                    sha: "c2a1b8e".into(),
+                    message: Some("Release v1.1".into()),
+                    timestamp: 1733187470,
+                },
+                Tag {
+                    name: "v1.0".into(),
+                    sha: "9e8d7c6".into(),
+                    message: None,
+                    timestamp: 1733100000,
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_cherry_pick_revert_and_tags(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let file_path = repo_dir.path().join("file");
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        let commit_file = async |contents: &str, message: &str| {
+            smol::fs::write(&file_path, contents).await.unwrap();
+            repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+                .await
+                .unwrap();
+            repo.commit(
+                message.to_string().into(),
+                None,
+                CommitOptions::default(),
+                env.clone(),
+            )
+            .await
+            .unwrap();
+            repo.head_sha().await.unwrap()
+        };
+
+        let initial_sha = commit_file("a", "Initial commit").await;
+        repo.create_tag("v1".into(), "HEAD".into(), None, env.clone())
+            .await
+            .unwrap();
+        repo.create_tag(
+            "v1-annotated".into(),
+            "HEAD".into(),
+            Some("First release".into()),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        let change_sha = commit_file("b", "Change file").await;
+
+        let mut tags = repo.tags().await.unwrap();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            tags.iter()
+                .map(|tag| (tag.name.as_ref(), tag.sha.as_ref(), tag.message.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("v1", initial_sha.as_str(), None),
+                ("v1-annotated", initial_sha.as_str(), Some("First release")),
+            ]
+        );
+        repo.delete_tag("v1".into(), env.clone()).await.unwrap();
+        assert_eq!(repo.tags().await.unwrap().len(), 1);
+
+        repo.revert(vec![change_sha.clone()], env.clone())
+            .await
+            .unwrap();
+        assert_eq!(smol::fs::read_to_string(&file_path).await.unwrap(), "a");
+        repo.cherry_pick(vec![change_sha.clone()], env.clone())
+            .await
+            .unwrap();
+        assert_eq!(smol::fs::read_to_string(&file_path).await.unwrap(), "b");
+        assert_eq!(repo.sequencer_state().await, None);
+
+        // Reverting a change that was modified again since conflicts, and the revert stops
+        // until the conflicts are resolved or it is aborted.
+        commit_file("c", "Change file again").await;
+        repo.revert(vec![change_sha.clone()], env.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.sequencer_state().await,
+            Some(SequencerState {
+                operation: SequencerOperation::Revert,
+                sha: change_sha.into(),
+            })
+        );
+        repo.sequencer_command(
+            SequencerOperation::Revert,
+            RebaseCommand::Abort,
+            env.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(repo.sequencer_state().await, None);
+        assert_eq!(smol::fs::read_to_string(&file_path).await.unwrap(), "c");
+    }
+
+    #[test]
+    fn test_log_parsing() {
+        let input = "c2a1b8e\x00a1f0c3d 9e8d7c6\x00Jane Doe\x00jane@example.com\x001733187470\x00HEAD -> main, tag: v1.0\x00Merge branch 'feature'\n\
+            a1f0c3d\x00\x00John Doe\x00john@example.com\x001733100000\x00\x00Initial commit\n";
+        assert_eq!(
+            parse_log(input).unwrap(),
+            vec![
+                LogEntry {
+                    sha: "c2a1b8e".into(),
+                    parents: vec!["a1f0c3d".into(), "9e8d7c6".into()],
+                    subject: "Merge branch 'feature'".into(),
+                    author_name: "Jane Doe".into(),
+                    author_email: "jane@example.com".into(),
+                    commit_timestamp: 1733187470,
+                    refs: vec!["HEAD -> main".into(), "tag: v1.0".into()],
+                },
+                LogEntry {
+                    sha: "a1f0c3d".into(),
+                    parents: vec![],
+                    subject: "Initial commit".into(),
+                    author_name: "John Doe".into(),
+                    author_email: "john@example.com".into(),
+                    commit_timestamp: 1733100000,
+                    refs: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_history_args() {
+        let path = RepoPath::new(Path::new("src").join("lib.rs"));
+        let args = git_file_history_args(&path, Some(4..6), 10);
+        assert_eq!(args.last().unwrap(), "-L5,6:src/lib.rs");
+        let args = git_file_history_args(&path, None, 10);
+        assert_eq!(args.last().unwrap(), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_hunk_rows_parsing() {
+        assert_eq!(parse_hunk_rows("@@ -5,3 +5,4 @@"), Some(4..8));
+        assert_eq!(parse_hunk_rows("@@ -2 +2 @@ fn a() {"), Some(1..2));
+        assert_eq!(parse_hunk_rows("@@ -6,2 +5,0 @@"), Some(5..5));
+        assert_eq!(parse_hunk_rows("@@ -0,0 +1,7 @@"), Some(0..7));
+        assert_eq!(parse_hunk_rows(" fn a() {"), None);
+    }
+
+    #[gpui::test]
+    async fn test_file_history(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        let old_path = repo_dir.path().join("old.rs");
+        let new_path = repo_dir.path().join("new.rs");
+        for (path, contents, message) in [
+            (
+                &old_path,
+                "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n",
+                "Add a and b",
+            ),
+            (
+                &old_path,
+                "fn a() {\n    10\n}\n\nfn b() {\n    2\n}\n",
+                "Change a",
+            ),
+            (
+                &new_path,
+                "fn a() {\n    10\n}\n\nfn b() {\n    2\n}\n",
+                "Rename",
+            ),
+            (
+                &new_path,
+                "fn a() {\n    10\n}\n\nfn b() {\n    20\n    21\n}\n",
+                "Change b",
+            ),
+        ] {
+            if message == "Rename" {
+                smol::fs::rename(&old_path, &new_path).await.unwrap();
+            } else {
+                smol::fs::write(path, contents).await.unwrap();
+            }
+            repo.stage_paths(
+                vec![RepoPath::from_str("old.rs"), RepoPath::from_str("new.rs")],
+                env.clone(),
+            )
+            .await
+            .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+        }
+
+        let history = repo
+            .file_history(RepoPath::from_str("new.rs"), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| (
+                    entry.commit.subject.as_ref(),
+                    entry.file.path.to_string_lossy().into_owned(),
+                    entry.rows.clone()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("Change b", "new.rs".to_string(), vec![5..7]),
+                ("Rename", "new.rs".to_string(), vec![]),
+                ("Change a", "old.rs".to_string(), vec![1..2]),
+                ("Add a and b", "old.rs".to_string(), vec![0..7]),
+            ]
+        );
+        assert_eq!(
+            history[2].file.old_text.as_deref(),
+            Some("fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n")
+        );
+        assert_eq!(history[3].file.old_text, None);
+
+        // Tracing the rows of `b` skips the commits that didn't touch it.
+        let history = repo
+            .file_history(RepoPath::from_str("new.rs"), Some(4..8), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| (entry.commit.subject.as_ref(), entry.rows.clone()))
+                .collect::<Vec<_>>(),
+            [("Change b", vec![4..8]), ("Add a and b", vec![4..7])]
+        );
+        assert_eq!(
+            history[1].file.new_text.as_deref(),
+            Some("fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n")
+        );
+    }
+
+    #[test]
+    fn test_rebase_todo_script() {
+        let entry = |action, sha: &str, subject: &str, message: Option<&str>| RebaseTodoEntry {
+            action,
+            sha: sha.to_string().into(),
+            subject: subject.to_string().into(),
+            message: message.map(ToString::to_string),
+        };
+        let script = rebase_todo_script(&[
+            entry(RebaseAction::Pick, "aaa", "First", None),
+            entry(
+                RebaseAction::Reword,
+                "bbb",
+                "Second",
+                Some("Don't panic\n\nDetails"),
+            ),
+            entry(RebaseAction::Reword, "ccc", "Third", None),
+            entry(RebaseAction::Fixup, "ddd", "fixup! First", None),
+            entry(RebaseAction::Drop, "eee", "Fifth", None),
+        ]);
+        assert_eq!(
+            script,
+            "pick aaa\n\
+             pick bbb\n\
+             exec printf '%s\\n' 'Don'\\''t panic' '' 'Details' | git commit --amend --allow-empty --quiet --file=-\n\
+             reword ccc\n\
+             fixup ddd\n\
+             drop eee\n"
+        );
+    }
+
+    #[test]
+    fn test_validate_rebase_todo() {
+        let entry = |sha: &str, subject: &str| RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: sha.to_string().into(),
+            subject: subject.to_string().into(),
+            message: None,
+        };
+        assert!(validate_rebase_todo(&[entry("0a1b2c", "First")]).is_ok());
+        assert!(validate_rebase_todo(&[entry("0a1b2c\nexec touch pwned", "First")]).is_err());
+        assert!(validate_rebase_todo(&[entry("", "First")]).is_err());
+        assert!(validate_rebase_todo(&[entry("0a1b2c", "First\nexec touch pwned")]).is_err());
+        assert!(validate_rebase_todo(&[entry("0a1b2c", "First\rSecond")]).is_err());
+    }
+
+    #[test]
+    fn test_validate_worktree_paths() {
+        let worktree = |path: &str, is_main: bool| GitWorktree {
+            path: PathBuf::from(path),
+            head_sha: None,
+            branch: None,
+            is_main,
+            is_locked: false,
+            is_prunable: false,
+        };
+        let worktrees = [
+            worktree(path!("/src/zed"), true),
+            worktree(path!("/src/zed-feature"), false),
+            worktree(path!("/tmp/zed-elsewhere"), false),
+        ];
+        let work_directory = Path::new(path!("/src/zed-feature"));
+        let can_create = |path: &str| {
+            validate_new_worktree_path(Path::new(path), work_directory, &worktrees).is_ok()
+        };
+        let can_remove = |path: &str| {
+            validate_removed_worktree_path(Path::new(path), work_directory, &worktrees).is_ok()
+        };
+
+        assert!(can_create(path!("/src/zed-fix")));
+        assert!(!can_create(path!("/src/zed/fix")));
+        assert!(!can_create(path!("/src/../etc")));
+        assert!(!can_create(path!("/tmp/zed-fix")));
+        assert!(!can_create("zed-fix"));
+
+        assert!(can_remove(path!("/tmp/zed-elsewhere")));
+        assert!(!can_remove(path!("/src/zed-feature")));
+        assert!(!can_remove(path!("/src/zed")));
+        assert!(!can_remove(path!("/src/other")));
+    }
+
+    #[gpui::test]
+    async fn test_interactive_rebase(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let file_path = repo_dir.path().join("file");
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        for (contents, message) in [
+            ("one\n", "Add one"),
+            ("one\ntwo\n", "Add two"),
+            ("one\ntwo\nthree\n", "Add three"),
+            ("one\n2\nthree\n", "fixup! Add two"),
+        ] {
+            smol::fs::write(&file_path, contents).await.unwrap();
+            repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+                .await
+                .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+        }
+        assert_eq!(repo.rebase_status().await.unwrap(), None);
+
+        let onto = repo.revparse_batch(vec!["HEAD~3".into()]).await.unwrap()[0]
+            .clone()
+            .unwrap();
+        let mut todo = repo.rebase_todo(onto.clone()).await.unwrap();
+        assert_eq!(
+            todo.iter()
+                .map(|entry| entry.subject.as_ref())
+                .collect::<Vec<_>>(),
+            ["Add two", "Add three", "fixup! Add two"]
+        );
+
+        // Moving the fixup next to the commit it fixes makes both it and "Add three" conflict.
+        let fixup = todo.remove(2);
+        todo.insert(1, fixup);
+        todo[1].action = RebaseAction::Fixup;
+        todo[0].action = RebaseAction::Reword;
+        todo[0].message = Some("Add 2".into());
+        let fixup_sha = todo[1].sha.clone();
+        repo.rebase_start(onto.clone(), todo, env.clone())
+            .await
+            .unwrap();
+
+        // The reworded commit is amended by an `exec` step.
+        let status = repo.rebase_status().await.unwrap().unwrap();
+        assert!(status.head_name.is_some());
+        assert_eq!(status.onto.as_ref(), onto);
+        assert_eq!((status.step, status.total_steps), (3, 4));
+        assert_eq!(status.stopped_at, Some(fixup_sha));
+
+        for (contents, step) in [("one\n2\n", Some(4)), ("one\n2\nthree\n", None)] {
+            smol::fs::write(&file_path, contents).await.unwrap();
+            repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+                .await
+                .unwrap();
+            repo.rebase_command(RebaseCommand::Continue, env.clone())
+                .await
+                .unwrap();
+            let status = repo.rebase_status().await.unwrap();
+            assert_eq!(status.map(|status| status.step), step);
+        }
+
+        let log = repo
+            .log(LogOptions {
+                limit: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            log.iter()
+                .map(|entry| entry.subject.as_ref())
+                .collect::<Vec<_>>(),
+            ["Add three", "Add 2", "Add one"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rebase_onto_option(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        smol::fs::write(repo_dir.path().join("file"), "one\n")
+            .await
+            .unwrap();
+        repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+            .await
+            .unwrap();
+        repo.commit(
+            "Add one".into(),
+            None,
+            CommitOptions::default(),
+            env.clone(),
+        )
+        .await
+        .unwrap();
+
+        let marker = repo_dir.path().join("marker");
+        let onto = format!("--exec=touch {}", marker.display());
+        assert!(repo.rebase_start(onto, Vec::new(), env).await.is_err());
+        assert!(!marker.exists());
+        assert_eq!(repo.rebase_status().await.unwrap(), None);
+    }
+
+    #[gpui::test]
+    async fn test_load_conflict_versions(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let file_path = repo_dir.path().join("file");
+        let repo =
+            RealGitRepository::new(&repo_dir.path().join(".git"), None, cx.executor()).unwrap();
+        let env = Arc::new(checkpoint_author_envs());
+        for (contents, message) in [
+            ("one\n", "Add one"),
+            ("one\ntwo\n", "Add two"),
+            ("one\n2\n", "Change two"),
+        ] {
+            smol::fs::write(&file_path, contents).await.unwrap();
+            repo.stage_paths(vec![RepoPath::from_str("file")], env.clone())
+                .await
+                .unwrap();
+            repo.commit(message.into(), None, CommitOptions::default(), env.clone())
+                .await
+                .unwrap();
+        }
+
+        // Dropping the commit that added the line makes the change to it conflict.
+        let onto = repo.revparse_batch(vec!["HEAD~2".into()]).await.unwrap()[0]
+            .clone()
+            .unwrap();
+        let mut todo = repo.rebase_todo(onto.clone()).await.unwrap();
+        todo[0].action = RebaseAction::Drop;
+        repo.rebase_start(onto, todo, env.clone()).await.unwrap();
+
+        assert_eq!(
+            repo.load_conflict_versions(RepoPath::from_str("file"))
+                .await
+                .unwrap(),
+            ConflictVersions {
+                base: Some("one\ntwo\n".into()),
+                ours: Some("one\n".into()),
+                theirs: Some("one\n2\n".into()),
+            }
+        );
+    }
+
     #[test]
     fn test_branches_parsing() {
         // suppress "help: octal escapes are not supported, `\0` is always null"
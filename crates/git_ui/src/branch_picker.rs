@@ -2,22 +2,36 @@ use anyhow::Context as _;
 use fuzzy::StringMatchCandidate;
 
 use collections::HashSet;
-use git::repository::Branch;
+use git::SHORT_SHA_LENGTH;
+use git::repository::{Branch, CommitSummary, Tag};
 use gpui::{
-    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
-    IntoElement, Modifiers, ModifiersChangedEvent, ParentElement, Render, SharedString, Styled,
-    Subscription, Task, Window, rems,
+    Action, AnyElement, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, Modifiers, ModifiersChangedEvent, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, WeakEntity, Window, actions, rems,
 };
 use picker::{Picker, PickerDelegate, PickerEditorPosition};
 use project::git_store::Repository;
 use std::sync::Arc;
 use time::OffsetDateTime;
 use time_format::format_local_timestamp;
-use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use ui::{HighlightedLabel, KeyBinding, ListItem, ListItemSpacing, prelude::*};
 use util::ResultExt;
 use workspace::notifications::DetachAndPromptErr;
 use workspace::{ModalView, Workspace};
 
+use crate::commit_view::CommitView;
+use crate::git_panel::GitPanel;
+
+actions!(
+    branch_picker,
+    [
+        /// Deletes the selected tag.
+        DeleteTag,
+        /// Pushes the selected tag to a remote.
+        PushTag,
+    ]
+);
+
 pub fn register(workspace: &mut Workspace) {
     workspace.register_action(open);
     workspace.register_action(switch);
@@ -49,9 +63,17 @@ pub fn open(
     cx: &mut Context<Workspace>,
 ) {
     let repository = workspace.project().read(cx).active_repository(cx).clone();
+    let weak_workspace = cx.weak_entity();
     let style = BranchListStyle::Modal;
     workspace.toggle_modal(window, cx, |window, cx| {
-        BranchList::new(repository, style, rems(34.), window, cx)
+        BranchList::new(
+            repository,
+            Some(weak_workspace),
+            style,
+            rems(34.),
+            window,
+            cx,
+        )
     })
 }
 
@@ -61,7 +83,14 @@ pub fn popover(
     cx: &mut App,
 ) -> Entity<BranchList> {
     cx.new(|cx| {
-        let list = BranchList::new(repository, BranchListStyle::Popover, rems(20.), window, cx);
+        let list = BranchList::new(
+            repository,
+            None,
+            BranchListStyle::Popover,
+            rems(20.),
+            window,
+            cx,
+        );
         list.focus_handle(cx).focus(window);
         list
     })
@@ -80,8 +109,10 @@ pub struct BranchList {
 }
 
 impl BranchList {
+    /// Tags are only listed when there's a workspace to open their commits in.
     fn new(
         repository: Option<Entity<Repository>>,
+        workspace: Option<WeakEntity<Workspace>>,
         style: BranchListStyle,
         width: Rems,
         window: &mut Window,
@@ -133,8 +164,9 @@ impl BranchList {
         })
         .detach_and_log_err(cx);
 
-        let delegate = BranchListDelegate::new(repository.clone(), style);
+        let delegate = BranchListDelegate::new(repository.clone(), workspace, style);
         let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        picker.update(cx, |picker, cx| picker.delegate.reload_tags(window, cx));
 
         let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
             cx.emit(DismissEvent);
@@ -156,6 +188,18 @@ impl BranchList {
         self.picker
             .update(cx, |picker, _| picker.delegate.modifiers = ev.modifiers)
     }
+
+    fn handle_delete_tag(&mut self, _: &DeleteTag, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.delete_selected_tag(window, cx);
+        })
+    }
+
+    fn handle_push_tag(&mut self, _: &PushTag, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.push_selected_tag(window, cx);
+        })
+    }
 }
 impl ModalView for BranchList {}
 impl EventEmitter<DismissEvent> for BranchList {}
@@ -169,8 +213,11 @@ impl Focusable for BranchList {
 impl Render for BranchList {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         v_flex()
+            .key_context("BranchList")
             .w(self.width)
             .on_modifiers_changed(cx.listener(Self::handle_modifiers_changed))
+            .on_action(cx.listener(Self::handle_delete_tag))
+            .on_action(cx.listener(Self::handle_push_tag))
             .child(self.picker.clone())
             .on_mouse_down_out({
                 cx.listener(move |this, _, window, cx| {
@@ -183,16 +230,30 @@ impl Render for BranchList {
 }
 
 #[derive(Debug, Clone)]
-struct BranchEntry {
-    branch: Branch,
-    positions: Vec<usize>,
-    is_new: bool,
+enum BranchEntry {
+    Branch {
+        branch: Branch,
+        positions: Vec<usize>,
+    },
+    NewBranch {
+        name: String,
+    },
+    Tag {
+        tag: Tag,
+        positions: Vec<usize>,
+    },
+    /// A lightweight tag to create at `HEAD`.
+    NewTag {
+        name: String,
+    },
 }
 
 pub struct BranchListDelegate {
     matches: Vec<BranchEntry>,
     all_branches: Option<Vec<Branch>>,
+    all_tags: Option<Vec<Tag>>,
     repo: Option<Entity<Repository>>,
+    workspace: Option<WeakEntity<Workspace>>,
     style: BranchListStyle,
     selected_index: usize,
     last_query: String,
@@ -200,18 +261,114 @@ pub struct BranchListDelegate {
 }
 
 impl BranchListDelegate {
-    fn new(repo: Option<Entity<Repository>>, style: BranchListStyle) -> Self {
+    fn new(
+        repo: Option<Entity<Repository>>,
+        workspace: Option<WeakEntity<Workspace>>,
+        style: BranchListStyle,
+    ) -> Self {
         Self {
             matches: vec![],
             repo,
+            workspace,
             style,
             all_branches: None,
+            all_tags: None,
             selected_index: 0,
             last_query: Default::default(),
             modifiers: Default::default(),
         }
     }
 
+    fn reload_tags(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(repo), Some(_)) = (self.repo.clone(), self.workspace.as_ref()) else {
+            return;
+        };
+        let tags = repo.update(cx, |repo, _| repo.tags());
+        cx.spawn_in(window, async move |picker, cx| {
+            let tags = tags.await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.all_tags = Some(tags);
+                picker.refresh(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn selected_tag(&self) -> Option<&Tag> {
+        match self.matches.get(self.selected_index)? {
+            BranchEntry::Tag { tag, .. } => Some(tag),
+            _ => None,
+        }
+    }
+
+    fn create_tag(&self, name: String, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        cx.spawn_in(window, async move |picker, cx| {
+            repo.update(cx, |repo, _| repo.create_tag(name, "HEAD".into(), None))?
+                .await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.reload_tags(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to create tag", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn open_selected_tag(&self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(tag), Some(repo), Some(workspace)) = (
+            self.selected_tag(),
+            self.repo.as_ref(),
+            self.workspace.clone(),
+        ) else {
+            return;
+        };
+        let commit = CommitSummary {
+            sha: tag.sha.clone(),
+            subject: tag.message.clone().unwrap_or_default(),
+            commit_timestamp: tag.timestamp,
+            has_parent: true,
+        };
+        CommitView::open(commit, repo.downgrade(), workspace, window, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn delete_selected_tag(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(tag), Some(repo)) = (self.selected_tag().cloned(), self.repo.clone()) else {
+            return;
+        };
+        cx.spawn_in(window, async move |picker, cx| {
+            repo.update(cx, |repo, _| repo.delete_tag(tag.name.to_string()))?
+                .await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.reload_tags(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to delete tag", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn push_selected_tag(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(tag), Some(workspace)) = (self.selected_tag(), self.workspace.as_ref()) else {
+            return;
+        };
+        let Some(panel) = workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).panel::<GitPanel>(cx))
+        else {
+            return;
+        };
+        let name = tag.name.clone();
+        cx.emit(DismissEvent);
+        panel.update(cx, |panel, cx| panel.push_tag(name, window, cx));
+    }
+
     fn create_branch(
         &self,
         new_branch_name: SharedString,
@@ -245,7 +402,11 @@ impl PickerDelegate for BranchListDelegate {
     type ListItem = ListItem;
 
     fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
-        "Select branch…".into()
+        if self.workspace.is_some() {
+            "Select branch or tag…".into()
+        } else {
+            "Select branch…".into()
+        }
     }
 
     fn editor_position(&self) -> PickerEditorPosition {
@@ -281,18 +442,19 @@ impl PickerDelegate for BranchListDelegate {
         let Some(all_branches) = self.all_branches.clone() else {
             return Task::ready(());
         };
+        let all_tags = self.all_tags.clone();
 
         const RECENT_BRANCHES_COUNT: usize = 10;
+        const RECENT_TAGS_COUNT: usize = 5;
         cx.spawn_in(window, async move |picker, cx| {
             let mut matches: Vec<BranchEntry> = if query.is_empty() {
                 all_branches
                     .into_iter()
                     .filter(|branch| !branch.is_remote())
                     .take(RECENT_BRANCHES_COUNT)
-                    .map(|branch| BranchEntry {
+                    .map(|branch| BranchEntry::Branch {
                         branch,
                         positions: Vec::new(),
-                        is_new: false,
                     })
                     .collect()
             } else {
@@ -312,32 +474,64 @@ impl PickerDelegate for BranchListDelegate {
                 )
                 .await
                 .into_iter()
-                .map(|candidate| BranchEntry {
+                .map(|candidate| BranchEntry::Branch {
                     branch: all_branches[candidate.candidate_id].clone(),
                     positions: candidate.positions,
-                    is_new: false,
                 })
                 .collect()
             };
+            #[allow(clippy::nonminimal_bool)]
+            let is_new_branch = !query.is_empty()
+                && !matches.first().is_some_and(|entry| {
+                    matches!(entry, BranchEntry::Branch { branch, .. } if branch.name() == query)
+                });
+
+            let is_new_tag = match &all_tags {
+                Some(all_tags) if query.is_empty() => {
+                    matches.extend(all_tags.iter().take(RECENT_TAGS_COUNT).map(|tag| {
+                        BranchEntry::Tag {
+                            tag: tag.clone(),
+                            positions: Vec::new(),
+                        }
+                    }));
+                    false
+                }
+                Some(all_tags) => {
+                    let candidates = all_tags
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, tag)| StringMatchCandidate::new(ix, &tag.name))
+                        .collect::<Vec<StringMatchCandidate>>();
+                    matches.extend(
+                        fuzzy::match_strings(
+                            &candidates,
+                            &query,
+                            true,
+                            true,
+                            10000,
+                            &Default::default(),
+                            cx.background_executor().clone(),
+                        )
+                        .await
+                        .into_iter()
+                        .map(|candidate| BranchEntry::Tag {
+                            tag: all_tags[candidate.candidate_id].clone(),
+                            positions: candidate.positions,
+                        }),
+                    );
+                    !all_tags.iter().any(|tag| tag.name == query)
+                }
+                None => false,
+            };
+
             picker
                 .update(cx, |picker, _| {
-                    #[allow(clippy::nonminimal_bool)]
-                    if !query.is_empty()
-                        && !matches
-                            .first()
-                            .is_some_and(|entry| entry.branch.name() == query)
-                    {
-                        let query = query.replace(' ', "-");
-                        matches.push(BranchEntry {
-                            branch: Branch {
-                                ref_name: format!("refs/heads/{query}").into(),
-                                is_head: false,
-                                upstream: None,
-                                most_recent_commit: None,
-                            },
-                            positions: Vec::new(),
-                            is_new: true,
-                        })
+                    let name = query.replace(' ', "-");
+                    if is_new_branch {
+                        matches.push(BranchEntry::NewBranch { name: name.clone() });
+                    }
+                    if is_new_tag {
+                        matches.push(BranchEntry::NewTag { name });
                     }
                     let delegate = &mut picker.delegate;
                     delegate.matches = matches;
@@ -357,10 +551,21 @@ impl PickerDelegate for BranchListDelegate {
         let Some(entry) = self.matches.get(self.selected_index()) else {
             return;
         };
-        if entry.is_new {
-            self.create_branch(entry.branch.name().to_owned().into(), window, cx);
-            return;
-        }
+        let entry = match entry {
+            BranchEntry::Branch { branch, .. } => branch,
+            BranchEntry::NewBranch { name } => {
+                self.create_branch(name.clone().into(), window, cx);
+                return;
+            }
+            BranchEntry::Tag { .. } => {
+                self.open_selected_tag(window, cx);
+                return;
+            }
+            BranchEntry::NewTag { name } => {
+                self.create_tag(name.clone(), window, cx);
+                return;
+            }
+        };
 
         let current_branch = self.repo.as_ref().map(|repo| {
             repo.read_with(cx, |repo, _| {
@@ -370,14 +575,14 @@ impl PickerDelegate for BranchListDelegate {
 
         if current_branch
             .flatten()
-            .is_some_and(|current_branch| current_branch == entry.branch.ref_name)
+            .is_some_and(|current_branch| current_branch == entry.ref_name)
         {
             cx.emit(DismissEvent);
             return;
         }
 
         cx.spawn_in(window, {
-            let branch = entry.branch.clone();
+            let branch = entry.clone();
             async move |picker, cx| {
                 let branch_change_task = picker.update(cx, |this, cx| {
                     let repo = this
@@ -422,42 +627,80 @@ impl PickerDelegate for BranchListDelegate {
     ) -> Option<Self::ListItem> {
         let entry = &self.matches[ix];
 
-        let (commit_time, subject) = entry
-            .branch
-            .most_recent_commit
-            .as_ref()
-            .map(|commit| {
-                let subject = commit.subject.clone();
-                let commit_time = OffsetDateTime::from_unix_timestamp(commit.commit_timestamp)
-                    .unwrap_or_else(|_| OffsetDateTime::now_utc());
-                let formatted_time = format_local_timestamp(
-                    commit_time,
-                    OffsetDateTime::now_utc(),
-                    time_format::TimestampFormat::Relative,
-                );
-                (Some(formatted_time), Some(subject))
-            })
-            .unwrap_or_else(|| (None, None));
-
-        let branch_name = if entry.is_new {
+        let create_label = |icon: IconName, label: String| {
             h_flex()
                 .gap_1()
-                .child(
-                    Icon::new(IconName::Plus)
-                        .size(IconSize::Small)
-                        .color(Color::Muted),
-                )
-                .child(
-                    Label::new(format!("Create branch \"{}\"…", entry.branch.name()))
-                        .single_line()
-                        .truncate(),
-                )
-                .into_any_element()
-        } else {
-            HighlightedLabel::new(entry.branch.name().to_owned(), entry.positions.clone())
-                .truncate()
+                .child(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+                .child(Label::new(label).single_line().truncate())
                 .into_any_element()
         };
+        let based_off_head = || {
+            if let Some(current_branch) = self
+                .repo
+                .as_ref()
+                .and_then(|repo| repo.read(cx).branch.as_ref().map(|b| b.name()))
+            {
+                format!("based off {}", current_branch)
+            } else {
+                "based off the current branch".to_string()
+            }
+        };
+
+        let (name, timestamp, description) = match entry {
+            BranchEntry::Branch { branch, positions } => (
+                HighlightedLabel::new(branch.name().to_owned(), positions.clone())
+                    .truncate()
+                    .into_any_element(),
+                branch
+                    .most_recent_commit
+                    .as_ref()
+                    .map(|commit| commit.commit_timestamp),
+                branch
+                    .most_recent_commit
+                    .as_ref()
+                    .map_or("no commits found".to_string(), |commit| {
+                        commit.subject.to_string()
+                    }),
+            ),
+            BranchEntry::NewBranch { name } => (
+                create_label(IconName::Plus, format!("Create branch \"{name}\"…")),
+                None,
+                based_off_head(),
+            ),
+            BranchEntry::Tag { tag, positions } => (
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Hash)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(HighlightedLabel::new(tag.name.clone(), positions.clone()).truncate())
+                    .into_any_element(),
+                Some(tag.timestamp),
+                match &tag.message {
+                    Some(message) => message.to_string(),
+                    None => format!(
+                        "tag at {}",
+                        tag.sha.get(..SHORT_SHA_LENGTH).unwrap_or(&*tag.sha)
+                    ),
+                },
+            ),
+            BranchEntry::NewTag { name } => (
+                create_label(IconName::Plus, format!("Create tag \"{name}\"…")),
+                None,
+                "at the current commit".to_string(),
+            ),
+        };
+        let timestamp = timestamp.map(|timestamp| {
+            let time = OffsetDateTime::from_unix_timestamp(timestamp)
+                .unwrap_or_else(|_| OffsetDateTime::now_utc());
+            format_local_timestamp(
+                time,
+                OffsetDateTime::now_utc(),
+                time_format::TimestampFormat::Relative,
+            )
+        });
 
         Some(
             ListItem::new(SharedString::from(format!("vcs-menu-{ix}")))
@@ -473,10 +716,10 @@ impl PickerDelegate for BranchListDelegate {
                                 .gap_6()
                                 .justify_between()
                                 .overflow_x_hidden()
-                                .child(branch_name)
-                                .when_some(commit_time, |label, commit_time| {
+                                .child(name)
+                                .when_some(timestamp, |label, timestamp| {
                                     label.child(
-                                        Label::new(commit_time)
+                                        Label::new(timestamp)
                                             .size(LabelSize::Small)
                                             .color(Color::Muted)
                                             .into_element(),
@@ -484,31 +727,71 @@ impl PickerDelegate for BranchListDelegate {
                                 }),
                         )
                         .when(self.style == BranchListStyle::Modal, |el| {
-                            el.child(div().max_w_96().child({
-                                let message = if entry.is_new {
-                                    if let Some(current_branch) =
-                                        self.repo.as_ref().and_then(|repo| {
-                                            repo.read(cx).branch.as_ref().map(|b| b.name())
-                                        })
-                                    {
-                                        format!("based off {}", current_branch)
-                                    } else {
-                                        "based off the current branch".to_string()
-                                    }
-                                } else {
-                                    subject.unwrap_or("no commits found".into()).to_string()
-                                };
-                                Label::new(message)
-                                    .size(LabelSize::Small)
-                                    .truncate()
-                                    .color(Color::Muted)
-                            }))
+                            el.child(
+                                div().max_w_96().child(
+                                    Label::new(description)
+                                        .size(LabelSize::Small)
+                                        .truncate()
+                                        .color(Color::Muted),
+                                ),
+                            )
                         }),
                 ),
         )
     }
 
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        self.selected_tag()?;
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(footer_button(
+                    "delete-tag",
+                    "Delete",
+                    DeleteTag.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .child(footer_button(
+                    "push-tag",
+                    "Push",
+                    PushTag.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .child(footer_button(
+                    "view-tag",
+                    "View",
+                    menu::Confirm.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .into_any(),
+        )
+    }
+
     fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
         None
     }
 }
+
+fn footer_button(
+    id: &'static str,
+    label: &'static str,
+    action: Box<dyn Action>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Button {
+    Button::new(id, label)
+        .key_binding(KeyBinding::for_action(&*action, window, cx))
+        .on_click(move |_, window, cx| window.dispatch_action(action.boxed_clone(), cx))
+}
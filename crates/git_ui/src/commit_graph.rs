@@ -0,0 +1,237 @@
+//! Lays out the lanes of the branch graph drawn next to the commit history.
+//!
+//! Commits are added one at a time, newest first, in topological order. Each lane
+//! remembers which commit it is waiting for, so that the graph can be extended as
+//! more pages of history are loaded.
+
+use gpui::SharedString;
+
+/// A line drawn within a single row of the graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphEdge {
+    /// A lane passing by the row's commit, from the top of the row to the bottom.
+    PassThrough { lane: usize },
+    /// A child of the row's commit, from the top of the row into the commit.
+    Incoming { from_lane: usize },
+    /// A parent of the row's commit, from the commit to the bottom of the row.
+    Outgoing { to_lane: usize },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphRow {
+    /// The lane in which the row's commit is drawn.
+    pub lane: usize,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl GraphRow {
+    /// The number of lanes needed to draw this row.
+    pub fn width(&self) -> usize {
+        self.edges
+            .iter()
+            .map(|edge| match *edge {
+                GraphEdge::PassThrough { lane } => lane,
+                GraphEdge::Incoming { from_lane } => from_lane,
+                GraphEdge::Outgoing { to_lane } => to_lane,
+            })
+            .chain([self.lane])
+            .max()
+            .unwrap_or_default()
+            + 1
+    }
+}
+
+pub struct CommitGraph {
+    /// The commit that each lane continues to, or `None` for free lanes.
+    lanes: Vec<Option<SharedString>>,
+    rows: Vec<GraphRow>,
+    connect_parents: bool,
+}
+
+impl CommitGraph {
+    /// Creates an empty graph. Without `connect_parents`, every commit is drawn in the first
+    /// lane on its own: logs filtered by author or message skip the commits in between, so
+    /// their parents mostly never show up.
+    pub fn new(connect_parents: bool) -> Self {
+        Self {
+            lanes: Vec::new(),
+            rows: Vec::new(),
+            connect_parents,
+        }
+    }
+
+    pub fn rows(&self) -> &[GraphRow] {
+        &self.rows
+    }
+
+    pub fn push(&mut self, sha: &SharedString, parents: &[SharedString]) {
+        let parents = if self.connect_parents { parents } else { &[] };
+        let mut edges = Vec::new();
+        let mut lane = None;
+        for (ix, expected) in self.lanes.iter_mut().enumerate() {
+            let Some(expected_sha) = expected else {
+                continue;
+            };
+            if expected_sha == sha {
+                lane.get_or_insert(ix);
+                edges.push(GraphEdge::Incoming { from_lane: ix });
+                *expected = None;
+            } else {
+                edges.push(GraphEdge::PassThrough { lane: ix });
+            }
+        }
+        let lane = lane.unwrap_or_else(|| self.free_lane());
+
+        for (ix, parent) in parents.iter().enumerate() {
+            let to_lane = match self
+                .lanes
+                .iter()
+                .position(|expected| expected.as_ref() == Some(parent))
+            {
+                Some(existing_lane) => existing_lane,
+                None => {
+                    let to_lane = if ix == 0 { lane } else { self.free_lane() };
+                    self.lanes[to_lane] = Some(parent.clone());
+                    to_lane
+                }
+            };
+            edges.push(GraphEdge::Outgoing { to_lane });
+        }
+
+        while self.lanes.last().is_some_and(Option::is_none) {
+            self.lanes.pop();
+        }
+        self.rows.push(GraphRow { lane, edges });
+    }
+
+    fn free_lane(&mut self) -> usize {
+        if let Some(ix) = self.lanes.iter().position(Option::is_none) {
+            ix
+        } else {
+            self.lanes.push(None);
+            self.lanes.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(commits: &[(&str, &[&str])]) -> Vec<GraphRow> {
+        graph_with(CommitGraph::new(true), commits)
+    }
+
+    fn graph_with(mut graph: CommitGraph, commits: &[(&str, &[&str])]) -> Vec<GraphRow> {
+        for (sha, parents) in commits {
+            let parents = parents
+                .iter()
+                .map(|parent| SharedString::from(parent.to_string()))
+                .collect::<Vec<_>>();
+            graph.push(&SharedString::from(sha.to_string()), &parents);
+        }
+        graph.rows
+    }
+
+    #[test]
+    fn test_linear_history() {
+        let rows = graph(&[("c", &["b"]), ("b", &["a"]), ("a", &[])]);
+        assert_eq!(
+            rows,
+            [
+                GraphRow {
+                    lane: 0,
+                    edges: vec![GraphEdge::Outgoing { to_lane: 0 }],
+                },
+                GraphRow {
+                    lane: 0,
+                    edges: vec![
+                        GraphEdge::Incoming { from_lane: 0 },
+                        GraphEdge::Outgoing { to_lane: 0 },
+                    ],
+                },
+                GraphRow {
+                    lane: 0,
+                    edges: vec![GraphEdge::Incoming { from_lane: 0 }],
+                },
+            ]
+        );
+        assert!(rows.iter().all(|row| row.width() == 1));
+    }
+
+    #[test]
+    fn test_branch_and_merge() {
+        // d merges c into b, and both b and c branched off a.
+        let rows = graph(&[("d", &["b", "c"]), ("c", &["a"]), ("b", &["a"]), ("a", &[])]);
+        assert_eq!(
+            rows,
+            [
+                GraphRow {
+                    lane: 0,
+                    edges: vec![
+                        GraphEdge::Outgoing { to_lane: 0 },
+                        GraphEdge::Outgoing { to_lane: 1 },
+                    ],
+                },
+                GraphRow {
+                    lane: 1,
+                    edges: vec![
+                        GraphEdge::PassThrough { lane: 0 },
+                        GraphEdge::Incoming { from_lane: 1 },
+                        GraphEdge::Outgoing { to_lane: 1 },
+                    ],
+                },
+                GraphRow {
+                    lane: 0,
+                    edges: vec![
+                        GraphEdge::Incoming { from_lane: 0 },
+                        GraphEdge::PassThrough { lane: 1 },
+                        GraphEdge::Outgoing { to_lane: 1 },
+                    ],
+                },
+                GraphRow {
+                    lane: 1,
+                    edges: vec![GraphEdge::Incoming { from_lane: 1 }],
+                },
+            ]
+        );
+        assert_eq!(
+            rows.iter().map(GraphRow::width).collect::<Vec<_>>(),
+            [2, 2, 2, 2]
+        );
+    }
+
+    #[test]
+    fn test_unrelated_branch_tips() {
+        // With all branches shown, a tip that no loaded commit points at gets its own lane.
+        let rows = graph(&[("b", &["a"]), ("x", &["a"]), ("a", &[])]);
+        assert_eq!(rows[1].lane, 1);
+        assert_eq!(
+            rows[1].edges,
+            [
+                GraphEdge::PassThrough { lane: 0 },
+                GraphEdge::Outgoing { to_lane: 0 },
+            ]
+        );
+        assert_eq!(rows[2].lane, 0);
+    }
+
+    #[test]
+    fn test_unlisted_parents() {
+        // Every parent left open takes up a lane of its own, for all the rows below it.
+        let commits: &[(&str, &[&str])] = &[("c", &["x"]), ("b", &["y"]), ("a", &["z"])];
+        assert_eq!(
+            graph(commits)
+                .iter()
+                .map(GraphRow::width)
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+
+        let rows = graph_with(CommitGraph::new(false), commits);
+        assert!(
+            rows.iter()
+                .all(|row| row.lane == 0 && row.edges.is_empty() && row.width() == 1)
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use anyhow::{Context as _, Result};
 use buffer_diff::{BufferDiff, BufferDiffSnapshot};
 use editor::{Editor, EditorEvent, MultiBuffer, SelectionEffects};
-use git::repository::{CommitDetails, CommitDiff, CommitSummary, RepoPath};
+use futures::channel::oneshot;
+use git::repository::{
+    CommitDetails, CommitDiff, CommitSummary, RebaseCommand, RepoPath, SequencerOperation,
+    StashEntry,
+};
 use gpui::{
-    AnyElement, AnyView, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter,
-    FocusHandle, Focusable, IntoElement, Render, WeakEntity, Window,
+    AnyElement, AnyView, App, AppContext as _, AsyncApp, ClickEvent, Context, Entity, EventEmitter,
+    FocusHandle, Focusable, IntoElement, PromptLevel, Render, WeakEntity, Window, actions,
 };
 use language::{
     Anchor, Buffer, Capability, DiskState, File, LanguageRegistry, LineEnding, OffsetRangeExt as _,
@@ -19,29 +23,84 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use ui::{Color, Icon, IconName, Label, LabelCommon as _, SharedString};
+use ui::{Tooltip, prelude::*};
 use util::{ResultExt, truncate_and_trailoff};
+use workspace::notifications::DetachAndPromptErr;
 use workspace::{
     Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
     item::{BreadcrumbText, ItemEvent, TabContentParams},
     searchable::SearchableItemHandle,
 };
 
+actions!(
+    commit_view,
+    [
+        /// Applies the changes of the commit on top of the current branch.
+        CherryPick,
+        /// Commits the inverse of the changes of the commit.
+        Revert,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::ContinueCherryPick, window, cx| {
+        let operation = SequencerOperation::CherryPick;
+        run_sequencer_command(workspace, operation, RebaseCommand::Continue, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::AbortCherryPick, window, cx| {
+        let operation = SequencerOperation::CherryPick;
+        run_sequencer_command(workspace, operation, RebaseCommand::Abort, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::ContinueRevert, window, cx| {
+        let operation = SequencerOperation::Revert;
+        run_sequencer_command(workspace, operation, RebaseCommand::Continue, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::AbortRevert, window, cx| {
+        let operation = SequencerOperation::Revert;
+        run_sequencer_command(workspace, operation, RebaseCommand::Abort, window, cx);
+    });
+}
+
+fn run_sequencer_command(
+    workspace: &mut Workspace,
+    operation: SequencerOperation,
+    command: RebaseCommand,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let job = repository.update(cx, |repository, _| {
+        repository.sequencer_command(operation, command)
+    });
+    cx.background_spawn(async move { job.await? })
+        .detach_and_prompt_err(
+            &format!("Failed to {}", operation.as_str()),
+            window,
+            cx,
+            |e, _, _| Some(e.to_string()),
+        );
+}
+
 pub struct CommitView {
     commit: CommitDetails,
     editor: Entity<Editor>,
     multibuffer: Entity<MultiBuffer>,
+    repository: WeakEntity<Repository>,
+    /// Stashes are shown like commits, but can't be cherry-picked or reverted.
+    is_stash: bool,
 }
 
-struct GitBlob {
-    path: RepoPath,
-    worktree_id: WorktreeId,
-    is_deleted: bool,
+pub(crate) struct GitBlob {
+    pub(crate) path: RepoPath,
+    pub(crate) worktree_id: WorktreeId,
+    pub(crate) is_deleted: bool,
 }
 
-struct CommitMetadataFile {
-    title: Arc<Path>,
-    worktree_id: WorktreeId,
+pub(crate) struct CommitMetadataFile {
+    pub(crate) title: Arc<Path>,
+    pub(crate) worktree_id: WorktreeId,
 }
 
 const COMMIT_METADATA_NAMESPACE: u32 = 0;
@@ -58,9 +117,31 @@ impl CommitView {
         let commit_diff = repo
             .update(cx, |repo, _| repo.load_commit_diff(commit.sha.to_string()))
             .ok();
-        let commit_details = repo
-            .update(cx, |repo, _| repo.show(commit.sha.to_string()))
-            .ok();
+        Self::open_with_diff(commit.sha, commit_diff, false, repo, workspace, window, cx);
+    }
+
+    /// Opens the changes saved in a stash entry, including its untracked files.
+    pub fn open_stash(
+        entry: StashEntry,
+        repo: WeakEntity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let stash_diff = repo.update(cx, |repo, _| repo.stash_diff(entry.index)).ok();
+        Self::open_with_diff(entry.sha, stash_diff, true, repo, workspace, window, cx);
+    }
+
+    fn open_with_diff(
+        sha: SharedString,
+        commit_diff: Option<oneshot::Receiver<Result<CommitDiff>>>,
+        is_stash: bool,
+        repo: WeakEntity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let commit_details = repo.update(cx, |repo, _| repo.show(sha.to_string())).ok();
 
         window
             .spawn(cx, async move |cx| {
@@ -76,6 +157,7 @@ impl CommitView {
                             CommitView::new(
                                 commit_details,
                                 commit_diff,
+                                is_stash,
                                 repo,
                                 project.clone(),
                                 window,
@@ -87,8 +169,7 @@ impl CommitView {
                         pane.update(cx, |pane, cx| {
                             let ix = pane.items().position(|item| {
                                 let commit_view = item.downcast::<CommitView>();
-                                commit_view
-                                    .map_or(false, |view| view.read(cx).commit.sha == commit.sha)
+                                commit_view.map_or(false, |view| view.read(cx).commit.sha == sha)
                             });
                             if let Some(ix) = ix {
                                 pane.activate_item(ix, true, true, window, cx);
@@ -106,6 +187,7 @@ impl CommitView {
     fn new(
         commit: CommitDetails,
         commit_diff: CommitDiff,
+        is_stash: bool,
         repository: Entity<Repository>,
         project: Entity<Project>,
         window: &mut Window,
@@ -160,6 +242,7 @@ impl CommitView {
             });
         }
 
+        let weak_repository = repository.downgrade();
         cx.spawn(async move |this, mut cx| {
             for file in commit_diff.files {
                 let is_deleted = file.new_text.is_none();
@@ -211,8 +294,94 @@ impl CommitView {
             commit,
             editor,
             multibuffer,
+            repository: weak_repository,
+            is_stash,
         }
     }
+
+    fn cherry_pick(&mut self, _: &CherryPick, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply(SequencerOperation::CherryPick, window, cx);
+    }
+
+    fn revert(&mut self, _: &Revert, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply(SequencerOperation::Revert, window, cx);
+    }
+
+    /// Cherry-picks or reverts the commit, explaining how to go on when that stops because
+    /// of conflicts.
+    fn apply(
+        &mut self,
+        operation: SequencerOperation,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_stash {
+            return;
+        }
+        let Some(repository) = self.repository.upgrade() else {
+            return;
+        };
+        let commits = vec![self.commit.sha.to_string()];
+        let job = repository.update(cx, |repository, _| match operation {
+            SequencerOperation::CherryPick => repository.cherry_pick(commits),
+            SequencerOperation::Revert => repository.revert(commits),
+        });
+        cx.spawn_in(window, async move |_, cx| {
+            job.await??;
+            let state = repository
+                .update(cx, |repository, _| repository.sequencer_state())?
+                .await??;
+            if state.is_some() {
+                let message = format!("The {} stopped because of conflicts", operation.as_str());
+                let continue_action = match operation {
+                    SequencerOperation::CherryPick => "git: continue cherry pick",
+                    SequencerOperation::Revert => "git: continue revert",
+                };
+                let detail = format!(
+                    "Resolve the conflicts and stage the files, then run \"{continue_action}\"."
+                );
+                let prompt = cx.update(|window, cx| {
+                    window.prompt(PromptLevel::Warning, &message, Some(&detail), &["Ok"], cx)
+                })?;
+                prompt.await.ok();
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err(
+            &format!("Failed to {}", operation.as_str()),
+            window,
+            cx,
+            |e, _, _| Some(e.to_string()),
+        );
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .justify_end()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Button::new("cherry-pick", "Cherry-pick")
+                    .tooltip(Tooltip::text(
+                        "Apply the changes of this commit on top of the current branch",
+                    ))
+                    .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                        this.cherry_pick(&CherryPick, window, cx);
+                    })),
+            )
+            .child(
+                Button::new("revert", "Revert")
+                    .tooltip(Tooltip::text(
+                        "Commit the inverse of the changes of this commit",
+                    ))
+                    .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                        this.revert(&Revert, window, cx);
+                    })),
+            )
+    }
 }
 
 impl language::File for GitBlob {
@@ -287,7 +456,7 @@ impl language::File for CommitMetadataFile {
     }
 }
 
-async fn build_buffer(
+pub(crate) async fn build_buffer(
     mut text: String,
     blob: Arc<dyn File>,
     language_registry: &Arc<language::LanguageRegistry>,
@@ -320,7 +489,7 @@ async fn build_buffer(
     Ok(buffer)
 }
 
-async fn build_buffer_diff(
+pub(crate) async fn build_buffer_diff(
     mut old_text: Option<String>,
     buffer: &Entity<Buffer>,
     language_registry: &Arc<LanguageRegistry>,
@@ -517,7 +686,13 @@ impl Item for CommitView {
 }
 
 impl Render for CommitView {
-    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
-        self.editor.clone()
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("CommitView")
+            .size_full()
+            .on_action(cx.listener(Self::cherry_pick))
+            .on_action(cx.listener(Self::revert))
+            .when(!self.is_stash, |this| this.child(self.render_toolbar(cx)))
+            .child(div().flex_1().min_h_0().child(self.editor.clone()))
     }
 }
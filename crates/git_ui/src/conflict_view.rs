@@ -17,6 +17,8 @@ use ui::{
 };
 use util::{ResultExt as _, debug_panic, maybe};
 
+use crate::merge_view::MergeView;
+
 pub(crate) struct ConflictAddon {
     buffers: HashMap<BufferId, BufferConflicts>,
 }
@@ -475,9 +477,45 @@ fn render_conflict_buttons(
                     }
                 }),
         )
+        .child(
+            div()
+                .id("merge-editor")
+                .px_1()
+                .child("Open Merge Editor")
+                .rounded_t(rems(0.2))
+                .text_ui_sm(cx)
+                .hover(|this| this.bg(cx.theme().colors().element_background))
+                .cursor_pointer()
+                .on_click({
+                    let buffer_id = conflict.ours.end.buffer_id;
+                    move |_, window, cx| open_merge_editor(editor.clone(), buffer_id, window, cx)
+                }),
+        )
         .into_any()
 }
 
+fn open_merge_editor(
+    editor: WeakEntity<Editor>,
+    buffer_id: Option<BufferId>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    maybe!({
+        let editor = editor.upgrade()?;
+        let editor = editor.read(cx);
+        let workspace = editor.workspace()?;
+        let (repository, path) = editor
+            .project
+            .as_ref()?
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer_id?, cx)?;
+        MergeView::open(repository, path, workspace.downgrade(), window, cx);
+        Some(())
+    });
+}
+
 pub(crate) fn resolve_conflict(
     editor: WeakEntity<Editor>,
     excerpt_id: ExcerptId,
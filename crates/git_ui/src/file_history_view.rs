@@ -0,0 +1,519 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::Write as _,
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use editor::{Editor, EditorEvent, MultiBuffer, SelectionEffects, scroll::Autoscroll};
+use git::repository::{FileHistoryEntry, LogEntry, RepoPath};
+use gpui::{
+    AnyElement, AnyView, App, Context, Entity, EventEmitter, FocusHandle, Focusable, WeakEntity,
+    Window, actions,
+};
+use language::{Bias, Buffer, Capability, LineEnding, Point, TextBuffer};
+use multi_buffer::PathKey;
+use project::{Project, git_store::Repository};
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::prelude::*;
+use workspace::{
+    Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
+    item::{BreadcrumbText, ItemEvent, TabContentParams},
+    searchable::SearchableItemHandle,
+};
+
+use crate::commit_view::{
+    CommitMetadataFile, CommitView, GitBlob, build_buffer, build_buffer_diff,
+};
+
+actions!(
+    file_history,
+    [
+        /// Moves to the next, older commit in the history.
+        NextCommit,
+        /// Moves to the previous, newer commit in the history.
+        PreviousCommit,
+        /// Opens all the changes of the commit under the cursor.
+        OpenCommit,
+    ]
+);
+
+/// The number of commits that are loaded into the history.
+const MAX_COMMITS: usize = 200;
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::FileHistory, window, cx| {
+        FileHistoryView::deploy(workspace, false, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::SelectionHistory, window, cx| {
+        FileHistoryView::deploy(workspace, true, window, cx);
+    });
+}
+
+struct HistoryCommit {
+    entry: LogEntry,
+    header_key: PathKey,
+}
+
+/// Shows every revision of a file, or of a range of lines in it, as a diff in a
+/// multibuffer, newest first.
+pub struct FileHistoryView {
+    path: RepoPath,
+    rows: Option<Range<u32>>,
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    editor: Entity<Editor>,
+    multibuffer: Entity<MultiBuffer>,
+    commits: Vec<HistoryCommit>,
+    is_loading: bool,
+}
+
+impl FileHistoryView {
+    /// Opens the history of the file in the active editor, limited to the lines of its
+    /// newest selection when `selection` is true.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        selection: bool,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return;
+        };
+        let Some((buffer, buffer_rows)) = editor.update(cx, |editor, cx| {
+            let selection = editor.selections.newest::<Point>(cx);
+            let multi_buffer = editor.buffer().read(cx);
+            let snapshot = multi_buffer.snapshot(cx);
+            let buffer_ranges = snapshot.range_to_buffer_ranges(selection.range());
+            let (buffer, range, _) = buffer_ranges.first()?;
+            let start = buffer.offset_to_point(range.start);
+            let end = buffer.offset_to_point(range.end);
+            // A selection ending at the start of a line doesn't include that line.
+            let end_row = if end.column == 0 && end.row > start.row {
+                end.row
+            } else {
+                end.row + 1
+            };
+            let buffer = multi_buffer.buffer(buffer.remote_id())?;
+            Some((buffer, start.row..end_row))
+        }) else {
+            return;
+        };
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        let git_store = workspace.project().read(cx).git_store().clone();
+        let Some((repository, path)) = git_store
+            .read(cx)
+            .repository_and_path_for_project_path(&project_path, cx)
+        else {
+            return;
+        };
+        let rows = selection.then_some(buffer_rows);
+        telemetry::event!("Git File History Opened", selection);
+
+        let existing = workspace.items_of_type::<Self>(cx).find(|view| {
+            let view = view.read(cx);
+            view.repository == repository && view.path == path && view.rows == rows
+        });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let workspace_handle = cx.weak_entity();
+        let view = cx.new(|cx| {
+            Self::new(
+                repository,
+                path,
+                rows,
+                project,
+                workspace_handle,
+                window,
+                cx,
+            )
+        });
+        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        path: RepoPath,
+        rows: Option<Range<u32>>,
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
+        let editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), window, cx);
+            editor.disable_inline_diagnostics();
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let language_registry = project.read(cx).languages().clone();
+        let fallback_worktree_id = project
+            .read(cx)
+            .worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).id());
+        let history = repository.update(cx, |repository, _| {
+            repository.file_history(path.clone(), rows.clone(), MAX_COMMITS)
+        });
+        cx.spawn(async move |this, cx| {
+            let history = history.await??;
+            if history.is_empty() {
+                this.update(cx, |this, cx| {
+                    this.is_loading = false;
+                    cx.notify();
+                })?;
+            }
+
+            let history_len = history.len();
+            for (ix, FileHistoryEntry { commit, file, rows }) in history.into_iter().enumerate() {
+                let worktree_id = this
+                    .update(cx, |this, cx| {
+                        this.repository
+                            .read(cx)
+                            .repo_path_to_project_path(&file.path, cx)
+                            .map(|path| path.worktree_id)
+                            .or(fallback_worktree_id)
+                    })?
+                    .context("project has no worktrees")?;
+
+                let header_file = Arc::new(CommitMetadataFile {
+                    title: PathBuf::from(format!("commit {}", commit.sha)).into(),
+                    worktree_id,
+                });
+                let header_text = format_history_commit(&commit);
+                let header_buffer = cx.new(|cx| {
+                    let buffer = TextBuffer::new_normalized(
+                        0,
+                        cx.entity_id().as_non_zero_u64().into(),
+                        LineEnding::default(),
+                        header_text.into(),
+                    );
+                    Buffer::build(buffer, Some(header_file.clone()), Capability::ReadWrite)
+                })?;
+
+                let blob = Arc::new(GitBlob {
+                    path: file.path.clone(),
+                    is_deleted: file.new_text.is_none(),
+                    worktree_id,
+                }) as Arc<dyn language::File>;
+                let buffer = build_buffer(
+                    file.new_text.unwrap_or_default(),
+                    blob,
+                    &language_registry,
+                    cx,
+                )
+                .await?;
+                let buffer_diff =
+                    build_buffer_diff(file.old_text, &buffer, &language_registry, cx).await?;
+
+                this.update(cx, |this, cx| {
+                    // Each commit's header and file are kept together, and in order, by
+                    // giving them consecutive namespaces.
+                    let header_key = PathKey::namespaced(2 * ix as u32, header_file.title.clone());
+                    let file_key = PathKey::namespaced(2 * ix as u32 + 1, file.path.0.clone());
+                    this.multibuffer.update(cx, |multibuffer, cx| {
+                        let header_range = Point::zero()..header_buffer.read(cx).max_point();
+                        multibuffer.set_excerpts_for_path(
+                            header_key.clone(),
+                            header_buffer.clone(),
+                            [header_range],
+                            0,
+                            cx,
+                        );
+
+                        let snapshot = buffer.read(cx).snapshot();
+                        let ranges = rows.iter().map(|rows| {
+                            snapshot.clip_point(Point::new(rows.start, 0), Bias::Left)
+                                ..snapshot.clip_point(Point::new(rows.end, 0), Bias::Left)
+                        });
+                        multibuffer.set_excerpts_for_path(
+                            file_key,
+                            buffer,
+                            ranges,
+                            editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                            cx,
+                        );
+                        multibuffer.add_diff(buffer_diff, cx);
+                    });
+                    this.editor.update(cx, |editor, cx| {
+                        editor.disable_header_for_buffer(header_buffer.read(cx).remote_id(), cx);
+                    });
+                    this.commits.push(HistoryCommit {
+                        entry: commit,
+                        header_key,
+                    });
+                    this.is_loading = ix + 1 < history_len;
+                    cx.notify();
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        Self {
+            path,
+            rows,
+            repository,
+            workspace,
+            editor,
+            multibuffer,
+            commits: Vec::new(),
+            is_loading: true,
+        }
+    }
+
+    /// The commit whose changes the cursor is in.
+    fn current_commit(&self, cx: &App) -> Option<usize> {
+        let multibuffer = self.multibuffer.read(cx);
+        let snapshot = multibuffer.snapshot(cx);
+        let position = self.editor.read(cx).selections.newest_anchor().head();
+        self.commits
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(ix, commit)| {
+                let location = multibuffer.location_for_path(&commit.header_key, cx)?;
+                location.cmp(&position, &snapshot).is_le().then_some(ix)
+            })
+    }
+
+    fn move_to_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(commit) = self.commits.get(ix) else {
+            return;
+        };
+        let Some(position) = self
+            .multibuffer
+            .read(cx)
+            .location_for_path(&commit.header_key, cx)
+        else {
+            return;
+        };
+        self.editor.update(cx, |editor, cx| {
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::top_relative(0)),
+                window,
+                cx,
+                |selections| selections.select_ranges([position..position]),
+            );
+        });
+    }
+
+    fn next_commit(&mut self, _: &NextCommit, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.current_commit(cx).map_or(0, |ix| ix + 1);
+        self.move_to_commit(ix, window, cx);
+    }
+
+    fn previous_commit(&mut self, _: &PreviousCommit, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.current_commit(cx).and_then(|ix| ix.checked_sub(1)) {
+            self.move_to_commit(ix, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, _: &OpenCommit, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.current_commit(cx) else {
+            return;
+        };
+        CommitView::open(
+            self.commits[ix].entry.to_commit_summary(),
+            self.repository.downgrade(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+}
+
+fn format_history_commit(commit: &LogEntry) -> String {
+    let mut result = String::new();
+    writeln!(&mut result, "commit {}", commit.sha).unwrap();
+    writeln!(
+        &mut result,
+        "Author: {} <{}>",
+        commit.author_name, commit.author_email
+    )
+    .unwrap();
+    if let Ok(commit_time) = OffsetDateTime::from_unix_timestamp(commit.commit_timestamp) {
+        writeln!(
+            &mut result,
+            "Date:   {}",
+            format_local_timestamp(
+                commit_time,
+                OffsetDateTime::now_utc(),
+                time_format::TimestampFormat::MediumAbsolute,
+            ),
+        )
+        .unwrap();
+    }
+    result.push('\n');
+    write!(&mut result, "    {}", commit.subject).unwrap();
+    result
+}
+
+impl EventEmitter<EditorEvent> for FileHistoryView {}
+
+impl Focusable for FileHistoryView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for FileHistoryView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match &self.rows {
+            Some(rows) => format!("History: {file_name}:{}-{}", rows.start + 1, rows.end).into(),
+            None => format!("History: {file_name}").into(),
+        }
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("History of {}", self.path.display()).into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git File History Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        false
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+}
+
+impl Render for FileHistoryView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_empty = self.commits.is_empty();
+        v_flex()
+            .key_context("FileHistory")
+            .size_full()
+            .on_action(cx.listener(Self::next_commit))
+            .on_action(cx.listener(Self::previous_commit))
+            .on_action(cx.listener(Self::open_commit))
+            .map(|this| {
+                if is_empty {
+                    this.bg(cx.theme().colors().editor_background).child(
+                        h_flex().size_full().justify_center().child(
+                            Label::new(if self.is_loading {
+                                "Loading history…"
+                            } else {
+                                "No commits changed this file"
+                            })
+                            .color(Color::Muted),
+                        ),
+                    )
+                } else {
+                    this.child(self.editor.clone())
+                }
+            })
+    }
+}
@@ -3,6 +3,7 @@ use crate::commit_modal::CommitModal;
 use crate::commit_tooltip::CommitTooltip;
 use crate::commit_view::CommitView;
 use crate::git_panel_settings::StatusStyle;
+use crate::merge_view::MergeView;
 use crate::project_diff::{self, Diff, ProjectDiff};
 use crate::remote_output::{self, RemoteAction, SuccessMessage};
 use crate::{branch_picker, picker_prompt, render_remote_button};
@@ -17,16 +18,19 @@ use editor::{
     Editor, EditorElement, EditorMode, EditorSettings, MultiBuffer, ShowScrollbar,
     scroll::ScrollbarAutoHide,
 };
-use futures::StreamExt as _;
+use futures::{StreamExt as _, channel::oneshot};
 use git::blame::ParsedCommitMessage;
 use git::repository::{
     Branch, CommitDetails, CommitOptions, CommitSummary, DiffType, FetchOptions, GitCommitter,
-    PushOptions, Remote, RemoteCommandOutput, ResetMode, Upstream, UpstreamTracking,
-    UpstreamTrackingStatus, get_git_committer,
+    GitWorktree, PushOptions, Remote, RemoteCommandOutput, ResetMode, StashScope, Upstream,
+    UpstreamTracking, UpstreamTrackingStatus, get_git_committer,
 };
 use git::status::StageStatus;
 use git::{Amend, ToggleStaged, repository::RepoPath, status::FileStatus};
-use git::{ExpandCommitEditor, RestoreTrackedFiles, StageAll, TrashUntrackedFiles, UnstageAll};
+use git::{
+    ExpandCommitEditor, RestoreTrackedFiles, StageAll, StashAll, StashApply, StashPop, StashStaged,
+    TrashUntrackedFiles, UnstageAll, ViewStash,
+};
 use gpui::{
     Action, Animation, AnimationExt as _, AsyncApp, AsyncWindowContext, Axis, ClickEvent, Corner,
     DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, KeyContext,
@@ -139,7 +143,22 @@ fn git_panel_context_menu(
                 UnstageAll.boxed_clone(),
             )
             .separator()
+            .action_disabled_when(
+                !state.has_tracked_changes && !state.has_new_changes,
+                "Stash All",
+                StashAll.boxed_clone(),
+            )
+            .action_disabled_when(
+                !state.has_staged_changes,
+                "Stash Staged",
+                StashStaged.boxed_clone(),
+            )
+            .action("Pop Stash", StashPop.boxed_clone())
+            .action("View Stash…", ViewStash.boxed_clone())
+            .separator()
             .action("Open Diff", project_diff::Diff.boxed_clone())
+            .action("View History", git::ViewHistory.boxed_clone())
+            .action("Rebase…", git::Rebase.boxed_clone())
             .separator()
             .action_disabled_when(
                 !state.has_tracked_changes,
@@ -348,6 +367,8 @@ pub struct GitPanel {
     tracked_count: usize,
     tracked_staged_count: usize,
     update_visible_entries_task: Task<()>,
+    worktrees: Vec<GitWorktree>,
+    load_worktrees_task: Task<()>,
     width: Option<Pixels>,
     workspace: WeakEntity<Workspace>,
     context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
@@ -467,6 +488,7 @@ impl GitPanel {
                     GitStoreEvent::ActiveRepositoryChanged(_) => {
                         this.active_repository = this.project.read(cx).active_repository(cx);
                         this.schedule_update(true, window, cx);
+                        this.load_worktrees(cx);
                     }
                     GitStoreEvent::RepositoryUpdated(
                         _,
@@ -474,6 +496,9 @@ impl GitPanel {
                         true,
                     ) => {
                         this.schedule_update(*full_scan, window, cx);
+                        if *full_scan {
+                            this.load_worktrees(cx);
+                        }
                     }
 
                     GitStoreEvent::RepositoryAdded(_) | GitStoreEvent::RepositoryRemoved(_) => {
@@ -519,6 +544,8 @@ impl GitPanel {
                 tracked_count: 0,
                 tracked_staged_count: 0,
                 update_visible_entries_task: Task::ready(()),
+                worktrees: Vec::new(),
+                load_worktrees_task: Task::ready(()),
                 width: None,
                 show_placeholders: false,
                 local_committer: None,
@@ -533,6 +560,7 @@ impl GitPanel {
             };
 
             this.schedule_update(false, window, cx);
+            this.load_worktrees(cx);
             this
         });
 
@@ -927,6 +955,26 @@ impl GitPanel {
         });
     }
 
+    fn open_merge_editor(
+        &mut self,
+        _: &git::OpenMergeEditor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        maybe!({
+            let entry = self.entries.get(self.selected_entry?)?.status_entry()?;
+            let active_repo = self.active_repository.clone()?;
+            MergeView::open(
+                active_repo,
+                entry.repo_path.clone(),
+                self.workspace.clone(),
+                window,
+                cx,
+            );
+            Some(())
+        });
+    }
+
     fn revert_selected(
         &mut self,
         action: &git::RestoreFile,
@@ -1255,6 +1303,74 @@ impl GitPanel {
         self.change_file_stage(false, entries, cx);
     }
 
+    pub fn stash_all(&mut self, _: &StashAll, _window: &mut Window, cx: &mut Context<Self>) {
+        self.stash(StashScope::All, cx);
+    }
+
+    pub fn stash_staged(&mut self, _: &StashStaged, _window: &mut Window, cx: &mut Context<Self>) {
+        if !self.has_staged_changes() {
+            return;
+        }
+        self.stash(StashScope::Staged, cx);
+    }
+
+    fn stash_selected(&mut self, _: &git::StashFile, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self
+            .get_selected_entry()
+            .and_then(|entry| entry.status_entry())
+        else {
+            return;
+        };
+        self.stash(StashScope::Paths(vec![entry.repo_path.clone()]), cx);
+    }
+
+    fn stash(&mut self, scope: StashScope, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        telemetry::event!("Git Stashed");
+
+        let stash = active_repository.update(cx, |repo, cx| repo.stash_push(scope, None, cx));
+        self.report_stash_error("stash", stash, cx);
+    }
+
+    pub fn stash_pop(&mut self, _: &StashPop, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let pop = active_repository.update(cx, |repo, _| repo.stash_pop(0));
+        self.report_stash_error("stash pop", pop, cx);
+    }
+
+    pub fn stash_apply(&mut self, _: &StashApply, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(active_repository) = self.active_repository.clone() else {
+            return;
+        };
+        let apply = active_repository.update(cx, |repo, _| repo.stash_apply(0));
+        self.report_stash_error("stash apply", apply, cx);
+    }
+
+    fn report_stash_error(
+        &self,
+        action: &'static str,
+        job: oneshot::Receiver<anyhow::Result<()>>,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn(async move |this, cx| {
+            let result = job.await;
+            this.update(cx, |this, cx| {
+                if let Err(e) = result
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+                {
+                    this.show_error_toast(action, e, cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
     fn toggle_staged_for_entry(
         &mut self,
         entry: &GitListEntry,
@@ -2207,6 +2323,59 @@ impl GitPanel {
         !self.project.read(cx).is_via_collab()
     }
 
+    pub(crate) fn push_tag(
+        &mut self,
+        tag: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.can_push_and_pull(cx) {
+            return;
+        }
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        // Tags aren't tied to a branch's upstream, so offer every remote.
+        let remote = self.get_remote(true, window, cx);
+
+        cx.spawn_in(window, async move |this, cx| {
+            let remote = match remote.await {
+                Ok(Some(remote)) => remote,
+                Ok(None) => {
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::error!("Failed to get current remote: {}", e);
+                    this.update(cx, |this, cx| this.show_error_toast("push", e, cx))
+                        .ok();
+                    return Ok(());
+                }
+            };
+
+            let askpass_delegate = this.update_in(cx, |this, window, cx| {
+                this.askpass_delegate(format!("git push {} {}", remote.name, tag), window, cx)
+            })?;
+
+            let push = repo.update(cx, |repo, _| {
+                repo.push_tag(tag.clone(), remote.name.clone(), askpass_delegate)
+            })?;
+
+            let remote_output = push.await?;
+
+            let action = RemoteAction::PushTag(tag, remote);
+            this.update(cx, |this, cx| match remote_output {
+                Ok(remote_message) => this.show_remote_output(action, remote_message, cx),
+                Err(e) => {
+                    log::error!("Error while pushing tag {:?}", e);
+                    this.show_error_toast(action.name(), e, cx)
+                }
+            })?;
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn get_remote(
         &mut self,
         always_select: bool,
@@ -2402,6 +2571,28 @@ impl GitPanel {
         });
     }
 
+    fn load_worktrees(&mut self, cx: &mut Context<Self>) {
+        let Some(repository) = self.active_repository.clone() else {
+            self.worktrees.clear();
+            return;
+        };
+        let worktrees = repository.update(cx, |repository, _| repository.worktrees());
+        self.load_worktrees_task = cx.spawn(async move |this, cx| {
+            let Some(worktrees) = worktrees
+                .await
+                .log_err()
+                .and_then(|result| result.log_err())
+            else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.worktrees = worktrees;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
     fn reopen_commit_buffer(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(active_repo) = self.active_repository.as_ref() else {
             return;
@@ -3124,6 +3315,46 @@ impl GitPanel {
         )
     }
 
+    /// Shows which worktree the project is in, if the repository has linked worktrees.
+    pub(crate) fn render_worktree_button(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        if self.worktrees.len() < 2 {
+            return None;
+        }
+        let work_directory_abs_path = self
+            .active_repository
+            .as_ref()?
+            .read(cx)
+            .work_directory_abs_path
+            .clone();
+        let worktree = self
+            .worktrees
+            .iter()
+            .find(|worktree| worktree.path.as_path() == work_directory_abs_path.as_ref())?;
+        let name = worktree.path.file_name()?.to_string_lossy().into_owned();
+        let tooltip = if worktree.is_main {
+            format!("Main worktree ({} linked)", self.worktrees.len() - 1)
+        } else {
+            let main_worktree = self.worktrees.iter().find(|worktree| worktree.is_main)?;
+            format!("Linked worktree of {}", main_worktree.path.display())
+        };
+        Some(
+            Button::new("worktree-button", name)
+                .style(ButtonStyle::Transparent)
+                .size(ButtonSize::None)
+                .label_size(LabelSize::Small)
+                .color(Color::Muted)
+                .icon(IconName::FileTree)
+                .icon_size(IconSize::XSmall)
+                .icon_color(Color::Muted)
+                .icon_position(IconPosition::Start)
+                .tooltip(Tooltip::text(tooltip))
+                .on_click(|_, window, cx| {
+                    window.dispatch_action(git::ViewWorktrees.boxed_clone(), cx);
+                })
+                .into_any_element(),
+        )
+    }
+
     pub fn render_footer(
         &self,
         window: &mut Window,
@@ -3925,14 +4156,19 @@ impl GitPanel {
         } else {
             "Restore File"
         };
+        let is_conflicted = entry.status.is_conflicted();
         let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
             context_menu
                 .context(self.focus_handle.clone())
                 .action(stage_title, ToggleStaged.boxed_clone())
                 .action(restore_title, git::RestoreFile::default().boxed_clone())
+                .action("Stash File", git::StashFile.boxed_clone())
                 .separator()
                 .action("Open Diff", Confirm.boxed_clone())
                 .action("Open File", SecondaryConfirm.boxed_clone())
+                .when(is_conflicted, |menu| {
+                    menu.action("Open Merge Editor", git::OpenMergeEditor.boxed_clone())
+                })
         });
         self.selected_entry = Some(ix);
         self.set_context_menu(context_menu, position, window, cx);
@@ -4288,6 +4524,12 @@ impl Render for GitPanel {
                     .on_action(cx.listener(Self::restore_tracked_files))
                     .on_action(cx.listener(Self::revert_selected))
                     .on_action(cx.listener(Self::clean_all))
+                    .on_action(cx.listener(Self::stash_all))
+                    .on_action(cx.listener(Self::stash_staged))
+                    .on_action(cx.listener(Self::stash_selected))
+                    .on_action(cx.listener(Self::open_merge_editor))
+                    .on_action(cx.listener(Self::stash_pop))
+                    .on_action(cx.listener(Self::stash_apply))
                     .on_action(cx.listener(Self::generate_commit_message_action))
             })
             .on_action(cx.listener(Self::select_first))
@@ -4683,7 +4925,10 @@ impl RenderOnce for PanelRepoFooter {
                                 .child("/"),
                         )
                     })
-                    .child(branch_selector),
+                    .child(branch_selector)
+                    .children(self.git_panel.as_ref().and_then(|git_panel| {
+                        git_panel.update(cx, |git_panel, cx| git_panel.render_worktree_button(cx))
+                    })),
             )
             .children(if let Some(git_panel) = self.git_panel {
                 git_panel.update(cx, |git_panel, cx| git_panel.render_remote_button(cx))
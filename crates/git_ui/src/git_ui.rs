@@ -18,18 +18,26 @@ use workspace::Workspace;
 
 mod askpass_modal;
 pub mod branch_picker;
+mod commit_graph;
 mod commit_modal;
 pub mod commit_tooltip;
 mod commit_view;
 mod conflict_view;
 pub mod diff_view;
+pub mod file_history_view;
 pub mod git_panel;
 mod git_panel_settings;
+pub mod log_view;
+pub mod merge_view;
 pub mod onboarding;
 pub mod picker_prompt;
 pub mod project_diff;
+pub mod rebase_editor;
 pub(crate) mod remote_output;
 pub mod repository_selector;
+pub mod stash_picker;
+mod three_way_merge;
+pub mod worktree_picker;
 
 actions!(
     git,
@@ -55,6 +63,9 @@ pub fn init(cx: &mut App) {
         git_panel::register(workspace);
         repository_selector::register(workspace);
         branch_picker::register(workspace);
+        stash_picker::register(workspace);
+        log_view::register(workspace);
+        file_history_view::register(workspace);
 
         let project = workspace.project().read(cx);
         if project.is_read_only(cx) {
@@ -110,6 +121,13 @@ pub fn init(cx: &mut App) {
                 });
             });
         }
+        rebase_editor::register(workspace);
+        merge_view::register(workspace);
+        commit_view::register(workspace);
+        // Worktrees are opened in new windows, which is only possible for local projects.
+        if workspace.project().read(cx).is_local() {
+            worktree_picker::register(workspace);
+        }
         workspace.register_action(|workspace, action: &git::StageAll, window, cx| {
             let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
                 return;
@@ -126,6 +144,38 @@ pub fn init(cx: &mut App) {
                 panel.unstage_all(action, window, cx);
             });
         });
+        workspace.register_action(|workspace, action: &git::StashAll, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.stash_all(action, window, cx);
+            });
+        });
+        workspace.register_action(|workspace, action: &git::StashStaged, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.stash_staged(action, window, cx);
+            });
+        });
+        workspace.register_action(|workspace, action: &git::StashPop, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.stash_pop(action, window, cx);
+            });
+        });
+        workspace.register_action(|workspace, action: &git::StashApply, window, cx| {
+            let Some(panel) = workspace.panel::<git_panel::GitPanel>(cx) else {
+                return;
+            };
+            panel.update(cx, |panel, cx| {
+                panel.stash_apply(action, window, cx);
+            });
+        });
         CommandPaletteFilter::update_global(cx, |filter, _cx| {
             filter.hide_action_types(&[
                 zed_actions::OpenGitIntegrationOnboarding.type_id(),
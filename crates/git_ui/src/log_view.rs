@@ -0,0 +1,506 @@
+use std::{ops::Range, time::Duration};
+
+use editor::{Editor, EditorEvent};
+use git::repository::{LogEntry, LogOptions, RepoPath};
+use gpui::{
+    AnyElement, App, Bounds, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    Hsla, PathBuilder, Pixels, Point, ScrollStrategy, Subscription, Task, UniformListScrollHandle,
+    WeakEntity, Window, canvas, point, px, quad, uniform_list,
+};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use project::git_store::Repository;
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::{Tooltip, prelude::*};
+use util::ResultExt;
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+use crate::commit_graph::{CommitGraph, GraphEdge, GraphRow};
+use crate::commit_view::CommitView;
+
+const PAGE_SIZE: usize = 200;
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(300);
+const LANE_WIDTH: Pixels = px(14.);
+const COMMIT_DOT_SIZE: Pixels = px(7.);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::ViewHistory, window, cx| {
+        LogView::deploy(workspace, None, window, cx);
+    });
+}
+
+/// Shows the commit history of a repository, with its branch graph.
+pub struct LogView {
+    repository: Entity<Repository>,
+    head_sha: Option<SharedString>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    path_editor: Entity<Editor>,
+    author_editor: Entity<Editor>,
+    message_editor: Entity<Editor>,
+    all_branches: bool,
+    entries: Vec<LogEntry>,
+    graph: CommitGraph,
+    selected_entry: Option<usize>,
+    scroll_handle: UniformListScrollHandle,
+    has_more: bool,
+    load_task: Option<Task<()>>,
+    reload_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LogView {
+    /// Opens the history of the active repository, limited to `path` if one is given.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        path: Option<RepoPath>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        telemetry::event!("Git History Opened");
+
+        let existing = workspace
+            .items_of_type::<Self>(cx)
+            .find(|view| view.read(cx).repository == repository);
+        let log_view = if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            existing
+        } else {
+            let workspace_handle = cx.weak_entity();
+            let log_view = cx.new(|cx| Self::new(repository, workspace_handle, window, cx));
+            workspace.add_item_to_active_pane(Box::new(log_view.clone()), None, true, window, cx);
+            log_view
+        };
+        if let Some(path) = path {
+            log_view.update(cx, |log_view, cx| {
+                log_view.path_editor.update(cx, |editor, cx| {
+                    editor.set_text(path.to_string(), window, cx);
+                });
+            });
+        }
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut subscriptions = Vec::new();
+        let mut filter_editor = |placeholder: &'static str, cx: &mut Context<Self>| {
+            let editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text(placeholder, cx);
+                editor
+            });
+            subscriptions.push(cx.subscribe_in(
+                &editor,
+                window,
+                |this, _, event: &EditorEvent, _, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.schedule_reload(cx);
+                    }
+                },
+            ));
+            editor
+        };
+        let path_editor = filter_editor("Filter by path…", cx);
+        let author_editor = filter_editor("Filter by author…", cx);
+        let message_editor = filter_editor("Filter by message…", cx);
+        subscriptions.push(cx.observe(&repository, |this, repository, cx| {
+            // Reload when HEAD moves, e.g. after committing or switching branches.
+            let head_sha = Self::head_sha(&repository, cx);
+            if head_sha != this.head_sha {
+                this.head_sha = head_sha;
+                this.reload(cx);
+            }
+        }));
+
+        let mut this = Self {
+            head_sha: Self::head_sha(&repository, cx),
+            repository,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            path_editor,
+            author_editor,
+            message_editor,
+            all_branches: false,
+            entries: Vec::new(),
+            graph: CommitGraph::new(true),
+            selected_entry: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            has_more: true,
+            load_task: None,
+            reload_task: None,
+            _subscriptions: subscriptions,
+        };
+        this.load_more(cx);
+        this
+    }
+
+    fn head_sha(repository: &Entity<Repository>, cx: &App) -> Option<SharedString> {
+        let head_commit = repository.read(cx).head_commit.as_ref()?;
+        Some(head_commit.sha.clone())
+    }
+
+    fn filter_text(editor: &Entity<Editor>, cx: &App) -> Option<String> {
+        let text = editor.read(cx).text(cx);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    fn schedule_reload(&mut self, cx: &mut Context<Self>) {
+        self.reload_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(FILTER_DEBOUNCE).await;
+            this.update(cx, |this, cx| this.reload(cx)).ok();
+        }));
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        self.entries.clear();
+        self.graph = CommitGraph::new(
+            Self::filter_text(&self.author_editor, cx).is_none()
+                && Self::filter_text(&self.message_editor, cx).is_none(),
+        );
+        self.selected_entry = None;
+        self.has_more = true;
+        self.load_task = None;
+        self.load_more(cx);
+        cx.notify();
+    }
+
+    fn load_more(&mut self, cx: &mut Context<Self>) {
+        if self.load_task.is_some() || !self.has_more {
+            return;
+        }
+        let options = LogOptions {
+            skip: self.entries.len(),
+            limit: PAGE_SIZE,
+            all_branches: self.all_branches,
+            path: Self::filter_text(&self.path_editor, cx)
+                .map(|path| RepoPath::from_str(path.trim_start_matches('/'))),
+            author: Self::filter_text(&self.author_editor, cx),
+            message: Self::filter_text(&self.message_editor, cx),
+        };
+        let log = self
+            .repository
+            .update(cx, |repository, _| repository.log(options));
+        self.load_task = Some(cx.spawn(async move |this, cx| {
+            let entries = log.await.map_err(anyhow::Error::from).and_then(|r| r);
+            this.update(cx, |this, cx| {
+                this.load_task = None;
+                match entries {
+                    Ok(entries) => {
+                        this.has_more = entries.len() == PAGE_SIZE;
+                        for entry in &entries {
+                            this.graph.push(&entry.sha, &entry.parents);
+                        }
+                        this.entries.extend(entries);
+                    }
+                    Err(error) => {
+                        this.has_more = false;
+                        log::error!("failed to load git history: {error:?}");
+                    }
+                }
+                cx.notify();
+            })
+            .log_err();
+        }));
+    }
+
+    fn toggle_all_branches(&mut self, _: &ClickEvent, _: &mut Window, cx: &mut Context<Self>) {
+        self.all_branches = !self.all_branches;
+        self.reload(cx);
+    }
+
+    fn select_entry(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_entry = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.entries.is_empty() {
+            self.select_entry(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.entries.is_empty() {
+            self.select_entry(self.entries.len() - 1, cx);
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.selected_entry.map_or(0, |ix| ix + 1);
+        if ix < self.entries.len() {
+            self.select_entry(ix, cx);
+        }
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry.and_then(|ix| ix.checked_sub(1)) {
+            self.select_entry(ix, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.open_commit(ix, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        CommitView::open(
+            entry.to_commit_summary(),
+            self.repository.downgrade(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+
+    fn render_filter(&self, editor: &Entity<Editor>, cx: &Context<Self>) -> impl IntoElement {
+        div()
+            .flex_1()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(editor.clone())
+    }
+
+    fn render_entries(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        if self.has_more && range.end + PAGE_SIZE / 2 >= self.entries.len() {
+            self.load_more(cx);
+        }
+        let graph_width = self.graph.rows()[range.clone()]
+            .iter()
+            .map(GraphRow::width)
+            .max()
+            .unwrap_or_default();
+        let now = OffsetDateTime::now_utc();
+
+        range
+            .map(|ix| {
+                let entry = &self.entries[ix];
+                let row = self.graph.rows()[ix].clone();
+                let commit_time = OffsetDateTime::from_unix_timestamp(entry.commit_timestamp)
+                    .unwrap_or_else(|_| OffsetDateTime::now_utc());
+                let short_sha = entry.sha.get(..7).unwrap_or(&*entry.sha).to_string();
+
+                h_flex()
+                    .id(ix)
+                    .w_full()
+                    .h(px(24.))
+                    .pr_2()
+                    .gap_2()
+                    .cursor_pointer()
+                    .when(self.selected_entry == Some(ix), |this| {
+                        this.bg(cx.theme().colors().element_selected)
+                    })
+                    .hover(|this| this.bg(cx.theme().colors().element_hover))
+                    .child(render_graph_row(row, graph_width, cx))
+                    .child(
+                        h_flex()
+                            .flex_1()
+                            .min_w_0()
+                            .gap_1()
+                            .children(entry.refs.iter().map(|name| {
+                                div()
+                                    .px_1()
+                                    .rounded_sm()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border)
+                                    .child(Label::new(name.clone()).size(LabelSize::XSmall))
+                            }))
+                            .child(Label::new(entry.subject.clone()).truncate()),
+                    )
+                    .child(
+                        Label::new(entry.author_name.clone())
+                            .color(Color::Muted)
+                            .truncate(),
+                    )
+                    .child(
+                        Label::new(format_local_timestamp(
+                            commit_time,
+                            now,
+                            time_format::TimestampFormat::Relative,
+                        ))
+                        .color(Color::Muted),
+                    )
+                    .child(Label::new(short_sha).color(Color::Muted).buffer_font(cx))
+                    .on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                        this.select_entry(ix, cx);
+                        this.open_commit(ix, window, cx);
+                    }))
+                    .into_any_element()
+            })
+            .collect()
+    }
+}
+
+fn render_graph_row(row: GraphRow, lane_count: usize, cx: &App) -> impl IntoElement {
+    let accents = cx.theme().accents().clone();
+    let lane_color = move |lane: usize| accents.color_for_index(lane as u32);
+    canvas(
+        |_, _, _| {},
+        move |bounds: Bounds<Pixels>, _, window, _| {
+            let lane_x = |lane: usize| bounds.origin.x + LANE_WIDTH * (lane as f32 + 0.5);
+            let top = bounds.origin.y;
+            let center = bounds.center().y;
+            let bottom = bounds.bottom_left().y;
+            let commit = point(lane_x(row.lane), center);
+
+            let mut paint_line = |from: Point<Pixels>, to: Point<Pixels>, color: Hsla| {
+                let mut path = PathBuilder::stroke(px(1.5));
+                path.move_to(from);
+                path.line_to(to);
+                if let Ok(path) = path.build() {
+                    window.paint_path(path, color);
+                }
+            };
+            for edge in &row.edges {
+                match *edge {
+                    GraphEdge::PassThrough { lane } => paint_line(
+                        point(lane_x(lane), top),
+                        point(lane_x(lane), bottom),
+                        lane_color(lane),
+                    ),
+                    GraphEdge::Incoming { from_lane } => {
+                        paint_line(point(lane_x(from_lane), top), commit, lane_color(from_lane))
+                    }
+                    GraphEdge::Outgoing { to_lane } => {
+                        paint_line(commit, point(lane_x(to_lane), bottom), lane_color(to_lane))
+                    }
+                }
+            }
+
+            let dot = Bounds::centered_at(commit, gpui::size(COMMIT_DOT_SIZE, COMMIT_DOT_SIZE));
+            window.paint_quad(quad(
+                dot,
+                COMMIT_DOT_SIZE / 2.,
+                lane_color(row.lane),
+                px(0.),
+                gpui::transparent_black(),
+                Default::default(),
+            ));
+        },
+    )
+    .flex_none()
+    .h_full()
+    .w(LANE_WIDTH * lane_count.max(1) as f32)
+}
+
+impl EventEmitter<ItemEvent> for LogView {}
+
+impl Focusable for LogView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for LogView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let repository = self.repository.read(cx);
+        match repository.work_directory_abs_path.file_name() {
+            Some(name) => format!("History: {}", name.to_string_lossy()).into(),
+            None => "History".into(),
+        }
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git History Opened")
+    }
+}
+
+impl Render for LogView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_empty = self.entries.is_empty();
+        let is_loading = self.load_task.is_some();
+
+        v_flex()
+            .key_context("GitLog")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(self.render_filter(&self.path_editor, cx))
+                    .child(self.render_filter(&self.author_editor, cx))
+                    .child(self.render_filter(&self.message_editor, cx))
+                    .child(
+                        Button::new("all-branches", "All Branches")
+                            .toggle_state(self.all_branches)
+                            .tooltip(Tooltip::text("Show commits from every branch and tag"))
+                            .on_click(cx.listener(Self::toggle_all_branches)),
+                    ),
+            )
+            .map(|this| {
+                if is_empty {
+                    this.child(
+                        h_flex().flex_1().justify_center().child(
+                            Label::new(if is_loading {
+                                "Loading history…"
+                            } else {
+                                "No commits found"
+                            })
+                            .color(Color::Muted),
+                        ),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            "git-log-entries",
+                            self.entries.len(),
+                            cx.processor(|this, range: Range<usize>, _window, cx| {
+                                this.render_entries(range, cx)
+                            }),
+                        )
+                        .flex_1()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
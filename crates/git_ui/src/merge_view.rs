@@ -0,0 +1,699 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Context as _;
+use editor::{Editor, RowHighlightOptions, SelectionEffects, scroll::Autoscroll};
+use git::repository::{ConflictVersions, RepoPath};
+use gpui::{
+    AnyElement, App, AsyncApp, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    Hsla, PromptLevel, Subscription, Task, WeakEntity, Window, actions,
+};
+use language::{Buffer, LanguageRegistry, OffsetRangeExt as _, Point};
+use project::{ConflictRegion, ConflictSet, Project, WorktreeId, git_store::Repository};
+use ui::{Divider, KeyBinding, Tooltip, prelude::*};
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, SaveOptions, TabContentParams},
+};
+
+use crate::commit_view::{GitBlob, build_buffer};
+use crate::conflict_view::resolve_conflict;
+use crate::three_way_merge::{MergeHunkKind, base_rows, find_rows, merge_hunks, merge_text};
+
+actions!(
+    merge_view,
+    [
+        /// Resolves the conflict at the cursor by keeping our side of it.
+        AcceptOurs,
+        /// Resolves the conflict at the cursor by keeping their side of it.
+        AcceptTheirs,
+        /// Resolves the conflict at the cursor by keeping both sides of it.
+        AcceptBoth,
+        /// Moves to the next conflict in the result.
+        NextConflict,
+        /// Moves to the previous conflict in the result.
+        PreviousConflict,
+        /// Merges the changes within each conflict that don't overlap, leaving only
+        /// the lines that both sides changed differently.
+        ResolveNonConflicting,
+        /// Saves and stages the result, marking the file as resolved.
+        MarkResolved,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::OpenMergeEditor, window, cx| {
+        MergeView::deploy(workspace, window, cx);
+    });
+}
+
+enum MergeHunkHighlight {}
+
+/// Shows the base, ours and theirs versions of a conflicted file above an editable
+/// result, which is the file in the worktree.
+pub struct MergeView {
+    repository: Entity<Repository>,
+    path: RepoPath,
+    project: Entity<Project>,
+    versions: ConflictVersions,
+    buffer: Entity<Buffer>,
+    conflict_set: Entity<ConflictSet>,
+    ours_editor: Entity<Editor>,
+    base_editor: Entity<Editor>,
+    theirs_editor: Entity<Editor>,
+    result_editor: Entity<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MergeView {
+    /// Opens the merge editor for the file in the active editor.
+    fn deploy(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.update(cx, |editor, cx| {
+            let cursor = editor.selections.newest_anchor().head();
+            editor.buffer().read(cx).buffer(cursor.buffer_id?)
+        }) else {
+            return;
+        };
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        let Some((repository, path)) = workspace
+            .project()
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_project_path(&project_path, cx)
+        else {
+            return;
+        };
+        Self::open(repository, path, workspace.weak_handle(), window, cx);
+    }
+
+    pub fn open(
+        repository: Entity<Repository>,
+        path: RepoPath,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let existing = workspace.upgrade().and_then(|workspace| {
+            workspace.read(cx).items_of_type::<Self>(cx).find(|view| {
+                let view = view.read(cx);
+                view.repository == repository && view.path == path
+            })
+        });
+        if let Some(existing) = existing {
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace.activate_item(&existing, true, true, window, cx);
+                })
+                .ok();
+            return;
+        }
+        telemetry::event!("Git Merge Editor Opened");
+
+        let versions = repository.update(cx, |repository, _| {
+            repository.load_conflict_versions(path.clone())
+        });
+        window
+            .spawn(cx, {
+                let workspace = workspace.clone();
+                async move |cx| {
+                    let versions = versions.await??;
+                    anyhow::ensure!(
+                        versions.ours.is_some() || versions.theirs.is_some(),
+                        "{} has no conflicts to merge",
+                        path.display()
+                    );
+                    let (project, project_path) = workspace.update(cx, |workspace, cx| {
+                        let project_path = repository
+                            .read(cx)
+                            .repo_path_to_project_path(&path, cx)
+                            .context("conflicted file is not in the project")?;
+                        anyhow::Ok((workspace.project().clone(), project_path))
+                    })??;
+                    let buffer = project
+                        .update(cx, |project, cx| {
+                            project.open_buffer(project_path.clone(), cx)
+                        })?
+                        .await?;
+                    let language_registry =
+                        project.read_with(cx, |project, _| project.languages().clone())?;
+
+                    let worktree_id = project_path.worktree_id;
+                    let ours = build_side_buffer(
+                        versions.ours.clone(),
+                        &path,
+                        worktree_id,
+                        &language_registry,
+                        cx,
+                    )
+                    .await?;
+                    let base = build_side_buffer(
+                        versions.base.clone(),
+                        &path,
+                        worktree_id,
+                        &language_registry,
+                        cx,
+                    )
+                    .await?;
+                    let theirs = build_side_buffer(
+                        versions.theirs.clone(),
+                        &path,
+                        worktree_id,
+                        &language_registry,
+                        cx,
+                    )
+                    .await?;
+
+                    workspace.update_in(cx, |workspace, window, cx| {
+                        let view = cx.new(|cx| {
+                            Self::new(
+                                repository,
+                                path,
+                                versions,
+                                project,
+                                buffer,
+                                [ours, base, theirs],
+                                window,
+                                cx,
+                            )
+                        });
+                        workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+                    })?;
+                    anyhow::Ok(())
+                }
+            })
+            .detach_and_prompt_err("Failed to open merge editor", window, cx, |e, _, _| {
+                Some(e.to_string())
+            });
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        path: RepoPath,
+        versions: ConflictVersions,
+        project: Entity<Project>,
+        buffer: Entity<Buffer>,
+        [ours, base, theirs]: [Entity<Buffer>; 3],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        // The sides are left without a project, so that they don't get conflict UI or
+        // language servers of their own.
+        let side_editor = |buffer: Entity<Buffer>, window: &mut Window, cx: &mut Context<Self>| {
+            cx.new(|cx| {
+                let mut editor = Editor::for_buffer(buffer, None, window, cx);
+                editor.set_read_only(true);
+                editor.set_show_breakpoints(false, cx);
+                editor.set_show_code_actions(false, cx);
+                editor
+            })
+        };
+        let ours_editor = side_editor(ours, window, cx);
+        let base_editor = side_editor(base, window, cx);
+        let theirs_editor = side_editor(theirs, window, cx);
+        let result_editor =
+            cx.new(|cx| Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx));
+
+        let hunks = merge_hunks(
+            versions.base.as_deref().unwrap_or_default(),
+            versions.ours.as_deref().unwrap_or_default(),
+            versions.theirs.as_deref().unwrap_or_default(),
+        );
+        let colors = cx.theme().colors();
+        let ours_color = colors.version_control_conflict_marker_ours;
+        let theirs_color = colors.version_control_conflict_marker_theirs;
+        let base_color = colors.version_control_conflict.opacity(0.2);
+        highlight_rows(
+            &ours_editor,
+            hunks
+                .iter()
+                .filter(|hunk| hunk.kind != MergeHunkKind::Theirs)
+                .map(|hunk| hunk.ours.clone()),
+            ours_color,
+            cx,
+        );
+        highlight_rows(
+            &theirs_editor,
+            hunks
+                .iter()
+                .filter(|hunk| hunk.kind != MergeHunkKind::Ours)
+                .map(|hunk| hunk.theirs.clone()),
+            theirs_color,
+            cx,
+        );
+        highlight_rows(
+            &base_editor,
+            hunks
+                .iter()
+                .filter(|hunk| hunk.kind == MergeHunkKind::Conflict)
+                .map(|hunk| hunk.base.clone()),
+            base_color,
+            cx,
+        );
+
+        let conflict_set = project.read(cx).git_store().update(cx, |git_store, cx| {
+            git_store.open_conflict_set(buffer.clone(), cx)
+        });
+        let subscriptions = vec![cx.subscribe(&conflict_set, |_, _, _, cx| cx.notify())];
+
+        Self {
+            repository,
+            path,
+            project,
+            versions,
+            buffer,
+            conflict_set,
+            ours_editor,
+            base_editor,
+            theirs_editor,
+            result_editor,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn conflicts(&self, cx: &App) -> Arc<[ConflictRegion]> {
+        self.conflict_set.read(cx).snapshot().conflicts
+    }
+
+    fn cursor_offset(&self, cx: &mut App) -> usize {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        })
+    }
+
+    fn conflict_at_cursor(&self, cx: &mut App) -> Option<ConflictRegion> {
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        self.conflicts(cx)
+            .iter()
+            .find(|conflict| conflict.range.to_offset(&snapshot).contains(&cursor))
+            .cloned()
+    }
+
+    fn accept(
+        &mut self,
+        sides: impl FnOnce(&ConflictRegion) -> Vec<Range<language::Anchor>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(conflict) = self.conflict_at_cursor(cx) else {
+            return;
+        };
+        let Some(excerpt_id) = self
+            .result_editor
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .excerpt_ids()
+            .first()
+            .copied()
+        else {
+            return;
+        };
+        let ranges = sides(&conflict);
+        resolve_conflict(
+            self.result_editor.downgrade(),
+            excerpt_id,
+            conflict,
+            ranges,
+            window,
+            cx,
+        )
+        .detach();
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(|conflict| vec![conflict.ours.clone()], window, cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(|conflict| vec![conflict.theirs.clone()], window, cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(
+            |conflict| vec![conflict.ours.clone(), conflict.theirs.clone()],
+            window,
+            cx,
+        );
+    }
+
+    fn move_to_conflict(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_ranges([offset..offset]),
+            );
+        });
+        window.focus(&self.result_editor.focus_handle(cx));
+    }
+
+    fn next_conflict(&mut self, _: &NextConflict, window: &mut Window, cx: &mut Context<Self>) {
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let conflicts = self.conflicts(cx);
+        let mut starts = conflicts
+            .iter()
+            .map(|conflict| conflict.range.start.to_offset(&snapshot));
+        let next = starts
+            .clone()
+            .find(|start| *start > cursor)
+            .or_else(|| starts.next());
+        if let Some(offset) = next {
+            self.move_to_conflict(offset, window, cx);
+        }
+    }
+
+    fn previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cursor = self.cursor_offset(cx);
+        let snapshot = self.buffer.read(cx).snapshot();
+        let conflicts = self.conflicts(cx);
+        let mut starts = conflicts
+            .iter()
+            .rev()
+            .map(|conflict| conflict.range.start.to_offset(&snapshot));
+        let previous = starts
+            .clone()
+            .find(|start| *start < cursor)
+            .or_else(|| starts.next());
+        if let Some(offset) = previous {
+            self.move_to_conflict(offset, window, cx);
+        }
+    }
+
+    fn resolve_non_conflicting(
+        &mut self,
+        _: &ResolveNonConflicting,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let base = self.versions.base.as_deref().unwrap_or_default();
+        let ours = self.versions.ours.as_deref().unwrap_or_default();
+        let theirs = self.versions.theirs.as_deref().unwrap_or_default();
+        let snapshot = self.buffer.read(cx).snapshot();
+        let mut ours_row = 0;
+        let mut theirs_row = 0;
+        let mut edits = Vec::new();
+        for conflict in self.conflicts(cx).iter() {
+            let conflict_ours = snapshot
+                .text_for_range(conflict.ours.clone())
+                .collect::<String>();
+            let conflict_theirs = snapshot
+                .text_for_range(conflict.theirs.clone())
+                .collect::<String>();
+            // Without a base section in the markers, find the lines of the base that the
+            // conflict replaced by locating one of its sides in the full version of it.
+            let conflict_base = if let Some(base_range) = &conflict.base {
+                snapshot.text_for_range(base_range.clone()).collect()
+            } else if let Some(rows) = find_rows(ours, &conflict_ours, ours_row) {
+                ours_row = rows.end;
+                text_for_rows(base, base_rows(base, ours, rows))
+            } else if let Some(rows) = find_rows(theirs, &conflict_theirs, theirs_row) {
+                theirs_row = rows.end;
+                text_for_rows(base, base_rows(base, theirs, rows))
+            } else {
+                continue;
+            };
+
+            let merged = merge_text(&conflict_base, &conflict_ours, &conflict_theirs);
+            if merged.resolved > 0 {
+                edits.push((conflict.range.clone(), merged.text));
+            }
+        }
+
+        if edits.is_empty() {
+            return;
+        }
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+    }
+
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        let confirmation = (!self.conflicts(cx).is_empty()).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                "This file still contains conflict markers",
+                Some("Do you want to mark it as resolved anyway?"),
+                &["Mark Resolved", "Cancel"],
+                cx,
+            )
+        });
+        let project = self.project.clone();
+        let buffer = self.buffer.clone();
+        let repository = self.repository.clone();
+        let path = self.path.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            if let Some(confirmation) = confirmation {
+                if confirmation.await? != 0 {
+                    return Ok(());
+                }
+            }
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer, cx))?
+                .await?;
+            repository
+                .update(cx, |repository, cx| {
+                    repository.stage_entries(vec![path], cx)
+                })?
+                .await?;
+            this.update(cx, |_, cx| cx.emit(ItemEvent::CloseItem))?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err(
+            "Failed to mark file as resolved",
+            window,
+            cx,
+            |e, _, _| Some(e.to_string()),
+        );
+    }
+
+    fn render_pane(
+        &self,
+        label: &'static str,
+        color: Color,
+        editor: &Entity<Editor>,
+        cx: &App,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .min_h_0()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(label).size(LabelSize::Small).color(color)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+    }
+
+    fn render_toolbar(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.result_editor.focus_handle(cx);
+        let conflict_count = self.conflicts(cx).len();
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(self.path.display().to_string()))
+                    .child(
+                        Label::new(match conflict_count {
+                            0 => "No conflicts remaining".to_string(),
+                            1 => "1 conflict remaining".to_string(),
+                            count => format!("{count} conflicts remaining"),
+                        })
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("resolve-non-conflicting", "Resolve Non-Conflicting")
+                            .disabled(conflict_count == 0)
+                            .tooltip(Tooltip::text(
+                                "Merge the changes within each conflict that don't overlap",
+                            ))
+                            .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                                this.resolve_non_conflicting(&ResolveNonConflicting, window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("mark-resolved", "Mark Resolved")
+                            .style(ButtonStyle::Filled)
+                            .key_binding(
+                                KeyBinding::for_action_in(&MarkResolved, &focus_handle, window, cx)
+                                    .map(|kb| kb.size(rems_from_px(12.))),
+                            )
+                            .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                                this.mark_resolved(&MarkResolved, window, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+fn highlight_rows(
+    editor: &Entity<Editor>,
+    rows: impl Iterator<Item = Range<u32>>,
+    color: Hsla,
+    cx: &mut App,
+) {
+    editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        for rows in rows.filter(|rows| !rows.is_empty()) {
+            let start = snapshot.anchor_before(Point::new(rows.start, 0));
+            let end = snapshot.anchor_before(Point::new(rows.end - 1, 0));
+            editor.highlight_rows::<MergeHunkHighlight>(
+                start..end,
+                color,
+                RowHighlightOptions {
+                    include_gutter: true,
+                    ..Default::default()
+                },
+                cx,
+            );
+        }
+    });
+}
+
+async fn build_side_buffer(
+    text: Option<String>,
+    path: &RepoPath,
+    worktree_id: WorktreeId,
+    language_registry: &Arc<LanguageRegistry>,
+    cx: &mut AsyncApp,
+) -> anyhow::Result<Entity<Buffer>> {
+    let blob = Arc::new(GitBlob {
+        path: path.clone(),
+        worktree_id,
+        is_deleted: text.is_none(),
+    }) as Arc<dyn language::File>;
+    build_buffer(text.unwrap_or_default(), blob, language_registry, cx).await
+}
+
+fn text_for_rows(text: &str, rows: Range<u32>) -> String {
+    text.split_inclusive('\n')
+        .skip(rows.start as usize)
+        .take(rows.len())
+        .collect()
+}
+
+impl EventEmitter<ItemEvent> for MergeView {}
+
+impl Focusable for MergeView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl Item for MergeView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("Merge: {file_name}").into()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("Merge conflicts in {}", self.path.display()).into())
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.result_editor.save(options, project, window, cx)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Merge Editor Opened")
+    }
+}
+
+impl Render for MergeView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("MergeView")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::next_conflict))
+            .on_action(cx.listener(Self::previous_conflict))
+            .on_action(cx.listener(Self::resolve_non_conflicting))
+            .on_action(cx.listener(Self::mark_resolved))
+            .child(self.render_toolbar(window, cx))
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .items_stretch()
+                    .child(self.render_pane("Ours", Color::Created, &self.ours_editor, cx))
+                    .child(Divider::vertical())
+                    .child(self.render_pane("Base", Color::Muted, &self.base_editor, cx))
+                    .child(Divider::vertical())
+                    .child(self.render_pane("Theirs", Color::Modified, &self.theirs_editor, cx)),
+            )
+            .child(Divider::horizontal())
+            .child(self.render_pane("Result", Color::Default, &self.result_editor, cx))
+    }
+}
@@ -0,0 +1,870 @@
+use std::cmp::Reverse;
+
+use editor::{Editor, EditorEvent};
+use futures::channel::oneshot;
+use git::SHORT_SHA_LENGTH;
+use git::repository::{RebaseAction, RebaseCommand, RebaseStatus, RebaseTodoEntry, RepoPath};
+use gpui::{
+    AnyElement, App, ClickEvent, Context, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    Point, Subscription, Task, WeakEntity, Window, actions,
+};
+use menu::{SelectNext, SelectPrevious};
+use project::git_store::{Repository, RepositoryEvent};
+use ui::{KeyBinding, Tooltip, prelude::*};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, TabContentParams},
+};
+
+use crate::picker_prompt;
+
+actions!(
+    rebase_editor,
+    [
+        /// Keeps the selected commit as it is.
+        PickCommit,
+        /// Keeps the selected commit, but changes its message.
+        RewordCommit,
+        /// Stops after applying the selected commit, so that it can be amended.
+        EditCommit,
+        /// Combines the selected commit with the one before it, joining their messages.
+        SquashCommit,
+        /// Combines the selected commit with the one before it, keeping the earlier message.
+        FixupCommit,
+        /// Removes the selected commit.
+        DropCommit,
+        /// Moves the selected commit up, so that it is applied earlier.
+        MoveCommitUp,
+        /// Moves the selected commit down, so that it is applied later.
+        MoveCommitDown,
+        /// Starts rebasing with the edited list of commits.
+        StartRebase,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &git::Rebase, window, cx| {
+        RebaseEditor::deploy(workspace, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::ContinueRebase, window, cx| {
+        run_rebase_command(workspace, RebaseCommand::Continue, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::SkipRebase, window, cx| {
+        run_rebase_command(workspace, RebaseCommand::Skip, window, cx);
+    });
+    workspace.register_action(|workspace, _: &git::AbortRebase, window, cx| {
+        run_rebase_command(workspace, RebaseCommand::Abort, window, cx);
+    });
+}
+
+fn run_rebase_command(
+    workspace: &mut Workspace,
+    command: RebaseCommand,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let job = repository.update(cx, |repository, _| repository.rebase_command(command));
+    cx.background_spawn(async move { job.await? })
+        .detach_and_prompt_err("Failed to rebase", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+}
+
+#[derive(Clone)]
+struct DraggedRebaseEntry {
+    ix: usize,
+    subject: SharedString,
+}
+
+impl Render for DraggedRebaseEntry {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .rounded_sm()
+            .bg(cx.theme().colors().background)
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .child(Icon::new(IconName::Menu).size(IconSize::Small))
+            .child(Label::new(self.subject.clone()))
+    }
+}
+
+/// Edits the list of commits of an interactive rebase, and drives the rebase once
+/// it has started.
+pub struct RebaseEditor {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    onto: SharedString,
+    entries: Vec<RebaseTodoEntry>,
+    selected_entry: Option<usize>,
+    message_editor: Entity<Editor>,
+    updating_message: bool,
+    /// The progress of the rebase, once it has started.
+    status: Option<RebaseStatus>,
+    started: bool,
+    error: Option<SharedString>,
+    pending_task: Option<Task<()>>,
+    status_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl RebaseEditor {
+    /// Opens the rebase in progress in the active repository, or asks for a branch to
+    /// rebase the current branch onto.
+    pub fn deploy(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        if let Some(existing) = workspace
+            .items_of_type::<Self>(cx)
+            .find(|editor| editor.read(cx).repository == repository)
+        {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let (status, branches) = repository.update(cx, |repository, _| {
+            (repository.rebase_status(), repository.branches())
+        });
+        cx.spawn_in(window, async move |workspace, cx| {
+            if let Some(status) = status.await?? {
+                return workspace.update_in(cx, |workspace, window, cx| {
+                    let onto = status.onto.get(..SHORT_SHA_LENGTH).unwrap_or(&*status.onto);
+                    let onto = SharedString::from(onto.to_string());
+                    Self::open(repository, onto, Some(status), workspace, window, cx);
+                });
+            }
+
+            // Offer the upstream of the current branch first, then the most recently
+            // updated branches.
+            let mut branches = branches.await??;
+            let upstream = branches
+                .iter()
+                .find(|branch| branch.is_head)
+                .and_then(|branch| Some(branch.upstream.as_ref()?.ref_name.clone()));
+            branches.retain(|branch| !branch.is_head);
+            branches.sort_by_key(|branch| {
+                (
+                    Some(&branch.ref_name) != upstream.as_ref(),
+                    Reverse(
+                        branch
+                            .most_recent_commit
+                            .as_ref()
+                            .map(|commit| commit.commit_timestamp),
+                    ),
+                )
+            });
+            let options = branches
+                .iter()
+                .map(|branch| SharedString::from(branch.name().to_string()))
+                .collect::<Vec<_>>();
+
+            let selection = workspace
+                .update_in(cx, |workspace, window, cx| {
+                    picker_prompt::prompt(
+                        "Rebase onto…",
+                        options.clone(),
+                        workspace.weak_handle(),
+                        window,
+                        cx,
+                    )
+                })?
+                .await;
+            let Some(ix) = selection else {
+                return Ok(());
+            };
+            workspace.update_in(cx, |workspace, window, cx| {
+                Self::open(repository, options[ix].clone(), None, workspace, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to start rebase", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn open(
+        repository: Entity<Repository>,
+        onto: SharedString,
+        status: Option<RebaseStatus>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        telemetry::event!("Git Rebase Editor Opened");
+        let workspace_handle = cx.weak_entity();
+        let rebase_editor =
+            cx.new(|cx| Self::new(repository, onto, status, workspace_handle, window, cx));
+        workspace.add_item_to_active_pane(Box::new(rebase_editor), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        onto: SharedString,
+        status: Option<RebaseStatus>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let message_editor = cx.new(|cx| {
+            let mut editor = Editor::auto_height(3, 12, window, cx);
+            editor.set_placeholder_text("Commit message", cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &message_editor,
+                window,
+                |this, editor, event: &EditorEvent, _, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.update_message(editor.read(cx).text(cx));
+                    }
+                },
+            ),
+            cx.subscribe(&repository, |this, _, _: &RepositoryEvent, cx| {
+                if this.started {
+                    this.refresh_status(cx);
+                }
+            }),
+        ];
+
+        let mut this = Self {
+            repository,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            onto,
+            entries: Vec::new(),
+            selected_entry: None,
+            message_editor,
+            updating_message: false,
+            started: status.is_some(),
+            status,
+            error: None,
+            pending_task: None,
+            status_task: None,
+            _subscriptions: subscriptions,
+        };
+        if !this.started {
+            this.load_todo(window, cx);
+        }
+        this
+    }
+
+    fn load_todo(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let todo = self.repository.update(cx, |repository, _| {
+            repository.rebase_todo(self.onto.to_string())
+        });
+        self.pending_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let todo = todo.await.map_err(anyhow::Error::from).and_then(|r| r);
+            this.update_in(cx, |this, window, cx| {
+                this.pending_task = None;
+                match todo {
+                    Ok(entries) => {
+                        this.selected_entry = (!entries.is_empty()).then_some(0);
+                        this.entries = entries;
+                        this.sync_message_editor(window, cx);
+                    }
+                    Err(error) => this.error = Some(error.to_string().trim().to_string().into()),
+                }
+                cx.notify();
+            })
+            .log_err();
+        }));
+    }
+
+    fn refresh_status(&mut self, cx: &mut Context<Self>) {
+        let status = self
+            .repository
+            .update(cx, |repository, _| repository.rebase_status());
+        self.status_task = Some(cx.spawn(async move |this, cx| {
+            let status = status.await.map_err(anyhow::Error::from).and_then(|r| r);
+            this.update(cx, |this, cx| {
+                match status {
+                    Ok(None) if this.pending_task.is_some() => {}
+                    // The rebase either completed or was aborted.
+                    Ok(None) if this.error.is_none() => cx.emit(ItemEvent::CloseItem),
+                    // The rebase failed to start, so the list can be edited again.
+                    Ok(None) => {
+                        this.started = false;
+                        this.status = None;
+                    }
+                    Ok(status) => this.status = status,
+                    Err(error) => this.error = Some(error.to_string().trim().to_string().into()),
+                }
+                cx.notify();
+            })
+            .log_err();
+        }));
+    }
+
+    fn selected_entry_mut(&mut self) -> Option<&mut RebaseTodoEntry> {
+        self.entries.get_mut(self.selected_entry?)
+    }
+
+    fn update_message(&mut self, text: String) {
+        if self.updating_message {
+            return;
+        }
+        if let Some(entry) = self.selected_entry_mut() {
+            entry.message = Some(text);
+        }
+    }
+
+    /// Shows the message of the selected commit when it is reworded, loading the
+    /// original message the first time.
+    fn sync_message_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.selected_entry.and_then(|ix| self.entries.get(ix)) else {
+            return;
+        };
+        if entry.action != RebaseAction::Reword {
+            return;
+        }
+
+        let text = entry.message.clone().unwrap_or_default();
+        self.updating_message = true;
+        self.message_editor.update(cx, |editor, cx| {
+            editor.set_text(text, window, cx);
+        });
+        self.updating_message = false;
+        if entry.message.is_some() {
+            return;
+        }
+
+        let sha = entry.sha.clone();
+        let details = self
+            .repository
+            .update(cx, |repository, _| repository.show(sha.to_string()));
+        cx.spawn_in(window, async move |this, cx| {
+            let details = details.await??;
+            this.update_in(cx, |this, window, cx| {
+                let Some(entry) = this.entries.iter_mut().find(|entry| entry.sha == sha) else {
+                    return;
+                };
+                if entry.message.is_none() {
+                    entry.message = Some(details.message.trim_end().to_string());
+                    this.sync_message_editor(window, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_action(&mut self, action: RebaseAction, window: &mut Window, cx: &mut Context<Self>) {
+        if self.started {
+            return;
+        }
+        let Some(entry) = self.selected_entry_mut() else {
+            return;
+        };
+        entry.action = action;
+        self.error = None;
+        self.sync_message_editor(window, cx);
+        cx.notify();
+    }
+
+    fn pick(&mut self, _: &PickCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(RebaseAction::Pick, window, cx);
+    }
+
+    fn reword(&mut self, _: &RewordCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(RebaseAction::Reword, window, cx);
+        if self.selected_entry.is_some() {
+            window.focus(&self.message_editor.focus_handle(cx));
+        }
+    }
+
+    fn edit(&mut self, _: &EditCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(RebaseAction::Edit, window, cx);
+    }
+
+    fn squash(&mut self, _: &SquashCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(RebaseAction::Squash, window, cx);
+    }
+
+    fn fixup(&mut self, _: &FixupCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(RebaseAction::Fixup, window, cx);
+    }
+
+    fn drop_commit(&mut self, _: &DropCommit, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_action(RebaseAction::Drop, window, cx);
+    }
+
+    fn cycle_action(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_entry(ix, window, cx);
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        let position = RebaseAction::ALL
+            .iter()
+            .position(|action| *action == entry.action)
+            .unwrap_or_default();
+        let next = RebaseAction::ALL[(position + 1) % RebaseAction::ALL.len()];
+        self.set_action(next, window, cx);
+    }
+
+    fn move_entry(&mut self, from: usize, to: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if self.started || from >= self.entries.len() || to >= self.entries.len() {
+            return;
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        self.error = None;
+        self.select_entry(to, window, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveCommitUp, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry.filter(|ix| *ix > 0) {
+            self.move_entry(ix, ix - 1, window, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveCommitDown, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.move_entry(ix, ix + 1, window, cx);
+        }
+    }
+
+    fn select_entry(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix < self.entries.len() {
+            self.selected_entry = Some(ix);
+            self.sync_message_editor(window, cx);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.selected_entry.map_or(0, |ix| ix + 1);
+        self.select_entry(ix, window, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry.and_then(|ix| ix.checked_sub(1)) {
+            self.select_entry(ix, window, cx);
+        }
+    }
+
+    fn validate(&self) -> Result<(), SharedString> {
+        let first_kept = self
+            .entries
+            .iter()
+            .find(|entry| entry.action != RebaseAction::Drop);
+        if first_kept.is_some_and(|entry| entry.action.is_fold()) {
+            return Err(
+                "The first commit can't be squashed or fixed up, as there is no earlier commit to combine it with.".into(),
+            );
+        }
+        if self.entries.iter().any(|entry| {
+            entry.action == RebaseAction::Reword
+                && entry
+                    .message
+                    .as_ref()
+                    .is_some_and(|message| message.trim().is_empty())
+        }) {
+            return Err("Reworded commits need a message.".into());
+        }
+        Ok(())
+    }
+
+    fn start_rebase(&mut self, _: &StartRebase, _: &mut Window, cx: &mut Context<Self>) {
+        if self.started || self.pending_task.is_some() || self.entries.is_empty() {
+            return;
+        }
+        if let Err(error) = self.validate() {
+            self.error = Some(error);
+            cx.notify();
+            return;
+        }
+
+        let job = self.repository.update(cx, |repository, _| {
+            repository.rebase_start(self.onto.to_string(), self.entries.clone())
+        });
+        self.started = true;
+        self.run(job, cx);
+    }
+
+    fn run_command(&mut self, command: RebaseCommand, cx: &mut Context<Self>) {
+        if self.pending_task.is_some() {
+            return;
+        }
+        let job = self
+            .repository
+            .update(cx, |repository, _| repository.rebase_command(command));
+        self.run(job, cx);
+    }
+
+    fn run(&mut self, job: oneshot::Receiver<anyhow::Result<()>>, cx: &mut Context<Self>) {
+        self.error = None;
+        self.pending_task = Some(cx.spawn(async move |this, cx| {
+            let result = job.await.map_err(anyhow::Error::from).and_then(|r| r);
+            this.update(cx, |this, cx| {
+                this.pending_task = None;
+                if let Err(error) = result {
+                    this.error = Some(error.to_string().trim().to_string().into());
+                }
+                this.refresh_status(cx);
+                cx.notify();
+            })
+            .log_err();
+        }));
+        cx.notify();
+    }
+
+    fn open_conflict(&mut self, path: &RepoPath, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(project_path) = self.repository.read(cx).repo_path_to_project_path(path, cx)
+        else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(project_path, None, true, window, cx)
+                    .detach_and_prompt_err("Failed to open file", window, cx, |e, _, _| {
+                        Some(e.to_string())
+                    });
+            })
+            .ok();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &RebaseTodoEntry, cx: &Context<Self>) -> AnyElement {
+        let is_selected = self.selected_entry == Some(ix);
+        let short_sha = entry
+            .sha
+            .get(..SHORT_SHA_LENGTH)
+            .unwrap_or(&*entry.sha)
+            .to_string();
+        let action_color = match entry.action {
+            RebaseAction::Pick => Color::Default,
+            RebaseAction::Reword | RebaseAction::Edit => Color::Accent,
+            RebaseAction::Squash | RebaseAction::Fixup => Color::Warning,
+            RebaseAction::Drop => Color::Deleted,
+        };
+        let subject = entry.subject.clone();
+
+        h_flex()
+            .id(ix)
+            .w_full()
+            .h(px(28.))
+            .px_2()
+            .gap_2()
+            .cursor_grab()
+            .when(is_selected, |this| {
+                this.bg(cx.theme().colors().element_selected)
+            })
+            .hover(|this| this.bg(cx.theme().colors().element_hover))
+            .on_drag(
+                DraggedRebaseEntry { ix, subject },
+                |dragged, _: Point<Pixels>, _, cx| cx.new(|_| dragged.clone()),
+            )
+            .drag_over::<DraggedRebaseEntry>(|style, _, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(
+                cx.listener(move |this, dragged: &DraggedRebaseEntry, window, cx| {
+                    this.move_entry(dragged.ix, ix, window, cx);
+                }),
+            )
+            .on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                this.select_entry(ix, window, cx);
+                window.focus(&this.focus_handle);
+            }))
+            .child(
+                Icon::new(IconName::Menu)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                div().w(px(64.)).child(
+                    Button::new(("action", ix), entry.action.as_str())
+                        .label_size(LabelSize::Small)
+                        .color(action_color)
+                        .tooltip(Tooltip::text("Change action"))
+                        .on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                            this.cycle_action(ix, window, cx);
+                        })),
+                ),
+            )
+            .when(entry.action.is_fold(), |this| {
+                this.child(
+                    Icon::new(IconName::ArrowUp)
+                        .size(IconSize::XSmall)
+                        .color(Color::Muted),
+                )
+            })
+            .child(Label::new(short_sha).color(Color::Muted).buffer_font(cx))
+            .child(
+                Label::new(
+                    entry
+                        .message
+                        .as_ref()
+                        .filter(|_| entry.action == RebaseAction::Reword)
+                        .and_then(|message| message.lines().next())
+                        .map(|subject| SharedString::from(subject.to_string()))
+                        .unwrap_or_else(|| entry.subject.clone()),
+                )
+                .truncate()
+                .when(entry.action == RebaseAction::Drop, |label| {
+                    label.strikethrough().color(Color::Muted)
+                }),
+            )
+            .into_any_element()
+    }
+
+    fn render_todo(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_loading = self.pending_task.is_some();
+        let rewording = self
+            .selected_entry
+            .and_then(|ix| self.entries.get(ix))
+            .is_some_and(|entry| entry.action == RebaseAction::Reword);
+        let focus_handle = self.focus_handle.clone();
+
+        v_flex()
+            .flex_1()
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(format!(
+                        "Rebase {} commits onto {}",
+                        self.entries.len(),
+                        self.onto
+                    )))
+                    .child(
+                        Button::new("start-rebase", "Start Rebase")
+                            .style(ButtonStyle::Filled)
+                            .disabled(is_loading || self.entries.is_empty())
+                            .key_binding(
+                                KeyBinding::for_action_in(&StartRebase, &focus_handle, window, cx)
+                                    .map(|kb| kb.size(rems_from_px(12.))),
+                            )
+                            .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                                this.start_rebase(&StartRebase, window, cx);
+                            })),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .id("rebase-todo")
+                    .key_context("RebaseTodoList")
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::pick))
+                    .on_action(cx.listener(Self::reword))
+                    .on_action(cx.listener(Self::edit))
+                    .on_action(cx.listener(Self::squash))
+                    .on_action(cx.listener(Self::fixup))
+                    .on_action(cx.listener(Self::drop_commit))
+                    .on_action(cx.listener(Self::move_up))
+                    .on_action(cx.listener(Self::move_down))
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::select_previous))
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .map(|this| {
+                        if self.entries.is_empty() {
+                            this.p_2().child(
+                                Label::new(if is_loading {
+                                    "Loading commits…"
+                                } else {
+                                    "There are no commits to rebase"
+                                })
+                                .color(Color::Muted),
+                            )
+                        } else {
+                            this.children(
+                                self.entries
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(ix, entry)| self.render_entry(ix, entry, cx)),
+                            )
+                        }
+                    }),
+            )
+            .when(rewording, |this| {
+                this.child(
+                    v_flex()
+                        .p_2()
+                        .gap_1()
+                        .border_t_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(
+                            Label::new("New message")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(self.message_editor.clone()),
+                )
+            })
+    }
+
+    fn render_progress(&self, status: &RebaseStatus, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_running = self.pending_task.is_some();
+        let conflicts = self
+            .repository
+            .read(cx)
+            .merge
+            .conflicted_paths
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        let title = match &status.head_name {
+            Some(branch) => format!("Rebasing {branch} onto {}", self.onto),
+            None => format!("Rebasing onto {}", self.onto),
+        };
+
+        v_flex()
+            .flex_1()
+            .p_2()
+            .gap_2()
+            .child(Label::new(title))
+            .child(
+                Label::new(format!(
+                    "Step {} of {}",
+                    status.step.min(status.total_steps),
+                    status.total_steps
+                ))
+                .color(Color::Muted),
+            )
+            .when_some(status.stopped_at.as_ref(), |this, sha| {
+                let short_sha = sha.get(..SHORT_SHA_LENGTH).unwrap_or(&**sha).to_string();
+                this.child(
+                    Label::new(if conflicts.is_empty() {
+                        format!("Stopped at {short_sha}. Amend the commit, then continue.")
+                    } else {
+                        format!("Applying {short_sha} caused conflicts. Resolve them and stage the files, then continue.")
+                    })
+                    .color(Color::Muted),
+                )
+            })
+            .when(!conflicts.is_empty(), |this| {
+                this.child(
+                    v_flex().children(conflicts.into_iter().enumerate().map(|(ix, path)| {
+                        h_flex()
+                            .id(("conflict", ix))
+                            .gap_1()
+                            .px_1()
+                            .cursor_pointer()
+                            .hover(|this| this.bg(cx.theme().colors().element_hover))
+                            .child(
+                                Icon::new(IconName::Warning)
+                                    .size(IconSize::Small)
+                                    .color(Color::Conflict),
+                            )
+                            .child(Label::new(path.to_string()))
+                            .on_click(cx.listener(move |this, _: &ClickEvent, window, cx| {
+                                this.open_conflict(&path, window, cx);
+                            }))
+                    })),
+                )
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("continue-rebase", "Continue")
+                            .style(ButtonStyle::Filled)
+                            .disabled(is_running)
+                            .on_click(cx.listener(|this, _: &ClickEvent, _, cx| {
+                                this.run_command(RebaseCommand::Continue, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("skip-rebase", "Skip Commit")
+                            .disabled(is_running)
+                            .tooltip(Tooltip::text("Leave out the commit being applied"))
+                            .on_click(cx.listener(|this, _: &ClickEvent, _, cx| {
+                                this.run_command(RebaseCommand::Skip, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("abort-rebase", "Abort")
+                            .disabled(is_running)
+                            .tooltip(Tooltip::text("Return to the state before the rebase"))
+                            .on_click(cx.listener(|this, _: &ClickEvent, _, cx| {
+                                this.run_command(RebaseCommand::Abort, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+impl EventEmitter<ItemEvent> for RebaseEditor {}
+
+impl Focusable for RebaseEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for RebaseEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        format!("Rebase onto {}", self.onto).into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Rebase Editor Opened")
+    }
+}
+
+impl Render for RebaseEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("RebaseEditor")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::start_rebase))
+            .map(|this| match self.status.clone() {
+                Some(status) => this.child(self.render_progress(&status, cx)),
+                None if self.started => this.child(
+                    h_flex()
+                        .flex_1()
+                        .justify_center()
+                        .child(Label::new("Rebasing…").color(Color::Muted)),
+                ),
+                None => this.child(self.render_todo(window, cx)),
+            })
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .p_2()
+                        .gap_2()
+                        .border_t_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(
+                            Icon::new(IconName::XCircle)
+                                .size(IconSize::Small)
+                                .color(Color::Error),
+                        )
+                        .child(Label::new(error).color(Color::Error)),
+                )
+            })
+    }
+}
@@ -9,6 +9,7 @@ pub enum RemoteAction {
     Fetch(Option<Remote>),
     Pull(Remote),
     Push(SharedString, Remote),
+    PushTag(SharedString, Remote),
 }
 
 impl RemoteAction {
@@ -16,7 +17,7 @@ impl RemoteAction {
         match self {
             RemoteAction::Fetch(_) => "fetch",
             RemoteAction::Pull(_) => "pull",
-            RemoteAction::Push(_, _) => "push",
+            RemoteAction::Push(_, _) | RemoteAction::PushTag(_, _) => "push",
         }
     }
 }
@@ -152,5 +153,18 @@ pub fn format_output(action: &RemoteAction, output: RemoteCommandOutput) -> Succ
                 }
             }
         }
+        RemoteAction::PushTag(tag_name, remote_ref) => {
+            if output.stderr.starts_with("Everything up to date") {
+                SuccessMessage {
+                    message: output.stderr.trim().to_owned(),
+                    style: SuccessStyle::Toast,
+                }
+            } else {
+                SuccessMessage {
+                    message: format!("Pushed tag {} to {}", tag_name, remote_ref.name),
+                    style: SuccessStyle::ToastWithLog { output },
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,586 @@
+use fuzzy::StringMatchCandidate;
+
+use git::repository::{StashEntry, StashScope};
+use gpui::{
+    Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled, Subscription,
+    Task, WeakEntity, Window, actions, rems,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use time_format::format_local_timestamp;
+use ui::{HighlightedLabel, KeyBinding, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{ModalView, Workspace};
+
+use crate::commit_view::CommitView;
+
+actions!(
+    stash_picker,
+    [
+        /// Opens the changes saved in the selected stash entry.
+        ShowStashItem,
+        /// Drops the selected stash entry.
+        DropStashItem,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+}
+
+pub fn open(
+    workspace: &mut Workspace,
+    _: &git::ViewStash,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx);
+    let weak_workspace = cx.weak_entity();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        StashList::new(repository, weak_workspace, rems(34.), window, cx)
+    })
+}
+
+pub struct StashList {
+    width: Rems,
+    pub picker: Entity<Picker<StashListDelegate>>,
+    _subscription: Subscription,
+}
+
+impl StashList {
+    fn new(
+        repository: Option<Entity<Repository>>,
+        workspace: WeakEntity<Workspace>,
+        width: Rems,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = StashListDelegate::new(repository.clone(), workspace);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        picker.update(cx, |picker, cx| picker.delegate.reload(window, cx));
+
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+
+        Self {
+            picker,
+            width,
+            _subscription,
+        }
+    }
+
+    fn handle_show_stash_item(
+        &mut self,
+        _: &ShowStashItem,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.show_selected_entry(window, cx);
+        })
+    }
+
+    fn handle_drop_stash_item(
+        &mut self,
+        _: &DropStashItem,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.drop_selected_entry(window, cx);
+        })
+    }
+}
+
+impl ModalView for StashList {}
+impl EventEmitter<DismissEvent> for StashList {}
+
+impl Focusable for StashList {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for StashList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("StashList")
+            .w(self.width)
+            .on_action(cx.listener(Self::handle_show_stash_item))
+            .on_action(cx.listener(Self::handle_drop_stash_item))
+            .child(self.picker.clone())
+            .on_mouse_down_out({
+                cx.listener(move |this, _, window, cx| {
+                    this.picker.update(cx, |this, cx| {
+                        this.cancel(&Default::default(), window, cx);
+                    })
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum StashMatch {
+    Entry {
+        entry: StashEntry,
+        positions: Vec<usize>,
+    },
+    New {
+        message: String,
+    },
+}
+
+pub struct StashListDelegate {
+    matches: Vec<StashMatch>,
+    all_entries: Option<Vec<StashEntry>>,
+    repo: Option<Entity<Repository>>,
+    workspace: WeakEntity<Workspace>,
+    selected_index: usize,
+}
+
+impl StashListDelegate {
+    fn new(repo: Option<Entity<Repository>>, workspace: WeakEntity<Workspace>) -> Self {
+        Self {
+            matches: vec![],
+            all_entries: None,
+            repo,
+            workspace,
+            selected_index: 0,
+        }
+    }
+
+    fn reload(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let stash_list = repo.update(cx, |repo, _| repo.stash_list());
+        cx.spawn_in(window, async move |picker, cx| {
+            let entries = stash_list.await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.all_entries = Some(entries);
+                picker.refresh(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn selected_entry(&self) -> Option<&StashEntry> {
+        match self.matches.get(self.selected_index)? {
+            StashMatch::Entry { entry, .. } => Some(entry),
+            StashMatch::New { .. } => None,
+        }
+    }
+
+    fn show_selected_entry(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(entry), Some(repo)) = (self.selected_entry().cloned(), self.repo.as_ref()) else {
+            return;
+        };
+        CommitView::open_stash(entry, repo.downgrade(), self.workspace.clone(), window, cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn drop_selected_entry(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(entry), Some(repo)) = (self.selected_entry().cloned(), self.repo.clone()) else {
+            return;
+        };
+        cx.spawn_in(window, async move |picker, cx| {
+            repo.update(cx, |repo, _| repo.stash_drop(entry.index))?
+                .await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.reload(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to drop stash", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+}
+
+impl PickerDelegate for StashListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select stash or type a message to stash changes…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(all_entries) = self.all_entries.clone() else {
+            return Task::ready(());
+        };
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let mut matches: Vec<StashMatch> = if query.is_empty() {
+                all_entries
+                    .into_iter()
+                    .map(|entry| StashMatch::Entry {
+                        entry,
+                        positions: Vec::new(),
+                    })
+                    .collect()
+            } else {
+                let candidates = all_entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, entry)| StringMatchCandidate::new(ix, &entry.message))
+                    .collect::<Vec<StringMatchCandidate>>();
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+                .into_iter()
+                .map(|candidate| StashMatch::Entry {
+                    entry: all_entries[candidate.candidate_id].clone(),
+                    positions: candidate.positions,
+                })
+                .collect()
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let query = query.trim();
+                    if !query.is_empty() {
+                        matches.push(StashMatch::New {
+                            message: query.to_string(),
+                        });
+                    }
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            core::cmp::min(delegate.selected_index, delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(stash_match) = self.matches.get(self.selected_index()).cloned() else {
+            return;
+        };
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+
+        match stash_match {
+            StashMatch::New { message } => {
+                cx.spawn(async move |_, cx| {
+                    repo.update(cx, |repo, cx| {
+                        repo.stash_push(StashScope::All, Some(message), cx)
+                    })?
+                    .await??;
+                    anyhow::Ok(())
+                })
+                .detach_and_prompt_err(
+                    "Failed to stash changes",
+                    window,
+                    cx,
+                    |e, _, _| Some(e.to_string()),
+                );
+            }
+            StashMatch::Entry { entry, .. } => {
+                cx.spawn(async move |_, cx| {
+                    repo.update(cx, |repo, _| {
+                        if secondary {
+                            repo.stash_pop(entry.index)
+                        } else {
+                            repo.stash_apply(entry.index)
+                        }
+                    })?
+                    .await??;
+                    anyhow::Ok(())
+                })
+                .detach_and_prompt_err(
+                    "Failed to apply stash",
+                    window,
+                    cx,
+                    |e, _, _| Some(e.to_string()),
+                );
+            }
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let (entry, positions) = match &self.matches[ix] {
+            StashMatch::Entry { entry, positions } => (entry, positions),
+            StashMatch::New { message } => {
+                return Some(
+                    ListItem::new(SharedString::from(format!("stash-{ix}")))
+                        .inset(true)
+                        .spacing(ListItemSpacing::Sparse)
+                        .toggle_state(selected)
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Icon::new(IconName::Plus)
+                                        .size(IconSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    Label::new(format!("Stash all changes as \"{message}\"…"))
+                                        .single_line()
+                                        .truncate(),
+                                ),
+                        ),
+                );
+            }
+        };
+
+        let stash_time = OffsetDateTime::from_unix_timestamp(entry.commit_timestamp)
+            .unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let formatted_time = format_local_timestamp(
+            stash_time,
+            OffsetDateTime::now_utc(),
+            time_format::TimestampFormat::Relative,
+        );
+
+        Some(
+            ListItem::new(SharedString::from(format!("stash-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    v_flex()
+                        .w_full()
+                        .overflow_hidden()
+                        .child(
+                            h_flex()
+                                .gap_6()
+                                .justify_between()
+                                .overflow_x_hidden()
+                                .child(
+                                    HighlightedLabel::new(entry.message.clone(), positions.clone())
+                                        .truncate(),
+                                )
+                                .child(
+                                    Label::new(formatted_time)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        )
+                        .child(
+                            Label::new(entry.reference())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No stash entries".into())
+    }
+
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        self.selected_entry()?;
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(footer_button(
+                    "drop-stash",
+                    "Drop",
+                    DropStashItem.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .child(footer_button(
+                    "show-stash",
+                    "View",
+                    ShowStashItem.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .child(footer_button(
+                    "pop-stash",
+                    "Pop",
+                    menu::SecondaryConfirm.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .child(footer_button(
+                    "apply-stash",
+                    "Apply",
+                    menu::Confirm.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .into_any(),
+        )
+    }
+}
+
+fn footer_button(
+    id: &'static str,
+    label: &'static str,
+    action: Box<dyn Action>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Button {
+    Button::new(id, label)
+        .key_binding(KeyBinding::for_action(&*action, window, cx))
+        .on_click(move |_, window, cx| window.dispatch_action(action.boxed_clone(), cx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_settings::AgentSettings;
+    use editor::Editor;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project, WorktreeSettings};
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use std::path::Path;
+    use theme::LoadThemes;
+    use util::path;
+    use workspace::item::ItemHandle as _;
+
+    fn init_test(cx: &mut TestAppContext) {
+        zlog::init_test();
+
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AgentSettings::register(cx);
+            WorktreeSettings::register(cx);
+            workspace::init_settings(cx);
+            theme::init(LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_show_stash_entry(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": "one\n2\nthree\n",
+                "b.txt": "unchanged\n",
+            }),
+        )
+        .await;
+        fs.set_head_and_index_for_repo(
+            Path::new(path!("/project/.git")),
+            &[
+                ("a.txt".into(), "one\ntwo\nthree\n".into()),
+                ("b.txt".into(), "unchanged\n".into()),
+            ],
+        );
+
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let workspace =
+            cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        cx.run_until_parked();
+
+        let repository = project
+            .read_with(cx, |project, cx| project.active_repository(cx))
+            .unwrap();
+        repository
+            .update(cx, |repository, cx| {
+                repository.stash_push(StashScope::All, Some("Work in progress".into()), cx)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let stash_list = workspace
+            .update(cx, |workspace, window, cx| {
+                let weak_workspace = cx.weak_entity();
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    StashList::new(Some(repository), weak_workspace, rems(34.), window, cx)
+                });
+                workspace.active_modal::<StashList>(cx).unwrap()
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        stash_list.update_in(cx, |stash_list, window, cx| {
+            stash_list.handle_show_stash_item(&ShowStashItem, window, cx);
+        });
+        cx.run_until_parked();
+
+        let editor = workspace
+            .update(cx, |workspace, _, cx| {
+                workspace
+                    .active_item(cx)
+                    .and_then(|item| item.act_as::<Editor>(cx))
+            })
+            .unwrap()
+            .expect("the stash should be shown in a commit view");
+        let texts = editor.read_with(cx, |editor, cx| {
+            editor
+                .buffer()
+                .read(cx)
+                .all_buffers()
+                .into_iter()
+                .map(|buffer| buffer.read(cx).text())
+                .collect::<Vec<_>>()
+        });
+        assert!(texts.iter().any(|text| text == "one\n2\nthree\n"));
+        assert!(!texts.iter().any(|text| text == "unchanged\n"));
+    }
+}
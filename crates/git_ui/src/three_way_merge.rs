@@ -0,0 +1,282 @@
+//! Merges the changes that two sides made to a common base, line by line.
+//!
+//! Changes that only one side made, or that both sides made identically, are taken
+//! automatically. Changes that overlap, or that touch each other, are conflicts.
+
+use std::ops::Range;
+
+use language::line_diff;
+
+pub const OURS_MARKER: &str = "<<<<<<< ours\n";
+pub const BASE_MARKER: &str = "||||||| base\n";
+pub const SEPARATOR_MARKER: &str = "=======\n";
+pub const THEIRS_MARKER: &str = ">>>>>>> theirs\n";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeHunkKind {
+    /// Only our side changed these lines.
+    Ours,
+    /// Only their side changed these lines.
+    Theirs,
+    /// Both sides made the same change to these lines.
+    Both,
+    /// Both sides changed these lines, differently.
+    Conflict,
+}
+
+/// A range of lines that at least one side changed, as rows in each version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeHunk {
+    pub kind: MergeHunkKind,
+    pub base: Range<u32>,
+    pub ours: Range<u32>,
+    pub theirs: Range<u32>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MergeResult {
+    pub text: String,
+    /// The number of changes that were merged automatically.
+    pub resolved: usize,
+    pub conflicts: usize,
+}
+
+pub fn merge_hunks(base: &str, ours: &str, theirs: &str) -> Vec<MergeHunk> {
+    let mut our_edits = line_diff(base, ours).into_iter().peekable();
+    let mut their_edits = line_diff(base, theirs).into_iter().peekable();
+    let our_lines = lines(ours);
+    let their_lines = lines(theirs);
+    let mut our_delta = 0i64;
+    let mut their_delta = 0i64;
+    let mut hunks = Vec::new();
+
+    loop {
+        let ours_first = match (our_edits.peek(), their_edits.peek()) {
+            (None, None) => break,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some((our_base, _)), Some((their_base, _))) => our_base.start <= their_base.start,
+        };
+        let our_delta_before = our_delta;
+        let their_delta_before = their_delta;
+        let mut base_range = if ours_first {
+            our_edits.peek().unwrap().0.clone()
+        } else {
+            their_edits.peek().unwrap().0.clone()
+        };
+        let mut ours_changed = false;
+        let mut theirs_changed = false;
+
+        // Grow the hunk until no edit from either side overlaps or touches it.
+        loop {
+            let touches =
+                |(edit_base, _): &(Range<u32>, Range<u32>)| edit_base.start <= base_range.end;
+            if let Some((edit_base, edit_new)) = our_edits.next_if(touches) {
+                base_range.end = base_range.end.max(edit_base.end);
+                our_delta += edit_new.len() as i64 - edit_base.len() as i64;
+                ours_changed = true;
+            } else if let Some((edit_base, edit_new)) = their_edits.next_if(touches) {
+                base_range.end = base_range.end.max(edit_base.end);
+                their_delta += edit_new.len() as i64 - edit_base.len() as i64;
+                theirs_changed = true;
+            } else {
+                break;
+            }
+        }
+
+        let shift = |range: &Range<u32>, before: i64, after: i64| {
+            (range.start as i64 + before) as u32..(range.end as i64 + after) as u32
+        };
+        let ours_range = shift(&base_range, our_delta_before, our_delta);
+        let theirs_range = shift(&base_range, their_delta_before, their_delta);
+        let kind = match (ours_changed, theirs_changed) {
+            (true, false) => MergeHunkKind::Ours,
+            (false, true) => MergeHunkKind::Theirs,
+            _ if our_lines[range(&ours_range)] == their_lines[range(&theirs_range)] => {
+                MergeHunkKind::Both
+            }
+            _ => MergeHunkKind::Conflict,
+        };
+        hunks.push(MergeHunk {
+            kind,
+            base: base_range,
+            ours: ours_range,
+            theirs: theirs_range,
+        });
+    }
+
+    hunks
+}
+
+/// Merges the two sides, writing conflict markers around the lines that can't be
+/// merged automatically.
+pub fn merge_text(base: &str, ours: &str, theirs: &str) -> MergeResult {
+    let base_lines = lines(base);
+    let our_lines = lines(ours);
+    let their_lines = lines(theirs);
+    let mut text = String::new();
+    let mut resolved = 0;
+    let mut conflicts = 0;
+    let mut base_row = 0;
+    for hunk in merge_hunks(base, ours, theirs) {
+        text.extend(
+            base_lines[base_row..hunk.base.start as usize]
+                .iter()
+                .copied(),
+        );
+        base_row = hunk.base.end as usize;
+        if hunk.kind != MergeHunkKind::Conflict {
+            resolved += 1;
+        }
+        match hunk.kind {
+            MergeHunkKind::Ours | MergeHunkKind::Both => {
+                text.extend(our_lines[range(&hunk.ours)].iter().copied());
+            }
+            MergeHunkKind::Theirs => text.extend(their_lines[range(&hunk.theirs)].iter().copied()),
+            MergeHunkKind::Conflict => {
+                conflicts += 1;
+                for (marker, lines) in [
+                    (OURS_MARKER, &our_lines[range(&hunk.ours)]),
+                    (BASE_MARKER, &base_lines[range(&hunk.base)]),
+                    (SEPARATOR_MARKER, &their_lines[range(&hunk.theirs)]),
+                ] {
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    text.push_str(marker);
+                    text.extend(lines.iter().copied());
+                }
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(THEIRS_MARKER);
+            }
+        }
+    }
+    text.extend(base_lines[base_row..].iter().copied());
+    MergeResult {
+        text,
+        resolved,
+        conflicts,
+    }
+}
+
+/// Finds the rows of the base that correspond to the given rows of one side, given
+/// the text of both.
+pub fn base_rows(base: &str, side: &str, rows: Range<u32>) -> Range<u32> {
+    let mut delta_before = 0i64;
+    let mut delta_within = 0i64;
+    let mut start = None;
+    let mut end = None;
+    for (edit_base, edit_side) in line_diff(base, side) {
+        let delta = edit_base.len() as i64 - edit_side.len() as i64;
+        if edit_side.end < rows.start {
+            delta_before += delta;
+        } else if edit_side.start <= rows.end {
+            start.get_or_insert(edit_base.start);
+            end = Some(edit_base.end);
+            delta_within += delta;
+        } else {
+            break;
+        }
+    }
+    // Edits that touch the edges of the rows belong to them, so the range may need to
+    // grow to cover the base rows that they replaced.
+    let mapped_start = (rows.start as i64 + delta_before) as u32;
+    let mapped_end = (rows.end as i64 + delta_before + delta_within) as u32;
+    start.map_or(mapped_start, |start| start.min(mapped_start))
+        ..end.map_or(mapped_end, |end| end.max(mapped_end))
+}
+
+/// Finds the first occurrence of the lines of `needle` in `text`, at or after the
+/// given row.
+pub fn find_rows(text: &str, needle: &str, from_row: u32) -> Option<Range<u32>> {
+    let text_lines = lines(text);
+    let needle_lines = lines(needle);
+    if needle_lines.is_empty() {
+        return None;
+    }
+    (from_row as usize..=text_lines.len().checked_sub(needle_lines.len())?)
+        .find(|&row| text_lines[row..row + needle_lines.len()] == needle_lines[..])
+        .map(|row| row as u32..(row + needle_lines.len()) as u32)
+}
+
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn range(rows: &Range<u32>) -> Range<usize> {
+    rows.start as usize..rows.end as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "a\nb\nc\nd\ne\nf\ng\n";
+    const OURS: &str = "a\nB\nc\nd\ne\nf\ng\nh\n";
+
+    #[test]
+    fn test_non_overlapping_changes() {
+        let theirs = "a\nb\nc\nD\ne\nf\ng\n";
+        assert_eq!(
+            merge_hunks(BASE, OURS, theirs)
+                .into_iter()
+                .map(|hunk| (hunk.kind, hunk.base))
+                .collect::<Vec<_>>(),
+            [
+                (MergeHunkKind::Ours, 1..2),
+                (MergeHunkKind::Theirs, 3..4),
+                (MergeHunkKind::Ours, 7..7),
+            ]
+        );
+        assert_eq!(
+            merge_text(BASE, OURS, theirs),
+            MergeResult {
+                text: "a\nB\nc\nD\ne\nf\ng\nh\n".into(),
+                resolved: 3,
+                conflicts: 0,
+            }
+        );
+        assert_eq!(
+            merge_text(BASE, OURS, OURS),
+            MergeResult {
+                text: OURS.into(),
+                resolved: 2,
+                conflicts: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_conflicting_changes() {
+        let theirs = "a\nX\nc\nd\ne\nF\ng\n";
+        assert_eq!(
+            merge_text(BASE, OURS, theirs),
+            MergeResult {
+                text: concat!(
+                    "a\n<<<<<<< ours\nB\n||||||| base\nb\n=======\nX\n>>>>>>> theirs\n",
+                    "c\nd\ne\nF\ng\nh\n",
+                )
+                .into(),
+                resolved: 2,
+                conflicts: 1,
+            }
+        );
+
+        // Markers always start on their own line.
+        assert_eq!(
+            merge_text("a\nb", "a\nc", "a\nd").text,
+            "a\n<<<<<<< ours\nc\n||||||| base\nb\n=======\nd\n>>>>>>> theirs\n"
+        );
+    }
+
+    #[test]
+    fn test_base_rows() {
+        assert_eq!(base_rows(BASE, OURS, 1..2), 1..2);
+        assert_eq!(base_rows(BASE, OURS, 6..8), 6..7);
+        assert_eq!(base_rows(BASE, "a\nb\nx\ny\nz\ng\n", 2..5), 2..6);
+        assert_eq!(find_rows(OURS, "e\nf\n", 0), Some(4..6));
+        assert_eq!(find_rows(OURS, "e\nf\n", 5), None);
+    }
+}
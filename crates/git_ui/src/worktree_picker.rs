@@ -0,0 +1,674 @@
+use collections::HashSet;
+use fuzzy::StringMatchCandidate;
+
+use git::repository::{Branch, GitWorktree};
+use gpui::{
+    Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    InteractiveElement, IntoElement, ParentElement, PromptLevel, Render, SharedString, Styled,
+    Subscription, Task, Window, actions, rems,
+};
+use picker::{Picker, PickerDelegate};
+use project::git_store::Repository;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use ui::{HighlightedLabel, KeyBinding, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::notifications::DetachAndPromptErr;
+use workspace::{AppState, ModalView, OpenOptions, Workspace};
+
+actions!(
+    worktree_picker,
+    [
+        /// Removes the selected linked worktree.
+        RemoveWorktree,
+    ]
+);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(open);
+    workspace.register_action(prune);
+}
+
+pub fn open(
+    workspace: &mut Workspace,
+    _: &git::ViewWorktrees,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let repository = workspace.project().read(cx).active_repository(cx);
+    let app_state = workspace.app_state().clone();
+    workspace.toggle_modal(window, cx, |window, cx| {
+        WorktreeList::new(repository, app_state, rems(34.), window, cx)
+    })
+}
+
+fn prune(
+    workspace: &mut Workspace,
+    _: &git::PruneWorktrees,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let prune = repository.update(cx, |repository, _| repository.prune_worktrees());
+    cx.spawn(async move |_, _| {
+        prune.await??;
+        anyhow::Ok(())
+    })
+    .detach_and_prompt_err("Failed to prune worktrees", window, cx, |e, _, _| {
+        Some(e.to_string())
+    });
+}
+
+pub struct WorktreeList {
+    width: Rems,
+    pub picker: Entity<Picker<WorktreeListDelegate>>,
+    _subscription: Subscription,
+}
+
+impl WorktreeList {
+    fn new(
+        repository: Option<Entity<Repository>>,
+        app_state: Arc<AppState>,
+        width: Rems,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = WorktreeListDelegate::new(repository, app_state, cx);
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        picker.update(cx, |picker, cx| picker.delegate.reload(window, cx));
+
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+
+        Self {
+            picker,
+            width,
+            _subscription,
+        }
+    }
+
+    fn handle_remove_worktree(
+        &mut self,
+        _: &RemoveWorktree,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.remove_selected_worktree(window, cx);
+        })
+    }
+
+    fn handle_prune_worktrees(
+        &mut self,
+        _: &git::PruneWorktrees,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.prune_worktrees(window, cx);
+        })
+    }
+}
+
+impl ModalView for WorktreeList {}
+impl EventEmitter<DismissEvent> for WorktreeList {}
+
+impl Focusable for WorktreeList {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for WorktreeList {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("WorktreeList")
+            .w(self.width)
+            .on_action(cx.listener(Self::handle_remove_worktree))
+            .on_action(cx.listener(Self::handle_prune_worktrees))
+            .child(self.picker.clone())
+            .on_mouse_down_out({
+                cx.listener(move |this, _, window, cx| {
+                    this.picker.update(cx, |this, cx| {
+                        this.cancel(&Default::default(), window, cx);
+                    })
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum WorktreeEntry {
+    Worktree(GitWorktree),
+    /// A branch that isn't checked out in any worktree yet.
+    Branch(Branch),
+    NewBranch {
+        name: String,
+    },
+}
+
+impl WorktreeEntry {
+    fn label(&self) -> String {
+        match self {
+            WorktreeEntry::Worktree(worktree) => worktree.path.to_string_lossy().into_owned(),
+            WorktreeEntry::Branch(branch) => branch.name().to_string(),
+            WorktreeEntry::NewBranch { name } => name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WorktreeMatch {
+    entry: WorktreeEntry,
+    positions: Vec<usize>,
+}
+
+pub struct WorktreeListDelegate {
+    matches: Vec<WorktreeMatch>,
+    all_entries: Option<Vec<WorktreeEntry>>,
+    repo: Option<Entity<Repository>>,
+    current_path: Option<Arc<Path>>,
+    app_state: Arc<AppState>,
+    selected_index: usize,
+}
+
+impl WorktreeListDelegate {
+    fn new(repo: Option<Entity<Repository>>, app_state: Arc<AppState>, cx: &App) -> Self {
+        let current_path = repo
+            .as_ref()
+            .map(|repo| repo.read(cx).work_directory_abs_path.clone());
+        Self {
+            matches: vec![],
+            all_entries: None,
+            repo,
+            current_path,
+            app_state,
+            selected_index: 0,
+        }
+    }
+
+    fn reload(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        let (worktrees, branches) = repo.update(cx, |repo, _| (repo.worktrees(), repo.branches()));
+        cx.spawn_in(window, async move |picker, cx| {
+            let worktrees = worktrees.await??;
+            let mut branches = branches.await??;
+
+            // Remote branches are offered as their local branch, unless it already exists.
+            let local_branches = branches
+                .iter()
+                .filter(|branch| !branch.is_remote())
+                .map(|branch| branch.name().to_string())
+                .collect::<HashSet<_>>();
+            branches.retain(|branch| {
+                let checked_out = worktrees
+                    .iter()
+                    .any(|worktree| worktree.branch.as_ref() == Some(&branch.ref_name));
+                !checked_out
+                    && (!branch.is_remote() || !local_branches.contains(local_branch_name(branch)))
+            });
+            branches.sort_by_key(|branch| Reverse(branch.priority_key()));
+
+            let entries = worktrees
+                .into_iter()
+                .map(WorktreeEntry::Worktree)
+                .chain(branches.into_iter().map(WorktreeEntry::Branch))
+                .collect();
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.all_entries = Some(entries);
+                picker.refresh(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn main_worktree_path(&self) -> Option<&Path> {
+        self.all_entries
+            .as_ref()?
+            .iter()
+            .find_map(|entry| match entry {
+                WorktreeEntry::Worktree(worktree) if worktree.is_main => {
+                    Some(worktree.path.as_path())
+                }
+                _ => None,
+            })
+    }
+
+    /// Returns the selected worktree, if it can be removed: the main worktree can't, and
+    /// neither can the worktree that this project is in.
+    fn selected_removable_worktree(&self) -> Option<&GitWorktree> {
+        match &self.matches.get(self.selected_index)?.entry {
+            WorktreeEntry::Worktree(worktree)
+                if !worktree.is_main
+                    && self.current_path.as_deref() != Some(worktree.path.as_path()) =>
+            {
+                Some(worktree)
+            }
+            _ => None,
+        }
+    }
+
+    fn has_prunable_worktrees(&self) -> bool {
+        self.all_entries
+            .iter()
+            .flatten()
+            .any(|entry| matches!(entry, WorktreeEntry::Worktree(worktree) if worktree.is_prunable))
+    }
+
+    fn remove_selected_worktree(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let (Some(worktree), Some(repo)) = (
+            self.selected_removable_worktree().cloned(),
+            self.repo.clone(),
+        ) else {
+            return;
+        };
+        cx.spawn_in(window, async move |picker, cx| {
+            let path = worktree.path;
+            let result = repo
+                .update(cx, |repo, _| repo.remove_worktree(path.clone(), false))?
+                .await?;
+            if let Err(error) = result {
+                let detail = error.to_string();
+                let answer = picker.update_in(cx, |_, window, cx| {
+                    window.prompt(
+                        PromptLevel::Warning,
+                        "Remove the worktree anyway?",
+                        Some(&detail),
+                        &["Remove", "Cancel"],
+                        cx,
+                    )
+                })?;
+                if answer.await? != 0 {
+                    return Ok(());
+                }
+                repo.update(cx, |repo, _| repo.remove_worktree(path, true))?
+                    .await??;
+            }
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.reload(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to remove worktree", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn prune_worktrees(&mut self, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(repo) = self.repo.clone() else {
+            return;
+        };
+        cx.spawn_in(window, async move |picker, cx| {
+            repo.update(cx, |repo, _| repo.prune_worktrees())?.await??;
+            picker.update_in(cx, |picker, window, cx| {
+                picker.delegate.reload(window, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to prune worktrees", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+}
+
+fn open_worktree(
+    path: PathBuf,
+    app_state: Arc<AppState>,
+    cx: &mut App,
+) -> Task<anyhow::Result<()>> {
+    let open = workspace::open_paths(
+        &[path],
+        app_state,
+        OpenOptions {
+            open_new_workspace: Some(true),
+            ..Default::default()
+        },
+        cx,
+    );
+    cx.spawn(async move |_| {
+        open.await?;
+        Ok(())
+    })
+}
+
+/// The name of the local branch that checking out the given branch would use.
+fn local_branch_name(branch: &Branch) -> &str {
+    let name = branch.name();
+    if branch.is_remote() {
+        name.split_once('/').map_or(name, |(_, name)| name)
+    } else {
+        name
+    }
+}
+
+/// New worktrees are created next to the main worktree, named after it and the branch,
+/// e.g. `~/src/zed-my-feature` for the branch `my-feature` of `~/src/zed`.
+fn new_worktree_path(main_worktree_path: &Path, branch_name: &str) -> Option<PathBuf> {
+    let repository_name = main_worktree_path.file_name()?.to_string_lossy();
+    let branch_name = branch_name.replace('/', "-");
+    Some(
+        main_worktree_path
+            .parent()?
+            .join(format!("{repository_name}-{branch_name}")),
+    )
+}
+
+impl PickerDelegate for WorktreeListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select a worktree to open, or a branch to create one…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(all_entries) = self.all_entries.clone() else {
+            return Task::ready(());
+        };
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let mut matches: Vec<WorktreeMatch> = if query.is_empty() {
+                all_entries
+                    .into_iter()
+                    .map(|entry| WorktreeMatch {
+                        entry,
+                        positions: Vec::new(),
+                    })
+                    .collect()
+            } else {
+                let candidates = all_entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, entry)| StringMatchCandidate::new(ix, &entry.label()))
+                    .collect::<Vec<StringMatchCandidate>>();
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+                .into_iter()
+                .map(|candidate| WorktreeMatch {
+                    entry: all_entries[candidate.candidate_id].clone(),
+                    positions: candidate.positions,
+                })
+                .collect()
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let query = query.trim().replace(' ', "-");
+                    let branch_exists = all_entries.iter().any(|entry| match entry {
+                        WorktreeEntry::Worktree(worktree) => {
+                            worktree.branch_name() == Some(query.as_str())
+                        }
+                        WorktreeEntry::Branch(branch) => local_branch_name(branch) == query,
+                        WorktreeEntry::NewBranch { .. } => false,
+                    });
+                    if !query.is_empty() && !branch_exists {
+                        matches.push(WorktreeMatch {
+                            entry: WorktreeEntry::NewBranch { name: query },
+                            positions: Vec::new(),
+                        });
+                    }
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            core::cmp::min(delegate.selected_index, delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(worktree_match) = self.matches.get(self.selected_index()).cloned() else {
+            return;
+        };
+
+        let (branch_name, commit, new_branch) = match worktree_match.entry {
+            WorktreeEntry::Worktree(worktree) => {
+                open_worktree(worktree.path, self.app_state.clone(), cx).detach_and_prompt_err(
+                    "Failed to open worktree",
+                    window,
+                    cx,
+                    |e, _, _| Some(e.to_string()),
+                );
+                cx.emit(DismissEvent);
+                return;
+            }
+            WorktreeEntry::Branch(branch) => {
+                let branch_name = local_branch_name(&branch).to_string();
+                let new_branch = branch.is_remote().then(|| branch_name.clone());
+                (branch_name, branch.name().to_string(), new_branch)
+            }
+            WorktreeEntry::NewBranch { name } => (name.clone(), "HEAD".to_string(), Some(name)),
+        };
+        let (Some(repo), Some(path)) = (
+            self.repo.clone(),
+            self.main_worktree_path()
+                .and_then(|main_path| new_worktree_path(main_path, &branch_name)),
+        ) else {
+            return;
+        };
+
+        let create = repo.update(cx, |repo, _| {
+            repo.create_worktree(path.clone(), commit, new_branch)
+        });
+        let app_state = self.app_state.clone();
+        cx.spawn(async move |_, cx| {
+            create.await??;
+            cx.update(|cx| open_worktree(path, app_state, cx))?.await
+        })
+        .detach_and_prompt_err("Failed to create worktree", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let WorktreeMatch { entry, positions } = &self.matches[ix];
+        let (icon, description) = match entry {
+            WorktreeEntry::Worktree(worktree) => {
+                let mut description = match (worktree.branch_name(), &worktree.head_sha) {
+                    (Some(branch_name), _) => branch_name.to_string(),
+                    (None, Some(sha)) => {
+                        format!(
+                            "detached at {}",
+                            &sha[..git::SHORT_SHA_LENGTH.min(sha.len())]
+                        )
+                    }
+                    (None, None) => String::new(),
+                };
+                for (flag, name) in [
+                    (worktree.is_main, "main worktree"),
+                    (
+                        self.current_path.as_deref() == Some(worktree.path.as_path()),
+                        "current",
+                    ),
+                    (worktree.is_locked, "locked"),
+                    (worktree.is_prunable, "prunable"),
+                ] {
+                    if flag {
+                        description.push_str(" · ");
+                        description.push_str(name);
+                    }
+                }
+                (IconName::Folder, description)
+            }
+            WorktreeEntry::Branch(branch) => (
+                IconName::GitBranch,
+                if branch.is_remote() {
+                    "Create a worktree tracking this branch".to_string()
+                } else {
+                    "Create a worktree for this branch".to_string()
+                },
+            ),
+            WorktreeEntry::NewBranch { name } => {
+                return Some(
+                    ListItem::new(SharedString::from(format!("worktree-{ix}")))
+                        .inset(true)
+                        .spacing(ListItemSpacing::Sparse)
+                        .toggle_state(selected)
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Icon::new(IconName::Plus)
+                                        .size(IconSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .child(
+                                    Label::new(format!(
+                                        "Create a worktree with the new branch \"{name}\"…"
+                                    ))
+                                    .single_line()
+                                    .truncate(),
+                                ),
+                        ),
+                );
+            }
+        };
+
+        Some(
+            ListItem::new(SharedString::from(format!("worktree-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+                .child(
+                    v_flex()
+                        .w_full()
+                        .overflow_hidden()
+                        .child(HighlightedLabel::new(entry.label(), positions.clone()).truncate())
+                        .child(
+                            Label::new(description)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        ),
+                ),
+        )
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No worktrees".into())
+    }
+
+    fn render_footer(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<AnyElement> {
+        let selected = &self.matches.get(self.selected_index)?.entry;
+        let confirm_label = match selected {
+            WorktreeEntry::Worktree(_) => "Open",
+            WorktreeEntry::Branch(_) | WorktreeEntry::NewBranch { .. } => "Create",
+        };
+        Some(
+            h_flex()
+                .w_full()
+                .p_1p5()
+                .gap_0p5()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .when(self.has_prunable_worktrees(), |this| {
+                    this.child(footer_button(
+                        "prune-worktrees",
+                        "Prune",
+                        git::PruneWorktrees.boxed_clone(),
+                        window,
+                        cx,
+                    ))
+                })
+                .when(self.selected_removable_worktree().is_some(), |this| {
+                    this.child(footer_button(
+                        "remove-worktree",
+                        "Remove",
+                        RemoveWorktree.boxed_clone(),
+                        window,
+                        cx,
+                    ))
+                })
+                .child(footer_button(
+                    "confirm-worktree",
+                    confirm_label,
+                    menu::Confirm.boxed_clone(),
+                    window,
+                    cx,
+                ))
+                .into_any(),
+        )
+    }
+}
+
+fn footer_button(
+    id: &'static str,
+    label: &'static str,
+    action: Box<dyn Action>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Button {
+    Button::new(id, label)
+        .key_binding(KeyBinding::for_action(&*action, window, cx))
+        .on_click(move |_, window, cx| window.dispatch_action(action.boxed_clone(), cx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_worktree_path() {
+        assert_eq!(
+            new_worktree_path(Path::new("/home/user/src/zed"), "feature/worktrees"),
+            Some(PathBuf::from("/home/user/src/zed-feature-worktrees"))
+        );
+        assert_eq!(new_worktree_path(Path::new("/"), "main"), None);
+    }
+}
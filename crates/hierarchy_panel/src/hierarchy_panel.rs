@@ -0,0 +1,710 @@
+mod hierarchy_panel_settings;
+
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Result;
+use collections::HashMap;
+use editor::{Editor, SelectionEffects, scroll::Autoscroll};
+use gpui::{
+    Action, App, AsyncWindowContext, ClickEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    KeyContext, Pixels, ScrollStrategy, SharedString, Task, UniformListScrollHandle, WeakEntity,
+    Window, actions, px, uniform_list,
+};
+use hierarchy_panel_settings::{HierarchyPanelDockPosition, HierarchyPanelSettings};
+use language::{OffsetRangeExt as _, ToPoint as _};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use panel::{PanelHeader, panel_icon_button};
+use project::{Fs, HierarchyItem, Location, Project, ProjectItem as _};
+use settings::Settings as _;
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+use zed_actions::hierarchy_panel::{
+    ShowIncomingCalls, ShowOutgoingCalls, ShowSubtypes, ShowSupertypes,
+};
+
+actions!(
+    hierarchy_panel,
+    [
+        /// Toggles focus on the hierarchy panel.
+        ToggleFocus,
+        /// Switches between incoming and outgoing calls, or between supertypes and subtypes.
+        SwitchDirection,
+        /// Expands the selected entry, fetching its children if needed.
+        ExpandSelectedEntry,
+        /// Collapses the selected entry.
+        CollapseSelectedEntry
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    HierarchyPanelSettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<HierarchyPanel>(window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowIncomingCalls, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::IncomingCalls, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowOutgoingCalls, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::OutgoingCalls, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSupertypes, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::Supertypes, window, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSubtypes, window, cx| {
+            show_hierarchy(workspace, HierarchyKind::Subtypes, window, cx);
+        });
+    })
+    .detach();
+}
+
+fn show_hierarchy(
+    workspace: &mut Workspace,
+    kind: HierarchyKind,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<HierarchyPanel>(window, cx) {
+        panel.update(cx, |panel, cx| panel.show_hierarchy(kind, &editor, cx));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyKind {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyKind {
+    fn title(self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "Incoming Calls",
+            Self::OutgoingCalls => "Outgoing Calls",
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    fn reversed(self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+
+    fn is_call_hierarchy(self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+}
+
+struct HierarchyNode {
+    item: HierarchyItem,
+    /// Where the parent symbol calls this one, or this one calls the parent symbol.
+    call_sites: Vec<Location>,
+    depth: usize,
+    expanded: bool,
+    /// Indices of the child nodes, `None` until they were fetched.
+    children: Option<Vec<usize>>,
+}
+
+impl HierarchyNode {
+    fn new(item: HierarchyItem, call_sites: Vec<Location>, depth: usize) -> Self {
+        Self {
+            item,
+            call_sites,
+            depth,
+            expanded: false,
+            children: None,
+        }
+    }
+}
+
+/// Shows the callers and callees, or the supertypes and subtypes, of a symbol as
+/// a tree whose levels are requested from the language server as they're expanded.
+pub struct HierarchyPanel {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    fs: Arc<dyn Fs>,
+    kind: HierarchyKind,
+    nodes: Vec<HierarchyNode>,
+    root_nodes: Vec<usize>,
+    /// The node indices in the order they're displayed.
+    visible_entries: Vec<usize>,
+    selected_node: Option<usize>,
+    loading_children: HashMap<usize, Task<()>>,
+    status: Option<SharedString>,
+    prepare_task: Task<()>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    width: Option<Pixels>,
+}
+
+impl HierarchyPanel {
+    pub fn new(workspace: &mut Workspace, cx: &mut Context<Workspace>) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace = workspace.weak_handle();
+        cx.new(|cx| Self {
+            workspace,
+            project,
+            fs,
+            kind: HierarchyKind::IncomingCalls,
+            nodes: Vec::new(),
+            root_nodes: Vec::new(),
+            visible_entries: Vec::new(),
+            selected_node: None,
+            loading_children: HashMap::default(),
+            status: None,
+            prepare_task: Task::ready(()),
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            width: None,
+        })
+    }
+
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(async move |cx| workspace.update(cx, |workspace, cx| Self::new(workspace, cx)))
+    }
+
+    /// Replaces the tree with the hierarchy of the symbol under the newest cursor of the editor.
+    pub fn show_hierarchy(
+        &mut self,
+        kind: HierarchyKind,
+        editor: &Entity<Editor>,
+        cx: &mut Context<Self>,
+    ) {
+        let head = editor.read(cx).selections.newest_anchor().head();
+        let Some((buffer, position)) = editor
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .text_anchor_for_position(head, cx)
+        else {
+            return;
+        };
+
+        let items = self.project.update(cx, |project, cx| {
+            if kind.is_call_hierarchy() {
+                project.prepare_call_hierarchy(&buffer, position, cx)
+            } else {
+                project.prepare_type_hierarchy(&buffer, position, cx)
+            }
+        });
+        self.kind = kind;
+        self.clear();
+        self.status = Some("Loading…".into());
+        cx.notify();
+
+        self.prepare_task = cx.spawn(async move |this, cx| {
+            let items = items.await;
+            this.update(cx, |this, cx| {
+                match items {
+                    Ok(items) if items.is_empty() => {
+                        this.status = Some("No symbol found at the cursor".into());
+                    }
+                    Ok(items) => {
+                        this.status = None;
+                        this.set_root_items(items, cx);
+                    }
+                    Err(error) => {
+                        log::error!("failed to prepare {}: {error:#}", kind.title());
+                        this.status = Some(format!("{error:#}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.root_nodes.clear();
+        self.visible_entries.clear();
+        self.selected_node = None;
+        self.loading_children.clear();
+    }
+
+    fn set_root_items(&mut self, items: Vec<HierarchyItem>, cx: &mut Context<Self>) {
+        self.clear();
+        for item in items {
+            self.nodes.push(HierarchyNode::new(item, Vec::new(), 0));
+            self.root_nodes.push(self.nodes.len() - 1);
+        }
+        self.selected_node = self.root_nodes.first().copied();
+        for root in self.root_nodes.clone() {
+            self.expand_node(root, cx);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn switch_direction(&mut self, _: &SwitchDirection, _: &mut Window, cx: &mut Context<Self>) {
+        let root_items = self
+            .root_nodes
+            .iter()
+            .map(|&ix| self.nodes[ix].item.clone())
+            .collect();
+        self.kind = self.kind.reversed();
+        self.set_root_items(root_items, cx);
+    }
+
+    fn fetch_children(
+        &self,
+        item: &HierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<(HierarchyItem, Vec<Location>)>>> {
+        let kind = self.kind;
+        self.project.update(cx, |project, cx| match kind {
+            HierarchyKind::IncomingCalls | HierarchyKind::OutgoingCalls => {
+                let calls = if kind == HierarchyKind::IncomingCalls {
+                    project.incoming_calls(item, cx)
+                } else {
+                    project.outgoing_calls(item, cx)
+                };
+                cx.background_spawn(async move {
+                    Ok(calls
+                        .await?
+                        .into_iter()
+                        .map(|call| (call.item, call.call_sites))
+                        .collect())
+                })
+            }
+            HierarchyKind::Supertypes | HierarchyKind::Subtypes => {
+                let types = if kind == HierarchyKind::Supertypes {
+                    project.supertypes(item, cx)
+                } else {
+                    project.subtypes(item, cx)
+                };
+                cx.background_spawn(async move {
+                    Ok(types
+                        .await?
+                        .into_iter()
+                        .map(|item| (item, Vec::new()))
+                        .collect())
+                })
+            }
+        })
+    }
+
+    fn expand_node(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let node = &mut self.nodes[ix];
+        node.expanded = true;
+        if node.children.is_some() || self.loading_children.contains_key(&ix) {
+            self.update_visible_entries(cx);
+            return;
+        }
+
+        let kind = self.kind;
+        let children = self.fetch_children(&self.nodes[ix].item, cx);
+        let task = cx.spawn(async move |this, cx| {
+            let children = children.await;
+            this.update(cx, |this, cx| {
+                this.loading_children.remove(&ix);
+                let children = children.unwrap_or_else(|error| {
+                    log::error!("failed to fetch {}: {error:#}", kind.title());
+                    Vec::new()
+                });
+                let depth = this.nodes[ix].depth + 1;
+                let mut child_nodes = Vec::with_capacity(children.len());
+                for (item, call_sites) in children {
+                    this.nodes.push(HierarchyNode::new(item, call_sites, depth));
+                    child_nodes.push(this.nodes.len() - 1);
+                }
+                this.nodes[ix].children = Some(child_nodes);
+                this.update_visible_entries(cx);
+            })
+            .ok();
+        });
+        self.loading_children.insert(ix, task);
+        cx.notify();
+    }
+
+    fn toggle_node(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if self.nodes[ix].expanded {
+            self.nodes[ix].expanded = false;
+            self.update_visible_entries(cx);
+        } else {
+            self.expand_node(ix, cx);
+        }
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut Context<Self>) {
+        self.visible_entries.clear();
+        let mut stack = self.root_nodes.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(ix) = stack.pop() {
+            self.visible_entries.push(ix);
+            let node = &self.nodes[ix];
+            if node.expanded {
+                if let Some(children) = &node.children {
+                    stack.extend(children.iter().rev().copied());
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn expand_selected_entry(
+        &mut self,
+        _: &ExpandSelectedEntry,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.selected_node else {
+            return;
+        };
+        let node = &self.nodes[ix];
+        match &node.children {
+            // Like in the project panel, expanding an expanded entry moves to its first child.
+            Some(children) if node.expanded => {
+                if let Some(&child) = children.first() {
+                    self.selected_node = Some(child);
+                    self.autoscroll();
+                    cx.notify();
+                }
+            }
+            _ => self.expand_node(ix, cx),
+        }
+    }
+
+    fn collapse_selected_entry(
+        &mut self,
+        _: &CollapseSelectedEntry,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.selected_node else {
+            return;
+        };
+        if self.nodes[ix].expanded {
+            self.nodes[ix].expanded = false;
+            self.update_visible_entries(cx);
+        } else if let Some(parent) = self.parent_of(ix) {
+            self.selected_node = Some(parent);
+            self.autoscroll();
+            cx.notify();
+        }
+    }
+
+    fn parent_of(&self, ix: usize) -> Option<usize> {
+        let depth = self.nodes[ix].depth.checked_sub(1)?;
+        let position = self.visible_entries.iter().position(|&entry| entry == ix)?;
+        self.visible_entries[..position]
+            .iter()
+            .rev()
+            .copied()
+            .find(|&entry| self.nodes[entry].depth == depth)
+    }
+
+    fn selected_position(&self) -> Option<usize> {
+        let selected = self.selected_node?;
+        self.visible_entries
+            .iter()
+            .position(|&entry| entry == selected)
+    }
+
+    fn select_position(&mut self, position: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(&ix) = self.visible_entries.get(position) {
+            self.selected_node = Some(ix);
+            self.autoscroll();
+            self.open_node(ix, false, window, cx);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        let position = match self.selected_position() {
+            Some(position) => (position + 1).min(self.visible_entries.len().saturating_sub(1)),
+            None => 0,
+        };
+        self.select_position(position, window, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        let position = self
+            .selected_position()
+            .map_or(0, |position| position.saturating_sub(1));
+        self.select_position(position, window, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_position(0, window, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, window: &mut Window, cx: &mut Context<Self>) {
+        let position = self.visible_entries.len().saturating_sub(1);
+        self.select_position(position, window, cx);
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_node {
+            self.open_node(ix, true, window, cx);
+        }
+    }
+
+    fn autoscroll(&mut self) {
+        if let Some(position) = self.selected_position() {
+            self.scroll_handle
+                .scroll_to_item(position, ScrollStrategy::Center);
+        }
+    }
+
+    /// Opens the location of a node, as a preview unless `focus` is set. Callers are
+    /// shown where they make the call, everything else where it's declared.
+    fn open_node(&mut self, ix: usize, focus: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let node = &self.nodes[ix];
+        let location = match self.kind {
+            HierarchyKind::IncomingCalls => node.call_sites.first(),
+            _ => None,
+        }
+        .unwrap_or(&node.item.location);
+        let buffer = location.buffer.read(cx);
+        let range = location.range.to_point(&buffer.snapshot());
+        let Some(project_path) = buffer.project_path(cx) else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_path_preview(project_path, None, focus, !focus, true, window, cx)
+        });
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open_task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update_in(cx, |editor, window, cx| {
+                    editor.change_selections(
+                        SelectionEffects::scroll(Autoscroll::center()),
+                        window,
+                        cx,
+                        |s| s.select_ranges([range.start..range.start]),
+                    );
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> ListItem {
+        let node = &self.nodes[ix];
+        let item = &node.item;
+        let buffer = item.location.buffer.read(cx);
+        let row = item.location.range.start.to_point(&buffer.snapshot()).row + 1;
+        let file_name = buffer
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let toggle = match &node.children {
+            Some(children) if children.is_empty() => None,
+            _ => Some(node.expanded),
+        };
+        let is_loading = self.loading_children.contains_key(&ix);
+
+        ListItem::new(("hierarchy-entry", ix))
+            .spacing(ListItemSpacing::Dense)
+            .indent_level(node.depth)
+            .indent_step_size(px(HierarchyPanelSettings::get_global(cx).indent_size))
+            .toggle(toggle)
+            .on_toggle(cx.listener(move |this, _, _, cx| this.toggle_node(ix, cx)))
+            .toggle_state(self.selected_node == Some(ix))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Label::new(item.name.clone()).truncate())
+                    .when_some(item.detail.clone(), |this, detail| {
+                        this.child(
+                            Label::new(detail)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        )
+                    }),
+            )
+            .end_slot(
+                Label::new(if is_loading {
+                    "Loading…".to_string()
+                } else {
+                    format!("{file_name}:{row}")
+                })
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
+                this.selected_node = Some(ix);
+                this.open_node(ix, event.down.click_count > 1, window, cx);
+                cx.notify();
+            }))
+    }
+
+    fn root_name(&self) -> Option<&str> {
+        let &root = self.root_nodes.first()?;
+        Some(self.nodes[root].item.name.as_str())
+    }
+
+    fn render_empty_state(&self) -> impl IntoElement {
+        let message = self.status.clone().unwrap_or_else(|| {
+            "Show the call or type hierarchy of a symbol from the editor context menu".into()
+        });
+        h_flex()
+            .size_full()
+            .p_2()
+            .justify_center()
+            .items_center()
+            .child(Label::new(message).color(Color::Muted))
+    }
+}
+
+impl Render for HierarchyPanel {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let title: SharedString = match self.root_name() {
+            Some(name) => format!("{} of {name}", self.kind.title()).into(),
+            None => self.kind.title().into(),
+        };
+        let switch_tooltip = match self.kind.reversed() {
+            HierarchyKind::IncomingCalls => "Show Incoming Calls",
+            HierarchyKind::OutgoingCalls => "Show Outgoing Calls",
+            HierarchyKind::Supertypes => "Show Supertypes",
+            HierarchyKind::Subtypes => "Show Subtypes",
+        };
+
+        let content = if self.visible_entries.is_empty() {
+            self.render_empty_state().into_any_element()
+        } else {
+            uniform_list(
+                "hierarchy-entries",
+                self.visible_entries.len(),
+                cx.processor(|this, range: Range<usize>, _, cx| {
+                    this.visible_entries[range]
+                        .to_vec()
+                        .into_iter()
+                        .map(|ix| this.render_entry(ix, cx))
+                        .collect()
+                }),
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .overflow_hidden()
+            .bg(cx.theme().colors().panel_background)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::switch_direction))
+            .child(
+                self.panel_header_container(window, cx)
+                    .justify_between()
+                    .child(Label::new(title).size(LabelSize::Small).truncate())
+                    .child(
+                        panel_icon_button("hierarchy-switch-direction", IconName::ArrowRightLeft)
+                            .icon_size(IconSize::Small)
+                            .disabled(self.root_nodes.is_empty())
+                            .tooltip(Tooltip::for_action_title(switch_tooltip, &SwitchDirection))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(SwitchDirection.boxed_clone(), cx)
+                            }),
+                    ),
+            )
+            .child(content)
+    }
+}
+
+impl Focusable for HierarchyPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for HierarchyPanel {}
+
+impl Panel for HierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "Hierarchy Panel"
+    }
+
+    fn position(&self, _: &Window, cx: &App) -> DockPosition {
+        match HierarchyPanelSettings::get_global(cx).dock {
+            HierarchyPanelDockPosition::Left => DockPosition::Left,
+            HierarchyPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        settings::update_settings_file::<HierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => HierarchyPanelDockPosition::Left,
+                    DockPosition::Right => HierarchyPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, _: &Window, cx: &App) -> Pixels {
+        self.width
+            .unwrap_or_else(|| HierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, cx: &App) -> Option<IconName> {
+        HierarchyPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Function)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        4
+    }
+}
+
+impl PanelHeader for HierarchyPanel {}
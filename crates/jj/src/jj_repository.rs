@@ -1,30 +1,149 @@
-use std::path::Path;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, anyhow};
 use gpui::SharedString;
 use jj_lib::config::StackedConfig;
-use jj_lib::repo::StoreFactories;
+use jj_lib::repo::{RepoLoader, StoreFactories};
 use jj_lib::settings::UserSettings;
 use jj_lib::workspace::{self, DefaultWorkspaceLoaderFactory, WorkspaceLoaderFactory};
 
+/// Separates the fields of a record printed by one of the templates below.
+const FIELD_SEPARATOR: char = '\0';
+/// Terminates a record printed by one of the templates below.
+/// Descriptions may span several lines, so a newline alone can't be used.
+const RECORD_TERMINATOR: &str = "\0\n";
+
+const CHANGE_TEMPLATE: &str = r#"change_id.short(12) ++ "\0" ++ commit_id.short(12) ++ "\0" ++ author.name() ++ "\0" ++ committer.timestamp().ago() ++ "\0" ++ local_bookmarks.map(|b| b.name()).join(" ") ++ "\0" ++ if(empty, "empty") ++ "\0" ++ if(conflict, "conflict") ++ "\0" ++ if(current_working_copy, "working_copy") ++ "\0" ++ description ++ "\0\n""#;
+
+const OPERATION_TEMPLATE: &str = r#"id.short(12) ++ "\0" ++ user ++ "\0" ++ time.start().ago() ++ "\0" ++ description.first_line() ++ "\0\n""#;
+
 #[derive(Debug, Clone)]
 pub struct Bookmark {
     pub ref_name: SharedString,
 }
 
+/// A change (a commit, as seen through its stable change id) in a Jujutsu repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub change_id: SharedString,
+    pub commit_id: SharedString,
+    pub author: SharedString,
+    /// When the change was last rewritten, relative to now.
+    pub timestamp: SharedString,
+    pub bookmarks: Vec<SharedString>,
+    pub description: SharedString,
+    pub is_empty: bool,
+    pub has_conflict: bool,
+    pub is_working_copy: bool,
+}
+
+impl Change {
+    /// The first line of the description, or `None` when the change is not described yet.
+    pub fn summary(&self) -> Option<&str> {
+        self.description
+            .lines()
+            .next()
+            .filter(|line| !line.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatusKind {
+    Added,
+    Modified,
+    Removed,
+    Renamed,
+    Copied,
+}
+
+/// A file changed in the working-copy change, with the path relative to the workspace root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStatus {
+    pub path: PathBuf,
+    pub kind: FileStatusKind,
+    /// Where the file was renamed or copied from.
+    pub source: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingCopyStatus {
+    pub change: Change,
+    pub parents: Vec<Change>,
+    pub files: Vec<FileStatus>,
+    pub conflicted_paths: Vec<PathBuf>,
+}
+
+/// An entry of the operation log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    pub id: SharedString,
+    pub user: SharedString,
+    /// When the operation started, relative to now.
+    pub time: SharedString,
+    pub description: SharedString,
+}
+
+/// The operations on a Jujutsu workspace.
+///
+/// All methods block on the repository, so they should be called on a background thread.
+/// Revisions are identified by change ids.
 pub trait JujutsuRepository: Send + Sync {
+    fn workspace_root(&self) -> &Path;
+
+    /// The directory holding the heads of the operation log, which every operation changes.
+    fn op_heads_dir(&self) -> PathBuf;
+
     fn list_bookmarks(&self) -> Vec<Bookmark>;
+
+    /// Snapshots the working copy and returns its change, the parents of it and the files it changes.
+    fn status(&self) -> Result<WorkingCopyStatus>;
+
+    /// Returns up to `limit` changes matching the revset given, newest first.
+    fn log(&self, revset: &str, limit: usize) -> Result<Vec<Change>>;
+
+    /// Returns the content of a file in the parent of the working-copy change,
+    /// or `None` when the file does not exist there.
+    fn load_diff_base(&self, path: &Path) -> Result<Option<String>>;
+
+    fn describe(&self, change_id: &str, message: &str) -> Result<()>;
+
+    /// Starts a new, empty change on top of the working-copy change.
+    fn new_change(&self) -> Result<()>;
+
+    /// Moves the contents of a change into its parent, combining their descriptions.
+    fn squash(&self, change_id: &str) -> Result<()>;
+
+    fn abandon(&self, change_id: &str) -> Result<()>;
+
+    /// Makes the change given the working-copy change.
+    fn edit(&self, change_id: &str) -> Result<()>;
+
+    /// Returns up to `limit` operations, newest first.
+    fn operation_log(&self, limit: usize) -> Result<Vec<Operation>>;
+
+    /// Undoes the last operation.
+    fn undo(&self) -> Result<()>;
+
+    /// Restores the repository to the state right after the operation given.
+    fn restore_operation(&self, operation_id: &str) -> Result<()>;
 }
 
+/// Reads bookmarks with `jj-lib` and runs everything else through the `jj` CLI,
+/// so that the working copy is snapshotted and, in colocated repositories, exported to git
+/// the same way it is when running commands in a terminal.
 pub struct RealJujutsuRepository {
-    repository: Arc<jj_lib::repo::ReadonlyRepo>,
+    workspace_root: PathBuf,
+    repo_path: PathBuf,
+    repo_loader: RepoLoader,
 }
 
 impl RealJujutsuRepository {
     pub fn new(cwd: &Path) -> Result<Self> {
+        let workspace_root = Self::find_workspace_dir(cwd);
         let workspace_loader_factory = DefaultWorkspaceLoaderFactory;
-        let workspace_loader = workspace_loader_factory.create(Self::find_workspace_dir(cwd))?;
+        let workspace_loader = workspace_loader_factory.create(workspace_root)?;
 
         let config = StackedConfig::with_defaults();
         let settings = UserSettings::from_config(config)?;
@@ -35,10 +154,11 @@ impl RealJujutsuRepository {
             &workspace::default_working_copy_factories(),
         )?;
 
-        let repo_loader = workspace.repo_loader();
-        let repository = repo_loader.load_at_head()?;
-
-        Ok(Self { repository })
+        Ok(Self {
+            workspace_root: workspace_root.to_path_buf(),
+            repo_path: workspace.repo_path().to_path_buf(),
+            repo_loader: workspace.repo_loader().clone(),
+        })
     }
 
     fn find_workspace_dir(cwd: &Path) -> &Path {
@@ -46,12 +166,52 @@ impl RealJujutsuRepository {
             .find(|path| path.join(".jj").is_dir())
             .unwrap_or(cwd)
     }
+
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let output = util::command::new_std_command("jj")
+            .current_dir(&self.workspace_root)
+            .args(["--no-pager", "--color", "never"])
+            .args(args)
+            .output()
+            .context("failed to run jj")?;
+        anyhow::ensure!(
+            output.status.success(),
+            "jj {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        String::from_utf8(output.stdout).context("jj printed invalid UTF-8")
+    }
+
+    fn changes(&self, revset: &str, limit: Option<usize>) -> Result<Vec<Change>> {
+        let limit = limit.map(|limit| limit.to_string());
+        let mut args = vec!["log", "--no-graph", "-r", revset, "-T", CHANGE_TEMPLATE];
+        if let Some(limit) = &limit {
+            args.extend(["--limit", limit.as_str()]);
+        }
+        parse_changes(&self.run(&args)?)
+    }
 }
 
 impl JujutsuRepository for RealJujutsuRepository {
+    fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    fn op_heads_dir(&self) -> PathBuf {
+        self.repo_path.join("op_heads")
+    }
+
     fn list_bookmarks(&self) -> Vec<Bookmark> {
-        let bookmarks = self
-            .repository
+        let repository = match self.repo_loader.load_at_head() {
+            Ok(repository) => repository,
+            Err(error) => {
+                log::error!("failed to load jj repository: {error}");
+                return Vec::new();
+            }
+        };
+
+        let bookmarks = repository
             .view()
             .bookmarks()
             .map(|(ref_name, _target)| Bookmark {
@@ -61,12 +221,608 @@ impl JujutsuRepository for RealJujutsuRepository {
 
         bookmarks
     }
+
+    fn status(&self) -> Result<WorkingCopyStatus> {
+        // Listing the working-copy change first snapshots it, so the other commands see the same state.
+        let mut changes = self.changes("@ | @-", None)?;
+        let working_copy_ix = changes
+            .iter()
+            .position(|change| change.is_working_copy)
+            .context("no working-copy change in this workspace")?;
+        let change = changes.remove(working_copy_ix);
+        let files = parse_diff_summary(&self.run(&["diff", "--summary", "-r", "@"])?)?;
+        let conflicted_paths = if change.has_conflict {
+            parse_conflicted_paths(&self.run(&["resolve", "--list", "-r", "@"])?)
+        } else {
+            Vec::new()
+        };
+        Ok(WorkingCopyStatus {
+            change,
+            parents: changes,
+            files,
+            conflicted_paths,
+        })
+    }
+
+    fn log(&self, revset: &str, limit: usize) -> Result<Vec<Change>> {
+        self.changes(revset, Some(limit))
+    }
+
+    fn load_diff_base(&self, path: &Path) -> Result<Option<String>> {
+        // The parent of a merge is the merge of several revisions, which no revset names,
+        // so the base is recovered from the diff of the working-copy change instead.
+        let fileset = root_file_pattern(path);
+        let diff = self.run(&["diff", "--git", "-r", "@", &fileset])?;
+        if diff.lines().any(|line| line.starts_with("new file mode")) {
+            return Ok(None);
+        }
+        let text = if diff
+            .lines()
+            .any(|line| line.starts_with("deleted file mode"))
+        {
+            String::new()
+        } else if self
+            .run(&["file", "list", "-r", "@", &fileset])?
+            .trim()
+            .is_empty()
+        {
+            return Ok(None);
+        } else {
+            self.run(&["file", "show", "-r", "@", &fileset])?
+        };
+        revert_git_diff(&text, &diff).map(Some)
+    }
+
+    fn describe(&self, change_id: &str, message: &str) -> Result<()> {
+        self.run(&["describe", "-r", change_id, "-m", message])
+            .map(drop)
+    }
+
+    fn new_change(&self) -> Result<()> {
+        self.run(&["new"]).map(drop)
+    }
+
+    fn squash(&self, change_id: &str) -> Result<()> {
+        // Without a message, jj would open an editor to combine the descriptions.
+        let parent_revset = format!("{change_id}-");
+        let source = self.changes(change_id, Some(1))?;
+        let destination = self.changes(&parent_revset, None)?;
+        anyhow::ensure!(
+            destination.len() == 1,
+            "cannot squash {change_id} into more than one parent"
+        );
+        let message = combine_descriptions(destination.iter().chain(&source));
+        self.run(&["squash", "-r", change_id, "-m", &message])
+            .map(drop)
+    }
+
+    fn abandon(&self, change_id: &str) -> Result<()> {
+        self.run(&["abandon", change_id]).map(drop)
+    }
+
+    fn edit(&self, change_id: &str) -> Result<()> {
+        self.run(&["edit", change_id]).map(drop)
+    }
+
+    fn operation_log(&self, limit: usize) -> Result<Vec<Operation>> {
+        let limit = limit.to_string();
+        parse_operations(&self.run(&[
+            "op",
+            "log",
+            "--no-graph",
+            "--limit",
+            &limit,
+            "-T",
+            OPERATION_TEMPLATE,
+        ])?)
+    }
+
+    fn undo(&self) -> Result<()> {
+        self.run(&["undo"]).map(drop)
+    }
+
+    fn restore_operation(&self, operation_id: &str) -> Result<()> {
+        self.run(&["op", "restore", operation_id]).map(drop)
+    }
 }
 
-pub struct FakeJujutsuRepository {}
+/// Returns the contents of a file before a change, given its contents after the change and
+/// the change's diff of it, as printed by `jj diff --git`.
+fn revert_git_diff(text: &str, diff: &str) -> Result<String> {
+    let lines = text.split_inclusive('\n').collect::<Vec<_>>();
+    let mut base = String::new();
+    let mut line_ix = 0;
+    let mut last_line_kind = None;
+    let mut in_hunk = false;
+    for diff_line in diff.split_inclusive('\n') {
+        if let Some(header) = diff_line.strip_prefix("@@ ") {
+            let new_range = header
+                .split_whitespace()
+                .find_map(|range| range.strip_prefix('+'))
+                .with_context(|| format!("invalid hunk header: {diff_line:?}"))?;
+            let (start, len) = new_range.split_once(',').unwrap_or((new_range, "1"));
+            let (start, len) = (start.parse::<usize>()?, len.parse::<usize>()?);
+            // An empty range starts after the line given, and others at it.
+            let hunk_ix = if len == 0 {
+                start
+            } else {
+                start.saturating_sub(1)
+            };
+            anyhow::ensure!(
+                line_ix <= hunk_ix && hunk_ix <= lines.len(),
+                "hunk out of range: {diff_line:?}"
+            );
+            base.extend(lines[line_ix..hunk_ix].iter().copied());
+            line_ix = hunk_ix;
+            in_hunk = true;
+            continue;
+        }
+        if !in_hunk {
+            anyhow::ensure!(
+                !diff_line.starts_with("Binary files")
+                    && !diff_line.starts_with("GIT binary patch"),
+                "cannot load the base of a binary file"
+            );
+            continue;
+        }
+        match diff_line.chars().next() {
+            Some(kind @ (' ' | '-')) => {
+                base.push_str(&diff_line[1..]);
+                if kind == ' ' {
+                    line_ix += 1;
+                }
+                last_line_kind = Some(kind);
+            }
+            Some('+') => {
+                line_ix += 1;
+                last_line_kind = Some('+');
+            }
+            // `\ No newline at end of file` applies to the line before it.
+            Some('\\') => {
+                if last_line_kind.is_some_and(|kind| kind != '+') && base.ends_with('\n') {
+                    base.pop();
+                }
+            }
+            _ => in_hunk = false,
+        }
+    }
+    anyhow::ensure!(line_ix <= lines.len(), "diff doesn't match the file");
+    base.extend(lines[line_ix..].iter().copied());
+    Ok(base)
+}
+
+fn root_file_pattern(path: &Path) -> String {
+    let path = path
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("root-file:\"{path}\"")
+}
+
+fn combine_descriptions<'a>(changes: impl IntoIterator<Item = &'a Change>) -> String {
+    changes
+        .into_iter()
+        .map(|change| change.description.trim())
+        .filter(|description| !description.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn records(output: &str) -> impl Iterator<Item = Vec<&str>> {
+    output
+        .split(RECORD_TERMINATOR)
+        .filter(|record| !record.trim().is_empty())
+        .map(|record| record.split(FIELD_SEPARATOR).collect())
+}
+
+fn parse_changes(output: &str) -> Result<Vec<Change>> {
+    records(output)
+        .map(|fields| {
+            let [
+                change_id,
+                commit_id,
+                author,
+                timestamp,
+                bookmarks,
+                empty,
+                conflict,
+                working_copy,
+                description,
+            ] = fields[..]
+            else {
+                return Err(anyhow!("unexpected jj log output: {fields:?}"));
+            };
+            Ok(Change {
+                change_id: change_id.trim_start().to_string().into(),
+                commit_id: commit_id.to_string().into(),
+                author: author.to_string().into(),
+                timestamp: timestamp.to_string().into(),
+                bookmarks: bookmarks
+                    .split_whitespace()
+                    .map(|bookmark| bookmark.to_string().into())
+                    .collect(),
+                description: description.trim_end().to_string().into(),
+                is_empty: !empty.is_empty(),
+                has_conflict: !conflict.is_empty(),
+                is_working_copy: !working_copy.is_empty(),
+            })
+        })
+        .collect()
+}
+
+fn parse_operations(output: &str) -> Result<Vec<Operation>> {
+    records(output)
+        .map(|fields| {
+            let [id, user, time, description] = fields[..] else {
+                return Err(anyhow!("unexpected jj op log output: {fields:?}"));
+            };
+            Ok(Operation {
+                id: id.trim_start().to_string().into(),
+                user: user.to_string().into(),
+                time: time.to_string().into(),
+                description: description.to_string().into(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the output of `jj diff --summary`, e.g. `M src/main.rs` or `R src/{old.rs => new.rs}`.
+fn parse_diff_summary(output: &str) -> Result<Vec<FileStatus>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (kind, path) = line
+                .split_once(' ')
+                .with_context(|| format!("unexpected jj diff output: {line:?}"))?;
+            let kind = match kind {
+                "A" => FileStatusKind::Added,
+                "M" => FileStatusKind::Modified,
+                "D" => FileStatusKind::Removed,
+                "R" => FileStatusKind::Renamed,
+                "C" => FileStatusKind::Copied,
+                _ => anyhow::bail!("unexpected jj diff output: {line:?}"),
+            };
+            let (source, path) = match kind {
+                FileStatusKind::Renamed | FileStatusKind::Copied => {
+                    let (source, path) = parse_rename(path)
+                        .with_context(|| format!("unexpected jj diff output: {line:?}"))?;
+                    (Some(source), path)
+                }
+                _ => (None, PathBuf::from(path)),
+            };
+            Ok(FileStatus { path, kind, source })
+        })
+        .collect()
+}
+
+fn parse_rename(path: &str) -> Option<(PathBuf, PathBuf)> {
+    let start = path.find('{')?;
+    let end = start + path[start..].find('}')?;
+    let (from, to) = path[start + 1..end].split_once(" => ")?;
+    let (prefix, suffix) = (&path[..start], &path[end + 1..]);
+    let join = |middle: &str| {
+        // `{ => dir}/file` and `dir/{old => }/file` leave a doubled or leading separator behind.
+        let path = format!("{prefix}{middle}{suffix}");
+        PathBuf::from_iter(path.split(['/', '\\']).filter(|part| !part.is_empty()))
+    };
+    Some((join(from), join(to)))
+}
+
+/// Parses the output of `jj resolve --list`, where each path is followed by a description of the conflict.
+fn parse_conflicted_paths(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let path = line.split("    ").next()?.trim_end();
+            (!path.is_empty()).then(|| PathBuf::from(path))
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub struct FakeJujutsuRepositoryState {
+    pub bookmarks: Vec<Bookmark>,
+    pub status: Option<WorkingCopyStatus>,
+    pub changes: Vec<Change>,
+    pub diff_bases: HashMap<PathBuf, String>,
+    pub operations: Vec<Operation>,
+}
+
+pub struct FakeJujutsuRepository {
+    workspace_root: PathBuf,
+    state: Arc<Mutex<FakeJujutsuRepositoryState>>,
+}
+
+impl FakeJujutsuRepository {
+    pub fn new(workspace_root: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace_root: workspace_root.into(),
+            state: Arc::default(),
+        }
+    }
+
+    pub fn state(&self) -> Arc<Mutex<FakeJujutsuRepositoryState>> {
+        self.state.clone()
+    }
+
+    fn record_operation(&self, description: String) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let id = format!("{:012x}", state.operations.len());
+        state.operations.insert(
+            0,
+            Operation {
+                id: id.into(),
+                user: "test@localhost".into(),
+                time: "now".into(),
+                description: description.into(),
+            },
+        );
+        Ok(())
+    }
+
+    fn update_change(&self, change_id: &str, update: impl Fn(&mut Change)) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let mut found = false;
+        let status_changes = state
+            .status
+            .iter_mut()
+            .flat_map(|status| std::iter::once(&mut status.change).chain(&mut status.parents));
+        for change in status_changes.chain(&mut state.changes) {
+            if change.change_id.as_ref() == change_id {
+                update(change);
+                found = true;
+            }
+        }
+        anyhow::ensure!(found, "revision {change_id} doesn't exist");
+        Ok(())
+    }
+}
 
 impl JujutsuRepository for FakeJujutsuRepository {
+    fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    fn op_heads_dir(&self) -> PathBuf {
+        self.workspace_root.join(".jj/repo/op_heads")
+    }
+
     fn list_bookmarks(&self) -> Vec<Bookmark> {
-        Vec::new()
+        self.state.lock().unwrap().bookmarks.clone()
+    }
+
+    fn status(&self) -> Result<WorkingCopyStatus> {
+        self.state
+            .lock()
+            .unwrap()
+            .status
+            .clone()
+            .context("no working-copy change in this workspace")
+    }
+
+    fn log(&self, _revset: &str, limit: usize) -> Result<Vec<Change>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .changes
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn load_diff_base(&self, path: &Path) -> Result<Option<String>> {
+        Ok(self.state.lock().unwrap().diff_bases.get(path).cloned())
+    }
+
+    fn describe(&self, change_id: &str, message: &str) -> Result<()> {
+        self.update_change(change_id, |change| {
+            change.description = message.to_string().into()
+        })?;
+        self.record_operation(format!("describe commit {change_id}"))
+    }
+
+    fn new_change(&self) -> Result<()> {
+        self.record_operation("new empty commit".to_string())
+    }
+
+    fn squash(&self, change_id: &str) -> Result<()> {
+        self.update_change(change_id, |change| change.is_empty = true)?;
+        self.record_operation(format!("squash commits into {change_id}"))
+    }
+
+    fn abandon(&self, change_id: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let len = state.changes.len();
+        state
+            .changes
+            .retain(|change| change.change_id.as_ref() != change_id);
+        anyhow::ensure!(
+            state.changes.len() < len,
+            "revision {change_id} doesn't exist"
+        );
+        drop(state);
+        self.record_operation(format!("abandon commit {change_id}"))
+    }
+
+    fn edit(&self, change_id: &str) -> Result<()> {
+        self.update_change(change_id, |_| {})?;
+        self.record_operation(format!("edit commit {change_id}"))
+    }
+
+    fn operation_log(&self, limit: usize) -> Result<Vec<Operation>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .operations
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn undo(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        anyhow::ensure!(!state.operations.is_empty(), "nothing to undo");
+        state.operations.remove(0);
+        Ok(())
+    }
+
+    fn restore_operation(&self, operation_id: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let ix = state
+            .operations
+            .iter()
+            .position(|operation| operation.id.as_ref() == operation_id)
+            .with_context(|| format!("no operation {operation_id}"))?;
+        state.operations.drain(..ix);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_changes() {
+        let output = [
+            "qpvuntsmwlqt\0e8fd68d10437\0Jane\0two minutes ago\0main feature\0\0\0working_copy\0",
+            "Add the parser\n\nWith a body\n\0\n",
+            "zzzzzzzzzzzz\0ffffffffffff\0\0\0\0empty\0\0\0\0\n",
+        ]
+        .concat();
+        let output = output.as_str();
+        let changes = parse_changes(output).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].change_id.as_ref(), "qpvuntsmwlqt");
+        assert_eq!(
+            changes[0].bookmarks,
+            vec![SharedString::from("main"), SharedString::from("feature")]
+        );
+        assert_eq!(
+            changes[0].description.as_ref(),
+            "Add the parser\n\nWith a body"
+        );
+        assert_eq!(changes[0].summary(), Some("Add the parser"));
+        assert!(changes[0].is_working_copy && !changes[0].is_empty);
+        assert_eq!(changes[1].commit_id.as_ref(), "ffffffffffff");
+        assert_eq!(changes[1].summary(), None);
+        assert!(changes[1].is_empty && !changes[1].is_working_copy);
+
+        assert!(parse_changes("too\0few\0fields\0\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        let output =
+            "c39a2ae60c37\0jane@laptop\0three seconds ago\0describe commit e8fd68d10437\0\n";
+        assert_eq!(
+            parse_operations(output).unwrap(),
+            vec![Operation {
+                id: "c39a2ae60c37".into(),
+                user: "jane@laptop".into(),
+                time: "three seconds ago".into(),
+                description: "describe commit e8fd68d10437".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_diff_summary() {
+        let output = "M src/main.rs\nA README.md\nD old.rs\nR src/{parser.rs => parse.rs}\nR {a => b}/mod.rs\nC {lib.rs => src/lib.rs}\nR src/{nested => }/file.rs\n";
+        let file = |kind, path: &str, source: Option<&str>| FileStatus {
+            path: PathBuf::from(path),
+            kind,
+            source: source.map(PathBuf::from),
+        };
+        assert_eq!(
+            parse_diff_summary(output).unwrap(),
+            vec![
+                file(FileStatusKind::Modified, "src/main.rs", None),
+                file(FileStatusKind::Added, "README.md", None),
+                file(FileStatusKind::Removed, "old.rs", None),
+                file(
+                    FileStatusKind::Renamed,
+                    "src/parse.rs",
+                    Some("src/parser.rs")
+                ),
+                file(FileStatusKind::Renamed, "b/mod.rs", Some("a/mod.rs")),
+                file(FileStatusKind::Copied, "src/lib.rs", Some("lib.rs")),
+                file(
+                    FileStatusKind::Renamed,
+                    "src/file.rs",
+                    Some("src/nested/file.rs")
+                ),
+            ]
+        );
+        assert!(parse_diff_summary("X what\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_conflicted_paths() {
+        let output = "src/main.rs    2-sided conflict\nsrc/lib.rs    2-sided conflict including 1 deletion\n";
+        assert_eq!(
+            parse_conflicted_paths(output),
+            vec![PathBuf::from("src/main.rs"), PathBuf::from("src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn test_combine_descriptions() {
+        let change = |description: &str| Change {
+            change_id: "".into(),
+            commit_id: "".into(),
+            author: "".into(),
+            timestamp: "".into(),
+            bookmarks: Vec::new(),
+            description: description.to_string().into(),
+            is_empty: false,
+            has_conflict: false,
+            is_working_copy: false,
+        };
+        assert_eq!(
+            combine_descriptions(&[change("Parent\n"), change(""), change("Child")]),
+            "Parent\n\nChild"
+        );
+    }
+
+    #[test]
+    fn test_revert_git_diff() {
+        let diff = "diff --git a/file b/file\n\
+                    index 1111111..2222222 100644\n\
+                    --- a/file\n\
+                    +++ b/file\n\
+                    @@ -1,3 +1,3 @@\n\
+                    \x20one\n\
+                    -two\n\
+                    +2\n\
+                    \x20three\n\
+                    @@ -6,2 +5,0 @@\n\
+                    -six\n\
+                    -seven\n\
+                    @@ -9 +7,2 @@\n\
+                    -nine\n\
+                    \\ No newline at end of file\n\
+                    +9\n\
+                    +ten\n";
+        assert_eq!(
+            revert_git_diff("one\n2\nthree\nfour\nfive\neight\n9\nten\n", diff).unwrap(),
+            "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine"
+        );
+        assert_eq!(revert_git_diff("same\n", "").unwrap(), "same\n");
+        assert!(revert_git_diff("", "Binary files a/image.png and b/image.png differ\n").is_err());
+    }
+
+    #[test]
+    fn test_root_file_pattern() {
+        assert_eq!(
+            root_file_pattern(Path::new("src/a \"b\".rs")),
+            r#"root-file:"src/a \"b\".rs""#
+        );
     }
 }
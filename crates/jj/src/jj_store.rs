@@ -1,7 +1,12 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use gpui::{App, Entity, Global, prelude::*};
+use anyhow::Result;
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{App, Entity, EventEmitter, Global, Task, prelude::*};
 
 use crate::{JujutsuRepository, RealJujutsuRepository};
 
@@ -10,18 +15,28 @@ struct GlobalJujutsuStore(Entity<JujutsuStore>);
 
 impl Global for GlobalJujutsuStore {}
 
+const OP_HEADS_WATCH_LATENCY: Duration = Duration::from_millis(100);
+
+pub enum JujutsuStoreEvent {
+    /// An operation changed the repository with the given workspace root,
+    /// whether it was run from Zed or not.
+    RepositoryUpdated(Arc<Path>),
+}
+
 pub struct JujutsuStore {
-    repository: Arc<dyn JujutsuRepository>,
+    fs: Arc<dyn Fs>,
+    /// Repositories opened so far, by workspace root.
+    repositories: HashMap<PathBuf, Arc<dyn JujutsuRepository>>,
+    /// Tasks watching the operation heads of each repository, by workspace root.
+    op_heads_watchers: HashMap<PathBuf, Task<()>>,
 }
 
+impl EventEmitter<JujutsuStoreEvent> for JujutsuStore {}
+
 impl JujutsuStore {
     pub fn init_global(cx: &mut App) {
-        let Some(repository) = RealJujutsuRepository::new(&Path::new(".")).ok() else {
-            return;
-        };
-
-        let repository = Arc::new(repository);
-        let jj_store = cx.new(|cx| JujutsuStore::new(repository, cx));
+        let fs = <dyn Fs>::global(cx);
+        let jj_store = cx.new(|cx| JujutsuStore::new(fs, cx));
 
         cx.set_global(GlobalJujutsuStore(jj_store));
     }
@@ -31,11 +46,117 @@ impl JujutsuStore {
             .map(|global| global.0.clone())
     }
 
-    pub fn new(repository: Arc<dyn JujutsuRepository>, _cx: &mut Context<Self>) -> Self {
-        Self { repository }
+    pub fn new(fs: Arc<dyn Fs>, _cx: &mut Context<Self>) -> Self {
+        Self {
+            fs,
+            repositories: HashMap::default(),
+            op_heads_watchers: HashMap::default(),
+        }
+    }
+
+    /// Adds a repository, and emits [`JujutsuStoreEvent::RepositoryUpdated`] whenever its operation
+    /// heads change, so that operations run outside of Zed are picked up too.
+    pub fn add_repository(
+        &mut self,
+        repository: Arc<dyn JujutsuRepository>,
+        cx: &mut Context<Self>,
+    ) {
+        let workspace_root: Arc<Path> = repository.workspace_root().into();
+        let op_heads_dir = repository.op_heads_dir();
+        let fs = self.fs.clone();
+        let watcher = cx.spawn({
+            let workspace_root = workspace_root.clone();
+            async move |this, cx| {
+                let (mut events, _watcher) = fs.watch(&op_heads_dir, OP_HEADS_WATCH_LATENCY).await;
+                while events.next().await.is_some() {
+                    let emitted = this.update(cx, |_, cx| {
+                        cx.emit(JujutsuStoreEvent::RepositoryUpdated(workspace_root.clone()))
+                    });
+                    if emitted.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        self.op_heads_watchers
+            .insert(workspace_root.to_path_buf(), watcher);
+        self.repositories
+            .insert(workspace_root.to_path_buf(), repository);
+    }
+
+    /// Returns the repository of the Jujutsu workspace containing the absolute path given.
+    ///
+    /// Finding and loading a workspace that wasn't opened yet happens on a background thread.
+    pub fn repository_for_path(
+        &mut self,
+        abs_path: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Arc<dyn JujutsuRepository>>> {
+        if let Some(repository) = abs_path
+            .ancestors()
+            .find_map(|path| self.repositories.get(path))
+        {
+            return Task::ready(Some(repository.clone()));
+        }
+
+        cx.spawn(async move |this, cx| {
+            let repository = cx
+                .background_spawn(async move {
+                    let workspace_root = abs_path
+                        .ancestors()
+                        .find(|path| path.join(".jj").is_dir())?;
+                    match RealJujutsuRepository::new(workspace_root) {
+                        Ok(repository) => Some(Arc::new(repository) as Arc<dyn JujutsuRepository>),
+                        Err(error) => {
+                            log::error!(
+                                "failed to open jj repository at {}: {error:#}",
+                                workspace_root.display()
+                            );
+                            None
+                        }
+                    }
+                })
+                .await?;
+            this.update(cx, |this, cx| {
+                // Another lookup may have opened the same workspace in the meantime.
+                if let Some(existing) = this.repositories.get(repository.workspace_root()) {
+                    return existing.clone();
+                }
+                this.add_repository(repository.clone(), cx);
+                repository
+            })
+            .ok()
+        })
+    }
+
+    /// Reads from the repository on a background thread.
+    pub fn read_repository<T: Send + 'static>(
+        &self,
+        repository: Arc<dyn JujutsuRepository>,
+        read: impl FnOnce(&dyn JujutsuRepository) -> Result<T> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<T>> {
+        cx.background_spawn(async move { read(repository.as_ref()) })
     }
 
-    pub fn repository(&self) -> &Arc<dyn JujutsuRepository> {
-        &self.repository
+    /// Runs a command changing the repository on a background thread,
+    /// then emits [`JujutsuStoreEvent::RepositoryUpdated`], whether the command succeeded or not.
+    pub fn update_repository<T: Send + 'static>(
+        &self,
+        repository: Arc<dyn JujutsuRepository>,
+        update: impl FnOnce(&dyn JujutsuRepository) -> Result<T> + Send + 'static,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<T>> {
+        let workspace_root: Arc<Path> = repository.workspace_root().into();
+        cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { update(repository.as_ref()) })
+                .await;
+            this.update(cx, |_, cx| {
+                cx.emit(JujutsuStoreEvent::RepositoryUpdated(workspace_root))
+            })
+            .ok();
+            result
+        })
     }
 }
@@ -5,7 +5,7 @@ use gpui::{
     App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity, Window,
     prelude::*,
 };
-use jj::{Bookmark, JujutsuStore};
+use jj::{Bookmark, JujutsuRepository, JujutsuStore};
 use picker::{Picker, PickerDelegate};
 use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
 use util::ResultExt as _;
@@ -24,11 +24,21 @@ fn open(
     let Some(jj_store) = JujutsuStore::try_global(cx) else {
         return;
     };
-
-    workspace.toggle_modal(window, cx, |window, cx| {
-        let delegate = BookmarkPickerDelegate::new(cx.entity().downgrade(), jj_store, cx);
-        BookmarkPicker::new(delegate, window, cx)
-    });
+    let repository = crate::project_repository(workspace.project(), &jj_store, cx);
+    cx.spawn_in(window, async move |workspace, cx| {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    let delegate = BookmarkPickerDelegate::new(cx.entity().downgrade(), repository);
+                    BookmarkPicker::new(delegate, window, cx)
+                });
+            })
+            .ok();
+    })
+    .detach();
 }
 
 pub struct BookmarkPicker {
@@ -76,12 +86,8 @@ pub struct BookmarkPickerDelegate {
 }
 
 impl BookmarkPickerDelegate {
-    fn new(
-        picker: WeakEntity<BookmarkPicker>,
-        jj_store: Entity<JujutsuStore>,
-        cx: &mut Context<BookmarkPicker>,
-    ) -> Self {
-        let bookmarks = jj_store.read(cx).repository().list_bookmarks();
+    fn new(picker: WeakEntity<BookmarkPicker>, repository: Arc<dyn JujutsuRepository>) -> Self {
+        let bookmarks = repository.list_bookmarks();
 
         Self {
             picker,
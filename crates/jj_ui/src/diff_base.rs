@@ -0,0 +1,198 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use buffer_diff::BufferDiff;
+use editor::Editor;
+use feature_flags::{FeatureFlagAppExt as _, JjUiFeatureFlag};
+use gpui::{Context, Entity, Subscription, Task};
+use jj::{JujutsuRepository, JujutsuStore, JujutsuStoreEvent};
+use language::{Buffer, BufferEvent};
+use util::ResultExt as _;
+
+/// Keeps the diff of an editor's buffer against the parent of the working-copy change alive.
+struct JujutsuDiffAddon {
+    _diff_base: Entity<JujutsuDiffBase>,
+}
+
+impl editor::Addon for JujutsuDiffAddon {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Shows diff hunks in the gutter of files in Jujutsu workspaces that aren't colocated with git.
+///
+/// In colocated workspaces, the parent of the working-copy change is checked out as the git `HEAD`,
+/// so the uncommitted git diff already has the same base.
+pub(crate) fn register_editor(editor: &mut Editor, cx: &mut Context<Editor>) {
+    if !editor.mode().is_full() || !cx.has_flag::<JjUiFeatureFlag>() {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let Some(jj_store) = JujutsuStore::try_global(cx) else {
+        return;
+    };
+    let Some(abs_path) = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx))
+    else {
+        return;
+    };
+    let has_git_repository = project
+        .read(cx)
+        .git_store()
+        .read(cx)
+        .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)
+        .is_some();
+    if has_git_repository {
+        return;
+    }
+    let repository = jj_store.update(cx, |jj_store, cx| {
+        jj_store.repository_for_path(abs_path.clone(), cx)
+    });
+    cx.spawn(async move |editor, cx| {
+        let Some(repository) = repository.await else {
+            return;
+        };
+        let Ok(path) = abs_path
+            .strip_prefix(repository.workspace_root())
+            .map(Path::to_path_buf)
+        else {
+            return;
+        };
+        editor
+            .update(cx, |editor, cx| {
+                let diff = cx.new(|cx| BufferDiff::new(&buffer.read(cx).text_snapshot(), cx));
+                editor
+                    .buffer()
+                    .update(cx, |multibuffer, cx| multibuffer.add_diff(diff.clone(), cx));
+                let diff_base =
+                    cx.new(|cx| JujutsuDiffBase::new(repository, path, buffer, diff, jj_store, cx));
+                editor.register_addon(JujutsuDiffAddon {
+                    _diff_base: diff_base,
+                });
+            })
+            .ok();
+    })
+    .detach();
+}
+
+struct JujutsuDiffBase {
+    repository: Arc<dyn JujutsuRepository>,
+    /// Path of the buffer's file, relative to the workspace root.
+    path: PathBuf,
+    buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    jj_store: Entity<JujutsuStore>,
+    base_text: Option<Arc<String>>,
+    /// Whether the base text or the language changed since the diff was last computed.
+    base_changed: bool,
+    load_base_task: Task<()>,
+    update_diff_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl JujutsuDiffBase {
+    fn new(
+        repository: Arc<dyn JujutsuRepository>,
+        path: PathBuf,
+        buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        jj_store: Entity<JujutsuStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscriptions = vec![
+            cx.subscribe(&buffer, |this, _, event: &BufferEvent, cx| match event {
+                BufferEvent::Edited | BufferEvent::Reloaded => this.update_diff(false, cx),
+                BufferEvent::LanguageChanged => this.update_diff(true, cx),
+                _ => {}
+            }),
+            cx.subscribe(
+                &jj_store,
+                |this, _, event: &JujutsuStoreEvent, cx| match event {
+                    JujutsuStoreEvent::RepositoryUpdated(workspace_root) => {
+                        if this.repository.workspace_root() == workspace_root.as_ref() {
+                            this.load_base(cx);
+                        }
+                    }
+                },
+            ),
+        ];
+
+        let mut this = Self {
+            repository,
+            path,
+            buffer,
+            diff,
+            jj_store,
+            base_text: None,
+            base_changed: false,
+            load_base_task: Task::ready(()),
+            update_diff_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.load_base(cx);
+        this
+    }
+
+    fn load_base(&mut self, cx: &mut Context<Self>) {
+        let repository = self.repository.clone();
+        let path = self.path.clone();
+        let load = self.jj_store.update(cx, |jj_store, cx| {
+            jj_store.read_repository(
+                repository,
+                move |repository| repository.load_diff_base(&path),
+                cx,
+            )
+        });
+        self.load_base_task = cx.spawn(async move |this, cx| {
+            let Some(base_text) = load.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.base_text = base_text.map(Arc::new);
+                this.update_diff(true, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn update_diff(&mut self, base_changed: bool, cx: &mut Context<Self>) {
+        self.base_changed |= base_changed;
+        let buffer = self.buffer.read(cx);
+        let snapshot = buffer.text_snapshot();
+        let language = buffer.language().cloned();
+        let language_registry = buffer.language_registry();
+        let base_text = self.base_text.clone();
+        let base_changed = self.base_changed;
+        let diff = self.diff.clone();
+        self.update_diff_task = cx.spawn(async move |this, cx| {
+            let Some(diff_snapshot) = BufferDiff::update_diff(
+                diff.clone(),
+                snapshot.clone(),
+                base_text,
+                base_changed,
+                false,
+                language,
+                language_registry,
+                cx,
+            )
+            .await
+            .log_err() else {
+                return;
+            };
+            diff.update(cx, |diff, cx| {
+                diff.set_snapshot(diff_snapshot, &snapshot, cx);
+            })
+            .ok();
+            this.update(cx, |this, _| this.base_changed = false).ok();
+        });
+    }
+}
@@ -0,0 +1,786 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use editor::{Editor, EditorElement};
+use feature_flags::{FeatureFlagAppExt as _, JjUiFeatureFlag};
+use gpui::{
+    Action, App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    SharedString, Subscription, Task, WeakEntity, Window, actions, px,
+};
+use jj::{
+    Change, FileStatus, FileStatusKind, JujutsuRepository, JujutsuStore, JujutsuStoreEvent,
+    Operation, WorkingCopyStatus,
+};
+use panel::{
+    PanelHeader, panel_button, panel_editor_style, panel_filled_button, panel_icon_button,
+};
+use project::Project;
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use workspace::{
+    OpenOptions, Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+    notifications::NotifyTaskExt as _,
+};
+use zed_actions::jj::{AbandonChange, DescribeChange, NewChange, SquashChange, UndoOperation};
+
+use crate::project_repository;
+
+actions!(
+    jj_panel,
+    [
+        /// Toggles focus on the Jujutsu panel.
+        ToggleFocus
+    ]
+);
+
+/// The changes to show below the working copy, roughly the ones `jj log` shows by default.
+const LOG_REVSET: &str = "present(@) | ancestors(immutable_heads().., 2) | present(trunk())";
+const LOG_LIMIT: usize = 20;
+const OPERATION_LOG_LIMIT: usize = 20;
+const MAX_DESCRIPTION_LINES: usize = 6;
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+        workspace.toggle_panel_focus::<JujutsuPanel>(window, cx);
+    });
+    workspace.register_action(|workspace, _: &DescribeChange, window, cx| {
+        update_panel(workspace, window, cx, JujutsuPanel::describe_working_copy)
+    });
+    workspace.register_action(|workspace, _: &NewChange, window, cx| {
+        update_panel(workspace, window, cx, |panel, window, cx| {
+            panel.run_command(|repository| repository.new_change(), window, cx)
+        })
+    });
+    workspace.register_action(|workspace, _: &SquashChange, window, cx| {
+        update_panel(workspace, window, cx, |panel, window, cx| {
+            panel.run_on_working_copy(
+                |repository, change_id| repository.squash(change_id),
+                window,
+                cx,
+            )
+        })
+    });
+    workspace.register_action(|workspace, _: &AbandonChange, window, cx| {
+        update_panel(workspace, window, cx, |panel, window, cx| {
+            panel.run_on_working_copy(
+                |repository, change_id| repository.abandon(change_id),
+                window,
+                cx,
+            )
+        })
+    });
+    workspace.register_action(|workspace, _: &UndoOperation, window, cx| {
+        update_panel(workspace, window, cx, |panel, window, cx| {
+            panel.run_command(|repository| repository.undo(), window, cx)
+        })
+    });
+}
+
+fn update_panel(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+    update: impl FnOnce(&mut JujutsuPanel, &mut Window, &mut Context<JujutsuPanel>),
+) {
+    if let Some(panel) = workspace.panel::<JujutsuPanel>(cx) {
+        panel.update(cx, |panel, cx| update(panel, window, cx));
+    }
+}
+
+/// Shows the working-copy change of the Jujutsu workspace the project is in,
+/// the recent changes and the operation log, and runs `jj` commands on them.
+pub struct JujutsuPanel {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    jj_store: Entity<JujutsuStore>,
+    repository: Option<Arc<dyn JujutsuRepository>>,
+    status: Option<WorkingCopyStatus>,
+    changes: Vec<Change>,
+    operations: Vec<Operation>,
+    load_error: Option<SharedString>,
+    description_editor: Entity<Editor>,
+    /// The description of the working-copy change the editor was last filled with.
+    loaded_description: Option<(SharedString, SharedString)>,
+    focus_handle: FocusHandle,
+    position: DockPosition,
+    width: Option<Pixels>,
+    active: bool,
+    refresh_task: Task<()>,
+    pending_refresh: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl JujutsuPanel {
+    pub fn new(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let workspace = workspace.weak_handle();
+
+        cx.new(|cx| {
+            let jj_store = JujutsuStore::try_global(cx).unwrap_or_else(|| {
+                let fs = project.read(cx).fs().clone();
+                cx.new(|cx| JujutsuStore::new(fs, cx))
+            });
+            let description_editor = cx.new(|cx| {
+                let mut editor = Editor::auto_height(1, MAX_DESCRIPTION_LINES, window, cx);
+                editor.set_placeholder_text("Describe the working-copy change", cx);
+                editor.set_use_autoclose(false);
+                editor.set_show_gutter(false, cx);
+                editor.set_show_wrap_guides(false, cx);
+                editor.set_show_indent_guides(false, cx);
+                editor
+            });
+
+            let subscriptions = vec![
+                cx.subscribe(
+                    &jj_store,
+                    |this, _, event: &JujutsuStoreEvent, cx| match event {
+                        JujutsuStoreEvent::RepositoryUpdated(workspace_root) => {
+                            let is_shown = this.repository.as_ref().is_some_and(|repository| {
+                                repository.workspace_root() == workspace_root.as_ref()
+                            });
+                            if is_shown {
+                                this.refresh(cx);
+                            }
+                        }
+                    },
+                ),
+                cx.subscribe(
+                    &project,
+                    |this, _, event: &project::Event, cx| match event {
+                        project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                            this.repository = None;
+                            this.refresh(cx);
+                        }
+                        // Running `jj` snapshots the working copy, so only do that while the panel is open.
+                        project::Event::WorktreeUpdatedEntries(..) if this.active => {
+                            this.schedule_refresh(cx);
+                        }
+                        _ => {}
+                    },
+                ),
+            ];
+
+            let mut this = Self {
+                workspace,
+                project,
+                jj_store,
+                repository: None,
+                status: None,
+                changes: Vec::new(),
+                operations: Vec::new(),
+                load_error: None,
+                description_editor,
+                loaded_description: None,
+                focus_handle: cx.focus_handle(),
+                position: DockPosition::Left,
+                width: None,
+                active: false,
+                refresh_task: Task::ready(()),
+                pending_refresh: Task::ready(()),
+                _subscriptions: subscriptions,
+            };
+            this.refresh(cx);
+            this
+        })
+    }
+
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(async move |cx| {
+            workspace.update_in(cx, |workspace, window, cx| Self::new(workspace, window, cx))
+        })
+    }
+
+    fn schedule_refresh(&mut self, cx: &mut Context<Self>) {
+        self.pending_refresh = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            this.update(cx, |this, cx| this.refresh(cx)).ok();
+        });
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        if self.repository.is_none() {
+            let repository = project_repository(&self.project, &self.jj_store, cx);
+            self.refresh_task = cx.spawn(async move |this, cx| {
+                let repository = repository.await;
+                this.update(cx, |this, cx| {
+                    this.repository = repository;
+                    this.load_state(cx);
+                })
+                .ok();
+            });
+        } else {
+            self.load_state(cx);
+        }
+    }
+
+    fn load_state(&mut self, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.clone() else {
+            self.status = None;
+            self.changes.clear();
+            self.operations.clear();
+            self.load_error = None;
+            cx.notify();
+            return;
+        };
+
+        let load = self.jj_store.update(cx, |jj_store, cx| {
+            jj_store.read_repository(
+                repository,
+                |repository| {
+                    let status = repository.status()?;
+                    let changes = repository.log(LOG_REVSET, LOG_LIMIT)?;
+                    let operations = repository.operation_log(OPERATION_LOG_LIMIT)?;
+                    Ok((status, changes, operations))
+                },
+                cx,
+            )
+        });
+        self.refresh_task = cx.spawn(async move |this, cx| {
+            let result = load.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok((status, changes, operations)) => {
+                        this.update_description_editor(&status.change, cx);
+                        this.status = Some(status);
+                        this.changes = changes;
+                        this.operations = operations;
+                        this.load_error = None;
+                    }
+                    Err(error) => {
+                        log::error!("failed to load jj status: {error:#}");
+                        this.load_error = Some(format!("{error:#}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    /// Fills the description editor with the description of the working-copy change,
+    /// unless the description of the same change is being edited.
+    fn update_description_editor(&mut self, change: &Change, cx: &mut Context<Self>) {
+        let editor_text = self.description_editor.read(cx).text(cx);
+        let is_edited = self
+            .loaded_description
+            .as_ref()
+            .is_some_and(|(change_id, description)| {
+                *change_id == change.change_id && editor_text != description.as_ref()
+            });
+        if is_edited {
+            return;
+        }
+
+        self.loaded_description = Some((change.change_id.clone(), change.description.clone()));
+        if editor_text != change.description.as_ref() {
+            let description = change.description.clone();
+            self.description_editor.update(cx, |editor, cx| {
+                if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+                    buffer.update(cx, |buffer, cx| buffer.set_text(description.as_ref(), cx));
+                }
+            });
+        }
+    }
+
+    fn run_command(
+        &mut self,
+        command: impl FnOnce(&dyn JujutsuRepository) -> Result<()> + Send + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repository) = self.repository.clone() else {
+            return;
+        };
+        self.jj_store
+            .update(cx, |jj_store, cx| {
+                jj_store.update_repository(repository, command, cx)
+            })
+            .detach_and_notify_err(window, cx);
+    }
+
+    fn run_on_change(
+        &mut self,
+        change_id: SharedString,
+        command: impl FnOnce(&dyn JujutsuRepository, &str) -> Result<()> + Send + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.run_command(
+            move |repository| command(repository, &change_id),
+            window,
+            cx,
+        );
+    }
+
+    fn run_on_working_copy(
+        &mut self,
+        command: impl FnOnce(&dyn JujutsuRepository, &str) -> Result<()> + Send + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(status) = &self.status {
+            self.run_on_change(status.change.change_id.clone(), command, window, cx);
+        }
+    }
+
+    fn describe_working_copy(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let message = self.description_editor.read(cx).text(cx);
+        self.run_on_working_copy(
+            move |repository, change_id| repository.describe(change_id, &message),
+            window,
+            cx,
+        );
+    }
+
+    fn open_file(&mut self, file: &FileStatus, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = &self.repository else {
+            return;
+        };
+        let abs_path = repository.workspace_root().join(&file.path);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_abs_path(abs_path, OpenOptions::default(), window, cx)
+                    .detach_and_notify_err(window, cx);
+            })
+            .ok();
+    }
+
+    fn render_working_copy(
+        &self,
+        status: &WorkingCopyStatus,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let change = &status.change;
+        let is_edited = self
+            .loaded_description
+            .as_ref()
+            .is_some_and(|(_, description)| {
+                self.description_editor.read(cx).text(cx) != description.as_ref()
+            });
+        let parents = status
+            .parents
+            .iter()
+            .map(|parent| {
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Label::new("Parent")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(parent.change_id.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Accent),
+                    )
+                    .child(
+                        Label::new(parent.summary().unwrap_or("(no description)").to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .truncate(),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .p_2()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Label::new(change.change_id.clone()).color(Color::Accent))
+                    .children(change.bookmarks.iter().map(|bookmark| {
+                        Label::new(bookmark.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    }))
+                    .when(change.has_conflict, |this| {
+                        this.child(
+                            Label::new("conflict")
+                                .size(LabelSize::Small)
+                                .color(Color::Conflict),
+                        )
+                    }),
+            )
+            .children(parents)
+            .child(
+                div()
+                    .p_1()
+                    .border_1()
+                    .rounded_sm()
+                    .border_color(cx.theme().colors().border_variant)
+                    .bg(cx.theme().colors().editor_background)
+                    .child(EditorElement::new(
+                        &self.description_editor,
+                        panel_editor_style(true, window, cx),
+                    )),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .justify_end()
+                    .child(
+                        panel_button("New")
+                            .tooltip(Tooltip::for_action_title(
+                                "Start a new change on top of this one",
+                                &NewChange,
+                            ))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(NewChange.boxed_clone(), cx)
+                            }),
+                    )
+                    .child(
+                        panel_button("Squash")
+                            .disabled(change.is_empty || status.parents.len() != 1)
+                            .tooltip(Tooltip::for_action_title(
+                                "Move this change into its parent",
+                                &SquashChange,
+                            ))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(SquashChange.boxed_clone(), cx)
+                            }),
+                    )
+                    .child(
+                        panel_button("Abandon")
+                            .tooltip(Tooltip::for_action_title(
+                                "Abandon this change",
+                                &AbandonChange,
+                            ))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(AbandonChange.boxed_clone(), cx)
+                            }),
+                    )
+                    .child(
+                        panel_filled_button("Describe")
+                            .disabled(!is_edited)
+                            .tooltip(Tooltip::for_action_title(
+                                "Set the description of this change",
+                                &DescribeChange,
+                            ))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(DescribeChange.boxed_clone(), cx)
+                            }),
+                    ),
+            )
+    }
+
+    fn render_section_header(&self, title: impl Into<SharedString>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .pt_2()
+            .pb_1()
+            .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+    }
+
+    fn render_file(&self, ix: usize, file: &FileStatus, cx: &mut Context<Self>) -> ListItem {
+        let (status, color) = match file.kind {
+            FileStatusKind::Added => ("A", Color::VersionControlAdded),
+            FileStatusKind::Modified => ("M", Color::VersionControlModified),
+            FileStatusKind::Removed => ("D", Color::VersionControlDeleted),
+            FileStatusKind::Renamed => ("R", Color::VersionControlModified),
+            FileStatusKind::Copied => ("C", Color::VersionControlAdded),
+        };
+        let is_conflicted = self
+            .status
+            .as_ref()
+            .is_some_and(|status| status.conflicted_paths.contains(&file.path));
+        let path = file.path.to_string_lossy().to_string();
+        let file = file.clone();
+
+        ListItem::new(("jj-file", ix))
+            .spacing(ListItemSpacing::Sparse)
+            .start_slot(Label::new(status).size(LabelSize::Small).color(color))
+            .child(
+                Label::new(path)
+                    .size(LabelSize::Small)
+                    .color(if is_conflicted {
+                        Color::Conflict
+                    } else {
+                        Color::Default
+                    })
+                    .truncate(),
+            )
+            .when_some(file.source.clone(), |this, source| {
+                this.tooltip(Tooltip::text(format!("From {}", source.display())))
+            })
+            .when(file.kind != FileStatusKind::Removed, |this| {
+                this.on_click(
+                    cx.listener(move |this, _, window, cx| this.open_file(&file, window, cx)),
+                )
+            })
+    }
+
+    fn render_change(&self, ix: usize, change: &Change, cx: &mut Context<Self>) -> ListItem {
+        let description = change
+            .summary()
+            .map(|summary| summary.to_string())
+            .unwrap_or_else(|| "(no description)".to_string());
+        let change_id = change.change_id.clone();
+
+        ListItem::new(("jj-change", ix))
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(change.is_working_copy)
+            .start_slot(
+                Label::new(change.change_id.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Accent),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .children(change.bookmarks.iter().map(|bookmark| {
+                        Label::new(bookmark.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Info)
+                    }))
+                    .child(
+                        Label::new(description)
+                            .size(LabelSize::Small)
+                            .color(if change.summary().is_some() {
+                                Color::Default
+                            } else {
+                                Color::Muted
+                            })
+                            .truncate(),
+                    ),
+            )
+            .tooltip(Tooltip::text(format!(
+                "{} by {}, {}",
+                change.commit_id, change.author, change.timestamp
+            )))
+            .when(!change.is_working_copy, |this| {
+                this.end_hover_slot(
+                    h_flex()
+                        .child(
+                            panel_icon_button(format!("jj-edit-{ix}"), IconName::Pencil)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Edit this change"))
+                                .on_click(cx.listener({
+                                    let change_id = change_id.clone();
+                                    move |this, _, window, cx| {
+                                        this.run_on_change(
+                                            change_id.clone(),
+                                            |repository, change_id| repository.edit(change_id),
+                                            window,
+                                            cx,
+                                        )
+                                    }
+                                })),
+                        )
+                        .child(
+                            panel_icon_button(format!("jj-abandon-{ix}"), IconName::Trash)
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Abandon this change"))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.run_on_change(
+                                        change_id.clone(),
+                                        |repository, change_id| repository.abandon(change_id),
+                                        window,
+                                        cx,
+                                    )
+                                })),
+                        ),
+                )
+            })
+    }
+
+    fn render_operation(
+        &self,
+        ix: usize,
+        operation: &Operation,
+        cx: &mut Context<Self>,
+    ) -> ListItem {
+        let operation_id = operation.id.clone();
+        let button = if ix == 0 {
+            panel_icon_button("jj-undo", IconName::Undo)
+                .icon_size(IconSize::Small)
+                .tooltip(Tooltip::for_action_title(
+                    "Undo this operation",
+                    &UndoOperation,
+                ))
+                .on_click(|_, window, cx| window.dispatch_action(UndoOperation.boxed_clone(), cx))
+        } else {
+            panel_icon_button(format!("jj-restore-{ix}"), IconName::RotateCcw)
+                .icon_size(IconSize::Small)
+                .tooltip(Tooltip::text("Restore the repository to this operation"))
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    let operation_id = operation_id.clone();
+                    this.run_command(
+                        move |repository| repository.restore_operation(&operation_id),
+                        window,
+                        cx,
+                    )
+                }))
+        };
+
+        ListItem::new(("jj-operation", ix))
+            .spacing(ListItemSpacing::Sparse)
+            .child(
+                v_flex()
+                    .child(
+                        Label::new(operation.description.clone())
+                            .size(LabelSize::Small)
+                            .truncate(),
+                    )
+                    .child(
+                        Label::new(format!("{}, {}", operation.user, operation.time))
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .truncate(),
+                    ),
+            )
+            .end_hover_slot(button)
+    }
+
+    fn render_empty_state(&self) -> impl IntoElement {
+        let message: SharedString = match &self.load_error {
+            Some(error) => error.clone(),
+            None if self.repository.is_some() => "Loading…".into(),
+            None => "No Jujutsu repositories".into(),
+        };
+        h_flex()
+            .size_full()
+            .p_2()
+            .justify_center()
+            .items_center()
+            .child(Label::new(message).color(Color::Muted))
+    }
+}
+
+impl Render for JujutsuPanel {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = match &self.status {
+            Some(status) => v_flex()
+                .id("jj-panel-content")
+                .size_full()
+                .overflow_y_scroll()
+                .child(self.render_working_copy(status, window, cx))
+                .when_some(self.load_error.clone(), |this, error| {
+                    this.child(
+                        h_flex()
+                            .px_2()
+                            .child(Label::new(error).size(LabelSize::Small).color(Color::Error)),
+                    )
+                })
+                .child(self.render_section_header(format!("Changes ({})", status.files.len())))
+                .children(
+                    status
+                        .files
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, file)| self.render_file(ix, file, cx)),
+                )
+                .child(self.render_section_header("Log"))
+                .children(
+                    self.changes
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, change)| self.render_change(ix, change, cx)),
+                )
+                .child(self.render_section_header("Operations"))
+                .children(
+                    self.operations
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, operation)| self.render_operation(ix, operation, cx)),
+                )
+                .into_any_element(),
+            None => self.render_empty_state().into_any_element(),
+        };
+
+        v_flex()
+            .key_context("JujutsuPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .overflow_hidden()
+            .bg(cx.theme().colors().panel_background)
+            .child(
+                self.panel_header_container(window, cx)
+                    .justify_between()
+                    .child(Label::new("Jujutsu").size(LabelSize::Small))
+                    .child(
+                        panel_icon_button("jj-refresh", IconName::ArrowCircle)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Refresh"))
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx))),
+                    ),
+            )
+            .child(content)
+    }
+}
+
+impl Focusable for JujutsuPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for JujutsuPanel {}
+
+impl Panel for JujutsuPanel {
+    fn persistent_name() -> &'static str {
+        "JujutsuPanel"
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.width.unwrap_or(px(360.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.active = active;
+        if active {
+            self.refresh(cx);
+        }
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        Some(IconName::GitBranch)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Jujutsu Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        10
+    }
+
+    fn enabled(&self, cx: &App) -> bool {
+        cx.has_flag::<JjUiFeatureFlag>()
+    }
+}
+
+impl PanelHeader for JujutsuPanel {}
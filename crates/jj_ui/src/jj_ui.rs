@@ -1,9 +1,15 @@
 mod bookmark_picker;
+mod diff_base;
+pub mod jj_panel;
+
+use std::sync::Arc;
 
 use command_palette_hooks::CommandPaletteFilter;
+use editor::Editor;
 use feature_flags::FeatureFlagAppExt as _;
-use gpui::App;
-use jj::JujutsuStore;
+use gpui::{App, Entity, Task};
+use jj::{JujutsuRepository, JujutsuStore};
+use project::Project;
 use workspace::Workspace;
 
 pub fn init(cx: &mut App) {
@@ -11,26 +17,66 @@ pub fn init(cx: &mut App) {
 
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
         bookmark_picker::register(workspace);
+        jj_panel::register(workspace);
+    })
+    .detach();
+
+    cx.observe_new(|editor: &mut Editor, _window, cx| {
+        diff_base::register_editor(editor, cx);
     })
     .detach();
 
     feature_gate_jj_ui_actions(cx);
 }
 
+/// Returns the repository of the Jujutsu workspace the first of the project's worktrees is in.
+pub(crate) fn project_repository(
+    project: &Entity<Project>,
+    jj_store: &Entity<JujutsuStore>,
+    cx: &mut App,
+) -> Task<Option<Arc<dyn JujutsuRepository>>> {
+    let project = project.read(cx);
+    if !project.is_local() {
+        return Task::ready(None);
+    }
+    let worktree_paths = project
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+        .collect::<Vec<_>>();
+    let repositories = jj_store.update(cx, |jj_store, cx| {
+        worktree_paths
+            .into_iter()
+            .map(|path| jj_store.repository_for_path(path, cx))
+            .collect::<Vec<_>>()
+    });
+    cx.spawn(async move |_| {
+        for repository in repositories {
+            if let Some(repository) = repository.await {
+                return Some(repository);
+            }
+        }
+        None
+    })
+}
+
 fn feature_gate_jj_ui_actions(cx: &mut App) {
-    const JJ_ACTION_NAMESPACE: &str = "jj";
+    const JJ_ACTION_NAMESPACES: [&str; 2] = ["jj", "jj_panel"];
 
     CommandPaletteFilter::update_global(cx, |filter, _cx| {
-        filter.hide_namespace(JJ_ACTION_NAMESPACE);
+        for namespace in JJ_ACTION_NAMESPACES {
+            filter.hide_namespace(namespace);
+        }
     });
 
     cx.observe_flag::<feature_flags::JjUiFeatureFlag, _>({
         move |is_enabled, cx| {
             CommandPaletteFilter::update_global(cx, |filter, _cx| {
-                if is_enabled {
-                    filter.show_namespace(JJ_ACTION_NAMESPACE);
-                } else {
-                    filter.hide_namespace(JJ_ACTION_NAMESPACE);
+                for namespace in JJ_ACTION_NAMESPACES {
+                    if is_enabled {
+                        filter.show_namespace(namespace);
+                    } else {
+                        filter.hide_namespace(namespace);
+                    }
                 }
             });
         }
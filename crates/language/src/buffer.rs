@@ -114,6 +114,9 @@ pub struct Buffer {
     parse_status: (watch::Sender<ParseStatus>, watch::Receiver<ParseStatus>),
     non_text_state_update_count: usize,
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
+    /// Diagnostics that don't come from a language server, by their source. Unlike the diagnostics
+    /// of language servers, they are not replicated.
+    other_diagnostics: SmallVec<[(SharedString, DiagnosticSet); 1]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
     diagnostics_timestamp: clock::Lamport,
     completion_triggers: BTreeSet<String>,
@@ -147,6 +150,7 @@ pub struct BufferSnapshot {
     pub(crate) syntax: SyntaxSnapshot,
     file: Option<Arc<dyn File>>,
     diagnostics: SmallVec<[(LanguageServerId, DiagnosticSet); 2]>,
+    other_diagnostics: SmallVec<[(SharedString, DiagnosticSet); 1]>,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
     language: Option<Arc<Language>>,
     non_text_state_update_count: usize,
@@ -488,6 +492,9 @@ pub struct Chunk<'a> {
     /// The highlight style that has been applied to this chunk in
     /// the editor.
     pub highlight_style: Option<HighlightStyle>,
+    /// The style of the language server's semantic token covering this chunk,
+    /// which takes precedence over its syntax highlighting.
+    pub semantic_highlight_style: Option<HighlightStyle>,
     /// The severity of diagnostic associated with this chunk, if any.
     pub diagnostic_severity: Option<DiagnosticSeverity>,
     /// Whether this chunk of text is marked as unnecessary.
@@ -950,6 +957,7 @@ impl Buffer {
             language: None,
             remote_selections: Default::default(),
             diagnostics: Default::default(),
+            other_diagnostics: Default::default(),
             diagnostics_timestamp: Default::default(),
             completion_triggers: Default::default(),
             completion_triggers_per_language_server: Default::default(),
@@ -984,6 +992,7 @@ impl Buffer {
                 syntax,
                 file: None,
                 diagnostics: Default::default(),
+                other_diagnostics: Default::default(),
                 remote_selections: Default::default(),
                 language,
                 non_text_state_update_count: 0,
@@ -1002,6 +1011,7 @@ impl Buffer {
             syntax,
             file: None,
             diagnostics: Default::default(),
+            other_diagnostics: Default::default(),
             remote_selections: Default::default(),
             language: None,
             non_text_state_update_count: 0,
@@ -1030,6 +1040,7 @@ impl Buffer {
             syntax,
             file: None,
             diagnostics: Default::default(),
+            other_diagnostics: Default::default(),
             remote_selections: Default::default(),
             language,
             non_text_state_update_count: 0,
@@ -1050,6 +1061,7 @@ impl Buffer {
             file: self.file.clone(),
             remote_selections: self.remote_selections.clone(),
             diagnostics: self.diagnostics.clone(),
+            other_diagnostics: self.other_diagnostics.clone(),
             language: self.language.clone(),
             non_text_state_update_count: self.non_text_state_update_count,
         }
@@ -1575,6 +1587,33 @@ impl Buffer {
         self.send_operation(op, true, cx);
     }
 
+    /// Assign to the buffer a set of diagnostics that don't come from a language server,
+    /// replacing the ones from the same source.
+    pub fn update_other_diagnostics(
+        &mut self,
+        source: SharedString,
+        diagnostics: DiagnosticSet,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = self
+            .other_diagnostics
+            .binary_search_by(|(other_source, _)| other_source.cmp(&source));
+        if diagnostics.is_empty() {
+            let Ok(ix) = ix else {
+                return;
+            };
+            self.other_diagnostics.remove(ix);
+        } else {
+            match ix {
+                Err(ix) => self.other_diagnostics.insert(ix, (source, diagnostics)),
+                Ok(ix) => self.other_diagnostics[ix].1 = diagnostics,
+            };
+        }
+        self.non_text_state_update_count += 1;
+        cx.notify();
+        cx.emit(BufferEvent::DiagnosticsUpdated);
+    }
+
     pub fn get_diagnostics(&self, server_id: LanguageServerId) -> Option<&DiagnosticSet> {
         let Ok(idx) = self.diagnostics.binary_search_by_key(&server_id, |v| v.0) else {
             return None;
@@ -4285,7 +4324,14 @@ impl BufferSnapshot {
 
     /// Returns if the buffer contains any diagnostics.
     pub fn has_diagnostics(&self) -> bool {
-        !self.diagnostics.is_empty()
+        !self.diagnostics.is_empty() || !self.other_diagnostics.is_empty()
+    }
+
+    fn diagnostic_sets(&self) -> impl Iterator<Item = &DiagnosticSet> {
+        self.diagnostics
+            .iter()
+            .map(|(_, set)| set)
+            .chain(self.other_diagnostics.iter().map(|(_, set)| set))
     }
 
     /// Returns all the diagnostics intersecting the given range.
@@ -4299,9 +4345,8 @@ impl BufferSnapshot {
         O: 'a + FromAnchor,
     {
         let mut iterators: Vec<_> = self
-            .diagnostics
-            .iter()
-            .map(|(_, collection)| {
+            .diagnostic_sets()
+            .map(|collection| {
                 collection
                     .range::<T, text::Anchor>(search_range.clone(), self, true, reversed)
                     .peekable()
@@ -4336,11 +4381,12 @@ impl BufferSnapshot {
 
     /// Returns all the diagnostic groups associated with the given
     /// language server ID. If no language server ID is provided,
-    /// all diagnostics groups are returned.
+    /// all diagnostics groups are returned, including the ones that
+    /// don't come from a language server.
     pub fn diagnostic_groups(
         &self,
         language_server_id: Option<LanguageServerId>,
-    ) -> Vec<(LanguageServerId, DiagnosticGroup<Anchor>)> {
+    ) -> Vec<(Option<LanguageServerId>, DiagnosticGroup<Anchor>)> {
         let mut groups = Vec::new();
 
         if let Some(language_server_id) = language_server_id {
@@ -4350,11 +4396,14 @@ impl BufferSnapshot {
             {
                 self.diagnostics[ix]
                     .1
-                    .groups(language_server_id, &mut groups, self);
+                    .groups(Some(language_server_id), &mut groups, self);
             }
         } else {
             for (language_server_id, diagnostics) in self.diagnostics.iter() {
-                diagnostics.groups(*language_server_id, &mut groups, self);
+                diagnostics.groups(Some(*language_server_id), &mut groups, self);
+            }
+            for (_, diagnostics) in self.other_diagnostics.iter() {
+                diagnostics.groups(None, &mut groups, self);
             }
         }
 
@@ -4375,9 +4424,8 @@ impl BufferSnapshot {
     where
         O: FromAnchor + 'static,
     {
-        self.diagnostics
-            .iter()
-            .flat_map(move |(_, set)| set.group(group_id, self))
+        self.diagnostic_sets()
+            .flat_map(move |set| set.group(group_id, self))
     }
 
     /// An integer version number that accounts for all updates besides
@@ -4504,6 +4552,7 @@ impl Clone for BufferSnapshot {
             file: self.file.clone(),
             remote_selections: self.remote_selections.clone(),
             diagnostics: self.diagnostics.clone(),
+            other_diagnostics: self.other_diagnostics.clone(),
             language: self.language.clone(),
             non_text_state_update_count: self.non_text_state_update_count,
         }
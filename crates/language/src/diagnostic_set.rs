@@ -181,8 +181,8 @@ impl DiagnosticSet {
     /// Adds all of this set's diagnostic groups to the given output vector.
     pub fn groups(
         &self,
-        language_server_id: LanguageServerId,
-        output: &mut Vec<(LanguageServerId, DiagnosticGroup<Anchor>)>,
+        language_server_id: Option<LanguageServerId>,
+        output: &mut Vec<(Option<LanguageServerId>, DiagnosticGroup<Anchor>)>,
         buffer: &text::BufferSnapshot,
     ) {
         let mut groups = HashMap::default();
@@ -110,6 +110,20 @@ pub enum LanguageServerSelector {
     Name(LanguageServerName),
 }
 
+/// The key of the type hierarchy provider capability, which [`ServerCapabilities`] has no field
+/// for, so that it's kept among the experimental capabilities instead.
+pub const TYPE_HIERARCHY_PROVIDER: &str = "typeHierarchyProvider";
+
+/// The `initialize` request, with the response read as JSON to get the capabilities that
+/// [`ServerCapabilities`] doesn't have fields for.
+enum RawInitialize {}
+
+impl request::Request for RawInitialize {
+    type Params = InitializeParams;
+    type Result = Value;
+    const METHOD: &'static str = request::Initialize::METHOD;
+}
+
 /// Identifies a running language server.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
@@ -640,6 +654,9 @@ impl LanguageServer {
                     code_lens: Some(CodeLensWorkspaceClientCapabilities {
                         refresh_support: Some(true),
                     }),
+                    semantic_tokens: Some(SemanticTokensWorkspaceClientCapabilities {
+                        refresh_support: Some(true),
+                    }),
                     workspace_edit: Some(WorkspaceEditClientCapabilities {
                         resource_operations: Some(vec![
                             ResourceOperationKind::Create,
@@ -798,6 +815,70 @@ impl LanguageServer {
                     color_provider: Some(DocumentColorClientCapabilities {
                         dynamic_registration: Some(false),
                     }),
+                    call_hierarchy: Some(DynamicRegistrationClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    type_hierarchy: Some(DynamicRegistrationClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    folding_range: Some(FoldingRangeClientCapabilities {
+                        dynamic_registration: Some(false),
+                        line_folding_only: Some(true),
+                        ..FoldingRangeClientCapabilities::default()
+                    }),
+                    document_link: Some(DocumentLinkClientCapabilities {
+                        dynamic_registration: Some(false),
+                        tooltip_support: Some(false),
+                    }),
+                    semantic_tokens: Some(SemanticTokensClientCapabilities {
+                        dynamic_registration: Some(false),
+                        requests: SemanticTokensClientCapabilitiesRequests {
+                            range: Some(false),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        },
+                        token_types: vec![
+                            SemanticTokenType::NAMESPACE,
+                            SemanticTokenType::TYPE,
+                            SemanticTokenType::CLASS,
+                            SemanticTokenType::ENUM,
+                            SemanticTokenType::INTERFACE,
+                            SemanticTokenType::STRUCT,
+                            SemanticTokenType::TYPE_PARAMETER,
+                            SemanticTokenType::PARAMETER,
+                            SemanticTokenType::VARIABLE,
+                            SemanticTokenType::PROPERTY,
+                            SemanticTokenType::ENUM_MEMBER,
+                            SemanticTokenType::EVENT,
+                            SemanticTokenType::FUNCTION,
+                            SemanticTokenType::METHOD,
+                            SemanticTokenType::MACRO,
+                            SemanticTokenType::KEYWORD,
+                            SemanticTokenType::MODIFIER,
+                            SemanticTokenType::COMMENT,
+                            SemanticTokenType::STRING,
+                            SemanticTokenType::NUMBER,
+                            SemanticTokenType::REGEXP,
+                            SemanticTokenType::OPERATOR,
+                            SemanticTokenType::DECORATOR,
+                        ],
+                        token_modifiers: vec![
+                            SemanticTokenModifier::DECLARATION,
+                            SemanticTokenModifier::DEFINITION,
+                            SemanticTokenModifier::READONLY,
+                            SemanticTokenModifier::STATIC,
+                            SemanticTokenModifier::DEPRECATED,
+                            SemanticTokenModifier::ABSTRACT,
+                            SemanticTokenModifier::ASYNC,
+                            SemanticTokenModifier::MODIFICATION,
+                            SemanticTokenModifier::DOCUMENTATION,
+                            SemanticTokenModifier::DEFAULT_LIBRARY,
+                        ],
+                        formats: vec![TokenFormat::RELATIVE],
+                        overlapping_token_support: Some(false),
+                        multiline_token_support: Some(false),
+                        server_cancel_support: Some(true),
+                        augments_syntax_tokens: Some(true),
+                    }),
                     ..TextDocumentClientCapabilities::default()
                 }),
                 experimental: Some(json!({
@@ -838,7 +919,7 @@ impl LanguageServer {
     ) -> Task<Result<Arc<Self>>> {
         cx.spawn(async move |_| {
             let response = self
-                .request::<request::Initialize>(params)
+                .request::<RawInitialize>(params)
                 .await
                 .into_response()
                 .with_context(|| {
@@ -848,6 +929,20 @@ impl LanguageServer {
                         self.server_id()
                     )
                 })?;
+            let type_hierarchy_provider = response
+                .pointer("/capabilities/typeHierarchyProvider")
+                .cloned();
+            let mut response = serde_json::from_value::<InitializeResult>(response)
+                .context("parsing initialize response")?;
+            if let Some(provider) = type_hierarchy_provider {
+                let experimental = response
+                    .capabilities
+                    .experimental
+                    .get_or_insert_with(|| json!({}));
+                if let Some(experimental) = experimental.as_object_mut() {
+                    experimental.insert(TYPE_HIERARCHY_PROVIDER.to_string(), provider);
+                }
+            }
             if let Some(info) = response.server_info {
                 self.process_name = info.name.into();
             }
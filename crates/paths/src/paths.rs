@@ -155,6 +155,12 @@ pub fn remote_server_state_dir() -> &'static PathBuf {
     REMOTE_SERVER_STATE.get_or_init(|| data_dir().join("server_state"))
 }
 
+/// Returns the path to the directory the terminal's shell integration scripts are written to.
+pub fn shell_integration_dir() -> &'static PathBuf {
+    static SHELL_INTEGRATION_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SHELL_INTEGRATION_DIR.get_or_init(|| temp_dir().join("shell_integration"))
+}
+
 /// Returns the path to the `Zed.log` file.
 pub fn log_file() -> &'static PathBuf {
     static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
@@ -23,9 +23,12 @@ use git::{
     blame::Blame,
     parse_git_remote_url,
     repository::{
-        Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, FetchOptions,
-        GitRepository, GitRepositoryCheckpoint, PushOptions, Remote, RemoteCommandOutput, RepoPath,
-        ResetMode, UpstreamTrackingStatus,
+        Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, ConflictVersions, DiffType,
+        FetchOptions, FileHistoryEntry, GitRepository, GitRepositoryCheckpoint, GitWorktree,
+        LogEntry, LogOptions, PushOptions, RebaseAction, RebaseCommand, RebaseStatus,
+        RebaseTodoEntry, Remote, RemoteCommandOutput, RepoPath, ResetMode, SequencerOperation,
+        SequencerState, StashEntry, StashScope, Tag, UpstreamTrackingStatus,
+        validate_new_worktree_path, validate_rebase_todo, validate_removed_worktree_path,
     },
     status::{
         FileStatus, GitSummary, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
@@ -43,7 +46,10 @@ use parking_lot::Mutex;
 use postage::stream::Stream as _;
 use rpc::{
     AnyProtoClient, TypedEnvelope,
-    proto::{self, FromProto, SSH_PROJECT_ID, ToProto, git_reset, split_repository_update},
+    proto::{
+        self, FromProto, SSH_PROJECT_ID, ToProto, git_rebase_command, git_reset, git_stash_push,
+        rebase_todo_entry, split_repository_update,
+    },
 };
 use serde::Deserialize;
 use std::{
@@ -423,7 +429,32 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_reset);
         client.add_entity_request_handler(Self::handle_show);
         client.add_entity_request_handler(Self::handle_load_commit_diff);
+        client.add_entity_request_handler(Self::handle_log);
+        client.add_entity_request_handler(Self::handle_file_history);
+        client.add_entity_request_handler(Self::handle_rebase_todo);
+        client.add_entity_request_handler(Self::handle_rebase_start);
+        client.add_entity_request_handler(Self::handle_rebase_command);
+        client.add_entity_request_handler(Self::handle_rebase_status);
+        client.add_entity_request_handler(Self::handle_load_conflict_versions);
+        client.add_entity_request_handler(Self::handle_worktrees);
+        client.add_entity_request_handler(Self::handle_create_worktree);
+        client.add_entity_request_handler(Self::handle_remove_worktree);
+        client.add_entity_request_handler(Self::handle_prune_worktrees);
+        client.add_entity_request_handler(Self::handle_cherry_pick);
+        client.add_entity_request_handler(Self::handle_revert);
+        client.add_entity_request_handler(Self::handle_sequencer_state);
+        client.add_entity_request_handler(Self::handle_sequencer_command);
+        client.add_entity_request_handler(Self::handle_tags);
+        client.add_entity_request_handler(Self::handle_create_tag);
+        client.add_entity_request_handler(Self::handle_delete_tag);
+        client.add_entity_request_handler(Self::handle_push_tag);
         client.add_entity_request_handler(Self::handle_checkout_files);
+        client.add_entity_request_handler(Self::handle_stash_push);
+        client.add_entity_request_handler(Self::handle_stash_list);
+        client.add_entity_request_handler(Self::handle_stash_diff);
+        client.add_entity_request_handler(Self::handle_stash_apply);
+        client.add_entity_request_handler(Self::handle_stash_pop);
+        client.add_entity_request_handler(Self::handle_stash_drop);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
         client.add_entity_request_handler(Self::handle_set_index_text);
         client.add_entity_request_handler(Self::handle_askpass);
@@ -1875,438 +1906,1005 @@ impl GitStore {
         })
     }
 
-    async fn handle_reset(
+    async fn handle_log(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GitReset>,
+        envelope: TypedEnvelope<proto::GitLog>,
         mut cx: AsyncApp,
-    ) -> Result<proto::Ack> {
+    ) -> Result<proto::GitLogResponse> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let mode = match envelope.payload.mode() {
-            git_reset::ResetMode::Soft => ResetMode::Soft,
-            git_reset::ResetMode::Mixed => ResetMode::Mixed,
+        let options = LogOptions {
+            skip: envelope.payload.skip as usize,
+            limit: envelope.payload.limit as usize,
+            all_branches: envelope.payload.all_branches,
+            path: envelope.payload.path.as_deref().map(RepoPath::from_str),
+            author: envelope.payload.author,
+            message: envelope.payload.message,
         };
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.log(options)
+            })?
+            .await??;
+        Ok(proto::GitLogResponse {
+            entries: entries.into_iter().map(log_entry_to_proto).collect(),
+        })
+    }
 
-        repository_handle
-            .update(&mut cx, |repository_handle, cx| {
-                repository_handle.reset(envelope.payload.commit, mode, cx)
+    async fn handle_file_history(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitFileHistory>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitFileHistoryResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let path = RepoPath::from_str(&envelope.payload.path);
+        let rows = envelope
+            .payload
+            .rows
+            .map(|rows| rows.start as u32..rows.end as u32);
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.file_history(path, rows, envelope.payload.limit as usize)
             })?
             .await??;
-        Ok(proto::Ack {})
+        Ok(proto::GitFileHistoryResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::FileHistoryEntry {
+                    commit: Some(log_entry_to_proto(entry.commit)),
+                    file: Some(proto::CommitFile {
+                        path: entry.file.path.to_string(),
+                        old_text: entry.file.old_text,
+                        new_text: entry.file.new_text,
+                    }),
+                    rows: entry
+                        .rows
+                        .into_iter()
+                        .map(|rows| proto::Range {
+                            start: rows.start as u64,
+                            end: rows.end as u64,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
     }
 
-    async fn handle_checkout_files(
+    async fn handle_rebase_todo(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GitCheckoutFiles>,
+        envelope: TypedEnvelope<proto::GitRebaseTodo>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitRebaseTodoResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_todo(envelope.payload.onto)
+            })?
+            .await??;
+        Ok(proto::GitRebaseTodoResponse {
+            entries: entries.iter().map(rebase_todo_entry_to_proto).collect(),
+        })
+    }
+
+    async fn handle_rebase_start(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseStart>,
         mut cx: AsyncApp,
     ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
-        let paths = envelope
+
+        let todo = envelope
             .payload
-            .paths
+            .todo
             .iter()
-            .map(|s| RepoPath::from_str(s))
-            .collect();
-
+            .map(proto_to_rebase_todo_entry)
+            .collect::<Vec<_>>();
+        validate_rebase_todo(&todo)?;
         repository_handle
-            .update(&mut cx, |repository_handle, cx| {
-                repository_handle.checkout_files(&envelope.payload.commit, paths, cx)
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_start(envelope.payload.onto, todo)
             })?
             .await??;
         Ok(proto::Ack {})
     }
 
-    async fn handle_open_commit_message_buffer(
+    async fn handle_rebase_command(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::OpenCommitMessageBuffer>,
+        envelope: TypedEnvelope<proto::GitRebaseCommand>,
         mut cx: AsyncApp,
-    ) -> Result<proto::OpenBufferResponse> {
+    ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
-        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
-        let buffer = repository
-            .update(&mut cx, |repository, cx| {
-                repository.open_commit_buffer(None, this.read(cx).buffer_store.clone(), cx)
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let command = match envelope.payload.command() {
+            git_rebase_command::Command::Continue => RebaseCommand::Continue,
+            git_rebase_command::Command::Skip => RebaseCommand::Skip,
+            git_rebase_command::Command::Abort => RebaseCommand::Abort,
+        };
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_command(command)
             })?
-            .await?;
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-        let buffer_id = buffer.read_with(&cx, |buffer, _| buffer.remote_id())?;
-        this.update(&mut cx, |this, cx| {
-            this.buffer_store.update(cx, |buffer_store, cx| {
-                buffer_store
-                    .create_buffer_for_peer(
-                        &buffer,
-                        envelope.original_sender_id.unwrap_or(envelope.sender_id),
-                        cx,
-                    )
-                    .detach_and_log_err(cx);
-            })
-        })?;
+    async fn handle_rebase_status(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseStatus>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitRebaseStatusResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        Ok(proto::OpenBufferResponse {
-            buffer_id: buffer_id.to_proto(),
+        let status = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_status()
+            })?
+            .await??;
+        Ok(proto::GitRebaseStatusResponse {
+            status: status.map(|status| proto::RebaseStatus {
+                head_name: status.head_name.map(Into::into),
+                onto: status.onto.into(),
+                step: status.step as u64,
+                total_steps: status.total_steps as u64,
+                stopped_at: status.stopped_at.map(Into::into),
+            }),
         })
     }
 
-    async fn handle_askpass(
+    async fn handle_load_conflict_versions(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::AskPassRequest>,
+        envelope: TypedEnvelope<proto::GitLoadConflictVersions>,
         mut cx: AsyncApp,
-    ) -> Result<proto::AskPassResponse> {
+    ) -> Result<proto::GitLoadConflictVersionsResponse> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
-        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
-
-        let delegates = cx.update(|cx| repository.read(cx).askpass_delegates.clone())?;
-        let Some(mut askpass) = delegates.lock().remove(&envelope.payload.askpass_id) else {
-            debug_panic!("no askpass found");
-            anyhow::bail!("no askpass found");
-        };
-
-        let response = askpass.ask_password(envelope.payload.prompt).await?;
-
-        delegates
-            .lock()
-            .insert(envelope.payload.askpass_id, askpass);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        Ok(proto::AskPassResponse { response })
+        let path = RepoPath::from_str(&envelope.payload.path);
+        let versions = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.load_conflict_versions(path)
+            })?
+            .await??;
+        Ok(proto::GitLoadConflictVersionsResponse {
+            base: versions.base,
+            ours: versions.ours,
+            theirs: versions.theirs,
+        })
     }
 
-    async fn handle_check_for_pushed_commits(
+    async fn handle_worktrees(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::CheckForPushedCommits>,
+        envelope: TypedEnvelope<proto::GitWorktrees>,
         mut cx: AsyncApp,
-    ) -> Result<proto::CheckForPushedCommitsResponse> {
+    ) -> Result<proto::GitWorktreesResponse> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let branches = repository_handle
+        let worktrees = repository_handle
             .update(&mut cx, |repository_handle, _| {
-                repository_handle.check_for_pushed_commits()
+                repository_handle.worktrees()
             })?
             .await??;
-        Ok(proto::CheckForPushedCommitsResponse {
-            pushed_to: branches
+        Ok(proto::GitWorktreesResponse {
+            worktrees: worktrees
                 .into_iter()
-                .map(|commit| commit.to_string())
+                .map(|worktree| proto::Worktree {
+                    path: worktree.path.to_proto(),
+                    head_sha: worktree.head_sha.map(Into::into),
+                    branch: worktree.branch.map(Into::into),
+                    is_main: worktree.is_main,
+                    is_locked: worktree.is_locked,
+                    is_prunable: worktree.is_prunable,
+                })
                 .collect(),
         })
     }
 
-    async fn handle_git_diff(
+    async fn handle_create_worktree(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GitDiff>,
+        envelope: TypedEnvelope<proto::GitCreateWorktree>,
         mut cx: AsyncApp,
-    ) -> Result<proto::GitDiffResponse> {
+    ) -> Result<proto::Ack> {
         let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
         let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
-        let diff_type = match envelope.payload.diff_type() {
-            proto::git_diff::DiffType::HeadToIndex => DiffType::HeadToIndex,
-            proto::git_diff::DiffType::HeadToWorktree => DiffType::HeadToWorktree,
-        };
 
-        let mut diff = repository_handle
-            .update(&mut cx, |repository_handle, cx| {
-                repository_handle.diff(diff_type, cx)
+        let path = PathBuf::from_proto(envelope.payload.path);
+        let (work_directory, worktrees) =
+            repository_handle.update(&mut cx, |repository_handle, _| {
+                (
+                    repository_handle.work_directory_abs_path.clone(),
+                    repository_handle.worktrees(),
+                )
+            })?;
+        validate_new_worktree_path(&path, &work_directory, &worktrees.await??)?;
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.create_worktree(
+                    path,
+                    envelope.payload.commit,
+                    envelope.payload.new_branch,
+                )
             })?
             .await??;
-        const ONE_MB: usize = 1_000_000;
-        if diff.len() > ONE_MB {
-            diff = diff.chars().take(ONE_MB).collect()
-        }
-
-        Ok(proto::GitDiffResponse { diff })
+        Ok(proto::Ack {})
     }
 
-    async fn handle_open_unstaged_diff(
+    async fn handle_remove_worktree(
         this: Entity<Self>,
-        request: TypedEnvelope<proto::OpenUnstagedDiff>,
+        envelope: TypedEnvelope<proto::GitRemoveWorktree>,
         mut cx: AsyncApp,
-    ) -> Result<proto::OpenUnstagedDiffResponse> {
-        let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        let diff = this
-            .update(&mut cx, |this, cx| {
-                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
-                Some(this.open_unstaged_diff(buffer, cx))
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let path = PathBuf::from_proto(envelope.payload.path);
+        let (work_directory, worktrees) =
+            repository_handle.update(&mut cx, |repository_handle, _| {
+                (
+                    repository_handle.work_directory_abs_path.clone(),
+                    repository_handle.worktrees(),
+                )
+            })?;
+        validate_removed_worktree_path(&path, &work_directory, &worktrees.await??)?;
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.remove_worktree(path, envelope.payload.force)
             })?
-            .context("missing buffer")?
-            .await?;
-        this.update(&mut cx, |this, _| {
-            let shared_diffs = this
-                .shared_diffs
-                .entry(request.original_sender_id.unwrap_or(request.sender_id))
-                .or_default();
-            shared_diffs.entry(buffer_id).or_default().unstaged = Some(diff.clone());
-        })?;
-        let staged_text = diff.read_with(&cx, |diff, _| diff.base_text_string())?;
-        Ok(proto::OpenUnstagedDiffResponse { staged_text })
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_open_uncommitted_diff(
+    async fn handle_prune_worktrees(
         this: Entity<Self>,
-        request: TypedEnvelope<proto::OpenUncommittedDiff>,
+        envelope: TypedEnvelope<proto::GitPruneWorktrees>,
         mut cx: AsyncApp,
-    ) -> Result<proto::OpenUncommittedDiffResponse> {
-        let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        let diff = this
-            .update(&mut cx, |this, cx| {
-                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
-                Some(this.open_uncommitted_diff(buffer, cx))
-            })?
-            .context("missing buffer")?
-            .await?;
-        this.update(&mut cx, |this, _| {
-            let shared_diffs = this
-                .shared_diffs
-                .entry(request.original_sender_id.unwrap_or(request.sender_id))
-                .or_default();
-            shared_diffs.entry(buffer_id).or_default().uncommitted = Some(diff.clone());
-        })?;
-        diff.read_with(&cx, |diff, cx| {
-            use proto::open_uncommitted_diff_response::Mode;
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-            let unstaged_diff = diff.secondary_diff();
-            let index_snapshot = unstaged_diff.and_then(|diff| {
-                let diff = diff.read(cx);
-                diff.base_text_exists().then(|| diff.base_text())
-            });
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.prune_worktrees()
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-            let mode;
-            let staged_text;
-            let committed_text;
-            if diff.base_text_exists() {
-                let committed_snapshot = diff.base_text();
-                committed_text = Some(committed_snapshot.text());
-                if let Some(index_text) = index_snapshot {
-                    if index_text.remote_id() == committed_snapshot.remote_id() {
-                        mode = Mode::IndexMatchesHead;
-                        staged_text = None;
-                    } else {
-                        mode = Mode::IndexAndHead;
-                        staged_text = Some(index_text.text());
-                    }
-                } else {
-                    mode = Mode::IndexAndHead;
-                    staged_text = None;
-                }
-            } else {
-                mode = Mode::IndexAndHead;
-                committed_text = None;
-                staged_text = index_snapshot.as_ref().map(|buffer| buffer.text());
-            }
+    async fn handle_cherry_pick(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCherryPick>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-            proto::OpenUncommittedDiffResponse {
-                committed_text,
-                staged_text,
-                mode: mode.into(),
-            }
-        })
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.cherry_pick(envelope.payload.commits)
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_update_diff_bases(
+    async fn handle_revert(
         this: Entity<Self>,
-        request: TypedEnvelope<proto::UpdateDiffBases>,
+        envelope: TypedEnvelope<proto::GitRevert>,
         mut cx: AsyncApp,
-    ) -> Result<()> {
-        let buffer_id = BufferId::new(request.payload.buffer_id)?;
-        this.update(&mut cx, |this, cx| {
-            if let Some(diff_state) = this.diffs.get_mut(&buffer_id) {
-                if let Some(buffer) = this.buffer_store.read(cx).get(buffer_id) {
-                    let buffer = buffer.read(cx).text_snapshot();
-                    diff_state.update(cx, |diff_state, cx| {
-                        diff_state.handle_base_texts_updated(buffer, request.payload, cx);
-                    })
-                }
-            }
-        })
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.revert(envelope.payload.commits)
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    async fn handle_blame_buffer(
+    async fn handle_sequencer_state(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::BlameBuffer>,
+        envelope: TypedEnvelope<proto::GitSequencerState>,
         mut cx: AsyncApp,
-    ) -> Result<proto::BlameBufferResponse> {
-        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
-        let version = deserialize_version(&envelope.payload.version);
-        let buffer = this.read_with(&cx, |this, cx| {
-            this.buffer_store.read(cx).get_existing(buffer_id)
-        })??;
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(version.clone())
-            })?
-            .await?;
-        let blame = this
-            .update(&mut cx, |this, cx| {
-                this.blame_buffer(&buffer, Some(version), cx)
+    ) -> Result<proto::GitSequencerStateResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let state = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.sequencer_state()
             })?
-            .await?;
-        Ok(serialize_blame_buffer_response(blame))
+            .await??;
+        Ok(proto::GitSequencerStateResponse {
+            state: state.map(|state| proto::SequencerState {
+                operation: sequencer_operation_to_proto(state.operation).into(),
+                sha: state.sha.into(),
+            }),
+        })
     }
 
-    async fn handle_get_permalink_to_line(
+    async fn handle_sequencer_command(
         this: Entity<Self>,
-        envelope: TypedEnvelope<proto::GetPermalinkToLine>,
+        envelope: TypedEnvelope<proto::GitSequencerCommand>,
         mut cx: AsyncApp,
-    ) -> Result<proto::GetPermalinkToLineResponse> {
-        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
-        // let version = deserialize_version(&envelope.payload.version);
-        let selection = {
-            let proto_selection = envelope
-                .payload
-                .selection
-                .context("no selection to get permalink for defined")?;
-            proto_selection.start as u32..proto_selection.end as u32
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let operation = proto_to_sequencer_operation(envelope.payload.operation());
+        let command = match envelope.payload.command() {
+            git_rebase_command::Command::Continue => RebaseCommand::Continue,
+            git_rebase_command::Command::Skip => RebaseCommand::Skip,
+            git_rebase_command::Command::Abort => RebaseCommand::Abort,
         };
-        let buffer = this.read_with(&cx, |this, cx| {
-            this.buffer_store.read(cx).get_existing(buffer_id)
-        })??;
-        let permalink = this
-            .update(&mut cx, |this, cx| {
-                this.get_permalink_to_line(&buffer, selection, cx)
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.sequencer_command(operation, command)
             })?
-            .await?;
-        Ok(proto::GetPermalinkToLineResponse {
-            permalink: permalink.to_string(),
-        })
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    fn repository_for_request(
-        this: &Entity<Self>,
-        id: RepositoryId,
-        cx: &mut AsyncApp,
-    ) -> Result<Entity<Repository>> {
-        this.read_with(cx, |this, _| {
-            this.repositories
-                .get(&id)
-                .context("missing repository handle")
-                .cloned()
-        })?
-    }
+    async fn handle_tags(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitTags>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitTagsResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-    pub fn repo_snapshots(&self, cx: &App) -> HashMap<RepositoryId, RepositorySnapshot> {
-        self.repositories
-            .iter()
-            .map(|(id, repo)| (*id, repo.read(cx).snapshot.clone()))
-            .collect()
+        let tags = repository_handle
+            .update(&mut cx, |repository_handle, _| repository_handle.tags())?
+            .await??;
+        Ok(proto::GitTagsResponse {
+            tags: tags
+                .into_iter()
+                .map(|tag| proto::Tag {
+                    name: tag.name.into(),
+                    sha: tag.sha.into(),
+                    message: tag.message.map(Into::into),
+                    timestamp: tag.timestamp,
+                })
+                .collect(),
+        })
     }
-}
 
-impl BufferGitState {
-    fn new(_git_store: WeakEntity<GitStore>) -> Self {
-        Self {
-            unstaged_diff: Default::default(),
-            uncommitted_diff: Default::default(),
-            recalculate_diff_task: Default::default(),
-            language: Default::default(),
-            language_registry: Default::default(),
-            recalculating_tx: postage::watch::channel_with(false).0,
-            hunk_staging_operation_count: 0,
-            hunk_staging_operation_count_as_of_write: 0,
-            head_text: Default::default(),
-            index_text: Default::default(),
-            head_changed: Default::default(),
-            index_changed: Default::default(),
-            language_changed: Default::default(),
-            conflict_updated_futures: Default::default(),
-            conflict_set: Default::default(),
-            reparse_conflict_markers_task: Default::default(),
-        }
-    }
+    async fn handle_create_tag(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCreateTag>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-    fn buffer_language_changed(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
-        self.language = buffer.read(cx).language().cloned();
-        self.language_changed = true;
-        let _ = self.recalculate_diffs(buffer.read(cx).text_snapshot(), cx);
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.create_tag(
+                    envelope.payload.name,
+                    envelope.payload.commit,
+                    envelope.payload.message,
+                )
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    fn reparse_conflict_markers(
-        &mut self,
-        buffer: text::BufferSnapshot,
-        cx: &mut Context<Self>,
-    ) -> oneshot::Receiver<()> {
-        let (tx, rx) = oneshot::channel();
-
-        let Some(conflict_set) = self
-            .conflict_set
-            .as_ref()
-            .and_then(|conflict_set| conflict_set.upgrade())
-        else {
-            return rx;
-        };
+    async fn handle_delete_tag(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitDeleteTag>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let old_snapshot = conflict_set.read_with(cx, |conflict_set, _| {
-            if conflict_set.has_conflict {
-                Some(conflict_set.snapshot())
-            } else {
-                None
-            }
-        });
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.delete_tag(envelope.payload.name)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
 
-        if let Some(old_snapshot) = old_snapshot {
-            self.conflict_updated_futures.push(tx);
-            self.reparse_conflict_markers_task = Some(cx.spawn(async move |this, cx| {
-                let (snapshot, changed_range) = cx
-                    .background_spawn(async move {
-                        let new_snapshot = ConflictSet::parse(&buffer);
-                        let changed_range = old_snapshot.compare(&new_snapshot, &buffer);
-                        (new_snapshot, changed_range)
-                    })
-                    .await;
-                this.update(cx, |this, cx| {
-                    if let Some(conflict_set) = &this.conflict_set {
-                        conflict_set
-                            .update(cx, |conflict_set, cx| {
-                                conflict_set.set_snapshot(snapshot, changed_range, cx);
-                            })
-                            .ok();
-                    }
-                    let futures = std::mem::take(&mut this.conflict_updated_futures);
-                    for tx in futures {
-                        tx.send(()).ok();
-                    }
-                })
-            }))
-        }
+    async fn handle_push_tag(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitPushTag>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::RemoteMessageResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        rx
-    }
+        let askpass_id = envelope.payload.askpass_id;
+        let askpass = make_remote_delegate(
+            this,
+            envelope.payload.project_id,
+            repository_id,
+            askpass_id,
+            &mut cx,
+        );
 
-    fn unstaged_diff(&self) -> Option<Entity<BufferDiff>> {
-        self.unstaged_diff.as_ref().and_then(|set| set.upgrade())
-    }
+        let name = envelope.payload.name.into();
+        let remote_name = envelope.payload.remote_name.into();
 
-    fn uncommitted_diff(&self) -> Option<Entity<BufferDiff>> {
-        self.uncommitted_diff.as_ref().and_then(|set| set.upgrade())
+        let remote_output = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.push_tag(name, remote_name, askpass)
+            })?
+            .await??;
+        Ok(proto::RemoteMessageResponse {
+            stdout: remote_output.stdout,
+            stderr: remote_output.stderr,
+        })
     }
 
-    fn handle_base_texts_updated(
-        &mut self,
-        buffer: text::BufferSnapshot,
-        message: proto::UpdateDiffBases,
-        cx: &mut Context<Self>,
-    ) {
-        use proto::update_diff_bases::Mode;
-
-        let Some(mode) = Mode::from_i32(message.mode) else {
-            return;
-        };
+    async fn handle_reset(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitReset>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
 
-        let diff_bases_change = match mode {
-            Mode::HeadOnly => DiffBasesChange::SetHead(message.committed_text),
-            Mode::IndexOnly => DiffBasesChange::SetIndex(message.staged_text),
-            Mode::IndexMatchesHead => DiffBasesChange::SetBoth(message.committed_text),
-            Mode::IndexAndHead => DiffBasesChange::SetEach {
-                index: message.staged_text,
-                head: message.committed_text,
-            },
+        let mode = match envelope.payload.mode() {
+            git_reset::ResetMode::Soft => ResetMode::Soft,
+            git_reset::ResetMode::Mixed => ResetMode::Mixed,
         };
 
-        self.diff_bases_changed(buffer, Some(diff_bases_change), cx);
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.reset(envelope.payload.commit, mode, cx)
+            })?
+            .await??;
+        Ok(proto::Ack {})
     }
 
-    pub fn wait_for_recalculation(&mut self) -> Option<impl Future<Output = ()> + use<>> {
-        if *self.recalculating_tx.borrow() {
+    async fn handle_stash_push(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashPush>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let scope = match envelope.payload.scope() {
+            git_stash_push::StashScope::All => StashScope::All,
+            git_stash_push::StashScope::Staged => StashScope::Staged,
+            git_stash_push::StashScope::Paths => StashScope::Paths(
+                envelope
+                    .payload
+                    .paths
+                    .iter()
+                    .map(|path| RepoPath::from_str(path))
+                    .collect(),
+            ),
+        };
+
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.stash_push(scope, envelope.payload.message, cx)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_list(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashListResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_list()
+            })?
+            .await??;
+        Ok(proto::GitStashListResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::StashEntry {
+                    index: entry.index as u64,
+                    sha: entry.sha.into(),
+                    message: entry.message.into(),
+                    commit_timestamp: entry.commit_timestamp,
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_stash_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadCommitDiffResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let stash_diff = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_diff(envelope.payload.index as usize)
+            })?
+            .await??;
+        Ok(proto::LoadCommitDiffResponse {
+            files: stash_diff
+                .files
+                .into_iter()
+                .map(|file| proto::CommitFile {
+                    path: file.path.to_string(),
+                    old_text: file.old_text,
+                    new_text: file.new_text,
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_stash_apply(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashApply>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_apply(envelope.payload.index as usize)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_pop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashPop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_pop(envelope.payload.index as usize)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_drop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashDrop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_drop(envelope.payload.index as usize)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_checkout_files(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCheckoutFiles>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let paths = envelope
+            .payload
+            .paths
+            .iter()
+            .map(|s| RepoPath::from_str(s))
+            .collect();
+
+        repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.checkout_files(&envelope.payload.commit, paths, cx)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_open_commit_message_buffer(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::OpenCommitMessageBuffer>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::OpenBufferResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let buffer = repository
+            .update(&mut cx, |repository, cx| {
+                repository.open_commit_buffer(None, this.read(cx).buffer_store.clone(), cx)
+            })?
+            .await?;
+
+        let buffer_id = buffer.read_with(&cx, |buffer, _| buffer.remote_id())?;
+        this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                buffer_store
+                    .create_buffer_for_peer(
+                        &buffer,
+                        envelope.original_sender_id.unwrap_or(envelope.sender_id),
+                        cx,
+                    )
+                    .detach_and_log_err(cx);
+            })
+        })?;
+
+        Ok(proto::OpenBufferResponse {
+            buffer_id: buffer_id.to_proto(),
+        })
+    }
+
+    async fn handle_askpass(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::AskPassRequest>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::AskPassResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let delegates = cx.update(|cx| repository.read(cx).askpass_delegates.clone())?;
+        let Some(mut askpass) = delegates.lock().remove(&envelope.payload.askpass_id) else {
+            debug_panic!("no askpass found");
+            anyhow::bail!("no askpass found");
+        };
+
+        let response = askpass.ask_password(envelope.payload.prompt).await?;
+
+        delegates
+            .lock()
+            .insert(envelope.payload.askpass_id, askpass);
+
+        Ok(proto::AskPassResponse { response })
+    }
+
+    async fn handle_check_for_pushed_commits(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::CheckForPushedCommits>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::CheckForPushedCommitsResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+
+        let branches = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.check_for_pushed_commits()
+            })?
+            .await??;
+        Ok(proto::CheckForPushedCommitsResponse {
+            pushed_to: branches
+                .into_iter()
+                .map(|commit| commit.to_string())
+                .collect(),
+        })
+    }
+
+    async fn handle_git_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitDiffResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let diff_type = match envelope.payload.diff_type() {
+            proto::git_diff::DiffType::HeadToIndex => DiffType::HeadToIndex,
+            proto::git_diff::DiffType::HeadToWorktree => DiffType::HeadToWorktree,
+        };
+
+        let mut diff = repository_handle
+            .update(&mut cx, |repository_handle, cx| {
+                repository_handle.diff(diff_type, cx)
+            })?
+            .await??;
+        const ONE_MB: usize = 1_000_000;
+        if diff.len() > ONE_MB {
+            diff = diff.chars().take(ONE_MB).collect()
+        }
+
+        Ok(proto::GitDiffResponse { diff })
+    }
+
+    async fn handle_open_unstaged_diff(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::OpenUnstagedDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::OpenUnstagedDiffResponse> {
+        let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        let diff = this
+            .update(&mut cx, |this, cx| {
+                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
+                Some(this.open_unstaged_diff(buffer, cx))
+            })?
+            .context("missing buffer")?
+            .await?;
+        this.update(&mut cx, |this, _| {
+            let shared_diffs = this
+                .shared_diffs
+                .entry(request.original_sender_id.unwrap_or(request.sender_id))
+                .or_default();
+            shared_diffs.entry(buffer_id).or_default().unstaged = Some(diff.clone());
+        })?;
+        let staged_text = diff.read_with(&cx, |diff, _| diff.base_text_string())?;
+        Ok(proto::OpenUnstagedDiffResponse { staged_text })
+    }
+
+    async fn handle_open_uncommitted_diff(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::OpenUncommittedDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::OpenUncommittedDiffResponse> {
+        let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        let diff = this
+            .update(&mut cx, |this, cx| {
+                let buffer = this.buffer_store.read(cx).get(buffer_id)?;
+                Some(this.open_uncommitted_diff(buffer, cx))
+            })?
+            .context("missing buffer")?
+            .await?;
+        this.update(&mut cx, |this, _| {
+            let shared_diffs = this
+                .shared_diffs
+                .entry(request.original_sender_id.unwrap_or(request.sender_id))
+                .or_default();
+            shared_diffs.entry(buffer_id).or_default().uncommitted = Some(diff.clone());
+        })?;
+        diff.read_with(&cx, |diff, cx| {
+            use proto::open_uncommitted_diff_response::Mode;
+
+            let unstaged_diff = diff.secondary_diff();
+            let index_snapshot = unstaged_diff.and_then(|diff| {
+                let diff = diff.read(cx);
+                diff.base_text_exists().then(|| diff.base_text())
+            });
+
+            let mode;
+            let staged_text;
+            let committed_text;
+            if diff.base_text_exists() {
+                let committed_snapshot = diff.base_text();
+                committed_text = Some(committed_snapshot.text());
+                if let Some(index_text) = index_snapshot {
+                    if index_text.remote_id() == committed_snapshot.remote_id() {
+                        mode = Mode::IndexMatchesHead;
+                        staged_text = None;
+                    } else {
+                        mode = Mode::IndexAndHead;
+                        staged_text = Some(index_text.text());
+                    }
+                } else {
+                    mode = Mode::IndexAndHead;
+                    staged_text = None;
+                }
+            } else {
+                mode = Mode::IndexAndHead;
+                committed_text = None;
+                staged_text = index_snapshot.as_ref().map(|buffer| buffer.text());
+            }
+
+            proto::OpenUncommittedDiffResponse {
+                committed_text,
+                staged_text,
+                mode: mode.into(),
+            }
+        })
+    }
+
+    async fn handle_update_diff_bases(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::UpdateDiffBases>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let buffer_id = BufferId::new(request.payload.buffer_id)?;
+        this.update(&mut cx, |this, cx| {
+            if let Some(diff_state) = this.diffs.get_mut(&buffer_id) {
+                if let Some(buffer) = this.buffer_store.read(cx).get(buffer_id) {
+                    let buffer = buffer.read(cx).text_snapshot();
+                    diff_state.update(cx, |diff_state, cx| {
+                        diff_state.handle_base_texts_updated(buffer, request.payload, cx);
+                    })
+                }
+            }
+        })
+    }
+
+    async fn handle_blame_buffer(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::BlameBuffer>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::BlameBufferResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let version = deserialize_version(&envelope.payload.version);
+        let buffer = this.read_with(&cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(version.clone())
+            })?
+            .await?;
+        let blame = this
+            .update(&mut cx, |this, cx| {
+                this.blame_buffer(&buffer, Some(version), cx)
+            })?
+            .await?;
+        Ok(serialize_blame_buffer_response(blame))
+    }
+
+    async fn handle_get_permalink_to_line(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetPermalinkToLine>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetPermalinkToLineResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        // let version = deserialize_version(&envelope.payload.version);
+        let selection = {
+            let proto_selection = envelope
+                .payload
+                .selection
+                .context("no selection to get permalink for defined")?;
+            proto_selection.start as u32..proto_selection.end as u32
+        };
+        let buffer = this.read_with(&cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let permalink = this
+            .update(&mut cx, |this, cx| {
+                this.get_permalink_to_line(&buffer, selection, cx)
+            })?
+            .await?;
+        Ok(proto::GetPermalinkToLineResponse {
+            permalink: permalink.to_string(),
+        })
+    }
+
+    fn repository_for_request(
+        this: &Entity<Self>,
+        id: RepositoryId,
+        cx: &mut AsyncApp,
+    ) -> Result<Entity<Repository>> {
+        this.read_with(cx, |this, _| {
+            this.repositories
+                .get(&id)
+                .context("missing repository handle")
+                .cloned()
+        })?
+    }
+
+    pub fn repo_snapshots(&self, cx: &App) -> HashMap<RepositoryId, RepositorySnapshot> {
+        self.repositories
+            .iter()
+            .map(|(id, repo)| (*id, repo.read(cx).snapshot.clone()))
+            .collect()
+    }
+}
+
+impl BufferGitState {
+    fn new(_git_store: WeakEntity<GitStore>) -> Self {
+        Self {
+            unstaged_diff: Default::default(),
+            uncommitted_diff: Default::default(),
+            recalculate_diff_task: Default::default(),
+            language: Default::default(),
+            language_registry: Default::default(),
+            recalculating_tx: postage::watch::channel_with(false).0,
+            hunk_staging_operation_count: 0,
+            hunk_staging_operation_count_as_of_write: 0,
+            head_text: Default::default(),
+            index_text: Default::default(),
+            head_changed: Default::default(),
+            index_changed: Default::default(),
+            language_changed: Default::default(),
+            conflict_updated_futures: Default::default(),
+            conflict_set: Default::default(),
+            reparse_conflict_markers_task: Default::default(),
+        }
+    }
+
+    fn buffer_language_changed(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        self.language = buffer.read(cx).language().cloned();
+        self.language_changed = true;
+        let _ = self.recalculate_diffs(buffer.read(cx).text_snapshot(), cx);
+    }
+
+    fn reparse_conflict_markers(
+        &mut self,
+        buffer: text::BufferSnapshot,
+        cx: &mut Context<Self>,
+    ) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+
+        let Some(conflict_set) = self
+            .conflict_set
+            .as_ref()
+            .and_then(|conflict_set| conflict_set.upgrade())
+        else {
+            return rx;
+        };
+
+        let old_snapshot = conflict_set.read_with(cx, |conflict_set, _| {
+            if conflict_set.has_conflict {
+                Some(conflict_set.snapshot())
+            } else {
+                None
+            }
+        });
+
+        if let Some(old_snapshot) = old_snapshot {
+            self.conflict_updated_futures.push(tx);
+            self.reparse_conflict_markers_task = Some(cx.spawn(async move |this, cx| {
+                let (snapshot, changed_range) = cx
+                    .background_spawn(async move {
+                        let new_snapshot = ConflictSet::parse(&buffer);
+                        let changed_range = old_snapshot.compare(&new_snapshot, &buffer);
+                        (new_snapshot, changed_range)
+                    })
+                    .await;
+                this.update(cx, |this, cx| {
+                    if let Some(conflict_set) = &this.conflict_set {
+                        conflict_set
+                            .update(cx, |conflict_set, cx| {
+                                conflict_set.set_snapshot(snapshot, changed_range, cx);
+                            })
+                            .ok();
+                    }
+                    let futures = std::mem::take(&mut this.conflict_updated_futures);
+                    for tx in futures {
+                        tx.send(()).ok();
+                    }
+                })
+            }))
+        }
+
+        rx
+    }
+
+    fn unstaged_diff(&self) -> Option<Entity<BufferDiff>> {
+        self.unstaged_diff.as_ref().and_then(|set| set.upgrade())
+    }
+
+    fn uncommitted_diff(&self) -> Option<Entity<BufferDiff>> {
+        self.uncommitted_diff.as_ref().and_then(|set| set.upgrade())
+    }
+
+    fn handle_base_texts_updated(
+        &mut self,
+        buffer: text::BufferSnapshot,
+        message: proto::UpdateDiffBases,
+        cx: &mut Context<Self>,
+    ) {
+        use proto::update_diff_bases::Mode;
+
+        let Some(mode) = Mode::from_i32(message.mode) else {
+            return;
+        };
+
+        let diff_bases_change = match mode {
+            Mode::HeadOnly => DiffBasesChange::SetHead(message.committed_text),
+            Mode::IndexOnly => DiffBasesChange::SetIndex(message.staged_text),
+            Mode::IndexMatchesHead => DiffBasesChange::SetBoth(message.committed_text),
+            Mode::IndexAndHead => DiffBasesChange::SetEach {
+                index: message.staged_text,
+                head: message.committed_text,
+            },
+        };
+
+        self.diff_bases_changed(buffer, Some(diff_bases_change), cx);
+    }
+
+    pub fn wait_for_recalculation(&mut self) -> Option<impl Future<Output = ()> + use<>> {
+        if *self.recalculating_tx.borrow() {
             let mut rx = self.recalculating_tx.subscribe();
             return Some(async move {
                 loop {
@@ -2534,651 +3132,1341 @@ fn make_remote_delegate(
     })
 }
 
-impl RepositoryId {
-    pub fn to_proto(self) -> u64 {
-        self.0
+impl RepositoryId {
+    pub fn to_proto(self) -> u64 {
+        self.0
+    }
+
+    pub fn from_proto(id: u64) -> Self {
+        RepositoryId(id)
+    }
+}
+
+impl RepositorySnapshot {
+    fn empty(id: RepositoryId, work_directory_abs_path: Arc<Path>) -> Self {
+        Self {
+            id,
+            statuses_by_path: Default::default(),
+            work_directory_abs_path,
+            branch: None,
+            head_commit: None,
+            scan_id: 0,
+            merge: Default::default(),
+        }
+    }
+
+    fn initial_update(&self, project_id: u64) -> proto::UpdateRepository {
+        proto::UpdateRepository {
+            branch_summary: self.branch.as_ref().map(branch_to_proto),
+            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
+            updated_statuses: self
+                .statuses_by_path
+                .iter()
+                .map(|entry| entry.to_proto())
+                .collect(),
+            removed_statuses: Default::default(),
+            current_merge_conflicts: self
+                .merge
+                .conflicted_paths
+                .iter()
+                .map(|repo_path| repo_path.to_proto())
+                .collect(),
+            project_id,
+            id: self.id.to_proto(),
+            abs_path: self.work_directory_abs_path.to_proto(),
+            entry_ids: vec![self.id.to_proto()],
+            scan_id: self.scan_id,
+            is_last_update: true,
+        }
+    }
+
+    fn build_update(&self, old: &Self, project_id: u64) -> proto::UpdateRepository {
+        let mut updated_statuses: Vec<proto::StatusEntry> = Vec::new();
+        let mut removed_statuses: Vec<String> = Vec::new();
+
+        let mut new_statuses = self.statuses_by_path.iter().peekable();
+        let mut old_statuses = old.statuses_by_path.iter().peekable();
+
+        let mut current_new_entry = new_statuses.next();
+        let mut current_old_entry = old_statuses.next();
+        loop {
+            match (current_new_entry, current_old_entry) {
+                (Some(new_entry), Some(old_entry)) => {
+                    match new_entry.repo_path.cmp(&old_entry.repo_path) {
+                        Ordering::Less => {
+                            updated_statuses.push(new_entry.to_proto());
+                            current_new_entry = new_statuses.next();
+                        }
+                        Ordering::Equal => {
+                            if new_entry.status != old_entry.status {
+                                updated_statuses.push(new_entry.to_proto());
+                            }
+                            current_old_entry = old_statuses.next();
+                            current_new_entry = new_statuses.next();
+                        }
+                        Ordering::Greater => {
+                            removed_statuses.push(old_entry.repo_path.as_ref().to_proto());
+                            current_old_entry = old_statuses.next();
+                        }
+                    }
+                }
+                (None, Some(old_entry)) => {
+                    removed_statuses.push(old_entry.repo_path.as_ref().to_proto());
+                    current_old_entry = old_statuses.next();
+                }
+                (Some(new_entry), None) => {
+                    updated_statuses.push(new_entry.to_proto());
+                    current_new_entry = new_statuses.next();
+                }
+                (None, None) => break,
+            }
+        }
+
+        proto::UpdateRepository {
+            branch_summary: self.branch.as_ref().map(branch_to_proto),
+            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
+            updated_statuses,
+            removed_statuses,
+            current_merge_conflicts: self
+                .merge
+                .conflicted_paths
+                .iter()
+                .map(|path| path.as_ref().to_proto())
+                .collect(),
+            project_id,
+            id: self.id.to_proto(),
+            abs_path: self.work_directory_abs_path.to_proto(),
+            entry_ids: vec![],
+            scan_id: self.scan_id,
+            is_last_update: true,
+        }
+    }
+
+    pub fn status(&self) -> impl Iterator<Item = StatusEntry> + '_ {
+        self.statuses_by_path.iter().cloned()
+    }
+
+    pub fn status_summary(&self) -> GitSummary {
+        self.statuses_by_path.summary().item_summary
+    }
+
+    pub fn status_for_path(&self, path: &RepoPath) -> Option<StatusEntry> {
+        self.statuses_by_path
+            .get(&PathKey(path.0.clone()), &())
+            .cloned()
+    }
+
+    pub fn abs_path_to_repo_path(&self, abs_path: &Path) -> Option<RepoPath> {
+        abs_path
+            .strip_prefix(&self.work_directory_abs_path)
+            .map(RepoPath::from)
+            .ok()
+    }
+
+    pub fn had_conflict_on_last_merge_head_change(&self, repo_path: &RepoPath) -> bool {
+        self.merge.conflicted_paths.contains(&repo_path)
+    }
+
+    pub fn has_conflict(&self, repo_path: &RepoPath) -> bool {
+        let had_conflict_on_last_merge_head_change =
+            self.merge.conflicted_paths.contains(&repo_path);
+        let has_conflict_currently = self
+            .status_for_path(&repo_path)
+            .map_or(false, |entry| entry.status.is_conflicted());
+        had_conflict_on_last_merge_head_change || has_conflict_currently
+    }
+
+    /// This is the name that will be displayed in the repository selector for this repository.
+    pub fn display_name(&self) -> SharedString {
+        self.work_directory_abs_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+            .into()
+    }
+}
+
+impl MergeDetails {
+    async fn load(
+        backend: &Arc<dyn GitRepository>,
+        status: &SumTree<StatusEntry>,
+        prev_snapshot: &RepositorySnapshot,
+    ) -> Result<(MergeDetails, bool)> {
+        log::debug!("load merge details");
+        let message = backend.merge_message().await;
+        let heads = backend
+            .revparse_batch(vec![
+                "MERGE_HEAD".into(),
+                "CHERRY_PICK_HEAD".into(),
+                "REBASE_HEAD".into(),
+                "REVERT_HEAD".into(),
+                "APPLY_HEAD".into(),
+            ])
+            .await
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|opt| opt.map(SharedString::from))
+            .collect::<Vec<_>>();
+        let merge_heads_changed = heads != prev_snapshot.merge.heads;
+        let conflicted_paths = if merge_heads_changed {
+            let current_conflicted_paths = TreeSet::from_ordered_entries(
+                status
+                    .iter()
+                    .filter(|entry| entry.status.is_conflicted())
+                    .map(|entry| entry.repo_path.clone()),
+            );
+
+            // It can happen that we run a scan while a lengthy merge is in progress
+            // that will eventually result in conflicts, but before those conflicts
+            // are reported by `git status`. Since for the moment we only care about
+            // the merge heads state for the purposes of tracking conflicts, don't update
+            // this state until we see some conflicts.
+            if heads.iter().any(Option::is_some)
+                && !prev_snapshot.merge.heads.iter().any(Option::is_some)
+                && current_conflicted_paths.is_empty()
+            {
+                log::debug!("not updating merge heads because no conflicts found");
+                return Ok((
+                    MergeDetails {
+                        message: message.map(SharedString::from),
+                        ..prev_snapshot.merge.clone()
+                    },
+                    false,
+                ));
+            }
+
+            current_conflicted_paths
+        } else {
+            prev_snapshot.merge.conflicted_paths.clone()
+        };
+        let details = MergeDetails {
+            conflicted_paths,
+            message: message.map(SharedString::from),
+            heads,
+        };
+        Ok((details, merge_heads_changed))
+    }
+}
+
+impl Repository {
+    pub fn snapshot(&self) -> RepositorySnapshot {
+        self.snapshot.clone()
     }
 
-    pub fn from_proto(id: u64) -> Self {
-        RepositoryId(id)
+    fn local(
+        id: RepositoryId,
+        work_directory_abs_path: Arc<Path>,
+        dot_git_abs_path: Arc<Path>,
+        repository_dir_abs_path: Arc<Path>,
+        common_dir_abs_path: Arc<Path>,
+        project_environment: WeakEntity<ProjectEnvironment>,
+        fs: Arc<dyn Fs>,
+        git_store: WeakEntity<GitStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot = RepositorySnapshot::empty(id, work_directory_abs_path.clone());
+        Repository {
+            this: cx.weak_entity(),
+            git_store,
+            snapshot,
+            commit_message_buffer: None,
+            askpass_delegates: Default::default(),
+            paths_needing_status_update: Default::default(),
+            latest_askpass_id: 0,
+            job_sender: Repository::spawn_local_git_worker(
+                work_directory_abs_path,
+                dot_git_abs_path,
+                repository_dir_abs_path,
+                common_dir_abs_path,
+                project_environment,
+                fs,
+                cx,
+            ),
+            job_id: 0,
+            active_jobs: Default::default(),
+        }
     }
-}
 
-impl RepositorySnapshot {
-    fn empty(id: RepositoryId, work_directory_abs_path: Arc<Path>) -> Self {
+    fn remote(
+        id: RepositoryId,
+        work_directory_abs_path: Arc<Path>,
+        project_id: ProjectId,
+        client: AnyProtoClient,
+        git_store: WeakEntity<GitStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot = RepositorySnapshot::empty(id, work_directory_abs_path);
         Self {
-            id,
-            statuses_by_path: Default::default(),
-            work_directory_abs_path,
-            branch: None,
-            head_commit: None,
-            scan_id: 0,
-            merge: Default::default(),
+            this: cx.weak_entity(),
+            snapshot,
+            commit_message_buffer: None,
+            git_store,
+            paths_needing_status_update: Default::default(),
+            job_sender: Self::spawn_remote_git_worker(project_id, client, cx),
+            askpass_delegates: Default::default(),
+            latest_askpass_id: 0,
+            active_jobs: Default::default(),
+            job_id: 0,
         }
     }
 
-    fn initial_update(&self, project_id: u64) -> proto::UpdateRepository {
-        proto::UpdateRepository {
-            branch_summary: self.branch.as_ref().map(branch_to_proto),
-            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
-            updated_statuses: self
-                .statuses_by_path
-                .iter()
-                .map(|entry| entry.to_proto())
-                .collect(),
-            removed_statuses: Default::default(),
-            current_merge_conflicts: self
-                .merge
-                .conflicted_paths
-                .iter()
-                .map(|repo_path| repo_path.to_proto())
-                .collect(),
-            project_id,
-            id: self.id.to_proto(),
-            abs_path: self.work_directory_abs_path.to_proto(),
-            entry_ids: vec![self.id.to_proto()],
-            scan_id: self.scan_id,
-            is_last_update: true,
-        }
+    pub fn git_store(&self) -> Option<Entity<GitStore>> {
+        self.git_store.upgrade()
     }
 
-    fn build_update(&self, old: &Self, project_id: u64) -> proto::UpdateRepository {
-        let mut updated_statuses: Vec<proto::StatusEntry> = Vec::new();
-        let mut removed_statuses: Vec<String> = Vec::new();
+    fn reload_buffer_diff_bases(&mut self, cx: &mut Context<Self>) {
+        let this = cx.weak_entity();
+        let git_store = self.git_store.clone();
+        let _ = self.send_keyed_job(
+            Some(GitJobKey::ReloadBufferDiffBases),
+            None,
+            |state, mut cx| async move {
+                let RepositoryState::Local { backend, .. } = state else {
+                    log::error!("tried to recompute diffs for a non-local repository");
+                    return Ok(());
+                };
 
-        let mut new_statuses = self.statuses_by_path.iter().peekable();
-        let mut old_statuses = old.statuses_by_path.iter().peekable();
+                let Some(this) = this.upgrade() else {
+                    return Ok(());
+                };
 
-        let mut current_new_entry = new_statuses.next();
-        let mut current_old_entry = old_statuses.next();
-        loop {
-            match (current_new_entry, current_old_entry) {
-                (Some(new_entry), Some(old_entry)) => {
-                    match new_entry.repo_path.cmp(&old_entry.repo_path) {
-                        Ordering::Less => {
-                            updated_statuses.push(new_entry.to_proto());
-                            current_new_entry = new_statuses.next();
+                let repo_diff_state_updates = this.update(&mut cx, |this, cx| {
+                    git_store.update(cx, |git_store, cx| {
+                        git_store
+                            .diffs
+                            .iter()
+                            .filter_map(|(buffer_id, diff_state)| {
+                                let buffer_store = git_store.buffer_store.read(cx);
+                                let buffer = buffer_store.get(*buffer_id)?;
+                                let file = File::from_dyn(buffer.read(cx).file())?;
+                                let abs_path =
+                                    file.worktree.read(cx).absolutize(&file.path).ok()?;
+                                let repo_path = this.abs_path_to_repo_path(&abs_path)?;
+                                log::debug!(
+                                    "start reload diff bases for repo path {}",
+                                    repo_path.0.display()
+                                );
+                                diff_state.update(cx, |diff_state, _| {
+                                    let has_unstaged_diff = diff_state
+                                        .unstaged_diff
+                                        .as_ref()
+                                        .is_some_and(|diff| diff.is_upgradable());
+                                    let has_uncommitted_diff = diff_state
+                                        .uncommitted_diff
+                                        .as_ref()
+                                        .is_some_and(|set| set.is_upgradable());
+
+                                    Some((
+                                        buffer,
+                                        repo_path,
+                                        has_unstaged_diff.then(|| diff_state.index_text.clone()),
+                                        has_uncommitted_diff.then(|| diff_state.head_text.clone()),
+                                    ))
+                                })
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })??;
+
+                let buffer_diff_base_changes = cx
+                    .background_spawn(async move {
+                        let mut changes = Vec::new();
+                        for (buffer, repo_path, current_index_text, current_head_text) in
+                            &repo_diff_state_updates
+                        {
+                            let index_text = if current_index_text.is_some() {
+                                backend.load_index_text(repo_path.clone()).await
+                            } else {
+                                None
+                            };
+                            let head_text = if current_head_text.is_some() {
+                                backend.load_committed_text(repo_path.clone()).await
+                            } else {
+                                None
+                            };
+
+                            let change =
+                                match (current_index_text.as_ref(), current_head_text.as_ref()) {
+                                    (Some(current_index), Some(current_head)) => {
+                                        let index_changed =
+                                            index_text.as_ref() != current_index.as_deref();
+                                        let head_changed =
+                                            head_text.as_ref() != current_head.as_deref();
+                                        if index_changed && head_changed {
+                                            if index_text == head_text {
+                                                Some(DiffBasesChange::SetBoth(head_text))
+                                            } else {
+                                                Some(DiffBasesChange::SetEach {
+                                                    index: index_text,
+                                                    head: head_text,
+                                                })
+                                            }
+                                        } else if index_changed {
+                                            Some(DiffBasesChange::SetIndex(index_text))
+                                        } else if head_changed {
+                                            Some(DiffBasesChange::SetHead(head_text))
+                                        } else {
+                                            None
+                                        }
+                                    }
+                                    (Some(current_index), None) => {
+                                        let index_changed =
+                                            index_text.as_ref() != current_index.as_deref();
+                                        index_changed
+                                            .then_some(DiffBasesChange::SetIndex(index_text))
+                                    }
+                                    (None, Some(current_head)) => {
+                                        let head_changed =
+                                            head_text.as_ref() != current_head.as_deref();
+                                        head_changed.then_some(DiffBasesChange::SetHead(head_text))
+                                    }
+                                    (None, None) => None,
+                                };
+
+                            changes.push((buffer.clone(), change))
                         }
-                        Ordering::Equal => {
-                            if new_entry.status != old_entry.status {
-                                updated_statuses.push(new_entry.to_proto());
+                        changes
+                    })
+                    .await;
+
+                git_store.update(&mut cx, |git_store, cx| {
+                    for (buffer, diff_bases_change) in buffer_diff_base_changes {
+                        let buffer_snapshot = buffer.read(cx).text_snapshot();
+                        let buffer_id = buffer_snapshot.remote_id();
+                        let Some(diff_state) = git_store.diffs.get(&buffer_id) else {
+                            continue;
+                        };
+
+                        let downstream_client = git_store.downstream_client();
+                        diff_state.update(cx, |diff_state, cx| {
+                            use proto::update_diff_bases::Mode;
+
+                            if let Some((diff_bases_change, (client, project_id))) =
+                                diff_bases_change.clone().zip(downstream_client)
+                            {
+                                let (staged_text, committed_text, mode) = match diff_bases_change {
+                                    DiffBasesChange::SetIndex(index) => {
+                                        (index, None, Mode::IndexOnly)
+                                    }
+                                    DiffBasesChange::SetHead(head) => (None, head, Mode::HeadOnly),
+                                    DiffBasesChange::SetEach { index, head } => {
+                                        (index, head, Mode::IndexAndHead)
+                                    }
+                                    DiffBasesChange::SetBoth(text) => {
+                                        (None, text, Mode::IndexMatchesHead)
+                                    }
+                                };
+                                client
+                                    .send(proto::UpdateDiffBases {
+                                        project_id: project_id.to_proto(),
+                                        buffer_id: buffer_id.to_proto(),
+                                        staged_text,
+                                        committed_text,
+                                        mode: mode as i32,
+                                    })
+                                    .log_err();
                             }
-                            current_old_entry = old_statuses.next();
-                            current_new_entry = new_statuses.next();
-                        }
-                        Ordering::Greater => {
-                            removed_statuses.push(old_entry.repo_path.as_ref().to_proto());
-                            current_old_entry = old_statuses.next();
-                        }
-                    }
-                }
-                (None, Some(old_entry)) => {
-                    removed_statuses.push(old_entry.repo_path.as_ref().to_proto());
-                    current_old_entry = old_statuses.next();
-                }
-                (Some(new_entry), None) => {
-                    updated_statuses.push(new_entry.to_proto());
-                    current_new_entry = new_statuses.next();
-                }
-                (None, None) => break,
-            }
-        }
 
-        proto::UpdateRepository {
-            branch_summary: self.branch.as_ref().map(branch_to_proto),
-            head_commit_details: self.head_commit.as_ref().map(commit_details_to_proto),
-            updated_statuses,
-            removed_statuses,
-            current_merge_conflicts: self
-                .merge
-                .conflicted_paths
-                .iter()
-                .map(|path| path.as_ref().to_proto())
-                .collect(),
-            project_id,
-            id: self.id.to_proto(),
-            abs_path: self.work_directory_abs_path.to_proto(),
-            entry_ids: vec![],
-            scan_id: self.scan_id,
-            is_last_update: true,
-        }
+                            diff_state.diff_bases_changed(buffer_snapshot, diff_bases_change, cx);
+                        });
+                    }
+                })
+            },
+        );
     }
 
-    pub fn status(&self) -> impl Iterator<Item = StatusEntry> + '_ {
-        self.statuses_by_path.iter().cloned()
+    pub fn send_job<F, Fut, R>(
+        &mut self,
+        status: Option<SharedString>,
+        job: F,
+    ) -> oneshot::Receiver<R>
+    where
+        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        self.send_keyed_job(None, status, job)
     }
 
-    pub fn status_summary(&self) -> GitSummary {
-        self.statuses_by_path.summary().item_summary
+    fn send_keyed_job<F, Fut, R>(
+        &mut self,
+        key: Option<GitJobKey>,
+        status: Option<SharedString>,
+        job: F,
+    ) -> oneshot::Receiver<R>
+    where
+        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        let (result_tx, result_rx) = futures::channel::oneshot::channel();
+        let job_id = post_inc(&mut self.job_id);
+        let this = self.this.clone();
+        self.job_sender
+            .unbounded_send(GitJob {
+                key,
+                job: Box::new(move |state, cx: &mut AsyncApp| {
+                    let job = job(state, cx.clone());
+                    cx.spawn(async move |cx| {
+                        if let Some(s) = status.clone() {
+                            this.update(cx, |this, cx| {
+                                this.active_jobs.insert(
+                                    job_id,
+                                    JobInfo {
+                                        start: Instant::now(),
+                                        message: s.clone(),
+                                    },
+                                );
+
+                                cx.notify();
+                            })
+                            .ok();
+                        }
+                        let result = job.await;
+
+                        this.update(cx, |this, cx| {
+                            this.active_jobs.remove(&job_id);
+                            cx.notify();
+                        })
+                        .ok();
+
+                        result_tx.send(result).ok();
+                    })
+                }),
+            })
+            .ok();
+        result_rx
     }
 
-    pub fn status_for_path(&self, path: &RepoPath) -> Option<StatusEntry> {
-        self.statuses_by_path
-            .get(&PathKey(path.0.clone()), &())
-            .cloned()
+    pub fn set_as_active_repository(&self, cx: &mut Context<Self>) {
+        let Some(git_store) = self.git_store.upgrade() else {
+            return;
+        };
+        let entity = cx.entity();
+        git_store.update(cx, |git_store, cx| {
+            let Some((&id, _)) = git_store
+                .repositories
+                .iter()
+                .find(|(_, handle)| *handle == &entity)
+            else {
+                return;
+            };
+            git_store.active_repo_id = Some(id);
+            cx.emit(GitStoreEvent::ActiveRepositoryChanged(Some(id)));
+        });
     }
 
-    pub fn abs_path_to_repo_path(&self, abs_path: &Path) -> Option<RepoPath> {
-        abs_path
-            .strip_prefix(&self.work_directory_abs_path)
-            .map(RepoPath::from)
-            .ok()
+    pub fn cached_status(&self) -> impl '_ + Iterator<Item = StatusEntry> {
+        self.snapshot.status()
     }
 
-    pub fn had_conflict_on_last_merge_head_change(&self, repo_path: &RepoPath) -> bool {
-        self.merge.conflicted_paths.contains(&repo_path)
+    pub fn repo_path_to_project_path(&self, path: &RepoPath, cx: &App) -> Option<ProjectPath> {
+        let git_store = self.git_store.upgrade()?;
+        let worktree_store = git_store.read(cx).worktree_store.read(cx);
+        let abs_path = self.snapshot.work_directory_abs_path.join(&path.0);
+        let (worktree, relative_path) = worktree_store.find_worktree(abs_path, cx)?;
+        Some(ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: relative_path.into(),
+        })
     }
 
-    pub fn has_conflict(&self, repo_path: &RepoPath) -> bool {
-        let had_conflict_on_last_merge_head_change =
-            self.merge.conflicted_paths.contains(&repo_path);
-        let has_conflict_currently = self
-            .status_for_path(&repo_path)
-            .map_or(false, |entry| entry.status.is_conflicted());
-        had_conflict_on_last_merge_head_change || has_conflict_currently
+    pub fn project_path_to_repo_path(&self, path: &ProjectPath, cx: &App) -> Option<RepoPath> {
+        let git_store = self.git_store.upgrade()?;
+        let worktree_store = git_store.read(cx).worktree_store.read(cx);
+        let abs_path = worktree_store.absolutize(path, cx)?;
+        self.snapshot.abs_path_to_repo_path(&abs_path)
     }
 
-    /// This is the name that will be displayed in the repository selector for this repository.
-    pub fn display_name(&self) -> SharedString {
-        self.work_directory_abs_path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string()
-            .into()
+    pub fn contains_sub_repo(&self, other: &Entity<Self>, cx: &App) -> bool {
+        other
+            .read(cx)
+            .snapshot
+            .work_directory_abs_path
+            .starts_with(&self.snapshot.work_directory_abs_path)
     }
-}
 
-impl MergeDetails {
-    async fn load(
-        backend: &Arc<dyn GitRepository>,
-        status: &SumTree<StatusEntry>,
-        prev_snapshot: &RepositorySnapshot,
-    ) -> Result<(MergeDetails, bool)> {
-        log::debug!("load merge details");
-        let message = backend.merge_message().await;
-        let heads = backend
-            .revparse_batch(vec![
-                "MERGE_HEAD".into(),
-                "CHERRY_PICK_HEAD".into(),
-                "REBASE_HEAD".into(),
-                "REVERT_HEAD".into(),
-                "APPLY_HEAD".into(),
-            ])
-            .await
-            .log_err()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|opt| opt.map(SharedString::from))
-            .collect::<Vec<_>>();
-        let merge_heads_changed = heads != prev_snapshot.merge.heads;
-        let conflicted_paths = if merge_heads_changed {
-            let current_conflicted_paths = TreeSet::from_ordered_entries(
-                status
-                    .iter()
-                    .filter(|entry| entry.status.is_conflicted())
-                    .map(|entry| entry.repo_path.clone()),
-            );
+    pub fn open_commit_buffer(
+        &mut self,
+        languages: Option<Arc<LanguageRegistry>>,
+        buffer_store: Entity<BufferStore>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        let id = self.id;
+        if let Some(buffer) = self.commit_message_buffer.clone() {
+            return Task::ready(Ok(buffer));
+        }
+        let this = cx.weak_entity();
 
-            // It can happen that we run a scan while a lengthy merge is in progress
-            // that will eventually result in conflicts, but before those conflicts
-            // are reported by `git status`. Since for the moment we only care about
-            // the merge heads state for the purposes of tracking conflicts, don't update
-            // this state until we see some conflicts.
-            if heads.iter().any(Option::is_some)
-                && !prev_snapshot.merge.heads.iter().any(Option::is_some)
-                && current_conflicted_paths.is_empty()
-            {
-                log::debug!("not updating merge heads because no conflicts found");
-                return Ok((
-                    MergeDetails {
-                        message: message.map(SharedString::from),
-                        ..prev_snapshot.merge.clone()
-                    },
-                    false,
-                ));
+        let rx = self.send_job(None, move |state, mut cx| async move {
+            let Some(this) = this.upgrade() else {
+                bail!("git store was dropped");
+            };
+            match state {
+                RepositoryState::Local { .. } => {
+                    this.update(&mut cx, |_, cx| {
+                        Self::open_local_commit_buffer(languages, buffer_store, cx)
+                    })?
+                    .await
+                }
+                RepositoryState::Remote { project_id, client } => {
+                    let request = client.request(proto::OpenCommitMessageBuffer {
+                        project_id: project_id.0,
+                        repository_id: id.to_proto(),
+                    });
+                    let response = request.await.context("requesting to open commit buffer")?;
+                    let buffer_id = BufferId::new(response.buffer_id)?;
+                    let buffer = buffer_store
+                        .update(&mut cx, |buffer_store, cx| {
+                            buffer_store.wait_for_remote_buffer(buffer_id, cx)
+                        })?
+                        .await?;
+                    if let Some(language_registry) = languages {
+                        let git_commit_language =
+                            language_registry.language_for_name("Git Commit").await?;
+                        buffer.update(&mut cx, |buffer, cx| {
+                            buffer.set_language(Some(git_commit_language), cx);
+                        })?;
+                    }
+                    this.update(&mut cx, |this, _| {
+                        this.commit_message_buffer = Some(buffer.clone());
+                    })?;
+                    Ok(buffer)
+                }
             }
+        });
 
-            current_conflicted_paths
-        } else {
-            prev_snapshot.merge.conflicted_paths.clone()
-        };
-        let details = MergeDetails {
-            conflicted_paths,
-            message: message.map(SharedString::from),
-            heads,
-        };
-        Ok((details, merge_heads_changed))
-    }
-}
-
-impl Repository {
-    pub fn snapshot(&self) -> RepositorySnapshot {
-        self.snapshot.clone()
+        cx.spawn(|_, _: &mut AsyncApp| async move { rx.await? })
     }
 
-    fn local(
-        id: RepositoryId,
-        work_directory_abs_path: Arc<Path>,
-        dot_git_abs_path: Arc<Path>,
-        repository_dir_abs_path: Arc<Path>,
-        common_dir_abs_path: Arc<Path>,
-        project_environment: WeakEntity<ProjectEnvironment>,
-        fs: Arc<dyn Fs>,
-        git_store: WeakEntity<GitStore>,
+    fn open_local_commit_buffer(
+        language_registry: Option<Arc<LanguageRegistry>>,
+        buffer_store: Entity<BufferStore>,
         cx: &mut Context<Self>,
-    ) -> Self {
-        let snapshot = RepositorySnapshot::empty(id, work_directory_abs_path.clone());
-        Repository {
-            this: cx.weak_entity(),
-            git_store,
-            snapshot,
-            commit_message_buffer: None,
-            askpass_delegates: Default::default(),
-            paths_needing_status_update: Default::default(),
-            latest_askpass_id: 0,
-            job_sender: Repository::spawn_local_git_worker(
-                work_directory_abs_path,
-                dot_git_abs_path,
-                repository_dir_abs_path,
-                common_dir_abs_path,
-                project_environment,
-                fs,
-                cx,
-            ),
-            job_id: 0,
-            active_jobs: Default::default(),
-        }
-    }
+    ) -> Task<Result<Entity<Buffer>>> {
+        cx.spawn(async move |repository, cx| {
+            let buffer = buffer_store
+                .update(cx, |buffer_store, cx| buffer_store.create_buffer(cx))?
+                .await?;
 
-    fn remote(
-        id: RepositoryId,
-        work_directory_abs_path: Arc<Path>,
-        project_id: ProjectId,
-        client: AnyProtoClient,
-        git_store: WeakEntity<GitStore>,
-        cx: &mut Context<Self>,
-    ) -> Self {
-        let snapshot = RepositorySnapshot::empty(id, work_directory_abs_path);
-        Self {
-            this: cx.weak_entity(),
-            snapshot,
-            commit_message_buffer: None,
-            git_store,
-            paths_needing_status_update: Default::default(),
-            job_sender: Self::spawn_remote_git_worker(project_id, client, cx),
-            askpass_delegates: Default::default(),
-            latest_askpass_id: 0,
-            active_jobs: Default::default(),
-            job_id: 0,
-        }
+            if let Some(language_registry) = language_registry {
+                let git_commit_language = language_registry.language_for_name("Git Commit").await?;
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_language(Some(git_commit_language), cx);
+                })?;
+            }
+
+            repository.update(cx, |repository, _| {
+                repository.commit_message_buffer = Some(buffer.clone());
+            })?;
+            Ok(buffer)
+        })
     }
 
-    pub fn git_store(&self) -> Option<Entity<GitStore>> {
-        self.git_store.upgrade()
+    pub fn checkout_files(
+        &mut self,
+        commit: &str,
+        paths: Vec<RepoPath>,
+        _cx: &mut App,
+    ) -> oneshot::Receiver<Result<()>> {
+        let commit = commit.to_string();
+        let id = self.id;
+
+        self.send_job(
+            Some(format!("git checkout {}", commit).into()),
+            move |git_repo, _| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => {
+                        backend
+                            .checkout_files(commit, paths, environment.clone())
+                            .await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCheckoutFiles {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commit,
+                                paths: paths
+                                    .into_iter()
+                                    .map(|p| p.to_string_lossy().to_string())
+                                    .collect(),
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    fn reload_buffer_diff_bases(&mut self, cx: &mut Context<Self>) {
-        let this = cx.weak_entity();
-        let git_store = self.git_store.clone();
-        let _ = self.send_keyed_job(
-            Some(GitJobKey::ReloadBufferDiffBases),
-            None,
-            |state, mut cx| async move {
-                let RepositoryState::Local { backend, .. } = state else {
-                    log::error!("tried to recompute diffs for a non-local repository");
-                    return Ok(());
-                };
+    pub fn reset(
+        &mut self,
+        commit: String,
+        reset_mode: ResetMode,
+        _cx: &mut App,
+    ) -> oneshot::Receiver<Result<()>> {
+        let commit = commit.to_string();
+        let id = self.id;
 
-                let Some(this) = this.upgrade() else {
-                    return Ok(());
-                };
+        self.send_job(None, move |git_repo, _| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.reset(commit, reset_mode, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    client
+                        .request(proto::GitReset {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commit,
+                            mode: match reset_mode {
+                                ResetMode::Soft => git_reset::ResetMode::Soft.into(),
+                                ResetMode::Mixed => git_reset::ResetMode::Mixed.into(),
+                            },
+                        })
+                        .await?;
 
-                let repo_diff_state_updates = this.update(&mut cx, |this, cx| {
-                    git_store.update(cx, |git_store, cx| {
-                        git_store
-                            .diffs
-                            .iter()
-                            .filter_map(|(buffer_id, diff_state)| {
-                                let buffer_store = git_store.buffer_store.read(cx);
-                                let buffer = buffer_store.get(*buffer_id)?;
-                                let file = File::from_dyn(buffer.read(cx).file())?;
-                                let abs_path =
-                                    file.worktree.read(cx).absolutize(&file.path).ok()?;
-                                let repo_path = this.abs_path_to_repo_path(&abs_path)?;
-                                log::debug!(
-                                    "start reload diff bases for repo path {}",
-                                    repo_path.0.display()
-                                );
-                                diff_state.update(cx, |diff_state, _| {
-                                    let has_unstaged_diff = diff_state
-                                        .unstaged_diff
-                                        .as_ref()
-                                        .is_some_and(|diff| diff.is_upgradable());
-                                    let has_uncommitted_diff = diff_state
-                                        .uncommitted_diff
-                                        .as_ref()
-                                        .is_some_and(|set| set.is_upgradable());
+                    Ok(())
+                }
+            }
+        })
+    }
 
-                                    Some((
-                                        buffer,
-                                        repo_path,
-                                        has_unstaged_diff.then(|| diff_state.index_text.clone()),
-                                        has_uncommitted_diff.then(|| diff_state.head_text.clone()),
-                                    ))
-                                })
+    pub fn show(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDetails>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.show(commit).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let resp = client
+                        .request(proto::GitShow {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commit,
+                        })
+                        .await?;
+
+                    Ok(CommitDetails {
+                        sha: resp.sha.into(),
+                        message: resp.message.into(),
+                        commit_timestamp: resp.commit_timestamp,
+                        author_email: resp.author_email.into(),
+                        author_name: resp.author_name.into(),
+                    })
+                }
+            }
+        })
+    }
+
+    pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.load_commit(commit, cx).await,
+                RepositoryState::Remote {
+                    client, project_id, ..
+                } => {
+                    let response = client
+                        .request(proto::LoadCommitDiff {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commit,
+                        })
+                        .await?;
+                    Ok(CommitDiff {
+                        files: response
+                            .files
+                            .into_iter()
+                            .map(|file| CommitFile {
+                                path: Path::new(&file.path).into(),
+                                old_text: file.old_text,
+                                new_text: file.new_text,
                             })
-                            .collect::<Vec<_>>()
+                            .collect(),
                     })
-                })??;
-
-                let buffer_diff_base_changes = cx
-                    .background_spawn(async move {
-                        let mut changes = Vec::new();
-                        for (buffer, repo_path, current_index_text, current_head_text) in
-                            &repo_diff_state_updates
-                        {
-                            let index_text = if current_index_text.is_some() {
-                                backend.load_index_text(repo_path.clone()).await
-                            } else {
-                                None
-                            };
-                            let head_text = if current_head_text.is_some() {
-                                backend.load_committed_text(repo_path.clone()).await
-                            } else {
-                                None
-                            };
+                }
+            }
+        })
+    }
 
-                            let change =
-                                match (current_index_text.as_ref(), current_head_text.as_ref()) {
-                                    (Some(current_index), Some(current_head)) => {
-                                        let index_changed =
-                                            index_text.as_ref() != current_index.as_deref();
-                                        let head_changed =
-                                            head_text.as_ref() != current_head.as_deref();
-                                        if index_changed && head_changed {
-                                            if index_text == head_text {
-                                                Some(DiffBasesChange::SetBoth(head_text))
-                                            } else {
-                                                Some(DiffBasesChange::SetEach {
-                                                    index: index_text,
-                                                    head: head_text,
-                                                })
-                                            }
-                                        } else if index_changed {
-                                            Some(DiffBasesChange::SetIndex(index_text))
-                                        } else if head_changed {
-                                            Some(DiffBasesChange::SetHead(head_text))
-                                        } else {
-                                            None
-                                        }
-                                    }
-                                    (Some(current_index), None) => {
-                                        let index_changed =
-                                            index_text.as_ref() != current_index.as_deref();
-                                        index_changed
-                                            .then_some(DiffBasesChange::SetIndex(index_text))
-                                    }
-                                    (None, Some(current_head)) => {
-                                        let head_changed =
-                                            head_text.as_ref() != current_head.as_deref();
-                                        head_changed.then_some(DiffBasesChange::SetHead(head_text))
-                                    }
-                                    (None, None) => None,
-                                };
+    pub fn log(&mut self, options: LogOptions) -> oneshot::Receiver<Result<Vec<LogEntry>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.log(options).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitLog {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            skip: options.skip as u64,
+                            limit: options.limit as u64,
+                            all_branches: options.all_branches,
+                            path: options.path.map(|path| path.to_string()),
+                            author: options.author,
+                            message: options.message,
+                        })
+                        .await?;
 
-                            changes.push((buffer.clone(), change))
-                        }
-                        changes
-                    })
-                    .await;
+                    Ok(response
+                        .entries
+                        .into_iter()
+                        .map(proto_to_log_entry)
+                        .collect())
+                }
+            }
+        })
+    }
 
-                git_store.update(&mut cx, |git_store, cx| {
-                    for (buffer, diff_bases_change) in buffer_diff_base_changes {
-                        let buffer_snapshot = buffer.read(cx).text_snapshot();
-                        let buffer_id = buffer_snapshot.remote_id();
-                        let Some(diff_state) = git_store.diffs.get(&buffer_id) else {
-                            continue;
-                        };
+    pub fn file_history(
+        &mut self,
+        path: RepoPath,
+        rows: Option<Range<u32>>,
+        limit: usize,
+    ) -> oneshot::Receiver<Result<Vec<FileHistoryEntry>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => {
+                    backend.file_history(path, rows, limit).await
+                }
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitFileHistory {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            path: path.to_string(),
+                            rows: rows.map(|rows| proto::Range {
+                                start: rows.start as u64,
+                                end: rows.end as u64,
+                            }),
+                            limit: limit as u64,
+                        })
+                        .await?;
 
-                        let downstream_client = git_store.downstream_client();
-                        diff_state.update(cx, |diff_state, cx| {
-                            use proto::update_diff_bases::Mode;
+                    response
+                        .entries
+                        .into_iter()
+                        .map(|entry| {
+                            let file = entry.file.context("missing file")?;
+                            Ok(FileHistoryEntry {
+                                commit: proto_to_log_entry(entry.commit.context("missing commit")?),
+                                file: CommitFile {
+                                    path: Path::new(&file.path).into(),
+                                    old_text: file.old_text,
+                                    new_text: file.new_text,
+                                },
+                                rows: entry
+                                    .rows
+                                    .into_iter()
+                                    .map(|rows| rows.start as u32..rows.end as u32)
+                                    .collect(),
+                            })
+                        })
+                        .collect()
+                }
+            }
+        })
+    }
 
-                            if let Some((diff_bases_change, (client, project_id))) =
-                                diff_bases_change.clone().zip(downstream_client)
-                            {
-                                let (staged_text, committed_text, mode) = match diff_bases_change {
-                                    DiffBasesChange::SetIndex(index) => {
-                                        (index, None, Mode::IndexOnly)
-                                    }
-                                    DiffBasesChange::SetHead(head) => (None, head, Mode::HeadOnly),
-                                    DiffBasesChange::SetEach { index, head } => {
-                                        (index, head, Mode::IndexAndHead)
-                                    }
-                                    DiffBasesChange::SetBoth(text) => {
-                                        (None, text, Mode::IndexMatchesHead)
-                                    }
-                                };
-                                client
-                                    .send(proto::UpdateDiffBases {
-                                        project_id: project_id.to_proto(),
-                                        buffer_id: buffer_id.to_proto(),
-                                        staged_text,
-                                        committed_text,
-                                        mode: mode as i32,
-                                    })
-                                    .log_err();
-                            }
+    pub fn rebase_todo(&mut self, onto: String) -> oneshot::Receiver<Result<Vec<RebaseTodoEntry>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.rebase_todo(onto).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitRebaseTodo {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            onto,
+                        })
+                        .await?;
 
-                            diff_state.diff_bases_changed(buffer_snapshot, diff_bases_change, cx);
-                        });
-                    }
-                })
-            },
-        );
+                    Ok(response
+                        .entries
+                        .iter()
+                        .map(proto_to_rebase_todo_entry)
+                        .collect())
+                }
+            }
+        })
     }
 
-    pub fn send_job<F, Fut, R>(
+    pub fn rebase_start(
         &mut self,
-        status: Option<SharedString>,
-        job: F,
-    ) -> oneshot::Receiver<R>
-    where
-        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
-        Fut: Future<Output = R> + 'static,
-        R: Send + 'static,
-    {
-        self.send_keyed_job(None, status, job)
+        onto: String,
+        todo: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(Some("git rebase".into()), move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.rebase_start(onto, todo, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    client
+                        .request(proto::GitRebaseStart {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            onto,
+                            todo: todo.iter().map(rebase_todo_entry_to_proto).collect(),
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
     }
 
-    fn send_keyed_job<F, Fut, R>(
-        &mut self,
-        key: Option<GitJobKey>,
-        status: Option<SharedString>,
-        job: F,
-    ) -> oneshot::Receiver<R>
-    where
-        F: FnOnce(RepositoryState, AsyncApp) -> Fut + 'static,
-        Fut: Future<Output = R> + 'static,
-        R: Send + 'static,
-    {
-        let (result_tx, result_rx) = futures::channel::oneshot::channel();
-        let job_id = post_inc(&mut self.job_id);
-        let this = self.this.clone();
-        self.job_sender
-            .unbounded_send(GitJob {
-                key,
-                job: Box::new(move |state, cx: &mut AsyncApp| {
-                    let job = job(state, cx.clone());
-                    cx.spawn(async move |cx| {
-                        if let Some(s) = status.clone() {
-                            this.update(cx, |this, cx| {
-                                this.active_jobs.insert(
-                                    job_id,
-                                    JobInfo {
-                                        start: Instant::now(),
-                                        message: s.clone(),
-                                    },
-                                );
+    pub fn rebase_command(&mut self, command: RebaseCommand) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        let status = match command {
+            RebaseCommand::Continue => "git rebase --continue",
+            RebaseCommand::Skip => "git rebase --skip",
+            RebaseCommand::Abort => "git rebase --abort",
+        };
+        self.send_job(Some(status.into()), move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.rebase_command(command, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    let command = match command {
+                        RebaseCommand::Continue => git_rebase_command::Command::Continue,
+                        RebaseCommand::Skip => git_rebase_command::Command::Skip,
+                        RebaseCommand::Abort => git_rebase_command::Command::Abort,
+                    };
+                    client
+                        .request(proto::GitRebaseCommand {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            command: command.into(),
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn rebase_status(&mut self) -> oneshot::Receiver<Result<Option<RebaseStatus>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.rebase_status().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitRebaseStatus {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
 
-                                cx.notify();
-                            })
-                            .ok();
-                        }
-                        let result = job.await;
+                    Ok(response.status.map(|status| RebaseStatus {
+                        head_name: status.head_name.map(Into::into),
+                        onto: status.onto.into(),
+                        step: status.step as usize,
+                        total_steps: status.total_steps as usize,
+                        stopped_at: status.stopped_at.map(Into::into),
+                    }))
+                }
+            }
+        })
+    }
 
-                        this.update(cx, |this, cx| {
-                            this.active_jobs.remove(&job_id);
-                            cx.notify();
+    pub fn load_conflict_versions(
+        &mut self,
+        path: RepoPath,
+    ) -> oneshot::Receiver<Result<ConflictVersions>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => {
+                    backend.load_conflict_versions(path).await
+                }
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitLoadConflictVersions {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            path: path.to_string(),
                         })
-                        .ok();
+                        .await?;
 
-                        result_tx.send(result).ok();
+                    Ok(ConflictVersions {
+                        base: response.base,
+                        ours: response.ours,
+                        theirs: response.theirs,
                     })
-                }),
-            })
-            .ok();
-        result_rx
+                }
+            }
+        })
     }
 
-    pub fn set_as_active_repository(&self, cx: &mut Context<Self>) {
-        let Some(git_store) = self.git_store.upgrade() else {
-            return;
-        };
-        let entity = cx.entity();
-        git_store.update(cx, |git_store, cx| {
-            let Some((&id, _)) = git_store
-                .repositories
-                .iter()
-                .find(|(_, handle)| *handle == &entity)
-            else {
-                return;
-            };
-            git_store.active_repo_id = Some(id);
-            cx.emit(GitStoreEvent::ActiveRepositoryChanged(Some(id)));
-        });
+    pub fn worktrees(&mut self) -> oneshot::Receiver<Result<Vec<GitWorktree>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.worktrees().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitWorktrees {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(response
+                        .worktrees
+                        .into_iter()
+                        .map(|worktree| GitWorktree {
+                            path: PathBuf::from_proto(worktree.path),
+                            head_sha: worktree.head_sha.map(Into::into),
+                            branch: worktree.branch.map(Into::into),
+                            is_main: worktree.is_main,
+                            is_locked: worktree.is_locked,
+                            is_prunable: worktree.is_prunable,
+                        })
+                        .collect())
+                }
+            }
+        })
     }
 
-    pub fn cached_status(&self) -> impl '_ + Iterator<Item = StatusEntry> {
-        self.snapshot.status()
+    /// Adds a linked worktree at the given path, which is on the machine that hosts the
+    /// repository.
+    pub fn create_worktree(
+        &mut self,
+        path: PathBuf,
+        commit: String,
+        new_branch: Option<String>,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git worktree add".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => {
+                        backend
+                            .create_worktree(path, commit, new_branch, environment)
+                            .await
+                    }
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCreateWorktree {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                path: path.to_proto(),
+                                commit,
+                                new_branch,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn repo_path_to_project_path(&self, path: &RepoPath, cx: &App) -> Option<ProjectPath> {
-        let git_store = self.git_store.upgrade()?;
-        let worktree_store = git_store.read(cx).worktree_store.read(cx);
-        let abs_path = self.snapshot.work_directory_abs_path.join(&path.0);
-        let (worktree, relative_path) = worktree_store.find_worktree(abs_path, cx)?;
-        Some(ProjectPath {
-            worktree_id: worktree.read(cx).id(),
-            path: relative_path.into(),
-        })
+    pub fn remove_worktree(&mut self, path: PathBuf, force: bool) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git worktree remove".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.remove_worktree(path, force, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitRemoveWorktree {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                path: path.to_proto(),
+                                force,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn project_path_to_repo_path(&self, path: &ProjectPath, cx: &App) -> Option<RepoPath> {
-        let git_store = self.git_store.upgrade()?;
-        let worktree_store = git_store.read(cx).worktree_store.read(cx);
-        let abs_path = worktree_store.absolutize(path, cx)?;
-        self.snapshot.abs_path_to_repo_path(&abs_path)
+    pub fn prune_worktrees(&mut self) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git worktree prune".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.prune_worktrees(environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitPruneWorktrees {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn contains_sub_repo(&self, other: &Entity<Self>, cx: &App) -> bool {
-        other
-            .read(cx)
-            .snapshot
-            .work_directory_abs_path
-            .starts_with(&self.snapshot.work_directory_abs_path)
+    /// Cherry-picks the given commits onto `HEAD`. This succeeds when a commit stops with
+    /// conflicts; check `sequencer_state` to find out whether it did.
+    pub fn cherry_pick(&mut self, commits: Vec<String>) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git cherry-pick".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.cherry_pick(commits, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCherryPick {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                commits,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
     }
 
-    pub fn open_commit_buffer(
-        &mut self,
-        languages: Option<Arc<LanguageRegistry>>,
-        buffer_store: Entity<BufferStore>,
-        cx: &mut Context<Self>,
-    ) -> Task<Result<Entity<Buffer>>> {
+    /// Reverts the given commits. Like `cherry_pick`, this succeeds when a commit stops
+    /// with conflicts.
+    pub fn revert(&mut self, commits: Vec<String>) -> oneshot::Receiver<Result<()>> {
         let id = self.id;
-        if let Some(buffer) = self.commit_message_buffer.clone() {
-            return Task::ready(Ok(buffer));
-        }
-        let this = cx.weak_entity();
+        self.send_job(Some("git revert".into()), move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => backend.revert(commits, environment).await,
+                RepositoryState::Remote { project_id, client } => {
+                    client
+                        .request(proto::GitRevert {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            commits,
+                        })
+                        .await?;
 
-        let rx = self.send_job(None, move |state, mut cx| async move {
-            let Some(this) = this.upgrade() else {
-                bail!("git store was dropped");
-            };
-            match state {
-                RepositoryState::Local { .. } => {
-                    this.update(&mut cx, |_, cx| {
-                        Self::open_local_commit_buffer(languages, buffer_store, cx)
-                    })?
-                    .await
+                    Ok(())
                 }
+            }
+        })
+    }
+
+    pub fn sequencer_state(&mut self) -> oneshot::Receiver<Result<Option<SequencerState>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => Ok(backend.sequencer_state().await),
                 RepositoryState::Remote { project_id, client } => {
-                    let request = client.request(proto::OpenCommitMessageBuffer {
-                        project_id: project_id.0,
-                        repository_id: id.to_proto(),
-                    });
-                    let response = request.await.context("requesting to open commit buffer")?;
-                    let buffer_id = BufferId::new(response.buffer_id)?;
-                    let buffer = buffer_store
-                        .update(&mut cx, |buffer_store, cx| {
-                            buffer_store.wait_for_remote_buffer(buffer_id, cx)
-                        })?
+                    let response = client
+                        .request(proto::GitSequencerState {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
                         .await?;
-                    if let Some(language_registry) = languages {
-                        let git_commit_language =
-                            language_registry.language_for_name("Git Commit").await?;
-                        buffer.update(&mut cx, |buffer, cx| {
-                            buffer.set_language(Some(git_commit_language), cx);
-                        })?;
-                    }
-                    this.update(&mut cx, |this, _| {
-                        this.commit_message_buffer = Some(buffer.clone());
-                    })?;
-                    Ok(buffer)
+
+                    Ok(response.state.map(|state| SequencerState {
+                        operation: proto_to_sequencer_operation(state.operation()),
+                        sha: state.sha.into(),
+                    }))
+                }
+            }
+        })
+    }
+
+    pub fn sequencer_command(
+        &mut self,
+        operation: SequencerOperation,
+        command: RebaseCommand,
+    ) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        let flag = match command {
+            RebaseCommand::Continue => "--continue",
+            RebaseCommand::Skip => "--skip",
+            RebaseCommand::Abort => "--abort",
+        };
+        let status = format!("git {} {flag}", operation.as_str());
+        self.send_job(Some(status.into()), move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local {
+                    backend,
+                    environment,
+                    ..
+                } => {
+                    backend
+                        .sequencer_command(operation, command, environment)
+                        .await
                 }
-            }
-        });
+                RepositoryState::Remote { project_id, client } => {
+                    let command = match command {
+                        RebaseCommand::Continue => git_rebase_command::Command::Continue,
+                        RebaseCommand::Skip => git_rebase_command::Command::Skip,
+                        RebaseCommand::Abort => git_rebase_command::Command::Abort,
+                    };
+                    client
+                        .request(proto::GitSequencerCommand {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            operation: sequencer_operation_to_proto(operation).into(),
+                            command: command.into(),
+                        })
+                        .await?;
 
-        cx.spawn(|_, _: &mut AsyncApp| async move { rx.await? })
+                    Ok(())
+                }
+            }
+        })
     }
 
-    fn open_local_commit_buffer(
-        language_registry: Option<Arc<LanguageRegistry>>,
-        buffer_store: Entity<BufferStore>,
-        cx: &mut Context<Self>,
-    ) -> Task<Result<Entity<Buffer>>> {
-        cx.spawn(async move |repository, cx| {
-            let buffer = buffer_store
-                .update(cx, |buffer_store, cx| buffer_store.create_buffer(cx))?
-                .await?;
+    pub fn tags(&mut self) -> oneshot::Receiver<Result<Vec<Tag>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local { backend, .. } => backend.tags().await,
+                RepositoryState::Remote { project_id, client } => {
+                    let response = client
+                        .request(proto::GitTags {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                        })
+                        .await?;
 
-            if let Some(language_registry) = language_registry {
-                let git_commit_language = language_registry.language_for_name("Git Commit").await?;
-                buffer.update(cx, |buffer, cx| {
-                    buffer.set_language(Some(git_commit_language), cx);
-                })?;
+                    Ok(response
+                        .tags
+                        .into_iter()
+                        .map(|tag| Tag {
+                            name: tag.name.into(),
+                            sha: tag.sha.into(),
+                            message: tag.message.map(Into::into),
+                            timestamp: tag.timestamp,
+                        })
+                        .collect())
+                }
             }
-
-            repository.update(cx, |repository, _| {
-                repository.commit_message_buffer = Some(buffer.clone());
-            })?;
-            Ok(buffer)
         })
     }
 
-    pub fn checkout_files(
+    pub fn create_tag(
         &mut self,
-        commit: &str,
-        paths: Vec<RepoPath>,
-        _cx: &mut App,
+        name: String,
+        commit: String,
+        message: Option<String>,
     ) -> oneshot::Receiver<Result<()>> {
-        let commit = commit.to_string();
+        let id = self.id;
+        self.send_job(
+            Some(format!("git tag {name}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.create_tag(name, commit, message, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitCreateTag {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                name,
+                                commit,
+                                message,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn delete_tag(&mut self, name: String) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some(format!("git tag --delete {name}").into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.delete_tag(name, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitDeleteTag {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                name,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn push_tag(
+        &mut self,
+        name: SharedString,
+        remote: SharedString,
+        askpass: AskPassDelegate,
+    ) -> oneshot::Receiver<Result<RemoteCommandOutput>> {
+        let askpass_delegates = self.askpass_delegates.clone();
+        let askpass_id = util::post_inc(&mut self.latest_askpass_id);
         let id = self.id;
 
         self.send_job(
-            Some(format!("git checkout {}", commit).into()),
-            move |git_repo, _| async move {
+            Some(format!("git push {remote} tag {name}").into()),
+            move |git_repo, cx| async move {
                 match git_repo {
                     RepositoryState::Local {
                         backend,
@@ -3186,55 +4474,72 @@ impl Repository {
                         ..
                     } => {
                         backend
-                            .checkout_files(commit, paths, environment.clone())
+                            .push_tag(
+                                name.to_string(),
+                                remote.to_string(),
+                                askpass,
+                                environment,
+                                cx,
+                            )
                             .await
                     }
                     RepositoryState::Remote { project_id, client } => {
-                        client
-                            .request(proto::GitCheckoutFiles {
+                        askpass_delegates.lock().insert(askpass_id, askpass);
+                        let _defer = util::defer(|| {
+                            let askpass_delegate = askpass_delegates.lock().remove(&askpass_id);
+                            debug_assert!(askpass_delegate.is_some());
+                        });
+                        let response = client
+                            .request(proto::GitPushTag {
                                 project_id: project_id.0,
                                 repository_id: id.to_proto(),
-                                commit,
-                                paths: paths
-                                    .into_iter()
-                                    .map(|p| p.to_string_lossy().to_string())
-                                    .collect(),
+                                name: name.to_string(),
+                                remote_name: remote.to_string(),
+                                askpass_id,
                             })
-                            .await?;
+                            .await
+                            .context("sending push tag request")?;
 
-                        Ok(())
+                        Ok(RemoteCommandOutput {
+                            stdout: response.stdout,
+                            stderr: response.stderr,
+                        })
                     }
                 }
             },
         )
     }
 
-    pub fn reset(
+    pub fn stash_push(
         &mut self,
-        commit: String,
-        reset_mode: ResetMode,
+        scope: StashScope,
+        message: Option<String>,
         _cx: &mut App,
     ) -> oneshot::Receiver<Result<()>> {
-        let commit = commit.to_string();
         let id = self.id;
-
-        self.send_job(None, move |git_repo, _| async move {
+        self.send_job(Some("git stash".into()), move |git_repo, _cx| async move {
             match git_repo {
                 RepositoryState::Local {
                     backend,
                     environment,
                     ..
-                } => backend.reset(commit, reset_mode, environment).await,
+                } => backend.stash_push(scope, message, environment).await,
                 RepositoryState::Remote { project_id, client } => {
+                    let (scope, paths) = match scope {
+                        StashScope::All => (git_stash_push::StashScope::All, Vec::new()),
+                        StashScope::Staged => (git_stash_push::StashScope::Staged, Vec::new()),
+                        StashScope::Paths(paths) => (
+                            git_stash_push::StashScope::Paths,
+                            paths.iter().map(|path| path.to_string()).collect(),
+                        ),
+                    };
                     client
-                        .request(proto::GitReset {
+                        .request(proto::GitStashPush {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            commit,
-                            mode: match reset_mode {
-                                ResetMode::Soft => git_reset::ResetMode::Soft.into(),
-                                ResetMode::Mixed => git_reset::ResetMode::Mixed.into(),
-                            },
+                            scope: scope.into(),
+                            paths,
+                            message,
                         })
                         .await?;
 
@@ -3244,45 +4549,45 @@ impl Repository {
         })
     }
 
-    pub fn show(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDetails>> {
+    pub fn stash_list(&mut self) -> oneshot::Receiver<Result<Vec<StashEntry>>> {
         let id = self.id;
         self.send_job(None, move |git_repo, _cx| async move {
             match git_repo {
-                RepositoryState::Local { backend, .. } => backend.show(commit).await,
+                RepositoryState::Local { backend, .. } => backend.stash_list().await,
                 RepositoryState::Remote { project_id, client } => {
-                    let resp = client
-                        .request(proto::GitShow {
+                    let response = client
+                        .request(proto::GitStashList {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            commit,
                         })
                         .await?;
 
-                    Ok(CommitDetails {
-                        sha: resp.sha.into(),
-                        message: resp.message.into(),
-                        commit_timestamp: resp.commit_timestamp,
-                        author_email: resp.author_email.into(),
-                        author_name: resp.author_name.into(),
-                    })
+                    Ok(response
+                        .entries
+                        .into_iter()
+                        .map(|entry| StashEntry {
+                            index: entry.index as usize,
+                            sha: entry.sha.into(),
+                            message: entry.message.into(),
+                            commit_timestamp: entry.commit_timestamp,
+                        })
+                        .collect())
                 }
             }
         })
     }
 
-    pub fn load_commit_diff(&mut self, commit: String) -> oneshot::Receiver<Result<CommitDiff>> {
+    pub fn stash_diff(&mut self, index: usize) -> oneshot::Receiver<Result<CommitDiff>> {
         let id = self.id;
-        self.send_job(None, move |git_repo, cx| async move {
+        self.send_job(None, move |git_repo, _cx| async move {
             match git_repo {
-                RepositoryState::Local { backend, .. } => backend.load_commit(commit, cx).await,
-                RepositoryState::Remote {
-                    client, project_id, ..
-                } => {
+                RepositoryState::Local { backend, .. } => backend.stash_diff(index).await,
+                RepositoryState::Remote { project_id, client } => {
                     let response = client
-                        .request(proto::LoadCommitDiff {
+                        .request(proto::GitStashDiff {
                             project_id: project_id.0,
                             repository_id: id.to_proto(),
-                            commit,
+                            index: index as u64,
                         })
                         .await?;
                     Ok(CommitDiff {
@@ -3301,6 +4606,87 @@ impl Repository {
         })
     }
 
+    pub fn stash_apply(&mut self, index: usize) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git stash apply".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.stash_apply(index, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitStashApply {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                index: index as u64,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn stash_pop(&mut self, index: usize) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git stash pop".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.stash_pop(index, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitStashPop {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                index: index as u64,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn stash_drop(&mut self, index: usize) -> oneshot::Receiver<Result<()>> {
+        let id = self.id;
+        self.send_job(
+            Some("git stash drop".into()),
+            move |git_repo, _cx| async move {
+                match git_repo {
+                    RepositoryState::Local {
+                        backend,
+                        environment,
+                        ..
+                    } => backend.stash_drop(index, environment).await,
+                    RepositoryState::Remote { project_id, client } => {
+                        client
+                            .request(proto::GitStashDrop {
+                                project_id: project_id.0,
+                                repository_id: id.to_proto(),
+                                index: index as u64,
+                            })
+                            .await?;
+
+                        Ok(())
+                    }
+                }
+            },
+        )
+    }
+
     fn buffer_store(&self, cx: &App) -> Option<Entity<BufferStore>> {
         Some(self.git_store.upgrade()?.read(cx).buffer_store.clone())
     }
@@ -4547,6 +5933,78 @@ fn proto_to_commit_details(proto: &proto::GitCommitDetails) -> CommitDetails {
     }
 }
 
+fn log_entry_to_proto(entry: LogEntry) -> proto::GitLogEntry {
+    proto::GitLogEntry {
+        sha: entry.sha.into(),
+        parents: entry.parents.into_iter().map(Into::into).collect(),
+        subject: entry.subject.into(),
+        author_name: entry.author_name.into(),
+        author_email: entry.author_email.into(),
+        commit_timestamp: entry.commit_timestamp,
+        refs: entry.refs.into_iter().map(Into::into).collect(),
+    }
+}
+
+fn proto_to_log_entry(entry: proto::GitLogEntry) -> LogEntry {
+    LogEntry {
+        sha: entry.sha.into(),
+        parents: entry.parents.into_iter().map(Into::into).collect(),
+        subject: entry.subject.into(),
+        author_name: entry.author_name.into(),
+        author_email: entry.author_email.into(),
+        commit_timestamp: entry.commit_timestamp,
+        refs: entry.refs.into_iter().map(Into::into).collect(),
+    }
+}
+
+fn rebase_todo_entry_to_proto(entry: &RebaseTodoEntry) -> proto::RebaseTodoEntry {
+    let action = match entry.action {
+        RebaseAction::Pick => rebase_todo_entry::Action::Pick,
+        RebaseAction::Reword => rebase_todo_entry::Action::Reword,
+        RebaseAction::Edit => rebase_todo_entry::Action::Edit,
+        RebaseAction::Squash => rebase_todo_entry::Action::Squash,
+        RebaseAction::Fixup => rebase_todo_entry::Action::Fixup,
+        RebaseAction::Drop => rebase_todo_entry::Action::Drop,
+    };
+    proto::RebaseTodoEntry {
+        action: action.into(),
+        sha: entry.sha.to_string(),
+        subject: entry.subject.to_string(),
+        message: entry.message.clone(),
+    }
+}
+
+fn proto_to_rebase_todo_entry(proto: &proto::RebaseTodoEntry) -> RebaseTodoEntry {
+    let action = match proto.action() {
+        rebase_todo_entry::Action::Pick => RebaseAction::Pick,
+        rebase_todo_entry::Action::Reword => RebaseAction::Reword,
+        rebase_todo_entry::Action::Edit => RebaseAction::Edit,
+        rebase_todo_entry::Action::Squash => RebaseAction::Squash,
+        rebase_todo_entry::Action::Fixup => RebaseAction::Fixup,
+        rebase_todo_entry::Action::Drop => RebaseAction::Drop,
+    };
+    RebaseTodoEntry {
+        action,
+        sha: proto.sha.clone().into(),
+        subject: proto.subject.clone().into(),
+        message: proto.message.clone(),
+    }
+}
+
+fn sequencer_operation_to_proto(operation: SequencerOperation) -> proto::SequencerOperation {
+    match operation {
+        SequencerOperation::CherryPick => proto::SequencerOperation::CherryPick,
+        SequencerOperation::Revert => proto::SequencerOperation::Revert,
+    }
+}
+
+fn proto_to_sequencer_operation(operation: proto::SequencerOperation) -> SequencerOperation {
+    match operation {
+        proto::SequencerOperation::CherryPick => SequencerOperation::CherryPick,
+        proto::SequencerOperation::Revert => SequencerOperation::Revert,
+    }
+}
+
 async fn compute_snapshot(
     id: RepositoryId,
     work_directory_abs_path: Arc<Path>,
@@ -1,3 +1,6 @@
+mod document_link;
+mod folding_range;
+mod hierarchy;
 mod signature_help;
 
 use crate::{
@@ -37,6 +40,12 @@ use std::{
 use text::{BufferId, LineEnding};
 use util::{ResultExt as _, debug_panic};
 
+pub(crate) use document_link::GetDocumentLinks;
+pub(crate) use folding_range::GetFoldingRanges;
+pub(crate) use hierarchy::{
+    GetIncomingCalls, GetOutgoingCalls, GetSubtypes, GetSupertypes, PrepareCallHierarchy,
+    PrepareTypeHierarchy,
+};
 pub use signature_help::SignatureHelp;
 
 pub fn lsp_formatting_options(settings: &LanguageSettings) -> lsp::FormattingOptions {
@@ -3446,10 +3455,7 @@ impl LspCommand for GetCodeLens {
         capabilities
             .server_capabilities
             .code_lens_provider
-            .as_ref()
-            .map_or(false, |code_lens_options| {
-                code_lens_options.resolve_provider.unwrap_or(false)
-            })
+            .is_some()
     }
 
     fn to_lsp(
@@ -0,0 +1,153 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use client::proto::{self, PeerId};
+use gpui::{App, AsyncApp, Entity};
+use language::{
+    Bias, Buffer, point_from_lsp,
+    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
+};
+use lsp::{AdapterServerCapabilities, LanguageServer, LanguageServerId};
+use text::BufferId;
+
+use super::{LspCommand, make_text_document_identifier};
+use crate::{DocumentLink, lsp_store::LspStore};
+
+#[derive(Debug)]
+pub(crate) struct GetDocumentLinks;
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn display_name(&self) -> &str {
+        "Get document links"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::DocumentLinkParams> {
+        Ok(lsp::DocumentLinkParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::DocumentLink>>,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer.read_with(&mut cx, |buffer, _| {
+            message
+                .unwrap_or_default()
+                .into_iter()
+                // Links without a target would need a `documentLink/resolve` round trip, which
+                // we do not advertise support for.
+                .filter_map(|link| {
+                    let target = link.target?;
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(link.range.start), Bias::Left);
+                    let end = buffer.clip_point_utf16(point_from_lsp(link.range.end), Bias::Left);
+                    Some(DocumentLink {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        target: target.to_string(),
+                        tooltip: link.tooltip,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetDocumentLinksResponse {
+        let links = response
+            .into_iter()
+            .map(|link| proto::DocumentLink {
+                start: Some(serialize_anchor(&link.range.start)),
+                end: Some(serialize_anchor(&link.range.end)),
+                target: link.target,
+                tooltip: link.tooltip,
+            })
+            .collect();
+        proto::GetDocumentLinksResponse { links }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<DocumentLink>> {
+        let mut links = Vec::new();
+        for link in message.links {
+            let start = link
+                .start
+                .and_then(deserialize_anchor)
+                .context("missing document link start")?;
+            let end = link
+                .end
+                .and_then(deserialize_anchor)
+                .context("missing document link end")?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            links.push(DocumentLink {
+                range: start..end,
+                target: link.target,
+                tooltip: link.tooltip,
+            });
+        }
+        Ok(links)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
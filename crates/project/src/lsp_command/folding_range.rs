@@ -0,0 +1,175 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use client::proto::{self, PeerId};
+use gpui::{App, AsyncApp, Entity};
+use language::{
+    Buffer, Point,
+    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
+};
+use lsp::{AdapterServerCapabilities, LanguageServer, LanguageServerId};
+use text::BufferId;
+
+use super::{LspCommand, make_text_document_identifier};
+use crate::{FoldingRange, lsp_store::LspStore};
+
+#[derive(Debug)]
+pub(crate) struct GetFoldingRanges;
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn display_name(&self) -> &str {
+        "Get folding ranges"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .folding_range_provider
+            .is_some_and(|provider| match provider {
+                lsp::FoldingRangeProviderCapability::Simple(supported) => supported,
+                lsp::FoldingRangeProviderCapability::FoldingProvider(..) => true,
+                lsp::FoldingRangeProviderCapability::Options(..) => true,
+            })
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::FoldingRangeParams> {
+        Ok(lsp::FoldingRangeParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::FoldingRange>>,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer.read_with(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut ranges = message
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|lsp_range| {
+                    // Zed only asks for whole lines to be folded, so the first line of each range
+                    // stays visible and the rest of its lines are hidden.
+                    let start_row = lsp_range.start_line;
+                    let end_row = lsp_range.end_line.min(max_row);
+                    if start_row >= end_row {
+                        return None;
+                    }
+                    let start = Point::new(start_row, buffer.line_len(start_row));
+                    let end = Point::new(end_row, buffer.line_len(end_row));
+                    Some(FoldingRange {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: lsp_range.kind,
+                    })
+                })
+                .collect::<Vec<_>>();
+            ranges.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            ranges
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<FoldingRange>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut App,
+    ) -> proto::GetFoldingRangesResponse {
+        let ranges = response
+            .into_iter()
+            .map(|range| proto::FoldingRange {
+                start: Some(serialize_anchor(&range.range.start)),
+                end: Some(serialize_anchor(&range.range.end)),
+                kind: range.kind.map(|kind| {
+                    match kind {
+                        lsp::FoldingRangeKind::Comment => proto::folding_range::Kind::Comment,
+                        lsp::FoldingRangeKind::Imports => proto::folding_range::Kind::Imports,
+                        lsp::FoldingRangeKind::Region => proto::folding_range::Kind::Region,
+                    }
+                    .into()
+                }),
+            })
+            .collect();
+        proto::GetFoldingRangesResponse { ranges }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<FoldingRange>> {
+        let mut ranges = Vec::new();
+        for range in message.ranges {
+            let start = range
+                .start
+                .and_then(deserialize_anchor)
+                .context("missing folding range start")?;
+            let end = range
+                .end
+                .and_then(deserialize_anchor)
+                .context("missing folding range end")?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            let kind = range
+                .kind
+                .and_then(proto::folding_range::Kind::from_i32)
+                .map(|kind| match kind {
+                    proto::folding_range::Kind::Comment => lsp::FoldingRangeKind::Comment,
+                    proto::folding_range::Kind::Imports => lsp::FoldingRangeKind::Imports,
+                    proto::folding_range::Kind::Region => lsp::FoldingRangeKind::Region,
+                });
+            ranges.push(FoldingRange {
+                range: start..end,
+                kind,
+            });
+        }
+        Ok(ranges)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
@@ -0,0 +1,855 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use client::proto::{self, PeerId};
+use gpui::{App, AsyncApp, Entity};
+use language::{
+    Bias, Buffer, Location, PointUtf16, ToPointUtf16 as _, point_from_lsp,
+    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
+};
+use lsp::{AdapterServerCapabilities, LanguageServer, LanguageServerId};
+use text::BufferId;
+
+use super::{LspCommand, language_server_for_buffer, make_lsp_text_document_position};
+use crate::{HierarchyCall, HierarchyItem, LspHierarchyItem, lsp_store::LspStore};
+
+#[derive(Debug)]
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetIncomingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetOutgoingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSupertypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSubtypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+fn supports_call_hierarchy(capabilities: &AdapterServerCapabilities) -> bool {
+    capabilities
+        .server_capabilities
+        .call_hierarchy_provider
+        .as_ref()
+        .is_some_and(|provider| {
+            !matches!(provider, lsp::CallHierarchyServerCapability::Simple(false))
+        })
+}
+
+// `lsp::ServerCapabilities` has no field for the type hierarchy provider, so
+// `LanguageServer::initialize` keeps it among the experimental capabilities.
+fn supports_type_hierarchy(capabilities: &AdapterServerCapabilities) -> bool {
+    capabilities
+        .server_capabilities
+        .experimental
+        .as_ref()
+        .and_then(|experimental| experimental.get(lsp::TYPE_HIERARCHY_PROVIDER))
+        .is_some_and(|provider| {
+            !matches!(
+                provider,
+                serde_json::Value::Null | serde_json::Value::Bool(false)
+            )
+        })
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn display_name(&self) -> &str {
+        "Prepare call hierarchy"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_call_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::CallHierarchyPrepareParams> {
+        Ok(lsp::CallHierarchyPrepareParams {
+            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.unwrap_or_default() {
+            items.push(
+                hierarchy_item_from_lsp(
+                    LspHierarchyItem::Call(item),
+                    &lsp_store,
+                    &buffer,
+                    server_id,
+                    &mut cx,
+                )
+                .await?,
+            );
+        }
+        Ok(items)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .context("invalid position")?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.read_with(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetHierarchyItemsResponse {
+        hierarchy_items_to_proto(response, lsp_store, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHierarchyItemsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, &lsp_store, &mut cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<HierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn display_name(&self) -> &str {
+        "Get incoming calls"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_call_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::CallHierarchyIncomingCallsParams> {
+        Ok(lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyCall>> {
+        let mut calls = Vec::new();
+        for call in message.unwrap_or_default() {
+            let item = hierarchy_item_from_lsp(
+                LspHierarchyItem::Call(call.from),
+                &lsp_store,
+                &buffer,
+                server_id,
+                &mut cx,
+            )
+            .await?;
+            let call_sites =
+                locations_from_lsp_ranges(&item.location.buffer, call.from_ranges, &cx)?;
+            calls.push(HierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetHierarchyCallsResponse {
+        hierarchy_calls_to_proto(response, lsp_store, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHierarchyCallsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyCall>> {
+        hierarchy_calls_from_proto(message, &lsp_store, &mut cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<HierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn display_name(&self) -> &str {
+        "Get outgoing calls"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_call_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::CallHierarchyOutgoingCallsParams> {
+        Ok(lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyCall>> {
+        let mut calls = Vec::new();
+        for call in message.unwrap_or_default() {
+            let item = hierarchy_item_from_lsp(
+                LspHierarchyItem::Call(call.to),
+                &lsp_store,
+                &buffer,
+                server_id,
+                &mut cx,
+            )
+            .await?;
+            // The calls are made from the requested item, which lives in the queried buffer.
+            let call_sites = locations_from_lsp_ranges(&buffer, call.from_ranges, &cx)?;
+            calls.push(HierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetHierarchyCallsResponse {
+        hierarchy_calls_to_proto(response, lsp_store, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHierarchyCallsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyCall>> {
+        hierarchy_calls_from_proto(message, &lsp_store, &mut cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn display_name(&self) -> &str {
+        "Prepare type hierarchy"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_type_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::TypeHierarchyPrepareParams> {
+        Ok(lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        type_hierarchy_items_from_lsp(message, &lsp_store, &buffer, server_id, &mut cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .context("invalid position")?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.read_with(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetHierarchyItemsResponse {
+        hierarchy_items_to_proto(response, lsp_store, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHierarchyItemsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, &lsp_store, &mut cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn display_name(&self) -> &str {
+        "Get supertypes"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_type_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::TypeHierarchySupertypesParams> {
+        Ok(lsp::TypeHierarchySupertypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        type_hierarchy_items_from_lsp(message, &lsp_store, &buffer, server_id, &mut cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetHierarchyItemsResponse {
+        hierarchy_items_to_proto(response, lsp_store, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHierarchyItemsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, &lsp_store, &mut cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn display_name(&self) -> &str {
+        "Get subtypes"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        supports_type_hierarchy(&capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &App,
+    ) -> Result<lsp::TypeHierarchySubtypesParams> {
+        Ok(lsp::TypeHierarchySubtypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Entity<LspStore>,
+        buffer: Entity<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        type_hierarchy_items_from_lsp(message, &lsp_store, &buffer, server_id, &mut cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Entity<LspStore>,
+        _: Entity<Buffer>,
+        _: AsyncApp,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut App,
+    ) -> proto::GetHierarchyItemsResponse {
+        hierarchy_items_to_proto(response, lsp_store, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHierarchyItemsResponse,
+        lsp_store: Entity<LspStore>,
+        _: Entity<Buffer>,
+        mut cx: AsyncApp,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, &lsp_store, &mut cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+async fn type_hierarchy_items_from_lsp(
+    items: Option<Vec<lsp::TypeHierarchyItem>>,
+    lsp_store: &Entity<LspStore>,
+    buffer: &Entity<Buffer>,
+    server_id: LanguageServerId,
+    cx: &mut AsyncApp,
+) -> Result<Vec<HierarchyItem>> {
+    let mut hierarchy_items = Vec::new();
+    for item in items.unwrap_or_default() {
+        hierarchy_items.push(
+            hierarchy_item_from_lsp(
+                LspHierarchyItem::Type(item),
+                lsp_store,
+                buffer,
+                server_id,
+                cx,
+            )
+            .await?,
+        );
+    }
+    Ok(hierarchy_items)
+}
+
+/// Opens the document an item belongs to, which may not be open in the project yet.
+async fn hierarchy_item_from_lsp(
+    lsp_item: LspHierarchyItem,
+    lsp_store: &Entity<LspStore>,
+    buffer: &Entity<Buffer>,
+    server_id: LanguageServerId,
+    cx: &mut AsyncApp,
+) -> Result<HierarchyItem> {
+    let (uri, selection_range) = match &lsp_item {
+        LspHierarchyItem::Call(item) => (item.uri.clone(), item.selection_range),
+        LspHierarchyItem::Type(item) => (item.uri.clone(), item.selection_range),
+    };
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(lsp_store, buffer, server_id, cx)?;
+    let item_buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.open_local_buffer_via_lsp(
+                uri,
+                language_server.server_id(),
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+    let mut locations = locations_from_lsp_ranges(&item_buffer, [selection_range], cx)?;
+    let location = locations.pop().context("missing item location")?;
+    Ok(HierarchyItem::new(location, lsp_item, server_id))
+}
+
+fn locations_from_lsp_ranges(
+    buffer: &Entity<Buffer>,
+    ranges: impl IntoIterator<Item = lsp::Range>,
+    cx: &AsyncApp,
+) -> Result<Vec<Location>> {
+    buffer.read_with(cx, |snapshot, _| {
+        ranges
+            .into_iter()
+            .map(|range| {
+                let start = snapshot.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+                let end = snapshot.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+                Location {
+                    buffer: buffer.clone(),
+                    range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                }
+            })
+            .collect()
+    })
+}
+
+fn hierarchy_items_to_proto(
+    items: Vec<HierarchyItem>,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> proto::GetHierarchyItemsResponse {
+    proto::GetHierarchyItemsResponse {
+        items: items
+            .into_iter()
+            .map(|item| hierarchy_item_to_proto(item, lsp_store, peer_id, cx))
+            .collect(),
+    }
+}
+
+fn hierarchy_calls_to_proto(
+    calls: Vec<HierarchyCall>,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> proto::GetHierarchyCallsResponse {
+    proto::GetHierarchyCallsResponse {
+        calls: calls
+            .into_iter()
+            .map(|call| proto::HierarchyCall {
+                call_sites: call
+                    .call_sites
+                    .iter()
+                    .map(|call_site| location_to_proto(call_site, lsp_store, peer_id, cx))
+                    .collect(),
+                item: Some(hierarchy_item_to_proto(call.item, lsp_store, peer_id, cx)),
+            })
+            .collect(),
+    }
+}
+
+fn hierarchy_item_to_proto(
+    item: HierarchyItem,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> proto::HierarchyItem {
+    let lsp_item = match &item.lsp_item {
+        LspHierarchyItem::Call(lsp_item) => {
+            proto::hierarchy_item::LspItem::CallHierarchyItem(serde_json::to_vec(lsp_item).unwrap())
+        }
+        LspHierarchyItem::Type(lsp_item) => {
+            proto::hierarchy_item::LspItem::TypeHierarchyItem(serde_json::to_vec(lsp_item).unwrap())
+        }
+    };
+    proto::HierarchyItem {
+        location: Some(location_to_proto(&item.location, lsp_store, peer_id, cx)),
+        lsp_item: Some(lsp_item),
+        server_id: item.server_id.to_proto(),
+    }
+}
+
+fn location_to_proto(
+    location: &Location,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut App,
+) -> proto::Location {
+    lsp_store
+        .buffer_store()
+        .update(cx, |buffer_store, cx| {
+            buffer_store.create_buffer_for_peer(&location.buffer, peer_id, cx)
+        })
+        .detach_and_log_err(cx);
+    proto::Location {
+        buffer_id: location.buffer.read(cx).remote_id().into(),
+        start: Some(serialize_anchor(&location.range.start)),
+        end: Some(serialize_anchor(&location.range.end)),
+    }
+}
+
+async fn hierarchy_items_from_proto(
+    response: proto::GetHierarchyItemsResponse,
+    lsp_store: &Entity<LspStore>,
+    cx: &mut AsyncApp,
+) -> Result<Vec<HierarchyItem>> {
+    let mut items = Vec::new();
+    for item in response.items {
+        items.push(hierarchy_item_from_proto(item, lsp_store, cx).await?);
+    }
+    Ok(items)
+}
+
+async fn hierarchy_calls_from_proto(
+    response: proto::GetHierarchyCallsResponse,
+    lsp_store: &Entity<LspStore>,
+    cx: &mut AsyncApp,
+) -> Result<Vec<HierarchyCall>> {
+    let mut calls = Vec::new();
+    for call in response.calls {
+        let item = call.item.context("missing hierarchy item")?;
+        let item = hierarchy_item_from_proto(item, lsp_store, cx).await?;
+        let mut call_sites = Vec::new();
+        for call_site in call.call_sites {
+            call_sites.push(location_from_proto(call_site, lsp_store, cx).await?);
+        }
+        calls.push(HierarchyCall { item, call_sites });
+    }
+    Ok(calls)
+}
+
+async fn hierarchy_item_from_proto(
+    item: proto::HierarchyItem,
+    lsp_store: &Entity<LspStore>,
+    cx: &mut AsyncApp,
+) -> Result<HierarchyItem> {
+    let location = item.location.context("missing item location")?;
+    let location = location_from_proto(location, lsp_store, cx).await?;
+    let lsp_item = match item.lsp_item.context("missing language server item")? {
+        proto::hierarchy_item::LspItem::CallHierarchyItem(lsp_item) => {
+            LspHierarchyItem::Call(serde_json::from_slice(&lsp_item)?)
+        }
+        proto::hierarchy_item::LspItem::TypeHierarchyItem(lsp_item) => {
+            LspHierarchyItem::Type(serde_json::from_slice(&lsp_item)?)
+        }
+    };
+    Ok(HierarchyItem::new(
+        location,
+        lsp_item,
+        LanguageServerId::from_proto(item.server_id),
+    ))
+}
+
+async fn location_from_proto(
+    location: proto::Location,
+    lsp_store: &Entity<LspStore>,
+    cx: &mut AsyncApp,
+) -> Result<Location> {
+    let buffer_id = BufferId::new(location.buffer_id)?;
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .context("missing location start")?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .context("missing location end")?;
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+        .await?;
+    Ok(Location {
+        buffer,
+        range: start..end,
+    })
+}
@@ -1,16 +1,17 @@
 pub mod clangd_ext;
 pub mod lsp_ext_command;
 pub mod rust_analyzer_ext;
+pub mod semantic_tokens;
 
 use crate::{
     CodeAction, ColorPresentation, Completion, CompletionResponse, CompletionSource,
     CoreCompletion, DocumentColor, Hover, InlayHint, LocationLink, LspAction, LspPullDiagnostics,
-    ProjectItem, ProjectPath, ProjectTransaction, PulledDiagnostics, ResolveState, Symbol,
-    ToolchainStore,
+    ProjectItem, ProjectPath, ProjectTransaction, PulledDiagnostics, ResolveState,
+    SnippetVariables, Symbol, ToolchainStore,
     buffer_store::{BufferStore, BufferStoreEvent},
     environment::ProjectEnvironment,
     lsp_command::{self, *},
-    lsp_store,
+    lsp_store::{self, semantic_tokens::ServerSemanticTokens},
     manifest_tree::{
         AdapterQuery, LanguageServerTree, LanguageServerTreeNode, LaunchDisposition,
         ManifestQueryDelegate, ManifestTree,
@@ -44,7 +45,7 @@ use language::{
     Bias, BinaryStatus, Buffer, BufferSnapshot, CachedLspAdapter, CodeLabel, Diagnostic,
     DiagnosticEntry, DiagnosticSet, DiagnosticSourceKind, Diff, File as _, Language, LanguageName,
     LanguageRegistry, LanguageToolchainStore, LocalFile, LspAdapter, LspAdapterDelegate, Patch,
-    PointUtf16, TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    PointUtf16, TextBufferSnapshot, ToOffset, ToPoint, ToPointUtf16, Transaction, Unclipped,
     language_settings::{
         FormatOnSave, Formatter, LanguageSettings, SelectedFormatter, language_settings,
     },
@@ -167,6 +168,10 @@ pub struct LocalLspStore {
             )>,
         >,
     >,
+    /// Diagnostics that don't come from language servers, such as the problems found in task
+    /// output, keyed by their source.
+    other_diagnostics:
+        HashMap<SharedString, HashMap<ProjectPath, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     _subscription: gpui::Subscription,
     lsp_tree: Entity<LanguageServerTree>,
@@ -880,6 +885,29 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), cx| {
+                    let this = this.clone();
+                    let mut cx = cx.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.invalidate_semantic_tokens();
+                            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
                 let this = this.clone();
@@ -2183,6 +2211,20 @@ impl LocalLspStore {
                 .log_err();
             }
         }
+        let project_path = ProjectPath {
+            worktree_id,
+            path: file.path().clone(),
+        };
+        for (source, diagnostics) in &self.other_diagnostics {
+            if let Some(diagnostics) = diagnostics.get(&project_path) {
+                update_buffer_other_diagnostics(
+                    buffer_handle,
+                    source.clone(),
+                    diagnostics.clone(),
+                    cx,
+                );
+            }
+        }
         let Some(language) = language else {
             return;
         };
@@ -2299,22 +2341,8 @@ impl LocalLspStore {
                 end = entry.range.end;
             }
 
-            let mut range = snapshot.clip_point_utf16(start, Bias::Left)
-                ..snapshot.clip_point_utf16(end, Bias::Right);
-
-            // Expand empty ranges by one codepoint
-            if range.start == range.end {
-                // This will be go to the next boundary when being clipped
-                range.end.column += 1;
-                range.end = snapshot.clip_point_utf16(Unclipped(range.end), Bias::Right);
-                if range.start == range.end && range.end.column > 0 {
-                    range.start.column -= 1;
-                    range.start = snapshot.clip_point_utf16(Unclipped(range.start), Bias::Left);
-                }
-            }
-
             sanitized_diagnostics.push(DiagnosticEntry {
-                range,
+                range: clip_diagnostic_range(&snapshot, start..end),
                 diagnostic: entry.diagnostic,
             });
         }
@@ -3053,8 +3081,19 @@ impl LocalLspStore {
                                         }
                                     }
                                     Edit::Snippet(edit) => {
-                                        let Ok(snippet) = Snippet::parse(&edit.snippet.value)
-                                        else {
+                                        let position = range_from_lsp(edit.range)
+                                            .start
+                                            .to_point(buffer_to_edit.read(cx));
+                                        let variables = SnippetVariables::new(
+                                            &buffer_to_edit,
+                                            position..position,
+                                            position,
+                                            cx,
+                                        );
+                                        let Ok(snippet) = Snippet::parse_with_variables(
+                                            &edit.snippet.value,
+                                            &variables,
+                                        ) else {
                                             continue;
                                         };
 
@@ -3552,6 +3591,7 @@ pub struct LspStore {
     diagnostic_summaries:
         HashMap<WorktreeId, HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>>,
     lsp_data: HashMap<BufferId, DocumentColorData>,
+    semantic_tokens_data: HashMap<BufferId, SemanticTokensData>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -3570,6 +3610,24 @@ struct DocumentColorData {
     colors_update: Option<(Global, DocumentColorTask)>,
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct SemanticTokens {
+    pub servers: HashMap<LanguageServerId, Arc<ServerSemanticTokens>>,
+    pub cache_version: Option<usize>,
+}
+
+type SemanticTokensTask = Shared<Task<std::result::Result<SemanticTokens, Arc<anyhow::Error>>>>;
+
+#[derive(Debug, Default)]
+struct SemanticTokensData {
+    /// The buffer version the tokens were fetched for, or `None` after a server asked
+    /// for them to be refreshed.
+    tokens_for_version: Option<Global>,
+    tokens: HashMap<LanguageServerId, Arc<ServerSemanticTokens>>,
+    cache_version: usize,
+    tokens_update: Option<(Global, SemanticTokensTask)>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ColorFetchStrategy {
     IgnoreCache,
@@ -3594,8 +3652,10 @@ pub enum LspStoreEvent {
     Notification(String),
     RefreshInlayHints,
     RefreshCodeLens,
+    RefreshSemanticTokens,
     DiagnosticsUpdated {
-        language_server_id: LanguageServerId,
+        /// `None` for diagnostics that don't come from a language server.
+        language_server_id: Option<LanguageServerId>,
         path: ProjectPath,
     },
     DiskBasedDiagnosticsStarted {
@@ -3652,6 +3712,9 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_entity_request_handler(Self::handle_refresh_inlay_hints);
         client.add_entity_request_handler(Self::handle_refresh_code_lens);
+        client.add_entity_request_handler(Self::handle_resolve_code_lens);
+        client.add_entity_request_handler(Self::handle_get_semantic_tokens);
+        client.add_entity_request_handler(Self::handle_refresh_semantic_tokens);
         client.add_entity_request_handler(Self::handle_on_type_formatting);
         client.add_entity_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_entity_request_handler(Self::handle_register_buffer_with_language_servers);
@@ -3666,6 +3729,14 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_entity_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_entity_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
 
         client.add_entity_request_handler(Self::handle_lsp_ext_cancel_flycheck);
         client.add_entity_request_handler(Self::handle_lsp_ext_run_flycheck);
@@ -3785,6 +3856,7 @@ impl LspStore {
                 yarn,
                 next_diagnostic_group_id: Default::default(),
                 diagnostics: Default::default(),
+                other_diagnostics: Default::default(),
                 _subscription: cx.on_app_quit(|this, cx| {
                     this.as_local_mut()
                         .unwrap()
@@ -3805,6 +3877,7 @@ impl LspStore {
             nonce: StdRng::from_entropy().r#gen(),
             diagnostic_summaries: HashMap::default(),
             lsp_data: HashMap::default(),
+            semantic_tokens_data: HashMap::default(),
             active_entry: None,
             _maintain_workspace_config,
             _maintain_buffer_languages: Self::maintain_buffer_languages(languages, cx),
@@ -3862,6 +3935,7 @@ impl LspStore {
             nonce: StdRng::from_entropy().r#gen(),
             diagnostic_summaries: HashMap::default(),
             lsp_data: HashMap::default(),
+            semantic_tokens_data: HashMap::default(),
             active_entry: None,
             toolchain_store,
             _maintain_workspace_config,
@@ -4163,6 +4237,7 @@ impl LspStore {
                     };
                     if refcount == 0 {
                         lsp_store.lsp_data.remove(&buffer_id);
+                        lsp_store.semantic_tokens_data.remove(&buffer_id);
                         let local = lsp_store.as_local_mut().unwrap();
                         local.registered_buffers.remove(&buffer_id);
                         local.buffers_opened_in_servers.remove(&buffer_id);
@@ -4987,6 +5062,50 @@ impl LspStore {
         }
     }
 
+    /// Resolves the command of a code lens, which servers may omit from the initial
+    /// response when it is costly to compute, like the number of references.
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: &Entity<Buffer>,
+        mut action: CodeAction,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<CodeAction>> {
+        if action.resolved {
+            return Task::ready(Ok(action));
+        }
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens_action: Some(Self::serialize_code_action(&action)),
+            };
+            cx.spawn(async move |_, _| {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("code lens proto request")?;
+                match response.lens_action {
+                    Some(resolved_action) => Self::deserialize_code_action(resolved_action)
+                        .context("code lens proto resolve response conversion"),
+                    None => Ok(action),
+                }
+            })
+        } else {
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, action.server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(action));
+            };
+            cx.spawn(async move |_, _| {
+                LocalLspStore::try_resolve_code_action(&lang_server, &mut action)
+                    .await
+                    .context("code lens resolve LSP request")?;
+                Ok(action)
+            })
+        }
+    }
+
     pub fn resolve_color_presentation(
         &mut self,
         mut color: DocumentColor,
@@ -6802,6 +6921,174 @@ impl LspStore {
         }
     }
 
+    /// Returns the semantic tokens of every language server for the buffer, or `None` if
+    /// they haven't changed since `known_cache_version`.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: Entity<Buffer>,
+        ignore_cache: bool,
+        known_cache_version: Option<usize>,
+        cx: &mut Context<Self>,
+    ) -> Option<SemanticTokensTask> {
+        let version_queried_for = buffer.read(cx).version();
+        let buffer_id = buffer.read(cx).remote_id();
+
+        let tokens_data = self.semantic_tokens_data.entry(buffer_id).or_default();
+        let is_cached = tokens_data
+            .tokens_for_version
+            .as_ref()
+            .is_some_and(|version| !version_queried_for.changed_since(version));
+        if !ignore_cache && is_cached {
+            if Some(tokens_data.cache_version) == known_cache_version {
+                return None;
+            }
+            return Some(
+                Task::ready(Ok(SemanticTokens {
+                    servers: tokens_data.tokens.clone(),
+                    cache_version: Some(tokens_data.cache_version),
+                }))
+                .shared(),
+            );
+        }
+        if let Some((updating_for, running_update)) = &tokens_data.tokens_update {
+            if !version_queried_for.changed_since(updating_for) {
+                return Some(running_update.clone());
+            }
+        }
+
+        let query_version_queried_for = version_queried_for.clone();
+        let new_task = cx
+            .spawn(async move |lsp_store, cx| {
+                cx.background_executor()
+                    .timer(Duration::from_millis(30))
+                    .await;
+                let fetched_tokens = lsp_store
+                    .update(cx, |lsp_store, cx| {
+                        lsp_store.fetch_semantic_tokens_for_buffer(&buffer, cx)
+                    })?
+                    .await
+                    .context("fetching semantic tokens")
+                    .map_err(Arc::new);
+                lsp_store
+                    .update(cx, |lsp_store, _| {
+                        let tokens_data =
+                            lsp_store.semantic_tokens_data.entry(buffer_id).or_default();
+                        tokens_data.tokens_update = None;
+                        let fetched_tokens = fetched_tokens?;
+                        let is_newer =
+                            tokens_data
+                                .tokens_for_version
+                                .as_ref()
+                                .is_none_or(|version| {
+                                    !version.changed_since(&query_version_queried_for)
+                                });
+                        if is_newer {
+                            tokens_data.tokens_for_version = Some(query_version_queried_for);
+                            tokens_data.tokens = fetched_tokens;
+                            tokens_data.cache_version += 1;
+                        }
+                        Ok(SemanticTokens {
+                            servers: tokens_data.tokens.clone(),
+                            cache_version: Some(tokens_data.cache_version),
+                        })
+                    })
+                    .map_err(Arc::new)?
+            })
+            .shared();
+        tokens_data.tokens_update = Some((version_queried_for, new_task.clone()));
+        Some(new_task)
+    }
+
+    fn fetch_semantic_tokens_for_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<HashMap<LanguageServerId, Arc<ServerSemanticTokens>>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request_task = client.request(proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer_id.to_proto(),
+                version: serialize_version(&buffer.read(cx).version()),
+            });
+            return cx.background_spawn(async move {
+                Ok(request_task
+                    .await?
+                    .servers
+                    .into_iter()
+                    .map(|tokens| {
+                        (
+                            LanguageServerId::from_proto(tokens.server_id),
+                            Arc::new(ServerSemanticTokens::from_proto(tokens)),
+                        )
+                    })
+                    .collect())
+            });
+        }
+
+        let Some(local) = self.as_local() else {
+            return Task::ready(Ok(HashMap::default()));
+        };
+        let Some(abs_path) = File::from_dyn(buffer.read(cx).file())
+            .and_then(File::as_local)
+            .map(|file| file.abs_path(cx))
+        else {
+            return Task::ready(Ok(HashMap::default()));
+        };
+        let uri = match file_path_to_lsp_url(&abs_path) {
+            Ok(uri) => uri,
+            Err(e) => return Task::ready(Err(e)),
+        };
+        let previous_tokens = self
+            .semantic_tokens_data
+            .get(&buffer_id)
+            .map(|tokens_data| tokens_data.tokens.clone())
+            .unwrap_or_default();
+        let servers = buffer.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .map(|(_, server)| server.clone())
+                .filter(|server| {
+                    local
+                        .buffers_opened_in_servers
+                        .get(&buffer_id)
+                        .is_some_and(|servers| servers.contains(&server.server_id()))
+                })
+                .collect::<Vec<_>>()
+        });
+        let requests = servers
+            .into_iter()
+            .filter_map(|server| {
+                let options =
+                    semantic_tokens::semantic_tokens_options(&server.capabilities())?.clone();
+                let server_id = server.server_id();
+                let previous = previous_tokens.get(&server_id).cloned();
+                let request = semantic_tokens::request_semantic_tokens(
+                    server,
+                    uri.clone(),
+                    options,
+                    previous,
+                );
+                Some(async move { (server_id, request.await) })
+            })
+            .collect::<Vec<_>>();
+        cx.background_spawn(async move {
+            Ok(join_all(requests)
+                .await
+                .into_iter()
+                .filter_map(|(server_id, tokens)| Some((server_id, Arc::new(tokens.log_err()?))))
+                .collect())
+        })
+    }
+
+    /// Makes the next request for semantic tokens query the language servers again,
+    /// keeping the current tokens as the base for delta requests.
+    fn invalidate_semantic_tokens(&mut self) {
+        for tokens_data in self.semantic_tokens_data.values_mut() {
+            tokens_data.tokens_for_version = None;
+        }
+    }
+
     pub fn signature_help<T: ToPointUtf16>(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -7145,9 +7432,31 @@ impl LspStore {
         &'a self,
         include_ignored: bool,
         cx: &'a App,
-    ) -> impl Iterator<Item = (ProjectPath, LanguageServerId, DiagnosticSummary)> + 'a {
-        self.worktree_store
-            .read(cx)
+    ) -> impl Iterator<Item = (ProjectPath, Option<LanguageServerId>, DiagnosticSummary)> + 'a {
+        let worktree_store = self.worktree_store.read(cx);
+        let other_summaries = self
+            .as_local()
+            .into_iter()
+            .flat_map(|local| local.other_diagnostics.values())
+            .flatten()
+            .filter_map(move |(project_path, diagnostics)| {
+                let worktree = worktree_store
+                    .worktree_for_id(project_path.worktree_id, cx)?
+                    .read(cx);
+                let included = worktree.is_visible()
+                    && (include_ignored
+                        || worktree
+                            .entry_for_path(project_path.path.as_ref())
+                            .map_or(false, |entry| !entry.is_ignored));
+                included.then(|| {
+                    (
+                        project_path.clone(),
+                        None,
+                        DiagnosticSummary::new(diagnostics),
+                    )
+                })
+            });
+        worktree_store
             .visible_worktrees(cx)
             .filter_map(|worktree| {
                 let worktree = worktree.read(cx);
@@ -7170,12 +7479,13 @@ impl LspStore {
                                     worktree_id,
                                     path: path.clone(),
                                 },
-                                *server_id,
+                                Some(*server_id),
                                 *summary,
                             )
                         })
                     })
             })
+            .chain(other_summaries)
     }
 
     pub fn on_buffer_edited(
@@ -7464,6 +7774,9 @@ impl LspStore {
     fn remove_worktree(&mut self, id_to_remove: WorktreeId, cx: &mut Context<Self>) {
         self.diagnostic_summaries.remove(&id_to_remove);
         if let Some(local) = self.as_local_mut() {
+            for diagnostics in local.other_diagnostics.values_mut() {
+                diagnostics.retain(|project_path, _| project_path.worktree_id != id_to_remove);
+            }
             let to_remove = local.remove_worktree(id_to_remove, cx);
             for server in to_remove {
                 self.language_server_statuses.remove(&server);
@@ -7588,6 +7901,71 @@ impl LspStore {
         )
     }
 
+    /// Replaces all diagnostics reported by the given non-language-server source, such as a
+    /// task whose output was scanned for problems. Paths outside of the project are skipped.
+    pub fn update_other_diagnostics(
+        &mut self,
+        source: SharedString,
+        diagnostics: HashMap<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let worktree_store = self.worktree_store.read(cx);
+        let mut new_diagnostics = HashMap::default();
+        for (abs_path, diagnostics) in diagnostics {
+            let Some((worktree, relative_path)) = worktree_store.find_worktree(&abs_path, cx)
+            else {
+                log::warn!(
+                    "skipping {source} diagnostics, no worktree found for path {abs_path:?}"
+                );
+                continue;
+            };
+            let project_path = ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: relative_path.into(),
+            };
+            new_diagnostics
+                .entry(project_path)
+                .or_insert_with(Vec::new)
+                .extend(diagnostics);
+        }
+
+        let local = self
+            .as_local_mut()
+            .context("cannot update other diagnostics on a remote LspStore")?;
+        for diagnostics in new_diagnostics.values_mut() {
+            let mut group_ids = HashMap::default();
+            for entry in diagnostics.iter_mut() {
+                entry.diagnostic.group_id = *group_ids
+                    .entry(entry.diagnostic.group_id)
+                    .or_insert_with(|| post_inc(&mut local.next_diagnostic_group_id));
+            }
+        }
+        let old_diagnostics = if new_diagnostics.is_empty() {
+            local.other_diagnostics.remove(&source)
+        } else {
+            local
+                .other_diagnostics
+                .insert(source.clone(), new_diagnostics.clone())
+        }
+        .unwrap_or_default();
+
+        let cleared_paths = old_diagnostics
+            .into_keys()
+            .filter(|project_path| !new_diagnostics.contains_key(project_path))
+            .map(|project_path| (project_path, Vec::new()))
+            .collect::<Vec<_>>();
+        for (project_path, diagnostics) in cleared_paths.into_iter().chain(new_diagnostics) {
+            if let Some(buffer) = self.buffer_store.read(cx).get_by_path(&project_path) {
+                update_buffer_other_diagnostics(&buffer, source.clone(), diagnostics, cx);
+            }
+            cx.emit(LspStoreEvent::DiagnosticsUpdated {
+                language_server_id: None,
+                path: project_path,
+            });
+        }
+        Ok(())
+    }
+
     pub fn merge_diagnostic_entries(
         &mut self,
         server_id: LanguageServerId,
@@ -7656,7 +8034,7 @@ impl LspStore {
         })?;
         if updated {
             cx.emit(LspStoreEvent::DiagnosticsUpdated {
-                language_server_id: server_id,
+                language_server_id: Some(server_id),
                 path: project_path,
             })
         }
@@ -8655,7 +9033,7 @@ impl LspStore {
                         .log_err();
                 }
                 cx.emit(LspStoreEvent::DiagnosticsUpdated {
-                    language_server_id: LanguageServerId(message.language_server_id as usize),
+                    language_server_id: Some(LanguageServerId(message.language_server_id as usize)),
                     path: project_path,
                 });
             }
@@ -9573,6 +9951,30 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let action = envelope
+            .payload
+            .lens_action
+            .context("missing code lens to resolve")?;
+        let action = Self::deserialize_code_action(action)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let resolved_action = this
+            .update(&mut cx, |this, cx| {
+                this.resolve_code_lens(&buffer, action, cx)
+            })?
+            .await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens_action: Some(Self::serialize_code_action(&resolved_action)),
+        })
+    }
+
     async fn handle_refresh_code_lens(
         this: Entity<Self>,
         _: TypedEnvelope<proto::RefreshCodeLens>,
@@ -9584,6 +9986,47 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_get_semantic_tokens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let version = deserialize_version(&envelope.payload.version);
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| buffer.wait_for_version(version))?
+            .await?;
+        let tokens_task = this.update(&mut cx, |this, cx| {
+            this.semantic_tokens(buffer, false, None, cx)
+        })?;
+        let Some(tokens_task) = tokens_task else {
+            return Ok(proto::GetSemanticTokensResponse::default());
+        };
+        let tokens = tokens_task.await.map_err(|e| anyhow!("{e:#}"))?;
+        Ok(proto::GetSemanticTokensResponse {
+            servers: tokens
+                .servers
+                .iter()
+                .map(|(server_id, tokens)| tokens.to_proto(server_id.to_proto()))
+                .collect(),
+        })
+    }
+
+    async fn handle_refresh_semantic_tokens(
+        this: Entity<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            this.invalidate_semantic_tokens();
+            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -11225,6 +11668,10 @@ impl LspStore {
             buffer_lsp_data.colors.remove(&for_server);
             buffer_lsp_data.cache_version += 1;
         }
+        for buffer_tokens_data in self.semantic_tokens_data.values_mut() {
+            buffer_tokens_data.tokens.remove(&for_server);
+            buffer_tokens_data.cache_version += 1;
+        }
         if let Some(local) = self.as_local_mut() {
             local.buffer_pull_diagnostics_result_ids.remove(&for_server);
             for buffer_servers in local.buffers_opened_in_servers.values_mut() {
@@ -11515,6 +11962,58 @@ fn lsp_workspace_diagnostics_refresh(
     Some((tx, workspace_query_language_server))
 }
 
+fn clip_diagnostic_range(
+    snapshot: &TextBufferSnapshot,
+    range: Range<Unclipped<PointUtf16>>,
+) -> Range<PointUtf16> {
+    let mut range = snapshot.clip_point_utf16(range.start, Bias::Left)
+        ..snapshot.clip_point_utf16(range.end, Bias::Right);
+
+    // Expand empty ranges by one codepoint
+    if range.start == range.end {
+        // This will be go to the next boundary when being clipped
+        range.end.column += 1;
+        range.end = snapshot.clip_point_utf16(Unclipped(range.end), Bias::Right);
+        if range.start == range.end && range.end.column > 0 {
+            range.start.column -= 1;
+            range.start = snapshot.clip_point_utf16(Unclipped(range.start), Bias::Left);
+        }
+    }
+    range
+}
+
+fn update_buffer_other_diagnostics(
+    buffer: &Entity<Buffer>,
+    source: SharedString,
+    diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
+    cx: &mut App,
+) {
+    buffer.update(cx, |buffer, cx| {
+        let snapshot = buffer.text_snapshot();
+        let edits_since_save = Patch::new(
+            snapshot
+                .edits_since::<PointUtf16>(buffer.saved_version())
+                .collect(),
+        );
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|entry| {
+                let mut range = entry.range;
+                if entry.diagnostic.is_disk_based {
+                    range = Unclipped(edits_since_save.old_to_new(range.start.0))
+                        ..Unclipped(edits_since_save.old_to_new(range.end.0));
+                }
+                DiagnosticEntry {
+                    range: clip_diagnostic_range(&snapshot, range),
+                    diagnostic: entry.diagnostic,
+                }
+            })
+            .collect::<Vec<_>>();
+        let set = DiagnosticSet::new(diagnostics, &snapshot);
+        buffer.update_other_diagnostics(source, set, cx);
+    });
+}
+
 fn resolve_word_completion(snapshot: &BufferSnapshot, completion: &mut Completion) {
     let CompletionSource::BufferWord {
         word_range,
@@ -0,0 +1,301 @@
+use std::{cmp, ops::Range, sync::Arc};
+
+use anyhow::Result;
+use gpui::SharedString;
+use language::PointUtf16;
+use lsp::LanguageServer;
+use rpc::proto;
+
+/// Semantic tokens reported by one language server for a buffer, kept in the
+/// relative encoding of the protocol so that deltas can be applied to them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ServerSemanticTokens {
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+    pub token_types: Arc<[SharedString]>,
+    pub token_modifiers: Arc<[SharedString]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: Range<PointUtf16>,
+    pub token_type: SharedString,
+    pub modifiers: Vec<SharedString>,
+}
+
+impl ServerSemanticTokens {
+    /// Decodes the tokens, skipping those whose type is not in the server's legend.
+    pub fn tokens(&self) -> impl Iterator<Item = SemanticToken> + '_ {
+        let mut row = 0;
+        let mut column = 0;
+        self.data.chunks_exact(5).filter_map(move |token| {
+            let (delta_line, delta_start, length) = (token[0], token[1], token[2]);
+            if delta_line > 0 {
+                row += delta_line;
+                column = delta_start;
+            } else {
+                column += delta_start;
+            }
+            let token_type = self.token_types.get(token[3] as usize)?.clone();
+            let modifiers = self
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .take(u32::BITS as usize)
+                .filter(|(bit, _)| token[4] & (1 << bit) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect();
+            Some(SemanticToken {
+                range: PointUtf16::new(row, column)..PointUtf16::new(row, column + length),
+                token_type,
+                modifiers,
+            })
+        })
+    }
+
+    pub(super) fn to_proto(&self, server_id: u64) -> proto::ServerSemanticTokens {
+        proto::ServerSemanticTokens {
+            server_id,
+            token_types: self.token_types.iter().map(ToString::to_string).collect(),
+            token_modifiers: self
+                .token_modifiers
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+
+    pub(super) fn from_proto(tokens: proto::ServerSemanticTokens) -> Self {
+        Self {
+            result_id: None,
+            data: tokens.data,
+            token_types: tokens.token_types.into_iter().map(Into::into).collect(),
+            token_modifiers: tokens.token_modifiers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+pub(super) fn semantic_tokens_options(
+    capabilities: &lsp::ServerCapabilities,
+) -> Option<&lsp::SemanticTokensOptions> {
+    let options = match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options,
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            &options.semantic_tokens_options
+        }
+    };
+    let supports_full = options
+        .full
+        .as_ref()
+        .is_some_and(|full| !matches!(full, lsp::SemanticTokensFullOptions::Bool(false)));
+    supports_full.then_some(options)
+}
+
+/// Requests the tokens of a document, as a delta against the previous result when
+/// the server supports it, falling back to the full set of tokens otherwise.
+pub(super) async fn request_semantic_tokens(
+    server: Arc<LanguageServer>,
+    uri: lsp::Url,
+    options: lsp::SemanticTokensOptions,
+    previous: Option<Arc<ServerSemanticTokens>>,
+) -> Result<ServerSemanticTokens> {
+    let text_document = lsp::TextDocumentIdentifier::new(uri);
+    let token_types: Arc<[SharedString]> = options
+        .legend
+        .token_types
+        .iter()
+        .map(|token_type| SharedString::from(token_type.as_str().to_string()))
+        .collect();
+    let token_modifiers: Arc<[SharedString]> = options
+        .legend
+        .token_modifiers
+        .iter()
+        .map(|modifier| SharedString::from(modifier.as_str().to_string()))
+        .collect();
+    let supports_delta = matches!(
+        options.full,
+        Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+    );
+
+    let delta_base = previous
+        .filter(|_| supports_delta)
+        .and_then(|previous| Some((previous.result_id.clone()?, previous)));
+    if let Some((previous_result_id, previous)) = delta_base {
+        let response = server
+            .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                lsp::SemanticTokensDeltaParams {
+                    text_document: text_document.clone(),
+                    previous_result_id,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            )
+            .await
+            .into_response();
+        match response {
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta))) => {
+                let mut data = previous.data.clone();
+                apply_semantic_token_edits(&mut data, delta.edits);
+                return Ok(ServerSemanticTokens {
+                    result_id: delta.result_id,
+                    data,
+                    token_types,
+                    token_modifiers,
+                });
+            }
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens))) => {
+                return Ok(ServerSemanticTokens {
+                    result_id: tokens.result_id,
+                    data: flatten_semantic_tokens(tokens.data),
+                    token_types,
+                    token_modifiers,
+                });
+            }
+            Ok(_) => {}
+            Err(error) => log::warn!(
+                "semantic tokens delta via {} failed, requesting all tokens: {error:#}",
+                server.name()
+            ),
+        }
+    }
+
+    let response = server
+        .request::<lsp::request::SemanticTokensFullRequest>(lsp::SemanticTokensParams {
+            text_document,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .into_response()?;
+    let (result_id, data) = match response {
+        Some(lsp::SemanticTokensResult::Tokens(tokens)) => (tokens.result_id, tokens.data),
+        Some(lsp::SemanticTokensResult::Partial(partial)) => (None, partial.data),
+        None => (None, Vec::new()),
+    };
+    Ok(ServerSemanticTokens {
+        result_id,
+        data: flatten_semantic_tokens(data),
+        token_types,
+        token_modifiers,
+    })
+}
+
+fn flatten_semantic_tokens(tokens: Vec<lsp::SemanticToken>) -> Vec<u32> {
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+/// Applies the edits of a delta response to the data they were computed against.
+/// Edit offsets refer to the original data, so they're applied from last to first.
+fn apply_semantic_token_edits(data: &mut Vec<u32>, mut edits: Vec<lsp::SemanticTokensEdit>) {
+    edits.sort_by_key(|edit| cmp::Reverse(edit.start));
+    for edit in edits {
+        let start = (edit.start as usize).min(data.len());
+        let end = (start + edit.delete_count as usize).min(data.len());
+        data.splice(
+            start..end,
+            flatten_semantic_tokens(edit.data.unwrap_or_default()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(
+        delta_line: u32,
+        delta_start: u32,
+        length: u32,
+        token_type: u32,
+    ) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn test_decode_semantic_tokens() {
+        let tokens = ServerSemanticTokens {
+            result_id: None,
+            data: vec![
+                0, 3, 4, 0, 0b01, 0, 5, 2, 2, 0, 2, 1, 3, 1, 0b10, 0, 4, 1, 9, 0,
+            ],
+            token_types: Arc::from(["function".into(), "variable".into(), "macro".into()]),
+            token_modifiers: Arc::from(["declaration".into(), "mutable".into()]),
+        };
+        assert_eq!(
+            tokens.tokens().collect::<Vec<_>>(),
+            [
+                SemanticToken {
+                    range: PointUtf16::new(0, 3)..PointUtf16::new(0, 7),
+                    token_type: "function".into(),
+                    modifiers: vec!["declaration".into()],
+                },
+                SemanticToken {
+                    range: PointUtf16::new(0, 8)..PointUtf16::new(0, 10),
+                    token_type: "macro".into(),
+                    modifiers: Vec::new(),
+                },
+                SemanticToken {
+                    range: PointUtf16::new(2, 1)..PointUtf16::new(2, 4),
+                    token_type: "variable".into(),
+                    modifiers: vec!["mutable".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_semantic_token_edits() {
+        let mut data = flatten_semantic_tokens(vec![
+            token(0, 0, 1, 0),
+            token(1, 0, 1, 0),
+            token(1, 0, 1, 0),
+        ]);
+        apply_semantic_token_edits(
+            &mut data,
+            vec![
+                lsp::SemanticTokensEdit {
+                    start: 5,
+                    delete_count: 5,
+                    data: None,
+                },
+                lsp::SemanticTokensEdit {
+                    start: 15,
+                    delete_count: 0,
+                    data: Some(vec![token(2, 4, 3, 1)]),
+                },
+                lsp::SemanticTokensEdit {
+                    start: 0,
+                    delete_count: 0,
+                    data: Some(vec![token(0, 0, 2, 1)]),
+                },
+            ],
+        );
+        assert_eq!(
+            data,
+            flatten_semantic_tokens(vec![
+                token(0, 0, 2, 1),
+                token(0, 0, 1, 0),
+                token(1, 0, 1, 0),
+                token(2, 4, 3, 1),
+            ])
+        );
+    }
+}
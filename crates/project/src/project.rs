@@ -12,6 +12,7 @@ mod manifest_tree;
 pub mod prettier_store;
 pub mod project_settings;
 pub mod search;
+mod snippet_variables;
 mod task_inventory;
 pub mod task_store;
 pub mod terminals;
@@ -101,6 +102,7 @@ use settings::{InvalidSettingsError, Settings, SettingsLocation, SettingsStore};
 use smol::channel::Receiver;
 use snippet::Snippet;
 use snippet_provider::SnippetProvider;
+pub use snippet_variables::SnippetVariables;
 use std::{
     borrow::Cow,
     ops::Range,
@@ -131,8 +133,8 @@ pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
 pub use task_inventory::{
-    BasicContextProvider, ContextProviderWithTasks, DebugScenarioContext, Inventory, TaskContexts,
-    TaskSourceKind,
+    BasicContextProvider, ContextProviderWithTasks, DebugScenarioContext, Inventory,
+    ResolvedTaskTree, TaskContexts, TaskSourceKind,
 };
 
 pub use buffer_store::ProjectTransaction;
@@ -300,7 +302,8 @@ pub enum Event {
     },
     DiagnosticsUpdated {
         path: ProjectPath,
-        language_server_id: LanguageServerId,
+        /// `None` for diagnostics that don't come from a language server.
+        language_server_id: Option<LanguageServerId>,
     },
     RemoteIdChanged(Option<u64>),
     DisconnectedFromHost,
@@ -318,6 +321,7 @@ pub enum Event {
     Rejoined,
     RefreshInlayHints,
     RefreshCodeLens,
+    RefreshSemanticTokens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
     ExpandedAllForEntry(WorktreeId, ProjectEntryId),
@@ -706,6 +710,75 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol in a call or type hierarchy, as reported by a language server.
+#[derive(Debug, Clone)]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The range of the symbol's name.
+    pub location: Location,
+    pub(crate) lsp_item: LspHierarchyItem,
+    /// The server that reported the item, which is asked for its calls or relatives.
+    pub(crate) server_id: LanguageServerId,
+}
+
+/// The item the language server reported, which is sent back to it as is when
+/// requesting the calls or the relatives of a symbol.
+#[derive(Debug, Clone)]
+pub(crate) enum LspHierarchyItem {
+    Call(lsp::CallHierarchyItem),
+    Type(lsp::TypeHierarchyItem),
+}
+
+impl HierarchyItem {
+    pub(crate) fn new(
+        location: Location,
+        lsp_item: LspHierarchyItem,
+        server_id: LanguageServerId,
+    ) -> Self {
+        let (name, kind, detail) = match &lsp_item {
+            LspHierarchyItem::Call(item) => (item.name.clone(), item.kind, item.detail.clone()),
+            LspHierarchyItem::Type(item) => (item.name.clone(), item.kind, item.detail.clone()),
+        };
+        Self {
+            name,
+            kind,
+            detail,
+            location,
+            lsp_item,
+            server_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: HierarchyItem,
+    /// The ranges of the calls, which are always located in the calling symbol.
+    pub call_sites: Vec<Location>,
+}
+
+/// A range of lines that a language server suggests to fold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldingRange {
+    /// Spans from the end of the range's first line, which stays visible when folded,
+    /// to the end of its last line.
+    pub range: Range<language::Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// A link from a range of a document to another document or a web page, as reported
+/// by a language server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentLink {
+    pub range: Range<language::Anchor>,
+    /// The URI the link points to.
+    pub target: String,
+    pub tooltip: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -1126,6 +1199,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problems: Default::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1290,6 +1364,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problems: Default::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1542,6 +1617,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problems: Default::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -2878,6 +2954,7 @@ impl Project {
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
             LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -3443,6 +3520,134 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<HierarchyCall>>> {
+        let LspHierarchyItem::Call(lsp_item) = &item.lsp_item else {
+            return Task::ready(Err(anyhow!("{} is not a call hierarchy item", item.name)));
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls {
+                item: lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<HierarchyCall>>> {
+        let LspHierarchyItem::Call(lsp_item) = &item.lsp_item else {
+            return Task::ready(Err(anyhow!("{} is not a call hierarchy item", item.name)));
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls {
+                item: lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let LspHierarchyItem::Type(lsp_item) = &item.lsp_item else {
+            return Task::ready(Err(anyhow!("{} is not a type hierarchy item", item.name)));
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes {
+                item: lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: &HierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let LspHierarchyItem::Type(lsp_item) = &item.lsp_item else {
+            return Task::ready(Err(anyhow!("{} is not a type hierarchy item", item.name)));
+        };
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes {
+                item: lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    pub fn document_links(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            GetDocumentLinks,
+            cx,
+        )
+    }
+
     pub fn symbols(&self, query: &str, cx: &mut Context<Self>) -> Task<Result<Vec<Symbol>>> {
         self.lsp_store
             .update(cx, |lsp_store, cx| lsp_store.symbols(query, cx))
@@ -4234,7 +4439,7 @@ impl Project {
         &'a self,
         include_ignored: bool,
         cx: &'a App,
-    ) -> impl Iterator<Item = (ProjectPath, LanguageServerId, DiagnosticSummary)> + 'a {
+    ) -> impl Iterator<Item = (ProjectPath, Option<LanguageServerId>, DiagnosticSummary)> + 'a {
         self.lsp_store
             .read(cx)
             .diagnostic_summaries(include_ignored, cx)
@@ -1530,7 +1530,7 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
                     worktree_id: main_worktree_id,
                     path: Arc::from(Path::new("b.rs")),
                 },
-                server_id,
+                Some(server_id),
                 DiagnosticSummary {
                     error_count: 1,
                     warning_count: 0,
@@ -1542,6 +1542,174 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_other_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "let a = 1;",
+            "b.rs": "let b = 2;"
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let buffer_a = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let diagnostic = |range: Range<PointUtf16>, severity, message: &str| DiagnosticEntry {
+        range: Unclipped(range.start)..Unclipped(range.end),
+        diagnostic: Diagnostic {
+            severity,
+            message: message.to_string(),
+            is_primary: true,
+            ..Default::default()
+        },
+    };
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_other_diagnostics(
+                "tasks".into(),
+                HashMap::from_iter([
+                    (
+                        PathBuf::from(path!("/dir/a.rs")),
+                        vec![diagnostic(
+                            PointUtf16::new(0, 4)..PointUtf16::new(0, 5),
+                            DiagnosticSeverity::ERROR,
+                            "error 1",
+                        )],
+                    ),
+                    (
+                        PathBuf::from(path!("/dir/b.rs")),
+                        vec![diagnostic(
+                            PointUtf16::new(0, 0)..PointUtf16::new(0, u32::MAX),
+                            DiagnosticSeverity::WARNING,
+                            "warning 1",
+                        )],
+                    ),
+                ]),
+                cx,
+            )
+            .unwrap();
+    });
+
+    buffer_a.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("let ".to_string(), None),
+                ("a".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" = 1;".to_string(), None),
+            ]
+        );
+    });
+    let buffer_b = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/b.rs"), cx)
+        })
+        .await
+        .unwrap();
+    buffer_b.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [("let b = 2;".to_string(), Some(DiagnosticSeverity::WARNING))],
+            "buffers opened later should get the diagnostics too"
+        );
+    });
+    project.update(cx, |project, cx| {
+        let mut summaries = project.diagnostic_summaries(false, cx).collect::<Vec<_>>();
+        summaries.sort_by_key(|(path, _, _)| path.clone());
+        assert_eq!(
+            summaries,
+            [
+                (
+                    ProjectPath {
+                        worktree_id,
+                        path: Arc::from(Path::new("a.rs")),
+                    },
+                    None,
+                    DiagnosticSummary {
+                        error_count: 1,
+                        warning_count: 0,
+                    }
+                ),
+                (
+                    ProjectPath {
+                        worktree_id,
+                        path: Arc::from(Path::new("b.rs")),
+                    },
+                    None,
+                    DiagnosticSummary {
+                        error_count: 0,
+                        warning_count: 1,
+                    }
+                ),
+            ]
+        );
+    });
+
+    // Updating a source replaces all of its diagnostics.
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_other_diagnostics(
+                "tasks".into(),
+                HashMap::from_iter([(
+                    PathBuf::from(path!("/dir/b.rs")),
+                    vec![diagnostic(
+                        PointUtf16::new(0, 4)..PointUtf16::new(0, 5),
+                        DiagnosticSeverity::ERROR,
+                        "error 2",
+                    )],
+                )]),
+                cx,
+            )
+            .unwrap();
+    });
+    buffer_a.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [("let a = 1;".to_string(), None)]
+        );
+    });
+    buffer_b.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("let ".to_string(), None),
+                ("b".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" = 2;".to_string(), None),
+            ]
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summaries(false, cx).collect::<Vec<_>>(),
+            [(
+                ProjectPath {
+                    worktree_id,
+                    path: Arc::from(Path::new("b.rs")),
+                },
+                None,
+                DiagnosticSummary {
+                    error_count: 1,
+                    warning_count: 0,
+                }
+            )]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1617,7 +1785,7 @@ async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     assert_eq!(
         events.next().await.unwrap(),
         Event::DiagnosticsUpdated {
-            language_server_id: LanguageServerId(0),
+            language_server_id: Some(LanguageServerId(0)),
             path: (worktree_id, Path::new("a.rs")).into()
         }
     );
@@ -1626,7 +1794,7 @@ async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     assert_eq!(
         events.next().await.unwrap(),
         Event::DiskBasedDiagnosticsFinished {
-            language_server_id: LanguageServerId(0)
+            language_server_id: Some(LanguageServerId(0))
         }
     );
 
@@ -1665,7 +1833,7 @@ async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     assert_eq!(
         events.next().await.unwrap(),
         Event::DiagnosticsUpdated {
-            language_server_id: LanguageServerId(0),
+            language_server_id: Some(LanguageServerId(0)),
             path: (worktree_id, Path::new("a.rs")).into()
         }
     );
@@ -3047,6 +3215,228 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn a() {}",
+            "b.rs": "fn b() {\n    crate::a();\n}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let lsp_item = |name: &str, path: &str, range: lsp::Range| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range,
+        selection_range: range,
+        data: None,
+    };
+    let a_range = lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4));
+    let b_range = lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4));
+    fake_server.set_request_handler::<lsp::request::CallHierarchyPrepare, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 3)
+            );
+            Ok(Some(vec![lsp_item("a", path!("/dir/a.rs"), a_range)]))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "a");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("b", path!("/dir/b.rs"), b_range),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(1, 11),
+                    lsp::Position::new(1, 12),
+                )],
+            }]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 3, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "a");
+    assert_eq!(items[0].kind, lsp::SymbolKind::FUNCTION);
+
+    let calls = project
+        .update(cx, |project, cx| project.incoming_calls(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    cx.update(|cx| {
+        let caller = &calls[0].item;
+        assert_eq!(caller.name, "b");
+        let caller_buffer = caller.location.buffer.read(cx);
+        assert_eq!(
+            caller_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new(path!("/dir/b.rs")),
+        );
+        assert_eq!(caller.location.range.to_offset(caller_buffer), 3..4);
+        assert_eq!(
+            calls[0]
+                .call_sites
+                .iter()
+                .map(|call_site| call_site.range.to_offset(caller_buffer))
+                .collect::<Vec<_>>(),
+            [20..21]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "trait T {}\nstruct S;\nimpl T for S {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    // Only the second server supports type hierarchies, so it's the one asked for them.
+    let mut servers_without_support = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            name: "no-type-hierarchy-server",
+            ..Default::default()
+        },
+    );
+    let mut servers_with_support = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            name: "type-hierarchy-server",
+            capabilities: lsp::ServerCapabilities {
+                experimental: Some(json!({ "typeHierarchyProvider": true })),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let _server_without_support = servers_without_support.next().await.unwrap();
+    let fake_server = servers_with_support.next().await.unwrap();
+    let lsp_item = |name: &str, range: lsp::Range| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::STRUCT,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path!("/dir/a.rs")).unwrap(),
+        range,
+        selection_range: range,
+        data: None,
+    };
+    let t_range = lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 7));
+    let s_range = lsp::Range::new(lsp::Position::new(1, 7), lsp::Position::new(1, 8));
+    fake_server.set_request_handler::<lsp::request::TypeHierarchyPrepare, _, _>(
+        move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 7)
+            );
+            Ok(Some(vec![lsp_item("S", s_range)]))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::TypeHierarchySupertypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "S");
+            Ok(Some(vec![lsp_item("T", t_range)]))
+        },
+    );
+    fake_server.set_request_handler::<lsp::request::TypeHierarchySubtypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "T");
+            Ok(Some(vec![lsp_item("S", s_range)]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(1, 7), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "S");
+    assert_eq!(items[0].server_id, fake_server.server.server_id());
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].name, "T");
+    cx.update(|cx| {
+        let buffer = supertypes[0].location.buffer.read(cx);
+        assert_eq!(supertypes[0].location.range.to_offset(buffer), 6..7);
+    });
+
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&supertypes[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        subtypes.iter().map(|item| &item.name).collect::<Vec<_>>(),
+        ["S"]
+    );
+}
+
 #[gpui::test]
 async fn test_completions_with_text_edit(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -0,0 +1,156 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use chrono::{DateTime, Local};
+use gpui::{App, Entity};
+use language::{Buffer, Point};
+use snippet::VariableResolver;
+
+/// Values for the variables that snippets can reference, such as `$TM_FILENAME`
+/// or `$CURRENT_YEAR`, captured from a buffer when a snippet is inserted.
+///
+/// See https://code.visualstudio.com/docs/editor/userdefinedsnippets#_variables
+pub struct SnippetVariables {
+    selected_text: String,
+    current_line: String,
+    current_word: String,
+    line_index: u32,
+    file_path: Option<Arc<Path>>,
+    relative_file_path: Option<Arc<Path>>,
+    workspace_folder: Option<Arc<Path>>,
+    clipboard: Option<String>,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+    now: DateTime<Local>,
+}
+
+impl SnippetVariables {
+    /// Captures the variables for a snippet inserted with the cursor at `cursor`, and
+    /// `selection` selected, in buffer coordinates.
+    pub fn new(buffer: &Entity<Buffer>, selection: Range<Point>, cursor: Point, cx: &App) -> Self {
+        let buffer = buffer.read(cx);
+        let snapshot = buffer.snapshot();
+        let row = cursor.row;
+
+        let selected_text = snapshot.text_for_range(selection).collect();
+        let current_line = snapshot
+            .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+            .collect();
+        let (word_range, _) = snapshot.surrounding_word(cursor);
+        let current_word = snapshot.text_for_range(word_range).collect();
+
+        let language_scope = snapshot.language_scope_at(cursor);
+        let line_comment = language_scope.as_ref().and_then(|scope| {
+            scope
+                .line_comment_prefixes()
+                .first()
+                .map(|prefix| prefix.trim().to_string())
+        });
+        let block_comment = language_scope.as_ref().and_then(|scope| {
+            scope
+                .block_comment_delimiters()
+                .map(|(start, end)| (start.trim().to_string(), end.trim().to_string()))
+        });
+
+        let file = buffer.file();
+        let relative_file_path = file.map(|file| file.path().clone());
+        let file_path = file.map(|file| match file.as_local() {
+            Some(file) => file.abs_path(cx).into(),
+            None => file.full_path(cx).into(),
+        });
+        let workspace_folder = file.and_then(|file| {
+            let abs_path = file.as_local()?.abs_path(cx);
+            let depth = file.path().components().count();
+            abs_path.ancestors().nth(depth).map(Into::into)
+        });
+
+        Self {
+            selected_text,
+            current_line,
+            current_word,
+            line_index: row,
+            file_path,
+            relative_file_path,
+            workspace_folder,
+            clipboard: cx.read_from_clipboard().and_then(|item| item.text()),
+            line_comment,
+            block_comment,
+            now: Local::now(),
+        }
+    }
+}
+
+impl VariableResolver for SnippetVariables {
+    fn resolve(&self, name: &str) -> Option<String> {
+        let path_string = |path: &Option<Arc<Path>>| {
+            path.as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let date = |format: &str| self.now.format(format).to_string();
+
+        let value = match name {
+            "TM_SELECTED_TEXT" => self.selected_text.clone(),
+            "TM_CURRENT_LINE" => self.current_line.clone(),
+            "TM_CURRENT_WORD" => self.current_word.clone(),
+            "TM_LINE_INDEX" => self.line_index.to_string(),
+            "TM_LINE_NUMBER" => (self.line_index + 1).to_string(),
+            "TM_FILENAME" => self
+                .file_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "TM_FILENAME_BASE" => self
+                .file_path
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "TM_DIRECTORY" => self
+                .file_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .map(|parent| parent.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "TM_FILEPATH" => path_string(&self.file_path),
+            "RELATIVE_FILEPATH" => path_string(&self.relative_file_path),
+            "WORKSPACE_FOLDER" => path_string(&self.workspace_folder),
+            "WORKSPACE_NAME" => self
+                .workspace_folder
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "CLIPBOARD" => self.clipboard.clone().unwrap_or_default(),
+            "CURRENT_YEAR" => date("%Y"),
+            "CURRENT_YEAR_SHORT" => date("%y"),
+            "CURRENT_MONTH" => date("%m"),
+            "CURRENT_MONTH_NAME" => date("%B"),
+            "CURRENT_MONTH_NAME_SHORT" => date("%b"),
+            "CURRENT_DATE" => date("%d"),
+            "CURRENT_DAY_NAME" => date("%A"),
+            "CURRENT_DAY_NAME_SHORT" => date("%a"),
+            "CURRENT_HOUR" => date("%H"),
+            "CURRENT_MINUTE" => date("%M"),
+            "CURRENT_SECOND" => date("%S"),
+            "CURRENT_SECONDS_UNIX" => self.now.timestamp().to_string(),
+            "CURRENT_TIMEZONE_OFFSET" => date("%:z"),
+            "RANDOM" => format!("{:06}", rand::random::<u32>() % 1_000_000),
+            "RANDOM_HEX" => format!("{:06x}", rand::random::<u32>() & 0xff_ffff),
+            "UUID" => uuid::Uuid::new_v4().to_string(),
+            "LINE_COMMENT" => self.line_comment.clone().unwrap_or_default(),
+            "BLOCK_COMMENT_START" => self
+                .block_comment
+                .as_ref()
+                .map(|(start, _)| start.clone())
+                .unwrap_or_default(),
+            "BLOCK_COMMENT_END" => self
+                .block_comment
+                .as_ref()
+                .map(|(_, end)| end.clone())
+                .unwrap_or_default(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
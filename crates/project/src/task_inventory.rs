@@ -8,7 +8,7 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, bail};
 use collections::{HashMap, HashSet, VecDeque};
 use dap::DapRegistry;
 use fs::Fs;
@@ -38,6 +38,14 @@ pub struct DebugScenarioContext {
     pub active_buffer: Option<WeakEntity<Buffer>>,
 }
 
+/// A resolved task together with the tasks it depends on, which need to finish successfully before it is spawned.
+#[derive(Debug, Clone)]
+pub struct ResolvedTaskTree {
+    pub source_kind: TaskSourceKind,
+    pub task: ResolvedTask,
+    pub dependencies: Vec<ResolvedTaskTree>,
+}
+
 /// Inventory tracks available tasks for a given project.
 pub struct Inventory {
     fs: Arc<dyn Fs>,
@@ -45,6 +53,7 @@ pub struct Inventory {
     last_scheduled_scenarios: VecDeque<(DebugScenario, DebugScenarioContext)>,
     templates_from_settings: InventoryFor<TaskTemplate>,
     scenarios_from_settings: InventoryFor<DebugScenario>,
+    task_input_answers: HashMap<String, String>,
 }
 
 impl std::fmt::Debug for Inventory {
@@ -249,6 +258,7 @@ impl Inventory {
             last_scheduled_scenarios: VecDeque::default(),
             templates_from_settings: InventoryFor::default(),
             scenarios_from_settings: InventoryFor::default(),
+            task_input_answers: HashMap::default(),
         })
     }
 
@@ -613,6 +623,71 @@ impl Inventory {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
 
+    /// The value last given to the task input with the id given, during this session.
+    pub fn task_input_answer(&self, input_id: &str) -> Option<&str> {
+        self.task_input_answers.get(input_id).map(String::as_str)
+    }
+
+    /// Remembers the value given to a task input, to offer it as a default when the input is asked for again.
+    pub fn task_input_answered(&mut self, input_id: String, answer: String) {
+        self.task_input_answers.insert(input_id, answer);
+    }
+
+    /// Resolves [`TaskTemplate::depends_on`] of the task given, recursively, with the same [`TaskContext`] the task was resolved with.
+    /// Dependencies are looked up by their labels among the file-based tasks: the ones from the same source first,
+    /// then the ones from the task's worktree (if any), then the global ones.
+    pub fn resolve_task_dependencies(
+        &self,
+        task_source_kind: TaskSourceKind,
+        resolved_task: ResolvedTask,
+    ) -> Result<ResolvedTaskTree> {
+        let templates = self.templates_visible_from(&task_source_kind);
+        let mut labels_stack = vec![resolved_task.original_task().label.clone()];
+        let dependencies = resolve_dependencies(&resolved_task, &templates, &mut labels_stack)?;
+        Ok(ResolvedTaskTree {
+            source_kind: task_source_kind,
+            task: resolved_task,
+            dependencies,
+        })
+    }
+
+    /// Resolves the file-based task with the label given, looking it up the same way task dependencies are.
+    pub fn resolve_task_by_label(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        label: &str,
+        task_context: &TaskContext,
+    ) -> Result<(TaskSourceKind, ResolvedTask)> {
+        let (source_kind, template) = self
+            .templates_visible_from(task_source_kind)
+            .into_iter()
+            .find(|(_, template)| template.label == label)
+            .with_context(|| format!("Unknown task `{label}`"))?;
+        let resolved_task = template
+            .resolve_task(&source_kind.to_id_base(), task_context)
+            .with_context(|| format!("Could not resolve task `{label}`"))?;
+        Ok((source_kind, resolved_task))
+    }
+
+    /// File-based templates a task from the source given may refer to by their labels,
+    /// the ones from the same source first, then the ones from the task's worktree (if any), then the global ones.
+    fn templates_visible_from(
+        &self,
+        task_source_kind: &TaskSourceKind,
+    ) -> Vec<(TaskSourceKind, TaskTemplate)> {
+        let worktree_templates = match task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => {
+                self.worktree_templates_from_settings(*id).collect()
+            }
+            _ => Vec::new(),
+        };
+        worktree_templates
+            .into_iter()
+            .chain(self.global_templates_from_settings())
+            .sorted_by_key(|(kind, _)| kind != task_source_kind)
+            .collect()
+    }
+
     fn global_templates_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
@@ -804,6 +879,47 @@ impl Inventory {
     }
 }
 
+fn resolve_dependencies(
+    task: &ResolvedTask,
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    labels_stack: &mut Vec<String>,
+) -> Result<Vec<ResolvedTaskTree>> {
+    task.original_task()
+        .depends_on
+        .iter()
+        .map(|label| {
+            if labels_stack.contains(label) {
+                bail!(
+                    "Task dependency cycle: {} -> {label}",
+                    labels_stack.join(" -> ")
+                );
+            }
+            let (source_kind, template) = templates
+                .iter()
+                .find(|(_, template)| &template.label == label)
+                .with_context(|| {
+                    format!(
+                        "Task `{}` depends on an unknown task `{label}`",
+                        task.original_task().label
+                    )
+                })?;
+            let dependency = template
+                .resolve_task(&source_kind.to_id_base(), task.task_context())
+                .with_context(|| format!("Could not resolve task `{label}`"))?;
+
+            labels_stack.push(label.clone());
+            let dependencies = resolve_dependencies(&dependency, templates, labels_stack)?;
+            labels_stack.pop();
+
+            Ok(ResolvedTaskTree {
+                source_kind: source_kind.clone(),
+                task: dependency,
+                dependencies,
+            })
+        })
+        .collect()
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -1106,6 +1222,7 @@ mod tests {
     use pretty_assertions::assert_eq;
     use serde_json::json;
     use settings::SettingsLocation;
+    use task::DependsOrder;
 
     use crate::task_store::TaskStore;
 
@@ -1555,6 +1672,117 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_resolving_task_dependencies(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let inventory = cx.update(|cx| Inventory::new(fs, cx));
+        let worktree_id = WorktreeId::from_usize(1);
+
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    TaskSettingsLocation::Global(tasks_file()),
+                    Some(&mock_tasks_from_names(["lint", "build"])),
+                )
+                .unwrap();
+            inventory
+                .update_file_based_tasks(
+                    TaskSettingsLocation::Worktree(SettingsLocation {
+                        worktree_id,
+                        path: Path::new(".zed"),
+                    }),
+                    Some(
+                        &json!([
+                            {
+                                "label": "build",
+                                "command": "cargo",
+                                "args": ["build"],
+                                "depends_on": ["lint"],
+                            },
+                            {
+                                "label": "test",
+                                "command": "cargo",
+                                "args": ["test"],
+                                "depends_on": ["build"],
+                            },
+                            {
+                                "label": "all",
+                                "depends_on": ["test", "lint"],
+                                "depends_order": "sequence",
+                            },
+                            {
+                                "label": "cycle 1",
+                                "command": "echo",
+                                "depends_on": ["cycle 2"],
+                            },
+                            {
+                                "label": "cycle 2",
+                                "command": "echo",
+                                "depends_on": ["cycle 1"],
+                            },
+                            {
+                                "label": "broken",
+                                "command": "echo",
+                                "depends_on": ["missing"],
+                            },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        fn tree_labels(tree: &ResolvedTaskTree) -> String {
+            let label = match &tree.source_kind {
+                TaskSourceKind::AbsPath { .. } => format!("global {}", tree.task.resolved_label),
+                _ => tree.task.resolved_label.clone(),
+            };
+            if tree.dependencies.is_empty() {
+                label
+            } else {
+                format!(
+                    "{label}({})",
+                    tree.dependencies.iter().map(tree_labels).join(", ")
+                )
+            }
+        }
+
+        let resolve = |label: &str, cx: &mut TestAppContext| {
+            inventory.read_with(cx, |inventory, _| {
+                let (kind, template) = inventory
+                    .worktree_templates_from_settings(worktree_id)
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let resolved_task = template
+                    .resolve_task(&kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory.resolve_task_dependencies(kind, resolved_task)
+            })
+        };
+
+        let all = resolve("all", cx).unwrap();
+        assert_eq!(all.task.resolved.command, None);
+        assert_eq!(
+            all.task.original_task().depends_order,
+            DependsOrder::Sequence
+        );
+        assert_eq!(
+            tree_labels(&all),
+            "all(test(build(global lint)), global lint)",
+            "Dependencies from the same tasks file should be preferred over the global ones"
+        );
+
+        assert_eq!(
+            resolve("cycle 1", cx).unwrap_err().to_string(),
+            "Task dependency cycle: cycle 1 -> cycle 2 -> cycle 1"
+        );
+        assert_eq!(
+            resolve("broken", cx).unwrap_err().to_string(),
+            "Task `broken` depends on an unknown task `missing`"
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         zlog::init_test();
         TaskStore::init(None);
@@ -1,9 +1,13 @@
 use crate::{Project, ProjectPath};
 use anyhow::{Context as _, Result};
 use collections::HashMap;
-use gpui::{AnyWindowHandle, App, AppContext as _, Context, Entity, Task, WeakEntity};
+use gpui::{
+    AnyWindowHandle, App, AppContext as _, Context, Entity, Subscription, Task, WeakEntity,
+};
 use itertools::Itertools;
-use language::LanguageName;
+use language::{
+    Diagnostic, DiagnosticEntry, DiagnosticSourceKind, LanguageName, PointUtf16, Unclipped,
+};
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -11,8 +15,12 @@ use std::{
     env::{self},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
+};
+use task::{
+    DEFAULT_REMOTE_SHELL, Problem, ProblemMatcher, ProblemSeverity, Shell, ShellBuilder,
+    SpawnInTerminal, TaskId,
 };
-use task::{DEFAULT_REMOTE_SHELL, Shell, ShellBuilder, SpawnInTerminal};
 use terminal::{
     TaskState, TaskStatus, Terminal, TerminalBuilder,
     terminal_settings::{self, TerminalSettings, VenvSettings},
@@ -21,8 +29,27 @@ use util::ResultExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    pub(crate) task_problems: TaskProblems,
 }
 
+/// Problems matched in the output of tasks, reported as diagnostics.
+#[derive(Default)]
+pub(crate) struct TaskProblems {
+    tasks: HashMap<TaskId, TaskOutputProblems>,
+}
+
+struct TaskOutputProblems {
+    problems: Vec<Problem>,
+    /// The output line that the output was matched up to.
+    matched_line: usize,
+    scan: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+const TASK_OUTPUT_SCAN_THROTTLE: Duration = Duration::from_millis(500);
+/// The source that the problems of all tasks are reported as diagnostics of.
+const TASK_DIAGNOSTICS_SOURCE: &str = "tasks";
+
 /// Terminals are opened either for the users shell, or to run a task.
 
 #[derive(Debug)]
@@ -87,6 +114,18 @@ impl Project {
         kind: TerminalKind,
         window: AnyWindowHandle,
         cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Terminal>>> {
+        self.create_terminal_with_scrollback(kind, None, window, cx)
+    }
+
+    /// Creates a terminal that shows the given scrollback, saved from a previous
+    /// session, above the shell's first prompt.
+    pub fn create_terminal_with_scrollback(
+        &mut self,
+        kind: TerminalKind,
+        restored_scrollback: Option<String>,
+        window: AnyWindowHandle,
+        cx: &mut Context<Self>,
     ) -> Task<Result<Entity<Terminal>>> {
         let path: Option<Arc<Path>> = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| Arc::from(path.as_ref())),
@@ -121,7 +160,13 @@ impl Project {
                 None
             };
             project.update(cx, |project, cx| {
-                project.create_terminal_with_venv(kind, python_venv_directory, window, cx)
+                project.create_terminal_with_venv(
+                    kind,
+                    python_venv_directory,
+                    restored_scrollback.as_deref(),
+                    window,
+                    cx,
+                )
             })?
         })
     }
@@ -187,6 +232,7 @@ impl Project {
         &mut self,
         kind: TerminalKind,
         python_venv_directory: Option<PathBuf>,
+        restored_scrollback: Option<&str>,
         window: AnyWindowHandle,
         cx: &mut Context<Self>,
     ) -> Result<Entity<Terminal>> {
@@ -233,6 +279,7 @@ impl Project {
         };
 
         let mut python_venv_activate_command = None;
+        let mut task_problem_matchers = None;
 
         let (spawn_task, shell) = match kind {
             TerminalKind::Shell(_) => {
@@ -267,7 +314,14 @@ impl Project {
                     None => (None, settings.shell),
                 }
             }
-            TerminalKind::Task(spawn_task) => {
+            TerminalKind::Task(mut spawn_task) => {
+                if !spawn_task.problem_matchers.is_empty() {
+                    task_problem_matchers = Some((
+                        spawn_task.id.clone(),
+                        spawn_task.label.clone(),
+                        std::mem::take(&mut spawn_task.problem_matchers),
+                    ));
+                }
                 let task_state = Some(TaskState {
                     id: spawn_task.id,
                     full_label: spawn_task.full_label,
@@ -343,6 +397,8 @@ impl Project {
             settings.cursor_shape.unwrap_or_default(),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            settings.shell_integration,
+            restored_scrollback,
             ssh_details.is_some(),
             window,
             completion_tx,
@@ -372,10 +428,150 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+            if let Some((task_id, task_label, problem_matchers)) = task_problem_matchers {
+                if ssh_details.is_none() {
+                    this.match_task_problems(
+                        task_id,
+                        task_label,
+                        problem_matchers,
+                        path.map(|path| path.to_path_buf()),
+                        &terminal_handle,
+                        cx,
+                    );
+                }
+            }
             terminal_handle
         })
     }
 
+    /// Reports problems found in the task's output as diagnostics, as the output comes in.
+    /// Diagnostics reported by the previous run of the same task are cleared.
+    ///
+    /// Only tasks of local projects are matched: the diagnostics of remote projects come from the
+    /// host, and the paths in the output of their tasks are paths on the host.
+    fn match_task_problems(
+        &mut self,
+        task_id: TaskId,
+        task_label: String,
+        problem_matchers: Vec<ProblemMatcher>,
+        cwd: Option<PathBuf>,
+        terminal: &Entity<Terminal>,
+        cx: &mut Context<Self>,
+    ) {
+        let problem_matchers = Arc::new(problem_matchers);
+        let subscription = cx.subscribe(terminal, {
+            let task_id = task_id.clone();
+            move |project, task_terminal, event: &terminal::Event, cx| {
+                let finished = match event {
+                    terminal::Event::Wakeup => false,
+                    terminal::Event::TaskFinished => true,
+                    _ => return,
+                };
+                let Some(task_problems) = project.terminals.task_problems.tasks.get_mut(&task_id)
+                else {
+                    return;
+                };
+                if !finished && task_problems.scan.is_some() {
+                    return;
+                }
+
+                let matched_line = task_problems.matched_line;
+                let task_terminal = task_terminal.downgrade();
+                let problem_matchers = problem_matchers.clone();
+                let cwd = cwd.clone();
+                let task_label = task_label.clone();
+                let task_id = task_id.clone();
+                task_problems.scan = Some(cx.spawn(async move |project, cx| {
+                    if !finished {
+                        cx.background_executor()
+                            .timer(TASK_OUTPUT_SCAN_THROTTLE)
+                            .await;
+                    }
+                    let Ok((lines, end_line)) = task_terminal.update(cx, |terminal, cx| {
+                        terminal.output_lines_since(matched_line, finished, cx)
+                    }) else {
+                        return;
+                    };
+                    let (matched_line, problems) = cx
+                        .background_spawn(async move {
+                            let output = lines.iter().map(|(_, line)| line).join("\n");
+                            let (settled_lines, problems) = if finished {
+                                let problems = task::match_problems(
+                                    &problem_matchers,
+                                    &output,
+                                    cwd.as_deref(),
+                                    &task_label,
+                                );
+                                (lines.len(), problems)
+                            } else {
+                                task::match_settled_problems(
+                                    &problem_matchers,
+                                    &output,
+                                    cwd.as_deref(),
+                                    &task_label,
+                                )
+                            };
+                            let matched_line =
+                                lines.get(settled_lines).map_or(end_line, |(line, _)| *line);
+                            (matched_line, problems)
+                        })
+                        .await;
+                    project
+                        .update(cx, |project, cx| {
+                            let Some(task_problems) =
+                                project.terminals.task_problems.tasks.get_mut(&task_id)
+                            else {
+                                return;
+                            };
+                            task_problems.scan = None;
+                            task_problems.matched_line = matched_line;
+                            if !problems.is_empty() {
+                                task_problems.problems.extend(problems);
+                                project.report_task_problems(cx);
+                            }
+                        })
+                        .ok();
+                }));
+            }
+        });
+
+        let previous_run = self.terminals.task_problems.tasks.insert(
+            task_id,
+            TaskOutputProblems {
+                problems: Vec::new(),
+                matched_line: 0,
+                scan: None,
+                _subscription: subscription,
+            },
+        );
+        if previous_run.is_some_and(|previous_run| !previous_run.problems.is_empty()) {
+            self.report_task_problems(cx);
+        }
+    }
+
+    /// Replaces the diagnostics of tasks with the problems found in their output.
+    fn report_task_problems(&mut self, cx: &mut Context<Self>) {
+        let mut diagnostics = HashMap::<PathBuf, Vec<_>>::default();
+        for (group_id, problem) in self
+            .terminals
+            .task_problems
+            .tasks
+            .values()
+            .flat_map(|task| task.problems.iter())
+            .enumerate()
+        {
+            diagnostics
+                .entry(problem.path.clone())
+                .or_default()
+                .push(problem_to_diagnostic(problem.clone(), group_id));
+        }
+        self.lsp_store
+            .update(cx, |lsp_store, cx| {
+                lsp_store.update_other_diagnostics(TASK_DIAGNOSTICS_SOURCE.into(), diagnostics, cx)
+            })
+            .log_err();
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
@@ -640,3 +836,42 @@ mod tests {
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
 }
+
+fn problem_to_diagnostic(
+    problem: Problem,
+    group_id: usize,
+) -> DiagnosticEntry<Unclipped<PointUtf16>> {
+    let start = PointUtf16::new(
+        problem.line.saturating_sub(1),
+        problem.column.unwrap_or(1).saturating_sub(1),
+    );
+    let end = match (problem.end_line, problem.end_column) {
+        (end_line, Some(end_column)) => PointUtf16::new(
+            end_line.unwrap_or(problem.line).saturating_sub(1),
+            end_column.saturating_sub(1),
+        ),
+        // Without any columns, the whole line is the problem.
+        (_, None) if problem.column.is_none() => PointUtf16::new(start.row, u32::MAX),
+        (_, None) => start,
+    };
+    DiagnosticEntry {
+        range: Unclipped(start)..Unclipped(end),
+        diagnostic: Diagnostic {
+            source: problem.source,
+            code: problem.code.map(lsp::NumberOrString::String),
+            severity: match problem.severity {
+                ProblemSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+                ProblemSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+                ProblemSeverity::Info => lsp::DiagnosticSeverity::INFORMATION,
+                ProblemSeverity::Hint => lsp::DiagnosticSeverity::HINT,
+            },
+            message: problem.message,
+            group_id,
+            is_primary: true,
+            // The output of tasks is about the files on disk.
+            is_disk_based: true,
+            source_kind: DiagnosticSourceKind::Other,
+            ..Diagnostic::default()
+        },
+    }
+}
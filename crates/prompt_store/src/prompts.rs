@@ -114,9 +114,19 @@ pub struct TerminalAssistantPromptContext {
     pub shell: Option<String>,
     pub working_directory: Option<String>,
     pub latest_output: Vec<String>,
+    pub recent_commands: Vec<TerminalCommandContext>,
     pub user_prompt: String,
 }
 
+/// A command that finished in the terminal, as reported by the shell.
+#[derive(Serialize)]
+pub struct TerminalCommandContext {
+    pub command: String,
+    pub working_directory: Option<String>,
+    pub exit_code: i32,
+    pub succeeded: bool,
+}
+
 pub struct PromptLoadingParams<'a> {
     pub fs: Arc<dyn Fs>,
     pub repo_path: Option<PathBuf>,
@@ -430,6 +440,7 @@ impl PromptBuilder {
         shell: Option<&str>,
         working_directory: Option<&str>,
         latest_output: &[String],
+        recent_commands: Vec<TerminalCommandContext>,
     ) -> Result<String, RenderError> {
         let context = TerminalAssistantPromptContext {
             os: std::env::consts::OS.to_string(),
@@ -437,6 +448,7 @@ impl PromptBuilder {
             shell: shell.map(|s| s.to_string()),
             working_directory: working_directory.map(|s| s.to_string()),
             latest_output: latest_output.to_vec(),
+            recent_commands,
             user_prompt: user_prompt.to_string(),
         };
 
@@ -518,6 +530,35 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_terminal_assistant_prompt_lists_recent_commands() {
+        let prompt = PromptBuilder::new(None)
+            .unwrap()
+            .generate_terminal_assistant_prompt(
+                "rerun the tests",
+                Some("zsh"),
+                Some("/path/to/root"),
+                &[],
+                vec![
+                    TerminalCommandContext {
+                        command: "cargo build".into(),
+                        working_directory: Some("/path/to/root".into()),
+                        exit_code: 0,
+                        succeeded: true,
+                    },
+                    TerminalCommandContext {
+                        command: "cargo test -p foo".into(),
+                        working_directory: None,
+                        exit_code: 101,
+                        succeeded: false,
+                    },
+                ],
+            )
+            .unwrap();
+        assert!(prompt.contains("- `cargo build` in '/path/to/root', succeeded"));
+        assert!(prompt.contains("- `cargo test -p foo`, failed with exit code 101"));
+    }
+
     #[test]
     fn test_has_tool_helper() {
         let mut handlebars = Handlebars::new();
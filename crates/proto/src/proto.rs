@@ -221,12 +221,29 @@ messages!(
     (ResolveCompletionDocumentationResponse, Background),
     (ResolveInlayHint, Background),
     (ResolveInlayHintResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
     (GetDocumentColor, Background),
     (GetDocumentColorResponse, Background),
     (GetColorPresentation, Background),
     (GetColorPresentationResponse, Background),
     (RefreshCodeLens, Background),
     (GetCodeLens, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (RefreshSemanticTokens, Background),
+    (PrepareCallHierarchy, Background),
+    (GetIncomingCalls, Background),
+    (GetOutgoingCalls, Background),
+    (GetHierarchyCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (GetSupertypes, Background),
+    (GetSubtypes, Background),
+    (GetHierarchyItemsResponse, Background),
     (GetCodeLensResponse, Background),
     (RespondToChannelInvite, Foreground),
     (RespondToContactRequest, Foreground),
@@ -288,6 +305,40 @@ messages!(
     (UsersResponse, Foreground),
     (GitReset, Background),
     (GitCheckoutFiles, Background),
+    (GitStashPush, Background),
+    (GitStashList, Background),
+    (GitStashListResponse, Background),
+    (GitStashDiff, Background),
+    (GitStashApply, Background),
+    (GitStashPop, Background),
+    (GitStashDrop, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitFileHistory, Background),
+    (GitFileHistoryResponse, Background),
+    (GitRebaseTodo, Background),
+    (GitRebaseTodoResponse, Background),
+    (GitRebaseStart, Background),
+    (GitRebaseCommand, Background),
+    (GitRebaseStatus, Background),
+    (GitRebaseStatusResponse, Background),
+    (GitLoadConflictVersions, Background),
+    (GitLoadConflictVersionsResponse, Background),
+    (GitWorktrees, Background),
+    (GitWorktreesResponse, Background),
+    (GitCreateWorktree, Background),
+    (GitRemoveWorktree, Background),
+    (GitPruneWorktrees, Background),
+    (GitCherryPick, Background),
+    (GitRevert, Background),
+    (GitSequencerState, Background),
+    (GitSequencerStateResponse, Background),
+    (GitSequencerCommand, Background),
+    (GitTags, Background),
+    (GitTagsResponse, Background),
+    (GitCreateTag, Background),
+    (GitDeleteTag, Background),
+    (GitPushTag, Background),
     (GitShow, Background),
     (GitCommitDetails, Background),
     (SetIndexText, Background),
@@ -388,6 +439,7 @@ request_messages!(
     (PrepareRename, PrepareRenameResponse),
     (RefreshInlayHints, Ack),
     (RefreshCodeLens, Ack),
+    (RefreshSemanticTokens, Ack),
     (RejoinChannelBuffers, RejoinChannelBuffersResponse),
     (RejoinRoom, RejoinRoomResponse),
     (ReloadBuffers, ReloadBuffersResponse),
@@ -404,8 +456,18 @@ request_messages!(
         ResolveCompletionDocumentationResponse
     ),
     (ResolveInlayHint, ResolveInlayHintResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
     (GetDocumentColor, GetDocumentColorResponse),
     (GetColorPresentation, GetColorPresentationResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (PrepareCallHierarchy, GetHierarchyItemsResponse),
+    (GetIncomingCalls, GetHierarchyCallsResponse),
+    (GetOutgoingCalls, GetHierarchyCallsResponse),
+    (PrepareTypeHierarchy, GetHierarchyItemsResponse),
+    (GetSupertypes, GetHierarchyItemsResponse),
+    (GetSubtypes, GetHierarchyItemsResponse),
     (RespondToChannelInvite, Ack),
     (RespondToContactRequest, Ack),
     (SaveBuffer, BufferSaved),
@@ -464,6 +526,31 @@ request_messages!(
     (GitShow, GitCommitDetails),
     (GitReset, Ack),
     (GitCheckoutFiles, Ack),
+    (GitStashPush, Ack),
+    (GitStashList, GitStashListResponse),
+    (GitStashDiff, LoadCommitDiffResponse),
+    (GitStashApply, Ack),
+    (GitStashPop, Ack),
+    (GitStashDrop, Ack),
+    (GitLog, GitLogResponse),
+    (GitFileHistory, GitFileHistoryResponse),
+    (GitRebaseTodo, GitRebaseTodoResponse),
+    (GitRebaseStart, Ack),
+    (GitRebaseCommand, Ack),
+    (GitRebaseStatus, GitRebaseStatusResponse),
+    (GitLoadConflictVersions, GitLoadConflictVersionsResponse),
+    (GitWorktrees, GitWorktreesResponse),
+    (GitCreateWorktree, Ack),
+    (GitRemoveWorktree, Ack),
+    (GitPruneWorktrees, Ack),
+    (GitCherryPick, Ack),
+    (GitRevert, Ack),
+    (GitSequencerState, GitSequencerStateResponse),
+    (GitSequencerCommand, Ack),
+    (GitTags, GitTagsResponse),
+    (GitCreateTag, Ack),
+    (GitDeleteTag, Ack),
+    (GitPushTag, RemoteMessageResponse),
     (SetIndexText, Ack),
     (Push, RemoteMessageResponse),
     (Fetch, RemoteMessageResponse),
@@ -511,11 +598,18 @@ entity_messages!(
     GetDeclaration,
     GetImplementation,
     GetDocumentHighlights,
+    GetDocumentLinks,
     GetDocumentSymbols,
+    GetFoldingRanges,
     GetHover,
+    GetIncomingCalls,
+    GetOutgoingCalls,
     GetProjectSymbols,
     GetReferences,
+    GetSemanticTokens,
     GetSignatureHelp,
+    GetSubtypes,
+    GetSupertypes,
     OpenUnstagedDiff,
     OpenUncommittedDiff,
     GetTypeDefinition,
@@ -534,13 +628,17 @@ entity_messages!(
     OpenBufferForSymbol,
     OpenCommitMessageBuffer,
     PerformRename,
+    PrepareCallHierarchy,
     PrepareRename,
+    PrepareTypeHierarchy,
     RefreshInlayHints,
     RefreshCodeLens,
+    RefreshSemanticTokens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
     ResolveCompletionDocumentation,
+    ResolveCodeLens,
     ResolveInlayHint,
     SaveBuffer,
     Stage,
@@ -591,6 +689,31 @@ entity_messages!(
     GitShow,
     GitReset,
     GitCheckoutFiles,
+    GitStashPush,
+    GitStashList,
+    GitStashDiff,
+    GitStashApply,
+    GitStashPop,
+    GitStashDrop,
+    GitLog,
+    GitFileHistory,
+    GitRebaseTodo,
+    GitRebaseStart,
+    GitRebaseCommand,
+    GitRebaseStatus,
+    GitLoadConflictVersions,
+    GitWorktrees,
+    GitCreateWorktree,
+    GitRemoveWorktree,
+    GitPruneWorktrees,
+    GitCherryPick,
+    GitRevert,
+    GitSequencerState,
+    GitSequencerCommand,
+    GitTags,
+    GitCreateTag,
+    GitDeleteTag,
+    GitPushTag,
     SetIndexText,
 
     Push,
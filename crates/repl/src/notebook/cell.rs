@@ -1,14 +1,18 @@
 #![allow(unused, dead_code)]
+use std::future::Future;
 use std::sync::Arc;
 
-use editor::{Editor, EditorMode, MultiBuffer};
+use anyhow::{Context as _, Result};
+use editor::{Editor, EditorEvent, EditorMode, MultiBuffer};
 use futures::future::Shared;
 use gpui::{
-    App, Entity, Hsla, RetainAllImageCache, Task, TextStyleRefinement, image_cache, prelude::*,
+    App, ClickEvent, Entity, Hsla, RetainAllImageCache, Subscription, Task, TextStyleRefinement,
+    image_cache, prelude::*,
 };
 use language::{Buffer, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
 use nbformat::v4::{CellId, CellMetadata, CellType};
+use serde_json::{Map, Value, json};
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::{IconButtonShape, prelude::*};
@@ -112,9 +116,126 @@ fn convert_outputs(
         .collect()
 }
 
+/// Splits a cell's text into lines the way Jupyter stores cell sources.
+pub(crate) fn source_lines(text: &str) -> Vec<String> {
+    text.split_inclusive('\n').map(ToOwned::to_owned).collect()
+}
+
+/// Splits a cell's text at the given offset, dropping the line break the split happens at.
+pub(crate) fn split_source(text: &str, offset: usize) -> (&str, &str) {
+    let (before, after) = text.split_at(offset);
+    (
+        before.strip_suffix('\n').unwrap_or(before),
+        after.strip_prefix('\n').unwrap_or(after),
+    )
+}
+
+/// Joins the text of two cells being merged, separated by a line break.
+pub(crate) fn merge_sources(first: &str, second: &str) -> String {
+    if first.is_empty() {
+        second.to_string()
+    } else if second.is_empty() {
+        first.to_string()
+    } else {
+        format!("{}\n{second}", first.strip_suffix('\n').unwrap_or(first))
+    }
+}
+
+/// Returns the JSON of a new, empty cell of the given type.
+pub(crate) fn new_cell_json(cell_type: CellType, source: &str) -> Map<String, Value> {
+    let mut json = Map::new();
+    json.insert("id".into(), Value::String(uuid::Uuid::new_v4().to_string()));
+    json.insert("metadata".into(), json!({}));
+    json.insert("source".into(), json!(source_lines(source)));
+    set_cell_type(&mut json, cell_type);
+    json
+}
+
+/// Replaces the source in a cell's JSON.
+pub(crate) fn cell_json_with_source(
+    mut json: Map<String, Value>,
+    source: &str,
+) -> Map<String, Value> {
+    json.insert("source".into(), json!(source_lines(source)));
+    json
+}
+
+/// Changes the type of a cell's JSON, adding and removing the fields that only some cell types have.
+pub(crate) fn set_cell_type(json: &mut Map<String, Value>, cell_type: CellType) {
+    let cell_type = match cell_type {
+        CellType::Code => {
+            json.entry("execution_count").or_insert(Value::Null);
+            json.entry("outputs").or_insert(json!([]));
+            json.remove("attachments");
+            "code"
+        }
+        CellType::Markdown => {
+            json.remove("execution_count");
+            json.remove("outputs");
+            "markdown"
+        }
+        CellType::Raw => {
+            json.remove("execution_count");
+            json.remove("outputs");
+            "raw"
+        }
+    };
+    json.insert("cell_type".into(), Value::String(cell_type.into()));
+}
+
+fn cell_editor(text: &str, window: &mut Window, cx: &mut App) -> (Entity<Buffer>, Entity<Editor>) {
+    let buffer = cx.new(|cx| Buffer::local(text, cx));
+    let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+
+    let editor = cx.new(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight {
+                min_lines: 1,
+                max_lines: Some(1024),
+            },
+            multi_buffer,
+            None,
+            window,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size(cx).into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    });
+
+    (buffer, editor)
+}
+
+fn load_language<T: 'static>(
+    buffer: Entity<Buffer>,
+    language: impl Future<Output = Option<Arc<Language>>> + 'static,
+    window: &mut Window,
+    cx: &mut Context<T>,
+) -> Task<()> {
+    cx.spawn_in(window, async move |_, cx| {
+        let language = language.await;
+        buffer
+            .update(cx, |buffer, cx| buffer.set_language(language, cx))
+            .ok();
+    })
+}
+
 impl Cell {
+    /// Loads the cell parsed from the JSON given.
     pub fn load(
         cell: &nbformat::v4::Cell,
+        json: Map<String, Value>,
         languages: &Arc<LanguageRegistry>,
         notebook_language: Shared<Task<Option<Arc<Language>>>>,
         window: &mut Window,
@@ -130,35 +251,41 @@ impl Cell {
                 let source = source.join("");
 
                 let entity = cx.new(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn_in(window, async move |this, cx| {
-                            let parsed_markdown = cx
-                                .background_spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
+                    let (buffer, editor) = cell_editor(&source, window, cx);
+                    let markdown_language = languages.language_for_name("Markdown");
+                    let language_task = load_language(
+                        buffer,
+                        async move { markdown_language.await.ok() },
+                        window,
+                        cx,
+                    );
+                    let editor_subscription = cx.subscribe_in(
+                        &editor,
+                        window,
+                        |cell: &mut MarkdownCell, _, event: &EditorEvent, _, cx| {
+                            if let EditorEvent::Blurred = event {
+                                cell.stop_editing(cx);
+                            }
+                        },
+                    );
 
-                    MarkdownCell {
-                        markdown_parsing_task,
+                    let mut cell = MarkdownCell {
+                        markdown_parsing_task: Task::ready(()),
                         image_cache: RetainAllImageCache::new(cx),
                         languages: languages.clone(),
                         id: id.clone(),
                         metadata: metadata.clone(),
-                        source: source.clone(),
+                        json,
+                        editor,
+                        editing: source.trim().is_empty(),
                         parsed_markdown: None,
                         selected: false,
                         cell_position: None,
-                    }
+                        language_task,
+                        _editor_subscription: editor_subscription,
+                    };
+                    cell.parse_markdown(cx);
+                    cell
                 });
 
                 Cell::Markdown(entity)
@@ -170,56 +297,15 @@ impl Cell {
                 source,
                 outputs,
             } => Cell::Code(cx.new(|cx| {
-                let text = source.join("");
-
-                let buffer = cx.new(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight {
-                            min_lines: 1,
-                            max_lines: Some(1024),
-                        },
-                        multi_buffer,
-                        None,
-                        window,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size(cx).into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, window, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
-
-                let buffer = buffer.clone();
-                let language_task = cx.spawn_in(window, async move |this, cx| {
-                    let language = notebook_language.await;
-
-                    buffer.update(cx, |buffer, cx| {
-                        buffer.set_language(language.clone(), cx);
-                    });
-                });
+                let (buffer, editor) = cell_editor(&source.join(""), window, cx);
+                let language_task = load_language(buffer, notebook_language, window, cx);
 
                 CodeCell {
                     id: id.clone(),
                     metadata: metadata.clone(),
+                    json,
                     execution_count: *execution_count,
-                    source: source.join(""),
-                    editor: editor_view,
+                    editor,
                     outputs: convert_outputs(outputs, window, cx),
                     selected: false,
                     language_task,
@@ -230,15 +316,111 @@ impl Cell {
                 id,
                 metadata,
                 source,
-            } => Cell::Raw(cx.new(|_| RawCell {
-                id: id.clone(),
-                metadata: metadata.clone(),
-                source: source.join(""),
-                selected: false,
-                cell_position: None,
+            } => Cell::Raw(cx.new(|cx| {
+                let (_, editor) = cell_editor(&source.join(""), window, cx);
+                RawCell {
+                    id: id.clone(),
+                    metadata: metadata.clone(),
+                    json,
+                    editor,
+                    selected: false,
+                    cell_position: None,
+                }
             })),
         }
     }
+
+    /// Loads a cell from its JSON representation in the notebook file.
+    pub fn from_json(
+        json: Map<String, Value>,
+        languages: &Arc<LanguageRegistry>,
+        notebook_language: Shared<Task<Option<Arc<Language>>>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<Self> {
+        let cell = serde_json::from_value::<nbformat::v4::Cell>(Value::Object(json.clone()))
+            .context("parsing notebook cell")?;
+        Ok(Self::load(
+            &cell,
+            json,
+            languages,
+            notebook_language,
+            window,
+            cx,
+        ))
+    }
+
+    /// Returns the JSON to write to the notebook file for this cell, with its current source.
+    ///
+    /// Cells keep the JSON they were loaded from and only replace the source in it, so that the
+    /// fields and metadata Zed doesn't know about are written back unchanged.
+    pub fn to_json(&self, cx: &App) -> Map<String, Value> {
+        let json = match self {
+            Cell::Code(cell) => cell.read(cx).json.clone(),
+            Cell::Markdown(cell) => cell.read(cx).json.clone(),
+            Cell::Raw(cell) => cell.read(cx).json.clone(),
+        };
+        cell_json_with_source(json, &self.source(cx))
+    }
+
+    pub fn id(&self, cx: &App) -> CellId {
+        match self {
+            Cell::Code(cell) => cell.read(cx).id.clone(),
+            Cell::Markdown(cell) => cell.read(cx).id.clone(),
+            Cell::Raw(cell) => cell.read(cx).id.clone(),
+        }
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self {
+            Cell::Code(_) => CellType::Code,
+            Cell::Markdown(_) => CellType::Markdown,
+            Cell::Raw(_) => CellType::Raw,
+        }
+    }
+
+    pub fn editor(&self, cx: &App) -> Entity<Editor> {
+        match self {
+            Cell::Code(cell) => cell.read(cx).editor.clone(),
+            Cell::Markdown(cell) => cell.read(cx).editor.clone(),
+            Cell::Raw(cell) => cell.read(cx).editor.clone(),
+        }
+    }
+
+    pub fn source(&self, cx: &App) -> String {
+        self.editor(cx).read(cx).text(cx)
+    }
+
+    pub fn set_source(&self, source: &str, window: &mut Window, cx: &mut App) {
+        self.editor(cx)
+            .update(cx, |editor, cx| editor.set_text(source, window, cx));
+        if let Cell::Markdown(cell) = self {
+            cell.update(cx, |cell, cx| cell.parse_markdown(cx));
+        }
+    }
+
+    /// Focuses the cell's editor, switching markdown cells to editing mode.
+    pub fn focus(&self, window: &mut Window, cx: &mut App) {
+        match self {
+            Cell::Markdown(cell) => cell.update(cx, |cell, cx| cell.start_editing(window, cx)),
+            Cell::Code(_) | Cell::Raw(_) => window.focus(&self.editor(cx).focus_handle(cx)),
+        }
+    }
+
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        self.editor(cx).read(cx).buffer().read(cx).is_dirty(cx)
+    }
+
+    /// Marks the cell's source as saved.
+    pub fn did_save(&self, cx: &mut App) {
+        let buffers = self.editor(cx).read(cx).buffer().read(cx).all_buffers();
+        for buffer in buffers {
+            buffer.update(cx, |buffer, cx| {
+                let version = buffer.version();
+                buffer.did_save(version, None, cx);
+            });
+        }
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -247,7 +429,7 @@ pub trait RenderableCell: Render {
     fn id(&self) -> &CellId;
     fn cell_type(&self) -> CellType;
     fn metadata(&self) -> &CellMetadata;
-    fn source(&self) -> &String;
+    fn editor(&self) -> &Entity<Editor>;
     fn selected(&self) -> bool;
     fn set_selected(&mut self, selected: bool) -> &mut Self;
     fn selected_bg_color(&self, window: &mut Window, cx: &mut Context<Self>) -> Hsla {
@@ -335,13 +517,54 @@ pub trait RunnableCell: RenderableCell {
 pub struct MarkdownCell {
     id: CellId,
     metadata: CellMetadata,
+    json: Map<String, Value>,
     image_cache: Entity<RetainAllImageCache>,
-    source: String,
+    editor: Entity<Editor>,
+    /// Whether the source is shown in an editor rather than rendered.
+    editing: bool,
     parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
     cell_position: Option<CellPosition>,
     languages: Arc<LanguageRegistry>,
+    language_task: Task<()>,
+    _editor_subscription: Subscription,
+}
+
+impl MarkdownCell {
+    fn parse_markdown(&mut self, cx: &mut Context<Self>) {
+        let languages = self.languages.clone();
+        let source = self.editor.read(cx).text(cx);
+
+        self.markdown_parsing_task = cx.spawn(async move |this, cx| {
+            let parsed_markdown = cx
+                .background_spawn(
+                    async move { parse_markdown(&source, None, Some(languages)).await },
+                )
+                .await;
+
+            this.update(cx, |cell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+
+    pub fn start_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editing = true;
+        window.focus(&self.editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn stop_editing(&mut self, cx: &mut Context<Self>) {
+        if !self.editing || self.editor.read(cx).text(cx).trim().is_empty() {
+            return;
+        }
+        self.editing = false;
+        self.parse_markdown(cx);
+        cx.notify();
+    }
 }
 
 impl RenderableCell for MarkdownCell {
@@ -359,8 +582,8 @@ impl RenderableCell for MarkdownCell {
         &self.metadata
     }
 
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn selected(&self) -> bool {
@@ -388,13 +611,55 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let Some(parsed) = self.parsed_markdown.as_ref() else {
-            return div();
+        let content = if self.editing {
+            div()
+                .py_1p5()
+                .w_full()
+                .child(
+                    div()
+                        .flex()
+                        .size_full()
+                        .flex_1()
+                        .py_3()
+                        .px_5()
+                        .rounded_lg()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .bg(cx.theme().colors().editor_background)
+                        .child(div().w_full().child(self.editor.clone())),
+                )
+                .into_any_element()
+        } else {
+            let Some(parsed) = self.parsed_markdown.as_ref() else {
+                return div();
+            };
+
+            let mut markdown_render_context =
+                markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
+
+            v_flex()
+                .id(SharedString::from(format!("markdown-cell-{}", self.id)))
+                .image_cache(self.image_cache.clone())
+                .size_full()
+                .flex_1()
+                .p_3()
+                .font_ui(cx)
+                .text_size(TextSize::Default.rems(cx))
+                .children(parsed.children.iter().map(|child| {
+                    div().relative().child(
+                        div()
+                            .relative()
+                            .child(render_markdown_block(child, &mut markdown_render_context)),
+                    )
+                }))
+                .on_click(cx.listener(|cell, event: &ClickEvent, window, cx| {
+                    if event.up.click_count > 1 {
+                        cell.start_editing(window, cx);
+                    }
+                }))
+                .into_any_element()
         };
 
-        let mut markdown_render_context =
-            markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
-
         v_flex()
             .size_full()
             // TODO: Move base cell render into trait impl so we don't have to repeat this
@@ -408,20 +673,7 @@ impl Render for MarkdownCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(window, cx))
                     .child(self.gutter(window, cx))
-                    .child(
-                        v_flex()
-                            .image_cache(self.image_cache.clone())
-                            .size_full()
-                            .flex_1()
-                            .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .children(parsed.children.iter().map(|child| {
-                                div().relative().child(div().relative().child(
-                                    render_markdown_block(child, &mut markdown_render_context),
-                                ))
-                            })),
-                    ),
+                    .child(content),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
@@ -431,8 +683,8 @@ impl Render for MarkdownCell {
 pub struct CodeCell {
     id: CellId,
     metadata: CellMetadata,
+    json: Map<String, Value>,
     execution_count: Option<i32>,
-    source: String,
     editor: Entity<editor::Editor>,
     outputs: Vec<Output>,
     selected: bool,
@@ -441,15 +693,15 @@ pub struct CodeCell {
 }
 
 impl CodeCell {
-    pub fn is_dirty(&self, cx: &App) -> bool {
-        self.editor.read(cx).buffer().read(cx).is_dirty(cx)
-    }
     pub fn has_outputs(&self) -> bool {
         !self.outputs.is_empty()
     }
 
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
+        self.execution_count = None;
+        self.json.insert("outputs".into(), json!([]));
+        self.json.insert("execution_count".into(), Value::Null);
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -517,8 +769,8 @@ impl RenderableCell for CodeCell {
         &self.metadata
     }
 
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn control(&self, window: &mut Window, cx: &mut Context<Self>) -> Option<CellControl> {
@@ -671,7 +923,8 @@ impl Render for CodeCell {
 pub struct RawCell {
     id: CellId,
     metadata: CellMetadata,
-    source: String,
+    json: Map<String, Value>,
+    editor: Entity<Editor>,
     selected: bool,
     cell_position: Option<CellPosition>,
 }
@@ -691,8 +944,8 @@ impl RenderableCell for RawCell {
         &self.metadata
     }
 
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn selected(&self) -> bool {
@@ -735,12 +988,52 @@ impl Render for RawCell {
                             .size_full()
                             .flex_1()
                             .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .child(self.source.clone()),
+                            .child(div().w_full().child(self.editor.clone())),
                     ),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_lines() {
+        assert_eq!(source_lines("a = 1\nb = 2\n"), vec!["a = 1\n", "b = 2\n"]);
+        assert_eq!(source_lines("a = 1\nb = 2"), vec!["a = 1\n", "b = 2"]);
+        assert!(source_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_split_and_merge_sources() {
+        let source = "a = 1\nb = 2";
+        assert_eq!(split_source(source, 5), ("a = 1", "b = 2"));
+        assert_eq!(split_source(source, 6), ("a = 1", "b = 2"));
+        assert_eq!(split_source(source, 0), ("", source));
+
+        assert_eq!(merge_sources("a = 1\n", "b = 2"), source);
+        assert_eq!(merge_sources("", "b = 2"), "b = 2");
+        assert_eq!(merge_sources("a = 1", ""), "a = 1");
+    }
+
+    #[test]
+    fn test_set_cell_type() {
+        let mut json = new_cell_json(CellType::Markdown, "# Title");
+        assert_eq!(json["cell_type"], "markdown");
+        assert!(json.get("outputs").is_none());
+
+        set_cell_type(&mut json, CellType::Code);
+        assert_eq!(json["cell_type"], "code");
+        assert_eq!(json["outputs"], json!([]));
+        assert_eq!(json["execution_count"], Value::Null);
+        assert_eq!(json["source"], json!(["# Title"]));
+
+        set_cell_type(&mut json, CellType::Raw);
+        assert_eq!(json["cell_type"], "raw");
+        assert!(json.get("outputs").is_none());
+        assert!(json.get("execution_count").is_none());
+    }
+}
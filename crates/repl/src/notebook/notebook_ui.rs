@@ -1,29 +1,38 @@
 #![allow(unused, dead_code)]
 use std::future::Future;
+use std::time::Duration;
 use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{Context as _, Result};
 use client::proto::ViewId;
 use collections::HashMap;
+use editor::EditorEvent;
 use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
-use futures::FutureExt;
+use fs::Fs;
 use futures::future::Shared;
+use futures::{FutureExt, StreamExt as _};
 use gpui::{
     AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent, ListState,
-    Point, Task, actions, list, prelude::*,
+    Point, Subscription, Task, actions, list, prelude::*,
 };
 use language::{Language, LanguageRegistry};
 use project::{Project, ProjectEntryId, ProjectPath};
+use serde::Serialize as _;
+use serde_json::{Map, Value};
 use ui::{Tooltip, prelude::*};
+use util::ResultExt as _;
 use workspace::item::{ItemEvent, SaveOptions, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
 use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{
+    Cell, CellPosition, RenderableCell, cell_json_with_source, merge_sources, new_cell_json,
+    set_cell_type, split_source,
+};
 
-use nbformat::v4::CellId;
 use nbformat::v4::Metadata as NotebookMetadata;
+use nbformat::v4::{CellId, CellType};
 
 actions!(
     notebook,
@@ -42,6 +51,18 @@ actions!(
         AddMarkdownBlock,
         /// Adds a new code cell.
         AddCodeBlock,
+        /// Deletes the current cell.
+        DeleteCell,
+        /// Splits the current cell in two at the cursor.
+        SplitCell,
+        /// Merges the cell below into the current cell.
+        MergeCellBelow,
+        /// Changes the current cell into a code cell.
+        ChangeToCodeCell,
+        /// Changes the current cell into a markdown cell.
+        ChangeToMarkdownCell,
+        /// Changes the current cell into a raw cell.
+        ChangeToRawCell,
     ]
 );
 
@@ -52,6 +73,7 @@ pub(crate) const LARGE_SPACING_SIZE: f32 = 16.0;
 pub(crate) const GUTTER_WIDTH: f32 = 19.0;
 pub(crate) const CODE_BLOCK_INSET: f32 = MEDIUM_SPACING_SIZE;
 pub(crate) const CONTROL_SIZE: f32 = 20.0;
+const FILE_WATCH_LATENCY: Duration = Duration::from_millis(100);
 
 pub fn init(cx: &mut App) {
     if cx.has_flag::<NotebookFeatureFlag>() || std::env::var("LOCAL_NOTEBOOK_DEV").is_ok() {
@@ -77,6 +99,7 @@ pub struct NotebookEditor {
 
     focus_handle: FocusHandle,
     notebook_item: Entity<NotebookItem>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
 
     remote_id: Option<ViewId>,
     cell_list: ListState,
@@ -84,6 +107,18 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+    cell_subscriptions: HashMap<CellId, Subscription>,
+    /// Whether cells were added, removed, moved, or had their type or outputs changed since the
+    /// notebook was last loaded or saved. Edits to cell sources are tracked by the cells' buffers.
+    cells_changed: bool,
+    /// Whether the file changed on disk while the notebook had unsaved changes.
+    has_conflict: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+pub enum NotebookEditorEvent {
+    Edited,
+    TitleChanged,
 }
 
 impl NotebookEditor {
@@ -96,38 +131,16 @@ impl NotebookEditor {
         let focus_handle = cx.focus_handle();
 
         let languages = project.read(cx).languages().clone();
-        let language_name = notebook_item.read(cx).language_name();
 
         let notebook_language = notebook_item.read(cx).notebook_language();
         let notebook_language = cx
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
-
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), window, cx),
-            );
-        }
-
         let notebook_handle = cx.entity().downgrade();
-        let cell_count = cell_order.len();
 
-        let this = cx.entity();
         let cell_list = ListState::new(
-            cell_count,
+            0,
             gpui::ListAlignment::Top,
             px(1000.),
             move |ix, window, cx| {
@@ -150,17 +163,113 @@ impl NotebookEditor {
             },
         );
 
-        Self {
+        let subscriptions = vec![cx.subscribe_in(
+            &notebook_item,
+            window,
+            |this, _, event: &NotebookItemEvent, window, cx| match event {
+                NotebookItemEvent::ChangedOnDisk => {
+                    if this.is_dirty(cx) {
+                        this.has_conflict = true;
+                        cx.emit(NotebookEditorEvent::TitleChanged);
+                        cx.notify();
+                    } else {
+                        this.load_cells(window, cx);
+                    }
+                }
+            },
+        )];
+
+        let mut this = Self {
             project,
-            languages: languages.clone(),
+            languages,
             focus_handle,
             notebook_item,
+            notebook_language,
             remote_id: None,
             cell_list,
             selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            cell_subscriptions: HashMap::default(),
+            cells_changed: false,
+            has_conflict: false,
+            _subscriptions: subscriptions,
+        };
+        this.load_cells(window, cx);
+        this
+    }
+
+    /// Replaces all cells with the ones of the notebook as last read from disk.
+    fn load_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self.cell_subscriptions.clear();
+
+        let cells = notebook_cells(&self.notebook_item.read(cx).json);
+        for cell in cells {
+            let Some(cell) = Cell::from_json(
+                cell,
+                &self.languages,
+                self.notebook_language.clone(),
+                window,
+                cx,
+            )
+            .log_err() else {
+                continue;
+            };
+            self.insert_cell(self.cell_order.len(), cell, cx);
         }
+
+        self.cell_list.reset(self.cell_order.len());
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_order.len().saturating_sub(1));
+        self.cells_changed = false;
+        self.has_conflict = false;
+        cx.emit(NotebookEditorEvent::TitleChanged);
+        cx.notify();
+    }
+
+    /// Adds a cell to the notebook, without updating the cell list.
+    fn insert_cell(&mut self, index: usize, cell: Cell, cx: &mut Context<Self>) {
+        let cell_id = cell.id(cx);
+        let subscription = cx.subscribe(&cell.editor(cx), {
+            let cell_id = cell_id.clone();
+            move |this, _, event: &EditorEvent, cx| match event {
+                EditorEvent::BufferEdited => cx.emit(NotebookEditorEvent::Edited),
+                EditorEvent::DirtyChanged => cx.emit(NotebookEditorEvent::TitleChanged),
+                EditorEvent::Focused => {
+                    if let Some(index) = this.cell_order.iter().position(|id| *id == cell_id) {
+                        this.selected_cell_index = index;
+                        cx.notify();
+                    }
+                }
+                _ => {}
+            }
+        });
+        self.cell_subscriptions
+            .insert(cell_id.clone(), subscription);
+        self.cell_map.insert(cell_id.clone(), cell);
+        self.cell_order.insert(index, cell_id);
+    }
+
+    /// Removes a cell from the notebook, without updating the cell list.
+    fn remove_cell(&mut self, index: usize) -> Option<Cell> {
+        let cell_id = self.cell_order.remove(index);
+        self.cell_subscriptions.remove(&cell_id);
+        self.cell_map.remove(&cell_id)
+    }
+
+    fn selected_cell(&self) -> Option<&Cell> {
+        self.cell_order
+            .get(self.selected_cell_index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+    }
+
+    fn cells_changed(&mut self, cx: &mut Context<Self>) {
+        self.cells_changed = true;
+        cx.emit(NotebookEditorEvent::Edited);
+        cx.notify();
     }
 
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
@@ -174,13 +283,18 @@ impl NotebookEditor {
     }
 
     fn clear_outputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.has_outputs(window, cx) {
+            return;
+        }
         for cell in self.cell_map.values() {
             if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
+                code_cell.update(cx, |cell, cx| {
                     cell.clear_outputs();
+                    cx.notify();
                 });
             }
         }
+        self.cells_changed(cx);
     }
 
     fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -192,19 +306,198 @@ impl NotebookEditor {
     }
 
     fn move_cell_up(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_cell_index;
+        if index == 0 || index >= self.cell_order.len() {
+            return;
+        }
+        self.cell_order.swap(index - 1, index);
+        self.cell_list.splice(index - 1..index + 1, 2);
+        self.set_selected_index(index - 1, true, window, cx);
+        self.cells_changed(cx);
     }
 
     fn move_cell_down(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_cell_index;
+        if index + 1 >= self.cell_order.len() {
+            return;
+        }
+        self.cell_order.swap(index, index + 1);
+        self.cell_list.splice(index..index + 2, 2);
+        self.set_selected_index(index + 1, true, window, cx);
+        self.cells_changed(cx);
     }
 
     fn add_markdown_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add markdown block triggered");
+        self.add_cell(CellType::Markdown, window, cx);
     }
 
     fn add_code_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add code block triggered");
+        self.add_cell(CellType::Code, window, cx);
+    }
+
+    /// Adds an empty cell below the selected one and focuses it.
+    fn add_cell(&mut self, cell_type: CellType, window: &mut Window, cx: &mut Context<Self>) {
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            self.selected_cell_index + 1
+        };
+        let Some(cell) = Cell::from_json(
+            new_cell_json(cell_type, ""),
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        )
+        .log_err() else {
+            return;
+        };
+
+        self.insert_cell(index, cell.clone(), cx);
+        self.cell_list.splice(index..index, 1);
+        self.set_selected_index(index, true, window, cx);
+        cell.focus(window, cx);
+        self.cells_changed(cx);
+    }
+
+    fn delete_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        if index >= self.cell_order.len() {
+            return;
+        }
+        self.remove_cell(index);
+        self.cell_list.splice(index..index + 1, 0);
+        let index = index.min(self.cell_order.len().saturating_sub(1));
+        self.set_selected_index(index, true, window, cx);
+        window.focus(&self.focus_handle);
+        self.cells_changed(cx);
+    }
+
+    /// Splits the selected cell in two at the cursor.
+    fn split_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        let Some(cell) = self.selected_cell().cloned() else {
+            return;
+        };
+        let offset = cell.editor(cx).update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        });
+        let source = cell.source(cx);
+        let (before, after) = split_source(&source, offset);
+        let Some(new_cell) = Cell::from_json(
+            new_cell_json(cell.cell_type(), after),
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        )
+        .log_err() else {
+            return;
+        };
+
+        cell.set_source(before, window, cx);
+        self.insert_cell(index + 1, new_cell.clone(), cx);
+        self.cell_list.splice(index..index + 1, 2);
+        self.set_selected_index(index + 1, true, window, cx);
+        new_cell.focus(window, cx);
+        self.cells_changed(cx);
+    }
+
+    /// Appends the source of the cell below the selected one to it, and removes that cell.
+    fn merge_cell_below(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        if index + 1 >= self.cell_order.len() {
+            return;
+        }
+        let Some(cell) = self.selected_cell().cloned() else {
+            return;
+        };
+        let Some(next_cell) = self.remove_cell(index + 1) else {
+            return;
+        };
+
+        let source = merge_sources(&cell.source(cx), &next_cell.source(cx));
+        cell.set_source(&source, window, cx);
+        self.cell_list.splice(index..index + 2, 1);
+        self.cells_changed(cx);
+    }
+
+    fn change_cell_type(
+        &mut self,
+        cell_type: CellType,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let index = self.selected_cell_index;
+        let Some(cell) = self.selected_cell().cloned() else {
+            return;
+        };
+        if matches!(
+            (&cell, &cell_type),
+            (Cell::Code(_), CellType::Code)
+                | (Cell::Markdown(_), CellType::Markdown)
+                | (Cell::Raw(_), CellType::Raw)
+        ) {
+            return;
+        }
+
+        let mut json = cell.to_json(cx);
+        set_cell_type(&mut json, cell_type);
+        let Some(new_cell) = Cell::from_json(
+            json,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        )
+        .log_err() else {
+            return;
+        };
+
+        self.remove_cell(index);
+        self.insert_cell(index, new_cell.clone(), cx);
+        self.cell_list.splice(index..index + 1, 1);
+        new_cell.focus(window, cx);
+        self.cells_changed(cx);
+    }
+
+    /// Returns the notebook file contents for the current cells.
+    fn serialize(&self, cx: &App) -> Result<String> {
+        let cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| self.cell_map.get(cell_id))
+            .map(|cell| cell.to_json(cx))
+            .collect();
+        notebook_content(&self.notebook_item.read(cx).json, cells)
+    }
+
+    /// Writes the notebook to the given path, marking it as saved.
+    fn write(
+        &mut self,
+        abs_path: PathBuf,
+        project_path: ProjectPath,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let content = match self.serialize(cx) {
+            Ok(content) => content,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let write = self.notebook_item.update(cx, |notebook_item, cx| {
+            notebook_item.write(abs_path, project_path, content, cx)
+        });
+        cx.spawn(async move |this, cx| {
+            write.await?;
+            this.update(cx, |this, cx| {
+                for cell in this.cell_map.values() {
+                    cell.did_save(cx);
+                }
+                this.cells_changed = false;
+                this.has_conflict = false;
+                cx.emit(NotebookEditorEvent::TitleChanged);
+                cx.notify();
+            })
+        })
     }
 
     fn cell_count(&self) -> usize {
@@ -527,6 +820,20 @@ impl Render for NotebookEditor {
             .on_action(
                 cx.listener(|this, &AddCodeBlock, window, cx| this.add_code_block(window, cx)),
             )
+            .on_action(cx.listener(|this, &DeleteCell, window, cx| this.delete_cell(window, cx)))
+            .on_action(cx.listener(|this, &SplitCell, window, cx| this.split_cell(window, cx)))
+            .on_action(
+                cx.listener(|this, &MergeCellBelow, window, cx| this.merge_cell_below(window, cx)),
+            )
+            .on_action(cx.listener(|this, &ChangeToCodeCell, window, cx| {
+                this.change_cell_type(CellType::Code, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeToMarkdownCell, window, cx| {
+                this.change_cell_type(CellType::Markdown, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeToRawCell, window, cx| {
+                this.change_cell_type(CellType::Raw, window, cx)
+            }))
             .on_action(cx.listener(Self::select_next))
             .on_action(cx.listener(Self::select_previous))
             .on_action(cx.listener(Self::select_first))
@@ -561,10 +868,96 @@ pub struct NotebookItem {
     path: PathBuf,
     project_path: ProjectPath,
     languages: Arc<LanguageRegistry>,
+    fs: Arc<dyn Fs>,
     // Raw notebook data
     notebook: nbformat::v4::Notebook,
+    /// The notebook as JSON, with the fields Zed doesn't use, to write them back unchanged.
+    json: Map<String, Value>,
+    /// The file contents as last read or written, to tell our own writes apart from external changes.
+    saved_content: String,
     // Store our version of the notebook in memory (cell_order, cell_map)
     id: ProjectEntryId,
+    _watch_task: Task<()>,
+}
+
+pub enum NotebookItemEvent {
+    /// The file was changed by another program, and the new contents were read into the notebook.
+    ChangedOnDisk,
+}
+
+impl EventEmitter<NotebookItemEvent> for NotebookItem {}
+
+/// Parses a notebook file, upgrading notebooks in older v4 minor versions.
+///
+/// Returns the JSON of the notebook along with it, as the notebook only has the fields Zed uses.
+fn parse_notebook(content: &str) -> Result<(nbformat::v4::Notebook, Map<String, Value>)> {
+    match nbformat::parse_notebook(content) {
+        Ok(nbformat::Notebook::V4(notebook)) => {
+            let json = serde_json::from_str(content).context("parsing notebook JSON")?;
+            Ok((notebook, json))
+        }
+        // 4.1 - 4.4 are converted to 4.5
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            // TODO: Decide if we want to mutate the notebook by including Cell IDs
+            // and any other conversions
+            let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
+            let Value::Object(json) = serde_json::to_value(&notebook)? else {
+                anyhow::bail!("notebook is not a JSON object");
+            };
+            Ok((notebook, json))
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    }
+}
+
+/// Returns the JSON of the notebook's cells.
+fn notebook_cells(notebook: &Map<String, Value>) -> Vec<Map<String, Value>> {
+    notebook
+        .get("cells")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|cell| cell.as_object().cloned())
+        .collect()
+}
+
+/// Returns the contents of the notebook file with the given cells.
+fn notebook_content(
+    notebook: &Map<String, Value>,
+    cells: Vec<Map<String, Value>>,
+) -> Result<String> {
+    let mut notebook = notebook.clone();
+    notebook.insert(
+        "cells".into(),
+        Value::Array(cells.into_iter().map(Value::Object).collect()),
+    );
+    format_notebook(Value::Object(notebook))
+}
+
+/// Formats notebook JSON the way Jupyter writes it: with sorted keys, one-space indentation and a
+/// trailing newline, to keep diffs of saved notebooks small.
+pub(crate) fn format_notebook(mut notebook: serde_json::Value) -> Result<String> {
+    fn sort_keys(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.sort_keys();
+                map.values_mut().for_each(sort_keys);
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(sort_keys),
+            _ => {}
+        }
+    }
+
+    sort_keys(&mut notebook);
+    let mut content = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    notebook.serialize(&mut serializer)?;
+    content.push(b'\n');
+    Ok(String::from_utf8(content)?)
 }
 
 impl project::ProjectItem for NotebookItem {
@@ -584,36 +977,28 @@ impl project::ProjectItem for NotebookItem {
                     .read_with(cx, |project, cx| project.absolute_path(&path, cx))?
                     .with_context(|| format!("finding the absolute path of {path:?}"))?;
 
-                // todo: watch for changes to the file
                 let file_content = fs.load(&abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-                        let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
-                        notebook
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let (notebook, json) = parse_notebook(&file_content)?;
 
                 let id = project
                     .update(cx, |project, cx| project.entry_for_path(&path, cx))?
                     .context("Entry not found")?
                     .id;
 
-                cx.new(|_| NotebookItem {
-                    path: abs_path,
-                    project_path: path,
-                    languages,
-                    notebook,
-                    id,
+                cx.new(|cx| {
+                    let mut notebook_item = NotebookItem {
+                        path: abs_path,
+                        project_path: path,
+                        languages,
+                        fs,
+                        notebook,
+                        json,
+                        saved_content: file_content,
+                        id,
+                        _watch_task: Task::ready(()),
+                    };
+                    notebook_item.watch_file(cx);
+                    notebook_item
                 })
             }))
         } else {
@@ -661,9 +1046,88 @@ impl NotebookItem {
             }
         }
     }
+
+    /// Reads the notebook again whenever its file changes on disk.
+    fn watch_file(&mut self, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        let path = self.path.clone();
+        self._watch_task = cx.spawn(async move |this, cx| {
+            let (events, _watcher) = fs.watch(&path, FILE_WATCH_LATENCY).await;
+            futures::pin_mut!(events);
+
+            while events.next().await.is_some() {
+                let Ok(content) = fs.load(&path).await else {
+                    continue;
+                };
+                let Ok(()) = this.update(cx, |this, cx| this.file_changed(content, cx)) else {
+                    break;
+                };
+            }
+        });
+    }
+
+    fn file_changed(&mut self, content: String, cx: &mut Context<Self>) {
+        if content == self.saved_content {
+            return;
+        }
+        match parse_notebook(&content) {
+            Ok((notebook, json)) => {
+                self.notebook = notebook;
+                self.json = json;
+                self.saved_content = content;
+                cx.emit(NotebookItemEvent::ChangedOnDisk);
+            }
+            Err(error) => log::warn!("failed to reload {}: {error:#}", self.path.display()),
+        }
+    }
+
+    /// Reads the notebook from disk, discarding the contents read before.
+    fn reload(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let path = self.path.clone();
+        cx.spawn(async move |this, cx| {
+            let content = fs.load(&path).await?;
+            let (notebook, json) = parse_notebook(&content)?;
+            this.update(cx, |this, _| {
+                this.notebook = notebook;
+                this.json = json;
+                this.saved_content = content;
+            })
+        })
+    }
+
+    /// Writes the notebook to the given path, which becomes the notebook's path.
+    fn write(
+        &mut self,
+        abs_path: PathBuf,
+        project_path: ProjectPath,
+        content: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let (notebook, json) = match parse_notebook(&content) {
+            Ok(parsed) => parsed,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let path_changed = abs_path != self.path;
+        // Record the new contents before writing them, so the file watcher ignores our own write.
+        self.saved_content = content.clone();
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            fs.atomic_write(abs_path.clone(), content).await?;
+            this.update(cx, |this, cx| {
+                this.notebook = notebook;
+                this.json = json;
+                if path_changed {
+                    this.path = abs_path;
+                    this.project_path = project_path;
+                    this.watch_file(cx);
+                }
+            })
+        })
+    }
 }
 
-impl EventEmitter<()> for NotebookEditor {}
+impl EventEmitter<NotebookEditorEvent> for NotebookEditor {}
 
 // pub struct NotebookControls {
 //     pane_focused: bool,
@@ -711,7 +1175,17 @@ impl EventEmitter<()> for NotebookEditor {}
 // }
 
 impl Item for NotebookEditor {
-    type Event = ();
+    type Event = NotebookEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            NotebookEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            NotebookEditorEvent::TitleChanged => f(ItemEvent::UpdateTab),
+        }
+    }
 
     fn clone_on_split(
         &self,
@@ -782,49 +1256,77 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
+    fn can_save_as(&self, _cx: &App) -> bool {
+        true
+    }
+
     fn save(
         &mut self,
         _options: SaveOptions,
         _project: Entity<Project>,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let notebook_item = self.notebook_item.read(cx);
+        let abs_path = notebook_item.path.clone();
+        let project_path = notebook_item.project_path.clone();
+        self.write(abs_path, project_path, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Entity<Project>,
-        _path: ProjectPath,
+        project: Entity<Project>,
+        path: ProjectPath,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "finding the absolute path of {path:?}"
+            )));
+        };
+        let write = self.write(abs_path, path.clone(), cx);
+        cx.spawn(async move |this, cx| {
+            write.await?;
+            this.update(cx, |this, cx| {
+                let entry_id = project
+                    .read(cx)
+                    .entry_for_path(&path, cx)
+                    .map(|entry| entry.id);
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    if let Some(entry_id) = entry_id {
+                        notebook_item.id = entry_id;
+                    }
+                });
+            })
+        })
     }
-    // TODO
+
     fn reload(
         &mut self,
         _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let reload = self
+            .notebook_item
+            .update(cx, |notebook_item, cx| notebook_item.reload(cx));
+        cx.spawn_in(window, async move |this, cx| {
+            reload.await?;
+            this.update_in(cx, |this, window, cx| this.load_cells(window, cx))
+        })
+    }
+
+    fn has_conflict(&self, _cx: &App) -> bool {
+        self.has_conflict
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
-        })
+        self.cells_changed || self.cell_map.values().any(|cell| cell.is_dirty(cx))
     }
 }
 
@@ -847,3 +1349,111 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, window, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_notebook() {
+        let notebook = json!({
+            "nbformat": 4,
+            "nbformat_minor": 5,
+            "metadata": { "zed": { "b": 1, "a": 2 } },
+            "cells": [{ "source": ["x = 1"], "cell_type": "code" }],
+        });
+        assert_eq!(
+            format_notebook(notebook).unwrap(),
+            r#"{
+ "cells": [
+  {
+   "cell_type": "code",
+   "source": [
+    "x = 1"
+   ]
+  }
+ ],
+ "metadata": {
+  "zed": {
+   "a": 2,
+   "b": 1
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"#
+        );
+    }
+    #[test]
+    fn test_round_trip_unknown_fields() {
+        let content = r##"{
+ "cells": [
+  {
+   "cell_type": "code",
+   "execution_count": 3,
+   "id": "a1",
+   "metadata": {
+    "collapsed": true,
+    "custom_tool": {
+     "pinned": true
+    }
+   },
+   "outputs": [],
+   "source": [
+    "x = 1\n",
+    "x"
+   ],
+   "unknown_cell_field": "kept"
+  },
+  {
+   "attachments": {},
+   "cell_type": "markdown",
+   "id": "b2",
+   "metadata": {
+    "tags": [
+     "intro"
+    ]
+   },
+   "source": [
+    "# Title"
+   ]
+  }
+ ],
+ "metadata": {
+  "custom_extension": {
+   "version": 2
+  },
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5,
+ "unknown_notebook_field": [
+  1,
+  2
+ ]
+}
+"##;
+        let (_, json) = parse_notebook(content).unwrap();
+        let cells = notebook_cells(&json)
+            .into_iter()
+            .map(|cell| {
+                let cell_json = cell.clone();
+                let parsed =
+                    serde_json::from_value::<nbformat::v4::Cell>(Value::Object(cell)).unwrap();
+                let source = match &parsed {
+                    nbformat::v4::Cell::Code { source, .. }
+                    | nbformat::v4::Cell::Markdown { source, .. }
+                    | nbformat::v4::Cell::Raw { source, .. } => source.join(""),
+                };
+                cell_json_with_source(cell_json, &source)
+            })
+            .collect();
+        assert_eq!(notebook_content(&json, cells).unwrap(), content);
+    }
+}
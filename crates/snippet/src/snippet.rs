@@ -1,4 +1,5 @@
 use anyhow::{Context as _, Result};
+use regex::{Captures, Regex, RegexBuilder};
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
 
@@ -12,25 +13,136 @@ pub struct Snippet {
 pub struct TabStop {
     pub ranges: SmallVec<[Range<isize>; 2]>,
     pub choices: Option<Vec<String>>,
+    /// Ranges that mirror this tabstop through a transform, such as `${1/(.*)/${1:/upcase}/}`.
+    /// They aren't edited directly, and are updated from the tabstop's first range when it's exited.
+    pub transforms: Vec<(Range<isize>, Transform)>,
+}
+
+/// Resolves snippet variables such as `$TM_FILENAME` or `$CURRENT_YEAR`.
+pub trait VariableResolver {
+    /// Returns the value of the variable, or `None` if it isn't a known variable.
+    fn resolve(&self, name: &str) -> Option<String>;
+}
+
+impl VariableResolver for () {
+    fn resolve(&self, _: &str) -> Option<String> {
+        None
+    }
+}
+
+/// A regex transform, as in `${TM_FILENAME/(.*)\..+$/$1/}`.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, Case),
+    Conditional {
+        group: usize,
+        if_text: String,
+        else_text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Case {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+}
+
+struct ParseState<'a> {
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    variables: &'a dyn VariableResolver,
+    /// The text of each tabstop's first range, used as the input to its transforms.
+    tabstop_text: &'a BTreeMap<usize, String>,
+    unknown_variables: Vec<Range<isize>>,
+    has_transforms: bool,
 }
 
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &())
+    }
+
+    /// Parses a snippet, expanding variables with the given resolver. As in VS Code, unknown
+    /// variables without a default are inserted as placeholders containing their name.
+    pub fn parse_with_variables(source: &str, variables: &dyn VariableResolver) -> Result<Self> {
+        let no_tabstop_text = BTreeMap::new();
+        let first_pass = parse_pass(source, variables, &no_tabstop_text)?;
+
+        // A transform's input is the text of a tabstop that may be defined after it, so the
+        // snippet is parsed again once the tabstops' text is known.
+        let tabstop_text = if first_pass.has_transforms {
+            first_pass
+                .tabstops
+                .iter()
+                .filter_map(|(index, tabstop)| {
+                    let range = tabstop.ranges.first()?;
+                    let text = first_pass
+                        .text
+                        .get(range.start as usize..range.end as usize)?;
+                    Some((*index, text.to_string()))
+                })
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+        let state = if first_pass.has_transforms {
+            parse_pass(source, variables, &tabstop_text)?
+        } else {
+            first_pass
+        };
+
+        let ParseState {
+            text,
+            mut tabstops,
+            unknown_variables,
+            ..
+        } = state;
+
+        let first_unused_index = tabstops.keys().last().map_or(1, |index| index + 1);
+        for (index, range) in (first_unused_index..).zip(unknown_variables) {
+            tabstops.insert(
+                index,
+                TabStop {
+                    ranges: [range].into_iter().collect(),
+                    ..Default::default()
+                },
+            );
+        }
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
-        let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
+        // Tabstops that only appear in transforms can't be navigated to.
+        let mut tabstops = tabstops
+            .into_values()
+            .filter(|tabstop| !tabstop.ranges.is_empty())
+            .collect::<Vec<_>>();
 
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
         } else {
             let end_tabstop = TabStop {
                 ranges: [len..len].into_iter().collect(),
-                choices: None,
+                ..Default::default()
             };
 
             if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
@@ -42,17 +154,116 @@ impl Snippet {
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
+impl Transform {
+    /// Replaces the first match of the regex in the given text (or every match, with the `g`
+    /// option) with the format string.
+    pub fn apply(&self, text: &str) -> String {
+        let replacer = |captures: &Captures| self.format(captures);
+        if self.global {
+            self.regex.replace_all(text, replacer).into_owned()
+        } else {
+            self.regex.replace(text, replacer).into_owned()
+        }
+    }
+
+    fn format(&self, captures: &Captures) -> String {
+        let group = |index: usize| captures.get(index).map_or("", |m| m.as_str());
+        let mut result = String::new();
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => result.push_str(text),
+                FormatItem::Group(index) => result.push_str(group(*index)),
+                FormatItem::Case(index, case) => result.push_str(&case.apply(group(*index))),
+                FormatItem::Conditional {
+                    group: index,
+                    if_text,
+                    else_text,
+                } => {
+                    if group(*index).is_empty() {
+                        result.push_str(else_text)
+                    } else {
+                        result.push_str(if_text)
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Case {
+    fn apply(self, text: &str) -> String {
+        match self {
+            Case::Upcase => text.to_uppercase(),
+            Case::Downcase => text.to_lowercase(),
+            Case::Capitalize => {
+                let mut chars = text.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Case::Camelcase | Case::Pascalcase => {
+                let mut result = String::new();
+                for word in text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        if self == Case::Camelcase && result.is_empty() {
+                            result.extend(first.to_lowercase());
+                        } else {
+                            result.extend(first.to_uppercase());
+                        }
+                        result.extend(chars);
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+fn parse_pass<'a>(
+    source: &str,
+    variables: &'a dyn VariableResolver,
+    tabstop_text: &'a BTreeMap<usize, String>,
+) -> Result<ParseState<'a>> {
+    let mut state = ParseState {
+        text: String::with_capacity(source.len()),
+        tabstops: BTreeMap::new(),
+        variables,
+        tabstop_text,
+        unknown_variables: Vec::new(),
+        has_transforms: false,
+    };
+    parse_snippet(source, false, &mut state).context("failed to parse snippet")?;
+    Ok(state)
+}
+
+fn parse_snippet<'a>(mut source: &'a str, nested: bool, state: &mut ParseState) -> Result<&'a str> {
     loop {
         match source.chars().next() {
             None => return Ok(""),
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                let rest = &source[1..];
+                if rest.starts_with(|c: char| c.is_ascii_digit())
+                    || rest
+                        .strip_prefix('{')
+                        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+                {
+                    source = parse_tabstop(rest, state)?;
+                } else if rest.starts_with(is_variable_start)
+                    || rest
+                        .strip_prefix('{')
+                        .is_some_and(|rest| rest.starts_with(is_variable_start))
+                {
+                    source = parse_variable(rest, state)?;
+                } else {
+                    state.text.push('$');
+                    source = rest;
+                }
             }
             Some('\\') => {
                 // As specified in the LSP spec (`Grammar` section),
@@ -61,42 +272,39 @@ fn parse_snippet<'a>(
                 source = &source[1..];
                 if let Some(c) = source.chars().next() {
                     if c == '$' || c == '\\' || c == '}' {
-                        text.push(c);
+                        state.text.push(c);
                         // All escapable characters are 1 byte long:
                         source = &source[1..];
                     } else {
-                        text.push('\\');
+                        state.text.push('\\');
                     }
                 } else {
-                    text.push('\\');
+                    state.text.push('\\');
                 }
             }
             Some('}') => {
                 if nested {
                     return Ok(source);
                 } else {
-                    text.push('}');
+                    state.text.push('}');
                     source = &source[1..];
                 }
             }
             Some(_) => {
                 let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
                 let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
+                state.text.push_str(chunk);
                 source = rest;
             }
         }
     }
 }
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
+fn parse_tabstop<'a>(mut source: &'a str, state: &mut ParseState) -> Result<&'a str> {
+    let tabstop_start = state.text.len();
     let tabstop_index;
     let mut choices = None;
+    let mut transform = None;
 
     if source.starts_with('{') {
         let (index, rest) = parse_int(&source[1..])?;
@@ -104,11 +312,21 @@ fn parse_tabstop<'a>(
         source = rest;
 
         if source.starts_with("|") {
-            (source, choices) = parse_choices(&source[1..], text)?;
+            (source, choices) = parse_choices(&source[1..], &mut state.text)?;
         }
 
         if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+            source = parse_snippet(&source[1..], true, state)?;
+        } else if source.starts_with('/') {
+            let parsed;
+            (parsed, source) = parse_transform(&source[1..])?;
+            let text = state
+                .tabstop_text
+                .get(&tabstop_index)
+                .map_or("", String::as_str);
+            state.text.push_str(&parsed.apply(text));
+            state.has_transforms = true;
+            transform = Some(parsed);
         }
 
         if source.starts_with('}') {
@@ -122,17 +340,258 @@ fn parse_tabstop<'a>(
         source = rest;
     }
 
-    tabstops
+    let range = tabstop_start as isize..state.text.len() as isize;
+    let tabstop = state
+        .tabstops
         .entry(tabstop_index)
         .or_insert_with(|| TabStop {
             ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
+            choices: None,
+            transforms: Vec::new(),
+        });
+    if let Some(transform) = transform {
+        tabstop.transforms.push((range, transform));
+    } else {
+        if tabstop.choices.is_none() {
+            tabstop.choices = choices;
+        }
+        tabstop.ranges.push(range);
+    }
+    Ok(source)
+}
+
+fn is_variable_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic()
+}
+
+fn parse_variable<'a>(mut source: &'a str, state: &mut ParseState) -> Result<&'a str> {
+    let braced = source.starts_with('{');
+    if braced {
+        source = &source[1..];
+    }
+    let name_len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    let (name, rest) = source.split_at(name_len);
+    source = rest;
+
+    let variable_start = state.text.len();
+    let value = state.variables.resolve(name);
+    let is_known = value.is_some();
+    let value = value.filter(|value| !value.is_empty());
+
+    if braced {
+        if let Some(rest) = source.strip_prefix(':') {
+            // The default is only used when the variable is unknown or empty,
+            // but it's always parsed so that the rest of the snippet can be found.
+            let tabstops = state.tabstops.clone();
+            let unknown_variable_count = state.unknown_variables.len();
+            source = parse_snippet(rest, true, state)?;
+            if let Some(value) = &value {
+                state.text.truncate(variable_start);
+                state.tabstops = tabstops;
+                state.unknown_variables.truncate(unknown_variable_count);
+                state.text.push_str(value);
+            }
+        } else if let Some(rest) = source.strip_prefix('/') {
+            let transform;
+            (transform, source) = parse_transform(rest)?;
+            state
+                .text
+                .push_str(&transform.apply(value.as_deref().unwrap_or_default()));
+        } else if is_known {
+            state.text.push_str(value.as_deref().unwrap_or_default());
+        } else {
+            state.text.push_str(name);
+            state
+                .unknown_variables
+                .push(variable_start as isize..state.text.len() as isize);
+        }
+
+        if source.starts_with('}') {
+            source = &source[1..];
+        } else {
+            anyhow::bail!("expected a closing brace");
+        }
+    } else if is_known {
+        state.text.push_str(value.as_deref().unwrap_or_default());
+    } else {
+        state.text.push_str(name);
+        state
+            .unknown_variables
+            .push(variable_start as isize..state.text.len() as isize);
+    }
+
     Ok(source)
 }
 
+// Parses `regex/format/options` (the part after the first slash), up to the closing brace.
+fn parse_transform(source: &str) -> Result<(Transform, &str)> {
+    let (pattern, source) = parse_until_slash(source, false)?;
+    let (format, source) = parse_until_slash(source, true)?;
+    let options_len = source.find('}').context("expected a closing brace")?;
+    let (options, source) = source.split_at(options_len);
+
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.contains('i'))
+        .multi_line(options.contains('m'))
+        .build()?;
+    let transform = Transform {
+        regex,
+        format: parse_format(&format)?,
+        global: options.contains('g'),
+    };
+    Ok((transform, source))
+}
+
+// Reads up to an unescaped `/`, unescaping `\/`. Other escapes are kept for the regex (or
+// the format string, which handles them itself). Slashes within `${...}` in a format string,
+// such as `${1:/upcase}`, don't end it.
+fn parse_until_slash(source: &str, is_format: bool) -> Result<(String, &str)> {
+    let mut result = String::new();
+    let mut depth = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((ix, c)) = chars.next() {
+        match c {
+            '/' if depth == 0 => return Ok((result, &source[ix + 1..])),
+            '$' if is_format && chars.peek().is_some_and(|(_, c)| *c == '{') => {
+                depth += 1;
+                result.push(c);
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                result.push(c);
+            }
+            '\\' => match chars.next() {
+                Some((_, '/')) => result.push('/'),
+                Some((_, c)) => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                None => result.push('\\'),
+            },
+            c => result.push(c),
+        }
+    }
+    anyhow::bail!("expected a closing slash in transform")
+}
+
+fn parse_format(mut source: &str) -> Result<Vec<FormatItem>> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => break,
+            Some('$') => {
+                let rest = &source[1..];
+                let (item, rest) = if let Some(rest) = rest.strip_prefix('{') {
+                    let (group, rest) = parse_int(rest)?;
+                    parse_format_group(group, rest)?
+                } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                    let (group, rest) = parse_int(rest)?;
+                    (FormatItem::Group(group), rest)
+                } else {
+                    text.push('$');
+                    source = rest;
+                    continue;
+                };
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(std::mem::take(&mut text)));
+                }
+                items.push(item);
+                source = rest;
+            }
+            Some('\\') => {
+                let (unescaped, rest) = parse_format_escape(&source[1..]);
+                text.push_str(&unescaped);
+                source = rest;
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+    if !text.is_empty() {
+        items.push(FormatItem::Text(text));
+    }
+    Ok(items)
+}
+
+// Parses the rest of `${1}`, `${1:/upcase}`, `${1:+if}`, `${1:?if:else}`, `${1:-else}` or
+// `${1:else}`, after the group number.
+fn parse_format_group(group: usize, source: &str) -> Result<(FormatItem, &str)> {
+    if let Some(rest) = source.strip_prefix('}') {
+        return Ok((FormatItem::Group(group), rest));
+    }
+    let source = source
+        .strip_prefix(':')
+        .context("expected a colon or closing brace")?;
+
+    if let Some(rest) = source.strip_prefix('/') {
+        let end = rest.find('}').context("expected a closing brace")?;
+        let case = match &rest[..end] {
+            "upcase" => Case::Upcase,
+            "downcase" => Case::Downcase,
+            "capitalize" => Case::Capitalize,
+            "camelcase" => Case::Camelcase,
+            "pascalcase" => Case::Pascalcase,
+            other => anyhow::bail!("unknown case modifier {other:?}"),
+        };
+        return Ok((FormatItem::Case(group, case), &rest[end + 1..]));
+    }
+
+    let (if_text, else_text, rest) = if let Some(rest) = source.strip_prefix('+') {
+        let (if_text, rest) = parse_format_text(rest, &['}'])?;
+        (if_text, String::new(), &rest[1..])
+    } else if let Some(rest) = source.strip_prefix('?') {
+        let (if_text, rest) = parse_format_text(rest, &[':'])?;
+        let (else_text, rest) = parse_format_text(&rest[1..], &['}'])?;
+        (if_text, else_text, &rest[1..])
+    } else {
+        let source = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, rest) = parse_format_text(source, &['}'])?;
+        (String::new(), else_text, &rest[1..])
+    };
+    Ok((
+        FormatItem::Conditional {
+            group,
+            if_text,
+            else_text,
+        },
+        rest,
+    ))
+}
+
+// Reads literal text up to (but not including) one of the terminators.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => anyhow::bail!("expected one of {terminators:?}"),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                let (unescaped, rest) = parse_format_escape(&source[1..]);
+                text.push_str(&unescaped);
+                source = rest;
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn parse_format_escape(source: &str) -> (String, &str) {
+    match source.chars().next() {
+        Some('n') => ("\n".to_string(), &source[1..]),
+        Some('t') => ("\t".to_string(), &source[1..]),
+        Some(c @ ('$' | '\\' | '/' | '}' | ':' | '+' | '?' | '-')) => (c.to_string(), &source[1..]),
+        _ => ("\\".to_string(), source),
+    }
+}
+
 fn parse_int(source: &str) -> Result<(usize, &str)> {
     let len = source
         .find(|c: char| !c.is_ascii_digit())
@@ -324,6 +783,98 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = TestVariables(&[
+            ("TM_FILENAME", "main.rs"),
+            ("TM_SELECTED_TEXT", ""),
+            ("CURRENT_YEAR", "2025"),
+        ]);
+
+        let snippet =
+            Snippet::parse_with_variables("// $TM_FILENAME (c) ${CURRENT_YEAR}$1", &variables)
+                .unwrap();
+        assert_eq!(snippet.text, "// main.rs (c) 2025");
+        assert_eq!(tabstops(&snippet), &[vec![19..19]]);
+
+        // Defaults are used when a variable is empty or unknown.
+        let snippet = Snippet::parse_with_variables(
+            "${TM_SELECTED_TEXT:${1:default}} ${TM_FILENAME:${2:unused}}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "default main.rs");
+        assert_eq!(tabstops(&snippet), &[vec![0..7], vec![15..15]]);
+
+        // Unknown variables become placeholders with their name.
+        let snippet =
+            Snippet::parse_with_variables("$1 $UNKNOWN ${ALSO_UNKNOWN}", &variables).unwrap();
+        assert_eq!(snippet.text, " UNKNOWN ALSO_UNKNOWN");
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![0..0], vec![1..8], vec![9..21], vec![21..21]]
+        );
+
+        // A dollar sign that doesn't start a tabstop or variable is literal.
+        let snippet = Snippet::parse("cost: $ 5").unwrap();
+        assert_eq!(snippet.text, "cost: $ 5");
+    }
+
+    #[test]
+    fn test_snippet_with_variable_transforms() {
+        let variables = TestVariables(&[("TM_FILENAME", "snippet_parser.rs")]);
+
+        let snippet =
+            Snippet::parse_with_variables("${TM_FILENAME/(.*)\\..+$/$1/}", &variables).unwrap();
+        assert_eq!(snippet.text, "snippet_parser");
+
+        let snippet = Snippet::parse_with_variables(
+            "struct ${TM_FILENAME/(.*)\\..+$/${1:/pascalcase}/};",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "struct SnippetParser;");
+
+        let snippet = Snippet::parse_with_variables("${TM_FILENAME/_/-/g}", &variables).unwrap();
+        assert_eq!(snippet.text, "snippet-parser.rs");
+
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME/(test_)?(.*)/${1:?test:src} ${2:/upcase}/}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "src SNIPPET_PARSER.RS");
+    }
+
+    #[test]
+    fn test_snippet_with_tabstop_transforms() {
+        let snippet = Snippet::parse("${1/(.*)/${1:/upcase}/} = ${1:name};").unwrap();
+        assert_eq!(snippet.text, "NAME = name;");
+        assert_eq!(tabstops(&snippet), &[vec![7..11], vec![12..12]]);
+
+        let transforms = &snippet.tabstops[0].transforms;
+        assert_eq!(transforms.len(), 1);
+        assert_eq!(transforms[0].0, 0..4);
+        assert_eq!(transforms[0].1.apply("other"), "OTHER");
+
+        let snippet = Snippet::parse("${1:a}${1/(a)|b/${1:+one}${1:-two}/}").unwrap();
+        assert_eq!(snippet.text, "aone");
+        let transform = &snippet.tabstops[0].transforms[0].1;
+        assert_eq!(transform.apply("b"), "two");
+        assert_eq!(transform.apply("c"), "c");
+    }
+
+    struct TestVariables(&'static [(&'static str, &'static str)]);
+
+    impl VariableResolver for TestVariables {
+        fn resolve(&self, name: &str) -> Option<String> {
+            self.0
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
@@ -0,0 +1,690 @@
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Extracts problems (errors, warnings, etc.) out of the task output, to show them as diagnostics.
+///
+/// Either a name of a built-in matcher, such as `$rustc`, `$tsc`, `$gcc`, `$eslint-compact` or `$eslint-stylish`,
+/// or a custom matcher definition.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    Preset(String),
+    Custom(ProblemMatcherDefinition),
+}
+
+/// A custom problem matcher.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcherDefinition {
+    /// A name to show as the source of the diagnostics produced, defaults to the task label.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// The severity of the problems that do not capture their own severity, defaults to `error`.
+    #[serde(default)]
+    pub severity: Option<ProblemSeverity>,
+    /// How to interpret the file paths captured:
+    /// * `autodetect` — use absolute paths as is, resolve the rest relative to `base_dir` (default)
+    /// * `absolute` — all paths are absolute
+    /// * `relative` — all paths are relative to `base_dir`
+    #[serde(default)]
+    pub file_location: FileLocation,
+    /// The directory to resolve relative file paths against, defaults to the task's working directory.
+    /// May use the task variables.
+    #[serde(default)]
+    pub base_dir: Option<String>,
+    /// Patterns to match against consecutive lines of the task output.
+    /// All captures of the patterns are combined into a single problem.
+    pub pattern: Vec<ProblemPattern>,
+}
+
+/// A regular expression to match a single line of the task output against, with the numbers of its capture groups
+/// that contain parts of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line against.
+    pub regexp: String,
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// A group with the `line`, `line,column` or `line,column,end_line,end_column` location.
+    #[serde(default)]
+    pub location: Option<usize>,
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    #[serde(default)]
+    pub severity: Option<usize>,
+    #[serde(default)]
+    pub code: Option<usize>,
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of a multi-line matcher may match multiple consecutive lines,
+    /// producing a problem for each of them.
+    #[serde(default)]
+    pub r#loop: bool,
+}
+
+/// How to interpret the file paths captured by a [`ProblemMatcher`].
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileLocation {
+    /// Use absolute paths as is, resolve the rest relative to the base directory.
+    #[default]
+    #[serde(alias = "autoDetect")]
+    Autodetect,
+    /// All paths are absolute.
+    Absolute,
+    /// All paths are relative to the base directory.
+    Relative,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn from_output(text: &str) -> Option<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "error" | "fatal error" | "e" => Some(Self::Error),
+            "warning" | "warn" | "w" => Some(Self::Warning),
+            "info" | "information" | "note" | "i" => Some(Self::Info),
+            "hint" | "help" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in the task output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub path: PathBuf,
+    /// 1-based.
+    pub line: u32,
+    /// 1-based.
+    pub column: Option<u32>,
+    /// 1-based.
+    pub end_line: Option<u32>,
+    /// 1-based, exclusive.
+    pub end_column: Option<u32>,
+    pub severity: ProblemSeverity,
+    pub code: Option<String>,
+    pub message: String,
+    pub source: Option<String>,
+}
+
+impl ProblemMatcher {
+    /// Definition of the matcher, with the presets looked up by their names.
+    pub fn definition(&self) -> Option<ProblemMatcherDefinition> {
+        match self {
+            Self::Preset(name) => preset(name),
+            Self::Custom(definition) => Some(definition.clone()),
+        }
+    }
+}
+
+/// Looks up a built-in problem matcher by its name, e.g. `$rustc`.
+pub fn preset(name: &str) -> Option<ProblemMatcherDefinition> {
+    let name = name.strip_prefix('$').unwrap_or(name);
+    let (source, pattern) = match name {
+        "rustc" => (
+            "rustc",
+            vec![
+                ProblemPattern {
+                    regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s+-->\s+(.*?):(\d+):(\d+)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..ProblemPattern::default()
+                },
+            ],
+        ),
+        "tsc" | "tsc-watch" => (
+            "ts",
+            vec![ProblemPattern {
+                regexp: r"^([^\s].*?)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+TS(\d+)\s*:\s*(.*)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..ProblemPattern::default()
+            }],
+        ),
+        "gcc" => (
+            "gcc",
+            vec![ProblemPattern {
+                regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..ProblemPattern::default()
+            }],
+        ),
+        "eslint-compact" => (
+            "eslint",
+            vec![ProblemPattern {
+                regexp: r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                code: Some(6),
+                ..ProblemPattern::default()
+            }],
+        ),
+        "eslint-stylish" => (
+            "eslint",
+            vec![
+                ProblemPattern {
+                    regexp: r"^(\S.*)$".to_string(),
+                    file: Some(1),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.+?)(?:\s\s+(.*))?$"
+                        .to_string(),
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    r#loop: true,
+                    ..ProblemPattern::default()
+                },
+            ],
+        ),
+        _ => return None,
+    };
+    Some(ProblemMatcherDefinition {
+        source: Some(source.to_string()),
+        pattern,
+        ..ProblemMatcherDefinition::default()
+    })
+}
+
+/// Finds all problems in the task output given, with every matcher given.
+/// Relative paths are resolved against the matcher's base directory, or `cwd` if there is none.
+pub fn match_problems(
+    matchers: &[ProblemMatcher],
+    output: &str,
+    cwd: Option<&Path>,
+    default_source: &str,
+) -> Vec<Problem> {
+    let lines = output.lines().map(str::trim_end).collect::<Vec<_>>();
+    let (problems, _) = match_problems_in_lines(matchers, &lines, cwd, default_source);
+    problems.into_iter().map(|(_, problem)| problem).collect()
+}
+
+/// Like [`match_problems`], for the output of a task that's still running.
+///
+/// Returns the number of leading lines of the output whose problems won't change with more output,
+/// along with the problems found in them. The lines after them should be matched again, with the
+/// output appended to them.
+pub fn match_settled_problems(
+    matchers: &[ProblemMatcher],
+    output: &str,
+    cwd: Option<&Path>,
+    default_source: &str,
+) -> (usize, Vec<Problem>) {
+    let lines = output.lines().map(str::trim_end).collect::<Vec<_>>();
+    let (problems, settled_lines) = match_problems_in_lines(matchers, &lines, cwd, default_source);
+    let problems = problems
+        .into_iter()
+        .filter(|(start_ix, _)| *start_ix < settled_lines)
+        .map(|(_, problem)| problem)
+        .collect();
+    (settled_lines, problems)
+}
+
+/// Returns the problems found, with the index of the line their match starts at, and the number of
+/// lines before the first match that was cut off by the end of the lines.
+fn match_problems_in_lines(
+    matchers: &[ProblemMatcher],
+    lines: &[&str],
+    cwd: Option<&Path>,
+    default_source: &str,
+) -> (Vec<(usize, Problem)>, usize) {
+    let mut problems = Vec::new();
+    let mut settled_lines = lines.len();
+    for matcher in matchers {
+        let Some(definition) = matcher.definition() else {
+            log::warn!("Unknown problem matcher {matcher:?}");
+            continue;
+        };
+        let regexes = match definition
+            .pattern
+            .iter()
+            .map(|pattern| Regex::new(&pattern.regexp))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(regexes) => regexes,
+            Err(e) => {
+                log::warn!("Invalid problem matcher {matcher:?}: {e}");
+                continue;
+            }
+        };
+        if regexes.is_empty() {
+            continue;
+        }
+        let base_dir = definition.base_dir.as_deref().map(Path::new).or(cwd);
+        let cut_off_ix = DefinitionMatcher {
+            definition: &definition,
+            regexes: &regexes,
+            base_dir,
+            default_source,
+        }
+        .match_lines(lines, &mut problems);
+        if let Some(cut_off_ix) = cut_off_ix {
+            settled_lines = settled_lines.min(cut_off_ix);
+        }
+    }
+    (problems, settled_lines)
+}
+
+struct DefinitionMatcher<'a> {
+    definition: &'a ProblemMatcherDefinition,
+    regexes: &'a [Regex],
+    base_dir: Option<&'a Path>,
+    default_source: &'a str,
+}
+
+#[derive(Clone, Default)]
+struct ProblemData {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<String>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl DefinitionMatcher<'_> {
+    /// Returns the index of the first line of a match that the lines ran out before the end of.
+    fn match_lines(&self, lines: &[&str], problems: &mut Vec<(usize, Problem)>) -> Option<usize> {
+        let patterns = &self.definition.pattern;
+        let mut cut_off_ix = None;
+        let mut ix = 0;
+        while ix < lines.len() {
+            let mut data = ProblemData::default();
+            if !self.match_line(0, lines[ix], &mut data) {
+                ix += 1;
+                continue;
+            }
+
+            let mut next_ix = ix + 1;
+            let mut matched = true;
+            for pattern_ix in 1..patterns.len() {
+                let is_last = pattern_ix == patterns.len() - 1;
+                if is_last && patterns[pattern_ix].r#loop {
+                    let mut looped = false;
+                    while let Some(line) = lines.get(next_ix) {
+                        let mut looped_data = data.clone();
+                        if !self.match_line(pattern_ix, line, &mut looped_data) {
+                            break;
+                        }
+                        problems.extend(self.to_problem(looped_data).map(|problem| (ix, problem)));
+                        looped = true;
+                        next_ix += 1;
+                    }
+                    if next_ix == lines.len() {
+                        cut_off_ix.get_or_insert(ix);
+                    }
+                    if looped {
+                        ix = next_ix;
+                    } else {
+                        ix += 1;
+                    }
+                    matched = false;
+                    break;
+                }
+
+                match lines.get(next_ix) {
+                    Some(line) if self.match_line(pattern_ix, line, &mut data) => next_ix += 1,
+                    line => {
+                        if line.is_none() {
+                            cut_off_ix.get_or_insert(ix);
+                        }
+                        ix += 1;
+                        matched = false;
+                        break;
+                    }
+                }
+            }
+
+            if matched {
+                problems.extend(self.to_problem(data).map(|problem| (ix, problem)));
+                ix = next_ix;
+            }
+        }
+        cut_off_ix
+    }
+
+    fn match_line(&self, pattern_ix: usize, line: &str, data: &mut ProblemData) -> bool {
+        let Some(captures) = self.regexes[pattern_ix].captures(line) else {
+            return false;
+        };
+        let pattern = &self.definition.pattern[pattern_ix];
+        let group = |ix: Option<usize>| -> Option<String> {
+            captures
+                .get(ix?)
+                .map(|group| group.as_str().trim())
+                .filter(|group| !group.is_empty())
+                .map(ToOwned::to_owned)
+        };
+        let number = |ix: Option<usize>| group(ix)?.parse::<u32>().ok();
+
+        if let Some(file) = group(pattern.file) {
+            data.file = Some(file);
+        }
+        if let Some(location) = group(pattern.location) {
+            let mut parts = location
+                .split(',')
+                .map(|part| part.trim().parse::<u32>().ok());
+            data.line = parts.next().flatten().or(data.line);
+            data.column = parts.next().flatten().or(data.column);
+            data.end_line = parts.next().flatten().or(data.end_line);
+            data.end_column = parts.next().flatten().or(data.end_column);
+        }
+        set_if_some(&mut data.line, number(pattern.line));
+        set_if_some(&mut data.column, number(pattern.column));
+        set_if_some(&mut data.end_line, number(pattern.end_line));
+        set_if_some(&mut data.end_column, number(pattern.end_column));
+        set_if_some(&mut data.severity, group(pattern.severity));
+        set_if_some(&mut data.code, group(pattern.code));
+        append_message(&mut data.message, &captures, pattern.message);
+        true
+    }
+
+    fn to_problem(&self, data: ProblemData) -> Option<Problem> {
+        let file = data.file?;
+        let path = match self.definition.file_location {
+            FileLocation::Absolute => PathBuf::from(file),
+            FileLocation::Relative => match self.base_dir {
+                Some(base_dir) => base_dir.join(file),
+                None => PathBuf::from(file),
+            },
+            FileLocation::Autodetect => {
+                let path = PathBuf::from(file);
+                match self.base_dir {
+                    Some(base_dir) if path.is_relative() => base_dir.join(path),
+                    _ => path,
+                }
+            }
+        };
+        Some(Problem {
+            path,
+            line: data.line?,
+            column: data.column,
+            end_line: data.end_line,
+            end_column: data.end_column,
+            severity: data
+                .severity
+                .as_deref()
+                .and_then(ProblemSeverity::from_output)
+                .or(self.definition.severity)
+                .unwrap_or_default(),
+            code: data.code,
+            message: data.message?,
+            source: Some(
+                self.definition
+                    .source
+                    .clone()
+                    .unwrap_or_else(|| self.default_source.to_string()),
+            ),
+        })
+    }
+}
+
+fn set_if_some<T>(target: &mut Option<T>, value: Option<T>) {
+    if value.is_some() {
+        *target = value;
+    }
+}
+
+/// Messages captured by multiple lines are joined, same as multi-line compiler messages are.
+fn append_message(message: &mut Option<String>, captures: &Captures, group: Option<usize>) {
+    let Some(new_message) = group
+        .and_then(|group| captures.get(group))
+        .map(|group| group.as_str().trim())
+        .filter(|group| !group.is_empty())
+    else {
+        return;
+    };
+    match message {
+        Some(message) => {
+            message.push('\n');
+            message.push_str(new_message);
+        }
+        None => *message = Some(new_message.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(path: &str, line: u32, column: u32, severity: ProblemSeverity) -> Problem {
+        Problem {
+            path: PathBuf::from(path),
+            line,
+            column: Some(column),
+            end_line: None,
+            end_column: None,
+            severity,
+            code: None,
+            message: String::new(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_rustc_preset() {
+        let output = r#"   Compiling foo v0.1.0 (/project)
+warning: unused variable: `x`
+ --> src/main.rs:2:9
+  |
+2 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+
+error[E0308]: mismatched types
+  --> src/lib.rs:10:18
+   |
+error: aborting due to 1 previous error
+error: could not compile `foo` (bin "foo") due to 1 previous error; 1 warning emitted
+"#;
+        let problems = match_problems(
+            &[ProblemMatcher::Preset("$rustc".to_string())],
+            output,
+            Some(Path::new("/project")),
+            "cargo build",
+        );
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    message: "unused variable: `x`".to_string(),
+                    source: Some("rustc".to_string()),
+                    ..problem("/project/src/main.rs", 2, 9, ProblemSeverity::Warning)
+                },
+                Problem {
+                    code: Some("E0308".to_string()),
+                    message: "mismatched types".to_string(),
+                    source: Some("rustc".to_string()),
+                    ..problem("/project/src/lib.rs", 10, 18, ProblemSeverity::Error)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsc_and_gcc_presets() {
+        let output = "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+                      src/app.ts:12:1 - warning TS6133: 'foo' is declared but its value is never read.\n\
+                      /abs/main.c:4:5: warning: implicit declaration of function 'foo'\n\
+                      main.c:7:1: fatal error: bar.h: No such file or directory\n";
+        let problems = match_problems(
+            &[
+                ProblemMatcher::Preset("$tsc".to_string()),
+                ProblemMatcher::Preset("$gcc".to_string()),
+            ],
+            output,
+            Some(Path::new("/root")),
+            "build",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_string_lossy().into_owned(),
+                    problem.line,
+                    problem.column,
+                    problem.severity,
+                    problem.code.clone(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "/root/src/index.ts".to_string(),
+                    3,
+                    Some(7),
+                    ProblemSeverity::Error,
+                    Some("2322".to_string())
+                ),
+                (
+                    "/root/src/app.ts".to_string(),
+                    12,
+                    Some(1),
+                    ProblemSeverity::Warning,
+                    Some("6133".to_string())
+                ),
+                (
+                    "/abs/main.c".to_string(),
+                    4,
+                    Some(5),
+                    ProblemSeverity::Warning,
+                    None
+                ),
+                (
+                    "/root/main.c".to_string(),
+                    7,
+                    Some(1),
+                    ProblemSeverity::Error,
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_looping_custom_matcher() {
+        let matcher = ProblemMatcher::Custom(ProblemMatcherDefinition {
+            severity: Some(ProblemSeverity::Warning),
+            file_location: FileLocation::Relative,
+            base_dir: Some("/base".to_string()),
+            pattern: vec![
+                ProblemPattern {
+                    regexp: r"^== (.+) ==$".to_string(),
+                    file: Some(1),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^  (\d+),(\d+): (.+)$".to_string(),
+                    location: Some(1),
+                    column: Some(2),
+                    message: Some(3),
+                    r#loop: true,
+                    ..ProblemPattern::default()
+                },
+            ],
+            ..ProblemMatcherDefinition::default()
+        });
+        let output = "== a.txt ==\n  1,2: first\n  3,4: second\nunrelated\n== b.txt ==\n== c.txt ==\n  5,6: third\n";
+        let problems = match_problems(&[matcher], output, Some(Path::new("/cwd")), "lint");
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    message: "first".to_string(),
+                    source: Some("lint".to_string()),
+                    ..problem("/base/a.txt", 1, 2, ProblemSeverity::Warning)
+                },
+                Problem {
+                    message: "second".to_string(),
+                    source: Some("lint".to_string()),
+                    ..problem("/base/a.txt", 3, 4, ProblemSeverity::Warning)
+                },
+                Problem {
+                    message: "third".to_string(),
+                    source: Some("lint".to_string()),
+                    ..problem("/base/c.txt", 5, 6, ProblemSeverity::Warning)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_settled_problems() {
+        let matchers = [ProblemMatcher::Preset("$rustc".to_string())];
+        let warning = Problem {
+            message: "unused variable: `x`".to_string(),
+            source: Some("rustc".to_string()),
+            ..problem("/project/src/main.rs", 2, 9, ProblemSeverity::Warning)
+        };
+
+        // The location of the error isn't printed yet.
+        let output =
+            "warning: unused variable: `x`\n --> src/main.rs:2:9\n\nerror: mismatched types";
+        let (settled_lines, problems) =
+            match_settled_problems(&matchers, output, Some(Path::new("/project")), "cargo");
+        assert_eq!(settled_lines, 3);
+        assert_eq!(problems, vec![warning.clone()]);
+
+        let output = "error: mismatched types\n  --> src/lib.rs:10:18\n";
+        let (settled_lines, problems) =
+            match_settled_problems(&matchers, output, Some(Path::new("/project")), "cargo");
+        assert_eq!(settled_lines, 2);
+        assert_eq!(
+            problems,
+            vec![Problem {
+                message: "mismatched types".to_string(),
+                source: Some("rustc".to_string()),
+                ..problem("/project/src/lib.rs", 10, 18, ProblemSeverity::Error)
+            }]
+        );
+
+        // Once the task has finished, all of its output is matched.
+        let output = "warning: unused variable: `x`\n --> src/main.rs:2:9\nerror: mismatched types";
+        assert_eq!(
+            match_problems(&matchers, output, Some(Path::new("/project")), "cargo"),
+            vec![warning]
+        );
+    }
+}
@@ -2,13 +2,16 @@
 
 mod adapter_schema;
 mod debug_format;
+mod problem_matcher;
 mod serde_helpers;
 mod shell_builder;
 pub mod static_source;
+mod task_input;
 mod task_template;
 mod vscode_debug_format;
 mod vscode_format;
 
+use anyhow::Context as _;
 use collections::{HashMap, HashSet, hash_map};
 use gpui::SharedString;
 use schemars::JsonSchema;
@@ -22,9 +25,16 @@ pub use debug_format::{
     AttachRequest, BuildTaskDefinition, DebugRequest, DebugScenario, DebugTaskFile, LaunchRequest,
     Request, TcpArgumentsTemplate, ZedDebugConfig,
 };
+pub use problem_matcher::{
+    FileLocation, Problem, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern,
+    ProblemSeverity, match_problems, match_settled_problems,
+};
 pub use shell_builder::{DEFAULT_REMOTE_SHELL, ShellBuilder};
+pub use task_input::{
+    CommandInput, PickStringOption, TaskInput, TaskInputKind, input_references, substitute_inputs,
+};
 pub use task_template::{
-    DebugArgsRequest, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates,
+    DebugArgsRequest, DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates,
     substitute_variables_in_map, substitute_variables_in_str,
 };
 pub use vscode_debug_format::VsCodeDebugTaskFile;
@@ -74,6 +84,8 @@ pub struct SpawnInTerminal {
     pub show_command: bool,
     /// Whether to show the rerun button in the terminal tab.
     pub show_rerun: bool,
+    /// Matchers to turn the task output into diagnostics.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 impl SpawnInTerminal {
@@ -121,6 +133,8 @@ pub struct ResolvedTask {
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
     substituted_variables: HashSet<VariableName>,
+    /// The context the task got resolved with, to resolve its dependencies the same way.
+    task_context: TaskContext,
     /// Further actions that need to take place after the resolved task is spawned,
     /// with all task variables resolved.
     pub resolved: SpawnInTerminal,
@@ -137,10 +151,75 @@ impl ResolvedTask {
         &self.substituted_variables
     }
 
+    /// The context the task template got resolved with.
+    pub fn task_context(&self) -> &TaskContext {
+        &self.task_context
+    }
+
     /// A human-readable label to display in the UI.
     pub fn display_label(&self) -> &str {
         self.resolved.label.as_str()
     }
+
+    /// Inputs the task references as `${input:id}` in its command, arguments, environment or working directory,
+    /// in the order of their first use.
+    /// Fails if any of the referenced inputs is not defined in the task template.
+    pub fn referenced_inputs(&self) -> anyhow::Result<Vec<&TaskInput>> {
+        let cwd = self
+            .resolved
+            .cwd
+            .as_ref()
+            .map(|cwd| cwd.to_string_lossy().into_owned());
+        let texts = self
+            .resolved
+            .command
+            .iter()
+            .chain(&self.resolved.args)
+            .chain(self.resolved.env.values())
+            .chain(&cwd);
+
+        let mut inputs = Vec::new();
+        for id in texts.flat_map(|text| input_references(text)) {
+            if inputs.iter().any(|input: &&TaskInput| input.id == id) {
+                continue;
+            }
+            let input = self
+                .original_task
+                .inputs
+                .iter()
+                .find(|input| input.id == id)
+                .with_context(|| {
+                    format!(
+                        "Task `{}` references an undefined input `{id}`",
+                        self.original_task.label
+                    )
+                })?;
+            inputs.push(input);
+        }
+        Ok(inputs)
+    }
+
+    /// Replaces the `${input:id}` references in the task with the values given.
+    pub fn substitute_inputs(&mut self, values: &HashMap<String, String>) {
+        let resolved = &mut self.resolved;
+        for text in resolved
+            .command
+            .iter_mut()
+            .chain(&mut resolved.args)
+            .chain(resolved.env.values_mut())
+            .chain([
+                &mut resolved.label,
+                &mut resolved.full_label,
+                &mut resolved.command_label,
+                &mut self.resolved_label,
+            ])
+        {
+            *text = substitute_inputs(text, values);
+        }
+        if let Some(cwd) = &mut resolved.cwd {
+            *cwd = PathBuf::from(substitute_inputs(&cwd.to_string_lossy(), values));
+        }
+    }
 }
 
 /// Variables, available for use in [`TaskContext`] when a Zed's [`TaskTemplate`] gets resolved into a [`ResolvedTask`].
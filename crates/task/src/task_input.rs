@@ -0,0 +1,190 @@
+use collections::HashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const INPUT_REFERENCE_PREFIX: &str = "${input:";
+
+/// A value the user is asked for when spawning a task,
+/// referenced as `${input:id}` in the task's `command`, `args`, `env` or `cwd`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TaskInput {
+    /// Name to reference the input with, as `${input:id}`.
+    pub id: String,
+    /// Text to show when asking for the value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// How to get the value.
+    #[serde(flatten)]
+    pub kind: TaskInputKind,
+}
+
+/// How to get the value of a [`TaskInput`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskInputKind {
+    /// Ask for a free-form text.
+    PromptString {
+        /// Text to prefill the prompt with, when the input was not answered before.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
+    /// Ask to pick one of the options.
+    PickString {
+        /// Values to pick from, either strings or `{ "label": ..., "value": ... }` objects.
+        options: Vec<PickStringOption>,
+        /// Value to select first, when the input was not answered before.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
+    /// Use the output of a command, with the trailing newlines trimmed.
+    Command(CommandInput),
+}
+
+/// What to run to get the value of a [`TaskInputKind::Command`] input.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CommandInput {
+    /// A shell command, spawned with the `cwd`, `env` and `shell` of the task that needs the input.
+    Shell {
+        command: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+    },
+    /// Another task, looked up by its label the same way task dependencies are,
+    /// and resolved with the same context as the task that needs the input.
+    Task { task: String },
+}
+
+/// An option of a [`TaskInputKind::PickString`] input.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PickStringOption {
+    Value(String),
+    Labeled { label: String, value: String },
+}
+
+impl PickStringOption {
+    /// Text to display in the picker.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Value(value) => value,
+            Self::Labeled { label, .. } => label,
+        }
+    }
+
+    /// Value to substitute into the task.
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Value(value) => value,
+            Self::Labeled { value, .. } => value,
+        }
+    }
+}
+
+/// Ids of the inputs referenced as `${input:id}` in the text given, in the order of appearance.
+pub fn input_references(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices(INPUT_REFERENCE_PREFIX)
+        .filter_map(move |(start, _)| {
+            let reference = &text[start + INPUT_REFERENCE_PREFIX.len()..];
+            reference.find('}').map(|end| &reference[..end])
+        })
+}
+
+/// Replaces the `${input:id}` references in the text with the values given, leaving unknown ones as is.
+pub fn substitute_inputs(text: &str, values: &HashMap<String, String>) -> String {
+    let mut substituted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(INPUT_REFERENCE_PREFIX) {
+        let reference = &rest[start + INPUT_REFERENCE_PREFIX.len()..];
+        let Some(end) = reference.find('}') else {
+            break;
+        };
+        substituted.push_str(&rest[..start]);
+        match values.get(&reference[..end]) {
+            Some(value) => substituted.push_str(value),
+            None => {
+                substituted.push_str(&rest[start..start + INPUT_REFERENCE_PREFIX.len() + end + 1])
+            }
+        }
+        rest = &reference[end + 1..];
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_inputs() {
+        let inputs: Vec<TaskInput> = serde_json_lenient::from_str(
+            r#"[
+                { "id": "name", "type": "prompt_string", "description": "Your name", "default": "Zed" },
+                {
+                    "id": "environment",
+                    "type": "pick_string",
+                    "options": ["staging", { "label": "Production", "value": "prod" }]
+                },
+                { "id": "branch", "type": "command", "command": "git", "args": ["branch", "--show-current"] },
+                { "id": "targets", "type": "command", "task": "list targets" }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            inputs,
+            vec![
+                TaskInput {
+                    id: "name".to_string(),
+                    description: Some("Your name".to_string()),
+                    kind: TaskInputKind::PromptString {
+                        default: Some("Zed".to_string()),
+                    },
+                },
+                TaskInput {
+                    id: "environment".to_string(),
+                    description: None,
+                    kind: TaskInputKind::PickString {
+                        options: vec![
+                            PickStringOption::Value("staging".to_string()),
+                            PickStringOption::Labeled {
+                                label: "Production".to_string(),
+                                value: "prod".to_string(),
+                            },
+                        ],
+                        default: None,
+                    },
+                },
+                TaskInput {
+                    id: "branch".to_string(),
+                    description: None,
+                    kind: TaskInputKind::Command(CommandInput::Shell {
+                        command: "git".to_string(),
+                        args: vec!["branch".to_string(), "--show-current".to_string()],
+                    }),
+                },
+                TaskInput {
+                    id: "targets".to_string(),
+                    description: None,
+                    kind: TaskInputKind::Command(CommandInput::Task {
+                        task: "list targets".to_string(),
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_substitution() {
+        let text = "deploy --to ${input:env} --as ${input:user} ${input:env} ${ZED_FILE} ${input:";
+        assert_eq!(
+            input_references(text).collect::<Vec<_>>(),
+            vec!["env", "user", "env"]
+        );
+        let values = HashMap::from_iter([("env".to_string(), "staging".to_string())]);
+        assert_eq!(
+            substitute_inputs(text, &values),
+            "deploy --to staging --as ${input:user} staging ${ZED_FILE} ${input:"
+        );
+    }
+}
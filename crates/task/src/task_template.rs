@@ -9,8 +9,8 @@ use util::serde::default_true;
 use util::{ResultExt, truncate_and_remove_front};
 
 use crate::{
-    AttachRequest, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
-    VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
+    AttachRequest, ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext,
+    TaskId, TaskInput, VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
 };
 
 /// A template definition of a Zed task to run.
@@ -25,6 +25,8 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    /// May be omitted for composite tasks, that only run the tasks from `depends_on`.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -72,6 +74,25 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one.
+    /// If any of them fails, this task is not started.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks from `depends_on`:
+    /// * `parallel` — start all of them at once (default)
+    /// * `sequence` — run them one after another, in the order they are listed
+    #[serde(default, skip_serializing_if = "DependsOrder::is_parallel")]
+    pub depends_order: DependsOrder,
+    /// Matchers to turn the task output into diagnostics, either names of the built-in ones
+    /// (`$rustc`, `$tsc`, `$gcc`, `$eslint-compact`, `$eslint-stylish`) or custom definitions.
+    /// Diagnostics of the task are cleared when it is rerun.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Values to ask for when the task is spawned, referenced as `${input:id}`
+    /// in the `command`, `args`, `env` and `cwd` of the task.
+    /// Answers are remembered for the rest of the session and offered as defaults next time.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<TaskInput>,
 }
 
 #[derive(Deserialize, Eq, PartialEq, Clone, Debug)]
@@ -109,6 +130,23 @@ pub enum HideStrategy {
     OnSuccess,
 }
 
+/// How to run the tasks a [`TaskTemplate`] depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Start all dependencies at once.
+    #[default]
+    Parallel,
+    /// Run the dependencies one after another, stopping at the first failure.
+    Sequence,
+}
+
+impl DependsOrder {
+    fn is_parallel(&self) -> bool {
+        *self == Self::Parallel
+    }
+}
+
 /// A group of Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
@@ -133,7 +171,8 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        let is_composite = self.command.trim().is_empty();
+        if self.label.trim().is_empty() || (is_composite && self.depends_on.is_empty()) {
             return None;
         }
 
@@ -196,12 +235,16 @@ impl TaskTemplate {
             string
         });
 
-        let command = substitute_all_template_variables_in_str(
-            &self.command,
-            &task_variables,
-            &variable_names,
-            &mut substituted_variables,
-        )?;
+        let command = if is_composite {
+            None
+        } else {
+            Some(substitute_all_template_variables_in_str(
+                &self.command,
+                &task_variables,
+                &variable_names,
+                &mut substituted_variables,
+            )?)
+        };
         let args_with_substitutions = substitute_all_template_variables_in_vec(
             &self.args,
             &task_variables,
@@ -209,6 +252,20 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
+        let mut problem_matchers = self.problem_matchers.clone();
+        for problem_matcher in &mut problem_matchers {
+            if let ProblemMatcher::Custom(definition) = problem_matcher {
+                if let Some(base_dir) = &mut definition.base_dir {
+                    *base_dir = substitute_all_template_variables_in_str(
+                        base_dir,
+                        &task_variables,
+                        &variable_names,
+                        &mut substituted_variables,
+                    )?;
+                }
+            }
+        }
+
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
             .log_err()?;
@@ -242,20 +299,24 @@ impl TaskTemplate {
             substituted_variables,
             original_task: self.clone(),
             resolved_label: full_label.clone(),
+            task_context: cx.clone(),
             resolved: SpawnInTerminal {
                 id,
                 cwd,
                 full_label,
                 label: human_readable_label,
-                command_label: args_with_substitutions.iter().fold(
-                    command.clone(),
-                    |mut command_label, arg| {
-                        command_label.push(' ');
-                        command_label.push_str(arg);
-                        command_label
-                    },
-                ),
-                command: Some(command),
+                command_label: match &command {
+                    Some(command) => args_with_substitutions.iter().fold(
+                        command.clone(),
+                        |mut command_label, arg| {
+                            command_label.push(' ');
+                            command_label.push_str(arg);
+                            command_label
+                        },
+                    ),
+                    None => self.depends_on.join(", "),
+                },
+                command,
                 args: self.args.clone(),
                 env,
                 use_new_terminal: self.use_new_terminal,
@@ -267,6 +328,7 @@ impl TaskTemplate {
                 show_summary: self.show_summary,
                 show_command: self.show_command,
                 show_rerun: true,
+                problem_matchers,
             },
         })
     }
@@ -425,7 +487,7 @@ fn substitute_all_template_variables_in_map(
 mod tests {
     use std::{borrow::Cow, path::Path};
 
-    use crate::{TaskVariables, VariableName};
+    use crate::{PickStringOption, TaskInputKind, TaskVariables, VariableName};
 
     use super::*;
 
@@ -464,6 +526,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_composite_templates() {
+        let composite_task = TaskTemplate {
+            label: "build all".to_string(),
+            depends_on: vec!["build server".to_string(), "build client".to_string()],
+            depends_order: DependsOrder::Sequence,
+            ..TaskTemplate::default()
+        };
+        let resolved_task = composite_task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve a task with dependencies and no command");
+        assert_eq!(resolved_task.resolved.command, None);
+        assert_eq!(
+            resolved_task.resolved.command_label,
+            "build server, build client"
+        );
+
+        let task_without_dependencies = TaskTemplate {
+            depends_on: Vec::new(),
+            ..composite_task
+        };
+        assert_eq!(
+            task_without_dependencies.resolve_task(TEST_ID_BASE, &TaskContext::default()),
+            None,
+            "should not resolve a task with neither command nor dependencies"
+        );
+    }
+
+    #[test]
+    fn test_resolving_task_inputs() {
+        let environment = TaskInput {
+            id: "environment".to_string(),
+            description: None,
+            kind: TaskInputKind::PickString {
+                options: vec![PickStringOption::Value("staging".to_string())],
+                default: None,
+            },
+        };
+        let template = TaskTemplate {
+            label: "deploy to ${input:environment}".to_string(),
+            command: "./deploy.sh".to_string(),
+            args: vec!["--env".to_string(), "${input:environment}".to_string()],
+            cwd: Some("/deployments/${input:environment}".to_string()),
+            inputs: vec![environment.clone()],
+            ..TaskTemplate::default()
+        };
+        let mut resolved_task = template
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .unwrap();
+        assert_eq!(
+            resolved_task.referenced_inputs().unwrap(),
+            vec![&environment]
+        );
+
+        resolved_task.substitute_inputs(&HashMap::from_iter([(
+            "environment".to_string(),
+            "staging".to_string(),
+        )]));
+        assert_eq!(
+            resolved_task.resolved.args,
+            vec!["--env".to_string(), "staging".to_string()]
+        );
+        assert_eq!(
+            resolved_task.resolved.cwd,
+            Some(PathBuf::from("/deployments/staging"))
+        );
+        assert_eq!(resolved_task.resolved.label, "deploy to staging");
+        assert_eq!(
+            resolved_task.resolved.command_label,
+            "./deploy.sh --env staging"
+        );
+        assert!(resolved_task.referenced_inputs().unwrap().is_empty());
+
+        let undefined_input = TaskTemplate {
+            inputs: Vec::new(),
+            ..template
+        }
+        .resolve_task(TEST_ID_BASE, &TaskContext::default())
+        .unwrap();
+        assert!(undefined_input.referenced_inputs().is_err());
+    }
+
     #[test]
     fn test_template_cwd_resolution() {
         let task_without_cwd = TaskTemplate {
@@ -1,9 +1,13 @@
-use anyhow::bail;
+use anyhow::{Context as _, bail};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{EnvVariableReplacer, TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    DependsOrder, EnvVariableReplacer, FileLocation, PickStringOption, ProblemMatcher,
+    ProblemMatcherDefinition, ProblemPattern, ProblemSeverity, TaskInput, TaskInputKind,
+    TaskTemplate, TaskTemplates, VariableName, input_references, problem_matcher::preset,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +26,18 @@ struct VsCodeTaskDefinition {
     #[serde(flatten)]
     other_attributes: HashMap<String, serde_json_lenient::Value>,
     options: Option<TaskOptions>,
+    depends_on: Option<DependsOn>,
+    #[serde(default)]
+    depends_order: DependsOrder,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum DependsOn {
+    Single(String),
+    Multiple(Vec<String>),
+    /// E.g. references to auto-detected tasks by their `type`, which Zed has no equivalent of.
+    Unsupported(serde_json_lenient::Value),
 }
 
 #[derive(Clone, Deserialize, PartialEq, Debug)]
@@ -41,26 +57,191 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatchers {
+    Single(VsCodeProblemMatcher),
+    Multiple(Vec<VsCodeProblemMatcher>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Custom(VsCodeProblemMatcherDefinition),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemMatcherDefinition {
+    base: Option<String>,
+    owner: Option<String>,
+    source: Option<String>,
+    severity: Option<ProblemSeverity>,
+    file_location: Option<VsCodeFileLocation>,
+    pattern: Option<VsCodeProblemPatterns>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeFileLocation {
+    Kind(FileLocation),
+    WithBaseDir(FileLocation, String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemPatterns {
+    Named(String),
+    Single(VsCodeProblemPattern),
+    Multiple(Vec<VsCodeProblemPattern>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    location: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default)]
+    r#loop: bool,
+}
+
+impl From<VsCodeProblemPattern> for ProblemPattern {
+    fn from(pattern: VsCodeProblemPattern) -> Self {
+        Self {
+            regexp: pattern.regexp,
+            file: pattern.file,
+            location: pattern.location,
+            line: pattern.line,
+            column: pattern.column,
+            end_line: pattern.end_line,
+            end_column: pattern.end_column,
+            severity: pattern.severity,
+            code: pattern.code,
+            message: pattern.message,
+            r#loop: pattern.r#loop,
+        }
+    }
+}
+
+impl VsCodeProblemMatcher {
+    fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<ProblemMatcher> {
+        let definition = match self {
+            Self::Named(name) => {
+                if preset(&name).is_none() {
+                    bail!("Unknown problem matcher `{name}`");
+                }
+                return Ok(ProblemMatcher::Preset(name));
+            }
+            Self::Custom(definition) => definition,
+        };
+
+        let mut zed_definition = match &definition.base {
+            Some(base) => {
+                preset(base).with_context(|| format!("Unknown problem matcher `{base}`"))?
+            }
+            None => ProblemMatcherDefinition::default(),
+        };
+        if let Some(source) = definition.source.or(definition.owner) {
+            zed_definition.source = Some(source);
+        }
+        if let Some(severity) = definition.severity {
+            zed_definition.severity = Some(severity);
+        }
+        match definition.file_location {
+            Some(VsCodeFileLocation::Kind(file_location)) => {
+                zed_definition.file_location = file_location;
+            }
+            Some(VsCodeFileLocation::WithBaseDir(file_location, base_dir)) => {
+                zed_definition.file_location = file_location;
+                zed_definition.base_dir = Some(replacer.replace(&base_dir));
+            }
+            None => {}
+        }
+        match definition.pattern {
+            Some(VsCodeProblemPatterns::Named(name)) => {
+                zed_definition.pattern = preset(&name)
+                    .with_context(|| format!("Unknown problem pattern `{name}`"))?
+                    .pattern;
+            }
+            Some(VsCodeProblemPatterns::Single(pattern)) => {
+                zed_definition.pattern = vec![pattern.into()];
+            }
+            Some(VsCodeProblemPatterns::Multiple(patterns)) => {
+                zed_definition.pattern = patterns.into_iter().map(Into::into).collect();
+            }
+            None => {}
+        }
+        anyhow::ensure!(
+            !zed_definition.pattern.is_empty(),
+            "Problem matcher has no patterns"
+        );
+        Ok(ProblemMatcher::Custom(zed_definition))
+    }
+}
+
+fn problem_matchers_from_vscode(
+    problem_matchers: Option<&serde_json_lenient::Value>,
+    replacer: &EnvVariableReplacer,
+) -> Vec<ProblemMatcher> {
+    let Some(problem_matchers) = problem_matchers else {
+        return Vec::new();
+    };
+    let problem_matchers =
+        match serde_json_lenient::from_value::<VsCodeProblemMatchers>(problem_matchers.clone()) {
+            Ok(VsCodeProblemMatchers::Single(problem_matcher)) => vec![problem_matcher],
+            Ok(VsCodeProblemMatchers::Multiple(problem_matchers)) => problem_matchers,
+            Err(e) => {
+                log::warn!("Skipping unsupported `problemMatcher`: {e}");
+                return Vec::new();
+            }
+        };
+    problem_matchers
+        .into_iter()
+        .filter_map(|problem_matcher| problem_matcher.into_zed_format(replacer).log_err())
+        .collect()
+}
+
 impl VsCodeTaskDefinition {
     fn into_zed_format(
         self,
         replacer: &EnvVariableReplacer,
     ) -> anyhow::Result<Option<TaskTemplate>> {
-        if self.other_attributes.contains_key("dependsOn") {
-            log::warn!(
-                "Skipping deserializing of a task `{}` with the unsupported `dependsOn` key",
-                self.label
-            );
-            return Ok(None);
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
+        let depends_on = match self.depends_on {
+            None => Vec::new(),
+            Some(DependsOn::Single(label)) => vec![label],
+            Some(DependsOn::Multiple(labels)) => labels,
+            Some(DependsOn::Unsupported(_)) => {
+                log::warn!(
+                    "Skipping deserializing of a task `{}` with the unsupported `dependsOn` value",
+                    self.label
+                );
+                return Ok(None);
+            }
+        };
+        // `type` is not set in tasks that only run their `dependsOn` tasks, hence command is an Option.
         let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+            if depends_on.is_empty() {
+                bail!("Missing `type` field in task");
+            }
+            return Ok(Some(TaskTemplate {
+                label: self.label,
+                depends_on,
+                depends_order: self.depends_order,
+                ..TaskTemplate::default()
+            }));
         };
 
+        let problem_matchers =
+            problem_matchers_from_vscode(self.other_attributes.get("problemMatcher"), replacer);
         let (command, args) = match command {
             Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
             Command::Shell { command, args } => (command, args),
@@ -73,6 +254,9 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order: self.depends_order,
+            problem_matchers,
             ..TaskTemplate::default()
         };
         if let Some(options) = self.options {
@@ -83,10 +267,63 @@ impl VsCodeTaskDefinition {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum VsCodeTaskInput {
+    PromptString {
+        id: String,
+        description: Option<String>,
+        default: Option<String>,
+    },
+    PickString {
+        id: String,
+        description: Option<String>,
+        options: Vec<PickStringOption>,
+        default: Option<String>,
+    },
+}
+
+impl From<VsCodeTaskInput> for TaskInput {
+    fn from(input: VsCodeTaskInput) -> Self {
+        match input {
+            VsCodeTaskInput::PromptString {
+                id,
+                description,
+                default,
+            } => Self {
+                id,
+                description,
+                kind: TaskInputKind::PromptString { default },
+            },
+            VsCodeTaskInput::PickString {
+                id,
+                description,
+                options,
+                default,
+            } => Self {
+                id,
+                description,
+                kind: TaskInputKind::PickString { options, default },
+            },
+        }
+    }
+}
+
+fn references_input(template: &TaskTemplate, input_id: &str) -> bool {
+    std::iter::once(&template.command)
+        .chain(&template.args)
+        .chain(&template.cwd)
+        .chain(template.env.values())
+        .any(|text| input_references(text).any(|id| id == input_id))
+}
+
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    // Inputs are parsed one by one, to skip the kinds Zed does not support (such as VS Code commands).
+    #[serde(default)]
+    inputs: Vec<serde_json_lenient::Value>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskTemplates {
@@ -105,6 +342,16 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::SelectedText.to_string(),
             ),
         ]));
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(|input| {
+                serde_json_lenient::from_value::<VsCodeTaskInput>(input)
+                    .context("Skipping unsupported task input")
+                    .log_err()
+            })
+            .map(TaskInput::from)
+            .collect::<Vec<_>>();
         let templates = value
             .tasks
             .into_iter()
@@ -114,6 +361,14 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                     .log_err()
                     .flatten()
             })
+            .map(|mut template| {
+                template.inputs = inputs
+                    .iter()
+                    .filter(|input| references_input(&template, &input.id))
+                    .cloned()
+                    .collect();
+                template
+            })
             .collect();
         Ok(Self(templates))
     }
@@ -124,8 +379,11 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
-        vscode_format::{Command, VsCodeTaskDefinition},
+        DependsOrder, FileLocation, PickStringOption, ProblemMatcher, ProblemMatcherDefinition,
+        ProblemPattern, ProblemSeverity, TaskInput, TaskInputKind, TaskTemplate, TaskTemplates,
+        VsCodeTaskFile,
+        problem_matcher::preset,
+        vscode_format::{Command, DependsOn, VsCodeTaskDefinition},
     };
 
     use super::EnvVariableReplacer;
@@ -182,6 +440,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "tsc: watch ./src".to_string(),
@@ -196,6 +456,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "npm: build:compiler".to_string(),
@@ -204,6 +466,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "npm: build:tests".to_string(),
@@ -212,6 +476,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: Default::default(),
             },
         ];
 
@@ -227,6 +493,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::Preset("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -238,18 +505,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcher::Preset("$tsc-watch".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcher::Preset("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::Preset("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -271,6 +541,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Extension".to_string(),
@@ -279,6 +551,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server".to_string(),
@@ -288,6 +562,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release)".to_string(),
@@ -297,6 +573,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Pretest".to_string(),
@@ -305,18 +583,30 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server and Extension".to_string(),
                 command: None,
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: Some(DependsOn::Multiple(vec![
+                    "Build Server".to_string(),
+                    "Build Extension".to_string(),
+                ])),
+                depends_order: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release) and Extension".to_string(),
                 command: None,
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: Some(DependsOn::Multiple(vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ])),
+                depends_order: Default::default(),
             },
         ];
         assert_eq!(vscode_definitions.tasks.len(), expected.len());
@@ -325,37 +615,232 @@ mod tests {
             .iter()
             .zip(expected)
             .for_each(|(lhs, rhs)| compare_without_other_attributes(lhs.clone(), rhs));
+        let tsc_in_extension_dir = |base| {
+            ProblemMatcher::Custom(ProblemMatcherDefinition {
+                file_location: FileLocation::Relative,
+                base_dir: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                ..preset(base).unwrap()
+            })
+        };
         let expected = vec![
             TaskTemplate {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![tsc_in_extension_dir("$tsc-watch")],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![tsc_in_extension_dir("$tsc")],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::Preset("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::Preset("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![tsc_in_extension_dir("$tsc")],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_task_dependencies() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "test",
+                        "type": "shell",
+                        "command": "cargo test",
+                        "dependsOn": "build"
+                    },
+                    {
+                        "label": "ci",
+                        "dependsOn": ["lint", "test"],
+                        "dependsOrder": "sequence"
+                    },
+                    {
+                        "label": "detected",
+                        "dependsOn": [{ "type": "npm", "script": "build" }]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "test".to_string(),
+                    command: "cargo test".to_string(),
+                    depends_on: vec!["build".to_string()],
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "ci".to_string(),
+                    depends_on: vec!["lint".to_string(), "test".to_string()],
+                    depends_order: DependsOrder::Sequence,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn can_deserialize_custom_problem_matchers() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "lint",
+                        "type": "shell",
+                        "command": "lint",
+                        "problemMatcher": [
+                            "$unknown",
+                            {
+                                "owner": "lint",
+                                "severity": "warning",
+                                "fileLocation": "absolute",
+                                "pattern": {
+                                    "regexp": "^(.*):(\\d+):(\\d+): (.*)$",
+                                    "file": 1,
+                                    "line": 2,
+                                    "column": 3,
+                                    "message": 4
+                                }
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![TaskTemplate {
+                label: "lint".to_string(),
+                command: "lint".to_string(),
+                problem_matchers: vec![ProblemMatcher::Custom(ProblemMatcherDefinition {
+                    source: Some("lint".to_string()),
+                    severity: Some(ProblemSeverity::Warning),
+                    file_location: FileLocation::Absolute,
+                    base_dir: None,
+                    pattern: vec![ProblemPattern {
+                        regexp: r"^(.*):(\d+):(\d+): (.*)$".to_string(),
+                        file: Some(1),
+                        line: Some(2),
+                        column: Some(3),
+                        message: Some(4),
+                        ..ProblemPattern::default()
+                    }],
+                })],
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn can_deserialize_task_inputs() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "deploy",
+                        "type": "shell",
+                        "command": "./deploy.sh",
+                        "args": ["--env", "${input:environment}"]
+                    },
+                    {
+                        "label": "build",
+                        "type": "shell",
+                        "command": "cargo build"
+                    }
+                ],
+                "inputs": [
+                    {
+                        "id": "environment",
+                        "type": "pickString",
+                        "description": "Where to deploy",
+                        "options": ["staging", { "label": "Production", "value": "prod" }],
+                        "default": "staging"
+                    },
+                    {
+                        "id": "token",
+                        "type": "promptString",
+                        "password": true
+                    },
+                    {
+                        "id": "folder",
+                        "type": "command",
+                        "command": "extension.pickFolder"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "deploy".to_string(),
+                    command: "./deploy.sh".to_string(),
+                    args: vec!["--env".to_string(), "${input:environment}".to_string()],
+                    inputs: vec![TaskInput {
+                        id: "environment".to_string(),
+                        description: Some("Where to deploy".to_string()),
+                        kind: TaskInputKind::PickString {
+                            options: vec![
+                                PickStringOption::Value("staging".to_string()),
+                                PickStringOption::Labeled {
+                                    label: "Production".to_string(),
+                                    value: "prod".to_string(),
+                                },
+                            ],
+                            default: Some("staging".to_string()),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "build".to_string(),
+                    command: "cargo build".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, ParentElement, Render, SharedString, Styled, Subscription, Task, WeakEntity,
+    Window, rems,
+};
+use picker::{Picker, PickerDelegate};
+use task::{PickStringOption, TaskInput, TaskInputKind};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, TaskInputProvider, Workspace};
+
+/// Asks for the values of task inputs with modal pickers.
+pub(crate) struct TaskInputPrompts;
+
+impl TaskInputProvider for TaskInputPrompts {
+    fn prompt(
+        &self,
+        input: TaskInput,
+        default: Option<String>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Option<String>> {
+        let (tx, rx) = oneshot::channel();
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    TaskInputPrompt::new(input, default, tx, window, cx)
+                })
+            })
+            .log_err();
+        cx.spawn(async move |_| rx.await.ok().flatten())
+    }
+}
+
+pub(crate) struct TaskInputPrompt {
+    picker: Entity<Picker<TaskInputPromptDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TaskInputPrompt {
+    fn new(
+        input: TaskInput,
+        default: Option<String>,
+        tx: oneshot::Sender<Option<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let placeholder: Arc<str> = match &input.description {
+            Some(description) => description.as_str().into(),
+            None => format!("Value for ${{input:{}}}", input.id).into(),
+        };
+        let (options, default) = match input.kind {
+            TaskInputKind::PickString {
+                options,
+                default: template_default,
+            } => (options, default.or(template_default)),
+            TaskInputKind::PromptString {
+                default: template_default,
+            } => (Vec::new(), default.or(template_default)),
+            TaskInputKind::Command(_) => (Vec::new(), default),
+        };
+        let is_prompt = options.is_empty();
+        let selected_index = default
+            .as_deref()
+            .and_then(|default| options.iter().position(|option| option.value() == default))
+            .unwrap_or(0);
+        let delegate = TaskInputPromptDelegate {
+            placeholder,
+            options,
+            matches: Vec::new(),
+            selected_index,
+            query: String::new(),
+            tx: Some(tx),
+        };
+
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        if is_prompt {
+            if let Some(default) = default {
+                picker.update(cx, |picker, cx| picker.set_query(default, window, cx));
+            }
+        }
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for TaskInputPrompt {}
+impl EventEmitter<DismissEvent> for TaskInputPrompt {}
+
+impl Focusable for TaskInputPrompt {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for TaskInputPrompt {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|this, _, window, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.cancel(&Default::default(), window, cx);
+                })
+            }))
+    }
+}
+
+pub(crate) struct TaskInputPromptDelegate {
+    placeholder: Arc<str>,
+    /// Options to pick from, empty when the input is a free-form text.
+    options: Vec<PickStringOption>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    tx: Option<oneshot::Sender<Option<String>>>,
+}
+
+impl PickerDelegate for TaskInputPromptDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        self.placeholder.clone()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        if self.options.is_empty() {
+            None
+        } else {
+            Some("No matching options".into())
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        self.query = query.clone();
+        if self.options.is_empty() {
+            return Task::ready(());
+        }
+
+        let candidates = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(ix, option)| StringMatchCandidate::new(ix, option.label()))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    true,
+                    1000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let answer = if self.options.is_empty() {
+            self.query.clone()
+        } else {
+            let Some(option) = self
+                .matches
+                .get(self.selected_index)
+                .and_then(|hit| self.options.get(hit.candidate_id))
+            else {
+                return;
+            };
+            option.value().to_string()
+        };
+        if let Some(tx) = self.tx.take() {
+            tx.send(Some(answer)).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(tx) = self.tx.take() {
+            tx.send(None).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        Some(
+            ListItem::new(SharedString::from(format!("task-input-option-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    hit.string.clone(),
+                    hit.positions.clone(),
+                )),
+        )
+    }
+}
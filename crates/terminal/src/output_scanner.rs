@@ -0,0 +1,910 @@
+//! Scans the PTY output for sequences that Alacritty doesn't handle: shell integration marks and
+//! inline images.
+//!
+//! The scanner runs on the PTY reader thread, before Alacritty parses the same bytes. Image
+//! sequences are withheld from Alacritty and replaced with cursor motion that makes room for the
+//! image. Everything picked out of the output is queued with the number of line feeds read before
+//! it, and placed in the terminal grid on the next wakeup, once Alacritty parsed the same bytes.
+
+use std::{
+    io::{self, Write as _},
+    mem,
+    sync::Arc,
+    time::Instant,
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::{Term, TermMode, cell::Flags},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use parking_lot::Mutex;
+use polling::{Event as PollingEvent, PollMode, Poller};
+use util::ResultExt as _;
+
+use crate::{
+    shell_integration::{self, ShellMark},
+    terminal_images::{self, ImageEvent, ImagePlacement, KittyGraphics},
+};
+
+/// Longer OSC sequences are still skipped, but their content is dropped.
+const MAX_SEQUENCE_LEN: usize = 4096;
+/// Longer image sequences are still withheld from Alacritty, but they're dropped.
+const MAX_IMAGE_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+/// Longer DCS parameters aren't the start of a Sixel image.
+const MAX_DCS_PARAMS_LEN: usize = 32;
+
+/// Something picked out of the PTY output.
+pub(crate) enum OutputEvent {
+    Shell(ShellMark),
+    Image(ImageEvent),
+}
+
+/// How far the point an event applies to is before the cursor, when no line feeds were written
+/// after it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CursorOffset {
+    lines: usize,
+    columns: usize,
+}
+
+struct PendingEvent {
+    event: OutputEvent,
+    /// The number of line feeds read before the event.
+    line_feeds: usize,
+    offset: CursorOffset,
+    alt_screen: bool,
+    read_at: Instant,
+}
+
+/// Events read from the PTY that weren't placed in the terminal grid yet.
+#[derive(Default)]
+struct PendingOutput {
+    events: Vec<PendingEvent>,
+    /// The number of line feeds read so far, outside of the alternate screen.
+    line_feeds: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    /// After an escape, which is withheld until it's known whether it starts an image.
+    Escape,
+    Osc,
+    Csi,
+    /// After `ESC P`, while its parameters are withheld.
+    DcsStart,
+    Sixel,
+    SixelEscape,
+    /// After `ESC _`, which is withheld until its first byte.
+    ApcStart,
+    Kitty,
+    KittyEscape,
+}
+
+/// The bytes to pass on to Alacritty and to write back to the PTY, after scanning a read.
+#[derive(Default)]
+struct ScannedBytes {
+    forward: Vec<u8>,
+    responses: Vec<u8>,
+}
+
+/// Picks shell integration marks and images out of the bytes read from the PTY, and counts the
+/// line feeds between them so that they can be placed in the grid once Alacritty parsed the same
+/// bytes.
+struct OutputScanner {
+    state: ScanState,
+    sequence: Vec<u8>,
+    /// The start of a sequence, withheld from Alacritty until it's known whether it's an image.
+    held: Vec<u8>,
+    alt_screen: bool,
+    window_size: WindowSize,
+    kitty: KittyGraphics,
+    /// Whether Kitty images may be read from files, which only makes sense for local shells.
+    allow_file_transmission: bool,
+}
+
+impl OutputScanner {
+    fn new(window_size: WindowSize, allow_file_transmission: bool) -> Self {
+        Self {
+            state: ScanState::Ground,
+            sequence: Vec::new(),
+            held: Vec::new(),
+            alt_screen: false,
+            window_size,
+            kitty: KittyGraphics::default(),
+            allow_file_transmission,
+        }
+    }
+
+    fn advance(&mut self, bytes: &[u8], scanned: &mut ScannedBytes, pending: &mut PendingOutput) {
+        for &byte in bytes {
+            self.advance_byte(byte, scanned, pending);
+        }
+    }
+
+    fn advance_byte(&mut self, byte: u8, scanned: &mut ScannedBytes, pending: &mut PendingOutput) {
+        match self.state {
+            ScanState::Ground => match byte {
+                0x1b => self.state = ScanState::Escape,
+                b'\n' => {
+                    scanned.forward.push(byte);
+                    self.line_feed(pending);
+                }
+                _ => scanned.forward.push(byte),
+            },
+            ScanState::Escape => self.escape(byte, scanned, pending),
+            ScanState::Osc => match byte {
+                0x07 => {
+                    scanned.forward.push(byte);
+                    self.finish_osc(pending);
+                    self.state = ScanState::Ground;
+                }
+                // Like Alacritty, any escape ends the OSC, not only the string terminator.
+                0x1b => {
+                    self.finish_osc(pending);
+                    self.state = ScanState::Escape;
+                }
+                _ => {
+                    scanned.forward.push(byte);
+                    self.push(byte);
+                }
+            },
+            ScanState::Csi => {
+                if byte == 0x1b {
+                    self.state = ScanState::Escape;
+                    return;
+                }
+                scanned.forward.push(byte);
+                match byte {
+                    0x20..=0x3f => self.push(byte),
+                    0x40..=0x7e => {
+                        self.finish_csi(byte);
+                        self.state = ScanState::Ground;
+                    }
+                    b'\n' => self.line_feed(pending),
+                    0x00..=0x1f => {}
+                    _ => self.state = ScanState::Ground,
+                }
+            }
+            ScanState::DcsStart => match byte {
+                b'0'..=b'9' | b';' if self.held.len() < MAX_DCS_PARAMS_LEN => self.held.push(byte),
+                // The parameters only affect how the background is drawn, and it's always left
+                // transparent.
+                b'q' => {
+                    self.held.clear();
+                    self.sequence.clear();
+                    self.state = ScanState::Sixel;
+                }
+                _ => self.release_held(byte, scanned, pending),
+            },
+            ScanState::ApcStart => {
+                if byte == b'G' {
+                    self.held.clear();
+                    self.sequence.clear();
+                    self.state = ScanState::Kitty;
+                } else {
+                    self.release_held(byte, scanned, pending);
+                }
+            }
+            ScanState::Sixel | ScanState::Kitty => match byte {
+                0x1b if self.state == ScanState::Sixel => self.state = ScanState::SixelEscape,
+                0x1b => self.state = ScanState::KittyEscape,
+                // Cancel and substitute abort the sequence.
+                0x18 | 0x1a => {
+                    self.sequence.clear();
+                    self.state = ScanState::Ground;
+                }
+                _ => {
+                    if self.sequence.len() < MAX_IMAGE_SEQUENCE_LEN {
+                        self.sequence.push(byte);
+                    }
+                }
+            },
+            ScanState::SixelEscape | ScanState::KittyEscape => {
+                if self.state == ScanState::SixelEscape {
+                    self.finish_sixel(scanned, pending);
+                } else {
+                    self.finish_kitty(scanned, pending);
+                }
+                if byte == b'\\' {
+                    self.state = ScanState::Ground;
+                } else {
+                    self.state = ScanState::Escape;
+                    self.advance_byte(byte, scanned, pending);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self, byte: u8, scanned: &mut ScannedBytes, pending: &mut PendingOutput) {
+        match byte {
+            b'P' => {
+                self.held = vec![0x1b, byte];
+                self.state = ScanState::DcsStart;
+                return;
+            }
+            b'_' => {
+                self.held = vec![0x1b, byte];
+                self.state = ScanState::ApcStart;
+                return;
+            }
+            0x1b => {
+                scanned.forward.push(byte);
+                return;
+            }
+            _ => {}
+        }
+        scanned.forward.extend_from_slice(&[0x1b, byte]);
+        self.sequence.clear();
+        self.state = match byte {
+            b']' => ScanState::Osc,
+            b'[' => ScanState::Csi,
+            // Index and next line.
+            b'D' | b'E' => {
+                self.line_feed(pending);
+                ScanState::Ground
+            }
+            _ => ScanState::Ground,
+        };
+    }
+
+    /// Passes on a withheld sequence that turned out not to be an image.
+    fn release_held(&mut self, byte: u8, scanned: &mut ScannedBytes, pending: &mut PendingOutput) {
+        scanned.forward.append(&mut self.held);
+        self.state = ScanState::Ground;
+        self.advance_byte(byte, scanned, pending);
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.sequence.len() < MAX_SEQUENCE_LEN {
+            self.sequence.push(byte);
+        }
+    }
+
+    fn line_feed(&self, pending: &mut PendingOutput) {
+        if !self.alt_screen {
+            pending.line_feeds += 1;
+        }
+    }
+
+    fn push_event(&self, event: OutputEvent, offset: CursorOffset, pending: &mut PendingOutput) {
+        pending.events.push(PendingEvent {
+            event,
+            line_feeds: pending.line_feeds,
+            offset,
+            alt_screen: self.alt_screen,
+            read_at: Instant::now(),
+        });
+    }
+
+    fn finish_osc(&mut self, pending: &mut PendingOutput) {
+        let Ok(osc) = std::str::from_utf8(&self.sequence) else {
+            return;
+        };
+        if let Some(mark) = shell_integration::parse_osc(osc) {
+            self.push_event(OutputEvent::Shell(mark), CursorOffset::default(), pending);
+        }
+    }
+
+    fn finish_csi(&mut self, action: u8) {
+        if !matches!(action, b'h' | b'l') {
+            return;
+        }
+        let Some(params) = self.sequence.strip_prefix(b"?") else {
+            return;
+        };
+        let switches_screen = params
+            .split(|byte| *byte == b';')
+            .any(|param| matches!(param, b"47" | b"1047" | b"1049"));
+        if switches_screen {
+            self.alt_screen = action == b'h';
+        }
+    }
+
+    fn cell_size(&self) -> (f32, f32) {
+        (
+            self.window_size.cell_width.max(1) as f32,
+            self.window_size.cell_height.max(1) as f32,
+        )
+    }
+
+    /// Places a Sixel image at the cursor, and moves the cursor to the line below it, keeping its
+    /// column.
+    fn finish_sixel(&mut self, scanned: &mut ScannedBytes, pending: &mut PendingOutput) {
+        let data = mem::take(&mut self.sequence);
+        if data.len() >= MAX_IMAGE_SEQUENCE_LEN {
+            return;
+        }
+        let Some(image) = terminal_images::decode_sixel(&data) else {
+            return;
+        };
+        let placement =
+            ImagePlacement::new(terminal_images::render_image(image), None, self.cell_size());
+        let lines = placement.lines.ceil() as usize;
+        for _ in 0..lines {
+            scanned.forward.push(b'\n');
+            self.line_feed(pending);
+        }
+        self.push_event(
+            OutputEvent::Image(ImageEvent::Place(placement)),
+            CursorOffset { lines, columns: 0 },
+            pending,
+        );
+    }
+
+    /// Runs a Kitty graphics command. Placed images leave the cursor after their last cell, on
+    /// their last line, unless the command asks not to move it.
+    fn finish_kitty(&mut self, scanned: &mut ScannedBytes, pending: &mut PendingOutput) {
+        let data = mem::take(&mut self.sequence);
+        if data.len() >= MAX_IMAGE_SEQUENCE_LEN {
+            return;
+        }
+        let outcome = self
+            .kitty
+            .handle(&data, self.cell_size(), self.allow_file_transmission);
+        if let Some(response) = outcome.response {
+            scanned.responses.extend_from_slice(&response);
+        }
+        let Some(event) = outcome.event else {
+            return;
+        };
+        let mut offset = CursorOffset::default();
+        if let ImageEvent::Place(placement) = &event
+            && outcome.move_cursor
+        {
+            offset.lines = (placement.lines.ceil() as usize).saturating_sub(1);
+            offset.columns = placement.columns.ceil() as usize;
+            for _ in 0..offset.lines {
+                scanned.forward.push(b'\n');
+                self.line_feed(pending);
+            }
+            if offset.columns > 0 {
+                write!(scanned.forward, "\x1b[{}C", offset.columns).ok();
+            }
+        }
+        self.push_event(OutputEvent::Image(event), offset, pending);
+    }
+}
+
+/// A PTY whose output is scanned as it's read.
+pub(crate) struct ScanningPty<P> {
+    pty: P,
+    scanner: OutputScanner,
+    pending: Arc<Mutex<PendingOutput>>,
+    scanned: ScannedBytes,
+    /// How much of the scanned bytes was already passed on to Alacritty.
+    forwarded: usize,
+}
+
+impl<P: EventedReadWrite> io::Read for ScanningPty<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.forwarded == self.scanned.forward.len() {
+            self.scanned.forward.clear();
+            self.forwarded = 0;
+            let read = self.pty.reader().read(buf)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            self.scanner
+                .advance(&buf[..read], &mut self.scanned, &mut self.pending.lock());
+            // Answer right away, so that the answers come before those to any later queries,
+            // which Alacritty answers once it parsed them.
+            if !self.scanned.responses.is_empty() {
+                let responses = mem::take(&mut self.scanned.responses);
+                self.pty.writer().write_all(&responses).log_err();
+            }
+        }
+        let forward = &self.scanned.forward[self.forwarded..];
+        let len = forward.len().min(buf.len());
+        buf[..len].copy_from_slice(&forward[..len]);
+        self.forwarded += len;
+        Ok(len)
+    }
+}
+
+impl<P: EventedReadWrite> EventedReadWrite for ScanningPty<P> {
+    type Reader = Self;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poller, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poller, interest, mode)
+    }
+
+    fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poller)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for ScanningPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<P: OnResize> OnResize for ScanningPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.scanner.window_size = window_size;
+        self.pty.on_resize(window_size)
+    }
+}
+
+/// A point in the terminal's output, whose line counts from the first line ever written, so that
+/// it doesn't change as lines scroll into the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OutputPoint {
+    pub(crate) line: usize,
+    pub(crate) column: Column,
+}
+
+/// Where an event from the PTY output applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EventPoint {
+    Output(OutputPoint),
+    /// A point on the alternate screen, which has no history to anchor to.
+    AltScreen(AlacPoint),
+}
+
+pub(crate) struct PlacedEvent {
+    pub(crate) event: OutputEvent,
+    pub(crate) point: EventPoint,
+    pub(crate) read_at: Instant,
+}
+
+/// The state of the terminal grid that events are placed against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct GridState {
+    pub(crate) cursor: AlacPoint,
+    pub(crate) history_size: usize,
+    pub(crate) max_history_size: usize,
+}
+
+/// Places the events scanned from the PTY output in the terminal grid, and keeps track of the lines
+/// dropped from the top of the history, so that [`OutputPoint`]s can be found in the grid.
+pub(crate) struct OutputTracker {
+    pending: Arc<Mutex<PendingOutput>>,
+    /// The number of lines dropped from the top of the history, which [`OutputPoint`] lines are
+    /// offset by.
+    evicted_lines: usize,
+    /// The line feed count and grid state when events were last placed.
+    line_feeds: usize,
+    grid: Option<GridState>,
+    /// How many rows the line the cursor was on when events were last placed wrapped over,
+    /// above the cursor.
+    cursor_wrapped_rows: usize,
+}
+
+impl OutputTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: Arc::default(),
+            evicted_lines: 0,
+            line_feeds: 0,
+            grid: None,
+            cursor_wrapped_rows: 0,
+        }
+    }
+
+    pub(crate) fn wrap_pty<P>(
+        &self,
+        pty: P,
+        window_size: WindowSize,
+        allow_file_transmission: bool,
+    ) -> ScanningPty<P> {
+        ScanningPty {
+            pty,
+            scanner: OutputScanner::new(window_size, allow_file_transmission),
+            pending: self.pending.clone(),
+            scanned: ScannedBytes::default(),
+            forwarded: 0,
+        }
+    }
+
+    /// Places the events read since the last call in the terminal grid.
+    ///
+    /// The terminal must be locked fairly, so that all bytes read from the PTY were parsed.
+    pub(crate) fn sync<T>(&mut self, term: &Term<T>, max_history_size: usize) -> Vec<PlacedEvent> {
+        let alt_screen = term.mode().contains(TermMode::ALT_SCREEN);
+        let (events, line_feeds) = {
+            let mut pending = self.pending.lock();
+            // Events from the primary screen are placed once the terminal is back on it.
+            let deferred_from = if alt_screen {
+                pending
+                    .events
+                    .iter()
+                    .position(|event| !event.alt_screen)
+                    .unwrap_or(pending.events.len())
+            } else {
+                pending.events.len()
+            };
+            let deferred = pending.events.split_off(deferred_from);
+            (
+                mem::replace(&mut pending.events, deferred),
+                pending.line_feeds,
+            )
+        };
+        let cursor = term.grid().cursor.point;
+        let topmost = term.topmost_line();
+        let bottommost = term.bottommost_line();
+        let last_column = term.last_column();
+        let wraps = |line: Line| {
+            line >= topmost
+                && line <= bottommost
+                && term.grid()[line][last_column]
+                    .flags
+                    .contains(Flags::WRAPLINE)
+        };
+        if alt_screen {
+            return events
+                .into_iter()
+                .map(|event| PlacedEvent {
+                    point: EventPoint::AltScreen(offset_point(cursor, event.offset)),
+                    event: event.event,
+                    read_at: event.read_at,
+                })
+                .collect();
+        }
+
+        let grid = GridState {
+            cursor,
+            history_size: term.history_size(),
+            max_history_size,
+        };
+        self.update(grid, line_feeds, &wraps);
+        events
+            .into_iter()
+            .filter(|event| !event.alt_screen)
+            .map(|event| PlacedEvent {
+                point: EventPoint::Output(self.event_point(
+                    grid,
+                    line_feeds - event.line_feeds,
+                    event.offset,
+                    &wraps,
+                )),
+                event: event.event,
+                read_at: event.read_at,
+            })
+            .collect()
+    }
+
+    /// Updates the grid state, given the total number of line feeds read and whether each line
+    /// of the grid wraps onto the next one.
+    pub(crate) fn update(
+        &mut self,
+        grid: GridState,
+        line_feeds: usize,
+        wraps: &dyn Fn(Line) -> bool,
+    ) {
+        if let Some(last_grid) = self.grid {
+            if grid.history_size < last_grid.history_size {
+                // The history was cleared, the lines that are gone were at its top.
+                self.evicted_lines += last_grid.history_size - grid.history_size;
+            } else if grid.history_size == grid.max_history_size {
+                // Every row written either moved the cursor down or scrolled a line into the full
+                // history, dropping its topmost line. The rows written since the last update go
+                // back to the line the cursor was on then, which may have been wrapped already.
+                let last_line_start =
+                    line_start_before(grid.cursor.line, line_feeds - self.line_feeds, wraps);
+                let rows_written = (grid.cursor.line.0 - last_line_start.0) as i64
+                    - self.cursor_wrapped_rows as i64;
+                let cursor_moved = grid.cursor.line.0 - last_grid.cursor.line.0;
+                let scrolled = rows_written - cursor_moved as i64;
+                let history_growth = grid.history_size - last_grid.history_size;
+                self.evicted_lines += (scrolled.max(0) as usize).saturating_sub(history_growth);
+            }
+        }
+        self.line_feeds = line_feeds;
+        self.grid = Some(grid);
+        self.cursor_wrapped_rows =
+            (grid.cursor.line.0 - line_start_before(grid.cursor.line, 0, wraps).0) as usize;
+    }
+
+    /// Returns where an event happened, given the number of line feeds written after it.
+    ///
+    /// Without line feeds after the event, it's placed relative to the cursor. Otherwise, only
+    /// its line is known, and it's placed at the start of the line, counting the rows of the lines
+    /// after it that wrapped.
+    pub(crate) fn event_point(
+        &self,
+        grid: GridState,
+        line_feeds_after: usize,
+        offset: CursorOffset,
+        wraps: &dyn Fn(Line) -> bool,
+    ) -> OutputPoint {
+        let point = if line_feeds_after == 0 {
+            offset_point(grid.cursor, offset)
+        } else {
+            AlacPoint::new(
+                line_start_before(grid.cursor.line, line_feeds_after + offset.lines, wraps),
+                Column(0),
+            )
+        };
+        OutputPoint {
+            line: self.evicted_lines + (grid.history_size as i32 + point.line.0).max(0) as usize,
+            column: point.column,
+        }
+    }
+
+    /// The number of lines dropped from the top of the history.
+    pub(crate) fn evicted_lines(&self) -> usize {
+        self.evicted_lines
+    }
+
+    /// Returns the line of a point in the current grid, which is above the history if the point
+    /// was dropped from it.
+    pub(crate) fn grid_line(&self, point: OutputPoint) -> Option<Line> {
+        let grid = self.grid?;
+        let line = point.line as i64 - self.evicted_lines as i64 - grid.history_size as i64;
+        Some(Line(line.max(i32::MIN as i64) as i32))
+    }
+
+    /// Returns the point in the current grid, if it's still there.
+    pub(crate) fn grid_point(&self, point: OutputPoint) -> Option<AlacPoint> {
+        let grid = self.grid?;
+        let line = self.grid_line(point)?;
+        (line.0 >= -(grid.history_size as i32)).then(|| AlacPoint::new(line, point.column))
+    }
+}
+
+/// Returns the first row of the line the given number of line feeds before the one on the given
+/// row, where a line that wraps spans several rows and only its last one ends with a line feed.
+fn line_start_before(line: Line, line_feeds: usize, wraps: &dyn Fn(Line) -> bool) -> Line {
+    let line_start = |mut line: Line| {
+        while wraps(Line(line.0 - 1)) {
+            line = Line(line.0 - 1);
+        }
+        line
+    };
+    let mut line = line_start(line);
+    for _ in 0..line_feeds {
+        line = line_start(Line(line.0 - 1));
+    }
+    line
+}
+
+fn offset_point(cursor: AlacPoint, offset: CursorOffset) -> AlacPoint {
+    AlacPoint::new(
+        Line(cursor.line.0 - offset.lines as i32),
+        Column(cursor.column.0.saturating_sub(offset.columns)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_size() -> WindowSize {
+        WindowSize {
+            num_lines: 24,
+            num_cols: 80,
+            cell_width: 10,
+            cell_height: 20,
+        }
+    }
+
+    fn scan(chunks: &[&[u8]]) -> (Vec<u8>, Vec<(String, usize)>, usize) {
+        let mut scanner = OutputScanner::new(window_size(), false);
+        let mut scanned = ScannedBytes::default();
+        let mut pending = PendingOutput::default();
+        for chunk in chunks {
+            scanner.advance(chunk, &mut scanned, &mut pending);
+        }
+        let events = pending
+            .events
+            .into_iter()
+            .map(|pending| {
+                let event = match pending.event {
+                    OutputEvent::Shell(mark) => format!("{mark:?}"),
+                    OutputEvent::Image(ImageEvent::Place(placement)) => format!(
+                        "image {}x{} at -{},-{}",
+                        placement.columns,
+                        placement.lines,
+                        pending.offset.lines,
+                        pending.offset.columns
+                    ),
+                    OutputEvent::Image(ImageEvent::Delete { image_id }) => {
+                        format!("delete {image_id:?}")
+                    }
+                };
+                (event, pending.line_feeds)
+            })
+            .collect();
+        (scanned.forward, events, pending.line_feeds)
+    }
+
+    #[test]
+    fn test_scan_forwards_everything_but_images() {
+        let output: &[u8] =
+            b"a\x1b]0;title\x07\x1b[1mb\x1bP=1s\x1b\\\x1b_other\x1b\\\x1b\x1b[0m\x1bD";
+        let (forwarded, events, line_feeds) = scan(&[output]);
+        assert_eq!(forwarded, output);
+        assert!(events.is_empty());
+        assert_eq!(line_feeds, 1);
+
+        let (forwarded, _, _) = scan(&[b"\x1b", b"P1;", b"2", b"$q\x1b", b"\\"]);
+        assert_eq!(forwarded, b"\x1bP1;2$q\x1b\\");
+    }
+
+    #[test]
+    fn test_scan_images() {
+        // A red Sixel image, 20 by 30 pixels, split across reads.
+        let (forwarded, events, line_feeds) = scan(&[
+            b"a\n\x1bP0;1",
+            b";0q\"1;1;20;30#1;2;100;0;0#1!20~-!20~-!2",
+            b"0~-!20~-!20~\x1b",
+            b"\\b",
+        ]);
+        assert_eq!(forwarded, b"a\n\n\nb");
+        assert_eq!(events, vec![("image 2x1.5 at -2,-0".to_string(), 3)]);
+        assert_eq!(line_feeds, 3);
+
+        // A 24-bit Kitty image, 30 by 50 pixels, sent in two chunks.
+        let pixels = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            vec![255; 30 * 50 * 3],
+        );
+        let (first, rest) = pixels.split_at(4000);
+        let (forwarded, events, _) = scan(&[
+            format!("\x1b_Ga=T,f=24,s=30,v=50,m=1;{first}\x1b\\").as_bytes(),
+            format!("\x1b_Gm=0;{rest}\x1b\\\x1b_Ga=d\x1b\\").as_bytes(),
+        ]);
+        assert_eq!(forwarded, b"\n\n\x1b[3C");
+        assert_eq!(
+            events,
+            vec![
+                ("image 3x2.5 at -2,-3".to_string(), 2),
+                ("delete None".to_string(), 2),
+            ]
+        );
+
+        // Cancelled sequences are dropped.
+        let (forwarded, events, _) = scan(&[b"\x1bPq#0!10~\x18c"]);
+        assert_eq!(forwarded, b"c");
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_scan_shell_marks() {
+        let (forwarded, events, line_feeds) = scan(&[
+            b"\x1b]633;P;Cwd=/tmp/a\\x3bb\x07\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n",
+            b"\x1b]633;E;ls -l\\\\\x1b\\\x1b]633;C\x07a\r\nb\r\n\x1b]133;D;2\x07",
+        ]);
+        assert_eq!(
+            events,
+            vec![
+                ("WorkingDirectory(\"/tmp/a;b\")".to_string(), 0),
+                ("PromptStart".to_string(), 0),
+                ("CommandStart".to_string(), 0),
+                ("CommandLine(\"ls -l\\\\\")".to_string(), 1),
+                ("CommandExecuted".to_string(), 1),
+                ("CommandFinished(Some(2))".to_string(), 3),
+            ]
+        );
+        assert_eq!(line_feeds, 3);
+        assert!(forwarded.ends_with(b"b\r\n\x1b]133;D;2\x07"));
+
+        let (_, events, _) = scan(&[b"\x1b", b"]13", b"3;D", b";1", b"\x1b", b"\\"]);
+        assert_eq!(events, vec![("CommandFinished(Some(1))".to_string(), 0)]);
+    }
+
+    #[test]
+    fn test_scan_ignores_alternate_screen_line_feeds() {
+        let (_, events, line_feeds) = scan(&[
+            b"a\n\x1b[?1049h\n\n\x1b]133;A\x07\x1b[2J",
+            b"\x1b[?1049l\nb\x1bE\x1b]0;title\x07",
+        ]);
+        assert_eq!(events, vec![("PromptStart".to_string(), 1)]);
+        assert_eq!(line_feeds, 3);
+    }
+
+    fn grid(line: i32, column: usize, history_size: usize) -> GridState {
+        GridState {
+            cursor: AlacPoint::new(Line(line), Column(column)),
+            history_size,
+            max_history_size: 100,
+        }
+    }
+
+    #[test]
+    fn test_output_points() {
+        let mut tracker = OutputTracker::new();
+        let no_wraps = |_| false;
+        tracker.update(grid(3, 5, 0), 3, &no_wraps);
+        let offset = CursorOffset {
+            lines: 1,
+            columns: 2,
+        };
+        let point = tracker.event_point(grid(3, 5, 0), 0, offset, &no_wraps);
+        assert_eq!(
+            point,
+            OutputPoint {
+                line: 2,
+                column: Column(3)
+            }
+        );
+        assert_eq!(
+            tracker.event_point(grid(3, 5, 0), 2, offset, &no_wraps),
+            OutputPoint {
+                line: 0,
+                column: Column(0)
+            }
+        );
+
+        // Scrolling a full history drops lines from its top.
+        tracker.update(grid(9, 0, 100), 109, &no_wraps);
+        tracker.update(grid(9, 0, 100), 119, &no_wraps);
+        assert_eq!(tracker.evicted_lines(), 10);
+        assert_eq!(
+            tracker.grid_point(OutputPoint {
+                line: 12,
+                column: Column(3)
+            }),
+            Some(AlacPoint::new(Line(-98), Column(3)))
+        );
+        assert_eq!(
+            tracker.grid_point(OutputPoint {
+                line: 9,
+                column: Column(0)
+            }),
+            None
+        );
+
+        // Clearing the history drops all of it.
+        tracker.update(grid(9, 0, 0), 119, &no_wraps);
+        assert_eq!(tracker.evicted_lines(), 110);
+    }
+
+    #[test]
+    fn test_output_points_with_wrapped_lines() {
+        let mut tracker = OutputTracker::new();
+        // A command printed a line longer than the terminal is wide, over rows 1 to 3, and a short
+        // line on row 4.
+        let wraps = |line: Line| (1..=2).contains(&line.0);
+        tracker.update(grid(5, 0, 0), 3, &wraps);
+        assert_eq!(
+            tracker.event_point(grid(5, 0, 0), 2, CursorOffset::default(), &wraps),
+            OutputPoint {
+                line: 1,
+                column: Column(0)
+            }
+        );
+        assert_eq!(
+            tracker.event_point(grid(5, 0, 0), 1, CursorOffset::default(), &wraps),
+            OutputPoint {
+                line: 4,
+                column: Column(0)
+            }
+        );
+
+        // Wrapped lines scroll as many rows into a full history as they span.
+        tracker.update(grid(9, 0, 100), 109, &|_| false);
+        let wraps = |line: Line| (6..=7).contains(&line.0);
+        tracker.update(grid(9, 0, 100), 110, &wraps);
+        assert_eq!(tracker.evicted_lines(), 3);
+    }
+}
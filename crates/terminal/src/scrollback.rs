@@ -0,0 +1,255 @@
+//! Saving and restoring terminal scrollback across restarts.
+//!
+//! The scrollback is stored as text with SGR escape sequences, so that a restored
+//! terminal shows the previous session's output with its colors, and so that it
+//! can be replayed through the terminal parser at whatever size the new terminal has.
+
+use std::fmt::Write as _;
+
+use alacritty_terminal::{
+    Term,
+    event::EventListener,
+    grid::{Dimensions, GridCell},
+    index::{Column, Line},
+    term::{
+        TermMode,
+        cell::{Cell, Flags},
+    },
+    vte::ansi::{Color, NamedColor, Processor},
+};
+
+/// Printed below the restored scrollback, before the new shell prompt.
+const RESTORED_MARKER: &str = "\x1b[0;2mHistory restored\x1b[0m\r\n\r\n";
+
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+#[derive(Clone, Copy, PartialEq)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: Color::Named(NamedColor::Foreground),
+            bg: Color::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    fn of(cell: &Cell) -> Self {
+        Self {
+            fg: cell.fg,
+            bg: cell.bg,
+            flags: cell.flags & STYLE_FLAGS,
+        }
+    }
+
+    fn write_sgr(&self, out: &mut String) {
+        out.push_str("\x1b[0");
+        for (flag, param) in [
+            (Flags::BOLD, "1"),
+            (Flags::DIM, "2"),
+            (Flags::ITALIC, "3"),
+            (Flags::UNDERLINE, "4"),
+            (Flags::DOUBLE_UNDERLINE, "4:2"),
+            (Flags::UNDERCURL, "4:3"),
+            (Flags::DOTTED_UNDERLINE, "4:4"),
+            (Flags::DASHED_UNDERLINE, "4:5"),
+            (Flags::INVERSE, "7"),
+            (Flags::HIDDEN, "8"),
+            (Flags::STRIKEOUT, "9"),
+        ] {
+            if self.flags.contains(flag) {
+                out.push(';');
+                out.push_str(param);
+            }
+        }
+        write_color(out, self.fg, 30);
+        write_color(out, self.bg, 40);
+        out.push('m');
+    }
+}
+
+fn write_color(out: &mut String, color: Color, base: usize) {
+    match color {
+        Color::Named(name) => {
+            let index = name as usize;
+            let dim_black = NamedColor::DimBlack as usize;
+            let param = if index < 8 {
+                base + index
+            } else if index < 16 {
+                base + 60 + index - 8
+            } else if (dim_black..dim_black + 8).contains(&index) {
+                base + index - dim_black
+            } else {
+                // The default foreground and background colors need no parameter.
+                return;
+            };
+            write!(out, ";{param}").ok();
+        }
+        Color::Indexed(index) => {
+            write!(out, ";{};5;{index}", base + 8).ok();
+        }
+        Color::Spec(rgb) => {
+            write!(out, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b).ok();
+        }
+    }
+}
+
+/// Serializes up to the last `max_lines` lines above the cursor, leaving out the
+/// current prompt line. Returns `None` when there is nothing to save, or when a
+/// full screen application owns the terminal.
+pub(crate) fn serialize<T: EventListener>(term: &Term<T>, max_lines: usize) -> Option<String> {
+    if term.mode().contains(TermMode::ALT_SCREEN) {
+        return None;
+    }
+
+    let grid = term.grid();
+    let end = grid.cursor.point.line;
+    let start = grid
+        .topmost_line()
+        .max(end - max_lines.min(grid.total_lines()) as i32);
+    let last_column = grid.columns() - 1;
+
+    let mut out = String::new();
+    let mut style = Style::default();
+    for line in start.0..end.0 {
+        let row = &grid[Line(line)];
+        let Some(len) = (0..row.len()).rev().find(|&ix| !row[Column(ix)].is_empty()) else {
+            out.push_str("\r\n");
+            continue;
+        };
+
+        for cell in &row[..Column(len + 1)] {
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+            let cell_style = Style::of(cell);
+            if cell_style != style {
+                cell_style.write_sgr(&mut out);
+                style = cell_style;
+            }
+            out.push(cell.c);
+            if let Some(zerowidth) = cell.zerowidth() {
+                out.extend(zerowidth);
+            }
+        }
+
+        if !row[Column(last_column)].flags.contains(Flags::WRAPLINE) {
+            if style != Style::default() {
+                out.push_str("\x1b[0m");
+                style = Style::default();
+            }
+            out.push_str("\r\n");
+        }
+    }
+
+    if out.trim().is_empty() {
+        return None;
+    }
+    // The last line may have wrapped into the prompt line.
+    if !out.ends_with("\r\n") {
+        if style != Style::default() {
+            out.push_str("\x1b[0m");
+        }
+        out.push_str("\r\n");
+    }
+    Some(out)
+}
+
+/// Replays previously saved scrollback into a terminal that has not received
+/// any output yet, so the new shell's prompt appears below it.
+pub(crate) fn restore<T: EventListener>(term: &mut Term<T>, scrollback: &str) {
+    let mut processor: Processor = Processor::new();
+    processor.advance(term, scrollback.as_bytes());
+    processor.advance(term, RESTORED_MARKER.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{event::VoidListener, term::Config, term::test::TermSize};
+
+    use super::*;
+
+    fn new_term(columns: usize, lines: usize) -> Term<VoidListener> {
+        Term::new(
+            Config::default(),
+            &TermSize::new(columns, lines),
+            VoidListener,
+        )
+    }
+
+    fn feed(term: &mut Term<VoidListener>, bytes: &str) {
+        let mut processor: Processor = Processor::new();
+        processor.advance(term, bytes.as_bytes());
+    }
+
+    fn text(term: &Term<VoidListener>) -> Vec<String> {
+        let grid = term.grid();
+        (grid.topmost_line().0..=grid.bottommost_line().0)
+            .map(|line| {
+                crate::row_to_string(&grid[Line(line)])
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_serialize_scrollback() {
+        let mut term = new_term(10, 4);
+        feed(
+            &mut term,
+            "$ ls\r\n\x1b[1;31mred\x1b[0m plain\r\n0123456789abc\r\n\x1b[38;5;200mx\x1b[0m\r\n$ ",
+        );
+
+        assert_eq!(
+            serialize(&term, 100).unwrap(),
+            "$ ls\r\n\x1b[0;1;31mred\x1b[0m plain\r\n0123456789abc\r\n\x1b[0;38;5;200mx\x1b[0m\r\n"
+        );
+        assert_eq!(
+            serialize(&term, 2).unwrap(),
+            "abc\r\n\x1b[0;38;5;200mx\x1b[0m\r\n"
+        );
+
+        feed(&mut term, "\x1b[?1049h");
+        assert_eq!(serialize(&term, 100), None);
+        assert_eq!(serialize(&new_term(10, 4), 100), None);
+    }
+
+    #[test]
+    fn test_restore_scrollback() {
+        let mut old_term = new_term(10, 4);
+        feed(
+            &mut old_term,
+            "one\r\n\x1b[32mtwo\x1b[0m\r\n0123456789abc\r\n$ ",
+        );
+        let scrollback = serialize(&old_term, 100).unwrap();
+
+        let mut restored = new_term(20, 6);
+        restore(&mut restored, &scrollback);
+        feed(&mut restored, "$ ");
+
+        assert_eq!(
+            text(&restored),
+            ["one", "two", "0123456789abc", "History restored", "", "$"]
+        );
+        let grid = restored.grid();
+        assert_eq!(grid[Line(1)][Column(0)].fg, Color::Named(NamedColor::Green));
+        assert_eq!(grid.cursor.point.line, Line(5));
+    }
+}
@@ -0,0 +1,592 @@
+//! Shell integration: tracks where prompts, commands and their output start and end.
+//!
+//! Shells report these through OSC 133 and OSC 633 sequences, which Alacritty ignores. They're
+//! picked out of the shell's output by the output scanner as it's read, and the marks are placed in
+//! the terminal grid on the next wakeup.
+
+use std::{
+    cmp,
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use alacritty_terminal::index::{Line, Point as AlacPoint};
+use anyhow::Result;
+use collections::HashMap;
+use task::Shell;
+
+use crate::output_scanner::{OutputPoint, OutputTracker};
+
+/// The number of command blocks kept per terminal.
+const MAX_COMMAND_BLOCKS: usize = 1000;
+
+const BASH_INTEGRATION: &str = include_str!("shell_integration/zed.bash");
+const ZSH_INTEGRATION: &str = include_str!("shell_integration/zed.zsh");
+const ZSH_ENV: &str = include_str!("shell_integration/zshenv.zsh");
+const FISH_INTEGRATION: &str = include_str!("shell_integration/zed.fish");
+
+/// A mark sent by the shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ShellMark {
+    /// `A`: the prompt is about to be printed.
+    PromptStart,
+    /// `B`: the prompt was printed, and the user types the command after it.
+    CommandStart,
+    /// `633;E`: the command line about to run.
+    CommandLine(String),
+    /// `C`: the command is about to run, and its output follows.
+    CommandExecuted,
+    /// `D`: the command finished, with its exit code.
+    CommandFinished(Option<i32>),
+    /// `633;P;Cwd=` or OSC 7: the shell's working directory.
+    WorkingDirectory(PathBuf),
+}
+
+pub(crate) fn parse_osc(osc: &str) -> Option<ShellMark> {
+    let (code, rest) = osc.split_once(';').unwrap_or((osc, ""));
+    match code {
+        "133" | "633" => {
+            let mut params = rest.split(';');
+            match params.next()? {
+                "A" => Some(ShellMark::PromptStart),
+                "B" => Some(ShellMark::CommandStart),
+                "C" => Some(ShellMark::CommandExecuted),
+                "D" => Some(ShellMark::CommandFinished(
+                    params.next().and_then(|exit_code| exit_code.parse().ok()),
+                )),
+                "E" => Some(ShellMark::CommandLine(unescape(
+                    params.next().unwrap_or_default(),
+                ))),
+                "P" => {
+                    let (key, value) = params.next()?.split_once('=')?;
+                    (key == "Cwd").then(|| ShellMark::WorkingDirectory(unescape(value).into()))
+                }
+                _ => None,
+            }
+        }
+        "7" => {
+            let path = rest.strip_prefix("file://")?;
+            let path = &path[path.find('/')?..];
+            let path = urlencoding::decode(path).ok()?;
+            Some(ShellMark::WorkingDirectory(path.into_owned().into()))
+        }
+        _ => None,
+    }
+}
+
+/// Reverses the escaping of OSC 633 values, where `\` is sent as `\\` and other characters may be
+/// sent as `\xHH`.
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' {
+            if bytes.get(index + 1) == Some(&b'\\') {
+                unescaped.push(b'\\');
+                index += 2;
+                continue;
+            }
+            if bytes.get(index + 1) == Some(&b'x') {
+                let byte = bytes
+                    .get(index + 2..index + 4)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = byte {
+                    unescaped.push(byte);
+                    index += 4;
+                    continue;
+                }
+            }
+        }
+        unescaped.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// A command run in a shell with shell integration: its prompt, command line and output.
+#[derive(Debug, Clone)]
+pub struct CommandBlock {
+    /// The command line, if the shell reported it or it could be read after the prompt.
+    pub command: Option<String>,
+    /// The shell's working directory when the prompt was printed.
+    pub working_directory: Option<PathBuf>,
+    /// The command's exit code, once it finished.
+    pub exit_code: Option<i32>,
+    /// How long the command ran, once it finished.
+    pub duration: Option<Duration>,
+    prompt_start: OutputPoint,
+    command_start: Option<OutputPoint>,
+    output_start: Option<OutputPoint>,
+    output_end: Option<OutputPoint>,
+    started_at: Option<Instant>,
+}
+
+impl CommandBlock {
+    pub fn is_finished(&self) -> bool {
+        self.output_end.is_some()
+    }
+}
+
+/// The prompt of a command visible in the terminal, for rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandMark {
+    pub line: Line,
+    pub exit_code: Option<i32>,
+}
+
+pub(crate) struct ShellIntegration {
+    blocks: VecDeque<CommandBlock>,
+    working_directory: Option<PathBuf>,
+}
+
+impl ShellIntegration {
+    pub(crate) fn new() -> Self {
+        Self {
+            blocks: VecDeque::new(),
+            working_directory: None,
+        }
+    }
+
+    /// Drops the blocks whose prompt was dropped from the history.
+    pub(crate) fn prune(&mut self, tracker: &OutputTracker) {
+        while self
+            .blocks
+            .front()
+            .is_some_and(|block| block.prompt_start.line < tracker.evicted_lines())
+        {
+            self.blocks.pop_front();
+        }
+    }
+
+    pub(crate) fn apply_mark(
+        &mut self,
+        mark: ShellMark,
+        point: OutputPoint,
+        read_at: Instant,
+        tracker: &OutputTracker,
+        text_until_line_end: impl Fn(AlacPoint, Line) -> String,
+    ) {
+        if let ShellMark::WorkingDirectory(path) = mark {
+            self.working_directory = Some(path);
+            return;
+        }
+        if mark == ShellMark::PromptStart {
+            if self.blocks.len() == MAX_COMMAND_BLOCKS {
+                self.blocks.pop_front();
+            }
+            self.blocks.push_back(CommandBlock {
+                command: None,
+                working_directory: self.working_directory.clone(),
+                exit_code: None,
+                duration: None,
+                prompt_start: point,
+                command_start: None,
+                output_start: None,
+                output_end: None,
+                started_at: None,
+            });
+            return;
+        }
+
+        let command_start = self
+            .blocks
+            .back()
+            .and_then(|block| block.command_start)
+            .and_then(|start| tracker.grid_point(start));
+        let output_line = tracker.grid_point(point).map(|point| point.line);
+        let Some(block) = self.blocks.back_mut().filter(|block| !block.is_finished()) else {
+            return;
+        };
+        match mark {
+            ShellMark::CommandStart => block.command_start = Some(point),
+            ShellMark::CommandLine(command) => {
+                block.command = Some(command).filter(|command| !command.trim().is_empty());
+            }
+            ShellMark::CommandExecuted => {
+                block.output_start = Some(point);
+                block.started_at = Some(read_at);
+                if block.command.is_none()
+                    && let Some((command_start, output_line)) = command_start.zip(output_line)
+                {
+                    // Without the command line from the shell, read what was typed after the
+                    // prompt, up to the line the output starts on.
+                    let end = cmp::max(command_start.line, output_line - 1);
+                    let command = text_until_line_end(command_start, end);
+                    block.command =
+                        Some(command.trim().to_string()).filter(|command| !command.is_empty());
+                }
+            }
+            ShellMark::CommandFinished(exit_code) => {
+                if block.output_start.is_some() {
+                    block.output_end = Some(point);
+                    block.exit_code = exit_code;
+                    block.duration = block
+                        .started_at
+                        .map(|started_at| read_at.saturating_duration_since(started_at));
+                }
+            }
+            ShellMark::PromptStart | ShellMark::WorkingDirectory(_) => {}
+        }
+    }
+
+    pub(crate) fn blocks(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.blocks.iter()
+    }
+
+    /// Returns the grid lines of the prompts still in the terminal, from top to bottom.
+    pub(crate) fn prompt_lines<'a>(
+        &'a self,
+        tracker: &'a OutputTracker,
+    ) -> impl Iterator<Item = Line> + 'a {
+        self.blocks
+            .iter()
+            .filter_map(|block| tracker.grid_point(block.prompt_start))
+            .map(|point| point.line)
+    }
+
+    pub(crate) fn command_marks(
+        &self,
+        tracker: &OutputTracker,
+        visible_lines: std::ops::Range<i32>,
+    ) -> Vec<CommandMark> {
+        self.blocks
+            .iter()
+            .filter_map(|block| {
+                let point = tracker.grid_point(block.prompt_start)?;
+                visible_lines
+                    .contains(&point.line.0)
+                    .then_some(CommandMark {
+                        line: point.line,
+                        exit_code: block.exit_code,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the output of the last command that finished, from its first cell to its last one.
+    pub(crate) fn last_output_range(
+        &self,
+        tracker: &OutputTracker,
+    ) -> Option<(AlacPoint, AlacPoint)> {
+        let block = self.blocks.iter().rev().find(|block| block.is_finished())?;
+        let start = tracker.grid_point(block.output_start?)?;
+        let end = tracker.grid_point(block.output_end?)?;
+        Some((start, end))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ShellKind {
+    fn from_program(program: &str) -> Option<Self> {
+        let name = Path::new(program).file_name()?.to_str()?;
+        match name.trim_start_matches('-') {
+            "bash" => Some(Self::Bash),
+            "zsh" => Some(Self::Zsh),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Makes bash, zsh and fish load Zed's shell integration on startup.
+///
+/// Shells started with explicit arguments are left alone, as the arguments may not start an
+/// interactive shell.
+pub(crate) fn inject(shell: Shell, env: &mut HashMap<String, String>) -> Result<Shell> {
+    let env_var = |env: &HashMap<String, String>, name: &str| {
+        env.get(name).cloned().or_else(|| std::env::var(name).ok())
+    };
+    let program = match &shell {
+        Shell::System if cfg!(not(target_os = "windows")) => env_var(env, "SHELL"),
+        Shell::Program(program) => Some(program.clone()),
+        Shell::System | Shell::WithArguments { .. } => None,
+    };
+    let Some((program, kind)) =
+        program.and_then(|program| Some((program.clone(), ShellKind::from_program(&program)?)))
+    else {
+        return Ok(shell);
+    };
+
+    let dir = paths::shell_integration_dir();
+    match kind {
+        ShellKind::Bash => {
+            write_script(&dir.join("zed.bash"), BASH_INTEGRATION)?;
+            // `--init-file` only applies to shells that aren't login shells, so the integration
+            // loads the login files itself where the system shell is started as a login shell.
+            if matches!(shell, Shell::System) && cfg!(target_os = "macos") {
+                env.insert("ZED_BASH_LOGIN".to_string(), "1".to_string());
+            }
+            Ok(Shell::WithArguments {
+                program,
+                args: vec![
+                    "--init-file".to_string(),
+                    dir.join("zed.bash").to_string_lossy().into_owned(),
+                ],
+                title_override: None,
+            })
+        }
+        ShellKind::Zsh => {
+            write_script(&dir.join("zed.zsh"), ZSH_INTEGRATION)?;
+            write_script(&dir.join("zsh").join(".zshenv"), ZSH_ENV)?;
+            if let Some(zdotdir) = env_var(env, "ZDOTDIR") {
+                env.insert("ZED_USER_ZDOTDIR".to_string(), zdotdir);
+            }
+            env.insert(
+                "ZDOTDIR".to_string(),
+                dir.join("zsh").to_string_lossy().into_owned(),
+            );
+            env.insert(
+                "ZED_SHELL_INTEGRATION_DIR".to_string(),
+                dir.to_string_lossy().into_owned(),
+            );
+            Ok(shell)
+        }
+        ShellKind::Fish => {
+            let data_dir = dir.join("fish_data");
+            write_script(
+                &data_dir.join("fish/vendor_conf.d/zed.fish"),
+                FISH_INTEGRATION,
+            )?;
+            let data_dirs = match env_var(env, "XDG_DATA_DIRS") {
+                Some(data_dirs) => {
+                    env.insert("ZED_USER_XDG_DATA_DIRS".to_string(), data_dirs.clone());
+                    data_dirs
+                }
+                None => "/usr/local/share:/usr/share".to_string(),
+            };
+            env.insert(
+                "XDG_DATA_DIRS".to_string(),
+                format!("{}:{data_dirs}", data_dir.to_string_lossy()),
+            );
+            Ok(shell)
+        }
+    }
+}
+
+fn write_script(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::index::Column;
+
+    use super::*;
+    use crate::output_scanner::{CursorOffset, GridState};
+
+    fn grid(line: i32, column: usize, history_size: usize) -> GridState {
+        GridState {
+            cursor: AlacPoint::new(Line(line), Column(column)),
+            history_size,
+            max_history_size: 100,
+        }
+    }
+
+    /// Places marks read before the given numbers of line feeds, as a wakeup would.
+    fn apply(
+        shell_integration: &mut ShellIntegration,
+        tracker: &mut OutputTracker,
+        grid: GridState,
+        line_feeds: usize,
+        marks: Vec<(ShellMark, usize)>,
+        text_until_line_end: impl Fn(AlacPoint, Line) -> String,
+    ) {
+        tracker.update(grid, line_feeds, &|_| false);
+        shell_integration.prune(tracker);
+        for (mark, line_feeds_before) in marks {
+            let point = tracker.event_point(
+                grid,
+                line_feeds - line_feeds_before,
+                CursorOffset::default(),
+                &|_| false,
+            );
+            shell_integration.apply_mark(
+                mark,
+                point,
+                Instant::now(),
+                tracker,
+                &text_until_line_end,
+            );
+        }
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let mut shell_integration = ShellIntegration::new();
+        let mut tracker = OutputTracker::new();
+        let no_text = |_, _| String::new();
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(0, 2, 0),
+            0,
+            vec![
+                (ShellMark::WorkingDirectory("/tmp".into()), 0),
+                (ShellMark::PromptStart, 0),
+                (ShellMark::CommandStart, 0),
+            ],
+            no_text,
+        );
+        // `ls` was typed after the prompt, and printed two lines.
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(3, 0, 0),
+            3,
+            vec![
+                (ShellMark::CommandExecuted, 1),
+                (ShellMark::CommandFinished(Some(1)), 3),
+            ],
+            |start, end| {
+                assert_eq!(start, AlacPoint::new(Line(0), Column(2)));
+                assert_eq!(end, Line(0));
+                "ls ".to_string()
+            },
+        );
+        // The next command printed enough to scroll both prompts into the history.
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(9, 0, 4),
+            13,
+            vec![
+                (ShellMark::PromptStart, 3),
+                (ShellMark::CommandLine("make".to_string()), 4),
+                (ShellMark::CommandExecuted, 4),
+                (ShellMark::CommandFinished(Some(0)), 13),
+                (ShellMark::PromptStart, 13),
+            ],
+            no_text,
+        );
+
+        let blocks = shell_integration.blocks().collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].command.as_deref(), Some("ls"));
+        assert_eq!(blocks[0].working_directory, Some(PathBuf::from("/tmp")));
+        assert_eq!(blocks[0].exit_code, Some(1));
+        assert!(blocks[0].duration.is_some());
+        assert_eq!(blocks[1].command.as_deref(), Some("make"));
+        assert_eq!(blocks[1].exit_code, Some(0));
+        assert!(!blocks[2].is_finished());
+        assert_eq!(
+            shell_integration.prompt_lines(&tracker).collect::<Vec<_>>(),
+            vec![Line(-4), Line(-1), Line(9)]
+        );
+        assert_eq!(
+            shell_integration.command_marks(&tracker, -1..10),
+            vec![
+                CommandMark {
+                    line: Line(-1),
+                    exit_code: Some(0)
+                },
+                CommandMark {
+                    line: Line(9),
+                    exit_code: None
+                },
+            ]
+        );
+        assert_eq!(
+            shell_integration.last_output_range(&tracker),
+            Some((
+                AlacPoint::new(Line(0), Column(0)),
+                AlacPoint::new(Line(9), Column(0))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_command_blocks_without_a_prompt_or_command() {
+        let mut shell_integration = ShellIntegration::new();
+        let mut tracker = OutputTracker::new();
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(0, 0, 0),
+            0,
+            vec![
+                (ShellMark::CommandExecuted, 0),
+                (ShellMark::PromptStart, 0),
+                (ShellMark::CommandFinished(Some(130)), 0),
+            ],
+            |_, _| String::new(),
+        );
+        let blocks = shell_integration.blocks().collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].exit_code, None);
+        assert!(!blocks[0].is_finished());
+    }
+
+    #[test]
+    fn test_command_blocks_scrolled_out_of_full_history() {
+        let mut shell_integration = ShellIntegration::new();
+        let mut tracker = OutputTracker::new();
+        let no_text = |_, _| String::new();
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(5, 0, 100),
+            0,
+            vec![(ShellMark::PromptStart, 0)],
+            no_text,
+        );
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(5, 0, 100),
+            10,
+            vec![(ShellMark::PromptStart, 10)],
+            no_text,
+        );
+        assert_eq!(
+            shell_integration.prompt_lines(&tracker).collect::<Vec<_>>(),
+            vec![Line(-5), Line(5)]
+        );
+
+        // Clearing the history drops the blocks in it.
+        apply(
+            &mut shell_integration,
+            &mut tracker,
+            grid(5, 0, 0),
+            10,
+            Vec::new(),
+            no_text,
+        );
+        assert_eq!(
+            shell_integration.prompt_lines(&tracker).collect::<Vec<_>>(),
+            vec![Line(5)]
+        );
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r"a\\b\x3bc\x0ad\xzz"), "a\\b;c\nd\\xzz");
+        assert_eq!(unescape(r"\xc3\xa9"), "é");
+    }
+
+    #[test]
+    fn test_shell_kind_from_program() {
+        assert_eq!(ShellKind::from_program("/bin/bash"), Some(ShellKind::Bash));
+        assert_eq!(ShellKind::from_program("-zsh"), Some(ShellKind::Zsh));
+        assert_eq!(
+            ShellKind::from_program("/opt/homebrew/bin/fish"),
+            Some(ShellKind::Fish)
+        );
+        assert_eq!(ShellKind::from_program("nu"), None);
+    }
+}
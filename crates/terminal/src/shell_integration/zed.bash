@@ -0,0 +1,77 @@
+# Zed shell integration for bash, loaded with `--init-file`.
+#
+# Marks prompts, command lines and exit codes with OSC 633 sequences so that
+# Zed can tell where each command and its output start and end.
+
+# `--init-file` replaces the startup files, so load the ones bash would have.
+if [[ -n "$ZED_BASH_LOGIN" ]]; then
+    builtin unset ZED_BASH_LOGIN
+    if [ -r /etc/profile ]; then
+        builtin source /etc/profile
+    fi
+    if [ -r ~/.bash_profile ]; then
+        builtin source ~/.bash_profile
+    elif [ -r ~/.bash_login ]; then
+        builtin source ~/.bash_login
+    elif [ -r ~/.profile ]; then
+        builtin source ~/.profile
+    fi
+elif [ -r ~/.bashrc ]; then
+    builtin source ~/.bashrc
+fi
+
+if [[ -n "$__zed_shell_integration" ]]; then
+    builtin return
+fi
+__zed_shell_integration=1
+
+__zed_escape() {
+    local value="${1//\\/\\\\}"
+    value="${value//;/\\x3b}"
+    value="${value//$'\n'/\\x0a}"
+    builtin printf '%s' "$value"
+}
+
+__zed_precmd() {
+    local exit_code="$?"
+    builtin printf '\e]633;D;%s\a' "$exit_code"
+    builtin printf '\e]633;P;Cwd=%s\a' "$(__zed_escape "$PWD")"
+    builtin printf '\e]633;A\a'
+    return "$exit_code"
+}
+
+# Run after a command line is read and before it runs.
+__zed_preexec() {
+    local command
+    command="$(HISTTIMEFORMAT= builtin history 1)"
+    command="${command#*[[:digit:]][* ] }"
+    builtin printf '\e]633;E;%s\a' "$(__zed_escape "$command")"
+    builtin printf '\e]633;C\a'
+}
+
+if [[ "$(builtin declare -p PROMPT_COMMAND 2>/dev/null)" == "declare -a"* ]]; then
+    PROMPT_COMMAND=(__zed_precmd "${PROMPT_COMMAND[@]}")
+else
+    PROMPT_COMMAND="__zed_precmd${PROMPT_COMMAND:+; $PROMPT_COMMAND}"
+fi
+
+if (( BASH_VERSINFO[0] > 4 || (BASH_VERSINFO[0] == 4 && BASH_VERSINFO[1] >= 4) )); then
+    PS0="${PS0}"'$(__zed_preexec)'
+elif [[ -z "$(builtin trap -p DEBUG)" ]]; then
+    # Bash before 4.4 has no `PS0`, so run it from a DEBUG trap instead, before the first
+    # command after the prompt. A DEBUG trap that's already set is left alone, and commands
+    # aren't marked then.
+    __zed_at_prompt=
+    __zed_debug_trap() {
+        if [[ -z "$__zed_at_prompt" ]]; then
+            return
+        fi
+        __zed_at_prompt=
+        # An empty command line runs nothing before the next prompt.
+        if [[ "$BASH_COMMAND" != __zed_precmd* ]]; then
+            __zed_preexec
+        fi
+    }
+    PROMPT_COMMAND="${PROMPT_COMMAND}; __zed_at_prompt=1"
+    builtin trap '__zed_debug_trap' DEBUG
+fi
@@ -0,0 +1,33 @@
+# Zed shell integration for fish, loaded from `vendor_conf.d` through `XDG_DATA_DIRS`.
+#
+# Marks prompts, command lines and exit codes with OSC 633 sequences so that
+# Zed can tell where each command and its output start and end.
+
+if set -q ZED_USER_XDG_DATA_DIRS
+    set -gx XDG_DATA_DIRS "$ZED_USER_XDG_DATA_DIRS"
+    set -e ZED_USER_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+
+if status is-interactive; and not set -q __zed_shell_integration
+    set -g __zed_shell_integration 1
+
+    function __zed_escape
+        string split \n -- $argv[1] | string replace -a '\\' '\\\\' | string replace -a ';' '\x3b' | string join '\x0a'
+    end
+
+    function __zed_preexec --on-event fish_preexec
+        printf '\e]633;E;%s\a' (__zed_escape "$argv")
+        printf '\e]633;C\a'
+    end
+
+    function __zed_postexec --on-event fish_postexec
+        printf '\e]633;D;%s\a' $status
+    end
+
+    function __zed_prompt --on-event fish_prompt
+        printf '\e]633;P;Cwd=%s\a' (__zed_escape "$PWD")
+        printf '\e]633;A\a'
+    end
+end
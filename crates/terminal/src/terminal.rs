@@ -2,8 +2,12 @@ pub mod mappings;
 
 pub use alacritty_terminal;
 
+mod output_scanner;
 mod pty_info;
+mod scrollback;
+mod shell_integration;
 mod terminal_hyperlinks;
+mod terminal_images;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -25,7 +29,7 @@ use alacritty_terminal::{
         ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
     },
 };
-use anyhow::{Result, bail};
+use anyhow::{Context as _, Result, bail};
 
 use futures::{
     FutureExt,
@@ -39,16 +43,21 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use output_scanner::{EventPoint, OutputEvent, OutputTracker};
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::ShellIntegration;
+pub use shell_integration::{CommandBlock, CommandMark};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, TaskId};
 use terminal_hyperlinks::RegexSearches;
+pub use terminal_images::TerminalImage;
+use terminal_images::TerminalImages;
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use urlencoding;
-use util::{paths::home_dir, truncate_and_trailoff};
+use util::{ResultExt as _, paths::home_dir, truncate_and_trailoff};
 
 use std::{
     borrow::Cow,
@@ -99,6 +108,12 @@ actions!(
         ScrollToTop,
         /// Scrolls to the bottom of the terminal buffer.
         ScrollToBottom,
+        /// Scrolls to the previous shell prompt.
+        ScrollToPreviousPrompt,
+        /// Scrolls to the next shell prompt.
+        ScrollToNextPrompt,
+        /// Copies the output of the last command that finished to the clipboard.
+        CopyLastCommandOutput,
         /// Toggles vi mode in the terminal.
         ToggleViMode,
         /// Selects all text in the terminal.
@@ -130,6 +145,8 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The task running in the terminal has finished, and all of its output is in the terminal.
+    TaskFinished,
 }
 
 #[derive(Clone, Debug)]
@@ -158,6 +175,7 @@ enum InternalEvent {
     // FocusNextMatch,
     Scroll(AlacScroll),
     ScrollToAlacPoint(AlacPoint),
+    ScrollToPrompt(AlacDirection),
     SetSelection(Option<(Selection, AlacPoint)>),
     UpdateSelection(Point<Pixels>),
     // Adjusted mouse position, should open
@@ -349,6 +367,8 @@ impl TerminalBuilder {
         cursor_shape: CursorShape,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        shell_integration: bool,
+        restored_scrollback: Option<&str>,
         is_ssh_terminal: bool,
         window: AnyWindowHandle,
         completion_tx: Sender<Option<ExitStatus>>,
@@ -371,6 +391,15 @@ impl TerminalBuilder {
             release_channel::AppVersion::global(cx).to_string(),
         );
 
+        let shell = if shell_integration && task.is_none() && !is_ssh_terminal {
+            shell_integration::inject(shell.clone(), &mut env)
+                .context("injecting shell integration")
+                .log_err()
+                .unwrap_or(shell)
+        } else {
+            shell
+        };
+
         let mut terminal_title_override = None;
 
         let pty_options = {
@@ -446,6 +475,11 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Replay the previous session's output before the shell gets to print anything.
+        if let Some(scrollback) = restored_scrollback.filter(|_| task.is_none()) {
+            scrollback::restore(&mut term, scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -465,12 +499,14 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let output = OutputTracker::new();
 
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
             ZedListener(events_tx.clone()),
-            pty,
+            // Images can't be read from files over SSH, which are on the remote host.
+            output.wrap_pty(pty, TerminalBounds::default().into(), !is_ssh_terminal),
             pty_options.drain_on_exit,
             false,
         )?;
@@ -501,6 +537,9 @@ impl TerminalBuilder {
             vi_mode_enabled: false,
             is_ssh_terminal,
             python_venv_directory,
+            output,
+            shell_integration: ShellIntegration::new(),
+            images: TerminalImages::default(),
         };
 
         Ok(TerminalBuilder {
@@ -601,6 +640,10 @@ pub struct TerminalContent {
     pub last_hovered_word: Option<HoveredWord>,
     pub scrolled_to_top: bool,
     pub scrolled_to_bottom: bool,
+    /// The prompts of commands visible in the terminal.
+    pub command_marks: Vec<CommandMark>,
+    /// The inline images visible in the terminal.
+    pub images: Vec<TerminalImage>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -627,6 +670,8 @@ impl Default for TerminalContent {
             last_hovered_word: None,
             scrolled_to_top: false,
             scrolled_to_bottom: false,
+            command_marks: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -659,6 +704,9 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    output: OutputTracker,
+    shell_integration: ShellIntegration,
+    images: TerminalImages,
 }
 
 pub struct TaskState {
@@ -724,7 +772,14 @@ impl Terminal {
                     .into_bytes(),
                 )
             }
-            AlacTermEvent::PtyWrite(out) => self.write_to_pty(out.into_bytes()),
+            AlacTermEvent::PtyWrite(mut out) => {
+                // Alacritty identifies as a VT102, which can't show Sixel images. Identify as a
+                // VT220 with Sixel graphics instead, so that programs know they can send images.
+                if out == "\x1b[?6c" {
+                    out = "\x1b[?62;4;22c".to_string();
+                }
+                self.write_to_pty(out.into_bytes())
+            }
             AlacTermEvent::TextAreaSizeRequest(format) => {
                 self.write_to_pty(format(self.last_content.terminal_bounds.into()).into_bytes())
             }
@@ -741,6 +796,7 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                self.sync_output(cx);
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -766,6 +822,38 @@ impl Terminal {
         }
     }
 
+    /// Places the shell integration marks and images read from the PTY since the last wakeup.
+    fn sync_output(&mut self, cx: &mut Context<Self>) {
+        // Lock fairly, so that everything read from the PTY was parsed before it's placed.
+        let term = self.term.lock();
+        let events = self.output.sync(&term, self.term_config.scrolling_history);
+        self.shell_integration.prune(&self.output);
+        self.images
+            .prune(&self.output, term.mode().contains(TermMode::ALT_SCREEN));
+        for placed in events {
+            match (placed.event, placed.point) {
+                (OutputEvent::Shell(mark), EventPoint::Output(point)) => {
+                    self.shell_integration.apply_mark(
+                        mark,
+                        point,
+                        placed.read_at,
+                        &self.output,
+                        |start, end| {
+                            term.bounds_to_string(start, AlacPoint::new(end, term.last_column()))
+                        },
+                    );
+                }
+                (OutputEvent::Shell(_), EventPoint::AltScreen(_)) => {}
+                (OutputEvent::Image(event), point) => self.images.apply(event, point),
+            }
+        }
+        drop(term);
+
+        for image in self.images.take_unused() {
+            cx.drop_image(image, None);
+        }
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -911,6 +999,29 @@ impl Terminal {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word(window);
             }
+            InternalEvent::ScrollToPrompt(direction) => {
+                let current_offset = term.grid().display_offset();
+                let top_line = Line(-(current_offset as i32));
+                let mut prompt_lines = self.shell_integration.prompt_lines(&self.output);
+                let display_offset = match direction {
+                    AlacDirection::Left => prompt_lines
+                        .filter(|line| *line < top_line)
+                        .last()
+                        .map(|line| -line.0),
+                    // Past the last prompt, scroll to the bottom.
+                    AlacDirection::Right => Some(
+                        prompt_lines
+                            .find(|line| *line > top_line)
+                            .map_or(0, |line| -line.0),
+                    ),
+                };
+                if let Some(display_offset) = display_offset {
+                    term.scroll_display(AlacScroll::Delta(
+                        display_offset.max(0) - current_offset as i32,
+                    ));
+                    self.refresh_hovered_word(window);
+                }
+            }
             InternalEvent::ToggleViMode => {
                 self.vi_mode_enabled = !self.vi_mode_enabled;
                 term.toggle_vi_mode();
@@ -1121,6 +1232,44 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Left));
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Right));
+    }
+
+    /// Returns the commands run in the terminal, oldest first, if the shell reports them through
+    /// shell integration.
+    pub fn command_blocks(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.shell_integration.blocks()
+    }
+
+    /// Returns the output of the last command that finished, if it's still in the terminal.
+    pub fn last_command_output(&self) -> Option<String> {
+        let (start, end) = self.shell_integration.last_output_range(&self.output)?;
+        let term = self.term.lock_unfair();
+        // The output ends right before where the command finished.
+        let end = if end.column.0 > 0 {
+            AlacPoint::new(end.line, Column(end.column.0 - 1))
+        } else {
+            AlacPoint::new(Line(end.line.0 - 1), term.last_column())
+        };
+        if end < start {
+            return Some(String::new());
+        }
+        Some(term.bounds_to_string(start, end).trim_end().to_string())
+    }
+
+    pub fn copy_last_command_output(&mut self, cx: &mut Context<Self>) {
+        if let Some(output) = self.last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
     pub fn scrolled_to_top(&self) -> bool {
         self.last_content.scrolled_to_top
     }
@@ -1302,11 +1451,25 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, window, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content = Self::make_content(
+            &terminal,
+            &self.last_content,
+            &self.output,
+            &self.shell_integration,
+            &self.images,
+        );
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        last_content: &TerminalContent,
+        output: &OutputTracker,
+        shell_integration: &ShellIntegration,
+        images: &TerminalImages,
+    ) -> TerminalContent {
         let content = term.renderable_content();
+        let top_line = -(content.display_offset as i32);
+        let visible_lines = top_line..top_line + term.screen_lines() as i32;
         TerminalContent {
             cells: content
                 .display_iter
@@ -1332,6 +1495,12 @@ impl Terminal {
             last_hovered_word: last_content.last_hovered_word.clone(),
             scrolled_to_top: content.display_offset == term.history_size(),
             scrolled_to_bottom: content.display_offset == 0,
+            command_marks: shell_integration.command_marks(output, visible_lines.clone()),
+            images: images.visible(
+                output,
+                content.mode.contains(TermMode::ALT_SCREEN),
+                visible_lines,
+            ),
         }
     }
 
@@ -1342,6 +1511,65 @@ impl Terminal {
         term.bounds_to_string(start, end)
     }
 
+    /// Returns the lines of output from the output line given on, each with the output line it
+    /// starts at, and the output line after them, for reading the output as it comes in.
+    ///
+    /// Lines dropped from the history are skipped. The line the cursor is on is left out, as it
+    /// may still be written to, unless `include_cursor_line` is set.
+    pub fn output_lines_since(
+        &mut self,
+        line: usize,
+        include_cursor_line: bool,
+        cx: &mut Context<Self>,
+    ) -> (Vec<(usize, String)>, usize) {
+        self.sync_output(cx);
+        let term = self.term.lock_unfair();
+        let grid = term.grid();
+        let topmost = grid.topmost_line().0;
+        // The output line of the top of the screen.
+        let screen_output_line = (self.output.evicted_lines() + term.history_size()) as i64;
+        let output_line = |row: i32| (screen_output_line + row as i64) as usize;
+
+        let start =
+            (line as i64 - screen_output_line).clamp(topmost as i64, i32::MAX as i64) as i32;
+        let cursor_line = grid.cursor.point.line.0;
+        let end = if include_cursor_line {
+            cursor_line + 1
+        } else {
+            self.find_logical_line_start(grid, cursor_line, topmost)
+        }
+        .max(start);
+
+        let mut lines = Vec::new();
+        let mut row = start;
+        while row < end {
+            let mut last_row = row;
+            while last_row + 1 < end
+                && grid[Line(last_row)][term.last_column()]
+                    .flags
+                    .contains(Flags::WRAPLINE)
+            {
+                last_row += 1;
+            }
+            let text = term.bounds_to_string(
+                AlacPoint::new(Line(row), Column(0)),
+                AlacPoint::new(Line(last_row), term.last_column()),
+            );
+            lines.push((output_line(row), text));
+            row = last_row + 1;
+        }
+        (lines, output_line(end))
+    }
+
+    /// Returns up to `max_lines` lines of output above the prompt, with their colors,
+    /// for restoring them in a new terminal with [`TerminalBuilder::new`].
+    pub fn serialize_scrollback(&self, max_lines: usize) -> Option<String> {
+        if self.task.is_some() {
+            return None;
+        }
+        scrollback::serialize(&self.term.lock_unfair(), max_lines)
+    }
+
     pub fn last_n_non_empty_lines(&self, n: usize) -> Vec<String> {
         let term = self.term.clone();
         let terminal = term.lock_unfair();
@@ -1836,6 +2064,7 @@ impl Terminal {
             // After the task summary is output once, no more text is appended to the terminal.
             unsafe { append_text_to_term(&mut self.term.lock(), &lines_to_show) };
         }
+        cx.emit(Event::TaskFinished);
 
         match task.hide {
             HideStrategy::Never => {}
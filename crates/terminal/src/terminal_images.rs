@@ -0,0 +1,865 @@
+//! Inline images, sent with the Sixel and Kitty graphics protocols.
+//!
+//! Alacritty supports neither protocol, so images are decoded from the PTY output by the output
+//! scanner, and drawn over the cells they cover. Their placements are anchored to lines of the
+//! terminal's output, so that they scroll with the text around them.
+
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{Cursor, Read as _, Seek as _, SeekFrom},
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use alacritty_terminal::index::Point as AlacPoint;
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::{
+    Engine as _, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use collections::HashMap;
+use gpui::RenderImage;
+use image::{Frame, ImageFormat, ImageReader, Limits, RgbaImage};
+
+use crate::output_scanner::{EventPoint, OutputPoint, OutputTracker};
+
+/// Wider or taller images are dropped.
+const MAX_IMAGE_SIDE: u32 = 10_000;
+/// The most bytes of pixel data kept for Kitty images that may be placed again, and for placed
+/// images. The oldest ones are dropped first.
+const MAX_IMAGE_BYTES: usize = 320 * 1024 * 1024;
+/// The most image placements kept per terminal.
+const MAX_PLACEMENTS: usize = 1000;
+/// The most lines an image is placed over.
+const MAX_IMAGE_LINES: f32 = 1000.;
+/// Larger Kitty transfers are dropped.
+const MAX_TRANSFER_LEN: usize = 64 * 1024 * 1024;
+
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// An image placed in the terminal output.
+#[derive(Clone)]
+pub(crate) struct ImagePlacement {
+    pub(crate) image: Arc<RenderImage>,
+    /// The id of the Kitty image placed, if it has one.
+    pub(crate) image_id: Option<u32>,
+    /// The width of the placement, in cells.
+    pub(crate) columns: f32,
+    /// The height of the placement, in lines.
+    pub(crate) lines: f32,
+}
+
+impl ImagePlacement {
+    /// Places an image at its size in pixels.
+    pub(crate) fn new(
+        image: Arc<RenderImage>,
+        image_id: Option<u32>,
+        (cell_width, cell_height): (f32, f32),
+    ) -> Self {
+        let size = image.size(0);
+        Self {
+            image,
+            image_id,
+            columns: size.width.0 as f32 / cell_width,
+            lines: (size.height.0 as f32 / cell_height).min(MAX_IMAGE_LINES),
+        }
+    }
+}
+
+pub(crate) enum ImageEvent {
+    Place(ImagePlacement),
+    /// Removes the placements of a Kitty image, or of every image.
+    Delete {
+        image_id: Option<u32>,
+    },
+}
+
+/// Converts decoded pixels to an image gpui can draw.
+pub(crate) fn render_image(mut image: RgbaImage) -> Arc<RenderImage> {
+    // Convert from RGBA to BGRA.
+    for pixel in image.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Arc::new(RenderImage::new(vec![Frame::new(image)]))
+}
+
+/// The VT340's default color registers, as percentages of red, green and blue.
+const SIXEL_PALETTE: [[u32; 3]; 16] = [
+    [0, 0, 0],
+    [20, 20, 80],
+    [80, 13, 13],
+    [20, 80, 20],
+    [80, 20, 80],
+    [20, 80, 80],
+    [80, 80, 20],
+    [53, 53, 53],
+    [26, 26, 26],
+    [33, 33, 60],
+    [60, 26, 26],
+    [33, 60, 33],
+    [60, 33, 60],
+    [33, 60, 60],
+    [60, 60, 33],
+    [80, 80, 80],
+];
+
+/// Decodes the data of a Sixel image, between the `q` that starts it and the string terminator.
+///
+/// Pixels that no sixel sets are left transparent, whatever the background parameter asks for.
+pub(crate) fn decode_sixel(data: &[u8]) -> Option<RgbaImage> {
+    let mut palette = [[0u8; 4]; 256];
+    for (register, color) in SIXEL_PALETTE.iter().enumerate() {
+        palette[register] = rgb_color(color[0], color[1], color[2]);
+    }
+    let mut color = palette[0];
+    let mut rows: Vec<Vec<[u8; 4]>> = Vec::new();
+    let (mut x, mut y) = (0usize, 0usize);
+    let (mut width, mut height) = (0usize, 0usize);
+    let max_side = MAX_IMAGE_SIDE as usize;
+
+    let mut bytes = data.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        let (sixel, count) = match byte {
+            // Raster attributes: aspect ratio and size.
+            b'"' => {
+                if let [_, _, raster_width, raster_height, ..] = sixel_params(&mut bytes)[..] {
+                    width = raster_width.min(max_side);
+                    height = raster_height.min(max_side);
+                }
+                continue;
+            }
+            // Selects a color register, and defines it when given a color.
+            b'#' => {
+                let params = sixel_params(&mut bytes);
+                let Some(register) = params.first().filter(|register| **register < 256) else {
+                    continue;
+                };
+                if let [_, space, a, b, c] = params[..] {
+                    match space {
+                        1 => palette[*register] = hls_color(a as u32, b as u32, c as u32),
+                        2 => palette[*register] = rgb_color(a as u32, b as u32, c as u32),
+                        _ => {}
+                    }
+                }
+                color = palette[*register];
+                continue;
+            }
+            b'!' => {
+                let count = sixel_params(&mut bytes).first().copied().unwrap_or(1);
+                match bytes.next_if(|byte| (0x3f..=0x7e).contains(byte)) {
+                    Some(sixel) => (sixel, count.max(1)),
+                    None => continue,
+                }
+            }
+            b'$' => {
+                x = 0;
+                continue;
+            }
+            b'-' => {
+                x = 0;
+                y = y.saturating_add(6);
+                continue;
+            }
+            0x3f..=0x7e => (byte, 1),
+            _ => continue,
+        };
+
+        let end = x.saturating_add(count).min(max_side);
+        let bits = sixel - 0x3f;
+        for bit in 0..6 {
+            let row = y + bit;
+            if bits & (1 << bit) == 0 || row >= max_side || x >= end {
+                continue;
+            }
+            if rows.len() <= row {
+                rows.resize_with(row + 1, Vec::new);
+            }
+            let row = &mut rows[row];
+            if row.len() < end {
+                row.resize(end, [0; 4]);
+            }
+            row[x..end].fill(color);
+        }
+        x = end;
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0).max(width);
+    let height = rows.len().max(height);
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let mut image = RgbaImage::new(width as u32, height as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            image.put_pixel(x as u32, y as u32, image::Rgba(*pixel));
+        }
+    }
+    Some(image)
+}
+
+/// Reads numeric parameters separated by semicolons, leaving the byte after them.
+fn sixel_params(bytes: &mut std::iter::Peekable<impl Iterator<Item = u8>>) -> Vec<usize> {
+    let mut params = vec![0usize];
+    while let Some(byte) = bytes.next_if(|byte| byte.is_ascii_digit() || *byte == b';') {
+        match byte {
+            b';' => params.push(0),
+            digit => {
+                let param = params.last_mut().unwrap();
+                *param = param
+                    .saturating_mul(10)
+                    .saturating_add((digit - b'0') as usize);
+            }
+        }
+    }
+    params
+}
+
+fn rgb_color(red: u32, green: u32, blue: u32) -> [u8; 4] {
+    let channel = |percent: u32| ((percent.min(100) * 255 + 50) / 100) as u8;
+    [channel(red), channel(green), channel(blue), 255]
+}
+
+/// Converts a Sixel HLS color, where hues start at blue rather than red.
+fn hls_color(hue: u32, lightness: u32, saturation: u32) -> [u8; 4] {
+    let hue = ((hue + 240) % 360) as f32 / 360.;
+    let lightness = lightness.min(100) as f32 / 100.;
+    let saturation = saturation.min(100) as f32 / 100.;
+    let q = if lightness < 0.5 {
+        lightness * (1. + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2. * lightness - q;
+    let channel = |t: f32| {
+        let t = t.rem_euclid(1.);
+        let value = if t < 1. / 6. {
+            p + (q - p) * 6. * t
+        } else if t < 1. / 2. {
+            q
+        } else if t < 2. / 3. {
+            p + (q - p) * (2. / 3. - t) * 6.
+        } else {
+            p
+        };
+        (value * 255.).round() as u8
+    };
+    [
+        channel(hue + 1. / 3.),
+        channel(hue),
+        channel(hue - 1. / 3.),
+        255,
+    ]
+}
+
+/// A Kitty graphics command, `ESC _ G <key>=<value>,... ; <payload> ESC \`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KittyCommand {
+    /// `a`: transmit, transmit and place, place, delete or query.
+    action: u8,
+    /// `f`: 24 or 32 bit pixels, or PNG.
+    format: u32,
+    /// `t`: sent directly, or as the path of a file or a temporary file.
+    medium: u8,
+    /// `o=z`: the data is compressed with zlib.
+    compressed: bool,
+    /// `s` and `v`: the size of the image in pixels, for raw pixels.
+    width: u32,
+    height: u32,
+    /// `S` and `O`: the size and offset of the data to read from a file.
+    file_size: u64,
+    file_offset: u64,
+    /// `i`: the id of the image, which it can be placed or deleted by later.
+    image_id: Option<u32>,
+    /// `c` and `r`: the size of the placement in cells and lines.
+    columns: u32,
+    rows: u32,
+    /// `C=1`: the cursor stays where it was after placing the image.
+    move_cursor: bool,
+    /// `d`: what to delete.
+    delete: u8,
+    /// `m=1`: more chunks of the payload follow.
+    more: bool,
+    /// `q`: 1 silences responses on success, 2 silences them on failure too.
+    quiet: u32,
+}
+
+impl Default for KittyCommand {
+    fn default() -> Self {
+        Self {
+            action: b't',
+            format: 32,
+            medium: b'd',
+            compressed: false,
+            width: 0,
+            height: 0,
+            file_size: 0,
+            file_offset: 0,
+            image_id: None,
+            columns: 0,
+            rows: 0,
+            move_cursor: true,
+            delete: b'a',
+            more: false,
+            quiet: 0,
+        }
+    }
+}
+
+impl KittyCommand {
+    /// Parses a command, returning it with its payload. Unknown keys are ignored.
+    fn parse(command: &[u8]) -> (Self, &[u8]) {
+        let (keys, payload) = match command.iter().position(|byte| *byte == b';') {
+            Some(index) => (&command[..index], &command[index + 1..]),
+            None => (command, &[][..]),
+        };
+        let mut parsed = Self::default();
+        for key_value in keys.split(|byte| *byte == b',') {
+            let [key, b'=', value @ ..] = key_value else {
+                continue;
+            };
+            let number = std::str::from_utf8(value)
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok());
+            let char = value.first().copied().unwrap_or_default();
+            match key {
+                b'a' => parsed.action = char,
+                b'f' => parsed.format = number.unwrap_or(parsed.format),
+                b't' => parsed.medium = char,
+                b'o' => parsed.compressed = char == b'z',
+                b's' => parsed.width = number.unwrap_or_default(),
+                b'v' => parsed.height = number.unwrap_or_default(),
+                b'S' => parsed.file_size = number.unwrap_or_default().into(),
+                b'O' => parsed.file_offset = number.unwrap_or_default().into(),
+                b'i' => parsed.image_id = number.filter(|id| *id > 0),
+                b'c' => parsed.columns = number.unwrap_or_default(),
+                b'r' => parsed.rows = number.unwrap_or_default(),
+                b'C' => parsed.move_cursor = number != Some(1),
+                b'd' => parsed.delete = char,
+                b'm' => parsed.more = number == Some(1),
+                b'q' => parsed.quiet = number.unwrap_or_default(),
+                _ => {}
+            }
+        }
+        (parsed, payload)
+    }
+
+    fn placement(
+        &self,
+        image: Arc<RenderImage>,
+        (cell_width, cell_height): (f32, f32),
+    ) -> ImagePlacement {
+        let mut placement = ImagePlacement::new(image, self.image_id, (cell_width, cell_height));
+        let aspect_ratio = placement.columns * cell_width / (placement.lines * cell_height);
+        match (self.columns, self.rows) {
+            (0, 0) => {}
+            (columns, 0) => {
+                placement.columns = columns as f32;
+                placement.lines = columns as f32 * cell_width / aspect_ratio / cell_height;
+            }
+            (0, rows) => {
+                placement.columns = rows as f32 * cell_height * aspect_ratio / cell_width;
+                placement.lines = rows as f32;
+            }
+            (columns, rows) => {
+                placement.columns = columns as f32;
+                placement.lines = rows as f32;
+            }
+        }
+        placement.lines = placement.lines.min(MAX_IMAGE_LINES);
+        placement
+    }
+}
+
+/// What running a Kitty graphics command led to.
+#[derive(Default)]
+pub(crate) struct KittyOutcome {
+    /// The response to write back to the PTY.
+    pub(crate) response: Option<Vec<u8>>,
+    pub(crate) event: Option<ImageEvent>,
+    pub(crate) move_cursor: bool,
+}
+
+/// The state of the Kitty graphics protocol: transmitted images, which may be placed later, and
+/// the chunked transfer in progress.
+#[derive(Default)]
+pub(crate) struct KittyGraphics {
+    images: HashMap<u32, Arc<RenderImage>>,
+    /// Image ids, from the oldest transmitted to the newest.
+    image_ids: VecDeque<u32>,
+    image_bytes: usize,
+    /// The first command of a chunked transfer, and the payload received so far.
+    transfer: Option<(KittyCommand, Vec<u8>)>,
+}
+
+impl KittyGraphics {
+    pub(crate) fn handle(
+        &mut self,
+        sequence: &[u8],
+        cell_size: (f32, f32),
+        allow_file_transmission: bool,
+    ) -> KittyOutcome {
+        let (command, payload) = KittyCommand::parse(sequence);
+        // Chunks after the first only tell whether more follow.
+        let (command, payload) = match self.transfer.take() {
+            Some((first, mut data)) => {
+                if data.len() + payload.len() > MAX_TRANSFER_LEN {
+                    return KittyOutcome::default();
+                }
+                data.extend_from_slice(payload);
+                if command.more {
+                    self.transfer = Some((first, data));
+                    return KittyOutcome::default();
+                }
+                (first, data)
+            }
+            None if command.more => {
+                self.transfer = Some((command, payload.to_vec()));
+                return KittyOutcome::default();
+            }
+            None => (command, payload.to_vec()),
+        };
+
+        let result = self.run(&command, &payload, cell_size, allow_file_transmission);
+        let response = command.image_id.and_then(|image_id| {
+            let message = match &result {
+                Ok(_) if command.quiet == 0 && command.action != b'd' => "OK".to_string(),
+                Err(error) if command.quiet < 2 => error.to_string(),
+                _ => return None,
+            };
+            Some(format!("\x1b_Gi={image_id};{message}\x1b\\").into_bytes())
+        });
+        KittyOutcome {
+            response,
+            event: result.ok().flatten(),
+            move_cursor: command.move_cursor,
+        }
+    }
+
+    fn run(
+        &mut self,
+        command: &KittyCommand,
+        payload: &[u8],
+        cell_size: (f32, f32),
+        allow_file_transmission: bool,
+    ) -> Result<Option<ImageEvent>> {
+        match command.action {
+            b't' | b'T' | b'q' => {
+                let image = decode_kitty_image(command, payload, allow_file_transmission)?;
+                if command.action == b'q' {
+                    return Ok(None);
+                }
+                let image = render_image(image);
+                if let Some(image_id) = command.image_id {
+                    self.store(image_id, image.clone());
+                }
+                Ok((command.action == b'T')
+                    .then(|| ImageEvent::Place(command.placement(image, cell_size))))
+            }
+            b'p' => {
+                let image_id = command.image_id.context("EINVAL:no image id")?;
+                let image = self
+                    .images
+                    .get(&image_id)
+                    .context("ENOENT:no such image")?
+                    .clone();
+                Ok(Some(ImageEvent::Place(command.placement(image, cell_size))))
+            }
+            b'd' => {
+                let image_id = match command.delete {
+                    b'a' | b'A' => None,
+                    b'i' | b'I' => Some(command.image_id.context("EINVAL:no image id")?),
+                    // Deleting by position isn't supported.
+                    _ => return Ok(None),
+                };
+                // Upper case targets delete the image data, not only its placements.
+                if command.delete.is_ascii_uppercase() {
+                    match image_id {
+                        Some(image_id) => self.remove(image_id),
+                        None => {
+                            self.images.clear();
+                            self.image_ids.clear();
+                            self.image_bytes = 0;
+                        }
+                    }
+                }
+                Ok(Some(ImageEvent::Delete { image_id }))
+            }
+            _ => bail!("EINVAL:unsupported action"),
+        }
+    }
+
+    fn store(&mut self, image_id: u32, image: Arc<RenderImage>) {
+        self.remove(image_id);
+        self.image_bytes += image_bytes(&image);
+        self.images.insert(image_id, image);
+        self.image_ids.push_back(image_id);
+        while self.image_bytes > MAX_IMAGE_BYTES
+            && let Some(oldest) = self.image_ids.front().copied()
+        {
+            self.remove(oldest);
+        }
+    }
+
+    fn remove(&mut self, image_id: u32) {
+        if let Some(image) = self.images.remove(&image_id) {
+            self.image_bytes -= image_bytes(&image);
+            self.image_ids.retain(|id| *id != image_id);
+        }
+    }
+}
+
+fn image_bytes(image: &RenderImage) -> usize {
+    let size = image.size(0);
+    size.width.0 as usize * size.height.0 as usize * 4
+}
+
+fn decode_kitty_image(
+    command: &KittyCommand,
+    payload: &[u8],
+    allow_file_transmission: bool,
+) -> Result<RgbaImage> {
+    let payload = BASE64
+        .decode(payload)
+        .map_err(|_| anyhow!("EINVAL:invalid base64"))?;
+    let data = match command.medium {
+        b'd' => payload,
+        b'f' | b't' if allow_file_transmission => read_image_file(command, &payload)?,
+        _ => bail!("EINVAL:unsupported transmission medium"),
+    };
+    let data = if command.compressed {
+        let mut decompressed = Vec::new();
+        flate2::read::ZlibDecoder::new(data.as_slice())
+            .take(MAX_TRANSFER_LEN as u64 + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(|_| anyhow!("EINVAL:invalid compressed data"))?;
+        if decompressed.len() > MAX_TRANSFER_LEN {
+            bail!("EFBIG:image too large");
+        }
+        decompressed
+    } else {
+        data
+    };
+
+    match command.format {
+        100 => {
+            let mut limits = Limits::default();
+            limits.max_image_width = Some(MAX_IMAGE_SIDE);
+            limits.max_image_height = Some(MAX_IMAGE_SIDE);
+            limits.max_alloc = Some(MAX_IMAGE_BYTES as u64);
+            let mut reader = ImageReader::with_format(Cursor::new(data), ImageFormat::Png);
+            reader.limits(limits);
+            let image = reader
+                .decode()
+                .map_err(|error| anyhow!("EBADPNG:{error}"))?;
+            Ok(image.into_rgba8())
+        }
+        format @ (24 | 32) => {
+            let (width, height) = (command.width, command.height);
+            if width == 0 || height == 0 || width > MAX_IMAGE_SIDE || height > MAX_IMAGE_SIDE {
+                bail!("EINVAL:invalid image size");
+            }
+            let channels = format as usize / 8;
+            let len = width as usize * height as usize * channels;
+            let pixels = data.get(..len).context("ENODATA:not enough pixel data")?;
+            let pixels = if channels == 4 {
+                pixels.to_vec()
+            } else {
+                pixels
+                    .chunks_exact(3)
+                    .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+                    .collect()
+            };
+            RgbaImage::from_raw(width, height, pixels).context("EINVAL:invalid image size")
+        }
+        _ => bail!("EINVAL:unsupported format"),
+    }
+}
+
+/// Reads an image from the file whose path was sent. Temporary files are deleted once read, if
+/// they look like they were written for this.
+fn read_image_file(command: &KittyCommand, path: &[u8]) -> Result<Vec<u8>> {
+    let path = PathBuf::from(std::str::from_utf8(path).context("EINVAL:invalid path")?);
+    let is_file = fs::metadata(&path).is_ok_and(|metadata| metadata.is_file());
+    if !is_file {
+        bail!("EBADF:not a file");
+    }
+    let mut file = fs::File::open(&path).map_err(|_| anyhow!("EBADF:cannot open file"))?;
+    let limit = match command.file_size {
+        0 => MAX_TRANSFER_LEN as u64,
+        size => size.min(MAX_TRANSFER_LEN as u64),
+    };
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(command.file_offset))
+        .and_then(|_| file.take(limit).read_to_end(&mut data))
+        .map_err(|_| anyhow!("EBADF:cannot read file"))?;
+
+    let in_temp_dir = [std::env::temp_dir(), "/tmp".into(), "/dev/shm".into()]
+        .iter()
+        .any(|dir| path.starts_with(dir));
+    if command.medium == b't'
+        && in_temp_dir
+        && path.to_string_lossy().contains("tty-graphics-protocol")
+    {
+        fs::remove_file(&path).ok();
+    }
+    Ok(data)
+}
+
+/// An image to draw over the terminal grid.
+#[derive(Clone)]
+pub struct TerminalImage {
+    pub image: Arc<RenderImage>,
+    /// The cell the top left corner of the image is drawn at.
+    pub origin: AlacPoint,
+    /// The width of the image, in cells.
+    pub columns: f32,
+    /// The height of the image, in lines.
+    pub lines: f32,
+}
+
+/// The images placed in a terminal.
+#[derive(Default)]
+pub(crate) struct TerminalImages {
+    placements: VecDeque<(OutputPoint, ImagePlacement)>,
+    alt_screen_placements: Vec<(AlacPoint, ImagePlacement)>,
+    /// Images that were removed, and may have to be freed.
+    removed: Vec<Arc<RenderImage>>,
+}
+
+impl TerminalImages {
+    pub(crate) fn apply(&mut self, event: ImageEvent, point: EventPoint) {
+        match (event, point) {
+            (ImageEvent::Place(placement), EventPoint::Output(point)) => {
+                self.placements.push_back((point, placement));
+                while self.placements.len() > MAX_PLACEMENTS
+                    || self.placed_bytes() > MAX_IMAGE_BYTES
+                {
+                    let Some((_, placement)) = self.placements.pop_front() else {
+                        break;
+                    };
+                    self.removed.push(placement.image);
+                }
+            }
+            (ImageEvent::Place(placement), EventPoint::AltScreen(point)) => {
+                self.alt_screen_placements.push((point, placement));
+            }
+            (ImageEvent::Delete { image_id }, _) => {
+                let deleted = |placement: &ImagePlacement| {
+                    image_id.is_none_or(|image_id| placement.image_id == Some(image_id))
+                };
+                let removed = &mut self.removed;
+                self.placements.retain(|(_, placement)| {
+                    let deleted = deleted(placement);
+                    if deleted {
+                        removed.push(placement.image.clone());
+                    }
+                    !deleted
+                });
+                self.alt_screen_placements.retain(|(_, placement)| {
+                    let deleted = deleted(placement);
+                    if deleted {
+                        removed.push(placement.image.clone());
+                    }
+                    !deleted
+                });
+            }
+        }
+    }
+
+    /// Removes the placements that scrolled out of the history, and those on the alternate screen
+    /// once the terminal left it.
+    pub(crate) fn prune(&mut self, tracker: &OutputTracker, alt_screen: bool) {
+        while let Some((point, placement)) = self.placements.front()
+            && point.line + (placement.lines.ceil() as usize) <= tracker.evicted_lines()
+        {
+            let (_, placement) = self.placements.pop_front().unwrap();
+            self.removed.push(placement.image);
+        }
+        if !alt_screen {
+            self.removed.extend(
+                self.alt_screen_placements
+                    .drain(..)
+                    .map(|(_, placement)| placement.image),
+            );
+        }
+    }
+
+    fn placed_bytes(&self) -> usize {
+        self.placements
+            .iter()
+            .map(|(_, placement)| image_bytes(&placement.image))
+            .sum()
+    }
+
+    /// Returns the removed images that no placement shows anymore.
+    pub(crate) fn take_unused(&mut self) -> Vec<Arc<RenderImage>> {
+        let mut unused = std::mem::take(&mut self.removed);
+        unused.sort_by_key(|image| image.id);
+        unused.dedup_by_key(|image| image.id);
+        unused.retain(|image| {
+            let placements = self
+                .placements
+                .iter()
+                .map(|(_, placement)| placement)
+                .chain(
+                    self.alt_screen_placements
+                        .iter()
+                        .map(|(_, placement)| placement),
+                );
+            !placements
+                .into_iter()
+                .any(|placement| placement.image.id == image.id)
+        });
+        unused
+    }
+
+    /// Returns the images that overlap the given lines.
+    pub(crate) fn visible(
+        &self,
+        tracker: &OutputTracker,
+        alt_screen: bool,
+        visible_lines: Range<i32>,
+    ) -> Vec<TerminalImage> {
+        let overlaps = |origin: AlacPoint, placement: &ImagePlacement| {
+            origin.line.0 < visible_lines.end
+                && origin.line.0 + placement.lines.ceil() as i32 > visible_lines.start
+        };
+        let image = |origin: AlacPoint, placement: &ImagePlacement| TerminalImage {
+            image: placement.image.clone(),
+            origin,
+            columns: placement.columns,
+            lines: placement.lines,
+        };
+        if alt_screen {
+            return self
+                .alt_screen_placements
+                .iter()
+                .filter(|(origin, placement)| overlaps(*origin, placement))
+                .map(|(origin, placement)| image(*origin, placement))
+                .collect();
+        }
+        self.placements
+            .iter()
+            .filter_map(|(point, placement)| {
+                // The top of an image may have been dropped from the history, while its bottom is
+                // still there.
+                let origin = AlacPoint::new(tracker.grid_line(*point)?, point.column);
+                overlaps(origin, placement).then(|| image(origin, placement))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_sixel() {
+        // Two columns: a red one of 6 pixels, then a blue one of 12, drawn over two bands.
+        let image = decode_sixel(b"#1;2;100;0;0~#2;2;0;0;100$?~-!1?~").unwrap();
+        assert_eq!(image.dimensions(), (2, 12));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 5).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(0, 6).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(1, 11).0, [0, 0, 255, 255]);
+
+        // Raster attributes give the size, even where nothing is drawn.
+        let image = decode_sixel(b"\"1;1;8;7#0@").unwrap();
+        assert_eq!(image.dimensions(), (8, 7));
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 0, 0]);
+
+        assert!(decode_sixel(b"#1;2;0;0;0").is_none());
+    }
+
+    #[test]
+    fn test_sixel_colors() {
+        assert_eq!(hls_color(120, 50, 100), [255, 0, 0, 255]);
+        assert_eq!(hls_color(240, 50, 100), [0, 255, 0, 255]);
+        assert_eq!(hls_color(0, 50, 100), [0, 0, 255, 255]);
+        assert_eq!(hls_color(0, 100, 0), [255, 255, 255, 255]);
+        assert_eq!(rgb_color(100, 50, 0), [255, 128, 0, 255]);
+    }
+
+    #[test]
+    fn test_parse_kitty_command() {
+        let (command, payload) = KittyCommand::parse(b"a=T,f=100,i=7,C=1,c=10,q=2,x=1;aGk=");
+        assert_eq!(
+            command,
+            KittyCommand {
+                action: b'T',
+                format: 100,
+                image_id: Some(7),
+                move_cursor: false,
+                columns: 10,
+                quiet: 2,
+                ..KittyCommand::default()
+            }
+        );
+        assert_eq!(payload, b"aGk=");
+
+        let (command, payload) = KittyCommand::parse(b"a=d,d=I,i=3");
+        assert_eq!(command.delete, b'I');
+        assert_eq!(command.image_id, Some(3));
+        assert!(payload.is_empty());
+    }
+
+    fn kitty_command(graphics: &mut KittyGraphics, command: &str) -> KittyOutcome {
+        graphics.handle(command.as_bytes(), (10., 20.), false)
+    }
+
+    #[test]
+    fn test_kitty_graphics() {
+        let mut graphics = KittyGraphics::default();
+        let pixels = BASE64.encode([255, 0, 0, 255].repeat(20 * 20));
+
+        // Transmitting doesn't place the image.
+        let outcome = kitty_command(&mut graphics, &format!("a=t,s=20,v=20,i=1;{pixels}"));
+        assert_eq!(
+            outcome.response.as_deref(),
+            Some(&b"\x1b_Gi=1;OK\x1b\\"[..])
+        );
+        assert!(outcome.event.is_none());
+
+        // Placing it later, over 4 columns.
+        let outcome = kitty_command(&mut graphics, "a=p,i=1,c=4,q=1");
+        assert!(outcome.response.is_none());
+        let Some(ImageEvent::Place(placement)) = outcome.event else {
+            panic!("expected a placement");
+        };
+        assert_eq!((placement.columns, placement.lines), (4., 2.));
+        assert_eq!(placement.image_id, Some(1));
+
+        let outcome = kitty_command(&mut graphics, "a=p,i=2");
+        assert_eq!(
+            outcome.response.as_deref(),
+            Some(&b"\x1b_Gi=2;ENOENT:no such image\x1b\\"[..])
+        );
+
+        // Queries check the data without keeping it.
+        let outcome = kitty_command(&mut graphics, "a=q,i=31,s=1,v=1,f=24;AAAA");
+        assert_eq!(
+            outcome.response.as_deref(),
+            Some(&b"\x1b_Gi=31;OK\x1b\\"[..])
+        );
+        let outcome = kitty_command(&mut graphics, "a=q,i=31,t=f;L3RtcC9h");
+        assert_eq!(
+            outcome.response.as_deref(),
+            Some(&b"\x1b_Gi=31;EINVAL:unsupported transmission medium\x1b\\"[..])
+        );
+
+        let outcome = kitty_command(&mut graphics, "a=d,d=I,i=1");
+        assert!(matches!(
+            outcome.event,
+            Some(ImageEvent::Delete { image_id: Some(1) })
+        ));
+        assert!(graphics.images.is_empty());
+        assert_eq!(graphics.image_bytes, 0);
+    }
+}
@@ -47,6 +47,8 @@ pub struct TerminalSettings {
     pub default_height: Pixels,
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
+    pub shell_integration: bool,
+    pub persist_scrollback: bool,
     pub toolbar: Toolbar,
     pub scrollbar: ScrollbarSettings,
 }
@@ -225,6 +227,19 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: 10_000
     pub max_scroll_history_lines: Option<usize>,
+    /// Whether to load Zed's shell integration into bash, zsh and fish when
+    /// spawning them. It marks prompts, commands and their output, enabling
+    /// jumping between prompts and copying the last command's output.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// Whether to save the terminals' scrollback, with its colors, when Zed
+    /// quits and show it above the new shell prompt when the workspace is
+    /// restored. At most `max_scroll_history_lines` lines are saved.
+    ///
+    /// Default: true
+    pub persist_scrollback: Option<bool>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar-related settings
@@ -250,6 +265,14 @@ impl settings::Settings for TerminalSettings {
         vscode.bool_setting(&name("copyOnSelection"), &mut current.copy_on_select);
         vscode.bool_setting("macOptionIsMeta", &mut current.option_as_meta);
         vscode.usize_setting("scrollback", &mut current.max_scroll_history_lines);
+        vscode.bool_setting(
+            &name("shellIntegration.enabled"),
+            &mut current.shell_integration,
+        );
+        vscode.bool_setting(
+            &name("enablePersistentSessions"),
+            &mut current.persist_scrollback,
+        );
         match vscode.read_bool(&name("cursorBlinking")) {
             Some(true) => current.blinking = Some(TerminalBlink::On),
             Some(false) => current.blinking = Some(TerminalBlink::Off),
@@ -1,6 +1,6 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, DispatchPhase, Element,
+    AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, Corners, DispatchPhase, Element,
     ElementId, Entity, FocusHandle, Font, FontStyle, FontWeight, GlobalElementId, HighlightStyle,
     Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity, IntoElement, LayoutId, Length,
     ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point, ShapedLine,
@@ -12,7 +12,7 @@ use itertools::Itertools;
 use language::CursorShape;
 use settings::Settings;
 use terminal::{
-    IndexedCell, Terminal, TerminalBounds, TerminalContent,
+    IndexedCell, Terminal, TerminalBounds, TerminalContent, TerminalImage,
     alacritty_terminal::{
         grid::Dimensions,
         index::Point as AlacPoint,
@@ -47,6 +47,10 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// Display lines of the prompts of commands that failed, marked in the gutter.
+    failed_command_lines: Vec<i32>,
+    /// Inline images, drawn over the cells they cover.
+    images: Vec<TerminalImage>,
     block_below_cursor_element: Option<AnyElement>,
     base_text_style: TextStyle,
     content_mode: ContentMode,
@@ -826,10 +830,18 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_marks,
+                    images,
                     ..
                 } = &self.terminal.read(cx).last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
+                let failed_command_lines = command_marks
+                    .iter()
+                    .filter(|mark| mark.exit_code.is_some_and(|exit_code| exit_code != 0))
+                    .map(|mark| mark.line.0 + display_offset as i32)
+                    .collect();
+                let images = images.clone();
 
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
@@ -972,6 +984,8 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    failed_command_lines,
+                    images,
                     block_below_cursor_element,
                     base_text_style: text_style,
                     content_mode,
@@ -1057,6 +1071,19 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, window);
                     }
 
+                    let failed_command_color = cx.theme().status().error;
+                    for line in &layout.failed_command_lines {
+                        let marker_origin = point(
+                            bounds.origin.x,
+                            origin.y + layout.dimensions.line_height * *line as f32,
+                        );
+                        let marker_size = size(layout.gutter / 2., layout.dimensions.line_height);
+                        window.paint_quad(fill(
+                            Bounds::new(marker_origin, marker_size),
+                            failed_command_color,
+                        ));
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -1078,6 +1105,28 @@ impl Element for TerminalElement {
                         cell.paint(origin, &layout.dimensions, bounds, window, cx);
                     }
 
+                    for image in &layout.images {
+                        let image_origin = point(
+                            origin.x + layout.dimensions.cell_width * image.origin.column.0 as f32,
+                            origin.y
+                                + layout.dimensions.line_height
+                                    * (image.origin.line.0 + layout.display_offset as i32) as f32,
+                        );
+                        let image_size = size(
+                            layout.dimensions.cell_width * image.columns,
+                            layout.dimensions.line_height * image.lines,
+                        );
+                        window
+                            .paint_image(
+                                Bounds::new(image_origin, image_size),
+                                Corners::default(),
+                                image.image.clone(),
+                                0,
+                                false,
+                            )
+                            .log_err();
+                    }
+
                     if let Some(text_to_mark) = &marked_text_cloned {
                         if !text_to_mark.is_empty() {
                             if let Some(cursor_layout) = &original_cursor {
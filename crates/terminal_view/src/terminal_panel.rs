@@ -435,7 +435,13 @@ impl TerminalPanel {
         let window_handle = window.window_handle();
         let terminal = project
             .update(cx, |project, cx| {
-                project.create_terminal_with_venv(kind, python_venv_directory, window_handle, cx)
+                project.create_terminal_with_venv(
+                    kind,
+                    python_venv_directory,
+                    None,
+                    window_handle,
+                    cx,
+                )
             })
             .ok()?;
 
@@ -19,9 +19,10 @@ use project::{Entry, Metadata, Project, search::SearchQuery, terminals::Terminal
 use schemars::JsonSchema;
 use task::TaskId;
 use terminal::{
-    Clear, Copy, Event, HoveredWord, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp,
-    ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskState,
-    TaskStatus, Terminal, TerminalBounds, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, HoveredWord, MAX_SCROLL_HISTORY_LINES,
+    MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp,
+    ScrollToBottom, ScrollToNextPrompt, ScrollToPreviousPrompt, ScrollToTop, ShowCharacterPalette,
+    TaskState, TaskStatus, Terminal, TerminalBounds, ToggleViMode,
     alacritty_terminal::{
         index::Point,
         term::{TermMode, search::RegexSearch},
@@ -63,6 +64,8 @@ use std::{
 };
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+/// How long the output has to stay unchanged before the scrollback is saved.
+const SCROLLBACK_SERIALIZATION_DEBOUNCE: Duration = Duration::from_secs(2);
 
 const GIT_DIFF_PATH_PREFIXES: &[&str] = &["a", "b"];
 
@@ -127,6 +130,8 @@ pub struct TerminalView {
     mode: TerminalMode,
     blinking_terminal_enabled: bool,
     cwd_serialized: bool,
+    scrollback_serialized: bool,
+    serialize_scrollback_task: Task<()>,
     blinking_paused: bool,
     blink_epoch: usize,
     hover: Option<HoverTarget>,
@@ -266,12 +271,15 @@ impl TerminalView {
             show_scrollbar: !Self::should_autohide_scrollbar(cx),
             hide_scrollbar_task: None,
             cwd_serialized: false,
+            scrollback_serialized: false,
+            serialize_scrollback_task: Task::ready(()),
             marked_text: None,
             marked_range_utf16: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                cx.on_app_quit(Self::save_scrollback),
             ],
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -427,6 +435,31 @@ impl TerminalView {
         self.context_menu = Some((context_menu, position, subscription));
     }
 
+    /// Saves the terminal's output, so it can be shown again when the workspace is restored.
+    fn save_scrollback(&mut self, cx: &mut Context<Self>) -> Task<()> {
+        let terminal = self.terminal.read(cx);
+        let Some(workspace_id) = self.workspace_id.filter(|_| terminal.task().is_none()) else {
+            return Task::ready(());
+        };
+        let settings = TerminalSettings::get_global(cx);
+        let scrollback = if settings.persist_scrollback {
+            let max_lines = settings
+                .max_scroll_history_lines
+                .unwrap_or(MAX_SCROLL_HISTORY_LINES)
+                .min(MAX_SCROLL_HISTORY_LINES);
+            terminal.serialize_scrollback(max_lines)
+        } else {
+            None
+        };
+        let item_id = cx.entity_id().as_u64();
+        cx.background_spawn(async move {
+            TERMINAL_DB
+                .save_scrollback(item_id, workspace_id, scrollback)
+                .await
+                .log_err();
+        })
+    }
+
     fn settings_changed(&mut self, cx: &mut Context<Self>) {
         let settings = TerminalSettings::get_global(cx);
         self.show_breadcrumbs = settings.toolbar.breadcrumbs;
@@ -614,6 +647,38 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(
+        &mut self,
+        _: &ScrollToNextPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(
+        &mut self,
+        _: &CopyLastCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, cx| term.copy_last_command_output(cx));
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, _: &mut Window, cx: &mut Context<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -1024,6 +1089,7 @@ fn subscribe_for_terminal_events(
 
             match event {
                 Event::Wakeup => {
+                    terminal_view.scrollback_serialized = false;
                     cx.notify();
                     cx.emit(Event::Wakeup);
                     cx.emit(ItemEvent::UpdateTab);
@@ -1201,6 +1267,7 @@ fn subscribe_for_terminal_events(
                     }
                 },
                 Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
+                Event::TaskFinished => cx.emit(ItemEvent::UpdateTab),
                 Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
                 Event::SelectionsChanged => {
                     window.invalidate_character_coordinates();
@@ -1519,6 +1586,9 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1672,6 +1742,7 @@ impl Item for TerminalView {
                 project.create_terminal_with_venv(
                     TerminalKind::Shell(working_directory),
                     python_venv_directory,
+                    None,
                     window_handle,
                     cx,
                 )
@@ -1775,15 +1846,35 @@ impl SerializableItem for TerminalView {
         &mut self,
         _workspace: &mut Workspace,
         item_id: workspace::ItemId,
-        _closing: bool,
+        closing: bool,
         _: &mut Window,
         cx: &mut Context<Self>,
     ) -> Option<Task<anyhow::Result<()>>> {
-        let terminal = self.terminal().read(cx);
-        if terminal.task().is_some() {
+        if self.terminal().read(cx).task().is_some() {
             return None;
         }
 
+        if !self.scrollback_serialized
+            && !closing
+            && TerminalSettings::get_global(cx).persist_scrollback
+        {
+            self.serialize_scrollback_task = cx.spawn(async move |terminal_view, cx| {
+                cx.background_executor()
+                    .timer(SCROLLBACK_SERIALIZATION_DEBOUNCE)
+                    .await;
+                if let Ok(task) =
+                    terminal_view.update(cx, |terminal_view, cx| terminal_view.save_scrollback(cx))
+                {
+                    task.await;
+                }
+            });
+        }
+        self.scrollback_serialized = true;
+
+        if self.cwd_serialized {
+            return None;
+        }
+        let terminal = self.terminal().read(cx);
         if let Some((cwd, workspace_id)) = terminal.working_directory().zip(self.workspace_id) {
             self.cwd_serialized = true;
             Some(cx.background_spawn(async move {
@@ -1797,7 +1888,7 @@ impl SerializableItem for TerminalView {
     }
 
     fn should_serialize(&self, _: &Self::Event) -> bool {
-        !self.cwd_serialized
+        !self.cwd_serialized || !self.scrollback_serialized
     }
 
     fn deserialize(
@@ -1810,6 +1901,20 @@ impl SerializableItem for TerminalView {
     ) -> Task<anyhow::Result<Entity<Self>>> {
         let window_handle = window.window_handle();
         window.spawn(cx, async move |cx| {
+            let scrollback = cx
+                .update(|_window, cx| {
+                    if TerminalSettings::get_global(cx).persist_scrollback {
+                        TERMINAL_DB
+                            .get_scrollback(item_id, workspace_id)
+                            .log_err()
+                            .flatten()
+                            .filter(|scrollback| !scrollback.is_empty())
+                    } else {
+                        None
+                    }
+                })
+                .ok()
+                .flatten();
             let cwd = cx
                 .update(|_window, cx| {
                     let from_db = TERMINAL_DB
@@ -1832,7 +1937,12 @@ impl SerializableItem for TerminalView {
 
             let terminal = project
                 .update(cx, |project, cx| {
-                    project.create_terminal(TerminalKind::Shell(cwd), window_handle, cx)
+                    project.create_terminal_with_scrollback(
+                        TerminalKind::Shell(cwd),
+                        scrollback,
+                        window_handle,
+                        cx,
+                    )
                 })?
                 .await?;
             cx.update(|window, cx| {
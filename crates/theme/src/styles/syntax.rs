@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use gpui::{HighlightStyle, Hsla};
+use gpui::{HighlightStyle, Hsla, StrikethroughStyle, px};
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct SyntaxTheme {
@@ -51,6 +51,57 @@ impl SyntaxTheme {
         Some(ix as u32)
     }
 
+    /// Returns the style for a semantic token reported by a language server.
+    ///
+    /// The token type is looked up as a syntax highlight name first, falling back to the
+    /// closest tree-sitter capture. Each modifier can then refine the style through a
+    /// `{name}.{modifier}` highlight, such as `variable.mutable`.
+    pub fn semantic_token_style(
+        &self,
+        token_type: &str,
+        modifiers: &[impl AsRef<str>],
+    ) -> Option<HighlightStyle> {
+        let fallback_name = match token_type {
+            "class" | "struct" | "interface" | "typeParameter" | "typeAlias" | "builtinType" => {
+                Some("type")
+            }
+            "enumMember" => Some("variant"),
+            "parameter" => Some("variable"),
+            "method" | "macro" => Some("function"),
+            "event" => Some("property"),
+            "decorator" => Some("attribute"),
+            "modifier" => Some("keyword"),
+            "regexp" => Some("string.regex"),
+            "selfKeyword" | "selfTypeKeyword" => Some("variable.special"),
+            "lifetime" => Some("label"),
+            _ => None,
+        };
+        let (name, mut style) = [Some(token_type), fallback_name]
+            .into_iter()
+            .flatten()
+            .find_map(|name| Some((name, self.style(name)?)))?;
+
+        for modifier in modifiers {
+            let modifier = modifier.as_ref();
+            if let Some(modifier_style) = self.style(&format!("{name}.{modifier}")) {
+                refine_style(&mut style, modifier_style);
+            } else if modifier == "deprecated" {
+                style.strikethrough = Some(StrikethroughStyle {
+                    thickness: px(1.),
+                    color: None,
+                });
+            }
+        }
+        Some(style)
+    }
+
+    fn style(&self, name: &str) -> Option<HighlightStyle> {
+        self.highlights
+            .iter()
+            .find(|(highlight_name, _)| highlight_name == name)
+            .map(|(_, style)| *style)
+    }
+
     /// Returns a new [`Arc<SyntaxTheme>`] with the given syntax styles merged in.
     pub fn merge(base: Arc<Self>, user_syntax_styles: Vec<(String, HighlightStyle)>) -> Arc<Self> {
         if user_syntax_styles.is_empty() {
@@ -64,18 +115,7 @@ impl SyntaxTheme {
                 .iter_mut()
                 .find(|(existing_name, _)| existing_name == &name)
             {
-                existing_highlight.color = highlight.color.or(existing_highlight.color);
-                existing_highlight.font_weight =
-                    highlight.font_weight.or(existing_highlight.font_weight);
-                existing_highlight.font_style =
-                    highlight.font_style.or(existing_highlight.font_style);
-                existing_highlight.background_color = highlight
-                    .background_color
-                    .or(existing_highlight.background_color);
-                existing_highlight.underline = highlight.underline.or(existing_highlight.underline);
-                existing_highlight.strikethrough =
-                    highlight.strikethrough.or(existing_highlight.strikethrough);
-                existing_highlight.fade_out = highlight.fade_out.or(existing_highlight.fade_out);
+                refine_style(existing_highlight, highlight);
             } else {
                 merged_highlights.push((name, highlight));
             }
@@ -87,6 +127,17 @@ impl SyntaxTheme {
     }
 }
 
+/// Overrides the properties of `style` that are set in `refinement`.
+fn refine_style(style: &mut HighlightStyle, refinement: HighlightStyle) {
+    style.color = refinement.color.or(style.color);
+    style.font_weight = refinement.font_weight.or(style.font_weight);
+    style.font_style = refinement.font_style.or(style.font_style);
+    style.background_color = refinement.background_color.or(style.background_color);
+    style.underline = refinement.underline.or(style.underline);
+    style.strikethrough = refinement.strikethrough.or(style.strikethrough);
+    style.fade_out = refinement.fade_out.or(style.fade_out);
+}
+
 #[cfg(test)]
 mod tests {
     use gpui::FontStyle;
@@ -194,4 +245,31 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn test_semantic_token_style() {
+        let syntax_theme = SyntaxTheme::new_test([
+            ("type", gpui::red()),
+            ("function", gpui::green()),
+            ("variable", gpui::blue()),
+            ("variable.mutable", gpui::yellow()),
+        ]);
+        let style = |token_type, modifiers: &[&str]| {
+            syntax_theme.semantic_token_style(token_type, modifiers)
+        };
+
+        assert_eq!(style("type", &[]).unwrap().color, Some(gpui::red()));
+        assert_eq!(style("struct", &[]).unwrap().color, Some(gpui::red()));
+        assert_eq!(style("method", &[]).unwrap().color, Some(gpui::green()));
+        assert_eq!(style("parameter", &[]).unwrap().color, Some(gpui::blue()));
+        assert_eq!(
+            style("variable", &["mutable"]).unwrap().color,
+            Some(gpui::yellow())
+        );
+        assert_eq!(style("namespace", &[]), None);
+
+        let deprecated = style("function", &["deprecated"]).unwrap();
+        assert_eq!(deprecated.color, Some(gpui::green()));
+        assert!(deprecated.strikethrough.is_some());
+    }
 }
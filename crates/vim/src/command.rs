@@ -1,12 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
 use command_palette_hooks::CommandInterceptResult;
 use editor::{
-    Bias, Editor, SelectionEffects, ToPoint,
+    Anchor, Bias, Editor, SelectionEffects, ToPoint,
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
-    display_map::ToDisplayPoint,
 };
-use gpui::{Action, App, AppContext as _, Context, Global, Window, actions};
+use gpui::{
+    Action, App, AppContext as _, AsyncWindowContext, Context, Entity, Global, Keystroke,
+    WeakEntity, Window, actions,
+};
 use itertools::Itertools;
 use language::Point;
 use multi_buffer::MultiBufferRow;
@@ -536,6 +538,14 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         action.run(vim, window, cx)
     });
 
+    Vim::action(editor, cx, |vim, action: &MoveLines, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &NormalCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
     })
@@ -741,6 +751,61 @@ impl VimCommand {
                     offset: Self::parse_offset(chars),
                 })
             }
+            '/' | '?' => {
+                // only treat this as an address if the pattern is terminated, so
+                // that :/foo still runs a search.
+                let mut lookahead = chars.clone();
+                let delimiter = lookahead.next()?;
+                let mut pattern = String::new();
+                let mut escaped = false;
+                let mut terminated = false;
+                while let Some(c) = lookahead.next() {
+                    if escaped {
+                        escaped = false;
+                        // unescape escaped parens
+                        if c != '(' && c != ')' && c != delimiter {
+                            pattern.push('\\')
+                        }
+                        pattern.push(c)
+                    } else if c == '\\' {
+                        escaped = true;
+                    } else if c == delimiter {
+                        terminated = true;
+                        break;
+                    } else {
+                        // escape unescaped parens
+                        if c == '(' || c == ')' {
+                            pattern.push('\\')
+                        }
+                        pattern.push(c)
+                    }
+                }
+                if !terminated {
+                    return None;
+                }
+                *chars = lookahead;
+                Some(Position::Search {
+                    pattern,
+                    backwards: delimiter == '?',
+                    offset: Self::parse_offset(chars),
+                })
+            }
+            '\\' => {
+                // \/ and \? search for the last used pattern
+                let mut lookahead = chars.clone();
+                lookahead.next();
+                let backwards = match lookahead.next()? {
+                    '/' => false,
+                    '?' => true,
+                    _ => return None,
+                };
+                *chars = lookahead;
+                Some(Position::Search {
+                    pattern: String::new(),
+                    backwards,
+                    offset: Self::parse_offset(chars),
+                })
+            }
             _ => None,
         }
     }
@@ -772,10 +837,25 @@ impl VimCommand {
 
 #[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq)]
 enum Position {
-    Line { row: u32, offset: i32 },
-    Mark { name: char, offset: i32 },
-    LastLine { offset: i32 },
-    CurrentLine { offset: i32 },
+    Line {
+        row: u32,
+        offset: i32,
+    },
+    Mark {
+        name: char,
+        offset: i32,
+    },
+    LastLine {
+        offset: i32,
+    },
+    CurrentLine {
+        offset: i32,
+    },
+    Search {
+        pattern: String,
+        backwards: bool,
+        offset: i32,
+    },
 }
 
 impl Position {
@@ -829,6 +909,49 @@ impl Position {
                 .to_point(&snapshot.buffer_snapshot)
                 .row
                 .saturating_add_signed(*offset),
+            Position::Search {
+                pattern,
+                backwards,
+                offset,
+            } => {
+                let pattern = if pattern.is_empty() {
+                    Vim::globals(cx)
+                        .registers
+                        .get(&'/')
+                        .map(|register| register.text.to_string())
+                        .context("no previous search pattern")?
+                } else {
+                    pattern.clone()
+                };
+                let regex = Regex::new(&pattern)?;
+                let buffer = &snapshot.buffer_snapshot;
+                let current_row = editor
+                    .selections
+                    .newest_anchor()
+                    .head()
+                    .to_point(buffer)
+                    .row;
+                // like vim with 'wrapscan', start on the next (or previous) line
+                // and wrap around the end of the buffer.
+                let row_count = buffer.max_row().0 + 1;
+                (1..=row_count)
+                    .map(|distance| {
+                        if *backwards {
+                            (current_row + row_count - distance) % row_count
+                        } else {
+                            (current_row + distance) % row_count
+                        }
+                    })
+                    .find(|row| {
+                        let line_end = Point::new(*row, buffer.line_len(MultiBufferRow(*row)));
+                        let line = buffer
+                            .text_for_range(Point::new(*row, 0)..line_end)
+                            .collect::<String>();
+                        regex.is_match(&line)
+                    })
+                    .with_context(|| format!("pattern not found: {pattern}"))?
+                    .saturating_add_signed(*offset)
+            }
         };
 
         Ok(MultiBufferRow(target).min(snapshot.buffer_snapshot.max_row()))
@@ -1244,6 +1367,10 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
         } else {
             None
         }
+    } else if query.starts_with("norm") {
+        NormalCommand::parse(query, range.clone())
+    } else if let Some(action) = MoveLines::parse(query, range.clone()) {
+        Some(action)
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else {
@@ -1430,74 +1557,420 @@ impl OnMatchingLines {
             });
         };
 
-        vim.update_editor(window, cx, |_, editor, window, cx| {
-            let snapshot = editor.snapshot(window, cx);
-            let mut row = range.start.0;
-
-            let point_range = Point::new(range.start.0, 0)
-                ..snapshot
-                    .buffer_snapshot
-                    .clip_point(Point::new(range.end.0 + 1, 0), Bias::Left);
-            cx.spawn_in(window, async move |editor, cx| {
-                let new_selections = cx
-                    .background_spawn(async move {
-                        let mut line = String::new();
-                        let mut new_selections = Vec::new();
-                        let chunks = snapshot
-                            .buffer_snapshot
-                            .text_for_range(point_range)
-                            .chain(["\n"]);
-
-                        for chunk in chunks {
-                            for (newline_ix, text) in chunk.split('\n').enumerate() {
-                                if newline_ix > 0 {
-                                    if regexes.iter().all(|(regex, should_match)| {
-                                        regex.is_match(&line) == *should_match
-                                    }) {
-                                        new_selections
-                                            .push(Point::new(row, 0).to_display_point(&snapshot))
-                                    }
-                                    row += 1;
-                                    line.clear();
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+        let snapshot = editor.update(cx, |editor, cx| editor.snapshot(window, cx));
+        let buffer_snapshot = snapshot.buffer_snapshot.clone();
+        let mut row = range.start.0;
+        let point_range = Point::new(range.start.0, 0)
+            ..snapshot
+                .buffer_snapshot
+                .clip_point(Point::new(range.end.0 + 1, 0), Bias::Left);
+
+        cx.spawn_in(window, async move |vim, cx| {
+            let matching_rows = cx
+                .background_spawn(async move {
+                    let mut line = String::new();
+                    let mut matching_rows = Vec::new();
+                    let chunks = snapshot
+                        .buffer_snapshot
+                        .text_for_range(point_range)
+                        .chain(["\n"]);
+
+                    for chunk in chunks {
+                        for (newline_ix, text) in chunk.split('\n').enumerate() {
+                            if newline_ix > 0 {
+                                if regexes.iter().all(|(regex, should_match)| {
+                                    regex.is_match(&line) == *should_match
+                                }) {
+                                    matching_rows.push(row)
                                 }
-                                line.push_str(text)
+                                row += 1;
+                                line.clear();
                             }
+                            line.push_str(text)
                         }
+                    }
 
-                        new_selections
-                    })
-                    .await;
+                    matching_rows
+                })
+                .await;
+
+            if matching_rows.is_empty() {
+                return anyhow::Ok(());
+            }
+
+            // Lines are tracked with anchors so that commands which insert or
+            // delete lines don't shift the remaining matches, as with vim's
+            // line marks.
+            let line_ranges = matching_rows
+                .into_iter()
+                .map(|row| {
+                    let end = Point::new(row, buffer_snapshot.line_len(MultiBufferRow(row)));
+                    buffer_snapshot.anchor_before(Point::new(row, 0))
+                        ..buffer_snapshot.anchor_after(end)
+                })
+                .collect::<Vec<_>>();
+
+            // Substitutions are applied through the search bar, which is
+            // asynchronous, so all matching lines are replaced in one pass.
+            if let Some(replace) = action.as_any().downcast_ref::<ReplaceCommand>() {
+                let replacement = replace.replacement.clone();
+                vim.update_in(cx, |vim, window, cx| {
+                    vim.replace_in_ranges(replacement, line_ranges, window, cx)
+                })?;
+                return anyhow::Ok(());
+            }
+
+            editor.update_in(cx, |editor, window, cx| {
+                editor.start_transaction_at(Instant::now(), window, cx);
+            })?;
+            // Each line gets its own update so that the dispatched action (and
+            // any actions it dispatches in turn) has run before the next line
+            // is visited.
+            if let Some(normal) = action.as_any().downcast_ref::<NormalCommand>() {
+                let positions = line_ranges.into_iter().map(|range| range.start).collect();
+                replay_keystrokes_at(&vim, &editor, &normal.keystrokes, positions, cx)?;
+            } else {
+                for line_range in line_ranges {
+                    editor.update_in(cx, |editor, window, cx| {
+                        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                            s.select_anchor_ranges([line_range.start..line_range.start]);
+                        });
+                        window.dispatch_action(action.boxed_clone(), cx);
+                    })?;
+                }
+            }
+            editor.update_in(cx, |editor, window, cx| {
+                let newest = editor.selections.newest::<Point>(cx).clone();
+                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.select(vec![newest]);
+                });
+                editor.end_transaction_at(Instant::now(), cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Moves lines below the given address (`:move`), or copies them there (`:copy`, `:t`).
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct MoveLines {
+    range: CommandRange,
+    address: Position,
+    copy: bool,
+}
+
+impl MoveLines {
+    // :m[ove] {address}, :co[py] {address} and :t {address}
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let (address, copy) = if let Some(address) = Self::strip_command(query, "m", "ove") {
+            (address, false)
+        } else if let Some(address) =
+            Self::strip_command(query, "co", "py").or_else(|| Self::strip_command(query, "t", ""))
+        {
+            (address, true)
+        } else {
+            return None;
+        };
+
+        let mut chars = address.trim().chars().peekable();
+        let address = VimCommand::parse_position(&mut chars)?;
+        if chars.next().is_some() {
+            return None;
+        }
+        let range = range.unwrap_or(CommandRange {
+            start: Position::CurrentLine { offset: 0 },
+            end: None,
+        });
+        Some(
+            MoveLines {
+                range,
+                address,
+                copy,
+            }
+            .boxed_clone(),
+        )
+    }
 
-                if new_selections.is_empty() {
+    fn strip_command<'a>(query: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+        let rest = query.strip_prefix(prefix)?;
+        let matched = rest
+            .chars()
+            .zip(suffix.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let rest = &rest[matched..];
+        if rest.starts_with(char::is_alphabetic) {
+            return None;
+        }
+        Some(rest)
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+            let range = self.range.buffer_range(vim, editor, window, cx)?;
+            // address 0 puts the lines above the first line
+            let destination = if self.address == (Position::Line { row: 0, offset: 0 }) {
+                None
+            } else {
+                Some(self.address.buffer_row(vim, editor, window, cx)?)
+            };
+            if !self.copy && destination.is_some_and(|row| row >= range.start && row < range.end) {
+                anyhow::bail!("cannot move a range of lines into itself");
+            }
+            anyhow::Ok((range, destination))
+        });
+
+        let (range, destination) = match result {
+            None => return,
+            Some(e @ Err(_)) => {
+                let Some(workspace) = vim.workspace(window) else {
                     return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+            Some(Ok(result)) => result,
+        };
+
+        let copy = self.copy;
+        vim.update_editor(window, cx, |_, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let line_count = range.end.0 - range.start.0 + 1;
+            let start = Point::new(range.start.0, 0);
+            let end = Point::new(range.end.0, snapshot.line_len(range.end));
+            let text = snapshot.text_for_range(start..end).collect::<String>();
+
+            let is_noop = !copy
+                && match destination {
+                    Some(row) => row == range.end || row.0 + 1 == range.start.0,
+                    None => range.start.0 == 0,
+                };
+            let last_row = if is_noop {
+                range.end.0
+            } else {
+                let (insert_at, new_text) = match destination {
+                    Some(row) => (
+                        Point::new(row.0, snapshot.line_len(row)),
+                        format!("\n{text}"),
+                    ),
+                    None => (Point::zero(), format!("{text}\n")),
+                };
+                let insert_anchor = snapshot.anchor_before(insert_at);
+                let mut edits = vec![(insert_at..insert_at, new_text)];
+                if !copy {
+                    // remove the lines along with one of their adjacent newlines
+                    let deletion = if range.end < snapshot.max_row() {
+                        start..Point::new(range.end.0 + 1, 0)
+                    } else if range.start.0 > 0 {
+                        let previous_row = MultiBufferRow(range.start.0 - 1);
+                        Point::new(previous_row.0, snapshot.line_len(previous_row))..end
+                    } else {
+                        start..end
+                    };
+                    edits.push((deletion, String::new()));
+                }
+
+                editor.transact(window, cx, |editor, _, cx| {
+                    editor.edit(edits, cx);
+                });
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                match destination {
+                    Some(_) => insert_anchor.to_point(&snapshot).row + line_count,
+                    None => line_count - 1,
                 }
+            };
+
+            // like vim, leave the cursor on the last line that was moved or copied
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let indent = snapshot.indent_size_for_line(MultiBufferRow(last_row)).len;
+            editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+                s.select_ranges([Point::new(last_row, indent)..Point::new(last_row, indent)]);
+            });
+        });
+    }
+}
+
+/// Executes normal mode keystrokes on each line of a range, or at each cursor.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct NormalCommand {
+    range: Option<CommandRange>,
+    keystrokes: Vec<Keystroke>,
+}
+
+impl NormalCommand {
+    // :norm[al][!] {commands}
+    // zed has no noremap distinction for keystrokes, so the ! is accepted but
+    // keystrokes are always resolved through the keymap.
+    pub fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let mut chars = query.strip_prefix("norm")?.chars().peekable();
+        let mut suffix = "al".chars().peekable();
+        while suffix.peek().is_some_and(|char| Some(char) == chars.peek()) {
+            suffix.next();
+            chars.next();
+        }
+        let bang = chars.next_if_eq(&'!').is_some();
+        let keys: String = chars.collect();
+        let keys = match keys.strip_prefix(' ') {
+            Some(keys) => keys,
+            None if bang => &keys,
+            None => return None,
+        };
+        let keystrokes = Self::parse_keystrokes(keys);
+        if keystrokes.is_empty() {
+            return None;
+        }
+        Some(NormalCommand { range, keystrokes }.boxed_clone())
+    }
+
+    // Characters are typed literally. As there is no :execute, special keys can
+    // be written with vim's key notation, e.g. <esc>, <cr> or <c-a>.
+    fn parse_keystrokes(keys: &str) -> Vec<Keystroke> {
+        let mut keystrokes = Vec::new();
+        let mut rest = keys;
+        while let Some(char) = rest.chars().next() {
+            if char == '<' {
+                if let Some((notation, after)) = rest[1..].split_once('>') {
+                    if let Some(keystroke) = Self::parse_key_notation(notation) {
+                        keystrokes.push(keystroke);
+                        rest = after;
+                        continue;
+                    }
+                }
+            }
+            let key = match char {
+                ' ' => "space".to_string(),
+                char => char.to_string(),
+            };
+            keystrokes.extend(Keystroke::parse(&key).log_err());
+            rest = &rest[char.len_utf8()..];
+        }
+        keystrokes
+    }
+
+    fn parse_key_notation(notation: &str) -> Option<Keystroke> {
+        let mut modifiers = String::new();
+        let mut key = notation;
+        while let Some((modifier, rest)) = key.split_once('-').filter(|(_, rest)| !rest.is_empty())
+        {
+            match modifier.to_ascii_lowercase().as_str() {
+                "c" => modifiers.push_str("ctrl-"),
+                "s" => modifiers.push_str("shift-"),
+                "a" | "m" => modifiers.push_str("alt-"),
+                "d" => modifiers.push_str("cmd-"),
+                _ => return None,
+            }
+            key = rest;
+        }
+        let key = match key.to_ascii_lowercase().as_str() {
+            "esc" => "escape".to_string(),
+            "cr" | "enter" | "return" => "enter".to_string(),
+            "bs" => "backspace".to_string(),
+            "del" => "delete".to_string(),
+            "lt" => "<".to_string(),
+            "bar" => "|".to_string(),
+            "bslash" => "\\".to_string(),
+            name @ ("tab" | "space" | "up" | "down" | "left" | "right" | "home" | "end"
+            | "pageup" | "pagedown") => name.to_string(),
+            _ if key.chars().count() == 1 && !modifiers.is_empty() => key.to_string(),
+            _ => return None,
+        };
+        Keystroke::parse(&(modifiers + &key)).ok()
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some(editor) = vim.editor() else {
+            return;
+        };
+        let positions = if let Some(range) = &self.range {
+            let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+                range.buffer_range(vim, editor, window, cx)
+            });
+            let range = match result {
+                None => return,
+                Some(e @ Err(_)) => {
+                    let Some(workspace) = vim.workspace(window) else {
+                        return;
+                    };
+                    workspace.update(cx, |workspace, cx| {
+                        e.notify_err(workspace, cx);
+                    });
+                    return;
+                }
+                Some(Ok(result)) => result,
+            };
+            let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+            (range.start.0..=range.end.0)
+                .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                .collect::<Vec<_>>()
+        } else {
+            // Without a range the keystrokes are run once for each cursor.
+            editor.update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
                 editor
-                    .update_in(cx, |editor, window, cx| {
-                        editor.start_transaction_at(Instant::now(), window, cx);
-                        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
-                            s.replace_cursors_with(|_| new_selections);
-                        });
-                        window.dispatch_action(action, cx);
-                        cx.defer_in(window, move |editor, window, cx| {
-                            let newest = editor.selections.newest::<Point>(cx).clone();
-                            editor.change_selections(
-                                SelectionEffects::no_scroll(),
-                                window,
-                                cx,
-                                |s| {
-                                    s.select(vec![newest]);
-                                },
-                            );
-                            editor.end_transaction_at(Instant::now(), cx);
-                        })
-                    })
-                    .ok();
+                    .selections
+                    .all::<Point>(cx)
+                    .into_iter()
+                    .map(|selection| snapshot.anchor_before(selection.head()))
+                    .collect::<Vec<_>>()
             })
-            .detach();
-        });
+        };
+        let keystrokes = self.keystrokes.clone();
+
+        cx.spawn_in(window, async move |vim, cx| {
+            editor.update_in(cx, |editor, window, cx| {
+                editor.start_transaction_at(Instant::now(), window, cx);
+            })?;
+            let result = replay_keystrokes_at(&vim, &editor, &keystrokes, positions, cx);
+            editor.update_in(cx, |editor, _, cx| {
+                editor.end_transaction_at(Instant::now(), cx);
+            })?;
+            result
+        })
+        .detach_and_log_err(cx);
     }
 }
 
+/// Replays the keystrokes with the cursor at each of the given positions in turn.
+fn replay_keystrokes_at(
+    vim: &WeakEntity<Vim>,
+    editor: &Entity<Editor>,
+    keystrokes: &[Keystroke],
+    positions: Vec<Anchor>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let vim = vim.upgrade().context("vim was dropped")?;
+    for position in positions {
+        editor.update_in(cx, |editor, window, cx| {
+            editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                s.select_anchor_ranges([position..position]);
+            });
+        })?;
+        // The replay (including any macros it runs) completes while effects are
+        // flushed at the end of this update.
+        vim.update_in(cx, |vim, window, cx| {
+            vim.replay_keystrokes(keystrokes.to_vec(), window, cx)
+        })?;
+        // As in vim, an incomplete command is aborted as though <esc> was typed.
+        cx.update(|window, cx| {
+            let vim = vim.read(cx);
+            if vim.active_operator().is_some()
+                || !matches!(vim.mode, Mode::Normal | Mode::HelixNormal)
+            {
+                window.dispatch_keystroke(Keystroke::parse("escape").unwrap(), cx);
+            }
+        })?;
+    }
+    Ok(())
+}
+
 /// Executes a shell command and returns the output.
 #[derive(Clone, Debug, PartialEq, Action)]
 #[action(namespace = vim, no_json, no_register)]
@@ -1702,6 +2175,7 @@ impl ShellExec {
                     show_summary: false,
                     show_command: false,
                     show_rerun: false,
+                    problem_matchers: Vec::new(),
                 };
 
                 let task_status = workspace.spawn_in_terminal(spawn_in_terminal, window, cx);
@@ -2203,6 +2677,233 @@ mod test {
             ˇa"});
     }
 
+    #[gpui::test]
+    async fn test_command_matching_lines_with_substitute(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇfoo one
+            bar one
+            foo one
+        "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / f o o / s / o n e / t w o");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(cx.buffer_text(), "foo two\nbar one\nfoo two\n");
+
+        cx.simulate_keystrokes(": v / f o o / d");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(cx.buffer_text(), "foo two\nfoo two\n");
+
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "foo two\nbar one\nfoo two\n");
+    }
+
+    #[gpui::test]
+    async fn test_move_and_copy_lines(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c
+            d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": m space + 2");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            b
+            c
+            ˇa
+            d"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": t .");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            b
+            c
+            a
+            ˇa
+            d"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": 1 , 2 c o $");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            b
+            c
+            a
+            a
+            d
+            b
+            ˇc"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": m 0");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            ˇc
+            b
+            c
+            a
+            a
+            d
+            b"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "b\nc\na\na\nd\nb\nc");
+    }
+
+    #[gpui::test]
+    async fn test_search_addresses(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            foo
+            two
+            bar
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": / f o o / + 1 d");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            one
+            foo
+            ˇbar
+            three"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": ? o n e ? m $");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            foo
+            bar
+            three
+            ˇone"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("/ b a r enter");
+        cx.simulate_keystrokes("g g : \\ / t 0");
+        cx.simulate_keystrokes("enter");
+        cx.assert_state(
+            indoc! {"
+            ˇbar
+            foo
+            bar
+            three
+            one"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_normal_command(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % n o r m space shift-a ;");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one;
+            two;
+            threeˇ;"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "one\ntwo\nthree");
+
+        cx.simulate_keystrokes(": 2 n o r m a l ! space shift-i x < e s c > shift-a y");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            one
+            xtwoˇy
+            three"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_normal_command_multi_cursor(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            tˇwo
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": n o r m space x");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ne
+            tˇo
+            three"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_normal_command_with_macro(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c
+            d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("q q shift-a ! escape q");
+        cx.simulate_keystrokes("j shift-v j :");
+        cx.simulate_keystrokes("n o r m space @ q");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(cx.buffer_text(), "a!\nb!\nc!\nd");
+
+        cx.simulate_keystrokes(": g / [ b d ] / n o r m space shift-i -");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        assert_eq!(cx.buffer_text(), "a!\n-b!\nc!\n-d");
+    }
+
     #[gpui::test]
     async fn test_del_marks(cx: &mut TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -1,21 +1,86 @@
-use editor::{DisplayPoint, Editor, movement};
+use editor::{
+    DisplayPoint, Editor, EditorSettings, SelectionEffects, ToOffset, display_map::ToDisplayPoint,
+    movement,
+};
 use gpui::{Action, actions};
 use gpui::{Context, Window};
-use language::{CharClassifier, CharKind};
+use language::{CharClassifier, CharKind, Point};
+use multi_buffer::MultiBufferRow;
+use regex::Regex;
+use search::{BufferSearchBar, SearchOptions, buffer_search};
+use settings::Settings;
 use text::SelectionGoal;
+use workspace::searchable::Direction;
 
-use crate::{Vim, motion::Motion, state::Mode};
+use crate::{
+    Vim,
+    motion::{Motion, MotionKind},
+    object::Object,
+    state::{Mode, Operator, SearchState},
+};
 
 actions!(
     vim,
     [
         /// Switches to normal mode after the cursor (Helix-style).
-        HelixNormalAfter
+        HelixNormalAfter,
+        /// Deletes the current selections (Helix-style).
+        HelixDelete,
+        /// Deletes the current selections and enters insert mode (Helix-style).
+        HelixChange,
+        /// Yanks the current selections without collapsing them (Helix-style).
+        HelixYank,
+        /// Extends the selections to cover whole lines, then selects the next line.
+        HelixSelectLine,
+        /// Extends the selections to cover whole lines.
+        HelixExtendToLineBounds,
+        /// Selects all regex matches within the current selections.
+        HelixSelectRegex,
+        /// Splits the current selections on regex matches.
+        HelixSplitSelection,
+        /// Collapses each selection onto its cursor.
+        HelixCollapseSelection,
+        /// Swaps the cursor and anchor of each selection.
+        HelixFlipSelections,
     ]
 );
 
+/// Determines what happens to the selections once a Helix regex prompt is submitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HelixRegexOp {
+    /// Replace each selection with the matches it contains (`s`).
+    Select,
+    /// Split each selection at the matches it contains (`S`).
+    Split,
+}
+
 pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
+    Vim::action(editor, cx, Vim::helix_delete);
+    Vim::action(editor, cx, Vim::helix_change);
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, |vim, _: &HelixSelectLine, window, cx| {
+        let times = Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        vim.helix_select_lines(true, times, window, cx);
+    });
+    Vim::action(
+        editor,
+        cx,
+        |vim, _: &HelixExtendToLineBounds, window, cx| {
+            Vim::take_count(cx);
+            Vim::take_forced_motion(cx);
+            vim.helix_select_lines(false, None, window, cx);
+        },
+    );
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, window, cx| {
+        vim.helix_regex_prompt(HelixRegexOp::Select, window, cx);
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, window, cx| {
+        vim.helix_regex_prompt(HelixRegexOp::Split, window, cx);
+    });
+    Vim::action(editor, cx, Vim::helix_collapse_selection);
+    Vim::action(editor, cx, Vim::helix_flip_selections);
 }
 
 impl Vim {
@@ -35,6 +100,319 @@ impl Vim {
         return;
     }
 
+    fn helix_delete(&mut self, _: &HelixDelete, window: &mut Window, cx: &mut Context<Self>) {
+        self.record_current_action(cx);
+        self.helix_delete_selections(window, cx);
+    }
+
+    fn helix_change(&mut self, _: &HelixChange, window: &mut Window, cx: &mut Context<Self>) {
+        self.start_recording(cx);
+        self.helix_delete_selections(window, cx);
+        self.switch_mode(Mode::Insert, true, window, cx);
+    }
+
+    /// Deletes every selection, treating a collapsed selection as the
+    /// character under the block cursor, and copies the text to the register.
+    fn helix_delete_selections(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_editor(window, cx, |vim, editor, window, cx| {
+            editor.transact(window, cx, |editor, window, cx| {
+                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.move_with(|map, selection| {
+                        if selection.is_empty() {
+                            selection.end = movement::right(map, selection.end)
+                        }
+                    })
+                });
+                vim.copy_selections_content(editor, MotionKind::Exclusive, window, cx);
+                editor.insert("", window, cx);
+            });
+        });
+    }
+
+    fn helix_yank(&mut self, _: &HelixYank, window: &mut Window, cx: &mut Context<Self>) {
+        Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        self.update_editor(window, cx, |vim, editor, window, cx| {
+            let (map, selections) = editor.selections.all_display(cx);
+            let ranges = selections
+                .into_iter()
+                .map(|selection| {
+                    let end = if selection.is_empty() {
+                        movement::right(&map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.start.to_point(&map)..end.to_point(&map)
+                })
+                .collect();
+            vim.copy_ranges(editor, MotionKind::Exclusive, true, ranges, window, cx);
+        });
+    }
+
+    /// Extends each selection to whole lines. When `extend_past_line_bounds` is
+    /// set and a selection already spans whole lines, the next `times` lines are
+    /// added to it instead, which is how repeated `x` presses grow a selection.
+    pub fn helix_select_lines(
+        &mut self,
+        extend_past_line_bounds: bool,
+        times: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let times = times.unwrap_or(1) as u32;
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+
+                    let mut last_row = if end.column == 0 && end.row > start.row {
+                        end.row - 1
+                    } else {
+                        end.row
+                    };
+                    let covers_whole_lines = start.column == 0
+                        && !selection.is_empty()
+                        && ((end.column == 0 && end.row > start.row) || end == max_point);
+
+                    if extend_past_line_bounds {
+                        last_row += if covers_whole_lines { times } else { times - 1 };
+                    }
+                    let last_row = last_row.min(max_point.row);
+
+                    let new_end = if last_row < max_point.row {
+                        Point::new(last_row + 1, 0)
+                    } else {
+                        Point::new(
+                            last_row,
+                            map.buffer_snapshot.line_len(MultiBufferRow(last_row)),
+                        )
+                    };
+
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = new_end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                });
+            });
+        });
+    }
+
+    fn helix_collapse_selection(
+        &mut self,
+        _: &HelixCollapseSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = if selection.is_empty() || selection.reversed {
+                        selection.head()
+                    } else {
+                        movement::left(map, selection.head())
+                    };
+                    selection.collapse_to(cursor, selection.goal);
+                });
+            });
+        });
+    }
+
+    fn helix_flip_selections(
+        &mut self,
+        _: &HelixFlipSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.move_with(|_, selection| selection.swap_head_tail());
+            });
+        });
+    }
+
+    /// Selects a text object around the cursor of each selection (`m i` / `m a`).
+    pub fn helix_object(
+        &mut self,
+        object: Object,
+        times: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(Operator::Object { around }) = self.active_operator() else {
+            return;
+        };
+        self.pop_operator(window, cx);
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = if selection.is_empty() || selection.reversed {
+                        selection.head()
+                    } else {
+                        movement::left(map, selection.head())
+                    };
+                    let mut cursor_selection = selection.clone();
+                    cursor_selection.collapse_to(cursor, selection.goal);
+
+                    if let Some(range) = object.range(map, cursor_selection, around, times) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                            selection.goal = SelectionGoal::None;
+                        }
+                    }
+                });
+            });
+        });
+    }
+
+    /// Opens the buffer search bar in regex mode so the user can type the
+    /// pattern for `s` or `S`. The selections at this point are remembered and
+    /// used as the search scope once the query is submitted.
+    fn helix_regex_prompt(
+        &mut self,
+        op: HelixRegexOp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        let prior_selections = self.editor_selections(window, cx);
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(window, cx) {
+                        return;
+                    }
+
+                    search_bar.select_query(window, cx);
+                    cx.focus_self(window);
+
+                    search_bar.set_replacement(None, cx);
+                    let mut options = SearchOptions::REGEX;
+                    if EditorSettings::get_global(cx).search.case_sensitive {
+                        options |= SearchOptions::CASE_SENSITIVE;
+                    }
+                    search_bar.set_search_options(options, cx);
+
+                    self.search = SearchState {
+                        direction: Direction::Next,
+                        count: 1,
+                        prior_selections,
+                        prior_operator: None,
+                        prior_mode: self.mode,
+                        helix_regex: Some(op),
+                    }
+                });
+            }
+        })
+    }
+
+    /// Forgets the pending `s` or `S` once its prompt was dismissed without being
+    /// submitted, so that a later `/` search doesn't split the selections.
+    pub(crate) fn clear_dismissed_helix_regex(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.search.helix_regex.is_none() {
+            return;
+        }
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let dismissed = pane
+            .read(cx)
+            .toolbar()
+            .read(cx)
+            .item_of_type::<BufferSearchBar>()
+            .is_none_or(|search_bar| search_bar.read(cx).is_dismissed());
+        if dismissed {
+            self.search = SearchState::default();
+        }
+    }
+
+    pub(crate) fn helix_regex_submit(
+        &mut self,
+        op: HelixRegexOp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            search_bar.update(cx, |search_bar, cx| {
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&buffer_search::Dismiss, window, cx);
+                Some(query)
+            })
+        });
+        let prior_selections: Vec<_> = self.search.prior_selections.drain(..).collect();
+        let Some(query) = query else {
+            return;
+        };
+
+        self.update_editor(window, cx, |_, editor, window, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let regex = match Regex::new(&query) {
+                Ok(regex) => Some(regex),
+                Err(error) => {
+                    log::info!("invalid helix selection regex {query:?}: {error}");
+                    None
+                }
+            };
+
+            let mut ranges = Vec::new();
+            if let Some(regex) = regex.filter(|_| !query.is_empty()) {
+                for selection in &prior_selections {
+                    let mut start = selection.start.to_offset(&snapshot);
+                    let mut end = selection.end.to_offset(&snapshot);
+                    if start > end {
+                        std::mem::swap(&mut start, &mut end);
+                    }
+                    let text = snapshot.text_for_range(start..end).collect::<String>();
+                    match op {
+                        HelixRegexOp::Select => {
+                            ranges.extend(
+                                regex
+                                    .find_iter(&text)
+                                    .filter(|found| !found.is_empty())
+                                    .map(|found| start + found.start()..start + found.end()),
+                            );
+                        }
+                        HelixRegexOp::Split => {
+                            let mut piece_start = start;
+                            for found in regex.find_iter(&text) {
+                                if start + found.start() > piece_start {
+                                    ranges.push(piece_start..start + found.start());
+                                }
+                                piece_start = start + found.end();
+                            }
+                            if piece_start < end {
+                                ranges.push(piece_start..end);
+                            }
+                        }
+                    }
+                }
+            }
+
+            editor.change_selections(Default::default(), window, cx, |s| {
+                if ranges.is_empty() {
+                    s.select_anchor_ranges(prior_selections)
+                } else {
+                    s.select_ranges(ranges)
+                }
+            });
+        });
+    }
+
     pub fn helix_normal_motion(
         &mut self,
         motion: Motion,
@@ -305,7 +683,7 @@ impl Vim {
 mod test {
     use indoc::indoc;
 
-    use crate::{state::Mode, test::VimTestContext};
+    use crate::{VimAddon, state::Mode, test::VimTestContext};
 
     #[gpui::test]
     async fn test_word_motions(cx: &mut gpui::TestAppContext) {
@@ -368,85 +746,249 @@ mod test {
         cx.assert_state("aa\n«ˇ  »bb", Mode::HelixNormal);
     }
 
-    // #[gpui::test]
-    // async fn test_delete(cx: &mut gpui::TestAppContext) {
-    //     let mut cx = VimTestContext::new(cx, true).await;
-
-    //     // test delete a selection
-    //     cx.set_state(
-    //         indoc! {"
-    //         The qu«ick ˇ»brown
-    //         fox jumps over
-    //         the lazy dog."},
-    //         Mode::HelixNormal,
-    //     );
-
-    //     cx.simulate_keystrokes("d");
-
-    //     cx.assert_state(
-    //         indoc! {"
-    //         The quˇbrown
-    //         fox jumps over
-    //         the lazy dog."},
-    //         Mode::HelixNormal,
-    //     );
-
-    //     // test deleting a single character
-    //     cx.simulate_keystrokes("d");
-
-    //     cx.assert_state(
-    //         indoc! {"
-    //         The quˇrown
-    //         fox jumps over
-    //         the lazy dog."},
-    //         Mode::HelixNormal,
-    //     );
-    // }
-
-    // #[gpui::test]
-    // async fn test_delete_character_end_of_line(cx: &mut gpui::TestAppContext) {
-    //     let mut cx = VimTestContext::new(cx, true).await;
-
-    //     cx.set_state(
-    //         indoc! {"
-    //         The quick brownˇ
-    //         fox jumps over
-    //         the lazy dog."},
-    //         Mode::HelixNormal,
-    //     );
-
-    //     cx.simulate_keystrokes("d");
-
-    //     cx.assert_state(
-    //         indoc! {"
-    //         The quick brownˇfox jumps over
-    //         the lazy dog."},
-    //         Mode::HelixNormal,
-    //     );
-    // }
-
-    // #[gpui::test]
-    // async fn test_delete_character_end_of_buffer(cx: &mut gpui::TestAppContext) {
-    //     let mut cx = VimTestContext::new(cx, true).await;
-
-    //     cx.set_state(
-    //         indoc! {"
-    //         The quick brown
-    //         fox jumps over
-    //         the lazy dog.ˇ"},
-    //         Mode::HelixNormal,
-    //     );
-
-    //     cx.simulate_keystrokes("d");
-
-    //     cx.assert_state(
-    //         indoc! {"
-    //         The quick brown
-    //         fox jumps over
-    //         the lazy dog.ˇ"},
-    //         Mode::HelixNormal,
-    //     );
-    // }
+    #[gpui::test]
+    async fn test_delete(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // test delete a selection
+        cx.set_state(
+            indoc! {"
+            The qu«ick ˇ»brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("d");
+
+        cx.assert_state(
+            indoc! {"
+            The quˇbrown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        // test deleting a single character
+        cx.simulate_keystrokes("d");
+
+        cx.assert_state(
+            indoc! {"
+            The quˇrown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_delete_character_end_of_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The quick brownˇ
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("d");
+
+        cx.assert_state(
+            indoc! {"
+            The quick brownˇfox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_delete_character_end_of_buffer(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The quick brown
+            fox jumps over
+            the lazy dog.ˇ"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("d");
+
+        cx.assert_state(
+            indoc! {"
+            The quick brown
+            fox jumps over
+            the lazy dog.ˇ"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_change_and_yank(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The «quickˇ» brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("y");
+
+        cx.assert_state(
+            indoc! {"
+            The «quickˇ» brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        assert_eq!(
+            cx.read_from_clipboard()
+                .map(|item| item.text().unwrap().to_string()),
+            Some("quick".into())
+        );
+
+        cx.simulate_keystrokes("c");
+
+        cx.assert_state(
+            indoc! {"
+            The ˇ brown
+            fox jumps over
+            the lazy dog."},
+            Mode::Insert,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            the lazy dog.ˇ»"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            The qu«ick brown
+            fox juˇ»mps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("shift-x");
+
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_select_and_split_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            «The quick brown
+            fox jumps overˇ»
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("s o");
+        cx.simulate_keystrokes("enter");
+
+        cx.assert_state(
+            indoc! {"
+            The quick br«oˇ»wn
+            f«oˇ»x jumps «oˇ»ver
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state("«one, two, threeˇ»", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("shift-s , space");
+        cx.simulate_keystrokes("enter");
+
+        cx.assert_state("«oneˇ», «twoˇ», «threeˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_regex_dismissed(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            «The quick brown
+            fox jumps overˇ»
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("s o");
+        cx.simulate_keystrokes("escape");
+        cx.run_until_parked();
+        cx.update_editor(|editor, _, cx| {
+            let vim = editor.addon::<VimAddon>().unwrap().entity.read(cx);
+            assert!(vim.search.helix_regex.is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_match_mode_text_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("fn main(foo, bˇar) {}", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("fn main(foo, «barˇ») {}", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("fn main«(foo, bar)ˇ» {}", Mode::HelixNormal);
+    }
 
     #[gpui::test]
     async fn test_f_and_t(cx: &mut gpui::TestAppContext) {
@@ -62,8 +62,6 @@ actions!(
         DeleteLeft,
         /// Deletes character to the right.
         DeleteRight,
-        /// Deletes using Helix-style behavior.
-        HelixDelete,
         /// Changes from cursor to end of line.
         ChangeToEndOfLine,
         /// Deletes from cursor to end of line.
@@ -126,20 +124,6 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         vim.delete_motion(Motion::Right, times, forced_motion, window, cx);
     });
 
-    Vim::action(editor, cx, |vim, _: &HelixDelete, window, cx| {
-        vim.record_current_action(cx);
-        vim.update_editor(window, cx, |_, editor, window, cx| {
-            editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
-                s.move_with(|map, selection| {
-                    if selection.is_empty() {
-                        selection.end = movement::right(map, selection.end)
-                    }
-                })
-            })
-        });
-        vim.visual_delete(false, window, cx);
-    });
-
     Vim::action(editor, cx, |vim, _: &ChangeToEndOfLine, window, cx| {
         vim.start_recording(cx);
         let times = Vim::take_count(cx);
@@ -8,7 +8,7 @@ use crate::{
     state::{Mode, Operator, RecordedSelection, ReplayableAction, VimGlobals},
 };
 use editor::Editor;
-use gpui::{Action, App, Context, Window, actions};
+use gpui::{Action, App, Context, Keystroke, Window, actions};
 use workspace::Workspace;
 
 actions!(
@@ -52,7 +52,7 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
                 None
             }
         }
-        ReplayableAction::Insertion { .. } => None,
+        ReplayableAction::Insertion { .. } | ReplayableAction::Keystroke(_) => None,
     }
 }
 
@@ -156,6 +156,9 @@ impl Replayer {
                     editor.replay_insert_event(&text, utf16_range_to_replace.clone(), window, cx)
                 })
             }
+            ReplayableAction::Keystroke(keystroke) => {
+                window.dispatch_keystroke(keystroke, cx);
+            }
         }
         window.defer(cx, move |window, cx| self.next(window, cx));
     }
@@ -207,6 +210,25 @@ impl Vim {
         replayer.replay(repeated_actions, window, cx);
     }
 
+    /// Feeds keystrokes through the keymap as though they were typed, as used by `:normal`.
+    ///
+    /// Nested replays (such as `@q`) are spliced in at the current position, so
+    /// the keystrokes (and anything they trigger) have all run once effects are flushed.
+    pub(crate) fn replay_keystrokes(
+        &mut self,
+        keystrokes: Vec<Keystroke>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let actions = keystrokes
+            .into_iter()
+            .map(ReplayableAction::Keystroke)
+            .collect();
+        let globals = Vim::globals(cx);
+        let mut replayer = globals.replayer.get_or_insert_with(Replayer::new).clone();
+        replayer.replay(actions, window, cx);
+    }
+
     pub(crate) fn repeat(
         &mut self,
         from_insert_mode: bool,
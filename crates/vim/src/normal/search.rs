@@ -1,11 +1,11 @@
-use editor::{Editor, EditorSettings};
+use editor::{Anchor, Editor, EditorSettings};
 use gpui::{Action, Context, Window, actions};
 use language::Point;
 use schemars::JsonSchema;
 use search::{BufferSearchBar, SearchOptions, buffer_search};
 use serde_derive::Deserialize;
 use settings::Settings;
-use std::{iter::Peekable, str::Chars};
+use std::{iter::Peekable, ops::Range, str::Chars};
 use util::serde::default_true;
 use workspace::{notifications::NotifyResultExt, searchable::Direction};
 
@@ -193,6 +193,7 @@ impl Vim {
                         prior_selections,
                         prior_operator: self.operator_stack.last().cloned(),
                         prior_mode,
+                        helix_regex: None,
                     }
                 });
             }
@@ -206,6 +207,10 @@ impl Vim {
     }
 
     pub fn search_submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(op) = self.search.helix_regex.take() {
+            self.helix_regex_submit(op, window, cx);
+            return;
+        }
         self.store_visual_marks(window, cx);
         let Some(pane) = self.pane(window, cx) else {
             return;
@@ -447,27 +452,45 @@ impl Vim {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let replacement = action.replacement.clone();
-        let Some(((pane, workspace), editor)) = self
-            .pane(window, cx)
-            .zip(self.workspace(window))
-            .zip(self.editor())
-        else {
-            return;
-        };
-        if let Some(result) = self.update_editor(window, cx, |vim, editor, window, cx| {
+        let result = self.update_editor(window, cx, |vim, editor, window, cx| {
             let range = action.range.buffer_range(vim, editor, window, cx)?;
             let snapshot = &editor.snapshot(window, cx).buffer_snapshot;
             let end_point = Point::new(range.end.0, snapshot.line_len(range.end));
-            let range = snapshot.anchor_before(Point::new(range.start.0, 0))
-                ..snapshot.anchor_after(end_point);
-            editor.set_search_within_ranges(&[range], cx);
-            anyhow::Ok(())
-        }) {
-            workspace.update(cx, |workspace, cx| {
-                result.notify_err(workspace, cx);
-            })
-        }
+            anyhow::Ok(
+                snapshot.anchor_before(Point::new(range.start.0, 0))
+                    ..snapshot.anchor_after(end_point),
+            )
+        });
+        let range = match result {
+            None => return,
+            Some(e @ Err(_)) => {
+                let Some(workspace) = self.workspace(window) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+            Some(Ok(range)) => range,
+        };
+        self.replace_in_ranges(action.replacement.clone(), vec![range], window, cx);
+    }
+
+    /// Runs a substitution restricted to the given buffer ranges.
+    pub(crate) fn replace_in_ranges(
+        &mut self,
+        replacement: Replacement,
+        ranges: Vec<Range<Anchor>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((pane, editor)) = self.pane(window, cx).zip(self.editor()) else {
+            return;
+        };
+        editor.update(cx, |editor, cx| {
+            editor.set_search_within_ranges(&ranges, cx)
+        });
         let vim = cx.entity().clone();
         pane.update(cx, |pane, cx| {
             let mut options = SearchOptions::REGEX;
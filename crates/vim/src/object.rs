@@ -403,7 +403,14 @@ impl Vim {
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                 self.visual_object(object, count, window, cx)
             }
-            Mode::Insert | Mode::Replace | Mode::HelixNormal => {
+            Mode::HelixNormal => {
+                if matches!(self.active_operator(), Some(Operator::Object { .. })) {
+                    self.helix_object(object, count, window, cx)
+                } else {
+                    self.normal_object(object, count, window, cx)
+                }
+            }
+            Mode::Insert | Mode::Replace => {
                 // Shouldn't execute a text object in insert mode. Ignoring
             }
         }
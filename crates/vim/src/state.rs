@@ -1,4 +1,5 @@
 use crate::command::command_interceptor;
+use crate::helix::HelixRegexOp;
 use crate::motion::MotionKind;
 use crate::normal::repeat::Replayer;
 use crate::surrounds::SurroundsType;
@@ -13,7 +14,8 @@ use editor::display_map::{is_invisible, replacement};
 use editor::{Anchor, ClipboardSelection, Editor, MultiBuffer, ToPoint as EditorToPoint};
 use gpui::{
     Action, App, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, DismissEvent, Entity,
-    EntityId, Global, HighlightStyle, StyledText, Subscription, Task, TextStyle, WeakEntity,
+    EntityId, Global, HighlightStyle, Keystroke, StyledText, Subscription, Task, TextStyle,
+    WeakEntity,
 };
 use language::{Buffer, BufferEvent, BufferId, Chunk, Point};
 use multi_buffer::MultiBufferRow;
@@ -958,6 +960,7 @@ pub enum ReplayableAction {
         text: Arc<str>,
         utf16_range_to_replace: Option<Range<isize>>,
     },
+    Keystroke(Keystroke),
 }
 
 impl Clone for ReplayableAction {
@@ -971,6 +974,7 @@ impl Clone for ReplayableAction {
                 text: text.clone(),
                 utf16_range_to_replace: utf16_range_to_replace.clone(),
             },
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
         }
     }
 }
@@ -983,6 +987,7 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    pub helix_regex: Option<HelixRegexOp>,
 }
 
 impl Operator {
@@ -1043,7 +1048,9 @@ impl Operator {
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode == Mode::HelixNormal
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
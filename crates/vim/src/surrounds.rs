@@ -118,7 +118,11 @@ impl Vim {
                 });
             });
         });
-        self.switch_mode(Mode::Normal, false, window, cx);
+        if mode == Mode::HelixNormal {
+            self.switch_mode(Mode::HelixNormal, false, window, cx);
+        } else {
+            self.switch_mode(Mode::Normal, false, window, cx);
+        }
     }
 
     pub fn delete_surrounds(
@@ -1223,6 +1223,9 @@ impl Vim {
             }
         }
 
+        // Dismissing the search bar focuses the editor again.
+        self.clear_dismissed_helix_regex(window, cx);
+
         cx.emit(VimEvent::Focused);
         self.sync_vim_settings(window, cx);
 
@@ -1660,14 +1663,14 @@ impl Vim {
                         self.clear_operator(window, cx);
                     }
                 }
-                Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
+                Mode::Visual | Mode::VisualLine | Mode::VisualBlock | Mode::HelixNormal => {
                     self.add_surrounds(text, SurroundsType::Selection, window, cx);
                     self.clear_operator(window, cx);
                 }
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, window, cx);
                         self.clear_operator(window, cx);
@@ -1676,7 +1679,7 @@ impl Vim {
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     self.delete_surrounds(text, window, cx);
                     self.clear_operator(window, cx);
                 }
@@ -1,14 +1,30 @@
-use std::process::ExitStatus;
+use std::{
+    cell::RefCell,
+    process::{ExitStatus, Stdio},
+    rc::Rc,
+    sync::Arc,
+};
 
-use anyhow::Result;
-use gpui::{AppContext, Context, Entity, Task};
+use anyhow::{Context as _, Result, anyhow, bail};
+use collections::HashMap;
+use futures::{
+    FutureExt as _, TryFutureExt as _,
+    future::{LocalBoxFuture, Shared},
+};
+use gpui::{AppContext, AsyncWindowContext, Context, Entity, Task, WeakEntity};
 use language::Buffer;
-use project::{TaskSourceKind, WorktreeId};
+use project::{ResolvedTaskTree, TaskSourceKind, WorktreeId};
 use remote::ConnectionState;
-use task::{DebugScenario, ResolvedTask, SpawnInTerminal, TaskContext, TaskTemplate};
+use task::{
+    CommandInput, DebugScenario, DependsOrder, ResolvedTask, ShellBuilder, SpawnInTerminal,
+    TaskContext, TaskId, TaskInputKind, TaskTemplate,
+};
 use ui::Window;
 
-use crate::Workspace;
+use crate::{
+    Workspace,
+    notifications::{NotifyResultExt as _, NotifyTaskExt as _},
+};
 
 impl Workspace {
     pub fn schedule_task(
@@ -54,7 +70,60 @@ impl Workspace {
         window: &mut Window,
         cx: &mut Context<Workspace>,
     ) {
-        let spawn_in_terminal = resolved_task.resolved.clone();
+        let task_tree = if resolved_task.original_task().depends_on.is_empty() {
+            Ok(ResolvedTaskTree {
+                source_kind: task_source_kind,
+                task: resolved_task,
+                dependencies: Vec::new(),
+            })
+        } else {
+            self.project
+                .read(cx)
+                .task_store()
+                .read(cx)
+                .task_inventory()
+                .cloned()
+                .context("Cannot resolve task dependencies without a task inventory")
+                .and_then(|task_inventory| {
+                    task_inventory
+                        .read(cx)
+                        .resolve_task_dependencies(task_source_kind, resolved_task)
+                })
+        };
+        let Some(task_tree) = task_tree.notify_err(self, cx) else {
+            return;
+        };
+
+        if task_tree_tasks(&task_tree).any(|task| !task.original_task().inputs.is_empty()) {
+            let task_tree = self.resolve_task_inputs(task_tree, window, cx);
+            cx.spawn_in(window, async move |workspace, cx| {
+                if let Some(task_tree) = task_tree.await? {
+                    workspace.update_in(cx, |workspace, window, cx| {
+                        workspace.schedule_task_tree(task_tree, omit_history, window, cx)
+                    })?;
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_notify_err(window, cx);
+        } else {
+            self.schedule_task_tree(task_tree, omit_history, window, cx);
+        }
+    }
+
+    /// Runs the dependencies of the task first, in the order the task specifies,
+    /// and spawns the task itself only if all of them have succeeded.
+    fn schedule_task_tree(
+        &mut self,
+        task_tree: ResolvedTaskTree,
+        omit_history: bool,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let ResolvedTaskTree {
+            source_kind,
+            task,
+            dependencies,
+        } = task_tree;
         if !omit_history {
             if let Some(debugger_provider) = self.debugger_provider.as_ref() {
                 debugger_provider.task_scheduled(cx);
@@ -65,12 +134,138 @@ impl Workspace {
                     project.task_store().read(cx).task_inventory().cloned()
                 {
                     task_inventory.update(cx, |inventory, _| {
-                        inventory.task_scheduled(task_source_kind, resolved_task);
+                        inventory.task_scheduled(source_kind, task.clone());
                     })
                 }
             });
         }
 
+        if dependencies.is_empty() {
+            self.spawn_scheduled_task(task.resolved, window, cx);
+            return;
+        }
+        let depends_order = task.original_task().depends_order;
+        cx.spawn_in(window, async move |workspace, cx| {
+            run_task_trees(
+                workspace.clone(),
+                dependencies,
+                depends_order,
+                TaskRuns::default(),
+                cx.clone(),
+            )
+            .await?;
+            if task.resolved.command.is_some() {
+                workspace.update_in(cx, |workspace, window, cx| {
+                    workspace.spawn_scheduled_task(task.resolved, window, cx)
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_notify_err(window, cx);
+    }
+
+    /// Gets the values of the `${input:id}` references in the tasks of the tree, and substitutes them.
+    /// Every input is evaluated once, even if several tasks reference it.
+    /// Resolves to `None` if any of the prompts was dismissed.
+    fn resolve_task_inputs(
+        &mut self,
+        mut task_tree: ResolvedTaskTree,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Task<Result<Option<ResolvedTaskTree>>> {
+        let is_local = self.project.read(cx).is_local();
+        let task_inventory = self
+            .project
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .cloned();
+        cx.spawn_in(window, async move |workspace, cx| {
+            let task_inventory =
+                task_inventory.context("Cannot resolve task inputs without a task inventory")?;
+            let mut answers = HashMap::default();
+            for (source_kind, task) in task_tree_tasks_mut(&mut task_tree) {
+                let inputs = task
+                    .referenced_inputs()?
+                    .into_iter()
+                    .filter(|input| !answers.contains_key(&input.id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for input in inputs {
+                    let answer = match &input.kind {
+                        TaskInputKind::Command(command_input) => {
+                            anyhow::ensure!(
+                                is_local,
+                                "Task input `{}` cannot run commands in a remote project",
+                                input.id
+                            );
+                            let command = match command_input {
+                                CommandInput::Shell { command, args } => SpawnInTerminal {
+                                    command: Some(command.clone()),
+                                    args: args.clone(),
+                                    command_label: command.clone(),
+                                    ..task.resolved.clone()
+                                },
+                                CommandInput::Task { task: label } => {
+                                    let (_, input_task) =
+                                        task_inventory.read_with(cx, |inventory, _| {
+                                            inventory.resolve_task_by_label(
+                                                source_kind,
+                                                label,
+                                                task.task_context(),
+                                            )
+                                        })??;
+                                    input_task.resolved
+                                }
+                            };
+                            cx.background_spawn(command_output(command))
+                                .await
+                                .with_context(|| format!("evaluating task input `{}`", input.id))?
+                        }
+                        TaskInputKind::PromptString { .. } | TaskInputKind::PickString { .. } => {
+                            let previous_answer =
+                                task_inventory.read_with(cx, |inventory, _| {
+                                    inventory
+                                        .task_input_answer(&input.id)
+                                        .map(ToOwned::to_owned)
+                                })?;
+                            let prompt = workspace.update_in(cx, |workspace, window, cx| {
+                                let provider = workspace
+                                    .task_input_provider
+                                    .clone()
+                                    .context("Cannot ask for task inputs in this workspace")?;
+                                anyhow::Ok(provider.prompt(
+                                    input.clone(),
+                                    previous_answer,
+                                    cx.weak_entity(),
+                                    window,
+                                    cx,
+                                ))
+                            })??;
+                            let Some(answer) = prompt.await else {
+                                return Ok(None);
+                            };
+                            task_inventory.update(cx, |inventory, _| {
+                                inventory.task_input_answered(input.id.clone(), answer.clone())
+                            })?;
+                            answer
+                        }
+                    };
+                    answers.insert(input.id, answer);
+                }
+                task.substitute_inputs(&answers);
+            }
+            Ok(Some(task_tree))
+        })
+    }
+
+    fn spawn_scheduled_task(
+        &mut self,
+        spawn_in_terminal: SpawnInTerminal,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
         if let Some(terminal_provider) = self.terminal_provider.as_ref() {
             let task_status = terminal_provider.spawn(spawn_in_terminal, window, cx);
             cx.background_spawn(async move {
@@ -124,3 +319,220 @@ impl Workspace {
         }
     }
 }
+
+fn task_tree_tasks(task_tree: &ResolvedTaskTree) -> impl Iterator<Item = &ResolvedTask> {
+    let mut stack = vec![task_tree];
+    std::iter::from_fn(move || {
+        let task_tree = stack.pop()?;
+        stack.extend(task_tree.dependencies.iter().rev());
+        Some(&task_tree.task)
+    })
+}
+
+/// Tasks of the tree along with their sources, the task itself first, then its dependencies, depth-first.
+fn task_tree_tasks_mut(
+    task_tree: &mut ResolvedTaskTree,
+) -> Vec<(&TaskSourceKind, &mut ResolvedTask)> {
+    let mut tasks = Vec::new();
+    let mut stack = vec![task_tree];
+    while let Some(task_tree) = stack.pop() {
+        tasks.push((&task_tree.source_kind, &mut task_tree.task));
+        stack.extend(task_tree.dependencies.iter_mut().rev());
+    }
+    tasks
+}
+
+/// Runs the command of the task without a terminal, and returns its output without the trailing newlines.
+async fn command_output(spawn_in_terminal: SpawnInTerminal) -> Result<String> {
+    let command_label = spawn_in_terminal.command_label;
+    let command = spawn_in_terminal
+        .command
+        .with_context(|| format!("Task `{}` has no command", spawn_in_terminal.label))?;
+    let (program, args) = ShellBuilder::new(true, &spawn_in_terminal.shell)
+        .non_interactive()
+        .build(Some(command), &spawn_in_terminal.args);
+    let mut command = util::command::new_smol_command(program);
+    command
+        .args(args)
+        .envs(spawn_in_terminal.env)
+        .stdin(Stdio::null());
+    if let Some(cwd) = spawn_in_terminal.cwd {
+        command.current_dir(cwd);
+    }
+    let output = command
+        .output()
+        .await
+        .with_context(|| format!("spawning `{command_label}`"))?;
+    if !output.status.success() {
+        bail!(
+            "`{command_label}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end_matches(['\n', '\r'])
+        .to_string())
+}
+
+/// The runs of the tasks of a tree, by task id, so that a task that several others
+/// depend on runs only once.
+type TaskRuns =
+    Rc<RefCell<HashMap<TaskId, Shared<LocalBoxFuture<'static, Result<(), Arc<anyhow::Error>>>>>>>;
+
+fn run_task_trees(
+    workspace: WeakEntity<Workspace>,
+    task_trees: Vec<ResolvedTaskTree>,
+    depends_order: DependsOrder,
+    runs: TaskRuns,
+    cx: AsyncWindowContext,
+) -> LocalBoxFuture<'static, Result<()>> {
+    async move {
+        let tree_runs = task_trees
+            .into_iter()
+            .map(|task_tree| run_task_tree(workspace.clone(), task_tree, runs.clone(), cx.clone()));
+        match depends_order {
+            DependsOrder::Sequence => {
+                for run in tree_runs {
+                    run.await?;
+                }
+            }
+            DependsOrder::Parallel => {
+                futures::future::try_join_all(tree_runs).await?;
+            }
+        }
+        Ok(())
+    }
+    .boxed_local()
+}
+
+/// Runs the task after its own dependencies, and waits for it to finish successfully.
+/// A task that several tasks of the tree depend on is run by the first of them, the others wait for that run.
+fn run_task_tree(
+    workspace: WeakEntity<Workspace>,
+    task_tree: ResolvedTaskTree,
+    runs: TaskRuns,
+    cx: AsyncWindowContext,
+) -> impl Future<Output = Result<()>> + use<> {
+    let run = runs
+        .borrow_mut()
+        .entry(task_tree.task.id.clone())
+        .or_insert_with(|| {
+            spawn_task_tree(workspace, task_tree, runs.clone(), cx)
+                .map_err(Arc::new)
+                .boxed_local()
+                .shared()
+        })
+        .clone();
+    async move { run.await.map_err(|e| anyhow!("{e:#}")) }
+}
+
+async fn spawn_task_tree(
+    workspace: WeakEntity<Workspace>,
+    task_tree: ResolvedTaskTree,
+    runs: TaskRuns,
+    mut cx: AsyncWindowContext,
+) -> Result<()> {
+    let ResolvedTaskTree {
+        task, dependencies, ..
+    } = task_tree;
+    let depends_order = task.original_task().depends_order;
+    run_task_trees(
+        workspace.clone(),
+        dependencies,
+        depends_order,
+        runs,
+        cx.clone(),
+    )
+    .await?;
+    if task.resolved.command.is_none() {
+        return Ok(());
+    }
+
+    let label = task.resolved.label.clone();
+    let task_status = workspace
+        .update_in(&mut cx, |workspace, window, cx| {
+            workspace.spawn_in_terminal(task.resolved, window, cx)
+        })?
+        .await;
+    match task_status {
+        Some(Ok(status)) if status.success() => Ok(()),
+        Some(Ok(status)) => match status.code() {
+            Some(code) => bail!("Task `{label}` failed with exit code {code}"),
+            None => bail!("Task `{label}` was terminated"),
+        },
+        Some(Err(e)) => Err(e.context(format!("spawning task `{label}`"))),
+        None => bail!("Task `{label}` was cancelled"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::FakeFs;
+    use gpui::{App, TestAppContext};
+    use project::Project;
+
+    use super::*;
+    use crate::{TerminalProvider, tests::init_test};
+
+    struct TestTerminalProvider {
+        spawned_labels: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl TerminalProvider for TestTerminalProvider {
+        fn spawn(
+            &self,
+            task: SpawnInTerminal,
+            _: &mut Window,
+            _: &mut App,
+        ) -> Task<Option<Result<ExitStatus>>> {
+            self.spawned_labels.borrow_mut().push(task.label);
+            Task::ready(Some(Ok(ExitStatus::default())))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_shared_dependency_runs_once(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+        let spawned_labels = Rc::new(RefCell::new(Vec::new()));
+        workspace.update(cx, |workspace, _| {
+            workspace.set_terminal_provider(TestTerminalProvider {
+                spawned_labels: spawned_labels.clone(),
+            })
+        });
+
+        let task_tree = |label: &str, dependencies: Vec<ResolvedTaskTree>| {
+            let template = TaskTemplate {
+                label: label.to_string(),
+                command: "echo".to_string(),
+                args: vec![label.to_string()],
+                ..TaskTemplate::default()
+            };
+            ResolvedTaskTree {
+                source_kind: TaskSourceKind::UserInput,
+                task: template
+                    .resolve_task("test", &TaskContext::default())
+                    .unwrap(),
+                dependencies,
+            }
+        };
+        // Both `test` and `lint` depend on `build`.
+        let build = task_tree("build", Vec::new());
+        let all = task_tree(
+            "all",
+            vec![
+                task_tree("test", vec![build.clone()]),
+                task_tree("lint", vec![build]),
+            ],
+        );
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.schedule_task_tree(all, true, window, cx)
+        });
+        cx.run_until_parked();
+
+        assert_eq!(*spawned_labels.borrow(), ["build", "test", "lint", "all"]);
+    }
+}
@@ -29,6 +29,7 @@ use gpui::{
     TitlebarOptions, UpdateGlobal, Window, WindowKind, WindowOptions, actions, image_cache, point,
     px, retain_all,
 };
+use hierarchy_panel::HierarchyPanel;
 use image_viewer::ImageInfo;
 use language_tools::lsp_tool::{self, LspTool};
 use migrate::{MigrationBanner, MigrationEvent, MigrationNotification, MigrationType};
@@ -492,6 +493,7 @@ fn initialize_panels(
     cx.spawn_in(window, async move |workspace_handle, cx| {
         let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
         let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+        let hierarchy_panel = HierarchyPanel::load(workspace_handle.clone(), cx.clone());
         let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
         let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
         let channels_panel =
@@ -503,36 +505,43 @@ fn initialize_panels(
             cx.clone(),
         );
         let debug_panel = DebugPanel::load(workspace_handle.clone(), cx);
+        let jj_panel = jj_ui::jj_panel::JujutsuPanel::load(workspace_handle.clone(), cx.clone());
 
         let (
             project_panel,
             outline_panel,
+            hierarchy_panel,
             terminal_panel,
             git_panel,
             channels_panel,
             chat_panel,
             notification_panel,
             debug_panel,
+            jj_panel,
         ) = futures::try_join!(
             project_panel,
             outline_panel,
+            hierarchy_panel,
             git_panel,
             terminal_panel,
             channels_panel,
             chat_panel,
             notification_panel,
             debug_panel,
+            jj_panel,
         )?;
 
         workspace_handle.update_in(cx, |workspace, window, cx| {
             workspace.add_panel(project_panel, window, cx);
             workspace.add_panel(outline_panel, window, cx);
+            workspace.add_panel(hierarchy_panel, window, cx);
             workspace.add_panel(terminal_panel, window, cx);
             workspace.add_panel(git_panel, window, cx);
             workspace.add_panel(channels_panel, window, cx);
             workspace.add_panel(chat_panel, window, cx);
             workspace.add_panel(notification_panel, window, cx);
             workspace.add_panel(debug_panel, window, cx);
+            workspace.add_panel(jj_panel, window, cx);
         })?;
 
         let is_assistant2_enabled = !cfg!(test);
@@ -4322,8 +4331,10 @@ mod tests {
                 "git_onboarding",
                 "git_panel",
                 "go_to_line",
+                "hierarchy_panel",
                 "icon_theme_selector",
                 "jj",
+                "jj_panel",
                 "journal",
                 "keymap_editor",
                 "language_selector",
@@ -4456,6 +4467,7 @@ mod tests {
             git_ui::init(cx);
             project_panel::init(cx);
             outline_panel::init(cx);
+            hierarchy_panel::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
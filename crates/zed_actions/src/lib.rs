@@ -184,7 +184,35 @@ pub mod jj {
         jj,
         [
             /// Opens the Jujutsu bookmark list.
-            BookmarkList
+            BookmarkList,
+            /// Sets the description of the working-copy change to the one in the Jujutsu panel.
+            DescribeChange,
+            /// Starts a new, empty change on top of the working-copy change.
+            NewChange,
+            /// Moves the working-copy change into its parent.
+            SquashChange,
+            /// Abandons the working-copy change.
+            AbandonChange,
+            /// Undoes the last Jujutsu operation.
+            UndoOperation
+        ]
+    );
+}
+
+pub mod hierarchy_panel {
+    use gpui::actions;
+
+    actions!(
+        hierarchy_panel,
+        [
+            /// Shows the functions calling the symbol under the cursor in the hierarchy panel.
+            ShowIncomingCalls,
+            /// Shows the functions called by the symbol under the cursor in the hierarchy panel.
+            ShowOutgoingCalls,
+            /// Shows the supertypes of the type under the cursor in the hierarchy panel.
+            ShowSupertypes,
+            /// Shows the subtypes of the type under the cursor in the hierarchy panel.
+            ShowSubtypes
         ]
     );
 }
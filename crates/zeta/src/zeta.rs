@@ -401,7 +401,7 @@ impl Zeta {
                     .into_iter()
                     .filter_map(|(language_server_id, diagnostic_group)| {
                         let language_server =
-                            local_lsp_store.running_language_server_for_id(language_server_id)?;
+                            local_lsp_store.running_language_server_for_id(language_server_id?)?;
 
                         Some((
                             language_server.name(),